@@ -227,6 +227,10 @@ public final class NativeBridge {
 
     private static native long nativeGetProfileData(long handle);
 
+    private static native boolean nativeStartRecording(long handle, String path);
+
+    private static native boolean nativeStopRecording(long handle);
+
     // Callback registration
     private static native void nativeRegisterCallbacks(long handle, Object callbackHandler);
 
@@ -1002,6 +1006,40 @@ public final class NativeBridge {
         }
     }
 
+    /**
+     * Start recording native API calls to a replay file (for crash repros)
+     *
+     * @return true if recording started
+     */
+    public boolean startReplayRecording(String path) {
+        if (!checkReady())
+            return false;
+
+        lock.writeLock().lock();
+        try {
+            return nativeStartRecording(engineHandle, path);
+        } finally {
+            lock.writeLock().unlock();
+        }
+    }
+
+    /**
+     * Stop recording and flush the replay file
+     *
+     * @return true if a recording was in progress
+     */
+    public boolean stopReplayRecording() {
+        if (!checkReady())
+            return false;
+
+        lock.writeLock().lock();
+        try {
+            return nativeStopRecording(engineHandle);
+        } finally {
+            lock.writeLock().unlock();
+        }
+    }
+
     // =========================================================================
     // UTILITY
     // =========================================================================
@@ -0,0 +1,34 @@
+//! Generates the C header for the `capi` module.
+//!
+//! The header is written to `OUT_DIR`. The committed `include/libs.h` is
+//! only rewritten when `LIBS_REGENERATE_HEADER` is set.
+
+use std::env;
+use std::path::PathBuf;
+
+fn main() {
+    println!("cargo:rerun-if-changed=src/capi.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    println!("cargo:rerun-if-env-changed=LIBS_REGENERATE_HEADER");
+
+    let crate_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    let config = cbindgen::Config::from_file(crate_dir.join("cbindgen.toml")).unwrap_or_default();
+
+    // Only the C API is exported; JNI entry points stay out of the header
+    match cbindgen::Builder::new()
+        .with_config(config)
+        .with_src(crate_dir.join("src/capi.rs"))
+        .generate()
+    {
+        Ok(bindings) => {
+            bindings.write_to_file(out_dir.join("libs.h"));
+            if env::var_os("LIBS_REGENERATE_HEADER").is_some() {
+                let include_dir = crate_dir.join("include");
+                let _ = std::fs::create_dir_all(&include_dir);
+                bindings.write_to_file(include_dir.join("libs.h"));
+            }
+        }
+        Err(e) => println!("cargo:warning=Failed to generate libs.h: {}", e),
+    }
+}
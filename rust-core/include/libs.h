@@ -0,0 +1,163 @@
+#ifndef LIBS_H
+#define LIBS_H
+
+/* Generated by cbindgen from src/capi.rs. Do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Opaque engine handle
+ */
+typedef struct LibsHandle LibsHandle;
+
+/**
+ * Engine creation options
+ */
+typedef struct LibsConfig {
+  /**
+   * Skip renderer and audio (dedicated servers, tools)
+   */
+  bool headless;
+  /**
+   * Seed for lockstep mode; 0 leaves lockstep disabled
+   */
+  uint64_t lockstep_seed;
+} LibsConfig;
+
+/**
+ * Engine statistics
+ */
+typedef struct LibsStats {
+  /**
+   * Live entities
+   */
+  uint32_t entity_count;
+  /**
+   * Loaded chunk columns
+   */
+  uint32_t loaded_chunks;
+  /**
+   * Subsystems that initialized
+   */
+  uint32_t ready_subsystems;
+  /**
+   * Simulation ticks run
+   */
+  uint64_t ticks;
+  /**
+   * State hash of the last lockstep tick
+   */
+  uint64_t tick_hash;
+} LibsStats;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Get default config (headless, lockstep disabled)
+ */
+struct LibsConfig libs_config_default(void);
+
+/**
+ * Get library version as a NUL-terminated string (static, do not free)
+ */
+const char *libs_version(void);
+
+/**
+ * Create and initialize an engine; null config uses `libs_config_default`
+ *
+ * # Safety
+ * `config` must be null or point to a valid `LibsConfig`.
+ */
+struct LibsHandle *libs_engine_create(const struct LibsConfig *config);
+
+/**
+ * Shut down and free an engine
+ *
+ * # Safety
+ * `handle` must be null or come from `libs_engine_create`, and must not be used afterwards.
+ */
+void libs_engine_destroy(struct LibsHandle *handle);
+
+/**
+ * Advance the simulation
+ *
+ * # Safety
+ * `handle` must be null or a live engine handle.
+ */
+void libs_engine_tick(struct LibsHandle *handle, float delta_time);
+
+/**
+ * Advance one lockstep tick without inputs; returns the state hash
+ *
+ * # Safety
+ * `handle` must be null or a live engine handle.
+ */
+uint64_t libs_engine_step(struct LibsHandle *handle);
+
+/**
+ * Submit chunk payload bytes; returns the chunk handle or -1
+ *
+ * # Safety
+ * `handle` must be null or a live engine handle; `data` must point to `len` readable bytes.
+ */
+int64_t libs_engine_submit_chunk(struct LibsHandle *handle, int32_t x, int32_t z, const uint8_t *data, size_t len);
+
+/**
+ * Spawn an entity; returns its handle or -1
+ *
+ * # Safety
+ * `handle` must be null or a live engine handle.
+ */
+int64_t libs_engine_spawn_entity(struct LibsHandle *handle, int32_t entity_id, int32_t entity_type, double x, double y, double z);
+
+/**
+ * Move an entity
+ *
+ * # Safety
+ * `handle` must be null or a live engine handle.
+ */
+void libs_engine_update_entity(struct LibsHandle *handle, int32_t entity_id, double x, double y, double z, float yaw, float pitch);
+
+/**
+ * Despawn an entity
+ *
+ * # Safety
+ * `handle` must be null or a live engine handle.
+ */
+void libs_engine_despawn_entity(struct LibsHandle *handle, int32_t entity_id);
+
+/**
+ * Start recording API calls to a replay file; returns false on failure
+ *
+ * # Safety
+ * `handle` must be null or a live engine handle; `path` must be null or a NUL-terminated string.
+ */
+bool libs_engine_start_recording(struct LibsHandle *handle, const char *path);
+
+/**
+ * Stop recording and flush the replay file; returns false if none was recording
+ *
+ * # Safety
+ * `handle` must be null or a live engine handle.
+ */
+bool libs_engine_stop_recording(struct LibsHandle *handle);
+
+/**
+ * Fill `out` with engine statistics; returns false on null arguments
+ *
+ * # Safety
+ * `handle` must be null or a live engine handle; `out` must be null or writable.
+ */
+bool libs_engine_get_stats(const struct LibsHandle *handle, struct LibsStats *out);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* LIBS_H */
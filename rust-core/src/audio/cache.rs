@@ -0,0 +1,110 @@
+//! # Decoded Sound Cache
+//!
+//! Keeps decoded PCM for recently played sounds within a byte budget.
+//! When inserting pushes the total over budget, the least recently used
+//! sounds are dropped; they are decoded again from the encoded asset the
+//! next time they play.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use super::decoder::PcmBuffer;
+
+/// Default decoded-sample budget (64 MiB)
+pub const DEFAULT_CACHE_BYTES: usize = 64 * 1024 * 1024;
+
+/// LRU cache of decoded sounds
+#[derive(Debug)]
+pub struct SoundCache {
+    /// Decoded sounds and their last-use tick
+    entries: HashMap<String, (Arc<PcmBuffer>, u64)>,
+    /// Sum of `PcmBuffer::byte_size` over entries
+    total_bytes: usize,
+    /// Byte budget
+    budget: usize,
+    /// Use counter
+    tick: u64,
+}
+
+impl SoundCache {
+    /// Create a cache with a byte budget
+    pub fn new(budget: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            total_bytes: 0,
+            budget,
+            tick: 0,
+        }
+    }
+
+    /// Get a sound, marking it recently used
+    pub fn get(&mut self, name: &str) -> Option<Arc<PcmBuffer>> {
+        self.tick += 1;
+        let tick = self.tick;
+        self.entries.get_mut(name).map(|(pcm, used)| {
+            *used = tick;
+            pcm.clone()
+        })
+    }
+
+    /// Insert a sound, evicting least recently used ones over budget
+    ///
+    /// The inserted sound is kept even if it alone exceeds the budget.
+    pub fn insert(&mut self, name: &str, pcm: Arc<PcmBuffer>) {
+        self.remove(name);
+        self.tick += 1;
+        self.total_bytes += pcm.byte_size();
+        self.entries.insert(name.to_string(), (pcm, self.tick));
+
+        while self.total_bytes > self.budget && self.entries.len() > 1 {
+            let Some(oldest) = self.entries.iter().min_by_key(|(_, (_, used))| *used).map(|(n, _)| n.clone()) else {
+                break;
+            };
+            log::trace!("Evicting decoded sound: {}", oldest);
+            self.remove(&oldest);
+        }
+    }
+
+    /// Drop a sound
+    pub fn remove(&mut self, name: &str) {
+        if let Some((pcm, _)) = self.entries.remove(name) {
+            self.total_bytes -= pcm.byte_size();
+        }
+    }
+
+    /// Check if a sound is cached (without marking it used)
+    pub fn contains(&self, name: &str) -> bool {
+        self.entries.contains_key(name)
+    }
+
+    /// Set the byte budget (evicts on next insert)
+    pub fn set_budget(&mut self, budget: usize) {
+        self.budget = budget;
+    }
+
+    /// Get the byte budget
+    pub fn budget(&self) -> usize {
+        self.budget
+    }
+
+    /// Get bytes used by cached samples
+    pub fn total_bytes(&self) -> usize {
+        self.total_bytes
+    }
+
+    /// Get cached sound count
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if nothing is cached
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl Default for SoundCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_CACHE_BYTES)
+    }
+}
@@ -0,0 +1,367 @@
+//! # Audio Decoding
+//!
+//! Turns encoded sound assets into interleaved `f32` PCM. WAV (integer
+//! PCM at 8/16/24/32 bits, or 32-bit float) is decoded fully or streamed
+//! block by block with `WavStream`. OGG Vorbis is decoded fully with
+//! `lewton` after the identification header has been validated.
+
+use std::fmt;
+use std::sync::Arc;
+
+/// Encoded sound format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AudioFormat {
+    Wav,
+    OggVorbis,
+}
+
+/// Audio decoding errors
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AudioError {
+    /// Malformed or truncated data
+    InvalidData(String),
+    /// Valid data this decoder can't handle
+    Unsupported(String),
+    /// No sound loaded under this name
+    NotLoaded(String),
+}
+
+impl fmt::Display for AudioError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidData(e) => write!(f, "Invalid audio data: {}", e),
+            Self::Unsupported(e) => write!(f, "Unsupported audio: {}", e),
+            Self::NotLoaded(name) => write!(f, "Sound not loaded: {}", name),
+        }
+    }
+}
+
+impl std::error::Error for AudioError {}
+
+/// Decoded PCM samples
+#[derive(Debug, Clone, PartialEq)]
+pub struct PcmBuffer {
+    /// Frames per second
+    pub sample_rate: u32,
+    /// Interleaved channels
+    pub channels: u16,
+    /// Interleaved samples in -1..=1
+    pub samples: Vec<f32>,
+}
+
+impl PcmBuffer {
+    /// Get frame count (samples per channel)
+    pub fn frame_count(&self) -> usize {
+        self.samples.len() / self.channels.max(1) as usize
+    }
+
+    /// Get duration in seconds
+    pub fn duration(&self) -> f32 {
+        self.frame_count() as f32 / self.sample_rate.max(1) as f32
+    }
+
+    /// Get memory used by the samples
+    pub fn byte_size(&self) -> usize {
+        self.samples.len() * std::mem::size_of::<f32>()
+    }
+}
+
+/// Sample encoding of a WAV data chunk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WavEncoding {
+    /// Unsigned 8-bit, signed 16/24/32-bit integer
+    Int(u16),
+    /// 32-bit IEEE float
+    Float32,
+}
+
+/// Parsed WAV header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WavInfo {
+    /// Frames per second
+    pub sample_rate: u32,
+    /// Interleaved channels
+    pub channels: u16,
+    /// Bits per sample
+    pub bits_per_sample: u16,
+    /// Byte range of the sample data
+    data_start: usize,
+    data_len: usize,
+    encoding: WavEncoding,
+}
+
+impl WavInfo {
+    /// Parse the RIFF header, `fmt ` and `data` chunks
+    pub fn parse(bytes: &[u8]) -> Result<Self, AudioError> {
+        if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+            return Err(AudioError::InvalidData("missing RIFF/WAVE header".to_string()));
+        }
+
+        let mut format = None;
+        let mut offset = 12;
+        while offset + 8 <= bytes.len() {
+            let id = &bytes[offset..offset + 4];
+            let size = u32::from_le_bytes(bytes[offset + 4..offset + 8].try_into().unwrap()) as usize;
+            let body = offset + 8;
+
+            match id {
+                b"fmt " => {
+                    if size < 16 || body + 16 > bytes.len() {
+                        return Err(AudioError::InvalidData("truncated fmt chunk".to_string()));
+                    }
+                    let le16 = |at: usize| u16::from_le_bytes([bytes[body + at], bytes[body + at + 1]]);
+                    let tag = le16(0);
+                    let channels = le16(2);
+                    let sample_rate = u32::from_le_bytes(bytes[body + 4..body + 8].try_into().unwrap());
+                    let bits = le16(14);
+                    // WAVE_FORMAT_EXTENSIBLE keeps the real tag in the sub-format GUID
+                    let tag = if tag == 0xFFFE && size >= 26 && body + 26 <= bytes.len() { le16(24) } else { tag };
+
+                    let encoding = match (tag, bits) {
+                        (1, 8 | 16 | 24 | 32) => WavEncoding::Int(bits),
+                        (3, 32) => WavEncoding::Float32,
+                        _ => return Err(AudioError::Unsupported(format!("WAV format tag {} at {} bits", tag, bits))),
+                    };
+                    if channels == 0 || sample_rate == 0 {
+                        return Err(AudioError::InvalidData("zero channels or sample rate".to_string()));
+                    }
+                    format = Some((sample_rate, channels, bits, encoding));
+                }
+                b"data" => {
+                    let (sample_rate, channels, bits_per_sample, encoding) =
+                        format.ok_or_else(|| AudioError::InvalidData("data chunk before fmt chunk".to_string()))?;
+                    // Tolerate a data size running past the end (streamed writers leave it unset)
+                    let data_len = size.min(bytes.len() - body);
+                    return Ok(Self { sample_rate, channels, bits_per_sample, data_start: body, data_len, encoding });
+                }
+                _ => {}
+            }
+
+            // Chunks are padded to even sizes
+            offset = body.saturating_add(size).saturating_add(size & 1);
+        }
+
+        Err(AudioError::InvalidData("no data chunk".to_string()))
+    }
+
+    /// Get bytes per interleaved frame
+    pub fn frame_bytes(&self) -> usize {
+        (self.bits_per_sample / 8) as usize * self.channels as usize
+    }
+
+    /// Get frame count
+    pub fn frame_count(&self) -> usize {
+        self.data_len / self.frame_bytes()
+    }
+
+    /// Convert one sample to `f32`
+    fn sample(&self, raw: &[u8]) -> f32 {
+        match self.encoding {
+            WavEncoding::Int(8) => (raw[0] as f32 - 128.0) / 128.0,
+            WavEncoding::Int(16) => i16::from_le_bytes([raw[0], raw[1]]) as f32 / 32_768.0,
+            WavEncoding::Int(24) => (i32::from_le_bytes([0, raw[0], raw[1], raw[2]]) >> 8) as f32 / 8_388_608.0,
+            WavEncoding::Int(_) => i32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]]) as f32 / 2_147_483_648.0,
+            WavEncoding::Float32 => f32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]]),
+        }
+    }
+}
+
+/// Decode a whole WAV file
+pub fn decode_wav(bytes: &[u8]) -> Result<PcmBuffer, AudioError> {
+    let info = WavInfo::parse(bytes)?;
+    let width = (info.bits_per_sample / 8) as usize;
+    let data = &bytes[info.data_start..info.data_start + info.frame_count() * info.frame_bytes()];
+
+    Ok(PcmBuffer {
+        sample_rate: info.sample_rate,
+        channels: info.channels,
+        samples: data.chunks_exact(width).map(|raw| info.sample(raw)).collect(),
+    })
+}
+
+/// Incremental WAV decoder over shared encoded bytes
+#[derive(Debug, Clone)]
+pub struct WavStream {
+    bytes: Arc<[u8]>,
+    info: WavInfo,
+    /// Next frame to decode
+    frame: usize,
+}
+
+impl WavStream {
+    /// Open a stream, validating the header
+    pub fn new(bytes: Arc<[u8]>) -> Result<Self, AudioError> {
+        let info = WavInfo::parse(&bytes)?;
+        Ok(Self { bytes, info, frame: 0 })
+    }
+
+    /// Get the stream header
+    pub fn info(&self) -> &WavInfo {
+        &self.info
+    }
+
+    /// Decode up to `out.len()` samples (whole frames); returns samples written
+    ///
+    /// With `looping`, decoding wraps to the start instead of ending.
+    pub fn read(&mut self, out: &mut [f32], looping: bool) -> usize {
+        let channels = self.info.channels as usize;
+        let width = (self.info.bits_per_sample / 8) as usize;
+        let total = self.info.frame_count();
+        let mut written = 0;
+
+        while written + channels <= out.len() {
+            if self.frame >= total {
+                if !looping || total == 0 {
+                    break;
+                }
+                self.frame = 0;
+            }
+            let start = self.info.data_start + self.frame * self.info.frame_bytes();
+            for (c, raw) in self.bytes[start..start + self.info.frame_bytes()].chunks_exact(width).enumerate() {
+                out[written + c] = self.info.sample(raw);
+            }
+            written += channels;
+            self.frame += 1;
+        }
+
+        written
+    }
+
+    /// Check if a non-looping read has consumed every frame
+    pub fn is_finished(&self) -> bool {
+        self.frame >= self.info.frame_count()
+    }
+
+    /// Restart from the first frame
+    pub fn rewind(&mut self) {
+        self.frame = 0;
+    }
+}
+
+/// Vorbis stream parameters from the identification header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VorbisInfo {
+    pub channels: u8,
+    pub sample_rate: u32,
+}
+
+/// Read the first Ogg page's Vorbis identification header
+pub fn parse_ogg_vorbis(bytes: &[u8]) -> Result<VorbisInfo, AudioError> {
+    if bytes.len() < 27 || &bytes[0..4] != b"OggS" {
+        return Err(AudioError::InvalidData("missing Ogg page header".to_string()));
+    }
+
+    let segments = bytes[26] as usize;
+    let packet_start = 27 + segments;
+    let packet_len: usize = bytes.get(27..packet_start)
+        .ok_or_else(|| AudioError::InvalidData("truncated Ogg segment table".to_string()))?
+        .iter()
+        .map(|&s| s as usize)
+        .sum();
+    let packet = bytes.get(packet_start..packet_start + packet_len)
+        .ok_or_else(|| AudioError::InvalidData("truncated Ogg page".to_string()))?;
+
+    if packet.len() < 16 || packet[0] != 1 || &packet[1..7] != b"vorbis" {
+        return Err(AudioError::Unsupported("Ogg stream is not Vorbis".to_string()));
+    }
+    let channels = packet[11];
+    let sample_rate = u32::from_le_bytes(packet[12..16].try_into().unwrap());
+    if channels == 0 || sample_rate == 0 {
+        return Err(AudioError::InvalidData("zero channels or sample rate".to_string()));
+    }
+
+    Ok(VorbisInfo { channels, sample_rate })
+}
+
+/// Decode a whole OGG Vorbis file
+pub fn decode_ogg_vorbis(bytes: &[u8]) -> Result<PcmBuffer, AudioError> {
+    let info = parse_ogg_vorbis(bytes)?;
+    let mut reader = lewton::inside_ogg::OggStreamReader::new(std::io::Cursor::new(bytes))
+        .map_err(|e| AudioError::InvalidData(format!("Vorbis headers: {}", e)))?;
+
+    let mut samples = Vec::new();
+    while let Some(packet) = reader
+        .read_dec_packet_generic::<lewton::samples::InterleavedSamples<f32>>()
+        .map_err(|e| AudioError::InvalidData(format!("Vorbis audio: {}", e)))?
+    {
+        samples.extend_from_slice(&packet.samples);
+    }
+
+    Ok(PcmBuffer {
+        sample_rate: info.sample_rate,
+        channels: info.channels as u16,
+        samples,
+    })
+}
+
+/// Decode a whole sound
+pub fn decode(bytes: &[u8], format: AudioFormat) -> Result<PcmBuffer, AudioError> {
+    match format {
+        AudioFormat::Wav => decode_wav(bytes),
+        AudioFormat::OggVorbis => decode_ogg_vorbis(bytes),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build a 16-bit PCM WAV file
+    fn wav(sample_rate: u32, channels: u16, samples: &[i16]) -> Vec<u8> {
+        let data_len = (samples.len() * 2) as u32;
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(b"RIFF");
+        bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+        bytes.extend_from_slice(b"WAVEfmt ");
+        bytes.extend_from_slice(&16u32.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&channels.to_le_bytes());
+        bytes.extend_from_slice(&sample_rate.to_le_bytes());
+        bytes.extend_from_slice(&(sample_rate * channels as u32 * 2).to_le_bytes());
+        bytes.extend_from_slice(&(channels * 2).to_le_bytes());
+        bytes.extend_from_slice(&16u16.to_le_bytes());
+        bytes.extend_from_slice(b"data");
+        bytes.extend_from_slice(&data_len.to_le_bytes());
+        for s in samples {
+            bytes.extend_from_slice(&s.to_le_bytes());
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_wav_decodes_to_pcm() {
+        let bytes = wav(22_050, 2, &[0, 16_384, -32_768, 32_767, 0, 0]);
+        let pcm = decode(&bytes, AudioFormat::Wav).unwrap();
+        assert_eq!(pcm.sample_rate, 22_050);
+        assert_eq!(pcm.channels, 2);
+        assert_eq!(pcm.frame_count(), 3);
+        assert_eq!(&pcm.samples[..3], &[0.0, 0.5, -1.0]);
+
+        // Streaming yields the same samples and wraps when looping
+        let mut stream = WavStream::new(bytes.into()).unwrap();
+        let mut out = [0.0f32; 8];
+        assert_eq!(stream.read(&mut out, true), 8);
+        assert_eq!(&out[..6], &pcm.samples[..]);
+        assert_eq!(&out[6..], &pcm.samples[..2]);
+        stream.rewind();
+        assert_eq!(stream.read(&mut out, false), 6);
+        assert!(stream.is_finished());
+
+        assert!(matches!(decode(b"RIFF\0\0\0\0AVI ", AudioFormat::Wav), Err(AudioError::InvalidData(_))));
+    }
+
+    #[test]
+    fn test_ogg_vorbis_decodes_to_pcm() {
+        // 1024 frames of a mono 22050 Hz tone, one spectral line per block
+        let bytes = include_bytes!("../../tests/fixtures/tone.ogg");
+        let pcm = decode(bytes, AudioFormat::OggVorbis).unwrap();
+        assert_eq!(pcm.sample_rate, 22_050);
+        assert_eq!(pcm.channels, 1);
+        assert_eq!(pcm.frame_count(), 1024);
+        assert!(pcm.samples.iter().all(|s| (-1.0..=1.0).contains(s)));
+        assert!(pcm.samples.iter().any(|s| s.abs() > 0.001));
+
+        // Cutting off the setup header fails instead of producing silence
+        assert!(decode(&bytes[..100], AudioFormat::OggVorbis).is_err());
+    }
+}
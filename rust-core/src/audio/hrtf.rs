@@ -0,0 +1,289 @@
+//! # HRTF Spatialization
+//!
+//! Binaural rendering for headphones. Each voice is convolved with a pair
+//! of head-related impulse responses (HRIRs) chosen by the source's
+//! direction relative to the listener, so the ears get the interaural
+//! delay, the level difference and the head-shadow filtering that place a
+//! sound in 3D.
+//!
+//! The built-in dataset is small: 12 azimuths (every 30 degrees) by 4
+//! elevations, generated once from a spherical-head model (Woodworth delay,
+//! high-frequency shadowing of the far ear, and an elevation-dependent
+//! pinna notch). Lookups blend the four surrounding bins bilinearly.
+//!
+//! Directions follow the game's camera: yaw 0 faces +Z, yaw 90 faces -X,
+//! positive pitch looks down. Azimuth is clockwise from straight ahead
+//! (90 = right, -90 = left) and elevation is positive upwards.
+
+use glam::Vec3;
+
+/// Sample rate the HRIRs are built for
+pub const HRIR_SAMPLE_RATE: f32 = 44_100.0;
+
+/// Taps per HRIR
+pub const HRIR_LEN: usize = 48;
+
+/// Degrees between azimuth bins
+pub const AZIMUTH_STEP: f32 = 30.0;
+
+/// Number of azimuth bins
+pub const AZIMUTH_BINS: usize = 12;
+
+/// Elevation of each bin row (degrees)
+pub const ELEVATIONS: [f32; 4] = [-45.0, 0.0, 45.0, 90.0];
+
+/// Head radius (m) and speed of sound (m/s) of the head model
+const HEAD_RADIUS: f32 = 0.0875;
+const SPEED_OF_SOUND: f32 = 343.0;
+
+/// How voices are placed in the stereo field
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Spatialization {
+    /// Constant-power panning (speakers)
+    #[default]
+    Stereo,
+    /// Binaural HRIR convolution (headphones)
+    Hrtf,
+}
+
+/// Impulse responses for both ears
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hrir {
+    pub left: [f32; HRIR_LEN],
+    pub right: [f32; HRIR_LEN],
+}
+
+impl Hrir {
+    const SILENT: Hrir = Hrir { left: [0.0; HRIR_LEN], right: [0.0; HRIR_LEN] };
+}
+
+/// Direction of `source` as (azimuth, elevation) in degrees for a listener
+pub fn source_direction(listener: Vec3, yaw: f32, pitch: f32, source: Vec3) -> (f32, f32) {
+    let Some(d) = (source - listener).try_normalize() else {
+        return (0.0, 0.0);
+    };
+    let (yaw, pitch) = (yaw.to_radians(), pitch.to_radians());
+    let forward = Vec3::new(-yaw.sin() * pitch.cos(), -pitch.sin(), yaw.cos() * pitch.cos());
+    let right = Vec3::new(-yaw.cos(), 0.0, -yaw.sin());
+    let up = right.cross(forward);
+
+    let azimuth = d.dot(right).atan2(d.dot(forward)).to_degrees();
+    let elevation = d.dot(up).clamp(-1.0, 1.0).asin().to_degrees();
+    (azimuth, elevation)
+}
+
+/// Built-in HRIR table
+pub struct HrirSet {
+    /// Row-major by elevation, then azimuth
+    bins: Vec<Hrir>,
+}
+
+impl HrirSet {
+    /// Build the table from the head model
+    pub fn new() -> Self {
+        let mut bins = Vec::with_capacity(ELEVATIONS.len() * AZIMUTH_BINS);
+        for &elevation in &ELEVATIONS {
+            for az in 0..AZIMUTH_BINS {
+                bins.push(model_hrir(az as f32 * AZIMUTH_STEP, elevation));
+            }
+        }
+        Self { bins }
+    }
+
+    /// Bin index of an azimuth/elevation pair
+    pub fn bin_index(azimuth_bin: usize, elevation_row: usize) -> usize {
+        elevation_row * AZIMUTH_BINS + azimuth_bin % AZIMUTH_BINS
+    }
+
+    /// Nearest bin to a direction
+    pub fn nearest_bin(azimuth: f32, elevation: f32) -> usize {
+        let az = (azimuth.rem_euclid(360.0) / AZIMUTH_STEP).round() as usize % AZIMUTH_BINS;
+        let row = ELEVATIONS
+            .iter()
+            .enumerate()
+            .min_by(|a, b| (a.1 - elevation).abs().total_cmp(&(b.1 - elevation).abs()))
+            .map_or(0, |(row, _)| row);
+        Self::bin_index(az, row)
+    }
+
+    /// The four bins around a direction with their bilinear weights
+    pub fn neighbors(azimuth: f32, elevation: f32) -> [(usize, f32); 4] {
+        let az = azimuth.rem_euclid(360.0) / AZIMUTH_STEP;
+        let (az0, taz) = (az.floor() as usize % AZIMUTH_BINS, az.fract());
+
+        let elevation = elevation.clamp(ELEVATIONS[0], ELEVATIONS[ELEVATIONS.len() - 1]);
+        let row0 = ELEVATIONS.windows(2).position(|w| elevation < w[1]).unwrap_or(ELEVATIONS.len() - 2);
+        let tel = (elevation - ELEVATIONS[row0]) / (ELEVATIONS[row0 + 1] - ELEVATIONS[row0]);
+
+        [
+            (Self::bin_index(az0, row0), (1.0 - taz) * (1.0 - tel)),
+            (Self::bin_index(az0 + 1, row0), taz * (1.0 - tel)),
+            (Self::bin_index(az0, row0 + 1), (1.0 - taz) * tel),
+            (Self::bin_index(az0 + 1, row0 + 1), taz * tel),
+        ]
+    }
+
+    /// Get a bin's HRIR
+    pub fn bin(&self, index: usize) -> &Hrir {
+        &self.bins[index]
+    }
+
+    /// HRIR for any direction, blended from the surrounding bins
+    pub fn interpolate(&self, azimuth: f32, elevation: f32) -> Hrir {
+        let mut hrir = Hrir::SILENT;
+        for (index, weight) in Self::neighbors(azimuth, elevation) {
+            let bin = &self.bins[index];
+            for tap in 0..HRIR_LEN {
+                hrir.left[tap] += bin.left[tap] * weight;
+                hrir.right[tap] += bin.right[tap] * weight;
+            }
+        }
+        hrir
+    }
+}
+
+impl Default for HrirSet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Spherical-head HRIR for one direction
+fn model_hrir(azimuth: f32, elevation: f32) -> Hrir {
+    let (az, el) = (azimuth.to_radians(), elevation.to_radians());
+    // Lateral angle: 0 ahead/behind, +90 fully right
+    let lateral = (az.sin() * el.cos()).clamp(-1.0, 1.0).asin();
+    let itd = HEAD_RADIUS / SPEED_OF_SOUND * (lateral.abs() + lateral.abs().sin()) * HRIR_SAMPLE_RATE;
+    let shadow = lateral.abs() / std::f32::consts::FRAC_PI_2;
+    // Pinna reflection arrives later for sources below
+    let notch_delay = 2.0 + (1.0 - el.sin()) * 3.0;
+
+    let near = ear_response(0.0, 1.0 + 0.25 * shadow, 0.0, notch_delay);
+    let far = ear_response(itd, 1.0 - 0.5 * shadow, 0.2 + 0.6 * shadow, notch_delay);
+    if lateral >= 0.0 {
+        Hrir { left: far, right: near }
+    } else {
+        Hrir { left: near, right: far }
+    }
+}
+
+/// Delayed, low-passed impulse with a pinna reflection
+fn ear_response(delay: f32, gain: f32, smoothing: f32, notch_delay: f32) -> [f32; HRIR_LEN] {
+    let mut ir = [0.0; HRIR_LEN];
+    let mut add = |at: f32, amount: f32| {
+        // Fractional delay split across two taps
+        let tap = at.floor() as usize;
+        let t = at.fract();
+        if tap + 1 < HRIR_LEN {
+            ir[tap] += amount * (1.0 - t);
+            ir[tap + 1] += amount * t;
+        }
+    };
+    add(delay, gain);
+    add(delay + notch_delay, -0.25 * gain);
+
+    // One-pole lowpass for head shadow; unity DC gain keeps the level
+    let mut state = 0.0;
+    for sample in ir.iter_mut() {
+        state = *sample * (1.0 - smoothing) + state * smoothing;
+        *sample = state;
+    }
+    ir
+}
+
+/// Per-voice binaural convolver
+pub struct HrtfConvolver {
+    /// Last `HRIR_LEN` input samples, oldest first
+    history: [f32; HRIR_LEN],
+    /// HRIR used for the previous block (crossfaded from to avoid clicks)
+    current: Option<Hrir>,
+}
+
+impl HrtfConvolver {
+    /// Create a convolver with silent history
+    pub fn new() -> Self {
+        Self { history: [0.0; HRIR_LEN], current: None }
+    }
+
+    /// Convolve a mono block into interleaved stereo, adding to `out`
+    ///
+    /// The HRIR changes smoothly over the block from the previous one to `hrir`.
+    pub fn process(&mut self, input: &[f32], hrir: &Hrir, gain: f32, out: &mut [f32]) {
+        let previous = self.current.take().unwrap_or_else(|| hrir.clone());
+        let frames = input.len().min(out.len() / 2);
+
+        // History followed by the block, so every output sees HRIR_LEN inputs
+        let mut signal = Vec::with_capacity(HRIR_LEN + frames);
+        signal.extend_from_slice(&self.history);
+        signal.extend_from_slice(&input[..frames]);
+
+        for frame in 0..frames {
+            let t = (frame + 1) as f32 / frames as f32;
+            let (mut left, mut right) = (0.0, 0.0);
+            let newest = HRIR_LEN + frame;
+            for tap in 0..HRIR_LEN {
+                let x = signal[newest - tap];
+                left += x * (previous.left[tap] + (hrir.left[tap] - previous.left[tap]) * t);
+                right += x * (previous.right[tap] + (hrir.right[tap] - previous.right[tap]) * t);
+            }
+            out[frame * 2] += left * gain;
+            out[frame * 2 + 1] += right * gain;
+        }
+
+        self.history.copy_from_slice(&signal[signal.len() - HRIR_LEN..]);
+        self.current = Some(hrir.clone());
+    }
+}
+
+impl Default for HrtfConvolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Constant-power (left, right) gains for an azimuth
+pub fn pan_gains(azimuth: f32) -> (f32, f32) {
+    let pan = azimuth.to_radians().sin();
+    let angle = (pan + 1.0) * std::f32::consts::FRAC_PI_4;
+    (angle.cos(), angle.sin())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_to_the_left_selects_left_bin() {
+        // Facing +Z (yaw 0), +X is on the listener's left
+        let listener = Vec3::new(10.0, 64.0, 10.0);
+        let (azimuth, elevation) = source_direction(listener, 0.0, 0.0, listener + Vec3::X * 5.0);
+        assert!((azimuth + 90.0).abs() < 1e-3 && elevation.abs() < 1e-3, "{} {}", azimuth, elevation);
+
+        // 270 degrees clockwise at ear height: azimuth bin 9 of row 1
+        let bin = HrirSet::nearest_bin(azimuth, elevation);
+        assert_eq!(bin, HrirSet::bin_index(9, 1));
+        assert_eq!(HrirSet::neighbors(azimuth, elevation)[0], (bin, 1.0));
+
+        // Turning to face +X puts it straight ahead; above is elevation 90
+        let (ahead, _) = source_direction(listener, -90.0, 0.0, listener + Vec3::X);
+        assert!(ahead.abs() < 1e-3, "{}", ahead);
+        assert!((source_direction(listener, 0.0, 0.0, listener + Vec3::Y).1 - 90.0).abs() < 1e-3);
+
+        // The left ear hears it first and louder
+        let set = HrirSet::new();
+        let hrir = set.interpolate(azimuth, elevation);
+        let onset = |ir: &[f32]| ir.iter().position(|s| s.abs() > 0.05).unwrap();
+        assert!(onset(&hrir.left) + 20 < onset(&hrir.right));
+        let energy = |ir: &[f32]| ir.iter().map(|s| s * s).sum::<f32>();
+        assert!(energy(&hrir.left) > energy(&hrir.right) * 2.0);
+
+        // Convolving an impulse reproduces the HRIR
+        let mut convolver = HrtfConvolver::new();
+        let mut impulse = [0.0; HRIR_LEN];
+        impulse[0] = 1.0;
+        let mut out = [0.0; HRIR_LEN * 2];
+        convolver.process(&impulse, &hrir, 1.0, &mut out);
+        assert!((0..HRIR_LEN).all(|i| (out[i * 2] - hrir.left[i]).abs() < 1e-6));
+        assert!((0..HRIR_LEN).all(|i| (out[i * 2 + 1] - hrir.right[i]).abs() < 1e-6));
+    }
+}
@@ -3,13 +3,46 @@
 //! 3D positional audio system.
 
 pub mod raytracer;
+pub mod decoder;
+pub mod cache;
+pub mod hrtf;
 
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+pub use decoder::{AudioError, AudioFormat, PcmBuffer, WavStream};
+pub use cache::SoundCache;
+pub use hrtf::Spatialization;
 
 /// Next sound handle
 static NEXT_SOUND_HANDLE: AtomicU64 = AtomicU64::new(1);
 
+/// Encoded sounds at least this large are streamed instead of decoded up front
+pub const STREAM_THRESHOLD_BYTES: usize = 1024 * 1024;
+
+/// Default simultaneous voice cap
+pub const DEFAULT_MAX_VOICES: usize = 64;
+
+/// Outcome of `AudioEngine::play`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlayResult {
+    /// The sound is playing under this handle
+    Started(u64),
+    /// Every voice was busy with a more important sound
+    Culled,
+}
+
+impl PlayResult {
+    /// Get the handle if the sound started
+    pub fn handle(self) -> Option<u64> {
+        match self {
+            PlayResult::Started(handle) => Some(handle),
+            PlayResult::Culled => None,
+        }
+    }
+}
+
 /// Audio engine
 pub struct AudioEngine {
     /// Master volume
@@ -21,6 +54,27 @@ pub struct AudioEngine {
     /// Sound name to handle mapping
     name_to_handles: HashMap<String, Vec<u64>>,
     
+    /// Loaded sound assets
+    assets: HashMap<String, SoundAsset>,
+    
+    /// Decoded samples of recently played sounds
+    cache: SoundCache,
+    
+    /// Simultaneous voice cap
+    max_voices: usize,
+    
+    /// Per-sound priority weights (default 1.0)
+    priority_weights: HashMap<String, f32>,
+    
+    /// Voices culled so far
+    voices_culled: u64,
+    
+    /// Panning or binaural rendering
+    spatialization: Spatialization,
+    
+    /// HRIR table (built when HRTF is first enabled)
+    hrirs: Option<hrtf::HrirSet>,
+    
     /// Listener position
     listener_x: f32,
     listener_y: f32,
@@ -38,6 +92,29 @@ struct SoundInstance {
     volume: f32,
     pitch: f32,
     playing: bool,
+    looping: bool,
+    priority_weight: f32,
+    /// Samples to mix (`None` if the sound isn't loaded)
+    samples: Option<SoundSamples>,
+    /// Binaural filter state (HRTF mode only)
+    convolver: Option<hrtf::HrtfConvolver>,
+}
+
+/// An encoded sound asset
+struct SoundAsset {
+    bytes: Arc<[u8]>,
+    format: AudioFormat,
+    looping: bool,
+    /// Decoded block by block while playing instead of cached
+    streamed: bool,
+}
+
+/// Sample source of a playing sound
+enum SoundSamples {
+    /// Cached PCM and the next frame to mix
+    Buffer { pcm: Arc<PcmBuffer>, frame: usize },
+    /// Incremental decoder for a large file
+    Stream(WavStream),
 }
 
 impl AudioEngine {
@@ -49,6 +126,13 @@ impl AudioEngine {
             master_volume: 1.0,
             sounds: HashMap::new(),
             name_to_handles: HashMap::new(),
+            assets: HashMap::new(),
+            cache: SoundCache::default(),
+            max_voices: DEFAULT_MAX_VOICES,
+            priority_weights: HashMap::new(),
+            voices_culled: 0,
+            spatialization: Spatialization::Stereo,
+            hrirs: None,
             listener_x: 0.0,
             listener_y: 0.0,
             listener_z: 0.0,
@@ -57,10 +141,176 @@ impl AudioEngine {
         })
     }
     
+    /// Load an encoded sound under a name
+    ///
+    /// Small sounds are decoded now and cached; files of at least
+    /// `STREAM_THRESHOLD_BYTES` are validated and streamed when played.
+    pub fn load_sound(&mut self, name: &str, bytes: &[u8], format: AudioFormat) -> Result<(), AudioError> {
+        let streamed = bytes.len() >= STREAM_THRESHOLD_BYTES && format == AudioFormat::Wav;
+        let bytes: Arc<[u8]> = bytes.into();
+        
+        self.cache.remove(name);
+        if streamed {
+            WavStream::new(bytes.clone())?;
+        } else {
+            self.cache.insert(name, Arc::new(decoder::decode(&bytes, format)?));
+        }
+        
+        let looping = self.assets.get(name).is_some_and(|a| a.looping);
+        self.assets.insert(name.to_string(), SoundAsset { bytes, format, looping, streamed });
+        log::debug!("Sound loaded: {} ({:?}{})", name, format, if streamed { ", streamed" } else { "" });
+        Ok(())
+    }
+    
+    /// Unload a sound (playing instances keep their samples)
+    pub fn unload_sound(&mut self, name: &str) {
+        self.assets.remove(name);
+        self.cache.remove(name);
+    }
+    
+    /// Check if a sound is loaded
+    pub fn is_loaded(&self, name: &str) -> bool {
+        self.assets.contains_key(name)
+    }
+    
+    /// Check if a sound is streamed rather than cached
+    pub fn is_streamed(&self, name: &str) -> bool {
+        self.assets.get(name).is_some_and(|a| a.streamed)
+    }
+    
+    /// Set whether new instances of a sound loop (ambient tracks)
+    pub fn set_looping(&mut self, name: &str, looping: bool) -> Result<(), AudioError> {
+        let asset = self.assets.get_mut(name).ok_or_else(|| AudioError::NotLoaded(name.to_string()))?;
+        asset.looping = looping;
+        Ok(())
+    }
+    
+    /// Check if a sound loops
+    pub fn is_looping(&self, name: &str) -> bool {
+        self.assets.get(name).is_some_and(|a| a.looping)
+    }
+    
+    /// Get a sound's decoded samples, decoding again if evicted
+    pub fn decoded(&mut self, name: &str) -> Result<Arc<PcmBuffer>, AudioError> {
+        if let Some(pcm) = self.cache.get(name) {
+            return Ok(pcm);
+        }
+        let asset = self.assets.get(name).ok_or_else(|| AudioError::NotLoaded(name.to_string()))?;
+        let pcm = Arc::new(decoder::decode(&asset.bytes, asset.format)?);
+        self.cache.insert(name, pcm.clone());
+        Ok(pcm)
+    }
+    
+    /// Set the decoded-sample cache budget in bytes
+    pub fn set_cache_budget(&mut self, bytes: usize) {
+        self.cache.set_budget(bytes);
+    }
+    
+    /// Get bytes held by decoded samples
+    pub fn cache_bytes(&self) -> usize {
+        self.cache.total_bytes()
+    }
+    
+    /// Check if a playing sound has samples to mix
+    pub fn has_samples(&self, handle: u64) -> bool {
+        self.sounds.get(&handle).is_some_and(|s| s.samples.is_some())
+    }
+    
+    /// Check if a playing sound loops
+    pub fn is_instance_looping(&self, handle: u64) -> bool {
+        self.sounds.get(&handle).is_some_and(|s| s.looping)
+    }
+    
+    /// Get the sample source for a new instance of a sound
+    fn samples_for(&mut self, name: &str) -> Option<SoundSamples> {
+        let asset = self.assets.get(name)?;
+        if asset.streamed {
+            return WavStream::new(asset.bytes.clone()).ok().map(SoundSamples::Stream);
+        }
+        match self.decoded(name) {
+            Ok(pcm) => Some(SoundSamples::Buffer { pcm, frame: 0 }),
+            Err(e) => {
+                log::warn!("Failed to decode sound {}: {}", name, e);
+                None
+            }
+        }
+    }
+    
+    /// Set the simultaneous voice cap, culling the least important voices over it
+    pub fn set_max_voices(&mut self, max_voices: usize) {
+        self.max_voices = max_voices;
+        while self.sounds.len() > self.max_voices {
+            let Some((handle, _)) = self.least_important_voice() else { break };
+            self.cull(handle);
+        }
+    }
+    
+    /// Get the simultaneous voice cap
+    pub fn max_voices(&self) -> usize {
+        self.max_voices
+    }
+    
+    /// Set how important a sound is relative to others at the same loudness
+    pub fn set_priority_weight(&mut self, name: &str, weight: f32) {
+        self.priority_weights.insert(name.to_string(), weight.max(0.0));
+    }
+    
+    /// Get voices culled so far
+    pub fn voices_culled(&self) -> u64 {
+        self.voices_culled
+    }
+    
+    /// Check if a sound is still playing
+    pub fn is_playing(&self, handle: u64) -> bool {
+        self.sounds.contains_key(&handle)
+    }
+    
+    /// Importance of a voice: how loud it is at the listener, times its weight
+    fn voice_priority(&self, x: f32, y: f32, z: f32, volume: f32, weight: f32) -> f32 {
+        self.calculate_attenuation(x, y, z) * volume * weight
+    }
+    
+    /// Find the playing voice with the lowest priority
+    fn least_important_voice(&self) -> Option<(u64, f32)> {
+        self.sounds
+            .iter()
+            .map(|(&handle, s)| (handle, self.voice_priority(s.x, s.y, s.z, s.volume, s.priority_weight)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+    }
+    
+    /// Stop a voice to make room for a more important one
+    fn cull(&mut self, handle: u64) {
+        self.stop(handle);
+        self.voices_culled += 1;
+        log::trace!("Voice culled: handle {}", handle);
+    }
+    
     /// Play a sound
-    pub fn play(&mut self, name: &str, x: f32, y: f32, z: f32, volume: f32, pitch: f32) -> u64 {
+    ///
+    /// When every voice is busy, the least important voice is culled if the
+    /// new sound outranks it; otherwise the new sound is culled.
+    pub fn play(&mut self, name: &str, x: f32, y: f32, z: f32, volume: f32, pitch: f32) -> PlayResult {
+        let priority_weight = self.priority_weights.get(name).copied().unwrap_or(1.0);
+        
+        if self.sounds.len() >= self.max_voices {
+            let priority = self.voice_priority(x, y, z, volume, priority_weight);
+            match self.least_important_voice() {
+                Some((handle, lowest)) if priority > lowest => self.cull(handle),
+                _ => {
+                    self.voices_culled += 1;
+                    log::trace!("Sound culled: {} at ({}, {}, {})", name, x, y, z);
+                    return PlayResult::Culled;
+                }
+            }
+        }
+        
         let handle = NEXT_SOUND_HANDLE.fetch_add(1, Ordering::SeqCst);
         
+        let samples = self.samples_for(name);
+        if samples.is_none() {
+            log::trace!("Sound not loaded, playing silently: {}", name);
+        }
+        
         let instance = SoundInstance {
             name: name.to_string(),
             x,
@@ -69,6 +319,10 @@ impl AudioEngine {
             volume,
             pitch,
             playing: true,
+            looping: self.is_looping(name),
+            priority_weight,
+            samples,
+            convolver: None,
         };
         
         self.sounds.insert(handle, instance);
@@ -81,7 +335,7 @@ impl AudioEngine {
         
         log::trace!("Sound playing: {} at ({}, {}, {})", name, x, y, z);
         
-        handle
+        PlayResult::Started(handle)
     }
     
     /// Stop a sound by handle
@@ -121,6 +375,63 @@ impl AudioEngine {
         self.listener_pitch = pitch;
     }
     
+    /// Choose speaker panning or headphone HRTF
+    pub fn set_spatialization(&mut self, mode: Spatialization) {
+        if mode == Spatialization::Hrtf && self.hrirs.is_none() {
+            self.hrirs = Some(hrtf::HrirSet::new());
+        }
+        if mode != self.spatialization {
+            // Filter history from the other mode would click
+            for sound in self.sounds.values_mut() {
+                sound.convolver = None;
+            }
+        }
+        self.spatialization = mode;
+    }
+    
+    /// Get the spatialization mode
+    pub fn spatialization(&self) -> Spatialization {
+        self.spatialization
+    }
+    
+    /// Get a voice's (azimuth, elevation) relative to the listener in degrees
+    pub fn voice_direction(&self, handle: u64) -> Option<(f32, f32)> {
+        let sound = self.sounds.get(&handle)?;
+        Some(hrtf::source_direction(
+            glam::Vec3::new(self.listener_x, self.listener_y, self.listener_z),
+            self.listener_yaw,
+            self.listener_pitch,
+            glam::Vec3::new(sound.x, sound.y, sound.z),
+        ))
+    }
+    
+    /// Spatialize a mono block of a voice, adding interleaved stereo to `out`
+    ///
+    /// Returns false if the handle isn't playing.
+    pub fn render_voice(&mut self, handle: u64, input: &[f32], out: &mut [f32]) -> bool {
+        let Some((azimuth, elevation)) = self.voice_direction(handle) else {
+            return false;
+        };
+        let sound = &self.sounds[&handle];
+        let gain = self.calculate_attenuation(sound.x, sound.y, sound.z) * sound.volume;
+        
+        match (self.spatialization, self.hrirs.as_ref()) {
+            (Spatialization::Hrtf, Some(hrirs)) => {
+                let hrir = hrirs.interpolate(azimuth, elevation);
+                let sound = self.sounds.get_mut(&handle).unwrap();
+                sound.convolver.get_or_insert_with(hrtf::HrtfConvolver::new).process(input, &hrir, gain, out);
+            }
+            _ => {
+                let (left, right) = hrtf::pan_gains(azimuth);
+                for (frame, &sample) in out.chunks_exact_mut(2).zip(input) {
+                    frame[0] += sample * gain * left;
+                    frame[1] += sample * gain * right;
+                }
+            }
+        }
+        true
+    }
+    
     /// Set master volume
     pub fn set_master_volume(&mut self, volume: f32) {
         self.master_volume = volume.clamp(0.0, 1.0);
@@ -155,3 +466,81 @@ impl AudioEngine {
 pub fn shutdown() {
     log::debug!("Audio subsystem shutdown");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Mono 16-bit WAV of `frames` silent frames
+    fn silent_wav(frames: usize) -> Vec<u8> {
+        let data_len = (frames * 2) as u32;
+        let mut bytes = b"RIFF".to_vec();
+        bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+        bytes.extend_from_slice(b"WAVEfmt ");
+        for field in [16u32, 1 | 1 << 16, 44_100, 88_200, 2 | 16 << 16] {
+            bytes.extend_from_slice(&field.to_le_bytes());
+        }
+        bytes.extend_from_slice(b"data");
+        bytes.extend_from_slice(&data_len.to_le_bytes());
+        bytes.resize(bytes.len() + frames * 2, 0);
+        bytes
+    }
+
+    #[test]
+    fn test_loaded_sounds_play_with_samples() {
+        let mut audio = AudioEngine::new().unwrap();
+        audio.load_sound("step", &silent_wav(1000), AudioFormat::Wav).unwrap();
+        audio.load_sound("rain", &silent_wav(1000), AudioFormat::Wav).unwrap();
+        audio.set_looping("rain", true).unwrap();
+        assert!(audio.set_looping("missing", true).is_err());
+
+        let step = audio.play("step", 0.0, 0.0, 0.0, 1.0, 1.0).handle().unwrap();
+        let rain = audio.play("rain", 0.0, 0.0, 0.0, 1.0, 1.0).handle().unwrap();
+        let missing = audio.play("missing", 0.0, 0.0, 0.0, 1.0, 1.0).handle().unwrap();
+        assert!(audio.has_samples(step) && audio.has_samples(rain));
+        assert!(!audio.has_samples(missing));
+        assert!(audio.is_instance_looping(rain) && !audio.is_instance_looping(step));
+
+        // Over budget the least recently used sound is evicted, then decoded again on demand
+        audio.set_cache_budget(4000 * 3 / 2);
+        audio.load_sound("click", &silent_wav(1000), AudioFormat::Wav).unwrap();
+        assert!(audio.cache_bytes() <= 6000);
+        assert!(!audio.cache.contains("step"));
+        assert_eq!(audio.decoded("step").unwrap().frame_count(), 1000);
+
+        // Large files are streamed instead of cached
+        audio.load_sound("music", &silent_wav(STREAM_THRESHOLD_BYTES), AudioFormat::Wav).unwrap();
+        assert!(audio.is_streamed("music"));
+        assert!(!audio.cache.contains("music"));
+        let music = audio.play("music", 0.0, 0.0, 0.0, 1.0, 1.0).handle().unwrap();
+        assert!(audio.has_samples(music));
+    }
+
+    #[test]
+    fn test_voice_cap_keeps_nearest_and_loudest() {
+        let mut audio = AudioEngine::new().unwrap();
+        audio.set_max_voices(3);
+
+        let far = audio.play("zombie", 15.0, 0.0, 0.0, 1.0, 1.0).handle().unwrap();
+        let quiet = audio.play("zombie", 2.0, 0.0, 0.0, 0.1, 1.0).handle().unwrap();
+        let near = audio.play("zombie", 1.0, 0.0, 0.0, 1.0, 1.0).handle().unwrap();
+
+        // A loud nearby explosion replaces the distant zombie
+        let boom = audio.play("explode", 4.0, 0.0, 0.0, 1.0, 1.0).handle().unwrap();
+        assert!(!audio.is_playing(far));
+        assert_eq!(audio.sound_count(), 3);
+
+        // A sound quieter than every voice is refused
+        assert_eq!(audio.play("zombie", 15.5, 0.0, 0.0, 1.0, 1.0), PlayResult::Culled);
+
+        // Weight makes an otherwise quiet sound win
+        audio.set_priority_weight("ui", 100.0);
+        assert!(audio.play("ui", 15.0, 0.0, 0.0, 0.5, 1.0).handle().is_some());
+        assert!(!audio.is_playing(quiet));
+
+        // Lowering the cap drops the least important voices first
+        audio.set_max_voices(2);
+        assert!(audio.is_playing(near) && !audio.is_playing(boom));
+        assert_eq!(audio.voices_culled(), 4);
+    }
+}
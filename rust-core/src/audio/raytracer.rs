@@ -109,7 +109,7 @@ pub struct AudioStats {
 
 impl AudioRaytracer {
     /// Create new audio raytracer
-    pub fn new() -> Self {
+    pub fn new() -> Result<Self, String> {
         log::info!("Initializing Ray-Traced Audio");
         
         // Register default materials
@@ -124,7 +124,7 @@ impl AudioRaytracer {
         materials.insert(35, MaterialAcoustics::wool());   // Wool
         materials.insert(9, MaterialAcoustics::water());   // Water
         
-        Self {
+        Ok(Self {
             listener_pos: Vec3::ZERO,
             listener_dir: Vec3::Z,
             sources: HashMap::new(),
@@ -132,7 +132,7 @@ impl AudioRaytracer {
             occlusion_cache: HashMap::new(),
             reverb: ReverbParams::default(),
             stats: AudioStats::default(),
-        }
+        })
     }
     
     /// Update listener position
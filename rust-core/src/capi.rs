@@ -0,0 +1,322 @@
+//! # C API
+//!
+//! Stable `extern "C"` surface for non-JVM hosts (C/C++ launchers, tools).
+//! Engines are opaque handles; config and stats cross the boundary as
+//! `#[repr(C)]` structs. The header `include/libs.h` is generated from this
+//! file by `build.rs`; build with `LIBS_REGENERATE_HEADER=1` to refresh it.
+//!
+//! Every function accepts a null handle and treats it as a no-op (or
+//! returns the documented failure value).
+
+use std::ffi::{c_char, CStr};
+
+use crate::lockstep::Command;
+use crate::replay::ReplayRecorder;
+use crate::LibsEngine;
+
+/// Opaque engine handle
+pub struct LibsHandle {
+    engine: LibsEngine,
+}
+
+/// Engine creation options
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct LibsConfig {
+    /// Skip renderer and audio (dedicated servers, tools)
+    pub headless: bool,
+    /// Seed for lockstep mode; 0 leaves lockstep disabled
+    pub lockstep_seed: u64,
+}
+
+/// Engine statistics
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LibsStats {
+    /// Live entities
+    pub entity_count: u32,
+    /// Loaded chunk columns
+    pub loaded_chunks: u32,
+    /// Subsystems that initialized
+    pub ready_subsystems: u32,
+    /// Simulation ticks run
+    pub ticks: u64,
+    /// State hash of the last lockstep tick
+    pub tick_hash: u64,
+}
+
+/// Get default config (headless, lockstep disabled)
+#[no_mangle]
+pub extern "C" fn libs_config_default() -> LibsConfig {
+    LibsConfig { headless: true, lockstep_seed: 0 }
+}
+
+/// Get library version as a NUL-terminated string (static, do not free)
+#[no_mangle]
+pub extern "C" fn libs_version() -> *const c_char {
+    static VERSION: &CStr = c"1.0.0";
+    VERSION.as_ptr()
+}
+
+/// Create and initialize an engine; null config uses `libs_config_default`
+///
+/// # Safety
+/// `config` must be null or point to a valid `LibsConfig`.
+#[no_mangle]
+pub unsafe extern "C" fn libs_engine_create(config: *const LibsConfig) -> *mut LibsHandle {
+    let config = config.as_ref().copied().unwrap_or_else(|| libs_config_default());
+
+    let mut engine = if config.headless {
+        LibsEngine::new_headless()
+    } else {
+        let mut engine = LibsEngine::new();
+        engine.initialize();
+        engine
+    };
+    if config.lockstep_seed != 0 {
+        engine.enable_lockstep(config.lockstep_seed);
+    }
+
+    Box::into_raw(Box::new(LibsHandle { engine }))
+}
+
+/// Shut down and free an engine
+///
+/// # Safety
+/// `handle` must be null or come from `libs_engine_create`, and must not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn libs_engine_destroy(handle: *mut LibsHandle) {
+    if !handle.is_null() {
+        let mut handle = Box::from_raw(handle);
+        handle.engine.shutdown();
+    }
+}
+
+/// Advance the simulation
+///
+/// # Safety
+/// `handle` must be null or a live engine handle.
+#[no_mangle]
+pub unsafe extern "C" fn libs_engine_tick(handle: *mut LibsHandle, delta_time: f32) {
+    if let Some(handle) = handle.as_mut() {
+        handle.engine.tick(delta_time);
+    }
+}
+
+/// Advance one lockstep tick without inputs; returns the state hash
+///
+/// # Safety
+/// `handle` must be null or a live engine handle.
+#[no_mangle]
+pub unsafe extern "C" fn libs_engine_step(handle: *mut LibsHandle) -> u64 {
+    match handle.as_mut() {
+        Some(handle) => handle.engine.step_with_inputs(&[]),
+        None => 0,
+    }
+}
+
+/// Submit chunk payload bytes; returns the chunk handle or -1
+///
+/// # Safety
+/// `handle` must be null or a live engine handle; `data` must point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn libs_engine_submit_chunk(
+    handle: *mut LibsHandle,
+    x: i32,
+    z: i32,
+    data: *const u8,
+    len: usize,
+) -> i64 {
+    let Some(handle) = handle.as_mut() else {
+        return -1;
+    };
+    let data = if data.is_null() || len == 0 { &[][..] } else { std::slice::from_raw_parts(data, len) };
+    handle.engine.submit_chunk(x, z, data).unwrap_or(-1)
+}
+
+/// Spawn an entity; returns its handle or -1
+///
+/// # Safety
+/// `handle` must be null or a live engine handle.
+#[no_mangle]
+pub unsafe extern "C" fn libs_engine_spawn_entity(
+    handle: *mut LibsHandle,
+    entity_id: i32,
+    entity_type: i32,
+    x: f64,
+    y: f64,
+    z: f64,
+) -> i64 {
+    let Some(handle) = handle.as_mut() else {
+        return -1;
+    };
+    handle.engine.execute(&Command::SpawnEntity { id: entity_id, entity_type, x, y, z });
+    handle.engine.ecs.as_ref()
+        .and_then(|ecs| ecs.external_entity(entity_id as u64))
+        .map_or(-1, |entity| entity.to_bits() as i64)
+}
+
+/// Move an entity
+///
+/// # Safety
+/// `handle` must be null or a live engine handle.
+#[no_mangle]
+pub unsafe extern "C" fn libs_engine_update_entity(
+    handle: *mut LibsHandle,
+    entity_id: i32,
+    x: f64,
+    y: f64,
+    z: f64,
+    yaw: f32,
+    pitch: f32,
+) {
+    if let Some(handle) = handle.as_mut() {
+        handle.engine.execute(&Command::MoveEntity { id: entity_id, x, y, z, yaw, pitch });
+    }
+}
+
+/// Despawn an entity
+///
+/// # Safety
+/// `handle` must be null or a live engine handle.
+#[no_mangle]
+pub unsafe extern "C" fn libs_engine_despawn_entity(handle: *mut LibsHandle, entity_id: i32) {
+    if let Some(handle) = handle.as_mut() {
+        handle.engine.execute(&Command::DespawnEntity { id: entity_id });
+    }
+}
+
+/// Start recording API calls to a replay file; returns false on failure
+///
+/// # Safety
+/// `handle` must be null or a live engine handle; `path` must be null or a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn libs_engine_start_recording(handle: *mut LibsHandle, path: *const c_char) -> bool {
+    let Some(handle) = handle.as_mut() else {
+        return false;
+    };
+    if path.is_null() {
+        return false;
+    }
+    let Ok(path) = CStr::from_ptr(path).to_str() else {
+        return false;
+    };
+    match ReplayRecorder::create(path) {
+        Ok(recorder) => {
+            handle.engine.start_recording(recorder);
+            true
+        }
+        Err(e) => {
+            log::warn!("Cannot record replay to {}: {}", path, e);
+            false
+        }
+    }
+}
+
+/// Stop recording and flush the replay file; returns false if none was recording
+///
+/// # Safety
+/// `handle` must be null or a live engine handle.
+#[no_mangle]
+pub unsafe extern "C" fn libs_engine_stop_recording(handle: *mut LibsHandle) -> bool {
+    handle.as_mut().and_then(|h| h.engine.stop_recording()).is_some()
+}
+
+/// Fill `out` with engine statistics; returns false on null arguments
+///
+/// # Safety
+/// `handle` must be null or a live engine handle; `out` must be null or writable.
+#[no_mangle]
+pub unsafe extern "C" fn libs_engine_get_stats(handle: *const LibsHandle, out: *mut LibsStats) -> bool {
+    let (Some(handle), Some(out)) = (handle.as_ref(), out.as_mut()) else {
+        return false;
+    };
+    let engine = &handle.engine;
+
+    let mut stats = LibsStats {
+        ready_subsystems: engine.init_report().ready.len() as u32,
+        tick_hash: engine.tick_hash(),
+        ..LibsStats::default()
+    };
+    if let Some(ecs) = &engine.ecs {
+        let ecs_stats = ecs.get_stats();
+        stats.entity_count = ecs_stats.total_entities;
+        stats.ticks = ecs_stats.ticks_processed;
+    }
+    if let Some(world) = &engine.world {
+        stats.loaded_chunks = world.chunk_count() as u32;
+    }
+
+    *out = stats;
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_tick_destroy() {
+        unsafe {
+            let config = LibsConfig { headless: true, lockstep_seed: 7 };
+            let engine = libs_engine_create(&config);
+            assert!(!engine.is_null());
+
+            assert!(libs_engine_submit_chunk(engine, 0, 0, std::ptr::null(), 0) >= 0);
+            assert!(libs_engine_spawn_entity(engine, 1, 0, 0.5, 64.0, 0.5) >= 0);
+            for _ in 0..3 {
+                libs_engine_tick(engine, 0.05);
+            }
+            let hash = libs_engine_step(engine);
+
+            let mut stats = LibsStats::default();
+            assert!(libs_engine_get_stats(engine, &mut stats));
+            assert_eq!(stats.entity_count, 1);
+            assert_eq!(stats.loaded_chunks, 1);
+            assert_eq!(stats.ticks, 4);
+            assert_eq!(stats.tick_hash, hash);
+
+            libs_engine_destroy(engine);
+
+            // Null handles are ignored
+            libs_engine_tick(std::ptr::null_mut(), 0.05);
+            assert!(!libs_engine_get_stats(std::ptr::null(), &mut stats));
+            assert_eq!(CStr::from_ptr(libs_version()).to_str(), Ok(crate::VERSION));
+        }
+    }
+
+    #[test]
+    fn test_entity_calls_are_recorded() {
+        use crate::replay::ReplayPlayer;
+
+        let path = std::env::temp_dir().join(format!("libs_capi_replay_{}.lrpl", std::process::id()));
+        let c_path = std::ffi::CString::new(path.to_str().unwrap()).unwrap();
+        unsafe {
+            let engine = libs_engine_create(std::ptr::null());
+            assert!(libs_engine_start_recording(engine, c_path.as_ptr()));
+            libs_engine_submit_chunk(engine, 0, 0, std::ptr::null(), 0);
+            assert!(libs_engine_spawn_entity(engine, 1, 0, 0.5, 64.0, 0.5) >= 0);
+            assert!(libs_engine_spawn_entity(engine, 2, 0, 4.5, 64.0, 0.5) >= 0);
+            for tick in 0..5 {
+                libs_engine_update_entity(engine, 1, 0.5 + tick as f64, 64.0, 0.5, 0.0, 0.0);
+                libs_engine_tick(engine, 0.05);
+            }
+            libs_engine_despawn_entity(engine, 2);
+            libs_engine_tick(engine, 0.05);
+            assert!(libs_engine_stop_recording(engine));
+            assert!(!libs_engine_stop_recording(engine));
+
+            let player = ReplayPlayer::open(&path).unwrap();
+            std::fs::remove_file(&path).unwrap();
+            let commands = player.events().iter().filter(|e| matches!(e.call, crate::replay::ReplayCall::Command(_)));
+            assert_eq!(commands.count(), 1 + 2 + 5 + 1);
+
+            let mut replayed = LibsEngine::new_headless();
+            player.play(&mut replayed);
+            let original = &(*engine).engine;
+            assert_eq!(replayed.ecs.as_ref().unwrap().entity_count(), 1);
+            assert_eq!(replayed.ecs.as_ref().unwrap().state_hash(), original.ecs.as_ref().unwrap().state_hash());
+            libs_engine_destroy(engine);
+        }
+    }
+}
@@ -7,6 +7,9 @@ use std::sync::Arc;
 use std::collections::{HashMap, HashSet};
 use parking_lot::RwLock;
 
+#[cfg(feature = "vulkan")]
+use crate::renderer::vulkan::FeatureReport;
+
 /// Compatibility level for a mod/feature
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum CompatLevel {
@@ -146,6 +149,9 @@ pub struct TheWeaver {
     detected_mods: Vec<String>,
     /// Statistics
     stats: WeaverStats,
+    /// Optional GPU features of the active device
+    #[cfg(feature = "vulkan")]
+    capabilities: Option<FeatureReport>,
     /// Initialized
     initialized: bool,
 }
@@ -174,6 +180,8 @@ impl TheWeaver {
             next_notification_id: 1,
             detected_mods: Vec::new(),
             stats: WeaverStats::default(),
+            #[cfg(feature = "vulkan")]
+            capabilities: None,
             initialized: false,
         };
         
@@ -436,6 +444,18 @@ impl TheWeaver {
         &self.stats
     }
     
+    /// Record the active device's optional features (after device creation)
+    #[cfg(feature = "vulkan")]
+    pub fn set_capabilities(&mut self, report: FeatureReport) {
+        self.capabilities = Some(report);
+    }
+    
+    /// Get the active device's optional features, if a device was created
+    #[cfg(feature = "vulkan")]
+    pub fn capabilities(&self) -> Option<&FeatureReport> {
+        self.capabilities.as_ref()
+    }
+    
     /// Generate compatibility report
     pub fn generate_report(&self) -> String {
         let mut report = String::new();
@@ -457,6 +477,12 @@ impl TheWeaver {
             }
         }
         
+        #[cfg(feature = "vulkan")]
+        if let Some(capabilities) = &self.capabilities {
+            report.push_str("\n--- GPU Features ---\n");
+            report.push_str(&capabilities.to_string());
+        }
+        
         report
     }
     
@@ -0,0 +1,189 @@
+//! # Command Console
+//!
+//! Text commands for poking at a running engine without recompiling, e.g.
+//! `set render_distance 12`, `debug wireframe on`, `profiler reset`. A
+//! command line is split on whitespace (double quotes group words), the
+//! first word picks the handler and the rest are passed to it as
+//! arguments. Handlers get mutable access to whatever the console drives
+//! and answer with text, so the same console works from chat input, a
+//! debug overlay or a test.
+
+use std::collections::BTreeMap;
+
+/// Command handler: gets the target and the arguments after the name
+pub type CommandHandler<C> = Box<dyn Fn(&mut C, &[&str]) -> Result<String, String> + Send + Sync>;
+
+/// Registered command
+struct Command<C> {
+    /// One-line usage shown by `help`
+    usage: String,
+    /// Handler
+    handler: CommandHandler<C>,
+}
+
+/// Parses command lines and dispatches them to registered handlers
+pub struct Console<C> {
+    /// Commands by name
+    commands: BTreeMap<String, Command<C>>,
+}
+
+impl<C> Console<C> {
+    /// Create a console with no commands (`help` is always available)
+    pub fn new() -> Self {
+        Self { commands: BTreeMap::new() }
+    }
+
+    /// Register a command (replacing one with the same name)
+    pub fn register(
+        &mut self,
+        name: &str,
+        handler: impl Fn(&mut C, &[&str]) -> Result<String, String> + Send + Sync + 'static,
+    ) {
+        self.register_with_usage(name, name, handler);
+    }
+
+    /// Register a command with the usage line `help` prints for it
+    pub fn register_with_usage(
+        &mut self,
+        name: &str,
+        usage: &str,
+        handler: impl Fn(&mut C, &[&str]) -> Result<String, String> + Send + Sync + 'static,
+    ) {
+        let command = Command { usage: usage.to_string(), handler: Box::new(handler) };
+        self.commands.insert(name.to_ascii_lowercase(), command);
+    }
+
+    /// Remove a command
+    pub fn unregister(&mut self, name: &str) -> bool {
+        self.commands.remove(&name.to_ascii_lowercase()).is_some()
+    }
+
+    /// Check if a command is registered
+    pub fn has_command(&self, name: &str) -> bool {
+        self.commands.contains_key(&name.to_ascii_lowercase())
+    }
+
+    /// Iterate registered command names in order
+    pub fn command_names(&self) -> impl Iterator<Item = &str> {
+        self.commands.keys().map(String::as_str)
+    }
+
+    /// Parse and run a command line against `target`
+    ///
+    /// A leading `/` (as typed in chat) is ignored. `help` lists commands.
+    pub fn execute(&self, target: &mut C, line: &str) -> Result<String, String> {
+        let words = tokenize(line.trim().trim_start_matches('/'))?;
+        let Some((name, args)) = words.split_first() else {
+            return Err("empty command".to_string());
+        };
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+        let name = name.to_ascii_lowercase();
+        if name == "help" && !self.commands.contains_key("help") {
+            return Ok(self.help());
+        }
+        let command = self.commands
+            .get(&name)
+            .ok_or_else(|| format!("unknown command '{}' (try 'help')", name))?;
+        (command.handler)(target, &args)
+    }
+
+    /// List commands with their usage
+    pub fn help(&self) -> String {
+        let mut lines = vec!["Commands:".to_string()];
+        lines.extend(self.commands.values().map(|c| format!("  {}", c.usage)));
+        lines.join("\n")
+    }
+}
+
+impl<C> Default for Console<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Split a line into words; double quotes keep spaces inside one word
+pub fn tokenize(line: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut current: Option<String> = None;
+    let mut quoted = false;
+
+    for ch in line.chars() {
+        match ch {
+            '"' => {
+                quoted = !quoted;
+                current.get_or_insert_with(String::new);
+            }
+            c if c.is_whitespace() && !quoted => {
+                if let Some(word) = current.take() {
+                    words.push(word);
+                }
+            }
+            c => current.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if quoted {
+        return Err("unterminated quote".to_string());
+    }
+    words.extend(current);
+    Ok(words)
+}
+
+/// Parse an on/off style argument
+pub fn parse_switch(arg: &str) -> Result<bool, String> {
+    match arg.to_ascii_lowercase().as_str() {
+        "on" | "true" | "1" | "yes" | "enable" => Ok(true),
+        "off" | "false" | "0" | "no" | "disable" => Ok(false),
+        _ => Err(format!("expected on/off, got '{}'", arg)),
+    }
+}
+
+/// Parse a numeric argument, naming it in the error
+pub fn parse_arg<T: std::str::FromStr>(arg: Option<&str>, name: &str) -> Result<T, String> {
+    let arg = arg.ok_or_else(|| format!("missing {}", name))?;
+    arg.parse().map_err(|_| format!("invalid {} '{}'", name, arg))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Settings {
+        render_distance: u32,
+        wireframe: bool,
+    }
+
+    #[test]
+    fn test_register_and_execute() {
+        let mut console = Console::<Settings>::new();
+        console.register_with_usage("set", "set <key> <value>", |s, args| match args {
+            ["render_distance", value] => {
+                s.render_distance = parse_arg(Some(*value), "render distance")?;
+                Ok(format!("render_distance = {}", s.render_distance))
+            }
+            [key, ..] => Err(format!("unknown setting '{}'", key)),
+            [] => Err("usage: set <key> <value>".to_string()),
+        });
+        console.register("debug", |s, args| {
+            s.wireframe = parse_switch(args.get(1).ok_or("usage: debug <flag> on|off")?)?;
+            Ok(format!("{} {}", args[0], if s.wireframe { "on" } else { "off" }))
+        });
+
+        let mut settings = Settings::default();
+        assert_eq!(console.execute(&mut settings, "set render_distance 12").unwrap(), "render_distance = 12");
+        assert_eq!(settings.render_distance, 12);
+        assert_eq!(console.execute(&mut settings, "/DEBUG wireframe on").unwrap(), "wireframe on");
+        assert!(settings.wireframe);
+
+        assert!(console.execute(&mut settings, "set render_distance far").unwrap_err().contains("invalid"));
+        assert!(console.execute(&mut settings, "teleport 0 0").unwrap_err().contains("unknown command"));
+        assert!(console.execute(&mut settings, "   ").is_err());
+        assert!(console.help().contains("set <key> <value>"));
+        assert_eq!(console.execute(&mut settings, "help").unwrap(), console.help());
+
+        assert_eq!(tokenize(r#"say "hello world" !"#).unwrap(), vec!["say", "hello world", "!"]);
+        assert_eq!(tokenize(r#"name """#).unwrap(), vec!["name", ""]);
+        assert!(tokenize(r#"say "oops"#).is_err());
+    }
+}
@@ -0,0 +1,416 @@
+//! # Entity AI
+//!
+//! Server-side mob behavior. Every entity with an `AiState` runs a small
+//! state machine each tick: the handler for its current state looks at the
+//! entities around it, sets its velocity and returns the state to be in
+//! next tick. Handlers are looked up by state ID in the `AiProfile`
+//! registered for the entity's type (`EntityType::type_id`), so mods can
+//! add states or replace the built-in idle, wander and chase. Entities of
+//! unregistered types are left alone. Neighbors come from a chunk-sized
+//! grid rebuilt from entity positions every tick.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use glam::Vec3;
+
+use super::components::{AiBehavior, AiState, EntityType, Position, Velocity};
+use super::{EcsWorld, Entity};
+
+/// AI state identifier (`AiBehavior` IDs are built in)
+pub type AiStateId = u16;
+
+/// `AiState::target_entity` when there is no target
+pub const NO_TARGET: Entity = Entity::NULL;
+
+/// Grid cell size (one chunk)
+const CELL_SIZE: f32 = 16.0;
+
+/// Horizontal distance at which a wander destination counts as reached
+const ARRIVE_DISTANCE: f32 = 0.5;
+
+/// State handler: steer the entity and return its next state
+pub type AiStateFn = Arc<dyn Fn(&mut AiContext) -> AiStateId + Send + Sync>;
+
+/// Entity within sight of a behaving entity
+#[derive(Clone, Copy)]
+pub struct Neighbor {
+    /// Entity ID
+    pub entity: Entity,
+    /// Position
+    pub position: Vec3,
+    /// Type (default if the entity has none)
+    pub kind: EntityType,
+}
+
+/// What a state handler sees and may change
+pub struct AiContext<'a> {
+    /// Entity being ticked
+    pub entity: Entity,
+    /// Its position
+    pub position: Vec3,
+    /// Its AI state (timer already advanced)
+    pub state: &'a mut AiState,
+    /// Its velocity, written back after the handler
+    pub velocity: &'a mut Velocity,
+    /// Entities within sight, nearest first
+    pub nearby: &'a [Neighbor],
+    /// Profile of the entity's type
+    pub profile: &'a AiProfile,
+    /// Tick length (seconds)
+    pub delta_time: f32,
+    /// AI ticks run so far
+    pub tick: u64,
+}
+
+impl AiContext<'_> {
+    /// Get the nearest player in sight
+    pub fn nearest_player(&self) -> Option<&Neighbor> {
+        self.nearby.iter().find(|n| n.kind.is_player)
+    }
+
+    /// Find an entity in sight
+    pub fn neighbor(&self, entity: Entity) -> Option<&Neighbor> {
+        self.nearby.iter().find(|n| n.entity == entity)
+    }
+
+    /// Steer horizontally toward a point at the profile's speed
+    ///
+    /// Vertical velocity is left to physics. Returns the remaining
+    /// horizontal distance.
+    pub fn move_toward(&mut self, target: Vec3) -> f32 {
+        let offset = Vec3::new(target.x - self.position.x, 0.0, target.z - self.position.z);
+        let distance = offset.length();
+        let direction = if distance > 1e-4 { offset / distance } else { Vec3::ZERO };
+        self.velocity.x = direction.x * self.profile.speed;
+        self.velocity.z = direction.z * self.profile.speed;
+        distance
+    }
+
+    /// Stop horizontal movement
+    pub fn stop(&mut self) {
+        self.velocity.x = 0.0;
+        self.velocity.z = 0.0;
+    }
+
+    /// Target the nearest player in sight, if any
+    fn acquire_player(&mut self) -> bool {
+        let Some(player) = self.nearest_player().map(|n| n.entity) else {
+            return false;
+        };
+        self.state.target_entity = player;
+        true
+    }
+}
+
+/// Behavior of an entity type
+#[derive(Clone)]
+pub struct AiProfile {
+    /// Movement speed (blocks per second)
+    pub speed: f32,
+    /// How far the entity notices others (blocks)
+    pub sight_radius: f32,
+    /// Seconds idle before wandering off
+    pub wander_interval: f32,
+    /// How far a wander goes (blocks)
+    pub wander_radius: f32,
+    /// Handlers by state
+    states: HashMap<AiStateId, AiStateFn>,
+}
+
+impl AiProfile {
+    /// Create a profile with the built-in idle, wander and chase states
+    pub fn new(speed: f32, sight_radius: f32) -> Self {
+        let mut profile = Self {
+            speed,
+            sight_radius,
+            wander_interval: 5.0,
+            wander_radius: 8.0,
+            states: HashMap::new(),
+        };
+        profile.set_state(AiBehavior::Idle.id(), Arc::new(idle));
+        profile.set_state(AiBehavior::Wander.id(), Arc::new(wander));
+        profile.set_state(AiBehavior::Chase.id(), Arc::new(chase));
+        profile
+    }
+
+    /// Add or replace a state handler
+    pub fn with_state(mut self, state: AiStateId, handler: AiStateFn) -> Self {
+        self.set_state(state, handler);
+        self
+    }
+
+    /// Add or replace a state handler
+    pub fn set_state(&mut self, state: AiStateId, handler: AiStateFn) {
+        self.states.insert(state, handler);
+    }
+
+    /// Get the handler for a state
+    pub fn handler(&self, state: AiStateId) -> Option<&AiStateFn> {
+        self.states.get(&state)
+    }
+}
+
+/// Built-in idle: stand still, chase players in sight, wander now and then
+fn idle(ctx: &mut AiContext) -> AiStateId {
+    ctx.stop();
+    if ctx.acquire_player() {
+        return chase(ctx);
+    }
+    if ctx.state.state_timer < ctx.profile.wander_interval {
+        return AiBehavior::Idle.id();
+    }
+
+    // Deterministic direction so lockstep peers agree
+    let mut seed = [0u8; 16];
+    seed[..8].copy_from_slice(&ctx.entity.to_bits().to_le_bytes());
+    seed[8..].copy_from_slice(&ctx.tick.to_le_bytes());
+    let turn = crate::util::hash::fnv1a(&seed) as u32 as f32 / u32::MAX as f32;
+    let angle = turn * std::f32::consts::TAU;
+    let target = ctx.position + Vec3::new(angle.cos(), 0.0, angle.sin()) * ctx.profile.wander_radius;
+    ctx.state.target_pos = target.to_array();
+    AiBehavior::Wander.id()
+}
+
+/// Built-in wander: walk to `target_pos`, then idle
+fn wander(ctx: &mut AiContext) -> AiStateId {
+    if ctx.acquire_player() {
+        return chase(ctx);
+    }
+    let remaining = ctx.move_toward(Vec3::from_array(ctx.state.target_pos));
+    if remaining < ARRIVE_DISTANCE || ctx.state.state_timer > ctx.profile.wander_interval * 2.0 {
+        ctx.stop();
+        return AiBehavior::Idle.id();
+    }
+    AiBehavior::Wander.id()
+}
+
+/// Built-in chase: run at the target until it leaves sight
+fn chase(ctx: &mut AiContext) -> AiStateId {
+    let Some(target) = ctx.neighbor(ctx.state.target_entity).map(|n| n.position) else {
+        ctx.state.target_entity = NO_TARGET;
+        ctx.stop();
+        return AiBehavior::Idle.id();
+    };
+    ctx.move_toward(target);
+    AiBehavior::Chase.id()
+}
+
+/// Entity positions bucketed by cell
+#[derive(Default)]
+struct SpatialGrid {
+    cells: HashMap<(i32, i32, i32), Vec<(Entity, Vec3)>>,
+}
+
+impl SpatialGrid {
+    /// Cell containing a position
+    fn cell_of(position: Vec3) -> (i32, i32, i32) {
+        let cell = (position / CELL_SIZE).floor();
+        (cell.x as i32, cell.y as i32, cell.z as i32)
+    }
+
+    fn clear(&mut self) {
+        self.cells.clear();
+    }
+
+    fn insert(&mut self, entity: Entity, position: Vec3) {
+        self.cells.entry(Self::cell_of(position)).or_default().push((entity, position));
+    }
+
+    /// Entities within `radius` of `center`, nearest first
+    fn within(&self, center: Vec3, radius: f32) -> Vec<(Entity, Vec3)> {
+        let (cx, cy, cz) = Self::cell_of(center);
+        let reach = (radius / CELL_SIZE).ceil() as i32;
+        let radius_sq = radius * radius;
+
+        let mut found: Vec<(Entity, Vec3)> = Vec::new();
+        for dx in -reach..=reach {
+            for dy in -reach..=reach {
+                for dz in -reach..=reach {
+                    let Some(cell) = self.cells.get(&(cx + dx, cy + dy, cz + dz)) else {
+                        continue;
+                    };
+                    found.extend(cell.iter().filter(|(_, p)| p.distance_squared(center) <= radius_sq));
+                }
+            }
+        }
+        found.sort_by(|a, b| {
+            a.1.distance_squared(center)
+                .total_cmp(&b.1.distance_squared(center))
+                .then(a.0.cmp(&b.0))
+        });
+        found
+    }
+}
+
+/// Runs AI state machines over the ECS
+pub struct AiSystem {
+    /// Profiles by entity type ID
+    profiles: HashMap<u16, AiProfile>,
+    /// Neighbor lookup, rebuilt each run
+    grid: SpatialGrid,
+    /// Runs so far
+    tick: u64,
+}
+
+impl AiSystem {
+    /// Create a system with no profiles
+    pub fn new() -> Self {
+        Self {
+            profiles: HashMap::new(),
+            grid: SpatialGrid::default(),
+            tick: 0,
+        }
+    }
+
+    /// Register the behavior of an entity type
+    pub fn register(&mut self, entity_type: u16, profile: AiProfile) {
+        self.profiles.insert(entity_type, profile);
+    }
+
+    /// Remove an entity type's behavior
+    pub fn unregister(&mut self, entity_type: u16) -> Option<AiProfile> {
+        self.profiles.remove(&entity_type)
+    }
+
+    /// Get an entity type's behavior
+    pub fn profile(&self, entity_type: u16) -> Option<&AiProfile> {
+        self.profiles.get(&entity_type)
+    }
+
+    /// Get an entity type's behavior for editing
+    pub fn profile_mut(&mut self, entity_type: u16) -> Option<&mut AiProfile> {
+        self.profiles.get_mut(&entity_type)
+    }
+
+    /// Tick every entity with an `AiState` and a registered type
+    ///
+    /// Decisions use positions from the start of the run; the chosen
+    /// velocities are written back as `Velocity` components.
+    pub fn run(&mut self, world: &mut EcsWorld, delta_time: f32) {
+        if self.profiles.is_empty() {
+            return;
+        }
+        let agents = world.query::<AiState>();
+        if agents.is_empty() {
+            return;
+        }
+
+        self.grid.clear();
+        for (entity, position) in world.query::<Position>() {
+            self.grid.insert(entity, to_vec3(position));
+        }
+
+        for (entity, mut state) in agents {
+            let kind = world.get_component::<EntityType>(entity).unwrap_or_default();
+            let Some(profile) = self.profiles.get(&kind.type_id) else {
+                continue;
+            };
+            let Some(position) = world.get_component::<Position>(entity).map(to_vec3) else {
+                continue;
+            };
+            let mut velocity = world.get_component::<Velocity>(entity).unwrap_or_default();
+
+            let nearby: Vec<Neighbor> = self.grid
+                .within(position, profile.sight_radius)
+                .into_iter()
+                .filter(|&(other, _)| other != entity)
+                .map(|(other, position)| Neighbor {
+                    entity: other,
+                    position,
+                    kind: world.get_component::<EntityType>(other).unwrap_or_default(),
+                })
+                .collect();
+
+            // Unknown states (e.g. from an unregistered mod) fall back to idle
+            let current = match profile.handler(state.state) {
+                Some(_) => state.state,
+                None => AiBehavior::Idle.id(),
+            };
+            let Some(handler) = profile.handler(current) else {
+                continue;
+            };
+
+            state.state_timer += delta_time;
+            let mut ctx = AiContext {
+                entity,
+                position,
+                state: &mut state,
+                velocity: &mut velocity,
+                nearby: &nearby,
+                profile,
+                delta_time,
+                tick: self.tick,
+            };
+            let next = handler(&mut ctx);
+            if next != state.state {
+                state.state = next;
+                state.state_timer = 0.0;
+            }
+
+            world.add_component(entity, state);
+            world.add_component(entity, velocity);
+        }
+
+        self.tick += 1;
+    }
+}
+
+impl Default for AiSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn to_vec3(position: Position) -> Vec3 {
+    Vec3::new(position.x as f32, position.y as f32, position.z as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ZOMBIE: u16 = 54;
+
+    fn spawn(world: &mut EcsWorld, x: f64, z: f64, kind: EntityType) -> Entity {
+        let entity = world.spawn();
+        world.add_component(entity, Position { x, y: 64.0, z });
+        world.add_component(entity, kind);
+        entity
+    }
+
+    #[test]
+    fn test_chaser_velocity_points_at_target() {
+        let mut world = EcsWorld::new();
+        world.ai_mut().register(ZOMBIE, AiProfile::new(4.0, 16.0));
+
+        let zombie = EntityType { type_id: ZOMBIE, is_hostile: true, is_passive: false, ..Default::default() };
+        let chaser = spawn(&mut world, 0.0, 0.0, zombie);
+        world.add_component(chaser, AiState::default());
+        world.add_component(chaser, Velocity::default());
+        let player = spawn(&mut world, 6.0, 8.0, EntityType { is_player: true, ..Default::default() });
+
+        // A second zombie with no player in sight stays put
+        let bystander = spawn(&mut world, 100.0, 100.0, zombie);
+        world.add_component(bystander, AiState::default());
+
+        world.tick(0.05);
+
+        let state = world.get_component::<AiState>(chaser).unwrap();
+        assert_eq!(state.state, AiBehavior::Chase.id());
+        assert_eq!(state.target_entity, player);
+
+        let velocity = world.get_component::<Velocity>(chaser).unwrap();
+        let direction = Vec3::new(velocity.x, velocity.y, velocity.z);
+        assert!((direction.length() - 4.0).abs() < 1e-4);
+        assert!(direction.normalize().dot(Vec3::new(0.6, 0.0, 0.8)) > 0.999);
+
+        // Physics ran after the AI in the same tick
+        let moved = world.get_component::<Position>(chaser).unwrap();
+        assert!(moved.x > 0.0 && moved.z > 0.0);
+
+        let idle = world.get_component::<AiState>(bystander).unwrap();
+        assert_eq!(idle.state, AiBehavior::Idle.id());
+        assert_eq!(world.get_component::<Velocity>(bystander).map(|v| v.x), Some(0.0));
+    }
+}
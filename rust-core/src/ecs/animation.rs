@@ -0,0 +1,305 @@
+//! # Keyframe Animation
+//!
+//! Bone animation for mob limbs and custom models. A clip is a list of
+//! keyframes, each holding one transform per bone; sampling between two
+//! keyframes lerps translation and scale and slerps rotation. Every entity
+//! with an `Animator` advances through its clip each tick, optionally
+//! crossfading out of the previous one, and the resulting bone matrices are
+//! kept here for the instanced renderer to upload as a per-instance palette.
+//!
+//! Bone transforms are model space: a bone's keyframes already include its
+//! parent's motion, so no hierarchy is walked at runtime.
+
+use std::collections::HashMap;
+
+use glam::{Mat4, Quat, Vec3};
+
+use super::components::Animator;
+use super::{EcsWorld, Entity};
+
+/// Registered clip identifier
+pub type ClipId = u16;
+
+/// `Animator` clip slot that holds nothing
+pub const NO_CLIP: ClipId = ClipId::MAX;
+
+/// One bone's pose
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoneTransform {
+    pub translation: Vec3,
+    pub rotation: Quat,
+    pub scale: Vec3,
+}
+
+impl BoneTransform {
+    /// Rest pose
+    pub const IDENTITY: BoneTransform = BoneTransform {
+        translation: Vec3::ZERO,
+        rotation: Quat::IDENTITY,
+        scale: Vec3::ONE,
+    };
+
+    /// Create a transform
+    pub fn new(translation: Vec3, rotation: Quat, scale: Vec3) -> Self {
+        Self { translation, rotation, scale }
+    }
+
+    /// Interpolate towards `other` (slerp for rotation)
+    pub fn lerp(&self, other: &BoneTransform, t: f32) -> BoneTransform {
+        BoneTransform {
+            translation: self.translation.lerp(other.translation, t),
+            rotation: self.rotation.slerp(other.rotation, t),
+            scale: self.scale.lerp(other.scale, t),
+        }
+    }
+
+    /// Get the bone matrix
+    pub fn matrix(&self) -> Mat4 {
+        Mat4::from_scale_rotation_translation(self.scale, self.rotation, self.translation)
+    }
+}
+
+impl Default for BoneTransform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+/// Pose of every bone at a point in a clip
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keyframe {
+    /// Time in the clip (seconds)
+    pub time: f32,
+    /// Transform per bone
+    pub bones: Vec<BoneTransform>,
+}
+
+impl Keyframe {
+    /// Create a keyframe
+    pub fn new(time: f32, bones: Vec<BoneTransform>) -> Self {
+        Self { time, bones }
+    }
+}
+
+/// Sequence of keyframes
+#[derive(Debug, Clone)]
+pub struct AnimationClip {
+    /// Name the clip was registered under
+    pub name: String,
+    /// Keyframes by ascending time
+    keyframes: Vec<Keyframe>,
+    /// Wrap around at the end instead of holding the last pose
+    pub looping: bool,
+}
+
+impl AnimationClip {
+    /// Get the clip length (time of the last keyframe)
+    pub fn duration(&self) -> f32 {
+        self.keyframes.last().map_or(0.0, |k| k.time)
+    }
+
+    /// Get the number of bones
+    pub fn bone_count(&self) -> usize {
+        self.keyframes.first().map_or(0, |k| k.bones.len())
+    }
+
+    /// Map a playback time into the clip (wrapping if looping)
+    pub fn wrap_time(&self, time: f32) -> f32 {
+        let duration = self.duration();
+        if duration <= 0.0 {
+            0.0
+        } else if self.looping {
+            time.rem_euclid(duration)
+        } else {
+            time.clamp(0.0, duration)
+        }
+    }
+
+    /// Pose at a time
+    pub fn sample(&self, time: f32) -> Vec<BoneTransform> {
+        let time = self.wrap_time(time);
+        // First keyframe after `time`; the pose lies between it and the one before
+        let next = self.keyframes.partition_point(|k| k.time <= time);
+        if next == 0 {
+            return self.keyframes[0].bones.clone();
+        }
+        if next == self.keyframes.len() {
+            return self.keyframes[next - 1].bones.clone();
+        }
+        let (a, b) = (&self.keyframes[next - 1], &self.keyframes[next]);
+        let t = (time - a.time) / (b.time - a.time);
+        a.bones.iter().zip(&b.bones).map(|(a, b)| a.lerp(b, t)).collect()
+    }
+}
+
+/// Clip registry and per-entity bone matrices
+pub struct AnimationSystem {
+    /// Clips by ID
+    clips: Vec<AnimationClip>,
+    /// Clip IDs by name
+    names: HashMap<String, ClipId>,
+    /// Bone matrices from the last run
+    poses: HashMap<Entity, Vec<Mat4>>,
+}
+
+impl AnimationSystem {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self { clips: Vec::new(), names: HashMap::new(), poses: HashMap::new() }
+    }
+
+    /// Register a looping clip (replacing one with the same name)
+    ///
+    /// Keyframes must be in ascending time and all have the same bone count.
+    pub fn register_clip(&mut self, name: &str, keyframes: Vec<Keyframe>) -> Result<ClipId, String> {
+        let Some(first) = keyframes.first() else {
+            return Err(format!("clip '{}' has no keyframes", name));
+        };
+        let bones = first.bones.len();
+        if keyframes.iter().any(|k| k.bones.len() != bones) {
+            return Err(format!("clip '{}' keyframes differ in bone count", name));
+        }
+        if keyframes.windows(2).any(|w| w[1].time <= w[0].time) {
+            return Err(format!("clip '{}' keyframe times must increase", name));
+        }
+
+        let clip = AnimationClip { name: name.to_string(), keyframes, looping: true };
+        if let Some(&id) = self.names.get(name) {
+            self.clips[id as usize] = clip;
+            return Ok(id);
+        }
+        if self.clips.len() >= NO_CLIP as usize {
+            return Err("too many animation clips".to_string());
+        }
+        let id = self.clips.len() as ClipId;
+        self.clips.push(clip);
+        self.names.insert(name.to_string(), id);
+        Ok(id)
+    }
+
+    /// Look up a clip ID by name
+    pub fn clip_id(&self, name: &str) -> Option<ClipId> {
+        self.names.get(name).copied()
+    }
+
+    /// Get a clip
+    pub fn clip(&self, id: ClipId) -> Option<&AnimationClip> {
+        self.clips.get(id as usize)
+    }
+
+    /// Get a clip (e.g. to turn off looping)
+    pub fn clip_mut(&mut self, id: ClipId) -> Option<&mut AnimationClip> {
+        self.clips.get_mut(id as usize)
+    }
+
+    /// Get the number of registered clips
+    pub fn clip_count(&self) -> usize {
+        self.clips.len()
+    }
+
+    /// Pose of an animator, blending a crossfade
+    pub fn sample(&self, animator: &Animator) -> Option<Vec<BoneTransform>> {
+        let target = self.clip(animator.clip)?.sample(animator.time);
+        let Some(from) = self.clip(animator.fade_from) else {
+            return Some(target);
+        };
+        let from = from.sample(animator.fade_from_time);
+        if from.len() != target.len() {
+            return Some(target);
+        }
+        let weight = animator.blend_weight.clamp(0.0, 1.0);
+        Some(from.iter().zip(&target).map(|(a, b)| a.lerp(b, weight)).collect())
+    }
+
+    /// Get an entity's bone matrices from the last run
+    pub fn bone_matrices(&self, entity: Entity) -> Option<&[Mat4]> {
+        self.poses.get(&entity).map(Vec::as_slice)
+    }
+
+    /// Advance every `Animator` and recompute bone matrices
+    ///
+    /// Entities whose clip isn't registered keep no pose.
+    pub fn run(&mut self, world: &mut EcsWorld, delta_time: f32) {
+        self.poses.clear();
+        for (entity, mut animator) in world.query::<Animator>() {
+            let Some(clip) = self.clip(animator.clip) else {
+                continue;
+            };
+            let step = delta_time * animator.speed;
+            animator.time = clip.wrap_time(animator.time + step);
+
+            if animator.is_blending() {
+                animator.fade_from_time = self.clip(animator.fade_from)
+                    .map_or(0.0, |from| from.wrap_time(animator.fade_from_time + step));
+                animator.blend_weight = if animator.fade_duration > 0.0 {
+                    animator.blend_weight + delta_time / animator.fade_duration
+                } else {
+                    1.0
+                };
+                if animator.blend_weight >= 1.0 {
+                    animator.fade_from = NO_CLIP;
+                    animator.blend_weight = 1.0;
+                }
+            }
+
+            if let Some(pose) = self.sample(&animator) {
+                self.poses.insert(entity, pose.iter().map(BoneTransform::matrix).collect());
+            }
+            world.add_component(entity, animator);
+        }
+    }
+}
+
+impl Default for AnimationSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample_midpoint_and_crossfade() {
+        let mut world = EcsWorld::new();
+        let swing = world.animation_mut().register_clip("swing", vec![
+            Keyframe::new(0.0, vec![BoneTransform::IDENTITY]),
+            Keyframe::new(1.0, vec![BoneTransform::new(
+                Vec3::new(2.0, 0.0, 0.0),
+                Quat::from_rotation_y(std::f32::consts::FRAC_PI_2),
+                Vec3::ONE,
+            )]),
+        ]).unwrap();
+        let idle = world.animation_mut()
+            .register_clip("idle", vec![Keyframe::new(0.0, vec![BoneTransform::IDENTITY])])
+            .unwrap();
+        assert_eq!(world.animation().clip_id("swing"), Some(swing));
+        assert!(world.animation_mut().register_clip("bad", vec![]).is_err());
+
+        let entity = world.spawn();
+        world.add_component(entity, Animator::new(swing));
+        world.tick(0.5);
+
+        // Halfway: half the translation, half the rotation
+        let bone = world.animation().bone_matrices(entity).unwrap()[0];
+        let (_, rotation, translation) = bone.to_scale_rotation_translation();
+        assert!(translation.abs_diff_eq(Vec3::new(1.0, 0.0, 0.0), 1e-5));
+        assert!(rotation.abs_diff_eq(Quat::from_rotation_y(std::f32::consts::FRAC_PI_4), 1e-5));
+
+        // Crossfade to idle over 0.5s: a quarter in, the pose is 3/4 swing
+        let mut animator = world.get_component::<Animator>(entity).unwrap();
+        animator.crossfade(idle, 0.5);
+        world.add_component(entity, animator);
+        world.tick(0.125);
+        let animator = world.get_component::<Animator>(entity).unwrap();
+        assert!(animator.is_blending());
+        assert!((animator.blend_weight - 0.25).abs() < 1e-5);
+        let translation = world.animation().bone_matrices(entity).unwrap()[0].w_axis;
+        assert!((translation.x - 1.25 * 0.75).abs() < 1e-4);
+
+        world.tick(0.5);
+        assert!(!world.get_component::<Animator>(entity).unwrap().is_blending());
+        assert_eq!(world.animation().bone_matrices(entity).unwrap()[0], Mat4::IDENTITY);
+    }
+}
@@ -0,0 +1,270 @@
+//! # Entity Collision
+//!
+//! Moves entities with a `Collision` box through the voxel world without
+//! passing into blocks. Movement is swept the way vanilla does it: the
+//! entity box is clipped against every solid block box near its path one
+//! axis at a time (Y, then X, then Z), so it slides along walls and lands
+//! flush on floors. Velocity on a blocked axis is zeroed and landing sets
+//! `Collision::on_ground`. Blocks are treated as full cubes.
+
+use glam::DVec3;
+
+use super::components::{Collision, Physics, Position, Velocity};
+use super::{EcsWorld, Entity};
+use crate::util::bvh::{Bounds, Bvh};
+use crate::world::WorldManager;
+
+/// Downward acceleration (blocks per second², vanilla's 0.08 per tick²)
+pub const GRAVITY: f32 = 32.0;
+
+/// Fastest fall speed (blocks per second, vanilla's 3.92 per tick)
+pub const TERMINAL_VELOCITY: f32 = 78.4;
+
+/// Check if entities collide with a block (air, fluids and plants don't)
+pub fn is_solid_block(block: u16) -> bool {
+    !matches!(
+        block,
+        0 | 6 | 8..=11 | 27 | 28 | 30..=32 | 37..=40 | 50 | 51 | 55 | 59 | 63 | 66 | 68 | 69 | 75..=77 | 83 | 90
+    )
+}
+
+/// Axis-aligned bounding box
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: DVec3,
+    pub max: DVec3,
+}
+
+impl Aabb {
+    /// Create a box from corners
+    pub fn new(min: DVec3, max: DVec3) -> Self {
+        Self { min, max }
+    }
+
+    /// Box of an entity standing at `feet` (bottom center)
+    pub fn from_entity(feet: DVec3, width: f32, height: f32) -> Self {
+        let half = width as f64 / 2.0;
+        Self {
+            min: DVec3::new(feet.x - half, feet.y, feet.z - half),
+            max: DVec3::new(feet.x + half, feet.y + height as f64, feet.z + half),
+        }
+    }
+
+    /// Box of the block at integer coordinates
+    pub fn block(x: i32, y: i32, z: i32) -> Self {
+        let min = DVec3::new(x as f64, y as f64, z as f64);
+        Self { min, max: min + DVec3::ONE }
+    }
+
+    /// Move the box
+    pub fn offset(&self, by: DVec3) -> Self {
+        Self { min: self.min + by, max: self.max + by }
+    }
+
+    /// Grow the box to cover a movement
+    pub fn expand_towards(&self, motion: DVec3) -> Self {
+        Self { min: self.min.min(self.min + motion), max: self.max.max(self.max + motion) }
+    }
+
+    /// Limit movement along `axis` (0 x, 1 y, 2 z) so `mover` stays out of this box
+    pub fn clip(&self, mover: &Aabb, axis: usize, motion: f64) -> f64 {
+        // Only boxes overlapping on the other two axes can block
+        for other in (0..3).filter(|&a| a != axis) {
+            if self.max[other] <= mover.min[other] || self.min[other] >= mover.max[other] {
+                return motion;
+            }
+        }
+        if motion > 0.0 && self.min[axis] >= mover.max[axis] {
+            motion.min(self.min[axis] - mover.max[axis])
+        } else if motion < 0.0 && self.max[axis] <= mover.min[axis] {
+            motion.max(self.max[axis] - mover.min[axis])
+        } else {
+            motion
+        }
+    }
+}
+
+/// Outcome of a swept move
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SweepResult {
+    /// Movement actually possible
+    pub motion: DVec3,
+    /// Axes where blocks cut the movement short
+    pub blocked: [bool; 3],
+}
+
+impl SweepResult {
+    /// Check if the move ended on top of a block
+    pub fn on_ground(&self, requested: DVec3) -> bool {
+        self.blocked[1] && requested.y < 0.0
+    }
+}
+
+/// Sweep a box through the world, clipping Y, then X, then Z
+pub fn sweep(world: &WorldManager, aabb: Aabb, motion: DVec3) -> SweepResult {
+    let region = aabb.expand_towards(motion);
+    let (lo, hi) = (region.min.floor().as_ivec3(), region.max.floor().as_ivec3());
+    let mut blocks = Vec::new();
+    for y in lo.y.max(world.height().min_y)..=hi.y {
+        for z in lo.z..=hi.z {
+            for x in lo.x..=hi.x {
+                if is_solid_block(world.get_block(x, y, z)) {
+                    blocks.push(Aabb::block(x, y, z));
+                }
+            }
+        }
+    }
+
+    let mut moved = aabb;
+    let mut result = motion;
+    for axis in [1, 0, 2] {
+        let requested = motion[axis];
+        if requested == 0.0 {
+            continue;
+        }
+        let allowed = blocks.iter().fold(requested, |m, block| block.clip(&moved, axis, m));
+        result[axis] = allowed;
+        let mut step = DVec3::ZERO;
+        step[axis] = allowed;
+        moved = moved.offset(step);
+    }
+
+    let blocked = [0, 1, 2].map(|axis| (result[axis] - motion[axis]).abs() > 1e-9);
+    SweepResult { motion: result, blocked }
+}
+
+/// Accelerate downward, capped at terminal velocity
+pub fn apply_gravity(velocity: &mut Velocity, gravity_scale: f32, delta_time: f32) {
+    velocity.y = (velocity.y - GRAVITY * gravity_scale * delta_time).max(-TERMINAL_VELOCITY);
+}
+
+impl EcsWorld {
+    /// Move entities with a `Collision` box against the world
+    ///
+    /// Applies gravity (scaled by `Physics::gravity_scale` when present),
+    /// sweeps the movement and resolves it. `no_clip` entities move freely.
+    /// Entities without a `Collision` box are integrated by `tick` instead.
+    pub fn tick_physics(&mut self, world: &WorldManager, delta_time: f32) {
+        for (entity, mut collision) in self.query::<Collision>() {
+            let Some(position) = self.get_component::<Position>(entity) else {
+                continue;
+            };
+            let mut velocity = self.get_component::<Velocity>(entity).unwrap_or_default();
+            let feet = DVec3::new(position.x, position.y, position.z);
+
+            let motion = if collision.no_clip {
+                DVec3::new(velocity.x as f64, velocity.y as f64, velocity.z as f64) * delta_time as f64
+            } else {
+                let gravity_scale = self.get_component::<Physics>(entity).map_or(1.0, |p| p.gravity_scale);
+                apply_gravity(&mut velocity, gravity_scale, delta_time);
+                let requested = DVec3::new(velocity.x as f64, velocity.y as f64, velocity.z as f64) * delta_time as f64;
+
+                let aabb = Aabb::from_entity(feet, collision.width, collision.height);
+                let swept = sweep(world, aabb, requested);
+                if swept.blocked[0] {
+                    velocity.x = 0.0;
+                }
+                if swept.blocked[1] {
+                    velocity.y = 0.0;
+                }
+                if swept.blocked[2] {
+                    velocity.z = 0.0;
+                }
+                collision.on_ground = swept.on_ground(requested);
+                swept.motion
+            };
+
+            let feet = feet + motion;
+            self.add_component(entity, Position { x: feet.x, y: feet.y, z: feet.z });
+            self.add_component(entity, velocity);
+            self.add_component(entity, collision);
+        }
+    }
+    
+    /// Build a BVH over the boxes of entities with `Position` and `Collision`
+    ///
+    /// For entity frustum culling and ray picks against entities; refit it
+    /// with the new boxes after physics instead of rebuilding every tick.
+    pub fn entity_bvh(&self) -> Bvh<Entity> {
+        Bvh::build(self.query::<Collision>().into_iter().filter_map(|(entity, collision)| {
+            let position = self.get_component::<Position>(entity)?;
+            let aabb = Aabb::from_entity(DVec3::new(position.x, position.y, position.z), collision.width, collision.height);
+            Some((entity, Bounds::new(aabb.min.as_vec3(), aabb.max.as_vec3())))
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::WorldHeight;
+
+    #[test]
+    fn test_falling_entity_lands_on_floor() {
+        let mut world = WorldManager::new();
+        world.submit_chunk(0, 0, &[]);
+        for x in 0..16 {
+            for z in 0..16 {
+                world.set_block(x, 63, z, 1);
+            }
+        }
+        // Wall two blocks east of the drop point
+        for y in 64..67 {
+            world.set_block(11, y, 8, 1);
+        }
+
+        let mut ecs = EcsWorld::new();
+        let entity = ecs.spawn();
+        ecs.add_component(entity, Position { x: 8.5, y: 70.0, z: 8.5 });
+        ecs.add_component(entity, Velocity::default());
+        ecs.add_component(entity, Collision { width: 0.6, height: 1.8, on_ground: false, no_clip: false });
+
+        for _ in 0..40 {
+            ecs.tick_physics(&world, 0.05);
+        }
+        let position = ecs.get_component::<Position>(entity).unwrap();
+        assert!((position.y - 64.0).abs() < 1e-9, "y = {}", position.y);
+        assert!(ecs.get_component::<Collision>(entity).unwrap().on_ground);
+        assert_eq!(ecs.get_component::<Velocity>(entity).unwrap().y, 0.0);
+
+        // Walking into the wall stops flush against it
+        ecs.add_component(entity, Velocity { x: 4.0, y: 0.0, z: 0.0 });
+        for _ in 0..20 {
+            ecs.tick_physics(&world, 0.05);
+        }
+        let position = ecs.get_component::<Position>(entity).unwrap();
+        assert!((position.x - (11.0 - 0.3)).abs() < 1e-6, "x = {}", position.x);
+        assert_eq!(ecs.get_component::<Velocity>(entity).unwrap().x, 0.0);
+        assert!((position.y - 64.0).abs() < 1e-9);
+
+        // Picking the entity with a ray along the ground
+        let bvh = ecs.entity_bvh();
+        let from = glam::Vec3::new(0.0, 65.0, 8.5);
+        assert_eq!(bvh.query_ray(from, glam::Vec3::X), vec![entity]);
+        assert!(bvh.query_ray(from, glam::Vec3::NEG_X).is_empty());
+    }
+
+    #[test]
+    fn test_entity_lands_below_y_zero() {
+        let mut world = WorldManager::with_height(WorldHeight::OVERWORLD);
+        world.submit_chunk(0, 0, &[]);
+        for x in 0..16 {
+            for z in 0..16 {
+                world.set_block(x, -41, z, 1);
+            }
+        }
+
+        let mut ecs = EcsWorld::new();
+        let entity = ecs.spawn();
+        ecs.add_component(entity, Position { x: 8.5, y: -30.0, z: 8.5 });
+        ecs.add_component(entity, Velocity::default());
+        ecs.add_component(entity, Collision { width: 0.6, height: 1.8, on_ground: false, no_clip: false });
+
+        for _ in 0..40 {
+            ecs.tick_physics(&world, 0.05);
+        }
+        let position = ecs.get_component::<Position>(entity).unwrap();
+        assert!((position.y + 40.0).abs() < 1e-9, "y = {}", position.y);
+        assert!(ecs.get_component::<Collision>(entity).unwrap().on_ground);
+    }
+}
@@ -2,11 +2,34 @@
 //!
 //! DOD (Data-Oriented Design) versions of Minecraft entity data
 
-use super::{Component, ComponentId};
+use super::ai::{AiStateId, NO_TARGET};
+use super::animation::{ClipId, NO_CLIP};
+use bytemuck::{Pod, Zeroable};
+
+use super::{pod_deserialize, pod_serialize, Component, ComponentId, EcsError, Entity};
+
+/// Check the length of a fixed-size encoding
+fn exact<const N: usize>(bytes: &[u8], name: &str) -> Result<[u8; N], EcsError> {
+    bytes.try_into().map_err(|_| EcsError::Corrupt(format!("{} of {} bytes, expected {}", name, bytes.len(), N)))
+}
+
+/// Decode a flag byte, rejecting anything but 0 and 1
+fn flag(byte: u8, name: &str) -> Result<bool, EcsError> {
+    match byte {
+        0 => Ok(false),
+        1 => Ok(true),
+        _ => Err(EcsError::Corrupt(format!("{} flag byte {:#04x}", name, byte))),
+    }
+}
+
+/// Read a little-endian `f32` at `at`
+fn f32_at(bytes: &[u8], at: usize) -> f32 {
+    f32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+}
 
 /// Position component
 #[repr(C)]
-#[derive(Clone, Copy, Default)]
+#[derive(Clone, Copy, Default, Pod, Zeroable)]
 pub struct Position {
     pub x: f64,
     pub y: f64,
@@ -15,11 +38,19 @@ pub struct Position {
 
 impl Component for Position {
     fn type_id() -> ComponentId { 1 }
+    
+    fn serialize(&self, out: &mut Vec<u8>) {
+        pod_serialize(self, out)
+    }
+    
+    fn deserialize(bytes: &[u8]) -> Result<Self, EcsError> {
+        pod_deserialize(bytes)
+    }
 }
 
 /// Velocity component
 #[repr(C)]
-#[derive(Clone, Copy, Default)]
+#[derive(Clone, Copy, Default, Pod, Zeroable)]
 pub struct Velocity {
     pub x: f32,
     pub y: f32,
@@ -28,11 +59,19 @@ pub struct Velocity {
 
 impl Component for Velocity {
     fn type_id() -> ComponentId { 2 }
+    
+    fn serialize(&self, out: &mut Vec<u8>) {
+        pod_serialize(self, out)
+    }
+    
+    fn deserialize(bytes: &[u8]) -> Result<Self, EcsError> {
+        pod_deserialize(bytes)
+    }
 }
 
 /// Health component
 #[repr(C)]
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Pod, Zeroable)]
 pub struct Health {
     pub current: f32,
     pub max: f32,
@@ -47,6 +86,14 @@ impl Default for Health {
 
 impl Component for Health {
     fn type_id() -> ComponentId { 3 }
+    
+    fn serialize(&self, out: &mut Vec<u8>) {
+        pod_serialize(self, out)
+    }
+    
+    fn deserialize(bytes: &[u8]) -> Result<Self, EcsError> {
+        pod_deserialize(bytes)
+    }
 }
 
 /// Collision component
@@ -61,14 +108,30 @@ pub struct Collision {
 
 impl Component for Collision {
     fn type_id() -> ComponentId { 4 }
+    
+    fn serialize(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.width.to_le_bytes());
+        out.extend_from_slice(&self.height.to_le_bytes());
+        out.extend_from_slice(&[self.on_ground as u8, self.no_clip as u8]);
+    }
+    
+    fn deserialize(bytes: &[u8]) -> Result<Self, EcsError> {
+        let b: [u8; 10] = exact(bytes, "collision")?;
+        Ok(Self {
+            width: f32_at(&b, 0),
+            height: f32_at(&b, 4),
+            on_ground: flag(b[8], "collision on_ground")?,
+            no_clip: flag(b[9], "collision no_clip")?,
+        })
+    }
 }
 
 /// AI State component
 #[repr(C)]
 #[derive(Clone, Copy)]
 pub struct AiState {
-    pub state: AiBehavior,
-    pub target_entity: u32,
+    pub state: AiStateId,
+    pub target_entity: Entity,
     pub target_pos: [f32; 3],
     pub state_timer: f32,
 }
@@ -85,11 +148,18 @@ pub enum AiBehavior {
     Follow = 5,
 }
 
+impl AiBehavior {
+    /// Get the state ID used in `AiState`
+    pub const fn id(self) -> AiStateId {
+        self as AiStateId
+    }
+}
+
 impl Default for AiState {
     fn default() -> Self {
         Self {
-            state: AiBehavior::Idle,
-            target_entity: 0,
+            state: AiBehavior::Idle.id(),
+            target_entity: NO_TARGET,
             target_pos: [0.0; 3],
             state_timer: 0.0,
         }
@@ -98,6 +168,25 @@ impl Default for AiState {
 
 impl Component for AiState {
     fn type_id() -> ComponentId { 5 }
+    
+    fn serialize(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.state.to_le_bytes());
+        out.extend_from_slice(&self.target_entity.to_bits().to_le_bytes());
+        for c in self.target_pos {
+            out.extend_from_slice(&c.to_le_bytes());
+        }
+        out.extend_from_slice(&self.state_timer.to_le_bytes());
+    }
+    
+    fn deserialize(bytes: &[u8]) -> Result<Self, EcsError> {
+        let b: [u8; 26] = exact(bytes, "AI state")?;
+        Ok(Self {
+            state: u16::from_le_bytes([b[0], b[1]]),
+            target_entity: Entity::from_bits(u64::from_le_bytes(b[2..10].try_into().unwrap())),
+            target_pos: [f32_at(&b, 10), f32_at(&b, 14), f32_at(&b, 18)],
+            state_timer: f32_at(&b, 22),
+        })
+    }
 }
 
 /// Render component
@@ -125,11 +214,30 @@ impl Default for Render {
 
 impl Component for Render {
     fn type_id() -> ComponentId { 6 }
+    
+    fn serialize(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.model_id.to_le_bytes());
+        out.extend_from_slice(&self.texture_id.to_le_bytes());
+        out.extend_from_slice(&self.animation_frame.to_le_bytes());
+        out.extend_from_slice(&self.animation_speed.to_le_bytes());
+        out.push(self.visible as u8);
+    }
+    
+    fn deserialize(bytes: &[u8]) -> Result<Self, EcsError> {
+        let b: [u8; 15] = exact(bytes, "render")?;
+        Ok(Self {
+            model_id: u32::from_le_bytes(b[0..4].try_into().unwrap()),
+            texture_id: u32::from_le_bytes(b[4..8].try_into().unwrap()),
+            animation_frame: u16::from_le_bytes([b[8], b[9]]),
+            animation_speed: f32_at(&b, 10),
+            visible: flag(b[14], "render visible")?,
+        })
+    }
 }
 
 /// Physics component
 #[repr(C)]
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Pod, Zeroable)]
 pub struct Physics {
     pub mass: f32,
     pub drag: f32,
@@ -150,6 +258,14 @@ impl Default for Physics {
 
 impl Component for Physics {
     fn type_id() -> ComponentId { 7 }
+    
+    fn serialize(&self, out: &mut Vec<u8>) {
+        pod_serialize(self, out)
+    }
+    
+    fn deserialize(bytes: &[u8]) -> Result<Self, EcsError> {
+        pod_deserialize(bytes)
+    }
 }
 
 /// Inventory component
@@ -179,6 +295,27 @@ impl Default for Inventory {
 
 impl Component for Inventory {
     fn type_id() -> ComponentId { 8 }
+    
+    fn serialize(&self, out: &mut Vec<u8>) {
+        out.push(self.selected_slot);
+        for slot in &self.slots {
+            out.extend_from_slice(&slot.item_id.to_le_bytes());
+            out.push(slot.count);
+            out.extend_from_slice(&slot.damage.to_le_bytes());
+        }
+    }
+    
+    fn deserialize(bytes: &[u8]) -> Result<Self, EcsError> {
+        let Some((&selected_slot, slots)) = bytes.split_first().filter(|(_, s)| s.len() % 7 == 0) else {
+            return Err(EcsError::Corrupt(format!("inventory of {} bytes", bytes.len())));
+        };
+        let slots = slots.chunks_exact(7).map(|b| ItemStack {
+            item_id: u32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+            count: b[4],
+            damage: u16::from_le_bytes([b[5], b[6]]),
+        }).collect();
+        Ok(Self { slots, selected_slot })
+    }
 }
 
 /// Entity type marker
@@ -204,4 +341,125 @@ impl Default for EntityType {
 
 impl Component for EntityType {
     fn type_id() -> ComponentId { 9 }
+    
+    fn serialize(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.type_id.to_le_bytes());
+        out.extend_from_slice(&[self.is_player as u8, self.is_hostile as u8, self.is_passive as u8]);
+    }
+    
+    fn deserialize(bytes: &[u8]) -> Result<Self, EcsError> {
+        let b: [u8; 5] = exact(bytes, "entity type")?;
+        Ok(Self {
+            type_id: u16::from_le_bytes([b[0], b[1]]),
+            is_player: flag(b[2], "entity type is_player")?,
+            is_hostile: flag(b[3], "entity type is_hostile")?,
+            is_passive: flag(b[4], "entity type is_passive")?,
+        })
+    }
+}
+
+/// Custom mesh component (drawn instanced, grouped by mesh)
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct Renderable {
+    pub mesh_id: u32,
+    pub texture_id: u32,
+    pub scale: f32,
+}
+
+impl Default for Renderable {
+    fn default() -> Self {
+        Self {
+            mesh_id: 0,
+            texture_id: 0,
+            scale: 1.0,
+        }
+    }
+}
+
+impl Component for Renderable {
+    fn type_id() -> ComponentId { 10 }
+    
+    fn serialize(&self, out: &mut Vec<u8>) {
+        pod_serialize(self, out)
+    }
+    
+    fn deserialize(bytes: &[u8]) -> Result<Self, EcsError> {
+        pod_deserialize(bytes)
+    }
+}
+
+/// Keyframe animation playback (clips live in `AnimationSystem`)
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct Animator {
+    /// Clip playing
+    pub clip: ClipId,
+    /// Clip fading out (`NO_CLIP` when not crossfading)
+    pub fade_from: ClipId,
+    /// Playback position in `clip` (seconds)
+    pub time: f32,
+    /// Playback position in `fade_from` (seconds)
+    pub fade_from_time: f32,
+    /// Playback rate (1.0 = authored speed)
+    pub speed: f32,
+    /// Weight of `clip` against `fade_from`, 0..1
+    pub blend_weight: f32,
+    /// Crossfade length (seconds)
+    pub fade_duration: f32,
+}
+
+impl Animator {
+    /// Play a clip from the start
+    pub fn new(clip: ClipId) -> Self {
+        Self {
+            clip,
+            fade_from: NO_CLIP,
+            time: 0.0,
+            fade_from_time: 0.0,
+            speed: 1.0,
+            blend_weight: 1.0,
+            fade_duration: 0.0,
+        }
+    }
+
+    /// Switch to another clip, blending out of the current one over `duration`
+    pub fn crossfade(&mut self, clip: ClipId, duration: f32) {
+        if clip == self.clip {
+            return;
+        }
+        if duration <= 0.0 {
+            *self = Self { speed: self.speed, ..Self::new(clip) };
+            return;
+        }
+        self.fade_from = self.clip;
+        self.fade_from_time = self.time;
+        self.clip = clip;
+        self.time = 0.0;
+        self.blend_weight = 0.0;
+        self.fade_duration = duration;
+    }
+
+    /// Check if a crossfade is in progress
+    pub fn is_blending(&self) -> bool {
+        self.fade_from != NO_CLIP
+    }
+}
+
+impl Default for Animator {
+    fn default() -> Self {
+        Self::new(NO_CLIP)
+    }
+}
+
+impl Component for Animator {
+    fn type_id() -> ComponentId { 11 }
+    
+    fn serialize(&self, out: &mut Vec<u8>) {
+        pod_serialize(self, out)
+    }
+    
+    fn deserialize(bytes: &[u8]) -> Result<Self, EcsError> {
+        pod_deserialize(bytes)
+    }
 }
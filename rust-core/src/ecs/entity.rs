@@ -0,0 +1,212 @@
+//! # Entity Handles
+//!
+//! Entities are `(index, generation)` pairs. Despawning frees the index and
+//! bumps its generation, so a handle kept past despawn (an AI target, a
+//! render instance, a network interest set) no longer matches the slot and
+//! is rejected instead of silently aliasing whatever reuses it. Freed
+//! indices are reused oldest first; allocation depends only on the
+//! spawn/despawn sequence, so lockstep peers hand out identical handles.
+
+use std::collections::VecDeque;
+use std::fmt;
+
+/// Entity handle
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct Entity {
+    /// Slot in the allocator
+    pub index: u32,
+    /// Times the slot had been reused when this handle was issued
+    pub generation: u32,
+}
+
+impl Entity {
+    /// Handle that never refers to a live entity
+    pub const NULL: Entity = Entity { index: u32::MAX, generation: u32::MAX };
+
+    /// Create a handle
+    pub const fn new(index: u32, generation: u32) -> Self {
+        Self { index, generation }
+    }
+
+    /// Pack into 64 bits (generation high, index low) for FFI and Java
+    pub const fn to_bits(self) -> u64 {
+        ((self.generation as u64) << 32) | self.index as u64
+    }
+
+    /// Unpack from `to_bits`
+    pub const fn from_bits(bits: u64) -> Self {
+        Self { index: bits as u32, generation: (bits >> 32) as u32 }
+    }
+
+    /// Check if this is `Entity::NULL`
+    pub fn is_null(self) -> bool {
+        self == Entity::NULL
+    }
+}
+
+impl Default for Entity {
+    fn default() -> Self {
+        Entity::NULL
+    }
+}
+
+impl fmt::Debug for Entity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_null() {
+            write!(f, "Entity(null)")
+        } else {
+            write!(f, "Entity({}v{})", self.index, self.generation)
+        }
+    }
+}
+
+impl fmt::Display for Entity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// Hands out entity handles, recycling despawned indices
+#[derive(Debug, Clone, Default)]
+pub struct EntityAllocator {
+    /// Current generation per index
+    generations: Vec<u32>,
+    /// Whether each index is in use
+    alive: Vec<bool>,
+    /// Freed indices, oldest first
+    free: VecDeque<u32>,
+    /// Live entity count
+    live: u32,
+}
+
+impl EntityAllocator {
+    /// Create an empty allocator
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allocate a handle, reusing the oldest freed index if there is one
+    pub fn allocate(&mut self) -> Entity {
+        self.live += 1;
+        if let Some(index) = self.free.pop_front() {
+            self.alive[index as usize] = true;
+            return Entity::new(index, self.generations[index as usize]);
+        }
+        let index = self.generations.len() as u32;
+        assert!(index != u32::MAX, "entity index space exhausted");
+        self.generations.push(0);
+        self.alive.push(true);
+        Entity::new(index, 0)
+    }
+
+    /// Free a handle; false if it was already stale
+    pub fn free(&mut self, entity: Entity) -> bool {
+        if !self.is_alive(entity) {
+            return false;
+        }
+        let slot = entity.index as usize;
+        self.alive[slot] = false;
+        self.generations[slot] = self.generations[slot].wrapping_add(1);
+        self.free.push_back(entity.index);
+        self.live -= 1;
+        true
+    }
+
+    /// Check if a handle refers to a live entity
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        let slot = entity.index as usize;
+        self.alive.get(slot).copied().unwrap_or(false) && self.generations[slot] == entity.generation
+    }
+
+    /// Get the number of live entities
+    pub fn len(&self) -> u32 {
+        self.live
+    }
+
+    /// Check if no entity is alive
+    pub fn is_empty(&self) -> bool {
+        self.live == 0
+    }
+
+    /// Get per-index generations and liveness, and the free queue
+    pub(super) fn slots(&self) -> (&[u32], &[bool], &VecDeque<u32>) {
+        (&self.generations, &self.alive, &self.free)
+    }
+
+    /// Rebuild an allocator from `slots` output, checking it is consistent
+    pub(super) fn from_slots(generations: Vec<u32>, alive: Vec<bool>, free: VecDeque<u32>) -> Result<Self, String> {
+        if generations.len() != alive.len() {
+            return Err(format!("{} generations for {} slots", generations.len(), alive.len()));
+        }
+        let mut queued = vec![false; alive.len()];
+        for &index in &free {
+            let slot = index as usize;
+            if alive.get(slot) != Some(&false) || std::mem::replace(&mut queued[slot], true) {
+                return Err(format!("free index {} is live, out of range or repeated", index));
+            }
+        }
+        let live = alive.iter().filter(|&&a| a).count();
+        if live + free.len() != alive.len() {
+            return Err(format!("{} dead slots but {} free", alive.len() - live, free.len()));
+        }
+        Ok(Self { generations, alive, free, live: live as u32 })
+    }
+
+    /// Forget every entity (handles from before are not rejected afterwards)
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reused_index_rejects_stale_handle() {
+        let mut entities = EntityAllocator::new();
+        let a = entities.allocate();
+        let b = entities.allocate();
+        assert_eq!((a.index, b.index), (0, 1));
+
+        assert!(entities.free(a));
+        assert!(!entities.free(a));
+        assert!(!entities.is_alive(a));
+
+        // The freed index comes back with a new generation
+        let c = entities.allocate();
+        assert_eq!(c.index, a.index);
+        assert_eq!(c.generation, a.generation + 1);
+        assert!(entities.is_alive(c));
+        assert!(!entities.is_alive(a));
+        assert_eq!(entities.len(), 2);
+
+        assert_eq!(Entity::from_bits(c.to_bits()), c);
+        assert!(!entities.is_alive(Entity::NULL));
+    }
+
+    #[test]
+    fn test_world_rejects_despawned_handle() {
+        use crate::ecs::components::Position;
+        use crate::ecs::EcsWorld;
+
+        let mut world = EcsWorld::new();
+        let old = world.spawn();
+        world.add_component(old, Position { x: 1.0, y: 2.0, z: 3.0 });
+        assert!(world.despawn(old));
+        assert!(!world.despawn(old));
+
+        let new = world.spawn();
+        assert_eq!(new.index, old.index);
+        world.add_component(new, Position { x: 4.0, y: 5.0, z: 6.0 });
+
+        // The stale handle neither reads nor writes the new entity
+        assert!(world.get_component::<Position>(old).is_none());
+        world.add_component(old, Position { x: 9.0, y: 9.0, z: 9.0 });
+        assert_eq!(world.get_component::<Position>(new).map(|p| p.x), Some(4.0));
+        assert_eq!(world.query::<Position>().len(), 1);
+        assert!(world.is_alive(new) && !world.is_alive(old));
+        assert_eq!(world.entity_count(), 1);
+    }
+}
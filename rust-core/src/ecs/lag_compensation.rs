@@ -0,0 +1,122 @@
+//! Lag Compensation
+//!
+//! Rolling per-entity position history so the server can rewind entities
+//! to the tick a shot was fired for hit detection.
+
+use std::collections::{HashMap, VecDeque};
+use glam::Vec3;
+
+use super::Entity;
+
+/// Default history depth in ticks (1 second at 20 TPS)
+pub const DEFAULT_DEPTH: u64 = 20;
+
+/// Position history buffer
+pub struct LagCompensation {
+    /// Ticks of history kept
+    depth: u64,
+    /// (tick, position) samples per entity, oldest first
+    history: HashMap<Entity, VecDeque<(u64, Vec3)>>,
+}
+
+impl LagCompensation {
+    /// Create a buffer keeping `depth` ticks
+    pub fn new(depth: u64) -> Self {
+        Self {
+            depth: depth.max(1),
+            history: HashMap::new(),
+        }
+    }
+
+    /// Record an entity's position at a tick
+    pub fn record(&mut self, entity: Entity, tick: u64, position: Vec3) {
+        let samples = self.history.entry(entity).or_default();
+
+        match samples.back_mut() {
+            Some((last, pos)) if *last == tick => *pos = position,
+            Some((last, _)) if *last > tick => return, // Out of order, ignore
+            _ => samples.push_back((tick, position)),
+        }
+
+        let oldest = tick.saturating_sub(self.depth);
+        while samples.front().is_some_and(|(t, _)| *t < oldest) {
+            samples.pop_front();
+        }
+    }
+
+    /// Get an entity's position at a past tick, interpolating between samples
+    ///
+    /// Returns `None` for unknown entities or ticks older than the history.
+    /// Ticks newer than the last sample return the last known position.
+    pub fn position_at_tick(&self, entity: Entity, tick: u64) -> Option<Vec3> {
+        let samples = self.history.get(&entity)?;
+        let &(first_tick, first_pos) = samples.front()?;
+        if tick < first_tick {
+            return None;
+        }
+        if tick == first_tick {
+            return Some(first_pos);
+        }
+
+        let after = samples.partition_point(|(t, _)| *t < tick);
+        let Some(&(next_tick, next_pos)) = samples.get(after) else {
+            return samples.back().map(|(_, pos)| *pos);
+        };
+        if next_tick == tick {
+            return Some(next_pos);
+        }
+
+        let (prev_tick, prev_pos) = samples[after - 1];
+        let t = (tick - prev_tick) as f32 / (next_tick - prev_tick) as f32;
+        Some(prev_pos.lerp(next_pos, t))
+    }
+
+    /// Get an entity's most recent recorded position
+    pub fn latest(&self, entity: Entity) -> Option<Vec3> {
+        self.history.get(&entity)?.back().map(|(_, pos)| *pos)
+    }
+
+    /// Forget an entity
+    pub fn remove(&mut self, entity: Entity) {
+        self.history.remove(&entity);
+    }
+
+    /// Get history depth in ticks
+    pub fn depth(&self) -> u64 {
+        self.depth
+    }
+
+    /// Clear all history
+    pub fn clear(&mut self) {
+        self.history.clear();
+    }
+}
+
+impl Default for LagCompensation {
+    fn default() -> Self {
+        Self::new(DEFAULT_DEPTH)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interpolated_rewind() {
+        let mut lag = LagCompensation::new(5);
+        let (entity, other) = (Entity::new(7, 0), Entity::new(8, 0));
+        // Entity reported every other tick, moving +2 x per report
+        for (tick, x) in [(10, 0.0), (12, 2.0), (14, 4.0), (16, 6.0)] {
+            lag.record(entity, tick, Vec3::new(x, 64.0, 0.0));
+        }
+
+        assert_eq!(lag.position_at_tick(entity, 13), Some(Vec3::new(3.0, 64.0, 0.0)));
+        assert_eq!(lag.position_at_tick(entity, 12), Some(Vec3::new(2.0, 64.0, 0.0)));
+        assert_eq!(lag.position_at_tick(entity, 20), Some(Vec3::new(6.0, 64.0, 0.0)));
+
+        // Tick 10 fell out of the 5-tick window
+        assert_eq!(lag.position_at_tick(entity, 10), None);
+        assert_eq!(lag.position_at_tick(other, 13), None);
+    }
+}
@@ -6,28 +6,55 @@
 pub mod parallel;
 pub mod components;
 pub mod archetype;
+pub mod lag_compensation;
+pub mod ai;
+pub mod collision;
+pub mod animation;
+pub mod entity;
+pub mod snapshot;
 
 use std::sync::Arc;
 use std::collections::HashMap;
 use parking_lot::RwLock;
 use rayon::prelude::*;
+use glam::Vec3;
 
-/// Entity ID
-pub type EntityId = u32;
+use crate::profiling::spans;
+use crate::util::jobs::JobSystem;
+
+pub use lag_compensation::LagCompensation;
+pub use ai::{AiProfile, AiSystem};
+pub use animation::{AnimationSystem, BoneTransform, Keyframe};
+pub use entity::{Entity, EntityAllocator};
+pub use snapshot::EcsError;
+
+use snapshot::ComponentCodec;
 
 /// Component type ID
 pub type ComponentId = u16;
 
 /// ECS World - manages all entities and components
 pub struct EcsWorld {
-    /// Entity counter
-    next_entity: EntityId,
+    /// Live entity handles
+    entities: EntityAllocator,
+    /// Entities spawned through the engine API, by the caller's ID
+    external_ids: HashMap<u64, Entity>,
     /// Component storage by archetype
     archetypes: Vec<Archetype>,
     /// Entity to archetype mapping
-    entity_archetype: HashMap<EntityId, usize>,
-    /// Thread pool for parallel processing
-    thread_pool: rayon::ThreadPool,
+    entity_archetype: HashMap<Entity, usize>,
+    /// Snapshot serializers by component type
+    codecs: HashMap<ComponentId, ComponentCodec>,
+    /// Position history for hit detection rewind
+    lag_compensation: LagCompensation,
+    /// Behavior state machines per entity type
+    ai: AiSystem,
+    /// Keyframe clips and bone poses
+    animation: AnimationSystem,
+    /// Own worker pool (`None` to use the shared one)
+    jobs: Option<JobSystem>,
+    /// Process in a canonical order so runs are bit-identical (lockstep)
+    deterministic: bool,
     /// Statistics
     stats: EcsStats,
 }
@@ -37,7 +64,7 @@ pub struct Archetype {
     /// Component type IDs in this archetype
     component_types: Vec<ComponentId>,
     /// Entities in this archetype
-    entities: Vec<EntityId>,
+    entities: Vec<Entity>,
     /// Component data arrays (SOA layout)
     components: HashMap<ComponentId, ComponentArray>,
 }
@@ -63,39 +90,146 @@ pub struct EcsStats {
 }
 
 impl EcsWorld {
-    /// Create new ECS world
+    /// Create new ECS world on the shared job system
     pub fn new() -> Self {
-        let thread_pool = rayon::ThreadPoolBuilder::new()
-            .num_threads(num_cpus::get().saturating_sub(2).max(2))
-            .thread_name(|i| format!("libs-ecs-{}", i))
-            .build()
-            .expect("Failed to create thread pool");
-        
-        log::info!("ECS World initialized with {} threads", thread_pool.current_num_threads());
-        
-        Self {
-            next_entity: 0,
+        Self::with_jobs(None)
+    }
+    
+    /// Create new ECS world with its own pool of `threads` workers
+    ///
+    /// 0 picks the default count, which leaves cores for the render thread
+    /// and the OS.
+    pub fn with_threads(threads: usize) -> Result<Self, String> {
+        Ok(Self::with_jobs(Some(JobSystem::dedicated("libs-ecs", threads, false)?)))
+    }
+    
+    /// Create new ECS world with its own pool, pinning workers to cores
+    pub fn with_pinned_threads(threads: usize) -> Result<Self, String> {
+        Ok(Self::with_jobs(Some(JobSystem::dedicated("libs-ecs", threads, true)?)))
+    }
+    
+    fn with_jobs(jobs: Option<JobSystem>) -> Self {
+        let mut world = Self {
+            entities: EntityAllocator::new(),
+            external_ids: HashMap::new(),
             archetypes: Vec::new(),
             entity_archetype: HashMap::new(),
-            thread_pool,
+            codecs: HashMap::new(),
+            lag_compensation: LagCompensation::default(),
+            ai: AiSystem::new(),
+            animation: AnimationSystem::new(),
+            jobs,
+            deterministic: false,
             stats: EcsStats::default(),
-        }
+        };
+        world.register_component::<components::Position>();
+        world.register_component::<components::Velocity>();
+        world.register_component::<components::Health>();
+        world.register_component::<components::Collision>();
+        world.register_component::<components::AiState>();
+        world.register_component::<components::Render>();
+        world.register_component::<components::Physics>();
+        world.register_component::<components::Inventory>();
+        world.register_component::<components::EntityType>();
+        world.register_component::<components::Renderable>();
+        world.register_component::<components::Animator>();
+        log::info!("ECS World initialized with {} threads", world.thread_count());
+        world
+    }
+    
+    /// Get the worker pool ticks run on
+    pub fn jobs(&self) -> &JobSystem {
+        self.jobs.as_ref().unwrap_or_else(|| JobSystem::global())
+    }
+    
+    /// Get the number of worker threads ticks run on
+    pub fn thread_count(&self) -> usize {
+        self.jobs().thread_count()
+    }
+    
+    /// Enable deterministic mode
+    ///
+    /// Archetypes are then processed one after another, sorted by their
+    /// component set, and queries return entities in handle order. Results
+    /// no longer depend on thread scheduling or on the order archetypes were
+    /// created in (e.g. after a snapshot restore), at the cost of the
+    /// parallel speedup.
+    pub fn set_deterministic(&mut self, deterministic: bool) {
+        self.deterministic = deterministic;
+    }
+    
+    /// Check if deterministic mode is on
+    pub fn is_deterministic(&self) -> bool {
+        self.deterministic
     }
     
     /// Spawn new entity with components
-    pub fn spawn(&mut self) -> EntityId {
-        let id = self.next_entity;
-        self.next_entity += 1;
-        self.stats.total_entities += 1;
-        id
+    pub fn spawn(&mut self) -> Entity {
+        let entity = self.entities.allocate();
+        self.stats.total_entities = self.entities.len();
+        entity
+    }
+    
+    /// Despawn an entity and drop its components; false if the handle was stale
+    pub fn despawn(&mut self, entity: Entity) -> bool {
+        if let Some((idx, row)) = self.location(entity) {
+            for (type_id, bytes) in self.archetypes[idx].swap_remove(row) {
+                self.codecs[&type_id].drop_stored(&bytes);
+            }
+        }
+        if !self.entities.free(entity) {
+            return false;
+        }
+        self.entity_archetype.remove(&entity);
+        self.lag_compensation.remove(entity);
+        self.stats.total_entities = self.entities.len();
+        true
+    }
+    
+    /// Check if a handle refers to a live entity
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        self.entities.is_alive(entity)
     }
     
     /// Add component to entity
-    pub fn add_component<T: Component>(&mut self, entity: EntityId, component: T) {
+    ///
+    /// Adding a type the entity doesn't have yet moves it to the archetype
+    /// holding its old components plus the new one. Stale handles are ignored.
+    pub fn add_component<T: Component>(&mut self, entity: Entity, component: T) {
+        if !self.entities.is_alive(entity) {
+            log::debug!("ECS: ignoring component for stale {}", entity);
+            return;
+        }
         let type_id = T::type_id();
+        let size = std::mem::size_of::<T>();
+        // The stored bytes own the component from here on
+        let component = std::mem::ManuallyDrop::new(component);
+        let bytes = unsafe {
+            std::slice::from_raw_parts(&*component as *const T as *const u8, size)
+        };
+        let codec = *self.codecs.entry(type_id).or_insert_with(ComponentCodec::of::<T>);
+        
+        let location = self.location(entity);
+        if let Some((idx, row)) = location {
+            // Re-attaching replaces (and drops) the component in place
+            if let Some(array) = self.archetypes[idx].components.get_mut(&type_id) {
+                let stored = &mut array.data[row * size..(row + 1) * size];
+                codec.drop_stored(stored);
+                stored.copy_from_slice(bytes);
+                return;
+            }
+        }
         
         // Find or create archetype
-        let archetype_idx = self.find_or_create_archetype(&[type_id]);
+        let mut types = location
+            .map(|(idx, _)| self.archetypes[idx].component_types.clone())
+            .unwrap_or_default();
+        types.push(type_id);
+        types.sort_unstable();
+        let archetype_idx = self.find_or_create_archetype(&types);
+        
+        // Carry the entity's other components over
+        let moved = location.map(|(idx, row)| self.archetypes[idx].swap_remove(row)).unwrap_or_default();
         
         // Store entity mapping
         self.entity_archetype.insert(entity, archetype_idx);
@@ -103,17 +237,60 @@ impl EcsWorld {
         // Add component data
         let archetype = &mut self.archetypes[archetype_idx];
         archetype.entities.push(entity);
-        
+        for (other_type, other_bytes) in moved {
+            if let Some(array) = archetype.components.get_mut(&other_type) {
+                array.push(&other_bytes);
+            }
+        }
         if let Some(array) = archetype.components.get_mut(&type_id) {
-            let bytes = unsafe {
-                std::slice::from_raw_parts(
-                    &component as *const T as *const u8,
-                    std::mem::size_of::<T>()
-                )
+            array.push(bytes);
+        }
+    }
+    
+    /// Get an entity's component of type `T`
+    pub fn get_component<T: Component + Copy>(&self, entity: Entity) -> Option<T> {
+        let (idx, row) = self.location(entity)?;
+        let array = self.archetypes[idx].components.get(&T::type_id())?;
+        let size = std::mem::size_of::<T>();
+        let bytes = array.data.get(row * size..(row + 1) * size)?;
+        Some(unsafe { std::ptr::read_unaligned(bytes.as_ptr() as *const T) })
+    }
+    
+    /// Archetype index and row of a live entity that has components
+    fn location(&self, entity: Entity) -> Option<(usize, usize)> {
+        if !self.entities.is_alive(entity) {
+            return None;
+        }
+        let idx = *self.entity_archetype.get(&entity)?;
+        let row = self.archetypes.get(idx)?.entities.iter().position(|&e| e == entity)?;
+        Some((idx, row))
+    }
+    
+    /// Get every live entity's component of type `T`
+    pub fn query<T: Component + Copy>(&self) -> Vec<(Entity, T)> {
+        let type_id = T::type_id();
+        let size = std::mem::size_of::<T>();
+        let mut result = Vec::new();
+        
+        for (idx, archetype) in self.archetypes.iter().enumerate() {
+            let Some(array) = archetype.components.get(&type_id) else {
+                continue;
             };
-            array.data.extend_from_slice(bytes);
-            array.count += 1;
+            for (i, &entity) in archetype.entities.iter().enumerate().take(array.count) {
+                if self.entity_archetype.get(&entity) != Some(&idx) {
+                    continue;
+                }
+                let component = unsafe {
+                    std::ptr::read_unaligned(array.data[i * size..].as_ptr() as *const T)
+                };
+                result.push((entity, component));
+            }
         }
+        
+        if self.deterministic {
+            result.sort_unstable_by_key(|&(entity, _)| entity);
+        }
+        result
     }
     
     /// Find or create archetype for component types
@@ -146,15 +323,39 @@ impl EcsWorld {
     
     /// Run parallel tick on all entities
     pub fn parallel_tick(&mut self, delta_time: f32) {
+        let _span = tracing::debug_span!(
+            spans::ECS_TICK,
+            entities = self.stats.total_entities,
+            archetypes = self.archetypes.len(),
+        ).entered();
         let start = std::time::Instant::now();
         
-        // Process each archetype in parallel
-        self.thread_pool.install(|| {
-            self.archetypes.par_iter_mut().for_each(|archetype| {
-                // Process entities in this archetype
-                Self::process_archetype(archetype, delta_time);
+        // Behaviors pick velocities before physics integrates them
+        let mut ai = std::mem::take(&mut self.ai);
+        ai.run(self, delta_time);
+        self.ai = ai;
+        
+        let mut animation = std::mem::take(&mut self.animation);
+        animation.run(self, delta_time);
+        self.animation = animation;
+        
+        if self.deterministic {
+            // Canonical order, on this thread
+            let mut order: Vec<usize> = (0..self.archetypes.len()).collect();
+            order.sort_by(|&a, &b| self.archetypes[a].component_types.cmp(&self.archetypes[b].component_types));
+            for idx in order {
+                Self::process_archetype(&mut self.archetypes[idx], delta_time);
+            }
+        } else {
+            // Process each archetype in parallel
+            let jobs = self.jobs.as_ref().unwrap_or_else(|| JobSystem::global());
+            jobs.install(|| {
+                self.archetypes.par_iter_mut().for_each(|archetype| {
+                    // Process entities in this archetype
+                    Self::process_archetype(archetype, delta_time);
+                });
             });
-        });
+        }
         
         self.stats.ticks_processed += 1;
         self.stats.avg_tick_time_us = start.elapsed().as_micros() as f32;
@@ -165,8 +366,10 @@ impl EcsWorld {
         // Check for Position + Velocity components for physics
         let has_position = archetype.component_types.contains(&components::Position::type_id());
         let has_velocity = archetype.component_types.contains(&components::Velocity::type_id());
+        // Entities with a collision box move in `tick_physics`
+        let has_collision = archetype.component_types.contains(&components::Collision::type_id());
         
-        if has_position && has_velocity {
+        if has_position && has_velocity && !has_collision {
             // Get raw pointers before any borrowing
             let pos_type = components::Position::type_id();
             let vel_type = components::Velocity::type_id();
@@ -211,30 +414,120 @@ impl EcsWorld {
         self.stats.clone()
     }
     
-    /// Spawn entity (API compatibility with engine)
+    /// Spawn entity (API compatibility with engine); returns the handle bits
+    ///
+    /// `entity_id` is the caller's (Java) ID, which later updates and
+    /// despawns refer to. If the caller reuses an ID, the entity previously
+    /// spawned under it is despawned first, so handles to it go stale.
     pub fn spawn_entity(&mut self, entity_id: i32, _entity_type: i32, x: f64, y: f64, z: f64) -> i64 {
-        // Store entity mapping
-        let id = entity_id as u32;
-        self.entity_archetype.insert(id, 0);
-        self.stats.total_entities += 1;
-        log::trace!("ECS: Spawned entity {} at ({}, {}, {})", id, x, y, z);
-        entity_id as i64
+        let external = entity_id as u64;
+        if let Some(previous) = self.external_ids.remove(&external) {
+            self.despawn(previous);
+        }
+        let entity = self.spawn();
+        self.external_ids.insert(external, entity);
+        self.add_component(entity, components::Position { x, y, z });
+        self.add_component(entity, components::Velocity::default());
+        self.lag_compensation.record(entity, self.stats.ticks_processed, Vec3::new(x as f32, y as f32, z as f32));
+        log::trace!("ECS: Spawned entity {} as {} at ({}, {}, {})", entity_id, entity, x, y, z);
+        entity.to_bits() as i64
     }
     
-    /// Despawn entity (API compatibility with engine)
+    /// Despawn entity by the caller's ID (API compatibility with engine)
     pub fn despawn_entity(&mut self, entity_id: u64) {
-        let id = entity_id as u32;
-        self.entity_archetype.remove(&id);
-        self.stats.total_entities = self.stats.total_entities.saturating_sub(1);
-        log::trace!("ECS: Despawned entity {}", id);
+        if let Some(entity) = self.external_ids.remove(&entity_id) {
+            self.despawn(entity);
+            log::trace!("ECS: Despawned entity {} ({})", entity_id, entity);
+        }
     }
     
-    /// Update entity (API compatibility with engine)
+    /// Update entity by the caller's ID (API compatibility with engine)
     pub fn update_entity(&mut self, entity_id: u64, x: f64, y: f64, z: f64, _yaw: f32, _pitch: f32) {
-        // Position updates happen through component system
+        let Some(&entity) = self.external_ids.get(&entity_id) else {
+            return;
+        };
+        self.add_component(entity, components::Position { x, y, z });
+        let tick = self.stats.ticks_processed;
+        self.lag_compensation.record(entity, tick, Vec3::new(x as f32, y as f32, z as f32));
         log::trace!("ECS: Updated entity {} to ({}, {}, {})", entity_id, x, y, z);
     }
     
+    /// Get the entity spawned under a caller's ID
+    pub fn external_entity(&self, entity_id: u64) -> Option<Entity> {
+        self.external_ids.get(&entity_id).copied()
+    }
+    
+    /// Get an entity's position as of a past tick (interpolated)
+    pub fn position_at_tick(&self, entity: Entity, tick: u64) -> Option<Vec3> {
+        self.lag_compensation.position_at_tick(entity, tick)
+    }
+    
+    /// Get an entity's latest known position
+    pub fn position(&self, entity: Entity) -> Option<Vec3> {
+        self.lag_compensation.latest(entity)
+    }
+    
+    /// Get the behavior system
+    pub fn ai(&self) -> &AiSystem {
+        &self.ai
+    }
+    
+    /// Get the behavior system (to register profiles)
+    pub fn ai_mut(&mut self) -> &mut AiSystem {
+        &mut self.ai
+    }
+    
+    /// Get the animation system
+    pub fn animation(&self) -> &AnimationSystem {
+        &self.animation
+    }
+    
+    /// Get the animation system (to register clips)
+    pub fn animation_mut(&mut self) -> &mut AnimationSystem {
+        &mut self.animation
+    }
+    
+    /// Set how many ticks of position history are kept
+    pub fn set_lag_compensation_depth(&mut self, depth: u64) {
+        self.lag_compensation = LagCompensation::new(depth);
+    }
+    
+    /// Hash of entity set, positions and velocities (lockstep verification)
+    pub fn state_hash(&self) -> u64 {
+        let mut ids: Vec<Entity> = self.external_ids.values().chain(self.entity_archetype.keys()).copied().collect();
+        ids.sort_unstable();
+        ids.dedup();
+        let mut positions = self.query::<components::Position>();
+        positions.sort_unstable_by_key(|&(entity, _)| entity);
+        let mut velocities = self.query::<components::Velocity>();
+        velocities.sort_unstable_by_key(|&(entity, _)| entity);
+        
+        let mut bytes = Vec::with_capacity(ids.len() * 8 + positions.len() * 32 + velocities.len() * 20 + 8);
+        bytes.extend_from_slice(&self.stats.ticks_processed.to_le_bytes());
+        for id in ids {
+            bytes.extend_from_slice(&id.to_bits().to_le_bytes());
+        }
+        for (entity, pos) in positions {
+            bytes.extend_from_slice(&entity.to_bits().to_le_bytes());
+            for c in [pos.x, pos.y, pos.z] {
+                bytes.extend_from_slice(&c.to_bits().to_le_bytes());
+            }
+        }
+        for (entity, vel) in velocities {
+            bytes.extend_from_slice(&entity.to_bits().to_le_bytes());
+            for c in [vel.x, vel.y, vel.z] {
+                bytes.extend_from_slice(&c.to_bits().to_le_bytes());
+            }
+        }
+        
+        crate::util::hash::fnv1a(&bytes)
+    }
+    
+    /// Get current tick number
+    pub fn current_tick(&self) -> u64 {
+        self.stats.ticks_processed
+    }
+    
     /// Get entity count (API compatibility with engine)
     pub fn entity_count(&self) -> u32 {
         self.stats.total_entities
@@ -245,18 +538,98 @@ impl EcsWorld {
         self.parallel_tick(delta_time);
     }
     
-    /// Clear all entities
+    /// Clear all entities, dropping their components
     pub fn clear(&mut self) {
+        for archetype in &self.archetypes {
+            archetype.drop_components(&self.codecs);
+        }
         self.archetypes.clear();
         self.entity_archetype.clear();
-        self.next_entity = 0;
+        self.lag_compensation.clear();
+        self.entities.clear();
+        self.external_ids.clear();
         self.stats = EcsStats::default();
     }
 }
 
+impl Drop for EcsWorld {
+    fn drop(&mut self) {
+        for archetype in &self.archetypes {
+            archetype.drop_components(&self.codecs);
+        }
+    }
+}
+
+impl Archetype {
+    /// Drop every stored component (the rows must not be read after)
+    fn drop_components(&self, codecs: &HashMap<ComponentId, ComponentCodec>) {
+        for (type_id, array) in &self.components {
+            let size = array.component_size;
+            for row in 0..array.count {
+                codecs[type_id].drop_stored(&array.data[row * size..(row + 1) * size]);
+            }
+        }
+    }
+    
+    /// Remove an entity's row, returning its component bytes by type
+    ///
+    /// The last row moves into the gap, as in `Vec::swap_remove`; the
+    /// caller owns (and must move or drop) the returned components.
+    fn swap_remove(&mut self, row: usize) -> Vec<(ComponentId, Vec<u8>)> {
+        self.entities.swap_remove(row);
+        self.components
+            .iter_mut()
+            .map(|(&type_id, array)| (type_id, array.swap_remove(row)))
+            .collect()
+    }
+}
+
+impl ComponentArray {
+    /// Append one component's bytes
+    fn push(&mut self, bytes: &[u8]) {
+        self.component_size = bytes.len();
+        self.data.extend_from_slice(bytes);
+        self.count += 1;
+    }
+    
+    /// Remove a row, moving the last row into its place
+    fn swap_remove(&mut self, row: usize) -> Vec<u8> {
+        let size = self.component_size;
+        let last = self.count - 1;
+        let removed = self.data[row * size..(row + 1) * size].to_vec();
+        if row != last {
+            self.data.copy_within(last * size..(last + 1) * size, row * size);
+        }
+        self.data.truncate(last * size);
+        self.count = last;
+        removed
+    }
+}
+
 /// Component trait
 pub trait Component: Sized + Send + Sync + 'static {
     fn type_id() -> ComponentId;
+    
+    /// Append this component's snapshot bytes
+    ///
+    /// `bytemuck::Pod` components can use `pod_serialize`; anything else
+    /// (bools, padding, pointers) needs its own encoding.
+    fn serialize(&self, out: &mut Vec<u8>);
+    
+    /// Rebuild a component from `serialize` output, rejecting invalid bytes
+    fn deserialize(bytes: &[u8]) -> Result<Self, EcsError>;
+}
+
+/// Append a plain-data component's in-memory bytes
+pub fn pod_serialize<T: bytemuck::Pod>(component: &T, out: &mut Vec<u8>) {
+    out.extend_from_slice(bytemuck::bytes_of(component));
+}
+
+/// Rebuild a plain-data component from `pod_serialize` output
+pub fn pod_deserialize<T: Component + bytemuck::Pod>(bytes: &[u8]) -> Result<T, EcsError> {
+    bytemuck::try_pod_read_unaligned(bytes).map_err(|_| EcsError::Corrupt(format!(
+        "component {} is {} bytes, expected {}", T::type_id(), bytes.len(), std::mem::size_of::<T>()
+    )))
 }
 
 /// Parallel ticker for chunk-based entity processing
@@ -269,7 +642,7 @@ pub struct ParallelTicker {
 
 /// Chunk of entities that can be processed together
 pub struct EntityChunk {
-    pub entities: Vec<EntityId>,
+    pub entities: Vec<Entity>,
     pub region: [i32; 3],
 }
 
@@ -283,10 +656,10 @@ impl ParallelTicker {
     }
     
     /// Group entities by spatial region
-    pub fn group_by_region(&mut self, positions: &[(EntityId, f64, f64, f64)]) {
+    pub fn group_by_region(&mut self, positions: &[(Entity, f64, f64, f64)]) {
         self.chunks.clear();
         
-        let mut region_map: HashMap<[i32; 3], Vec<EntityId>> = HashMap::new();
+        let mut region_map: HashMap<[i32; 3], Vec<Entity>> = HashMap::new();
         
         for &(entity, x, y, z) in positions {
             // 16-block regions (chunk-sized)
@@ -325,3 +698,109 @@ impl ParallelTicker {
         dx > 1 || dy > 1 || dz > 1
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use components::{Health, Position, Velocity};
+
+    /// World with the same entities, built with components attached in a
+    /// different order so its archetypes are created in a different order
+    fn build(health_first: bool) -> (EcsWorld, Vec<Entity>) {
+        let mut world = EcsWorld::new();
+        world.set_deterministic(true);
+        let mut entities = Vec::new();
+        for i in 0..64 {
+            let entity = world.spawn();
+            let health = Health { current: 20.0, max: 20.0, regeneration: 0.0 };
+            if health_first && i % 2 == 0 {
+                world.add_component(entity, health);
+            }
+            world.add_component(entity, Position { x: i as f64 * 0.1, y: 64.0, z: -(i as f64) / 3.0 });
+            world.add_component(entity, Velocity { x: 0.3 + i as f32 * 0.01, y: -0.7, z: 1.0 / (i + 1) as f32 });
+            if !health_first && i % 2 == 0 {
+                world.add_component(entity, health);
+            }
+            entities.push(entity);
+        }
+        (world, entities)
+    }
+
+    #[test]
+    fn test_deterministic_ticks_are_bit_identical() {
+        let (mut a, entities) = build(false);
+        let (mut b, _) = build(true);
+        assert!(a.is_deterministic());
+        for _ in 0..100 {
+            a.parallel_tick(0.05);
+            b.parallel_tick(0.05);
+        }
+
+        let bits = |world: &EcsWorld| -> Vec<(Entity, [u64; 3])> {
+            world.query::<Position>().into_iter().map(|(e, p)| (e, [p.x.to_bits(), p.y.to_bits(), p.z.to_bits()])).collect()
+        };
+        // Same entities, same order, same bits despite different archetype layouts
+        assert_eq!(bits(&a), bits(&b));
+        assert_eq!(bits(&a).iter().map(|(e, _)| *e).collect::<Vec<_>>(), entities);
+        assert!(a.get_component::<Position>(entities[1]).unwrap().y < 64.0);
+    }
+
+    /// Live `Tracked` values (only `test_removed_components_are_dropped` makes them)
+    static TRACKED_LIVE: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+    /// Component owning a resource, counting live instances
+    struct Tracked(Vec<u8>);
+
+    impl Tracked {
+        fn new(len: usize) -> Self {
+            TRACKED_LIVE.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Tracked(vec![7; len])
+        }
+    }
+
+    impl Drop for Tracked {
+        fn drop(&mut self) {
+            TRACKED_LIVE.fetch_sub(1, std::sync::atomic::Ordering::SeqCst);
+        }
+    }
+
+    impl Component for Tracked {
+        fn type_id() -> ComponentId { 1001 }
+
+        fn serialize(&self, out: &mut Vec<u8>) {
+            out.extend_from_slice(&self.0);
+        }
+
+        fn deserialize(bytes: &[u8]) -> Result<Self, EcsError> {
+            Ok(Tracked::new(bytes.len()))
+        }
+    }
+
+    #[test]
+    fn test_removed_components_are_dropped() {
+        let live = || TRACKED_LIVE.load(std::sync::atomic::Ordering::SeqCst);
+        let mut world = EcsWorld::new();
+        let entities: Vec<Entity> = (0..4).map(|_| world.spawn()).collect();
+        for &entity in &entities {
+            world.add_component(entity, Tracked::new(3));
+        }
+        world.add_component(entities[0], Position::default()); // Moves archetype
+        assert_eq!(live(), 4);
+
+        world.add_component(entities[1], Tracked::new(5)); // Replaces in place
+        assert_eq!(live(), 4);
+        assert!(world.despawn(entities[2]));
+        assert_eq!(live(), 3);
+
+        let snapshot = world.snapshot();
+        world.clear();
+        assert_eq!(live(), 0);
+        world.restore(&snapshot).unwrap();
+        assert_eq!(live(), 3);
+        // A rejected snapshot drops what it decoded and leaves the world alone
+        assert!(world.restore(&snapshot[..snapshot.len() - 1]).is_err());
+        assert_eq!(live(), 3);
+        drop(world);
+        assert_eq!(live(), 0);
+    }
+}
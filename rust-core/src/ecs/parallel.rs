@@ -9,6 +9,8 @@ use parking_lot::{RwLock, Mutex};
 use rayon::prelude::*;
 use std::collections::{HashMap, HashSet};
 
+use crate::util::jobs::JobSystem;
+
 /// Entity ID type
 pub type EntityId = u64;
 
@@ -143,7 +145,7 @@ pub struct ParallelStats {
 
 impl ParallelScheduler {
     pub fn new() -> Self {
-        let thread_count = num_cpus::get().saturating_sub(2).max(1);
+        let thread_count = JobSystem::global().thread_count();
         
         log::info!("Creating ParallelScheduler with {} worker threads", thread_count);
         
@@ -160,13 +162,6 @@ impl ParallelScheduler {
     
     /// Initialize the scheduler
     pub fn initialize(&mut self) {
-        // Initialize rayon thread pool
-        rayon::ThreadPoolBuilder::new()
-            .num_threads(self.thread_count)
-            .thread_name(|i| format!("libs-ecs-{}", i))
-            .build_global()
-            .ok();
-        
         self.initialized = true;
         log::info!("ParallelScheduler initialized");
     }
@@ -329,8 +324,8 @@ impl ParallelScheduler {
             
             if can_parallel && batch_entities.len() > 1 {
                 // Parallel execution
-                batch_entities.par_iter().for_each(|&entity_id| {
-                    tick_fn(entity_id, delta_time);
+                JobSystem::global().parallel_for(0..batch_entities.len(), 16, |i| {
+                    tick_fn(batch_entities[i], delta_time);
                 });
                 parallel_count += batch_entities.len() as u64;
             } else {
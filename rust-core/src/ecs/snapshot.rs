@@ -0,0 +1,416 @@
+//! # World Snapshots
+//!
+//! Byte format for saving an `EcsWorld` or transferring it to another
+//! server. Component arrays hold raw `T` bytes, so each type gets a
+//! `ComponentCodec` (registered when first added, or up front with
+//! `EcsWorld::register_component`) that goes through `Component::serialize`
+//! and `Component::deserialize`. The codec also drops stored values, since
+//! the bytes own whatever the component holds (an `Inventory`'s slots).
+//!
+//! Layout (little-endian):
+//! - magic `LECS`, `u32` version, `u64` tick
+//! - allocator: `u32` slot count, per slot `u32` generation + `u8` alive,
+//!   then `u32` free count + `u32` indices oldest first
+//! - external IDs: `u32` count, per entry `u64` ID + `u64` entity bits
+//! - archetypes: `u32` count, per archetype `u16` type count + `u16` type
+//!   IDs, `u32` entity count + `u64` entity bits, then per type per entity
+//!   `u32` length + component bytes
+//!
+//! Position history for lag compensation is not saved; it refills as
+//! entities move.
+
+use std::collections::{HashMap, VecDeque};
+
+use super::{Archetype, Component, ComponentArray, ComponentId, EcsStats, EcsWorld, Entity, EntityAllocator};
+
+/// Snapshot magic
+const SNAPSHOT_MAGIC: [u8; 4] = *b"LECS";
+
+/// Current snapshot format version
+pub const SNAPSHOT_VERSION: u32 = 2;
+
+/// ECS snapshot errors
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EcsError {
+    BadMagic,
+    UnsupportedVersion(u32),
+    UnknownComponent(ComponentId),
+    Corrupt(String),
+}
+
+impl std::fmt::Display for EcsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::BadMagic => write!(f, "Not an ECS snapshot"),
+            Self::UnsupportedVersion(v) => write!(f, "Unsupported snapshot version {} (expected {})", v, SNAPSHOT_VERSION),
+            Self::UnknownComponent(id) => write!(f, "No serializer registered for component {}", id),
+            Self::Corrupt(e) => write!(f, "Corrupt snapshot: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for EcsError {}
+
+/// Type-erased (de)serializer and destructor for one component type's stored bytes
+#[derive(Clone, Copy)]
+pub(crate) struct ComponentCodec {
+    encode: fn(&[u8], &mut Vec<u8>),
+    decode: fn(&[u8]) -> Result<Vec<u8>, EcsError>,
+    drop: fn(&[u8]),
+}
+
+impl ComponentCodec {
+    /// Codec for `T`
+    pub(crate) fn of<T: Component>() -> Self {
+        Self {
+            encode: encode_component::<T>,
+            decode: decode_component::<T>,
+            drop: drop_component::<T>,
+        }
+    }
+
+    /// Drop the component stored in `stored` (the bytes must not be used after)
+    pub(crate) fn drop_stored(&self, stored: &[u8]) {
+        (self.drop)(stored)
+    }
+}
+
+fn encode_component<T: Component>(stored: &[u8], out: &mut Vec<u8>) {
+    // The array owns this copy, so it must not be dropped here
+    let component = std::mem::ManuallyDrop::new(unsafe {
+        std::ptr::read_unaligned(stored.as_ptr() as *const T)
+    });
+    component.serialize(out);
+}
+
+fn drop_component<T: Component>(stored: &[u8]) {
+    if std::mem::needs_drop::<T>() {
+        // Read out first: the bytes carry no alignment
+        drop(unsafe { std::ptr::read_unaligned(stored.as_ptr() as *const T) });
+    }
+}
+
+fn decode_component<T: Component>(bytes: &[u8]) -> Result<Vec<u8>, EcsError> {
+    // Ownership moves into the returned bytes, as in `add_component`
+    let component = std::mem::ManuallyDrop::new(T::deserialize(bytes)?);
+    let raw = unsafe {
+        std::slice::from_raw_parts(&*component as *const T as *const u8, std::mem::size_of::<T>())
+    };
+    Ok(raw.to_vec())
+}
+
+/// Bounds-checked little-endian reader
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], EcsError> {
+        let end = self.pos.checked_add(len)
+            .filter(|&end| end <= self.data.len())
+            .ok_or_else(|| EcsError::Corrupt(format!("truncated at byte {}", self.pos)))?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, EcsError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, EcsError> {
+        let b = self.take(2)?;
+        Ok(u16::from_le_bytes([b[0], b[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, EcsError> {
+        let b = self.take(4)?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn u64(&mut self) -> Result<u64, EcsError> {
+        let b = self.take(8)?;
+        Ok(u64::from_le_bytes(b.try_into().unwrap()))
+    }
+
+    /// Read a `u32` count, rejecting counts the remaining bytes can't hold
+    fn count(&mut self, min_item_size: usize) -> Result<usize, EcsError> {
+        let count = self.u32()? as usize;
+        if count.saturating_mul(min_item_size) > self.data.len() - self.pos {
+            return Err(EcsError::Corrupt(format!("count {} at byte {} overruns the snapshot", count, self.pos - 4)));
+        }
+        Ok(count)
+    }
+}
+
+impl EcsWorld {
+    /// Register a component type so snapshots containing it can be restored
+    ///
+    /// Built-in components are registered already, and adding a component
+    /// registers its type, so this is only needed before restoring custom
+    /// components into a fresh world.
+    pub fn register_component<T: Component>(&mut self) {
+        self.codecs.insert(T::type_id(), ComponentCodec::of::<T>());
+    }
+
+    /// Serialize entities, the allocator and all components
+    pub fn snapshot(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(&SNAPSHOT_MAGIC);
+        out.extend_from_slice(&SNAPSHOT_VERSION.to_le_bytes());
+        out.extend_from_slice(&self.stats.ticks_processed.to_le_bytes());
+
+        let (generations, alive, free) = self.entities.slots();
+        out.extend_from_slice(&(generations.len() as u32).to_le_bytes());
+        for (&generation, &live) in generations.iter().zip(alive) {
+            out.extend_from_slice(&generation.to_le_bytes());
+            out.push(live as u8);
+        }
+        out.extend_from_slice(&(free.len() as u32).to_le_bytes());
+        for &index in free {
+            out.extend_from_slice(&index.to_le_bytes());
+        }
+
+        let mut external: Vec<(u64, Entity)> = self.external_ids.iter().map(|(&id, &e)| (id, e)).collect();
+        external.sort_unstable();
+        out.extend_from_slice(&(external.len() as u32).to_le_bytes());
+        for (id, entity) in external {
+            out.extend_from_slice(&id.to_le_bytes());
+            out.extend_from_slice(&entity.to_bits().to_le_bytes());
+        }
+
+        out.extend_from_slice(&(self.archetypes.len() as u32).to_le_bytes());
+        let mut component = Vec::new();
+        for archetype in &self.archetypes {
+            out.extend_from_slice(&(archetype.component_types.len() as u16).to_le_bytes());
+            for &type_id in &archetype.component_types {
+                out.extend_from_slice(&type_id.to_le_bytes());
+            }
+            out.extend_from_slice(&(archetype.entities.len() as u32).to_le_bytes());
+            for &entity in &archetype.entities {
+                out.extend_from_slice(&entity.to_bits().to_le_bytes());
+            }
+            for type_id in &archetype.component_types {
+                let array = &archetype.components[type_id];
+                // Every stored type went through `add_component`, which registers it
+                let codec = self.codecs[type_id];
+                for row in 0..array.count {
+                    let size = array.component_size;
+                    component.clear();
+                    (codec.encode)(&array.data[row * size..(row + 1) * size], &mut component);
+                    out.extend_from_slice(&(component.len() as u32).to_le_bytes());
+                    out.extend_from_slice(&component);
+                }
+            }
+        }
+        out
+    }
+
+    /// Replace the world's entities and components with a snapshot
+    ///
+    /// The world is left untouched if the snapshot is rejected.
+    pub fn restore(&mut self, bytes: &[u8]) -> Result<(), EcsError> {
+        let mut reader = Reader::new(bytes);
+        if reader.take(4).map_err(|_| EcsError::BadMagic)? != SNAPSHOT_MAGIC {
+            return Err(EcsError::BadMagic);
+        }
+        let version = reader.u32()?;
+        if version != SNAPSHOT_VERSION {
+            return Err(EcsError::UnsupportedVersion(version));
+        }
+        let tick = reader.u64()?;
+
+        let slots = reader.count(5)?;
+        let mut generations = Vec::with_capacity(slots);
+        let mut alive = Vec::with_capacity(slots);
+        for _ in 0..slots {
+            generations.push(reader.u32()?);
+            alive.push(reader.u8()? != 0);
+        }
+        let free_count = reader.count(4)?;
+        let mut free = VecDeque::with_capacity(free_count);
+        for _ in 0..free_count {
+            free.push_back(reader.u32()?);
+        }
+        let entities = EntityAllocator::from_slots(generations, alive, free).map_err(EcsError::Corrupt)?;
+
+        let external_count = reader.count(16)?;
+        let mut external_ids = HashMap::with_capacity(external_count);
+        for _ in 0..external_count {
+            let id = reader.u64()?;
+            let entity = Entity::from_bits(reader.u64()?);
+            if !entities.is_alive(entity) {
+                return Err(EcsError::Corrupt(format!("external ID {} maps to dead {}", id, entity)));
+            }
+            external_ids.insert(id, entity);
+        }
+
+        let archetype_count = reader.count(6)?;
+        // Decoded components are dropped again if the snapshot is rejected
+        let mut decoded = Decoded { codecs: &self.codecs, archetypes: Vec::with_capacity(archetype_count) };
+        let mut entity_archetype = HashMap::new();
+        for idx in 0..archetype_count {
+            let type_count = reader.u16()? as usize;
+            let mut component_types = Vec::with_capacity(type_count);
+            for _ in 0..type_count {
+                component_types.push(reader.u16()?);
+            }
+            if !component_types.windows(2).all(|w| w[0] < w[1]) {
+                return Err(EcsError::Corrupt(format!("archetype {} types are not sorted and unique", idx)));
+            }
+
+            let entity_count = reader.count(8)?;
+            let mut archetype_entities = Vec::with_capacity(entity_count);
+            for _ in 0..entity_count {
+                let entity = Entity::from_bits(reader.u64()?);
+                if !entities.is_alive(entity) || entity_archetype.insert(entity, idx).is_some() {
+                    return Err(EcsError::Corrupt(format!("{} is dead or in two archetypes", entity)));
+                }
+                archetype_entities.push(entity);
+            }
+
+            decoded.archetypes.push(Archetype {
+                component_types: component_types.clone(),
+                entities: archetype_entities,
+                components: HashMap::with_capacity(type_count),
+            });
+            for &type_id in &component_types {
+                let codec = *self.codecs.get(&type_id).ok_or(EcsError::UnknownComponent(type_id))?;
+                let archetype = decoded.archetypes.last_mut().unwrap();
+                let array = archetype.components.entry(type_id).or_insert(ComponentArray {
+                    data: Vec::new(),
+                    component_size: 0,
+                    count: 0,
+                });
+                for _ in 0..entity_count {
+                    let len = reader.u32()? as usize;
+                    array.push(&(codec.decode)(reader.take(len)?)?);
+                }
+            }
+        }
+        if reader.pos != bytes.len() {
+            return Err(EcsError::Corrupt(format!("{} trailing bytes", bytes.len() - reader.pos)));
+        }
+        let archetypes = std::mem::take(&mut decoded.archetypes);
+        drop(decoded);
+
+        self.clear();
+        self.stats = EcsStats {
+            total_entities: entities.len(),
+            archetypes: archetypes.len() as u32,
+            ticks_processed: tick,
+            ..EcsStats::default()
+        };
+        self.entities = entities;
+        self.external_ids = external_ids;
+        self.archetypes = archetypes;
+        self.entity_archetype = entity_archetype;
+        Ok(())
+    }
+}
+
+/// Archetypes being decoded, owning their components until handed over
+struct Decoded<'a> {
+    codecs: &'a HashMap<ComponentId, ComponentCodec>,
+    archetypes: Vec<Archetype>,
+}
+
+impl Drop for Decoded<'_> {
+    fn drop(&mut self) {
+        for archetype in &self.archetypes {
+            archetype.drop_components(self.codecs);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::components::{Collision, EntityType, Health, Position, Velocity};
+
+    /// Component with its own variable-length encoding
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Tag(u8);
+
+    impl Component for Tag {
+        fn type_id() -> ComponentId { 1000 }
+
+        fn serialize(&self, out: &mut Vec<u8>) {
+            out.extend(std::iter::repeat(0xAA).take(self.0 as usize));
+        }
+
+        fn deserialize(bytes: &[u8]) -> Result<Self, EcsError> {
+            Ok(Tag(bytes.len() as u8))
+        }
+    }
+
+    fn sorted<T: Component + Copy>(world: &EcsWorld, key: impl Fn(&T) -> f64) -> Vec<(Entity, f64)> {
+        let mut rows: Vec<_> = world.query::<T>().iter().map(|(e, c)| (*e, key(c))).collect();
+        rows.sort_by_key(|(e, _)| *e);
+        rows
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let mut world = EcsWorld::new();
+        let mut movers = Vec::new();
+        for i in 0..4 {
+            let entity = world.spawn();
+            world.add_component(entity, Position { x: i as f64, y: 64.0, z: -(i as f64) });
+            world.add_component(entity, Velocity { x: 0.5 * i as f32, y: 0.0, z: 1.0 });
+            movers.push(entity);
+        }
+        let tank = world.spawn();
+        world.add_component(tank, Health { current: 7.5, max: 40.0, regeneration: 0.25 });
+        world.add_component(tank, Tag(3));
+        world.despawn(movers[1]);
+        world.spawn_entity(42, 0, 1.0, 2.0, 3.0);
+
+        let bytes = world.snapshot();
+        let mut restored = EcsWorld::new();
+        assert_eq!(restored.restore(&bytes), Err(EcsError::UnknownComponent(1000)));
+        restored.register_component::<Tag>();
+        restored.restore(&bytes).unwrap();
+
+        assert_eq!(sorted::<Position>(&restored, |p| p.x), sorted::<Position>(&world, |p| p.x));
+        assert_eq!(sorted::<Velocity>(&restored, |v| v.x as f64), sorted::<Velocity>(&world, |v| v.x as f64));
+        assert_eq!(restored.get_component::<Health>(tank).map(|h| h.current), Some(7.5));
+        assert_eq!(restored.get_component::<Tag>(tank), Some(Tag(3)));
+        assert_eq!(restored.entity_count(), world.entity_count());
+        assert_eq!(restored.external_entity(42), world.external_entity(42));
+        assert!(!restored.is_alive(movers[1]));
+
+        // Both worlds hand out the same next handle
+        assert_eq!(restored.spawn(), world.spawn());
+        assert_eq!(restored.snapshot().len(), world.snapshot().len());
+
+        assert_eq!(restored.restore(&bytes[..bytes.len() - 1]).map_err(|e| matches!(e, EcsError::Corrupt(_))), Err(true));
+        assert_eq!(restored.restore(b"nope"), Err(EcsError::BadMagic));
+    }
+
+    #[test]
+    fn test_invalid_flag_bytes_are_rejected() {
+        let mut world = EcsWorld::new();
+        let entity = world.spawn();
+        world.add_component(entity, Collision { width: 0.6, height: 1.8, on_ground: true, no_clip: false });
+        world.add_component(entity, EntityType::default());
+        let mut bytes = world.snapshot();
+        let mut restored = EcsWorld::new();
+        restored.restore(&bytes).unwrap();
+        assert!(restored.get_component::<Collision>(entity).unwrap().on_ground);
+
+        // The collision encoding ends in its two flag bytes
+        let mut encoded = Vec::new();
+        Collision { width: 0.6, height: 1.8, on_ground: true, no_clip: false }.serialize(&mut encoded);
+        let at = bytes.windows(encoded.len()).position(|w| w == encoded).unwrap();
+        bytes[at + 8] = 2;
+        assert!(matches!(restored.restore(&bytes), Err(EcsError::Corrupt(_))));
+        // A rejected snapshot leaves the world as it was
+        assert!(restored.get_component::<Collision>(entity).unwrap().on_ground);
+    }
+}
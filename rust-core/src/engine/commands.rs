@@ -0,0 +1,129 @@
+//! # Engine Commands
+//!
+//! Built-in console commands for `AetherEngine`. The JNI layer forwards
+//! chat commands here through `AetherEngine::execute_command`; mods can add
+//! their own with `AetherEngine::console_mut().register(..)`.
+
+use tracing_subscriber::filter::LevelFilter;
+
+use super::AetherEngine;
+use crate::console::{parse_arg, parse_switch, Console};
+use crate::ecs::components::Position;
+
+/// Console with the built-in engine commands
+pub fn engine_console() -> Console<AetherEngine> {
+    let mut console = Console::<AetherEngine>::new();
+
+    console.register_with_usage("set", "set <render_distance|max_fps|render_scale|vsync|volume> <value>", |engine, args| {
+        let (key, value) = match args {
+            [key, value] => (*key, *value),
+            _ => return Err("usage: set <key> <value>".to_string()),
+        };
+        let config = &mut engine.config;
+        match key {
+            "render_distance" => config.render_distance = parse_arg(Some(value), key)?,
+            "max_fps" => config.max_fps = parse_arg(Some(value), key)?,
+            "render_scale" => config.render_scale = parse_arg::<f32>(Some(value), key)?.clamp(0.25, 4.0),
+            "vsync" => config.vsync = parse_switch(value)?,
+            "volume" => config.master_volume = parse_arg::<f32>(Some(value), key)?.clamp(0.0, 1.0),
+            _ => return Err(format!("unknown setting '{}'", key)),
+        }
+        Ok(format!("{} = {}", key, value))
+    });
+
+    console.register_with_usage("debug", "debug <flag> [on|off]", |engine, args| match args {
+        [flag] => Ok(format!("{} is {}", flag, on_off(engine.get_debug_flag(flag)))),
+        [flag, value] => {
+            let value = parse_switch(value)?;
+            engine.set_debug_flag(flag, value);
+            Ok(format!("{} {}", flag, on_off(value)))
+        }
+        _ => Err("usage: debug <flag> [on|off]".to_string()),
+    });
+
+    console.register_with_usage("profiler", "profiler <reset|report>", |_, args| match args {
+        ["reset"] => {
+            crate::profiler().reset();
+            Ok("profiler reset".to_string())
+        }
+        ["report"] => {
+            let frames = crate::profiler().get_frame_stats();
+            Ok(format!(
+                "frames: {} avg {:.2}ms p99 {:.2}ms",
+                frames.frame_count, frames.avg_frame_time_ms, frames.p99_frame_time_ms
+            ))
+        }
+        _ => Err("usage: profiler <reset|report>".to_string()),
+    });
+
+    console.register_with_usage("mem", "mem stats", |_, args| match args {
+        ["stats"] => Ok(format!(
+            "off-heap: {} bytes in {} allocations",
+            crate::memory::MemoryManager::get_allocated_bytes(),
+            crate::memory::MemoryManager::get_allocation_count()
+        )),
+        _ => Err("usage: mem stats".to_string()),
+    });
+
+    console.register_with_usage("stats", "stats", |engine, _| Ok(engine.get_debug_info()));
+
+    console.register_with_usage("spawn", "spawn <x> <y> <z>", |engine, args| {
+        let [x, y, z] = args else {
+            return Err("usage: spawn <x> <y> <z>".to_string());
+        };
+        let position = Position {
+            x: parse_arg(Some(*x), "x")?,
+            y: parse_arg(Some(*y), "y")?,
+            z: parse_arg(Some(*z), "z")?,
+        };
+        let ecs = engine.ecs.as_mut().ok_or("ECS not running")?;
+        let entity = ecs.spawn();
+        ecs.add_component(entity, position);
+        Ok(format!("spawned entity {}", entity))
+    });
+
+    console.register_with_usage("log", "log <module> <off|error|warn|info|debug|trace>", |_, args| {
+        let [module, level] = args else {
+            return Err("usage: log <module> <level>".to_string());
+        };
+        let filter: LevelFilter = level.parse().map_err(|_| format!("invalid level '{}'", level))?;
+        crate::util::logging::set_log_level(module, filter)?;
+        Ok(format!("{} logging at {}", module, filter))
+    });
+
+    console
+}
+
+/// Format a flag value
+fn on_off(value: bool) -> &'static str {
+    if value { "on" } else { "off" }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_engine_commands() {
+        let mut engine = AetherEngine::new(&[]).unwrap();
+
+        assert_eq!(engine.execute_command("set render_distance 20").unwrap(), "render_distance = 20");
+        assert_eq!(engine.config().render_distance, 20);
+        assert!(engine.execute_command("set render_distance -3").is_err());
+
+        engine.execute_command("debug wireframe on").unwrap();
+        assert!(engine.get_debug_flag("wireframe"));
+        assert_eq!(engine.execute_command("debug wireframe").unwrap(), "wireframe is on");
+
+        let before = engine.ecs.as_ref().unwrap().entity_count();
+        assert!(engine.execute_command("spawn 1 64 2.5").unwrap().starts_with("spawned entity"));
+        assert_eq!(engine.ecs.as_ref().unwrap().entity_count(), before + 1);
+
+        assert_eq!(engine.execute_command("profiler reset").unwrap(), "profiler reset");
+        assert!(engine.execute_command("mem stats").unwrap().starts_with("off-heap"));
+
+        // Mods can add their own
+        engine.console_mut().register("ping", |_, _| Ok("pong".to_string()));
+        assert_eq!(engine.execute_command("/ping").unwrap(), "pong");
+    }
+}
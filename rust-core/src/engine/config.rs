@@ -4,6 +4,8 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::LibsError;
+
 /// Render mode options
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
@@ -38,6 +40,10 @@ pub struct EngineConfig {
     #[serde(rename = "maxFps")]
     pub max_fps: u32,
     
+    /// Render distance in chunks
+    #[serde(rename = "renderDistance")]
+    pub render_distance: u32,
+    
     /// Render scale (1.0 = native)
     #[serde(rename = "renderScale")]
     pub render_scale: f32,
@@ -50,6 +56,14 @@ pub struct EngineConfig {
     #[serde(rename = "meshThreads")]
     pub mesh_threads: u32,
     
+    /// Worker thread count for ECS and background jobs (0 = all cores but two)
+    #[serde(rename = "workerThreads")]
+    pub worker_threads: u32,
+    
+    /// Pin worker threads to cores
+    #[serde(rename = "pinWorkerThreads")]
+    pub pin_worker_threads: bool,
+    
     /// Enable validation layers (debug)
     #[serde(rename = "validationLayers")]
     pub validation_layers: bool,
@@ -61,6 +75,10 @@ pub struct EngineConfig {
     /// Audio volume (0.0 - 1.0)
     #[serde(rename = "masterVolume")]
     pub master_volume: f32,
+    
+    /// Anisotropic filtering level (1, 2, 4, 8, 16)
+    #[serde(rename = "anisotropicFiltering")]
+    pub anisotropic_filtering: u32,
 }
 
 impl Default for EngineConfig {
@@ -70,26 +88,29 @@ impl Default for EngineConfig {
             max_offheap_mb: 512,
             vsync: true,
             max_fps: 0,
+            render_distance: 12,
             render_scale: 1.0,
             async_chunks: true,
             mesh_threads: 4,
+            worker_threads: 0,
+            pin_worker_threads: false,
             validation_layers: false,
             ecs_profiling: false,
             master_volume: 1.0,
+            anisotropic_filtering: 16,
         }
     }
 }
 
 impl EngineConfig {
     /// Parse config from bytes (JSON)
-    pub fn from_bytes(data: &[u8]) -> Result<Self, String> {
+    pub fn from_bytes(data: &[u8]) -> Result<Self, LibsError> {
         if data.is_empty() {
             log::warn!("Empty config data, using defaults");
             return Ok(Self::default());
         }
         
-        serde_json::from_slice(data)
-            .map_err(|e| format!("Config parse error: {}", e))
+        Ok(serde_json::from_slice(data)?)
     }
     
     /// Serialize to bytes
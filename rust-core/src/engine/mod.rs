@@ -4,15 +4,26 @@
 
 pub mod config;
 pub mod state;
+pub mod commands;
 
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::time::Instant;
 
-use crate::ecs::EcsWorld;
+use glam::Vec3;
+
+use crate::ecs::{EcsWorld, Entity};
+use crate::ecs::components::Renderable;
 use crate::renderer::Renderer;
+use crate::renderer::vulkan::mesh_shader::MeshVertex;
 use crate::audio::AudioEngine;
 use crate::world::WorldManager;
+use crate::memory::MemoryManager;
+use crate::console::Console;
+use crate::lockstep::Command;
+use crate::replay::{ReplayCall, ReplayRecorder, ReplayTarget};
+use crate::util::jobs::JobSystem;
+use crate::LibsError;
 
 pub use config::EngineConfig;
 pub use state::EngineState;
@@ -63,11 +74,17 @@ pub struct AetherEngine {
     /// Debug flags
     debug_flags: HashMap<String, bool>,
     
+    /// Runtime command console
+    console: Console<AetherEngine>,
+    
     /// Profile data pointer (for external profilers)
     profile_data: Vec<u8>,
     
     /// Prediction state buffer
     prediction_buffer: Vec<PredictionState>,
+    
+    /// API call recording for offline reproduction
+    recorder: Option<ReplayRecorder>,
 }
 
 /// Camera state
@@ -100,7 +117,7 @@ pub struct PredictionState {
 
 impl AetherEngine {
     /// Create a new engine instance
-    pub fn new(config_data: &[u8]) -> Result<Self, String> {
+    pub fn new(config_data: &[u8]) -> Result<Self, LibsError> {
         log::info!("Creating AetherEngine...");
         
         // Parse configuration
@@ -109,6 +126,14 @@ impl AetherEngine {
         log::info!("Engine config loaded:");
         log::info!("  Render mode: {:?}", config.render_mode);
         log::info!("  Max off-heap memory: {} MB", config.max_offheap_mb);
+        MemoryManager::set_limit((config.max_offheap_mb as usize).saturating_mul(1024 * 1024));
+        
+        // Size the shared worker pool before anything builds it
+        let workers = JobSystem::configure(config.worker_threads as usize)
+            .and_then(|_| JobSystem::configure_pinning(config.pin_worker_threads));
+        if let Err(e) = workers {
+            log::warn!("  Worker pool settings ignored: {}", e);
+        }
         
         // Initialize subsystems
         let ecs = Some(EcsWorld::new());
@@ -156,8 +181,10 @@ impl AetherEngine {
             textures: HashMap::new(),
             next_texture_handle: AtomicU64::new(1),
             debug_flags: HashMap::new(),
+            console: commands::engine_console(),
             profile_data: Vec::new(),
             prediction_buffer: Vec::new(),
+            recorder: None,
         })
     }
     
@@ -174,11 +201,15 @@ impl AetherEngine {
     
     /// Process a game tick
     pub fn tick(&mut self, delta_time: f32) {
+        self.record_call(|| ReplayCall::Tick(delta_time));
         self.delta_time = delta_time;
         
         // Update ECS
         if let Some(ref mut ecs) = self.ecs {
             ecs.tick(delta_time);
+            if let Some(ref world) = self.world {
+                ecs.tick_physics(world, delta_time);
+            }
         }
         
         // Update world
@@ -216,6 +247,9 @@ impl AetherEngine {
         // Begin renderer frame
         if let Some(ref mut renderer) = self.renderer {
             renderer.begin_frame();
+            if let Some(ref ecs) = self.ecs {
+                renderer.update_entity_instances(ecs);
+            }
         }
     }
     
@@ -249,6 +283,7 @@ impl AetherEngine {
     
     /// Submit chunk data
     pub fn submit_chunk(&mut self, x: i32, z: i32, data: &[u8]) -> i64 {
+        self.record_call(|| ReplayCall::Command(Command::SubmitChunk { x, z, data: data.to_vec() }));
         if let Some(ref mut world) = self.world {
             world.submit_chunk(x, z, data)
         } else {
@@ -258,8 +293,11 @@ impl AetherEngine {
     
     /// Update chunk data
     pub fn update_chunk(&mut self, x: i32, z: i32, data: &[u8]) {
+        self.record_call(|| ReplayCall::Command(Command::UpdateChunk { x, z, data: data.to_vec() }));
         if let Some(ref mut world) = self.world {
-            world.update_chunk(x, z, data);
+            if let Err(e) = world.update_chunk(x, z, data) {
+                log::warn!("{}", e);
+            }
         }
     }
     
@@ -272,6 +310,7 @@ impl AetherEngine {
     
     /// Unload a chunk
     pub fn unload_chunk(&mut self, x: i32, z: i32) {
+        self.record_call(|| ReplayCall::Command(Command::UnloadChunk { x, z }));
         if let Some(ref mut world) = self.world {
             world.unload_chunk(x, z);
         }
@@ -279,35 +318,84 @@ impl AetherEngine {
     
     /// Set a block
     pub fn set_block(&mut self, x: i32, y: i32, z: i32, block_id: u32) {
+        self.record_call(|| ReplayCall::Command(Command::SetBlock { x, y, z, block_id }));
         if let Some(ref mut world) = self.world {
             world.set_block(x, y, z, block_id);
         }
     }
     
+    /// Schedule a block update `delay_ticks` ticks from now
+    pub fn schedule_block_update(&mut self, x: i32, y: i32, z: i32, delay_ticks: u32) {
+        self.record_call(|| ReplayCall::Command(Command::ScheduleBlockUpdate { x, y, z, delay_ticks }));
+        if let Some(ref mut world) = self.world {
+            world.schedule_block_update(x, y, z, delay_ticks);
+        }
+    }
+    
     // ========================================================================
     // ENTITY FUNCTIONS
     // ========================================================================
     
-    /// Register an entity
-    pub fn register_entity(&mut self, entity_id: i32, entity_type: i32, x: f64, y: f64, z: f64) -> i64 {
-        if let Some(ref mut ecs) = self.ecs {
+    /// Register an entity, optionally drawn with a mesh from `register_mesh`
+    pub fn register_entity(&mut self, entity_id: i32, entity_type: i32, x: f64, y: f64, z: f64, mesh_id: Option<u32>) -> i64 {
+        self.record_call(|| ReplayCall::Command(Command::SpawnEntity { id: entity_id, entity_type, x, y, z }));
+        let handle = if let Some(ref mut ecs) = self.ecs {
             ecs.spawn_entity(entity_id, entity_type, x, y, z)
         } else {
-            0
+            return 0;
+        };
+        
+        if let Some(mesh_id) = mesh_id {
+            self.attach_renderable(Entity::from_bits(handle as u64), Renderable { mesh_id, ..Renderable::default() });
         }
+        handle
     }
     
-    /// Update entity position
-    pub fn update_entity(&mut self, handle: u64, x: f64, y: f64, z: f64, yaw: f32, pitch: f32) {
+    /// Attach (or replace) an entity's custom mesh
+    pub fn attach_renderable(&mut self, entity: Entity, renderable: Renderable) {
         if let Some(ref mut ecs) = self.ecs {
-            ecs.update_entity(handle, x, y, z, yaw, pitch);
+            ecs.add_component(entity, renderable);
         }
     }
     
-    /// Remove an entity
-    pub fn remove_entity(&mut self, handle: u64) {
+    /// Update entity position by the ID it was registered under
+    pub fn update_entity(&mut self, entity_id: i32, x: f64, y: f64, z: f64, yaw: f32, pitch: f32) {
+        self.record_call(|| ReplayCall::Command(Command::MoveEntity { id: entity_id, x, y, z, yaw, pitch }));
         if let Some(ref mut ecs) = self.ecs {
-            ecs.despawn_entity(handle);
+            ecs.update_entity(entity_id as u64, x, y, z, yaw, pitch);
+        }
+    }
+    
+    /// Remove an entity by the ID it was registered under
+    pub fn remove_entity(&mut self, entity_id: i32) {
+        self.record_call(|| ReplayCall::Command(Command::DespawnEntity { id: entity_id }));
+        if let Some(ref mut ecs) = self.ecs {
+            ecs.despawn_entity(entity_id as u64);
+        }
+    }
+    
+    /// Find the closest entity a ray hits within `max_dist`, with its distance
+    ///
+    /// Entities need a `Collision` box to be pickable. The ray stops at the
+    /// first solid block, so entities behind walls can't be picked.
+    pub fn pick_entity(&self, origin: Vec3, dir: Vec3, max_dist: f32) -> Option<(Entity, f32)> {
+        let ecs = self.ecs.as_ref()?;
+        let reach = self.world
+            .as_ref()
+            .and_then(|world| world.raycast(origin, dir, max_dist))
+            .map_or(max_dist, |hit| hit.distance);
+        ecs.entity_bvh().query_ray_hits(origin, dir, reach).first().copied()
+    }
+    
+    // ========================================================================
+    // MESH FUNCTIONS
+    // ========================================================================
+    
+    /// Register a custom entity mesh; returns its id (0 without a renderer)
+    pub fn register_mesh(&mut self, vertices: &[MeshVertex], indices: &[u32]) -> u32 {
+        match self.renderer {
+            Some(ref mut renderer) => renderer.register_mesh(vertices, indices),
+            None => 0,
         }
     }
     
@@ -370,7 +458,7 @@ impl AetherEngine {
     /// Play a sound
     pub fn play_sound(&mut self, name: &str, x: f32, y: f32, z: f32, volume: f32, pitch: f32) -> i64 {
         if let Some(ref mut audio) = self.audio {
-            audio.play(name, x, y, z, volume, pitch) as i64
+            audio.play(name, x, y, z, volume, pitch).handle().map_or(0, |h| h as i64)
         } else {
             0
         }
@@ -471,6 +559,25 @@ impl AetherEngine {
         self.debug_flags.get(flag).copied().unwrap_or(false)
     }
     
+    /// Run a console command (e.g. `debug wireframe on`) and get its response
+    pub fn execute_command(&mut self, line: &str) -> Result<String, String> {
+        let console = std::mem::take(&mut self.console);
+        let result = console.execute(self, line);
+        self.console = console;
+        log::debug!("Command '{}': {:?}", line, result);
+        result
+    }
+    
+    /// Get the console to register extra commands
+    pub fn console_mut(&mut self) -> &mut Console<AetherEngine> {
+        &mut self.console
+    }
+    
+    /// Get the configuration
+    pub fn config(&self) -> &EngineConfig {
+        &self.config
+    }
+    
     /// Get profile data pointer
     pub fn get_profile_data_ptr(&self) -> i64 {
         self.profile_data.as_ptr() as i64
@@ -494,8 +601,180 @@ impl AetherEngine {
     pub fn get_frame_count(&self) -> u64 {
         self.frame_count.load(Ordering::SeqCst)
     }
+    
+    /// Get renderer
+    pub fn renderer(&self) -> Option<&Renderer> {
+        self.renderer.as_ref()
+    }
+    
+    /// Get the ECS world
+    pub fn ecs(&self) -> Option<&EcsWorld> {
+        self.ecs.as_ref()
+    }
+    
+    /// Get the world
+    pub fn world(&self) -> Option<&WorldManager> {
+        self.world.as_ref()
+    }
+    
+    // ========================================================================
+    // REPLAY FUNCTIONS
+    // ========================================================================
+    
+    /// Seed random ticks and make ECS ticks deterministic
+    pub fn seed_simulation(&mut self, seed: u64) {
+        self.record_call(|| ReplayCall::Seed(seed));
+        if let Some(ref mut world) = self.world {
+            world.seed_random_ticks(seed);
+        }
+        if let Some(ref mut ecs) = self.ecs {
+            ecs.set_deterministic(true);
+        }
+    }
+    
+    /// Start recording API calls (replaces any recording in progress)
+    ///
+    /// The simulation is re-seeded with a fresh seed, recorded first, so
+    /// the replay's random ticks match.
+    pub fn start_recording(&mut self, recorder: ReplayRecorder) {
+        self.recorder = Some(recorder);
+        self.seed_simulation(rand::random());
+    }
+    
+    /// Stop recording, returning the recorder after flushing it
+    pub fn stop_recording(&mut self) -> Option<ReplayRecorder> {
+        let mut recorder = self.recorder.take()?;
+        if let Err(e) = recorder.flush() {
+            log::warn!("Replay flush failed: {}", e);
+        }
+        Some(recorder)
+    }
+    
+    /// Check if API calls are being recorded
+    pub fn is_recording(&self) -> bool {
+        self.recorder.is_some()
+    }
+    
+    /// Log a call if recording
+    fn record_call(&mut self, call: impl FnOnce() -> ReplayCall) {
+        if let Some(ref mut recorder) = self.recorder {
+            recorder.record(call());
+        }
+    }
+    
+    /// Apply a recorded command through the matching entry point
+    pub fn execute(&mut self, command: &Command) {
+        match *command {
+            Command::SpawnEntity { id, entity_type, x, y, z } => {
+                self.register_entity(id, entity_type, x, y, z, None);
+            }
+            Command::MoveEntity { id, x, y, z, yaw, pitch } => self.update_entity(id, x, y, z, yaw, pitch),
+            Command::DespawnEntity { id } => self.remove_entity(id),
+            Command::SetBlock { x, y, z, block_id } => self.set_block(x, y, z, block_id),
+            Command::ScheduleBlockUpdate { x, y, z, delay_ticks } => self.schedule_block_update(x, y, z, delay_ticks),
+            Command::SubmitChunk { x, z, ref data } => {
+                self.submit_chunk(x, z, data);
+            }
+            Command::UpdateChunk { x, z, ref data } => self.update_chunk(x, z, data),
+            Command::UnloadChunk { x, z } => self.unload_chunk(x, z),
+        }
+    }
+}
+
+impl ReplayTarget for AetherEngine {
+    fn replay_seed(&mut self, seed: u64) {
+        self.seed_simulation(seed);
+    }
+    
+    fn replay_tick(&mut self, delta_time: f32) {
+        self.tick(delta_time);
+    }
+    
+    fn replay_command(&mut self, command: &Command) {
+        self.execute(command);
+    }
 }
 
 // Ensure TextureInfo is Send + Sync (raw pointer needs explicit impl)
 unsafe impl Send for TextureInfo {}
 unsafe impl Sync for TextureInfo {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_renderable_joins_mesh_instance_group() {
+        let mut engine = AetherEngine::new(&[]).unwrap();
+
+        let triangle = [MeshVertex::default(); 3];
+        let mesh = engine.register_mesh(&triangle, &[0, 1, 2]);
+        assert_ne!(mesh, 0);
+
+        let a = engine.register_entity(1, 0, 1.0, 64.0, 2.0, Some(mesh));
+        let b = engine.register_entity(2, 0, 5.0, 64.0, 5.0, None);
+        engine.register_entity(3, 0, 0.0, 0.0, 0.0, Some(999)); // Unknown mesh
+        let (a, b) = (Entity::from_bits(a as u64), Entity::from_bits(b as u64));
+        engine.attach_renderable(b, Renderable { mesh_id: mesh, texture_id: 4, scale: 2.0 });
+
+        engine.begin_frame(0.0);
+        let renderer = engine.renderer().unwrap();
+        let group = renderer.instance_group(mesh);
+        assert_eq!(renderer.instanced_draw_count(), 1);
+        assert_eq!(group.len(), 2);
+        assert_eq!((group[0].entity, group[0].position, group[0].scale), (a, [1.0, 64.0, 2.0], 1.0));
+        assert_eq!((group[1].entity, group[1].texture_id, group[1].scale), (b, 4, 2.0));
+
+        // Re-attaching replaces the component; despawning drops the instance
+        engine.attach_renderable(b, Renderable { mesh_id: mesh, texture_id: 5, scale: 1.0 });
+        engine.remove_entity(1);
+        engine.begin_frame(0.0);
+        let group = engine.renderer().unwrap().instance_group(mesh);
+        assert_eq!(group.len(), 1);
+        assert_eq!(group[0].texture_id, 5);
+    }
+
+    #[test]
+    fn test_anisotropic_filtering_reaches_vulkan_config() {
+        let engine = AetherEngine::new(br#"{"anisotropicFiltering": 4}"#).unwrap();
+        let renderer = engine.renderer().unwrap();
+        assert_eq!(renderer.vulkan_config().anisotropy_level, 4);
+        let compositor = crate::renderer::quantum::compositor::GuiCompositor::with_config(renderer.compositor_config());
+        assert_eq!(compositor.max_anisotropy(), 4.0);
+
+        let engine = AetherEngine::new(&[]).unwrap();
+        assert_eq!(engine.renderer().unwrap().vulkan_config().anisotropy_level, 16);
+    }
+
+    #[test]
+    fn test_pick_entity_stops_at_blocks() {
+        use crate::ecs::components::{Collision, Position};
+
+        let mut engine = AetherEngine::new(&[]).unwrap();
+        engine.submit_chunk(0, 0, &[]);
+        let mut spawn = |x: f64| {
+            let ecs = engine.ecs.as_mut().unwrap();
+            let entity = ecs.spawn();
+            ecs.add_component(entity, Position { x, y: 64.0, z: 8.5 });
+            ecs.add_component(entity, Collision { width: 0.6, height: 1.8, on_ground: true, no_clip: false });
+            entity
+        };
+        let near = spawn(5.5);
+        let far = spawn(12.5);
+
+        let eye = Vec3::new(0.5, 65.0, 8.5);
+        let (entity, distance) = engine.pick_entity(eye, Vec3::X, 20.0).unwrap();
+        assert_eq!(entity, near);
+        assert!((distance - 4.7).abs() < 1e-4, "{}", distance);
+
+        // A wall between the two: only the near one is reachable, and
+        // nothing is once the wall is in front of both
+        engine.set_block(9, 65, 8, 1);
+        assert_eq!(engine.pick_entity(eye, Vec3::X, 20.0).map(|(e, _)| e), Some(near));
+        engine.ecs.as_mut().unwrap().despawn(near);
+        assert_eq!(engine.pick_entity(eye, Vec3::X, 20.0), None);
+        engine.set_block(9, 65, 8, 0);
+        assert_eq!(engine.pick_entity(eye, Vec3::X, 20.0).map(|(e, _)| e), Some(far));
+        assert_eq!(engine.pick_entity(eye, Vec3::X, 5.0), None);
+    }
+}
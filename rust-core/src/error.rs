@@ -0,0 +1,185 @@
+//! # Unified Error Type
+//!
+//! `LibsError` wraps every subsystem error so public APIs (and the JNI layer,
+//! which only needs to format one type) share a single error. Subsystem
+//! errors convert with `?` through the `From` impls below.
+
+#[cfg(feature = "vulkan")]
+use crate::renderer::quantum::RendererError;
+#[cfg(feature = "vulkan")]
+use crate::renderer::shaders::ShaderError;
+#[cfg(feature = "vulkan")]
+use crate::renderer::vulkan::VulkanError;
+#[cfg(feature = "jni")]
+use crate::jni::types::JniError;
+use crate::memory::void_manager::VoidError;
+use crate::network::PacketError;
+use crate::world::save::SaveError;
+
+/// Engine-wide error
+#[derive(Debug)]
+pub enum LibsError {
+    /// Vulkan device/resource error
+    #[cfg(feature = "vulkan")]
+    Vulkan(VulkanError),
+    /// Renderer error
+    #[cfg(feature = "vulkan")]
+    Renderer(RendererError),
+    /// Shader compilation/loading error
+    #[cfg(feature = "vulkan")]
+    Shader(ShaderError),
+    /// JNI error
+    #[cfg(feature = "jni")]
+    Jni(JniError),
+    /// Off-heap memory error
+    Memory(VoidError),
+    /// Network packet error
+    Packet(PacketError),
+    /// World save/load error
+    Save(SaveError),
+    /// Invalid engine configuration
+    Config(String),
+    /// I/O error
+    Io(std::io::Error),
+}
+
+impl std::fmt::Display for LibsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            #[cfg(feature = "vulkan")]
+            Self::Vulkan(e) => write!(f, "Vulkan: {}", e),
+            #[cfg(feature = "vulkan")]
+            Self::Renderer(e) => write!(f, "Renderer: {}", e),
+            #[cfg(feature = "vulkan")]
+            Self::Shader(e) => write!(f, "Shader: {}", e),
+            #[cfg(feature = "jni")]
+            Self::Jni(e) => write!(f, "JNI: {}", e),
+            Self::Memory(e) => write!(f, "Memory: {}", e),
+            Self::Packet(e) => write!(f, "Network: {}", e),
+            Self::Save(e) => write!(f, "Save: {}", e),
+            Self::Config(e) => write!(f, "Config: {}", e),
+            Self::Io(e) => write!(f, "I/O error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for LibsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            #[cfg(feature = "vulkan")]
+            Self::Vulkan(e) => Some(e),
+            #[cfg(feature = "vulkan")]
+            Self::Renderer(e) => Some(e),
+            #[cfg(feature = "vulkan")]
+            Self::Shader(e) => Some(e),
+            #[cfg(feature = "jni")]
+            Self::Jni(e) => Some(e),
+            Self::Memory(e) => Some(e),
+            Self::Packet(e) => Some(e),
+            Self::Save(e) => Some(e),
+            Self::Io(e) => Some(e),
+            Self::Config(_) => None,
+        }
+    }
+}
+
+#[cfg(feature = "vulkan")]
+impl From<VulkanError> for LibsError {
+    fn from(e: VulkanError) -> Self {
+        Self::Vulkan(e)
+    }
+}
+
+#[cfg(feature = "vulkan")]
+impl From<RendererError> for LibsError {
+    fn from(e: RendererError) -> Self {
+        Self::Renderer(e)
+    }
+}
+
+#[cfg(feature = "vulkan")]
+impl From<ShaderError> for LibsError {
+    fn from(e: ShaderError) -> Self {
+        Self::Shader(e)
+    }
+}
+
+#[cfg(feature = "jni")]
+impl From<JniError> for LibsError {
+    fn from(e: JniError) -> Self {
+        Self::Jni(e)
+    }
+}
+
+impl From<VoidError> for LibsError {
+    fn from(e: VoidError) -> Self {
+        Self::Memory(e)
+    }
+}
+
+impl From<PacketError> for LibsError {
+    fn from(e: PacketError) -> Self {
+        Self::Packet(e)
+    }
+}
+
+impl From<SaveError> for LibsError {
+    fn from(e: SaveError) -> Self {
+        Self::Save(e)
+    }
+}
+
+impl From<std::io::Error> for LibsError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for LibsError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Config(e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error;
+
+    #[test]
+    fn test_subsystem_errors_convert() {
+        let err: LibsError = VoidError::OutOfMemory.into();
+        assert!(matches!(err, LibsError::Memory(VoidError::OutOfMemory)));
+        assert_eq!(err.to_string(), "Memory: Out of native memory");
+        assert!(err.source().is_some());
+
+        let err: LibsError = PacketError::TooShort(3).into();
+        assert!(matches!(err, LibsError::Packet(PacketError::TooShort(3))));
+
+        let err: LibsError = SaveError::BadMagic.into();
+        assert!(matches!(err, LibsError::Save(SaveError::BadMagic)));
+
+        let err: LibsError = std::io::Error::other("disk").into();
+        assert!(matches!(err, LibsError::Io(_)));
+
+        let err: LibsError = serde_json::from_slice::<u32>(b"{").unwrap_err().into();
+        assert!(matches!(err, LibsError::Config(_)));
+        assert!(err.source().is_none());
+
+        #[cfg(feature = "vulkan")]
+        {
+            let err: LibsError = VulkanError::NoSuitableGpu.into();
+            assert!(matches!(err, LibsError::Vulkan(VulkanError::NoSuitableGpu)));
+            let err: LibsError = RendererError::NotInitialized.into();
+            assert!(matches!(err, LibsError::Renderer(RendererError::NotInitialized)));
+            let err: LibsError = ShaderError::FileNotFound("a.spv".into()).into();
+            assert!(matches!(err, LibsError::Shader(ShaderError::FileNotFound(_))));
+        }
+
+        #[cfg(feature = "jni")]
+        {
+            let err: LibsError = JniError::InvalidHandle.into();
+            assert!(matches!(err, LibsError::Jni(JniError::InvalidHandle)));
+        }
+    }
+}
@@ -0,0 +1,87 @@
+//! # Engine Events
+//!
+//! Notifications that subsystems raise for other subsystems to react to
+//! (e.g. streaming reducing LOD under GPU memory pressure). Producers push
+//! into a shared `EventQueue`; consumers drain it once per frame.
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+use parking_lot::Mutex;
+
+/// Engine-wide event
+#[derive(Debug, Clone, PartialEq)]
+pub enum EngineEvent {
+    /// GPU heap usage crossed the pressure threshold (or an allocation failed)
+    GpuMemoryPressure {
+        /// Memory heap index
+        heap: u32,
+        /// Bytes in use on the heap
+        used_bytes: u64,
+        /// Bytes the heap may use
+        budget_bytes: u64,
+    },
+    /// A GPU wait timed out
+    GpuHang {
+        /// Operation that was waiting
+        operation: String,
+        /// Timeout in effect (milliseconds)
+        timeout_ms: u64,
+    },
+    /// The device was lost (TDR, driver reset)
+    DeviceLost {
+        /// Operation that reported the loss
+        operation: String,
+    },
+    /// The device was recreated and persistent resources re-uploaded
+    DeviceRestored {
+        /// Persistent resources restored
+        resources: usize,
+    },
+    /// A watched asset changed on disk and was decoded again
+    AssetReloaded {
+        /// Asset path
+        path: String,
+    },
+    /// An off-heap allocation was refused for exceeding the memory limit
+    OffheapBudgetExceeded {
+        /// Bytes asked for
+        requested_bytes: u64,
+        /// Bytes allocated at the time
+        allocated_bytes: u64,
+        /// Configured ceiling
+        limit_bytes: u64,
+    },
+}
+
+/// Shared, cloneable event queue
+#[derive(Debug, Clone, Default)]
+pub struct EventQueue {
+    events: Arc<Mutex<VecDeque<EngineEvent>>>,
+}
+
+impl EventQueue {
+    /// Create an empty queue
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Push an event
+    pub fn emit(&self, event: EngineEvent) {
+        self.events.lock().push_back(event);
+    }
+
+    /// Take all pending events, oldest first
+    pub fn drain(&self) -> Vec<EngineEvent> {
+        self.events.lock().drain(..).collect()
+    }
+
+    /// Get pending event count
+    pub fn len(&self) -> usize {
+        self.events.lock().len()
+    }
+
+    /// Check if no events are pending
+    pub fn is_empty(&self) -> bool {
+        self.events.lock().is_empty()
+    }
+}
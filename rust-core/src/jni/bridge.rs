@@ -17,6 +17,7 @@ use jni::sys::{jboolean, jint, jlong, jfloat, jdouble, JNI_TRUE, JNI_FALSE};
 
 use crate::engine::AetherEngine;
 use crate::memory::MemoryManager;
+use crate::replay::ReplayRecorder;
 
 // ============================================================================
 // LIFECYCLE FUNCTIONS
@@ -283,7 +284,7 @@ pub unsafe extern "system" fn Java_dev_libs_bridge_NativeBridge_nativeSpawnEntit
         return;
     }
     
-    (*engine_ptr).register_entity(entity_id, 0, x, y, z);
+    (*engine_ptr).register_entity(entity_id, 0, x, y, z, None);
 }
 
 #[no_mangle]
@@ -295,7 +296,7 @@ pub unsafe extern "system" fn Java_dev_libs_bridge_NativeBridge_nativeRemoveEnti
 ) {
     let engine_ptr = handle as *mut AetherEngine;
     if !engine_ptr.is_null() {
-        (*engine_ptr).remove_entity(entity_id as u64);
+        (*engine_ptr).remove_entity(entity_id);
     }
 }
 
@@ -313,7 +314,7 @@ pub unsafe extern "system" fn Java_dev_libs_bridge_NativeBridge_nativeUpdateEnti
 ) {
     let engine_ptr = handle as *mut AetherEngine;
     if !engine_ptr.is_null() {
-        (*engine_ptr).update_entity(entity_id as u64, x, y, z, yaw, pitch);
+        (*engine_ptr).update_entity(entity_id, x, y, z, yaw, pitch);
     }
 }
 
@@ -688,6 +689,77 @@ pub unsafe extern "system" fn Java_dev_libs_bridge_NativeBridge_nativeSetDebugFl
     (*engine_ptr).set_debug_flag(&flag_name, value != 0);
 }
 
+#[no_mangle]
+pub unsafe extern "system" fn Java_dev_libs_bridge_NativeBridge_nativeExecuteCommand<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    handle: jlong,
+    command: JString<'local>,
+) -> JString<'local> {
+    let engine_ptr = handle as *mut AetherEngine;
+    if engine_ptr.is_null() {
+        return env.new_string("Engine not initialized").unwrap();
+    }
+    
+    let line: String = match env.get_string(&command) {
+        Ok(s) => s.into(),
+        Err(_) => return env.new_string("Error reading command").unwrap(),
+    };
+    
+    let response = match (*engine_ptr).execute_command(&line) {
+        Ok(response) => response,
+        Err(e) => format!("Error: {}", e),
+    };
+    match env.new_string(&response) {
+        Ok(s) => s,
+        Err(_) => env.new_string("Error returning response").unwrap(),
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_dev_libs_bridge_NativeBridge_nativeStartRecording(
+    mut env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+    path: JString,
+) -> jboolean {
+    let engine_ptr = handle as *mut AetherEngine;
+    if engine_ptr.is_null() {
+        return JNI_FALSE;
+    }
+    
+    let path: String = match env.get_string(&path) {
+        Ok(s) => s.into(),
+        Err(_) => return JNI_FALSE,
+    };
+    
+    match ReplayRecorder::create(&path) {
+        Ok(recorder) => {
+            (*engine_ptr).start_recording(recorder);
+            log::info!("Recording replay to {}", path);
+            JNI_TRUE
+        }
+        Err(e) => {
+            log::warn!("Cannot record replay to {}: {}", path, e);
+            JNI_FALSE
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_dev_libs_bridge_NativeBridge_nativeStopRecording(
+    _env: JNIEnv,
+    _class: JClass,
+    handle: jlong,
+) -> jboolean {
+    let engine_ptr = handle as *mut AetherEngine;
+    if !engine_ptr.is_null() && (*engine_ptr).stop_recording().is_some() {
+        JNI_TRUE
+    } else {
+        JNI_FALSE
+    }
+}
+
 #[no_mangle]
 pub unsafe extern "system" fn Java_dev_libs_bridge_NativeBridge_nativeGetProfileData(
     _env: JNIEnv,
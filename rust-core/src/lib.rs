@@ -19,40 +19,61 @@
 #![allow(unused_imports)]
 
 // Core modules
+#[cfg(feature = "jni")]
 pub mod jni;
+pub mod capi;
+#[cfg(all(feature = "vulkan", feature = "audio"))]
 pub mod engine;
+#[cfg(feature = "vulkan")]
 pub mod renderer;
 pub mod memory;
 pub mod ecs;
+#[cfg(feature = "audio")]
 pub mod audio;
 pub mod network;
 pub mod world;
 pub mod util;
 pub mod profiling;
 pub mod compat;
+pub mod error;
+pub mod events;
+pub mod lockstep;
+pub mod console;
+pub mod replay;
+#[cfg(any(test, feature = "test-support"))]
+pub mod test_support;
 
 // Re-exports
+#[cfg(all(feature = "vulkan", feature = "audio"))]
 pub use engine::AetherEngine;
+pub use error::LibsError;
 pub use memory::MemoryManager;
 pub use profiling::{profiler, Profiler};
 
 // New module re-exports
-pub use renderer::quantum::QuantumRenderer;
 pub use ecs::EcsWorld;
 pub use memory::void_manager::VoidManager;
 pub use network::prediction::PredictiveNetcode;
-pub use audio::raytracer::AudioRaytracer;
 pub use compat::TheWeaver;
+pub use ecs::parallel::ParallelScheduler;
+pub use world::NbtAssetLoader;
+pub use util::logging::set_log_level;
+#[cfg(feature = "audio")]
+pub use audio::raytracer::AudioRaytracer;
+#[cfg(feature = "vulkan")]
+pub use renderer::quantum::QuantumRenderer;
+#[cfg(feature = "vulkan")]
 pub use renderer::bindless::BindlessTextureManager;
+#[cfg(feature = "vulkan")]
 pub use renderer::quantum::greedy_mesh::GpuGreedyMesher;
+#[cfg(feature = "vulkan")]
 pub use renderer::vulkan::interop::VulkanGLInterop;
-pub use ecs::parallel::ParallelScheduler;
-pub use world::NbtAssetLoader;
+#[cfg(feature = "vulkan")]
 pub use renderer::quantum::nanite::NaniteManager;
 
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Once;
 use std::sync::Arc;
+use once_cell::sync::OnceCell;
 use parking_lot::RwLock;
 
 /// Library version
@@ -61,112 +82,321 @@ pub const VERSION: &str = "1.0.0";
 /// Library name  
 pub const NAME: &str = "libs_core";
 
-/// Initialization guard
-static INIT: Once = Once::new();
+/// Initialization flag
 static INITIALIZED: AtomicBool = AtomicBool::new(false);
 
-/// Global engine instance
-static mut ENGINE: Option<Arc<RwLock<LibsEngine>>> = None;
+/// Global engine instance (created once, reset in place by `shutdown`)
+static ENGINE: OnceCell<Arc<RwLock<LibsEngine>>> = OnceCell::new();
+
+/// Engine subsystem
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Subsystem {
+    Renderer,
+    Ecs,
+    Memory,
+    World,
+    Netcode,
+    Audio,
+}
+
+impl Subsystem {
+    /// All subsystems in initialization order
+    pub fn all() -> [Subsystem; 6] {
+        [Subsystem::Memory, Subsystem::Ecs, Subsystem::World, Subsystem::Renderer, Subsystem::Netcode, Subsystem::Audio]
+    }
+    
+    /// Subsystems that need a GPU or audio device
+    pub fn client_only() -> [Subsystem; 2] {
+        [Subsystem::Renderer, Subsystem::Audio]
+    }
+    
+    /// Cargo feature a subsystem is gated behind
+    pub fn feature(self) -> Option<&'static str> {
+        match self {
+            Subsystem::Renderer => Some("vulkan"),
+            Subsystem::Audio => Some("audio"),
+            _ => None,
+        }
+    }
+    
+    /// Check if the subsystem was compiled into this build
+    #[allow(clippy::match_like_matches_macro)] // Arms depend on cfg!
+    pub fn is_compiled(self) -> bool {
+        match self {
+            Subsystem::Renderer => cfg!(feature = "vulkan"),
+            Subsystem::Audio => cfg!(feature = "audio"),
+            _ => true,
+        }
+    }
+}
+
+/// Result of engine initialization
+#[derive(Debug, Clone, Default)]
+pub struct InitReport {
+    /// Subsystems that came up
+    pub ready: Vec<Subsystem>,
+    /// Subsystems that failed or were disabled, with reasons
+    pub failed: Vec<(Subsystem, String)>,
+}
+
+impl InitReport {
+    /// Check if a subsystem came up
+    pub fn is_ready(&self, subsystem: Subsystem) -> bool {
+        self.ready.contains(&subsystem)
+    }
+    
+    /// Get the failure reason for a subsystem
+    pub fn failure(&self, subsystem: Subsystem) -> Option<&str> {
+        self.failed.iter()
+            .find(|(s, _)| *s == subsystem)
+            .map(|(_, reason)| reason.as_str())
+    }
+    
+    /// Check if every subsystem came up
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
 
 /// LIBS Engine - Main entry point
 pub struct LibsEngine {
     /// Quantum Renderer
-    pub renderer: renderer::quantum::QuantumRenderer,
+    #[cfg(feature = "vulkan")]
+    pub renderer: Option<renderer::quantum::QuantumRenderer>,
     /// ECS World
-    pub ecs: ecs::EcsWorld,
+    pub ecs: Option<ecs::EcsWorld>,
     /// Void Manager (memory)
     pub memory: Arc<memory::void_manager::VoidManager>,
+    /// World (chunks, block ticks)
+    pub world: Option<world::WorldManager>,
     /// Predictive Netcode
-    pub netcode: network::prediction::PredictiveNetcode,
+    pub netcode: Option<network::prediction::PredictiveNetcode>,
     /// Audio Raytracer
-    pub audio: audio::raytracer::AudioRaytracer,
+    #[cfg(feature = "audio")]
+    pub audio: Option<audio::raytracer::AudioRaytracer>,
+    /// Last initialization report
+    report: InitReport,
+    /// Never bring up renderer or audio
+    headless: bool,
+    /// Lockstep ticks run
+    lockstep_tick: u64,
+    /// State hash after the last lockstep tick
+    last_tick_hash: u64,
+    /// API call recording for offline reproduction
+    recorder: Option<replay::ReplayRecorder>,
     /// Initialized flag
     initialized: bool,
 }
 
 impl LibsEngine {
-    /// Create new engine
+    /// Create new engine (subsystems come up in `initialize`)
     pub fn new() -> Self {
         log::info!("Creating LIBS Engine instance...");
         
         Self {
-            renderer: renderer::quantum::QuantumRenderer::new(),
-            ecs: ecs::EcsWorld::new(),
+            #[cfg(feature = "vulkan")]
+            renderer: None,
+            ecs: None,
             memory: memory::void_manager::VoidManager::new(),
-            netcode: network::prediction::PredictiveNetcode::new(),
-            audio: audio::raytracer::AudioRaytracer::new(),
+            world: None,
+            netcode: None,
+            #[cfg(feature = "audio")]
+            audio: None,
+            report: InitReport::default(),
+            headless: false,
+            lockstep_tick: 0,
+            last_tick_hash: 0,
+            recorder: None,
             initialized: false,
         }
     }
     
-    /// Initialize all subsystems
-    pub fn initialize(&mut self) -> Result<(), String> {
+    /// Create an initialized engine with simulation only (no renderer or audio)
+    ///
+    /// Used by dedicated servers and CI; no Vulkan or audio device is touched.
+    pub fn new_headless() -> Self {
+        let mut engine = Self::new();
+        engine.headless = true;
+        engine.initialize();
+        engine
+    }
+    
+    /// Initialize all subsystems (simulation only when headless)
+    pub fn initialize(&mut self) -> InitReport {
+        if self.headless {
+            self.initialize_with(&Subsystem::client_only())
+        } else {
+            self.initialize_with(&[])
+        }
+    }
+    
+    /// Initialize subsystems, skipping the disabled ones
+    ///
+    /// A subsystem that fails to come up is reported and left absent;
+    /// the rest of the engine keeps running without it.
+    pub fn initialize_with(&mut self, disabled: &[Subsystem]) -> InitReport {
         if self.initialized {
-            return Ok(());
+            return self.report.clone();
         }
         
         log::info!("╔════════════════════════════════════════════════════════════════╗");
         log::info!("║               LIBS ENGINE - Initializing                       ║");
         log::info!("╚════════════════════════════════════════════════════════════════╝");
         
-        // Note: Vulkan init is deferred until window surface is available
-        log::info!("Renderer: Ready (Vulkan init deferred)");
-        log::info!("ECS: {} threads available", num_cpus::get() - 2);
-        log::info!("Memory: Void Manager active");
-        log::info!("Network: Predictive netcode enabled");
-        log::info!("Audio: Ray-traced audio ready");
+        let mut report = InitReport::default();
+        
+        for subsystem in Subsystem::all() {
+            if disabled.contains(&subsystem) {
+                log::info!("{:?}: Disabled", subsystem);
+                report.failed.push((subsystem, "disabled".to_string()));
+                continue;
+            }
+            
+            match self.start_subsystem(subsystem) {
+                Ok(()) => report.ready.push(subsystem),
+                Err(e) => {
+                    log::warn!("{:?}: Init failed: {} - continuing without it", subsystem, e);
+                    report.failed.push((subsystem, e));
+                }
+            }
+        }
         
         self.initialized = true;
+        self.report = report.clone();
         
-        log::info!("LIBS Engine initialized successfully!");
+        log::info!("LIBS Engine initialized ({}/{} subsystems)", report.ready.len(), Subsystem::all().len());
+        
+        report
+    }
+    
+    /// Bring up a single subsystem; constructor errors are returned for the report
+    ///
+    /// Release builds abort on panic, so constructors report failure through
+    /// `Result` rather than unwinding.
+    fn start_subsystem(&mut self, subsystem: Subsystem) -> Result<(), String> {
+        if let (false, Some(feature)) = (subsystem.is_compiled(), subsystem.feature()) {
+            return Err(format!("built without the \"{}\" feature", feature));
+        }
         
+        match subsystem {
+            Subsystem::Memory => {
+                log::info!("Memory: Void Manager active");
+            }
+            Subsystem::Ecs => {
+                let jobs = util::jobs::JobSystem::try_global()?;
+                self.ecs = Some(ecs::EcsWorld::new());
+                log::info!("ECS: {} threads available", jobs.thread_count());
+            }
+            Subsystem::World => {
+                self.world = Some(world::WorldManager::new());
+                log::info!("World: Chunk storage ready");
+            }
+            #[cfg(feature = "vulkan")]
+            Subsystem::Renderer => {
+                // Note: Vulkan init is deferred until window surface is available
+                self.renderer = Some(renderer::quantum::QuantumRenderer::new().map_err(|e| e.to_string())?);
+                log::info!("Renderer: Ready (Vulkan init deferred)");
+            }
+            Subsystem::Netcode => {
+                self.netcode = Some(network::prediction::PredictiveNetcode::new());
+                log::info!("Network: Predictive netcode enabled");
+            }
+            #[cfg(feature = "audio")]
+            Subsystem::Audio => {
+                self.audio = Some(audio::raytracer::AudioRaytracer::new()?);
+                log::info!("Audio: Ray-traced audio ready");
+            }
+            #[allow(unreachable_patterns)]
+            _ => {}
+        }
         Ok(())
     }
     
     /// Process one tick
     pub fn tick(&mut self, delta_time: f32) {
+        self.record_call(|| replay::ReplayCall::Tick(delta_time));
         if !self.initialized {
             return;
         }
         
         // ECS parallel tick
-        self.ecs.parallel_tick(delta_time);
+        if let Some(ecs) = &mut self.ecs {
+            ecs.parallel_tick(delta_time);
+        }
+        
+        // Block updates and chunk meshing
+        if let Some(world) = &mut self.world {
+            world.tick();
+        }
         
         // Process audio
         // (audio processing happens on separate thread)
     }
     
+    /// Submit chunk data; returns the chunk handle (None without a world)
+    pub fn submit_chunk(&mut self, x: i32, z: i32, data: &[u8]) -> Option<i64> {
+        self.record_call(|| replay::ReplayCall::Command(lockstep::Command::SubmitChunk { x, z, data: data.to_vec() }));
+        self.world.as_mut().map(|world| world.submit_chunk(x, z, data))
+    }
+    
+    /// Reconcile with an authoritative server state; false if it could not be applied
+    pub fn reconcile(&mut self, tick: u64, server_state: &[u8]) -> bool {
+        match &mut self.netcode {
+            Some(netcode) => {
+                netcode.reconcile(tick, server_state);
+                netcode.ingest_snapshot(tick, network::snapshot::SnapshotData::Keyframe(server_state.to_vec()))
+            }
+            None => false,
+        }
+    }
+    
+    /// Check if running without renderer and audio
+    pub fn is_headless(&self) -> bool {
+        self.headless
+    }
+    
     /// Begin frame rendering
     pub fn begin_frame(&mut self) {
-        if !self.initialized {
-            return;
-        }
-        
         // Begin Vulkan frame (if initialized)
-        if self.renderer.is_initialized() {
-            let _ = self.renderer.begin_frame();
+        #[cfg(feature = "vulkan")]
+        if let (true, Some(renderer)) = (self.initialized, &mut self.renderer) {
+            if renderer.is_initialized() {
+                let _ = renderer.begin_frame();
+            }
         }
     }
     
     /// End frame rendering
     pub fn end_frame(&mut self) {
-        if !self.initialized {
-            return;
+        #[cfg(feature = "vulkan")]
+        if let (true, Some(renderer)) = (self.initialized, &mut self.renderer) {
+            renderer.end_frame();
         }
-        
-        self.renderer.end_frame();
     }
     
     /// Shutdown
     pub fn shutdown(&mut self) {
         log::info!("LIBS Engine shutting down...");
         
-        self.renderer.shutdown();
-        self.ecs.clear();
+        self.stop_recording();
+        #[cfg(feature = "vulkan")]
+        if let Some(mut renderer) = self.renderer.take() {
+            renderer.shutdown();
+        }
+        if let Some(mut ecs) = self.ecs.take() {
+            ecs.clear();
+        }
+        self.world = None;
         self.memory.clear();
-        self.netcode.clear();
-        self.audio.clear();
+        if let Some(mut netcode) = self.netcode.take() {
+            netcode.clear();
+        }
+        #[cfg(feature = "audio")]
+        if let Some(mut audio) = self.audio.take() {
+            audio.clear();
+        }
         
+        self.report = InitReport::default();
         self.initialized = false;
         
         log::info!("LIBS Engine shutdown complete");
@@ -176,13 +406,19 @@ impl LibsEngine {
     pub fn is_initialized(&self) -> bool {
         self.initialized
     }
+    
+    /// Get the last initialization report
+    pub fn init_report(&self) -> &InitReport {
+        &self.report
+    }
 }
 
 /// Initialize the LIBS library
+///
+/// Safe to call from any thread and any number of times; every caller
+/// observes the same engine instance. Re-initializes after `shutdown`.
 pub fn initialize() -> bool {
-    let mut success = false;
-    
-    INIT.call_once(|| {
+    let engine = ENGINE.get_or_init(|| {
         init_logging();
         
         log::info!("╔════════════════════════════════════════════════════════════════╗");
@@ -192,31 +428,28 @@ pub fn initialize() -> bool {
         log::info!("Platform: {} {}", std::env::consts::OS, std::env::consts::ARCH);
         log::info!("CPU Cores: {}", num_cpus::get());
         
-        // Create engine
-        let mut engine = LibsEngine::new();
-        
-        if let Err(e) = engine.initialize() {
-            log::error!("Engine init failed: {}", e);
-            return;
-        }
-        
-        // Store global instance
-        unsafe {
-            ENGINE = Some(Arc::new(RwLock::new(engine)));
-        }
-        
-        INITIALIZED.store(true, Ordering::SeqCst);
-        success = true;
-        
-        log::info!("LIBS Core initialization complete");
+        Arc::new(RwLock::new(LibsEngine::new()))
     });
     
-    INITIALIZED.load(Ordering::SeqCst)
+    let mut engine = engine.write();
+    if engine.is_initialized() {
+        return true;
+    }
+    
+    let report = engine.initialize();
+    for (subsystem, reason) in &report.failed {
+        log::error!("{:?} unavailable: {}", subsystem, reason);
+    }
+    
+    INITIALIZED.store(true, Ordering::SeqCst);
+    log::info!("LIBS Core initialization complete");
+    
+    true
 }
 
 /// Get engine instance
 pub fn get_engine() -> Option<Arc<RwLock<LibsEngine>>> {
-    unsafe { ENGINE.clone() }
+    ENGINE.get().cloned()
 }
 
 /// Check if initialized
@@ -226,18 +459,122 @@ pub fn is_initialized() -> bool {
 
 /// Shutdown
 pub fn shutdown() {
-    if let Some(engine) = get_engine() {
+    if let Some(engine) = ENGINE.get() {
         engine.write().shutdown();
     }
     INITIALIZED.store(false, Ordering::SeqCst);
 }
 
-/// Initialize logging
+/// Initialize logging (filtered by `RUST_LOG`, adjustable with `set_log_level`)
 fn init_logging() {
-    use tracing_subscriber::fmt;
-    use tracing_subscriber::prelude::*;
-    
-    let _ = tracing_subscriber::registry()
-        .with(fmt::layer())
-        .try_init();
+    util::logging::init();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_concurrent_initialize_shares_engine() {
+        let handles: Vec<_> = (0..8)
+            .map(|_| std::thread::spawn(|| {
+                assert!(initialize());
+                get_engine().unwrap()
+            }))
+            .collect();
+        let engines: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+
+        assert!(engines.iter().all(|e| Arc::ptr_eq(e, &engines[0])));
+        assert!(is_initialized());
+        assert!(engines[0].read().is_initialized());
+
+        shutdown();
+        assert!(!is_initialized());
+        assert!(!engines[0].read().is_initialized());
+
+        // Same instance comes back up after a reset
+        assert!(initialize());
+        assert!(Arc::ptr_eq(&get_engine().unwrap(), &engines[0]));
+        shutdown();
+    }
+
+    #[test]
+    #[cfg(all(feature = "vulkan", feature = "audio"))]
+    fn test_partial_init_without_audio() {
+        let mut engine = LibsEngine::new();
+        let report = engine.initialize_with(&[Subsystem::Audio]);
+
+        assert!(engine.is_initialized());
+        assert!(!report.is_complete());
+        assert!(!report.is_ready(Subsystem::Audio));
+        assert_eq!(report.failure(Subsystem::Audio), Some("disabled"));
+        assert!(report.is_ready(Subsystem::Ecs));
+        assert!(report.is_ready(Subsystem::Renderer));
+        assert!(engine.audio.is_none());
+        assert!(engine.ecs.is_some());
+
+        // Absent subsystems are skipped
+        engine.tick(0.05);
+        engine.begin_frame();
+        engine.end_frame();
+
+        engine.shutdown();
+        assert!(engine.ecs.is_none());
+    }
+
+    #[test]
+    fn test_headless_simulation_is_deterministic() {
+        fn run() -> (u64, Vec<u16>, Option<glam::Vec3>, Vec<u8>) {
+            let mut engine = LibsEngine::new_headless();
+            assert!(engine.is_headless());
+            #[cfg(feature = "vulkan")]
+            assert!(engine.renderer.is_none());
+            #[cfg(feature = "audio")]
+            assert!(engine.audio.is_none());
+            assert!(engine.init_report().is_ready(Subsystem::World));
+
+            engine.submit_chunk(0, 0, &[]).unwrap();
+            engine.ecs.as_mut().unwrap().spawn_entity(1, 0, 0.0, 64.0, 0.0);
+
+            for tick in 0..100u64 {
+                let world = engine.world.as_mut().unwrap();
+                world.set_block((tick % 16) as i32, 64 + (tick / 16) as i32, 3, 1 + (tick % 4) as u32);
+                engine.ecs.as_mut().unwrap().update_entity(1, tick as f64 * 0.5, 64.0, 0.0, 0.0, 0.0);
+                engine.reconcile(tick, &tick.to_le_bytes());
+                engine.tick(0.05);
+            }
+
+            let world = engine.world.as_ref().unwrap();
+            let blocks = (0..16).map(|x| world.get_block(x, 66, 3)).collect();
+            let ecs = engine.ecs.as_ref().unwrap();
+            (
+                world.current_tick(),
+                blocks,
+                ecs.external_entity(1).and_then(|entity| ecs.position_at_tick(entity, 95)),
+                engine.netcode.as_ref().unwrap().latest_reconstructed(),
+            )
+        }
+
+        let first = run();
+        assert_eq!(first.0, 100);
+        assert_eq!(first.2, Some(glam::Vec3::new(47.5, 64.0, 0.0)));
+        assert_eq!(first.3, 99u64.to_le_bytes());
+        assert_eq!(first, run());
+    }
+
+    #[test]
+    #[cfg(not(feature = "vulkan"))]
+    fn test_core_runs_without_client_features() {
+        let mut engine = LibsEngine::new();
+        let report = engine.initialize();
+
+        assert!(report.is_ready(Subsystem::Ecs) && report.is_ready(Subsystem::World));
+        assert_eq!(report.failure(Subsystem::Renderer), Some("built without the \"vulkan\" feature"));
+
+        engine.submit_chunk(0, 0, &[]).unwrap();
+        engine.tick(0.05);
+        engine.begin_frame();
+        engine.end_frame();
+        assert_eq!(engine.world.as_ref().unwrap().current_tick(), 1);
+    }
 }
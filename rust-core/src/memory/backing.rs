@@ -0,0 +1,67 @@
+//! # Backing Allocators
+//!
+//! The allocator underneath `MemoryManager` and `VoidManager`. Both keep
+//! their own byte and allocation accounting and only hand the raw
+//! `alloc`/`dealloc` to a `BackingAllocator`, so a deployment can swap in
+//! mimalloc or jemalloc to benchmark them without touching call sites.
+//!
+//! Any `GlobalAlloc` works through `GlobalAllocAdapter`, e.g.
+//! `GlobalAllocAdapter(mimalloc::MiMalloc)` or
+//! `GlobalAllocAdapter(tikv_jemallocator::Jemalloc)`; this crate doesn't
+//! depend on either.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+
+/// Raw allocator the memory managers allocate through
+pub trait BackingAllocator: Send + Sync {
+    /// Allocate memory for `layout`; null on failure
+    ///
+    /// # Safety
+    /// `layout` must have a non-zero size.
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8;
+
+    /// Free memory from `alloc`
+    ///
+    /// # Safety
+    /// `ptr` must come from this allocator's `alloc` with the same `layout`.
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout);
+
+    /// Get a name for logs
+    fn name(&self) -> &'static str;
+}
+
+/// The platform allocator
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemAllocator;
+
+impl BackingAllocator for SystemAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    fn name(&self) -> &'static str {
+        "system"
+    }
+}
+
+/// Any `GlobalAlloc` (mimalloc, jemalloc, ...) used as a backing allocator
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GlobalAllocAdapter<A>(pub A);
+
+impl<A: GlobalAlloc + Send + Sync> BackingAllocator for GlobalAllocAdapter<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        self.0.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.0.dealloc(ptr, layout)
+    }
+
+    fn name(&self) -> &'static str {
+        std::any::type_name::<A>()
+    }
+}
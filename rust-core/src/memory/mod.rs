@@ -1,22 +1,86 @@
 //! # Memory Management Module
 //! 
 //! Off-heap memory management for avoiding GC pauses.
+//!
+//! `MemoryManager` allocations are held to a ceiling (`max_offheap_mb` of
+//! the engine config): an allocation that would cross it returns `None`
+//! and raises `EngineEvent::OffheapBudgetExceeded` instead of running the
+//! process out of memory.
 
 pub mod void_manager;
+pub mod backing;
 
-use std::alloc::{alloc, dealloc, Layout};
+use std::alloc::Layout;
 use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock};
+
+use parking_lot::Mutex;
+
+use crate::events::{EngineEvent, EventQueue};
+
+pub use backing::{BackingAllocator, GlobalAllocAdapter, SystemAllocator};
 
 /// Global memory tracking
 static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
 static ALLOCATION_COUNT: AtomicUsize = AtomicUsize::new(0);
 
+/// Allocator behind `MemoryManager`, fixed on first use
+static BACKING: OnceLock<Arc<dyn BackingAllocator>> = OnceLock::new();
+
+/// Ceiling on `ALLOCATED_BYTES` (`usize::MAX` = unlimited)
+static LIMIT_BYTES: AtomicUsize = AtomicUsize::new(usize::MAX);
+
+/// Queue told about allocations refused by the ceiling
+static EVENTS: Mutex<Option<EventQueue>> = Mutex::new(None);
+
 /// Memory manager for off-heap allocations
 pub struct MemoryManager;
 
 impl MemoryManager {
-    /// Allocate memory
+    /// Install the backing allocator
+    ///
+    /// Must run before the first allocation; fails once the allocator is in
+    /// use, since memory must be freed by the allocator that made it.
+    pub fn install_allocator(allocator: Arc<dyn BackingAllocator>) -> Result<(), String> {
+        let name = allocator.name();
+        BACKING
+            .set(allocator)
+            .map_err(|_| format!("cannot switch to the {} allocator: {} is already in use", name, Self::allocator().name()))?;
+        log::info!("Off-heap allocator: {}", name);
+        Ok(())
+    }
+    
+    /// Get the backing allocator (the system allocator unless one was installed)
+    pub fn allocator() -> &'static dyn BackingAllocator {
+        BACKING.get_or_init(|| Arc::new(SystemAllocator)).as_ref()
+    }
+    
+    /// Set the most bytes allocated at once (`usize::MAX` = unlimited)
+    ///
+    /// Lowering it below the current usage frees nothing; allocations fail
+    /// until enough is freed.
+    pub fn set_limit(bytes: usize) {
+        LIMIT_BYTES.store(bytes, Ordering::SeqCst);
+        log::info!("Off-heap limit: {} bytes", bytes);
+    }
+    
+    /// Get the allocation ceiling
+    pub fn limit() -> usize {
+        LIMIT_BYTES.load(Ordering::SeqCst)
+    }
+    
+    /// Route `OffheapBudgetExceeded` events to `events`
+    pub fn set_event_queue(events: EventQueue) {
+        *EVENTS.lock() = Some(events);
+    }
+    
+    /// Allocate memory; `None` if it would exceed the limit
     pub fn allocate(size: usize) -> Option<*mut u8> {
+        Self::allocate_with(Self::allocator(), size)
+    }
+    
+    /// Allocate through `allocator`, counting the allocation
+    fn allocate_with(allocator: &dyn BackingAllocator, size: usize) -> Option<*mut u8> {
         if size == 0 {
             return None;
         }
@@ -26,12 +90,29 @@ impl MemoryManager {
             Err(_) => return None,
         };
         
+        // Reserve the bytes first so concurrent callers can't overshoot together
+        let limit = Self::limit();
+        let reserved = ALLOCATED_BYTES.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |allocated| {
+            allocated.checked_add(size).filter(|&total| total <= limit)
+        });
+        if let Err(allocated) = reserved {
+            log::warn!("Off-heap allocation of {} bytes refused: {} of {} bytes in use", size, allocated, limit);
+            if let Some(events) = EVENTS.lock().as_ref() {
+                events.emit(EngineEvent::OffheapBudgetExceeded {
+                    requested_bytes: size as u64,
+                    allocated_bytes: allocated as u64,
+                    limit_bytes: limit as u64,
+                });
+            }
+            return None;
+        }
+        
         unsafe {
-            let ptr = alloc(layout);
+            let ptr = allocator.alloc(layout);
             if ptr.is_null() {
+                ALLOCATED_BYTES.fetch_sub(size, Ordering::SeqCst);
                 None
             } else {
-                ALLOCATED_BYTES.fetch_add(size, Ordering::SeqCst);
                 ALLOCATION_COUNT.fetch_add(1, Ordering::SeqCst);
                 Some(ptr)
             }
@@ -54,12 +135,17 @@ impl MemoryManager {
     
     /// Free memory with known size
     pub unsafe fn free_sized(ptr: *mut u8, size: usize) {
+        Self::free_sized_with(Self::allocator(), ptr, size);
+    }
+    
+    /// Free through `allocator`, uncounting the allocation
+    unsafe fn free_sized_with(allocator: &dyn BackingAllocator, ptr: *mut u8, size: usize) {
         if ptr.is_null() || size == 0 {
             return;
         }
         
         if let Ok(layout) = Layout::from_size_align(size, 16) {
-            dealloc(ptr, layout);
+            allocator.dealloc(ptr, layout);
             ALLOCATED_BYTES.fetch_sub(size, Ordering::SeqCst);
             ALLOCATION_COUNT.fetch_sub(1, Ordering::SeqCst);
         }
@@ -77,7 +163,7 @@ impl MemoryManager {
 }
 
 /// Initialize memory subsystem
-pub fn init() -> Result<(), String> {
+pub fn init() -> Result<(), crate::LibsError> {
     log::debug!("Memory subsystem initialized");
     Ok(())
 }
@@ -101,8 +187,12 @@ pub fn shutdown() {
 mod tests {
     use super::*;
     
+    /// Serializes tests that go through the global limit
+    static GLOBAL_ALLOCATIONS: Mutex<()> = Mutex::new(());
+    
     #[test]
     fn test_allocate_free() {
+        let _global = GLOBAL_ALLOCATIONS.lock();
         let ptr = MemoryManager::allocate(1024);
         assert!(ptr.is_some());
         
@@ -110,4 +200,82 @@ mod tests {
             MemoryManager::free_sized(ptr.unwrap(), 1024);
         }
     }
+    
+    #[test]
+    fn test_limit_refuses_allocation_past_it() {
+        let _global = GLOBAL_ALLOCATIONS.lock();
+        let events = EventQueue::new();
+        MemoryManager::set_event_queue(events.clone());
+        let base = MemoryManager::get_allocated_bytes();
+        MemoryManager::set_limit(base + 4096);
+        
+        let blocks: Vec<*mut u8> = (0..4).map(|_| MemoryManager::allocate(1024).unwrap()).collect();
+        assert_eq!(MemoryManager::get_allocated_bytes(), base + 4096);
+        assert!(MemoryManager::allocate(1).is_none());
+        assert!(MemoryManager::allocate(usize::MAX).is_none());
+        assert!(MemoryManager::get_allocated_bytes() <= MemoryManager::limit());
+        assert_eq!(events.drain()[0], EngineEvent::OffheapBudgetExceeded {
+            requested_bytes: 1,
+            allocated_bytes: (base + 4096) as u64,
+            limit_bytes: (base + 4096) as u64,
+        });
+        
+        // Freeing makes room again
+        unsafe { MemoryManager::free_sized(blocks[0], 1024) };
+        let again = MemoryManager::allocate(1024).unwrap();
+        
+        MemoryManager::set_limit(usize::MAX);
+        for ptr in blocks.into_iter().skip(1).chain([again]) {
+            unsafe { MemoryManager::free_sized(ptr, 1024) };
+        }
+        assert_eq!(MemoryManager::get_allocated_bytes(), base);
+    }
+    
+    /// System allocator that counts its calls
+    #[derive(Default)]
+    struct CountingAllocator {
+        allocs: AtomicUsize,
+        deallocs: AtomicUsize,
+    }
+    
+    impl BackingAllocator for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            self.allocs.fetch_add(1, Ordering::SeqCst);
+            SystemAllocator.alloc(layout)
+        }
+        
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            self.deallocs.fetch_add(1, Ordering::SeqCst);
+            SystemAllocator.dealloc(ptr, layout)
+        }
+        
+        fn name(&self) -> &'static str {
+            "counting"
+        }
+    }
+    
+    #[test]
+    fn test_allocations_route_through_backing_allocator() {
+        let counting = Arc::new(CountingAllocator::default());
+        
+        let ptr = MemoryManager::allocate_with(counting.as_ref(), 256).unwrap();
+        assert!(MemoryManager::allocate_with(counting.as_ref(), 0).is_none());
+        unsafe { MemoryManager::free_sized_with(counting.as_ref(), ptr, 256) };
+        assert_eq!(counting.allocs.load(Ordering::SeqCst), 1);
+        assert_eq!(counting.deallocs.load(Ordering::SeqCst), 1);
+        
+        // The void manager takes the same allocator
+        let manager = void_manager::VoidManager::with_allocator(counting.clone());
+        let handle = manager.allocate(64, void_manager::AssetType::Texture).unwrap();
+        manager.free(handle).unwrap();
+        let kept = manager.allocate(32, void_manager::AssetType::Sound).unwrap();
+        assert_eq!(manager.get_stats().current_usage, kept.size as u64);
+        drop(manager);
+        assert_eq!(counting.allocs.load(Ordering::SeqCst), 3);
+        assert_eq!(counting.deallocs.load(Ordering::SeqCst), 3);
+        
+        // The global allocator is fixed once in use
+        MemoryManager::allocator();
+        assert!(MemoryManager::install_allocator(counting).is_err());
+    }
 }
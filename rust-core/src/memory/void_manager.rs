@@ -9,7 +9,9 @@ use std::sync::Arc;
 use std::collections::HashMap;
 use parking_lot::RwLock;
 use std::ptr::NonNull;
-use std::alloc::{alloc, dealloc, Layout};
+use std::alloc::Layout;
+
+use super::backing::{BackingAllocator, SystemAllocator};
 
 /// Memory handle (8 bytes, stored in Java)
 #[repr(C)]
@@ -45,6 +47,8 @@ pub struct VoidManager {
     stats: RwLock<VoidStats>,
     /// Next allocation ID
     next_id: std::sync::atomic::AtomicU64,
+    /// Allocator the asset memory comes from
+    allocator: Arc<dyn BackingAllocator>,
 }
 
 /// Allocation metadata
@@ -56,6 +60,10 @@ struct AllocationInfo {
     hash: u64,
 }
 
+// Owned allocation, only reachable through the manager's locks
+unsafe impl Send for AllocationInfo {}
+unsafe impl Sync for AllocationInfo {}
+
 /// Memory statistics
 #[derive(Default, Clone)]
 pub struct VoidStats {
@@ -71,13 +79,19 @@ pub struct VoidStats {
 impl VoidManager {
     /// Create new Void Manager
     pub fn new() -> Arc<Self> {
-        log::info!("Initializing Void Manager (Off-Heap Memory)");
+        Self::with_allocator(Arc::new(SystemAllocator))
+    }
+    
+    /// Create a Void Manager allocating through `allocator`
+    pub fn with_allocator(allocator: Arc<dyn BackingAllocator>) -> Arc<Self> {
+        log::info!("Initializing Void Manager (Off-Heap Memory, {} allocator)", allocator.name());
         
         Arc::new(Self {
             allocations: RwLock::new(HashMap::new()),
             dedup_map: RwLock::new(HashMap::new()),
             stats: RwLock::new(VoidStats::default()),
             next_id: std::sync::atomic::AtomicU64::new(1),
+            allocator,
         })
     }
     
@@ -90,7 +104,7 @@ impl VoidManager {
         let layout = Layout::from_size_align(size, 16)
             .map_err(|_| VoidError::InvalidAlignment)?;
         
-        let ptr = unsafe { alloc(layout) };
+        let ptr = unsafe { self.allocator.alloc(layout) };
         
         if ptr.is_null() {
             return Err(VoidError::OutOfMemory);
@@ -188,7 +202,7 @@ impl VoidManager {
             if info.ref_count == 0 {
                 // Actually free
                 unsafe {
-                    dealloc(info.ptr.as_ptr(), info.layout);
+                    self.allocator.dealloc(info.ptr.as_ptr(), info.layout);
                 }
                 
                 // Remove from dedup map if present
@@ -312,7 +326,7 @@ impl VoidManager {
         
         for (_, info) in allocations.drain() {
             unsafe {
-                dealloc(info.ptr.as_ptr(), info.layout);
+                self.allocator.dealloc(info.ptr.as_ptr(), info.layout);
             }
         }
         
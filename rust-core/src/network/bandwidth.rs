@@ -0,0 +1,157 @@
+//! # Bandwidth Accounting
+//!
+//! Windowed send/receive rate measurement and a token-bucket limiter
+//! for pacing outgoing packets per connection.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Measurement window for per-second rates
+const RATE_WINDOW: Duration = Duration::from_secs(1);
+
+/// Windowed byte counter for one direction
+#[derive(Debug, Default)]
+struct RateWindow {
+    /// (time, bytes) samples inside the window
+    samples: VecDeque<(Instant, usize)>,
+    /// Bytes inside the window
+    window_bytes: usize,
+    /// Bytes since creation
+    total: u64,
+}
+
+impl RateWindow {
+    fn record(&mut self, bytes: usize, now: Instant) {
+        self.samples.push_back((now, bytes));
+        self.window_bytes += bytes;
+        self.total += bytes as u64;
+        self.prune(now);
+    }
+
+    fn prune(&mut self, now: Instant) {
+        while let Some(&(time, bytes)) = self.samples.front() {
+            if now.duration_since(time) < RATE_WINDOW {
+                break;
+            }
+            self.samples.pop_front();
+            self.window_bytes -= bytes;
+        }
+    }
+}
+
+/// Per-connection bandwidth meter
+#[derive(Debug, Default)]
+pub struct BandwidthMeter {
+    sent: RateWindow,
+    received: RateWindow,
+}
+
+impl BandwidthMeter {
+    /// Create a new meter
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record bytes sent
+    pub fn record_sent(&mut self, bytes: usize, now: Instant) {
+        self.sent.record(bytes, now);
+    }
+
+    /// Record bytes received
+    pub fn record_received(&mut self, bytes: usize, now: Instant) {
+        self.received.record(bytes, now);
+    }
+
+    /// Drop samples that fell out of the window
+    pub fn update(&mut self, now: Instant) {
+        self.sent.prune(now);
+        self.received.prune(now);
+    }
+
+    /// Bytes sent over the last second
+    pub fn bytes_sent_per_sec(&self) -> usize {
+        self.sent.window_bytes
+    }
+
+    /// Bytes received over the last second
+    pub fn bytes_recv_per_sec(&self) -> usize {
+        self.received.window_bytes
+    }
+
+    /// Total bytes sent
+    pub fn total_sent(&self) -> u64 {
+        self.sent.total
+    }
+
+    /// Total bytes received
+    pub fn total_received(&self) -> u64 {
+        self.received.total
+    }
+}
+
+/// Token bucket (one second of burst capacity)
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    /// Refill rate (bytes per second)
+    rate: f64,
+    /// Available tokens
+    tokens: f64,
+    /// Last refill time (set on first use)
+    last_refill: Option<Instant>,
+}
+
+impl TokenBucket {
+    /// Create a full bucket
+    pub fn new(max_bytes_per_sec: u32) -> Self {
+        Self {
+            rate: max_bytes_per_sec as f64,
+            tokens: max_bytes_per_sec as f64,
+            last_refill: None,
+        }
+    }
+
+    /// Get refill rate
+    pub fn rate(&self) -> u32 {
+        self.rate as u32
+    }
+
+    /// Take tokens for a packet if the budget allows
+    ///
+    /// A packet larger than the whole bucket is let through once the
+    /// bucket is full, so it is delayed rather than stuck forever.
+    pub fn try_consume(&mut self, bytes: usize, now: Instant) -> bool {
+        if let Some(last) = self.last_refill {
+            let elapsed = now.saturating_duration_since(last).as_secs_f64();
+            self.tokens = (self.tokens + elapsed * self.rate).min(self.rate);
+        }
+        self.last_refill = Some(now);
+
+        let cost = bytes as f64;
+        if cost <= self.tokens || self.tokens >= self.rate {
+            self.tokens -= cost;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_meter_window_expires() {
+        let start = Instant::now();
+        let mut meter = BandwidthMeter::new();
+        meter.record_sent(400, start);
+        meter.record_sent(600, start + Duration::from_millis(500));
+        meter.record_received(50, start);
+        assert_eq!(meter.bytes_sent_per_sec(), 1000);
+
+        meter.update(start + Duration::from_millis(1200));
+        assert_eq!(meter.bytes_sent_per_sec(), 600);
+        assert_eq!(meter.bytes_recv_per_sec(), 0);
+        assert_eq!(meter.total_sent(), 1000);
+    }
+}
@@ -0,0 +1,135 @@
+//! # Packet Batching
+//!
+//! Coalesces many small messages (entity updates mostly) into one `BATCH`
+//! packet so they share a single header and datagram. Each message is
+//! framed as type (u16), length (u16) and payload; a packet is sealed when
+//! the next message would push it past the MTU, or on `flush`.
+
+use super::{PacketError, PacketHeader};
+
+/// Default datagram budget, small enough to avoid IP fragmentation
+pub const DEFAULT_MTU: usize = 1200;
+
+/// Bytes of framing before each message
+pub const MESSAGE_HEADER_SIZE: usize = 4;
+
+/// Packs messages into MTU-sized batch payloads
+pub struct PacketAggregator {
+    /// Largest payload, leaving room for the packet header
+    max_payload: usize,
+    /// Batch being filled
+    current: Vec<u8>,
+    /// Messages in the current batch
+    messages: usize,
+}
+
+impl PacketAggregator {
+    /// Create an aggregator for datagrams of up to `mtu` bytes
+    pub fn new(mtu: usize) -> Self {
+        let max_payload = mtu.saturating_sub(PacketHeader::SIZE).max(MESSAGE_HEADER_SIZE + 1);
+        Self { max_payload, current: Vec::with_capacity(max_payload), messages: 0 }
+    }
+
+    /// Add a message; returns the previous batch if this one didn't fit in it
+    ///
+    /// Messages that can't fit in an empty batch are rejected; send those
+    /// on their own with `flags::FRAGMENTED`.
+    pub fn push(&mut self, message_type: u16, payload: &[u8]) -> Result<Option<Vec<u8>>, String> {
+        let framed = MESSAGE_HEADER_SIZE + payload.len();
+        if framed > self.max_payload || payload.len() > u16::MAX as usize {
+            return Err(format!(
+                "Message of {} bytes exceeds the batch limit of {}",
+                payload.len(),
+                self.max_payload - MESSAGE_HEADER_SIZE
+            ));
+        }
+
+        let sealed = if self.current.len() + framed > self.max_payload { self.flush() } else { None };
+        self.current.extend_from_slice(&message_type.to_le_bytes());
+        self.current.extend_from_slice(&(payload.len() as u16).to_le_bytes());
+        self.current.extend_from_slice(payload);
+        self.messages += 1;
+        Ok(sealed)
+    }
+
+    /// Seal the current batch (None if empty)
+    pub fn flush(&mut self) -> Option<Vec<u8>> {
+        if self.messages == 0 {
+            return None;
+        }
+        self.messages = 0;
+        Some(std::mem::replace(&mut self.current, Vec::with_capacity(self.max_payload)))
+    }
+
+    /// Get the number of messages waiting in the current batch
+    pub fn pending(&self) -> usize {
+        self.messages
+    }
+
+    /// Get the largest batch payload in bytes
+    pub fn max_payload(&self) -> usize {
+        self.max_payload
+    }
+}
+
+impl Default for PacketAggregator {
+    fn default() -> Self {
+        Self::new(DEFAULT_MTU)
+    }
+}
+
+/// Split a batch payload back into (type, payload) messages
+pub fn unpack(bytes: &[u8]) -> Result<Vec<(u16, Vec<u8>)>, PacketError> {
+    let mut messages = Vec::new();
+    let mut rest = bytes;
+    while !rest.is_empty() {
+        if rest.len() < MESSAGE_HEADER_SIZE {
+            return Err(PacketError::TooShort(rest.len()));
+        }
+        let message_type = u16::from_le_bytes([rest[0], rest[1]]);
+        let length = u16::from_le_bytes([rest[2], rest[3]]) as usize;
+        let body = &rest[MESSAGE_HEADER_SIZE..];
+        if body.len() < length {
+            return Err(PacketError::LengthMismatch { expected: length as u32, actual: body.len() });
+        }
+        messages.push((message_type, body[..length].to_vec()));
+        rest = &body[length..];
+    }
+    Ok(messages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::packet_type;
+
+    #[test]
+    fn test_batch_roundtrip_and_mtu_split() {
+        let mut aggregator = PacketAggregator::default();
+        let messages: Vec<(u16, Vec<u8>)> = (0..40u8)
+            .map(|i| (packet_type::ENTITY_UPDATE, vec![i; 1 + i as usize % 12]))
+            .collect();
+        for (message_type, payload) in &messages {
+            assert_eq!(aggregator.push(*message_type, payload), Ok(None));
+        }
+        assert_eq!(aggregator.pending(), 40);
+
+        let batch = aggregator.flush().unwrap();
+        assert!(batch.len() <= DEFAULT_MTU - PacketHeader::SIZE);
+        assert_eq!(unpack(&batch).unwrap(), messages);
+        assert!(aggregator.flush().is_none());
+
+        // Past the MTU the full batch is handed back and a new one starts
+        let mut sealed = Vec::new();
+        for i in 0..20u16 {
+            sealed.extend(aggregator.push(i, &[0xAB; 100]).unwrap());
+        }
+        assert_eq!(sealed.len(), 1);
+        assert_eq!(unpack(&sealed[0]).unwrap().len(), aggregator.max_payload() / 104);
+        assert_eq!(aggregator.pending(), 20 - aggregator.max_payload() / 104);
+
+        assert!(aggregator.push(0, &[0; DEFAULT_MTU]).is_err());
+        assert!(matches!(unpack(&batch[..batch.len() - 1]), Err(PacketError::LengthMismatch { .. })));
+        assert_eq!(unpack(&[1, 0]), Err(PacketError::TooShort(2)));
+    }
+}
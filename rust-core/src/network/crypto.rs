@@ -0,0 +1,140 @@
+//! # Packet Encryption
+//!
+//! AES-256-GCM payload encryption for packets flagged `ENCRYPTED`.
+//! Encrypted payloads are laid out as `nonce (12) | ciphertext | tag (16)`;
+//! the header (minus its checksum) is authenticated as associated data.
+
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+
+use super::{flags, PacketError, PacketHeader};
+
+/// Nonce size in bytes
+pub const NONCE_SIZE: usize = 12;
+
+/// Authentication tag size in bytes
+pub const TAG_SIZE: usize = 16;
+
+/// Encrypted packet channel
+pub struct EncryptedChannel {
+    /// AES-256-GCM cipher
+    cipher: Aes256Gcm,
+    /// Random per-channel nonce prefix (keeps both peers' nonces disjoint)
+    nonce_prefix: [u8; 4],
+    /// Packets sealed so far (nonce counter)
+    send_counter: u64,
+}
+
+impl EncryptedChannel {
+    /// Create a channel from an established 32-byte key
+    pub fn new(key: [u8; 32]) -> Self {
+        Self {
+            cipher: Aes256Gcm::new(&key.into()),
+            nonce_prefix: rand::random(),
+            send_counter: 0,
+        }
+    }
+
+    /// Associated data: header bytes without the checksum
+    fn associated_data(header: &PacketHeader) -> [u8; 16] {
+        let mut aad = [0u8; 16];
+        aad.copy_from_slice(&header.to_bytes()[..16]);
+        aad
+    }
+
+    /// Encrypt a payload, setting the `ENCRYPTED` flag, length and checksum on the header
+    pub fn seal(&mut self, header: &mut PacketHeader, payload: &[u8]) -> Result<Vec<u8>, PacketError> {
+        let counter = self.send_counter;
+        self.send_counter = counter.checked_add(1).ok_or(PacketError::NonceExhausted)?;
+
+        let mut nonce = [0u8; NONCE_SIZE];
+        nonce[..4].copy_from_slice(&self.nonce_prefix);
+        nonce[4..].copy_from_slice(&counter.to_le_bytes());
+
+        header.flags |= flags::ENCRYPTED;
+        header.length = (NONCE_SIZE + payload.len() + TAG_SIZE) as u32;
+        let aad = Self::associated_data(header);
+
+        let ciphertext = self.cipher
+            .encrypt(Nonce::from_slice(&nonce), Payload { msg: payload, aad: &aad })
+            .map_err(|_| PacketError::AuthenticationFailed)?;
+
+        let mut sealed = Vec::with_capacity(NONCE_SIZE + ciphertext.len());
+        sealed.extend_from_slice(&nonce);
+        sealed.extend_from_slice(&ciphertext);
+        header.finalize(&sealed);
+
+        Ok(sealed)
+    }
+
+    /// Authenticate and decrypt a payload
+    pub fn open(&self, header: &PacketHeader, sealed: &[u8]) -> Result<Vec<u8>, PacketError> {
+        if header.flags & flags::ENCRYPTED == 0 {
+            return Err(PacketError::NotEncrypted);
+        }
+        if sealed.len() < NONCE_SIZE + TAG_SIZE {
+            return Err(PacketError::TooShort(sealed.len()));
+        }
+
+        let (nonce, ciphertext) = sealed.split_at(NONCE_SIZE);
+        let aad = Self::associated_data(header);
+
+        self.cipher
+            .decrypt(Nonce::from_slice(nonce), Payload { msg: ciphertext, aad: &aad })
+            .map_err(|_| PacketError::AuthenticationFailed)
+    }
+
+    /// Get number of packets sealed
+    pub fn packets_sealed(&self) -> u64 {
+        self.send_counter
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::packet_type;
+
+    #[test]
+    fn test_seal_open_roundtrip() {
+        let key = [7u8; 32];
+        let mut sender = EncryptedChannel::new(key);
+        let receiver = EncryptedChannel::new(key);
+
+        let mut first = PacketHeader::new(packet_type::PLAYER_INPUT, 0, 1);
+        let sealed_a = sender.seal(&mut first, b"jump").unwrap();
+        let mut second = PacketHeader::new(packet_type::PLAYER_INPUT, 0, 2);
+        let sealed_b = sender.seal(&mut second, b"jump").unwrap();
+
+        // Unique nonce per packet
+        assert_ne!(sealed_a[..NONCE_SIZE], sealed_b[..NONCE_SIZE]);
+        assert_ne!(sealed_a, sealed_b);
+
+        let mut packet = first.to_bytes().to_vec();
+        packet.extend_from_slice(&sealed_a);
+        assert_eq!(PacketHeader::validate(&packet), Ok(()));
+
+        let header = PacketHeader::from_bytes(&packet).unwrap();
+        assert_ne!(header.flags & flags::ENCRYPTED, 0);
+        assert_eq!(receiver.open(&header, &packet[PacketHeader::SIZE..]).unwrap(), b"jump");
+    }
+
+    #[test]
+    fn test_tampered_packet_rejected() {
+        let mut channel = EncryptedChannel::new([42u8; 32]);
+        let mut header = PacketHeader::new(packet_type::WORLD_EVENT, 0, 9);
+        let sealed = channel.seal(&mut header, b"explosion at 10 64 10").unwrap();
+
+        let mut tampered = sealed.clone();
+        tampered[NONCE_SIZE + 2] ^= 0x01;
+        assert_eq!(channel.open(&header, &tampered), Err(PacketError::AuthenticationFailed));
+
+        // Header is authenticated too
+        let mut forged = header;
+        forged.sequence += 1;
+        assert_eq!(channel.open(&forged, &sealed), Err(PacketError::AuthenticationFailed));
+
+        let wrong_key = EncryptedChannel::new([43u8; 32]);
+        assert_eq!(wrong_key.open(&header, &sealed), Err(PacketError::AuthenticationFailed));
+    }
+}
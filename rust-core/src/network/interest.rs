@@ -0,0 +1,198 @@
+//! # Interest Management
+//!
+//! Per-client relevance filtering: each client only receives entities and
+//! chunk columns within its view radius. Entities are bucketed in a
+//! chunk-column grid so a query only touches nearby cells.
+
+use std::collections::{HashMap, HashSet};
+use glam::Vec3;
+
+use crate::ecs::Entity;
+
+/// Client identifier
+pub type ClientId = u32;
+
+/// Grid cell size (one chunk column)
+const CELL_SIZE: f32 = 16.0;
+
+/// Changes in what a client should know about since its last query
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RelevanceDelta {
+    /// Newly relevant entities (send full snapshot)
+    pub entered: Vec<Entity>,
+    /// Entities no longer relevant (send despawn)
+    pub left: Vec<Entity>,
+    /// Entities still relevant (send delta update)
+    pub updated: Vec<Entity>,
+    /// Newly relevant chunk columns
+    pub chunks_entered: Vec<(i32, i32)>,
+    /// Chunk columns no longer relevant
+    pub chunks_left: Vec<(i32, i32)>,
+}
+
+/// Per-client view state
+struct ClientView {
+    position: Vec3,
+    entities: HashSet<Entity>,
+    chunks: HashSet<(i32, i32)>,
+}
+
+/// Interest manager
+pub struct InterestManager {
+    /// View radius in blocks
+    view_radius: f32,
+    /// Entities per chunk column
+    grid: HashMap<(i32, i32), HashSet<Entity>>,
+    /// Entity positions
+    entities: HashMap<Entity, Vec3>,
+    /// Connected clients
+    clients: HashMap<ClientId, ClientView>,
+}
+
+/// Chunk column containing a position
+fn cell_of(position: Vec3) -> (i32, i32) {
+    ((position.x / CELL_SIZE).floor() as i32, (position.z / CELL_SIZE).floor() as i32)
+}
+
+impl InterestManager {
+    /// Create an interest manager with a view radius in blocks
+    pub fn new(view_radius: f32) -> Self {
+        Self {
+            view_radius,
+            grid: HashMap::new(),
+            entities: HashMap::new(),
+            clients: HashMap::new(),
+        }
+    }
+
+    /// Insert or move an entity
+    pub fn update_entity(&mut self, entity: Entity, position: Vec3) {
+        if let Some(old) = self.entities.insert(entity, position) {
+            let old_cell = cell_of(old);
+            if old_cell == cell_of(position) {
+                return;
+            }
+            if let Some(cell) = self.grid.get_mut(&old_cell) {
+                cell.remove(&entity);
+                if cell.is_empty() {
+                    self.grid.remove(&old_cell);
+                }
+            }
+        }
+        self.grid.entry(cell_of(position)).or_default().insert(entity);
+    }
+
+    /// Remove an entity
+    pub fn remove_entity(&mut self, entity: Entity) {
+        if let Some(position) = self.entities.remove(&entity) {
+            let key = cell_of(position);
+            if let Some(cell) = self.grid.get_mut(&key) {
+                cell.remove(&entity);
+                if cell.is_empty() {
+                    self.grid.remove(&key);
+                }
+            }
+        }
+    }
+
+    /// Insert or move a client
+    pub fn update_client(&mut self, client: ClientId, position: Vec3) {
+        self.clients.entry(client)
+            .and_modify(|view| view.position = position)
+            .or_insert_with(|| ClientView {
+                position,
+                entities: HashSet::new(),
+                chunks: HashSet::new(),
+            });
+    }
+
+    /// Remove a client
+    pub fn remove_client(&mut self, client: ClientId) {
+        self.clients.remove(&client);
+    }
+
+    /// Compute what changed for a client since its last query
+    pub fn relevant_for(&mut self, client: ClientId) -> RelevanceDelta {
+        let Some(view) = self.clients.get(&client) else {
+            return RelevanceDelta::default();
+        };
+
+        let center = view.position;
+        let (cx, cz) = cell_of(center);
+        let reach = (self.view_radius / CELL_SIZE).ceil() as i32;
+        let radius_sq = self.view_radius * self.view_radius;
+
+        let mut chunks = HashSet::new();
+        let mut entities = HashSet::new();
+        for dx in -reach..=reach {
+            for dz in -reach..=reach {
+                let key = (cx + dx, cz + dz);
+                chunks.insert(key);
+
+                let Some(cell) = self.grid.get(&key) else {
+                    continue;
+                };
+                for &entity in cell {
+                    if self.entities[&entity].distance_squared(center) <= radius_sq {
+                        entities.insert(entity);
+                    }
+                }
+            }
+        }
+
+        let view = self.clients.get_mut(&client).unwrap();
+        let mut delta = RelevanceDelta {
+            entered: entities.difference(&view.entities).copied().collect(),
+            left: view.entities.difference(&entities).copied().collect(),
+            updated: entities.intersection(&view.entities).copied().collect(),
+            chunks_entered: chunks.difference(&view.chunks).copied().collect(),
+            chunks_left: view.chunks.difference(&chunks).copied().collect(),
+        };
+        delta.entered.sort_unstable();
+        delta.left.sort_unstable();
+        delta.updated.sort_unstable();
+        delta.chunks_entered.sort_unstable();
+        delta.chunks_left.sort_unstable();
+
+        view.entities = entities;
+        view.chunks = chunks;
+        delta
+    }
+
+    /// Get number of tracked clients
+    pub fn client_count(&self) -> usize {
+        self.clients.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clients_only_see_local_entities() {
+        let mut interest = InterestManager::new(48.0);
+        let [e1, e2, e3] = [1, 2, 3].map(|i| Entity::new(i, 0));
+        interest.update_entity(e1, Vec3::new(10.0, 64.0, 10.0));
+        interest.update_entity(e2, Vec3::new(-20.0, 70.0, 5.0));
+        interest.update_entity(e3, Vec3::new(5000.0, 64.0, 5000.0));
+        interest.update_client(100, Vec3::new(0.0, 64.0, 0.0));
+        interest.update_client(200, Vec3::new(5010.0, 64.0, 4990.0));
+
+        let near_spawn = interest.relevant_for(100);
+        assert_eq!(near_spawn.entered, vec![e1, e2]);
+        let far_away = interest.relevant_for(200);
+        assert_eq!(far_away.entered, vec![e3]);
+
+        // Entity 2 walks to the far client
+        interest.update_entity(e2, Vec3::new(5000.0, 64.0, 5020.0));
+        let near_spawn = interest.relevant_for(100);
+        assert_eq!(near_spawn.left, vec![e2]);
+        assert_eq!(near_spawn.updated, vec![e1]);
+        assert!(near_spawn.chunks_entered.is_empty());
+
+        let far_away = interest.relevant_for(200);
+        assert_eq!(far_away.entered, vec![e2]);
+        assert_eq!(far_away.updated, vec![e3]);
+    }
+}
@@ -0,0 +1,252 @@
+//! # Snapshot Interpolation
+//!
+//! Client-side smoothing for entities the server owns. Remote entities are
+//! drawn a fixed delay in the past (`render_time = now - interp_delay`), so
+//! there are usually two received snapshots around the render time to blend
+//! between. When the next snapshot is late the position keeps moving along
+//! the last known velocity, but only for a short, capped time: past that the
+//! entity holds still instead of running ahead and snapping back when the
+//! server catches up.
+
+use std::collections::{HashMap, VecDeque};
+
+use glam::Vec3;
+
+/// Default render delay behind the newest data (seconds)
+pub const DEFAULT_INTERP_DELAY: f64 = 0.1;
+
+/// Default longest extrapolation past the newest snapshot (seconds)
+pub const DEFAULT_MAX_EXTRAPOLATION: f64 = 0.25;
+
+/// Snapshots kept per entity
+pub const DEFAULT_CAPACITY: usize = 32;
+
+/// Entity transform carried by snapshots
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Transform {
+    /// World position
+    pub position: Vec3,
+    /// Yaw (degrees)
+    pub yaw: f32,
+    /// Pitch (degrees)
+    pub pitch: f32,
+}
+
+impl Transform {
+    /// Create a transform
+    pub fn new(position: Vec3, yaw: f32, pitch: f32) -> Self {
+        Self { position, yaw, pitch }
+    }
+
+    /// Blend towards `other`; yaw turns the short way round
+    pub fn lerp(&self, other: &Transform, t: f32) -> Transform {
+        let yaw_delta = (other.yaw - self.yaw + 180.0).rem_euclid(360.0) - 180.0;
+        Transform {
+            position: self.position.lerp(other.position, t),
+            yaw: self.yaw + yaw_delta * t,
+            pitch: self.pitch + (other.pitch - self.pitch) * t,
+        }
+    }
+}
+
+/// Timestamped transforms for one remote entity
+#[derive(Debug, Clone)]
+pub struct SnapshotBuffer {
+    /// `(server time, transform)` in increasing time order
+    snapshots: VecDeque<(f64, Transform)>,
+    /// Snapshots kept
+    capacity: usize,
+    /// Render delay (seconds)
+    interp_delay: f64,
+    /// Extrapolation cap (seconds)
+    max_extrapolation: f64,
+}
+
+impl SnapshotBuffer {
+    /// Create a buffer with a render delay and extrapolation cap (seconds)
+    pub fn new(interp_delay: f64, max_extrapolation: f64) -> Self {
+        Self {
+            snapshots: VecDeque::new(),
+            capacity: DEFAULT_CAPACITY,
+            interp_delay,
+            max_extrapolation,
+        }
+    }
+
+    /// Add a snapshot; ones not newer than the latest are dropped
+    pub fn push(&mut self, time: f64, transform: Transform) -> bool {
+        if self.snapshots.back().is_some_and(|&(latest, _)| time <= latest) {
+            return false;
+        }
+        self.snapshots.push_back((time, transform));
+        while self.snapshots.len() > self.capacity {
+            self.snapshots.pop_front();
+        }
+        true
+    }
+
+    /// Get the transform at `render_time`
+    ///
+    /// Between two snapshots the transform is interpolated; before the first
+    /// it is clamped to the first; after the last the position extrapolates
+    /// along the last velocity for at most `max_extrapolation`.
+    pub fn sample(&self, render_time: f64) -> Option<Transform> {
+        let &(first_time, first) = self.snapshots.front()?;
+        if render_time <= first_time {
+            return Some(first);
+        }
+
+        // First snapshot after the render time
+        let next = self.snapshots.partition_point(|&(time, _)| time <= render_time);
+        if next < self.snapshots.len() {
+            let (t0, a) = self.snapshots[next - 1];
+            let (t1, b) = self.snapshots[next];
+            let t = ((render_time - t0) / (t1 - t0)) as f32;
+            return Some(a.lerp(&b, t));
+        }
+
+        let (t1, last) = self.snapshots[next - 1];
+        if next < 2 {
+            return Some(last);
+        }
+        let (t0, previous) = self.snapshots[next - 2];
+        let velocity = (last.position - previous.position) / (t1 - t0) as f32;
+        let ahead = (render_time - t1).min(self.max_extrapolation) as f32;
+        Some(Transform { position: last.position + velocity * ahead, ..last })
+    }
+
+    /// Get the transform to draw at client time `now`
+    pub fn sample_at(&self, now: f64) -> Option<Transform> {
+        self.sample(now - self.interp_delay)
+    }
+
+    /// Get the newest snapshot
+    pub fn latest(&self) -> Option<(f64, Transform)> {
+        self.snapshots.back().copied()
+    }
+
+    /// Set the render delay (seconds)
+    pub fn set_interp_delay(&mut self, delay: f64) {
+        self.interp_delay = delay;
+    }
+
+    /// Get the render delay (seconds)
+    pub fn interp_delay(&self) -> f64 {
+        self.interp_delay
+    }
+
+    /// Get the number of buffered snapshots
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    /// Check if no snapshots have arrived
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    /// Drop all snapshots (e.g. after a teleport)
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+    }
+}
+
+impl Default for SnapshotBuffer {
+    fn default() -> Self {
+        Self::new(DEFAULT_INTERP_DELAY, DEFAULT_MAX_EXTRAPOLATION)
+    }
+}
+
+/// Snapshot buffers for every remote entity
+pub struct RemoteEntities {
+    /// Buffers by entity ID
+    buffers: HashMap<u32, SnapshotBuffer>,
+    /// Render delay for new buffers (seconds)
+    interp_delay: f64,
+    /// Extrapolation cap for new buffers (seconds)
+    max_extrapolation: f64,
+}
+
+impl RemoteEntities {
+    /// Create with a render delay and extrapolation cap (seconds)
+    pub fn new(interp_delay: f64, max_extrapolation: f64) -> Self {
+        Self { buffers: HashMap::new(), interp_delay, max_extrapolation }
+    }
+
+    /// Record a server snapshot of an entity
+    pub fn push(&mut self, entity_id: u32, time: f64, transform: Transform) -> bool {
+        let (delay, cap) = (self.interp_delay, self.max_extrapolation);
+        self.buffers
+            .entry(entity_id)
+            .or_insert_with(|| SnapshotBuffer::new(delay, cap))
+            .push(time, transform)
+    }
+
+    /// Get an entity's transform to draw at client time `now`
+    pub fn sample(&self, entity_id: u32, now: f64) -> Option<Transform> {
+        self.buffers.get(&entity_id)?.sample_at(now)
+    }
+
+    /// Get an entity's buffer
+    pub fn buffer(&self, entity_id: u32) -> Option<&SnapshotBuffer> {
+        self.buffers.get(&entity_id)
+    }
+
+    /// Forget a despawned entity
+    pub fn remove(&mut self, entity_id: u32) {
+        self.buffers.remove(&entity_id);
+    }
+
+    /// Get the number of tracked entities
+    pub fn len(&self) -> usize {
+        self.buffers.len()
+    }
+
+    /// Check if no entities are tracked
+    pub fn is_empty(&self) -> bool {
+        self.buffers.is_empty()
+    }
+}
+
+impl Default for RemoteEntities {
+    fn default() -> Self {
+        Self::new(DEFAULT_INTERP_DELAY, DEFAULT_MAX_EXTRAPOLATION)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample_lerps_then_clamps_extrapolation() {
+        let mut buffer = SnapshotBuffer::new(0.1, 0.2);
+        assert!(buffer.sample(0.0).is_none());
+        assert!(buffer.push(1.0, Transform::new(Vec3::new(0.0, 64.0, 0.0), 350.0, 0.0)));
+        assert!(buffer.push(1.5, Transform::new(Vec3::new(10.0, 64.0, 0.0), 10.0, 20.0)));
+        assert!(!buffer.push(1.2, Transform::default()));
+
+        // A quarter of the way between the two snapshots
+        let mid = buffer.sample(1.125).unwrap();
+        assert!((mid.position.x - 2.5).abs() < 1e-4);
+        assert!((mid.yaw - 355.0).abs() < 1e-4);
+        assert!((mid.pitch - 5.0).abs() < 1e-4);
+        assert_eq!(buffer.sample_at(1.225), Some(mid));
+
+        // Before the first snapshot holds it
+        assert_eq!(buffer.sample(0.5).unwrap().position.x, 0.0);
+
+        // Past the last: moves on at 20 blocks/s, but no more than 0.2s worth
+        let late = buffer.sample(1.6).unwrap();
+        assert!((late.position.x - 12.0).abs() < 1e-4);
+        let very_late = buffer.sample(3.0).unwrap();
+        assert!((very_late.position.x - 14.0).abs() < 1e-4);
+        assert_eq!(very_late.yaw, 10.0);
+
+        let mut remote = RemoteEntities::default();
+        remote.push(7, 1.0, Transform::new(Vec3::ONE, 0.0, 0.0));
+        assert_eq!(remote.sample(7, 2.0).unwrap().position, Vec3::ONE);
+        remote.remove(7);
+        assert!(remote.is_empty());
+    }
+}
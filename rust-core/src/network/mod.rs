@@ -3,10 +3,28 @@
 //! Network codec and compression utilities.
 
 pub mod prediction;
+pub mod crypto;
+pub mod bandwidth;
+pub mod reliable;
+pub mod snapshot;
+pub mod interest;
+pub mod session;
+pub mod interpolation;
+pub mod batch;
 
+pub use crypto::EncryptedChannel;
+pub use bandwidth::BandwidthMeter;
+pub use reliable::ReliableChannel;
+pub use interest::{InterestManager, RelevanceDelta};
+pub use session::{SessionEvent, SessionManager};
+pub use interpolation::{RemoteEntities, SnapshotBuffer};
+pub use batch::PacketAggregator;
+
+#[cfg(feature = "compression")]
 use std::io::{Read, Write};
 
 /// Compress data using zstd
+#[cfg(feature = "compression")]
 pub fn compress(data: &[u8]) -> Result<Vec<u8>, String> {
     let mut encoder = zstd::stream::Encoder::new(Vec::new(), 3)
         .map_err(|e| format!("Failed to create encoder: {}", e))?;
@@ -19,6 +37,7 @@ pub fn compress(data: &[u8]) -> Result<Vec<u8>, String> {
 }
 
 /// Decompress data using zstd
+#[cfg(feature = "compression")]
 pub fn decompress(data: &[u8]) -> Result<Vec<u8>, String> {
     let mut decoder = zstd::stream::Decoder::new(data)
         .map_err(|e| format!("Failed to create decoder: {}", e))?;
@@ -41,13 +60,76 @@ pub fn decompress_lz4(data: &[u8]) -> Result<Vec<u8>, String> {
         .map_err(|e| format!("LZ4 decompression failed: {}", e))
 }
 
+/// Current wire protocol version (stored in the top 4 bits of `flags`)
+pub const PROTOCOL_VERSION: u16 = 1;
+
+/// CRC-32 (IEEE) lookup table
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// Compute CRC-32 (IEEE) over several byte slices
+fn crc32(parts: &[&[u8]]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for part in parts {
+        for &byte in *part {
+            crc = CRC32_TABLE[((crc ^ byte as u32) & 0xFF) as usize] ^ (crc >> 8);
+        }
+    }
+    !crc
+}
+
+/// Packet validation errors
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PacketError {
+    TooShort(usize),
+    VersionMismatch(u16),
+    LengthMismatch { expected: u32, actual: usize },
+    ChecksumMismatch { expected: u32, actual: u32 },
+    NotEncrypted,
+    AuthenticationFailed,
+    NonceExhausted,
+}
+
+impl std::fmt::Display for PacketError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TooShort(len) => write!(f, "Packet too short: {} bytes", len),
+            Self::VersionMismatch(v) => write!(f, "Protocol version {} (expected {})", v, PROTOCOL_VERSION),
+            Self::LengthMismatch { expected, actual } => {
+                write!(f, "Payload length {} (header says {})", actual, expected)
+            }
+            Self::ChecksumMismatch { expected, actual } => {
+                write!(f, "Checksum {:#010x} (header says {:#010x})", actual, expected)
+            }
+            Self::NotEncrypted => write!(f, "Packet is not encrypted"),
+            Self::AuthenticationFailed => write!(f, "Packet failed authentication"),
+            Self::NonceExhausted => write!(f, "Nonce space exhausted, re-key the channel"),
+        }
+    }
+}
+
+impl std::error::Error for PacketError {}
+
 /// Packet header for network communication
 #[derive(Debug, Clone, Copy)]
 #[repr(C)]
 pub struct PacketHeader {
     /// Packet type ID
     pub packet_type: u16,
-    /// Packet flags
+    /// Packet flags (top 4 bits: protocol version)
     pub flags: u16,
     /// Payload length
     pub length: u32,
@@ -55,23 +137,68 @@ pub struct PacketHeader {
     pub sequence: u32,
     /// Acknowledgment number
     pub ack: u32,
+    /// CRC-32 over header (with this field zeroed) and payload
+    pub checksum: u32,
 }
 
 impl PacketHeader {
     /// Header size in bytes
-    pub const SIZE: usize = 16;
+    pub const SIZE: usize = 20;
     
     /// Create a new packet header
     pub fn new(packet_type: u16, length: u32, sequence: u32) -> Self {
         Self {
             packet_type,
-            flags: 0,
+            flags: PROTOCOL_VERSION << 12,
             length,
             sequence,
             ack: 0,
+            checksum: 0,
         }
     }
     
+    /// Get protocol version
+    pub fn version(&self) -> u16 {
+        self.flags >> 12
+    }
+    
+    /// Set payload length and checksum for the payload
+    pub fn finalize(&mut self, payload: &[u8]) {
+        self.length = payload.len() as u32;
+        self.checksum = self.compute_checksum(payload);
+    }
+    
+    /// Checksum of this header (checksum field zeroed) and a payload
+    fn compute_checksum(&self, payload: &[u8]) -> u32 {
+        let mut header = self.to_bytes();
+        header[16..20].fill(0);
+        crc32(&[&header, payload])
+    }
+    
+    /// Validate a full packet (header followed by payload)
+    pub fn validate(bytes: &[u8]) -> Result<(), PacketError> {
+        if bytes.len() < Self::SIZE {
+            return Err(PacketError::TooShort(bytes.len()));
+        }
+        
+        let header = Self::read(bytes);
+        if header.version() != PROTOCOL_VERSION {
+            return Err(PacketError::VersionMismatch(header.version()));
+        }
+        
+        let payload = &bytes[Self::SIZE..];
+        if payload.len() != header.length as usize {
+            return Err(PacketError::LengthMismatch { expected: header.length, actual: payload.len() });
+        }
+        
+        let actual = header.compute_checksum(payload);
+        if actual != header.checksum {
+            return Err(PacketError::ChecksumMismatch { expected: header.checksum, actual });
+        }
+        
+        Ok(())
+    }
+    
     /// Serialize header to bytes
     pub fn to_bytes(&self) -> [u8; Self::SIZE] {
         let mut bytes = [0u8; Self::SIZE];
@@ -80,22 +207,30 @@ impl PacketHeader {
         bytes[4..8].copy_from_slice(&self.length.to_le_bytes());
         bytes[8..12].copy_from_slice(&self.sequence.to_le_bytes());
         bytes[12..16].copy_from_slice(&self.ack.to_le_bytes());
+        bytes[16..20].copy_from_slice(&self.checksum.to_le_bytes());
         bytes
     }
     
-    /// Deserialize header from bytes
+    /// Deserialize header from bytes (rejects short input and other protocol versions)
     pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
         if bytes.len() < Self::SIZE {
             return None;
         }
         
-        Some(Self {
+        let header = Self::read(bytes);
+        (header.version() == PROTOCOL_VERSION).then_some(header)
+    }
+    
+    /// Read header fields (caller checks length)
+    fn read(bytes: &[u8]) -> Self {
+        Self {
             packet_type: u16::from_le_bytes([bytes[0], bytes[1]]),
             flags: u16::from_le_bytes([bytes[2], bytes[3]]),
             length: u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]),
             sequence: u32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]),
             ack: u32::from_le_bytes([bytes[12], bytes[13], bytes[14], bytes[15]]),
-        })
+            checksum: u32::from_le_bytes([bytes[16], bytes[17], bytes[18], bytes[19]]),
+        }
     }
 }
 
@@ -106,6 +241,8 @@ pub mod flags {
     pub const RELIABLE: u16 = 0x0004;
     pub const ORDERED: u16 = 0x0008;
     pub const FRAGMENTED: u16 = 0x0010;
+    /// Protocol version bits
+    pub const VERSION_MASK: u16 = 0xF000;
 }
 
 /// Packet types
@@ -117,6 +254,8 @@ pub mod packet_type {
     pub const ENTITY_UPDATE: u16 = 0x0020;
     pub const PLAYER_INPUT: u16 = 0x0030;
     pub const WORLD_EVENT: u16 = 0x0040;
+    /// Several messages framed by `batch::PacketAggregator`
+    pub const BATCH: u16 = 0x0050;
 }
 
 /// Shutdown network subsystem
@@ -128,6 +267,7 @@ pub fn shutdown() {
 mod tests {
     use super::*;
     
+    #[cfg(feature = "compression")]
     #[test]
     fn test_compression_roundtrip() {
         let data = b"Hello, World! This is test data for compression.";
@@ -158,4 +298,47 @@ mod tests {
         assert_eq!(header.length, restored.length);
         assert_eq!(header.sequence, restored.sequence);
     }
+    
+    #[test]
+    fn test_packet_checksum_roundtrip() {
+        let payload = b"chunk payload bytes";
+        let mut header = PacketHeader::new(packet_type::CHUNK_DATA, 0, 7);
+        header.finalize(payload);
+        
+        let mut packet = header.to_bytes().to_vec();
+        packet.extend_from_slice(payload);
+        assert_eq!(PacketHeader::validate(&packet), Ok(()));
+        assert_eq!(PacketHeader::from_bytes(&packet).unwrap().checksum, header.checksum);
+        
+        // Known CRC-32 check value
+        assert_eq!(crc32(&[b"123456789"]), 0xCBF4_3926);
+    }
+    
+    #[test]
+    fn test_packet_corruption_detected() {
+        let payload = b"entity update";
+        let mut header = PacketHeader::new(packet_type::ENTITY_UPDATE, 0, 1);
+        header.finalize(payload);
+        let mut packet = header.to_bytes().to_vec();
+        packet.extend_from_slice(payload);
+        
+        let mut corrupt = packet.clone();
+        corrupt[PacketHeader::SIZE + 3] ^= 0x01;
+        assert!(matches!(PacketHeader::validate(&corrupt), Err(PacketError::ChecksumMismatch { .. })));
+        
+        let mut corrupt = packet.clone();
+        corrupt[9] ^= 0x80; // sequence
+        assert!(matches!(PacketHeader::validate(&corrupt), Err(PacketError::ChecksumMismatch { .. })));
+        
+        assert!(matches!(
+            PacketHeader::validate(&packet[..packet.len() - 1]),
+            Err(PacketError::LengthMismatch { .. })
+        ));
+        assert_eq!(PacketHeader::validate(&packet[..10]), Err(PacketError::TooShort(10)));
+        
+        let mut other_version = packet.clone();
+        other_version[3] ^= 0x30;
+        assert_eq!(PacketHeader::validate(&other_version), Err(PacketError::VersionMismatch(2)));
+        assert!(PacketHeader::from_bytes(&other_version).is_none());
+    }
 }
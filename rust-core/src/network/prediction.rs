@@ -5,9 +5,11 @@
 //! - Latency masking (client-side prediction)
 //! - Smooth interpolation (no rubber-banding)
 
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use glam::Vec3;
 
+use super::snapshot::{SnapshotData, SnapshotHistory};
+
 /// Network packet types
 #[repr(u8)]
 #[derive(Clone, Copy, PartialEq)]
@@ -39,6 +41,15 @@ pub struct BlockChange {
     pub new_block: u16,
 }
 
+/// Positional error (blocks) below which an entity prediction counts as correct
+pub const PREDICTION_TOLERANCE: f32 = 1.0;
+
+/// Reconciles `misprediction_rate` looks back over
+pub const MISPREDICTION_WINDOW: usize = 128;
+
+/// Predicted states kept for byte-level reconciles
+const MAX_PREDICTED_STATES: usize = 128;
+
 /// Entity state for prediction
 #[derive(Clone)]
 pub struct EntityState {
@@ -59,6 +70,12 @@ pub struct PredictiveNetcode {
     predictions: Vec<Prediction>,
     /// Zstd compression context
     compressor: Option<ZstdContext>,
+    /// Authoritative snapshot history
+    snapshots: SnapshotHistory,
+    /// Locally predicted full states by tick, oldest first
+    predicted_states: VecDeque<(u64, Vec<u8>)>,
+    /// Whether each recent reconcile found a mismatch, oldest first
+    recent_reconciles: VecDeque<bool>,
     /// Statistics
     stats: NetcodeStats,
 }
@@ -95,6 +112,8 @@ pub struct NetcodeStats {
     pub predictions_correct: u32,
     pub interpolations: u32,
     pub avg_ping: f32,
+    pub reconciles: u32,
+    pub mispredictions: u32,
 }
 
 impl PredictiveNetcode {
@@ -110,6 +129,9 @@ impl PredictiveNetcode {
                 dictionary: Self::create_minecraft_dictionary(),
                 compression_level: 3,
             }),
+            snapshots: SnapshotHistory::default(),
+            predicted_states: VecDeque::new(),
+            recent_reconciles: VecDeque::with_capacity(MISPREDICTION_WINDOW),
             stats: NetcodeStats::default(),
         }
     }
@@ -201,6 +223,70 @@ impl PredictiveNetcode {
         result
     }
     
+    /// Ingest an authoritative snapshot (keyframe or delta); false if it needs a missing base
+    pub fn ingest_snapshot(&mut self, tick: u64, data: SnapshotData) -> bool {
+        self.stats.packets_received += 1;
+        self.snapshots.ingest(tick, data)
+    }
+    
+    /// Get the newest reconstructed snapshot
+    pub fn latest_reconstructed(&self) -> Vec<u8> {
+        self.snapshots.latest()
+    }
+    
+    /// Check if the server must send a full keyframe
+    pub fn needs_keyframe(&self) -> bool {
+        self.snapshots.needs_keyframe()
+    }
+    
+    /// Remember the locally predicted state for a tick
+    pub fn record_predicted_state(&mut self, tick: u64, state: &[u8]) {
+        if self.predicted_states.len() >= MAX_PREDICTED_STATES {
+            self.predicted_states.pop_front();
+        }
+        self.predicted_states.push_back((tick, state.to_vec()));
+    }
+    
+    /// Compare the server's state for a tick against our prediction
+    ///
+    /// Returns the number of differing bytes (a length difference counts
+    /// every missing byte), or None if nothing was predicted for the tick.
+    /// Predictions up to the tick are dropped either way.
+    pub fn reconcile(&mut self, tick: u64, server_state: &[u8]) -> Option<usize> {
+        let predicted = self.predicted_states.iter().find(|(t, _)| *t == tick).map(|(_, data)| {
+            let common = data.iter().zip(server_state).filter(|(a, b)| a != b).count();
+            common + data.len().abs_diff(server_state.len())
+        });
+        self.predicted_states.retain(|(t, _)| *t > tick);
+        
+        let differing = predicted?;
+        crate::profiler().record_histogram("prediction_error", differing as f64);
+        self.record_reconcile(differing > 0);
+        Some(differing)
+    }
+    
+    /// Count one reconcile towards the misprediction rate
+    fn record_reconcile(&mut self, mispredicted: bool) {
+        self.stats.reconciles += 1;
+        if mispredicted {
+            self.stats.mispredictions += 1;
+        }
+        if self.recent_reconciles.len() >= MISPREDICTION_WINDOW {
+            self.recent_reconciles.pop_front();
+        }
+        self.recent_reconciles.push_back(mispredicted);
+    }
+    
+    /// Get the fraction of the last `MISPREDICTION_WINDOW` reconciles that
+    /// found a mismatch (0 before any)
+    pub fn misprediction_rate(&self) -> f32 {
+        if self.recent_reconciles.is_empty() {
+            return 0.0;
+        }
+        let missed = self.recent_reconciles.iter().filter(|&&m| m).count();
+        missed as f32 / self.recent_reconciles.len() as f32
+    }
+    
     /// Receive entity update from server
     pub fn receive_entity_update(&mut self, entity_id: u32, state: EntityState) {
         self.stats.packets_received += 1;
@@ -296,22 +382,24 @@ impl PredictiveNetcode {
         };
         
         // Check predictions for this entity
+        let mut errors = Vec::new();
         self.predictions.retain(|pred| {
             if pred.entity_id != entity_id {
                 return true;
             }
             
-            // Compare prediction to actual
-            let error = (pred.predicted_state.position - server_state.position).length();
-            
-            if error < 1.0 {
-                // Good prediction
-                self.stats.predictions_correct += 1;
-            }
-            
-            // Remove old predictions
+            // Compare prediction to actual, then drop it
+            errors.push((pred.predicted_state.position - server_state.position).length());
             false
         });
+        
+        for error in errors {
+            crate::profiler().record_histogram("prediction_position_error", error as f64);
+            if error < PREDICTION_TOLERANCE {
+                self.stats.predictions_correct += 1;
+            }
+            self.record_reconcile(error >= PREDICTION_TOLERANCE);
+        }
     }
     
     /// Get statistics
@@ -328,6 +416,42 @@ impl PredictiveNetcode {
     pub fn clear(&mut self) {
         self.entity_states.clear();
         self.predictions.clear();
+        self.snapshots.clear();
+        self.predicted_states.clear();
+        self.recent_reconciles.clear();
         self.stats = NetcodeStats::default();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_misprediction_rate() {
+        let mut netcode = PredictiveNetcode::new();
+        assert_eq!(netcode.misprediction_rate(), 0.0);
+        
+        // Ticks 1-4 predicted; 2 and 4 differ from the server
+        for tick in 1..=4u64 {
+            netcode.record_predicted_state(tick, &[tick as u8, 0, 0, 0]);
+        }
+        assert_eq!(netcode.reconcile(1, &[1, 0, 0, 0]), Some(0));
+        assert_eq!(netcode.reconcile(2, &[2, 9, 9, 0]), Some(2));
+        assert_eq!(netcode.reconcile(3, &[3, 0, 0, 0]), Some(0));
+        assert_eq!(netcode.reconcile(4, &[4, 0, 0, 0, 7]), Some(1));
+        assert!((netcode.misprediction_rate() - 0.5).abs() < 1e-6);
+        
+        // Unpredicted ticks don't count
+        assert_eq!(netcode.reconcile(5, &[0]), None);
+        assert_eq!(netcode.get_stats().reconciles, 4);
+        
+        // The window forgets old reconciles
+        for tick in 10..10 + MISPREDICTION_WINDOW as u64 {
+            netcode.record_predicted_state(tick, &[1]);
+            netcode.reconcile(tick, &[1]);
+        }
+        assert_eq!(netcode.misprediction_rate(), 0.0);
+        assert_eq!(netcode.get_stats().mispredictions, 2);
+    }
+}
@@ -0,0 +1,210 @@
+//! # Reliable Channel
+//!
+//! Per-connection packet sequencing, acknowledgement and resend of
+//! `RELIABLE` packets, with bandwidth accounting and optional pacing.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use super::bandwidth::{BandwidthMeter, TokenBucket};
+use super::{flags, PacketError, PacketHeader};
+
+/// Default resend timeout for unacknowledged reliable packets
+pub const DEFAULT_RESEND_TIMEOUT: Duration = Duration::from_millis(250);
+
+/// Reliable packet awaiting acknowledgement
+struct InFlight {
+    bytes: Vec<u8>,
+    sent_at: Instant,
+}
+
+/// Reliable channel for one connection
+pub struct ReliableChannel {
+    /// Next outgoing sequence number
+    next_sequence: u32,
+    /// Highest sequence received from the peer (sent back as ack)
+    remote_sequence: u32,
+    /// Reliable packets not yet acknowledged
+    in_flight: BTreeMap<u32, InFlight>,
+    /// Packets deferred by the rate limit (oldest first)
+    deferred: VecDeque<Vec<u8>>,
+    /// Resend timeout
+    resend_timeout: Duration,
+    /// Bandwidth meter
+    meter: BandwidthMeter,
+    /// Outgoing rate limit
+    limiter: Option<TokenBucket>,
+}
+
+impl ReliableChannel {
+    /// Create a new channel
+    pub fn new() -> Self {
+        Self {
+            next_sequence: 1,
+            remote_sequence: 0,
+            in_flight: BTreeMap::new(),
+            deferred: VecDeque::new(),
+            resend_timeout: DEFAULT_RESEND_TIMEOUT,
+            meter: BandwidthMeter::new(),
+            limiter: None,
+        }
+    }
+
+    /// Cap outgoing bandwidth (`None` removes the cap)
+    pub fn rate_limit(&mut self, max_bytes_per_sec: Option<u32>) {
+        self.limiter = max_bytes_per_sec.map(TokenBucket::new);
+    }
+
+    /// Set resend timeout
+    pub fn set_resend_timeout(&mut self, timeout: Duration) {
+        self.resend_timeout = timeout;
+    }
+
+    /// Build and send a packet; returns the packets cleared for the wire now
+    ///
+    /// Packets over the rate limit are held back and released by later
+    /// `on_send`/`poll` calls in their original order.
+    pub fn on_send(&mut self, packet_type: u16, packet_flags: u16, payload: &[u8], now: Instant) -> Vec<Vec<u8>> {
+        let sequence = self.next_sequence;
+        self.next_sequence = self.next_sequence.wrapping_add(1);
+
+        let mut header = PacketHeader::new(packet_type, 0, sequence);
+        header.flags |= packet_flags & !flags::VERSION_MASK;
+        header.ack = self.remote_sequence;
+        header.finalize(payload);
+
+        let mut bytes = header.to_bytes().to_vec();
+        bytes.extend_from_slice(payload);
+
+        if packet_flags & flags::RELIABLE != 0 {
+            self.in_flight.insert(sequence, InFlight { bytes: bytes.clone(), sent_at: now });
+        }
+
+        self.deferred.push_back(bytes);
+        self.release(now)
+    }
+
+    /// Release deferred packets and resend timed-out reliable packets
+    pub fn poll(&mut self, now: Instant) -> Vec<Vec<u8>> {
+        let timeout = self.resend_timeout;
+        let mut resend = Vec::new();
+        for packet in self.in_flight.values_mut() {
+            if now.saturating_duration_since(packet.sent_at) >= timeout {
+                packet.sent_at = now;
+                resend.push(packet.bytes.clone());
+            }
+        }
+        self.deferred.extend(resend);
+
+        self.meter.update(now);
+        self.release(now)
+    }
+
+    /// Send deferred packets while the rate limit allows
+    fn release(&mut self, now: Instant) -> Vec<Vec<u8>> {
+        let mut ready = Vec::new();
+
+        while let Some(packet) = self.deferred.front() {
+            if let Some(limiter) = &mut self.limiter {
+                if !limiter.try_consume(packet.len(), now) {
+                    break;
+                }
+            }
+            let packet = self.deferred.pop_front().unwrap();
+            self.meter.record_sent(packet.len(), now);
+            ready.push(packet);
+        }
+
+        ready
+    }
+
+    /// Validate an incoming packet, process its ack and return header + payload
+    pub fn on_receive(&mut self, bytes: &[u8], now: Instant) -> Result<(PacketHeader, Vec<u8>), PacketError> {
+        PacketHeader::validate(bytes)?;
+        let header = PacketHeader::from_bytes(bytes).ok_or(PacketError::TooShort(bytes.len()))?;
+
+        self.meter.record_received(bytes.len(), now);
+
+        // Cumulative ack: everything up to `ack` arrived
+        let acked: Vec<u32> = self.in_flight.range(..=header.ack).map(|(&seq, _)| seq).collect();
+        for seq in acked {
+            self.in_flight.remove(&seq);
+        }
+
+        if header.sequence > self.remote_sequence {
+            self.remote_sequence = header.sequence;
+        }
+
+        Ok((header, bytes[PacketHeader::SIZE..].to_vec()))
+    }
+
+    /// Get bandwidth meter
+    pub fn meter(&self) -> &BandwidthMeter {
+        &self.meter
+    }
+
+    /// Bytes sent over the last second
+    pub fn bytes_sent_per_sec(&self) -> usize {
+        self.meter.bytes_sent_per_sec()
+    }
+
+    /// Bytes received over the last second
+    pub fn bytes_recv_per_sec(&self) -> usize {
+        self.meter.bytes_recv_per_sec()
+    }
+
+    /// Get number of reliable packets awaiting ack
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.len()
+    }
+
+    /// Get number of packets held back by the rate limit
+    pub fn deferred(&self) -> usize {
+        self.deferred.len()
+    }
+}
+
+impl Default for ReliableChannel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::packet_type;
+
+    #[test]
+    fn test_rate_limit_paces_burst() {
+        let start = Instant::now();
+        let payload = [0u8; 80];
+        let packet_size = PacketHeader::SIZE + payload.len();
+
+        let mut channel = ReliableChannel::new();
+        channel.rate_limit(Some(packet_size as u32 * 2));
+
+        // Burst of 6 packets: only the first 2 fit the budget
+        let mut sent = 0;
+        for _ in 0..6 {
+            sent += channel.on_send(packet_type::ENTITY_UPDATE, 0, &payload, start).len();
+        }
+        assert_eq!(sent, 2);
+        assert_eq!(channel.deferred(), 4);
+        assert_eq!(channel.bytes_sent_per_sec(), packet_size * 2);
+
+        // Half a second refills one packet's worth
+        let released = channel.poll(start + Duration::from_millis(500));
+        assert_eq!(released.len(), 1);
+
+        let released = channel.poll(start + Duration::from_millis(1500));
+        assert_eq!(released.len(), 2);
+        let released = channel.poll(start + Duration::from_millis(2000));
+        assert_eq!(released.len(), 1);
+        assert_eq!(channel.deferred(), 0);
+
+        // Deferred packets keep their order
+        let header = PacketHeader::from_bytes(&released[0]).unwrap();
+        assert_eq!(header.sequence, 6);
+    }
+}
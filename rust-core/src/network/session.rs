@@ -0,0 +1,235 @@
+//! # Sessions
+//!
+//! Connection lifecycle for a server: a peer becomes connected by sending
+//! `HANDSHAKE`, stays connected while it keeps sending (any packet counts,
+//! `HEARTBEAT` exists for idle peers), and is dropped on `DISCONNECT` or
+//! after going silent for the heartbeat timeout. Packets from peers that
+//! haven't handshaken are rejected rather than passed on.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+use super::{packet_type, PacketHeader};
+
+/// Default silence after which a peer is dropped
+pub const DEFAULT_HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Session identifier (unique for the manager's lifetime)
+pub type SessionId = u64;
+
+/// Why a session ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisconnectReason {
+    /// Peer sent `DISCONNECT`
+    Requested,
+    /// No packet within the heartbeat timeout
+    TimedOut,
+    /// Server closed the session
+    Kicked,
+}
+
+/// Why a packet was refused
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RejectReason {
+    /// Handshake while the server is at its peer limit
+    ServerFull,
+    /// Packet from a peer that hasn't handshaken
+    NotConnected,
+}
+
+/// Something the server should act on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionEvent {
+    /// Handshake accepted
+    Connected { addr: SocketAddr, session: SessionId },
+    /// Game packet from a connected peer
+    Data { addr: SocketAddr, packet_type: u16, payload: Vec<u8> },
+    /// Session ended
+    Disconnected { addr: SocketAddr, session: SessionId, reason: DisconnectReason },
+    /// Packet refused
+    Rejected { addr: SocketAddr, reason: RejectReason },
+}
+
+/// Connected peer
+#[derive(Debug, Clone)]
+pub struct Session {
+    /// Session ID
+    pub id: SessionId,
+    /// When the handshake was accepted
+    pub connected_at: Instant,
+    /// When the last packet arrived
+    pub last_heard: Instant,
+}
+
+/// Tracks connected peers by address
+pub struct SessionManager {
+    /// Sessions by peer address
+    sessions: HashMap<SocketAddr, Session>,
+    /// Silence after which a peer is dropped
+    timeout: Duration,
+    /// Peer limit (`None` for unlimited)
+    max_peers: Option<usize>,
+    /// Next session ID
+    next_id: SessionId,
+}
+
+impl SessionManager {
+    /// Create a manager with a heartbeat timeout
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            sessions: HashMap::new(),
+            timeout,
+            max_peers: None,
+            next_id: 1,
+        }
+    }
+
+    /// Set the heartbeat timeout
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// Get the heartbeat timeout
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Limit connected peers (`None` for unlimited)
+    pub fn set_max_peers(&mut self, max_peers: Option<usize>) {
+        self.max_peers = max_peers;
+    }
+
+    /// Handle a validated packet from `addr`
+    pub fn on_packet(&mut self, addr: SocketAddr, header: &PacketHeader, payload: &[u8], now: Instant) -> Vec<SessionEvent> {
+        if header.packet_type == packet_type::HANDSHAKE {
+            return self.handshake(addr, now);
+        }
+
+        let Some(session) = self.sessions.get_mut(&addr) else {
+            return vec![SessionEvent::Rejected { addr, reason: RejectReason::NotConnected }];
+        };
+        session.last_heard = now;
+
+        match header.packet_type {
+            packet_type::HEARTBEAT => Vec::new(),
+            packet_type::DISCONNECT => self.end(addr, DisconnectReason::Requested).into_iter().collect(),
+            packet_type => vec![SessionEvent::Data { addr, packet_type, payload: payload.to_vec() }],
+        }
+    }
+
+    /// Drop peers silent for longer than the timeout
+    pub fn tick(&mut self, now: Instant) -> Vec<SessionEvent> {
+        let mut expired: Vec<SocketAddr> = self.sessions
+            .iter()
+            .filter(|(_, s)| now.saturating_duration_since(s.last_heard) > self.timeout)
+            .map(|(&addr, _)| addr)
+            .collect();
+        expired.sort_unstable();
+
+        expired
+            .into_iter()
+            .filter_map(|addr| self.end(addr, DisconnectReason::TimedOut))
+            .collect()
+    }
+
+    /// Close a session from the server side
+    pub fn disconnect(&mut self, addr: SocketAddr) -> Option<SessionEvent> {
+        self.end(addr, DisconnectReason::Kicked)
+    }
+
+    /// Get a peer's session
+    pub fn session(&self, addr: &SocketAddr) -> Option<&Session> {
+        self.sessions.get(addr)
+    }
+
+    /// Check if a peer is connected
+    pub fn is_connected(&self, addr: &SocketAddr) -> bool {
+        self.sessions.contains_key(addr)
+    }
+
+    /// Get connected peer count
+    pub fn peer_count(&self) -> usize {
+        self.sessions.len()
+    }
+
+    /// Iterate connected peer addresses
+    pub fn peers(&self) -> impl Iterator<Item = &SocketAddr> {
+        self.sessions.keys()
+    }
+
+    /// Accept a handshake (a repeat from a connected peer only refreshes it)
+    fn handshake(&mut self, addr: SocketAddr, now: Instant) -> Vec<SessionEvent> {
+        if let Some(session) = self.sessions.get_mut(&addr) {
+            session.last_heard = now;
+            return Vec::new();
+        }
+        if self.max_peers.is_some_and(|max| self.sessions.len() >= max) {
+            return vec![SessionEvent::Rejected { addr, reason: RejectReason::ServerFull }];
+        }
+
+        let id = self.next_id;
+        self.next_id += 1;
+        self.sessions.insert(addr, Session { id, connected_at: now, last_heard: now });
+        log::debug!("Session {} opened for {}", id, addr);
+        vec![SessionEvent::Connected { addr, session: id }]
+    }
+
+    /// Remove a session
+    fn end(&mut self, addr: SocketAddr, reason: DisconnectReason) -> Option<SessionEvent> {
+        let session = self.sessions.remove(&addr)?;
+        log::debug!("Session {} for {} closed: {:?}", session.id, addr, reason);
+        Some(SessionEvent::Disconnected { addr, session: session.id, reason })
+    }
+}
+
+impl Default for SessionManager {
+    fn default() -> Self {
+        Self::new(DEFAULT_HEARTBEAT_TIMEOUT)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_handshake_heartbeat_then_timeout() {
+        let mut sessions = SessionManager::new(Duration::from_secs(5));
+        let addr: SocketAddr = "10.0.0.2:25565".parse().unwrap();
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+        let header = |kind| PacketHeader::new(kind, 0, 0);
+
+        // Game packets before the handshake are refused
+        let events = sessions.on_packet(addr, &header(packet_type::PLAYER_INPUT), b"w", at(0));
+        assert_eq!(events, vec![SessionEvent::Rejected { addr, reason: RejectReason::NotConnected }]);
+
+        let events = sessions.on_packet(addr, &header(packet_type::HANDSHAKE), &[], at(0));
+        let [SessionEvent::Connected { session, .. }] = events[..] else { panic!("{:?}", events) };
+        assert!(sessions.is_connected(&addr));
+
+        // A heartbeat at 4s keeps the peer alive past the original deadline
+        assert!(sessions.on_packet(addr, &header(packet_type::HEARTBEAT), &[], at(4)).is_empty());
+        assert!(sessions.tick(at(8)).is_empty());
+        let events = sessions.on_packet(addr, &header(packet_type::PLAYER_INPUT), b"w", at(8));
+        assert_eq!(events, vec![SessionEvent::Data { addr, packet_type: packet_type::PLAYER_INPUT, payload: b"w".to_vec() }]);
+
+        // Then silence past the timeout
+        assert!(sessions.tick(at(13)).is_empty());
+        let events = sessions.tick(at(14));
+        assert_eq!(events, vec![SessionEvent::Disconnected { addr, session, reason: DisconnectReason::TimedOut }]);
+        assert_eq!(sessions.peer_count(), 0);
+
+        // Reconnecting gets a fresh session; DISCONNECT ends it
+        let events = sessions.on_packet(addr, &header(packet_type::HANDSHAKE), &[], at(20));
+        assert!(matches!(events[..], [SessionEvent::Connected { session: s, .. }] if s != session));
+        let events = sessions.on_packet(addr, &header(packet_type::DISCONNECT), &[], at(21));
+        assert!(matches!(events[..], [SessionEvent::Disconnected { reason: DisconnectReason::Requested, .. }]));
+
+        // Peer limit
+        sessions.set_max_peers(Some(0));
+        let events = sessions.on_packet(addr, &header(packet_type::HANDSHAKE), &[], at(22));
+        assert_eq!(events, vec![SessionEvent::Rejected { addr, reason: RejectReason::ServerFull }]);
+    }
+}
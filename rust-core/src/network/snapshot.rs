@@ -0,0 +1,149 @@
+//! # Snapshot History
+//!
+//! Ring buffer of reconstructed authoritative snapshots. Deltas name the
+//! tick they were encoded against, so a lost delta only matters if a later
+//! one was built on it; when the base has left the window a keyframe is
+//! requested instead.
+
+use std::collections::VecDeque;
+
+/// Default number of snapshots kept
+pub const DEFAULT_HISTORY: usize = 32;
+
+/// Incoming snapshot payload
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnapshotData {
+    /// Full state
+    Keyframe(Vec<u8>),
+    /// XOR diff against the state at `base_tick` (base zero-extended to the diff length)
+    Delta { base_tick: u64, diff: Vec<u8> },
+}
+
+impl SnapshotData {
+    /// Encode `target` as a delta against `base`
+    pub fn delta(base_tick: u64, base: &[u8], target: &[u8]) -> Self {
+        let diff = target.iter().enumerate()
+            .map(|(i, &b)| b ^ base.get(i).copied().unwrap_or(0))
+            .collect();
+        SnapshotData::Delta { base_tick, diff }
+    }
+}
+
+/// Snapshot history
+pub struct SnapshotHistory {
+    /// (tick, full state), oldest first
+    snapshots: VecDeque<(u64, Vec<u8>)>,
+    /// Maximum snapshots kept
+    capacity: usize,
+    /// A delta arrived whose base is no longer available
+    needs_keyframe: bool,
+}
+
+impl SnapshotHistory {
+    /// Create a history keeping `capacity` snapshots
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            snapshots: VecDeque::with_capacity(capacity),
+            capacity: capacity.max(1),
+            needs_keyframe: false,
+        }
+    }
+
+    /// Ingest a snapshot; returns false if it could not be reconstructed
+    pub fn ingest(&mut self, tick: u64, data: SnapshotData) -> bool {
+        let state = match data {
+            SnapshotData::Keyframe(state) => {
+                self.needs_keyframe = false;
+                state
+            }
+            SnapshotData::Delta { base_tick, diff } => {
+                let Some(base) = self.state_at(base_tick) else {
+                    log::debug!("Snapshot {} base {} outside history, requesting keyframe", tick, base_tick);
+                    self.needs_keyframe = true;
+                    return false;
+                };
+                diff.iter().enumerate()
+                    .map(|(i, &d)| d ^ base.get(i).copied().unwrap_or(0))
+                    .collect()
+            }
+        };
+
+        // Keep ticks ordered; late arrivals slot in, duplicates replace
+        let index = self.snapshots.partition_point(|(t, _)| *t < tick);
+        match self.snapshots.get_mut(index) {
+            Some((t, existing)) if *t == tick => *existing = state,
+            _ => self.snapshots.insert(index, (tick, state)),
+        }
+
+        while self.snapshots.len() > self.capacity {
+            self.snapshots.pop_front();
+        }
+
+        true
+    }
+
+    /// Get reconstructed state at a tick
+    pub fn state_at(&self, tick: u64) -> Option<&[u8]> {
+        self.snapshots.iter()
+            .find(|(t, _)| *t == tick)
+            .map(|(_, state)| state.as_slice())
+    }
+
+    /// Get the newest reconstructed state (empty if none)
+    pub fn latest(&self) -> Vec<u8> {
+        self.snapshots.back().map(|(_, state)| state.clone()).unwrap_or_default()
+    }
+
+    /// Get the newest tick
+    pub fn latest_tick(&self) -> Option<u64> {
+        self.snapshots.back().map(|(tick, _)| *tick)
+    }
+
+    /// Check if a full keyframe must be requested
+    pub fn needs_keyframe(&self) -> bool {
+        self.needs_keyframe
+    }
+
+    /// Clear history
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+        self.needs_keyframe = false;
+    }
+}
+
+impl Default for SnapshotHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_HISTORY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reconstruct_after_lost_delta() {
+        let states: [&[u8]; 4] = [b"pos=0,0", b"pos=1,0", b"pos=2,0", b"pos=3,1!"];
+        let mut history = SnapshotHistory::new(4);
+
+        assert!(history.ingest(10, SnapshotData::Keyframe(states[0].to_vec())));
+        assert!(history.ingest(11, SnapshotData::delta(10, states[0], states[1])));
+        // Delta for tick 12 is lost; tick 13 was encoded against acked tick 11
+        assert!(history.ingest(13, SnapshotData::delta(11, states[1], states[3])));
+        assert_eq!(history.latest(), states[3]);
+        assert!(!history.needs_keyframe());
+
+        // A delta built on the lost tick cannot be applied
+        assert!(!history.ingest(14, SnapshotData::delta(12, states[2], states[0])));
+        assert!(history.needs_keyframe());
+        assert_eq!(history.latest_tick(), Some(13));
+
+        // Base aged out of the window
+        for tick in 20..24 {
+            history.ingest(tick, SnapshotData::Keyframe(states[0].to_vec()));
+        }
+        assert!(!history.needs_keyframe());
+        assert!(!history.ingest(24, SnapshotData::delta(13, states[3], states[1])));
+        assert!(history.needs_keyframe());
+    }
+}
@@ -60,9 +60,15 @@ impl GpuProfiler {
         self.enabled = enabled;
     }
     
+    /// Check if queries are recorded (enabled here and `GPU_TIMERS` on in
+    /// the global profiler)
+    pub fn is_active(&self) -> bool {
+        self.enabled && super::profiler().is_category_enabled(super::ProfileCategory::GPU_TIMERS)
+    }
+    
     /// Reset queries for new frame
     pub fn begin_frame(&mut self, device: &ash::Device, cmd: vk::CommandBuffer) {
-        if !self.enabled {
+        if !self.is_active() {
             return;
         }
         
@@ -77,7 +83,7 @@ impl GpuProfiler {
     
     /// End frame and collect results
     pub fn end_frame(&mut self, device: &ash::Device) -> HashMap<String, GpuTimingResult> {
-        if !self.enabled || self.current_query == 0 {
+        if !self.is_active() || self.current_query == 0 {
             return HashMap::new();
         }
         
@@ -118,7 +124,7 @@ impl GpuProfiler {
     
     /// Begin a named GPU timing region
     pub fn begin_region(&mut self, device: &ash::Device, cmd: vk::CommandBuffer, name: &str, depth: u32) {
-        if !self.enabled || self.current_query >= self.query_count - 1 {
+        if !self.is_active() || self.current_query >= self.query_count - 1 {
             return;
         }
         
@@ -144,7 +150,7 @@ impl GpuProfiler {
     
     /// End a named GPU timing region
     pub fn end_region(&mut self, device: &ash::Device, cmd: vk::CommandBuffer, name: &str) {
-        if !self.enabled || self.current_query >= self.query_count {
+        if !self.is_active() || self.current_query >= self.query_count {
             return;
         }
         
@@ -222,6 +222,42 @@ pub struct HistogramSummary {
     pub p99: f64,
 }
 
+/// Effectiveness of a cache, reported as `cache_<name>_<field>` gauges
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheMetrics {
+    /// Lookups served from the cache
+    pub hits: u64,
+    /// Lookups that missed
+    pub misses: u64,
+    /// Entries currently held
+    pub entries: u64,
+    /// Bytes currently held
+    pub bytes: u64,
+    /// Entries dropped to make room
+    pub evictions: u64,
+}
+
+impl CacheMetrics {
+    /// Get the fraction of lookups that hit (0 with no lookups)
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 { 0.0 } else { self.hits as f64 / lookups as f64 }
+    }
+
+    /// Gauge names and values for a cache
+    pub fn gauges(&self, cache: &str) -> [(String, f64); 6] {
+        let name = |field: &str| format!("{}{}_{}", metric_names::CACHE_PREFIX, cache, field);
+        [
+            (name("hits"), self.hits as f64),
+            (name("misses"), self.misses as f64),
+            (name("entries"), self.entries as f64),
+            (name("bytes"), self.bytes as f64),
+            (name("evictions"), self.evictions as f64),
+            (name("hit_rate"), self.hit_rate()),
+        ]
+    }
+}
+
 /// Predefined metric names
 pub mod metric_names {
     pub const FRAME_TIME_MS: &str = "frame_time_ms";
@@ -240,4 +276,6 @@ pub mod metric_names {
     pub const NETWORK_BYTES_IN: &str = "network_bytes_in";
     pub const NETWORK_BYTES_OUT: &str = "network_bytes_out";
     pub const AUDIO_SOURCES: &str = "audio_sources";
+    /// Prefix of per-cache gauges (`cache_shader_hits`, ...)
+    pub const CACHE_PREFIX: &str = "cache_";
 }
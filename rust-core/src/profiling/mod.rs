@@ -4,15 +4,19 @@
 
 pub mod timer;
 pub mod metrics;
+#[cfg(feature = "vulkan")]
 pub mod gpu_profiler;
 pub mod memory_tracker;
+pub mod spans;
+pub mod prometheus;
 
 use std::collections::HashMap;
-use std::sync::{Arc, RwLock, atomic::{AtomicU64, AtomicBool, Ordering}};
+use std::sync::{Arc, RwLock, atomic::{AtomicU32, AtomicU64, AtomicBool, Ordering}};
 use std::time::{Duration, Instant};
 
 pub use timer::*;
 pub use metrics::*;
+#[cfg(feature = "vulkan")]
 pub use gpu_profiler::*;
 pub use memory_tracker::*;
 
@@ -24,10 +28,46 @@ pub fn profiler() -> &'static Profiler {
     &PROFILER
 }
 
+/// Percentile of ascending-sorted samples, interpolating between ranks (R-7)
+///
+/// `p` is in `[0, 1]`; returns 0 for no samples.
+pub fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let Some(&last) = sorted.last() else {
+        return 0.0;
+    };
+    
+    let rank = (sorted.len() - 1) as f64 * p.clamp(0.0, 1.0);
+    let lo = rank.floor() as usize;
+    if lo >= sorted.len() - 1 {
+        return last;
+    }
+    
+    sorted[lo] + (rank - lo as f64) * (sorted[lo + 1] - sorted[lo])
+}
+
+bitflags::bitflags! {
+    /// Kinds of data the profiler collects, switchable at runtime
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct ProfileCategory: u32 {
+        /// Frame times and FPS
+        const FRAMES = 1 << 0;
+        /// Named CPU timers
+        const TIMERS = 1 << 1;
+        /// Allocation tracking
+        const MEMORY = 1 << 2;
+        /// GPU timestamp queries
+        const GPU_TIMERS = 1 << 3;
+        /// Metrics, counters and histograms
+        const METRICS = 1 << 4;
+    }
+}
+
 /// Main profiler
 pub struct Profiler {
     /// Is profiling enabled
     enabled: AtomicBool,
+    /// Enabled `ProfileCategory` bits
+    categories: AtomicU32,
     /// Frame data
     frames: RwLock<FrameHistory>,
     /// CPU timers
@@ -47,6 +87,7 @@ impl Profiler {
     pub fn new() -> Self {
         Self {
             enabled: AtomicBool::new(true),
+            categories: AtomicU32::new(ProfileCategory::all().bits()),
             frames: RwLock::new(FrameHistory::new(300)), // 5 seconds at 60 FPS
             cpu_timers: RwLock::new(HashMap::new()),
             metrics: RwLock::new(MetricsCollector::new()),
@@ -66,9 +107,28 @@ impl Profiler {
         self.enabled.load(Ordering::SeqCst)
     }
     
+    /// Enable/disable categories (e.g. keep only `FRAMES` in shipping builds)
+    pub fn set_category_enabled(&self, categories: ProfileCategory, enabled: bool) {
+        if enabled {
+            self.categories.fetch_or(categories.bits(), Ordering::SeqCst);
+        } else {
+            self.categories.fetch_and(!categories.bits(), Ordering::SeqCst);
+        }
+    }
+    
+    /// Get the enabled categories
+    pub fn categories(&self) -> ProfileCategory {
+        ProfileCategory::from_bits_truncate(self.categories.load(Ordering::SeqCst))
+    }
+    
+    /// Check if profiling is enabled and collecting all of `categories`
+    pub fn is_category_enabled(&self, categories: ProfileCategory) -> bool {
+        self.is_enabled() && self.categories().contains(categories)
+    }
+    
     /// Begin a new frame
     pub fn begin_frame(&self) {
-        if !self.is_enabled() {
+        if !self.is_category_enabled(ProfileCategory::FRAMES) {
             return;
         }
         
@@ -78,7 +138,7 @@ impl Profiler {
     
     /// End the current frame
     pub fn end_frame(&self) {
-        if !self.is_enabled() {
+        if !self.is_category_enabled(ProfileCategory::FRAMES) {
             return;
         }
         
@@ -116,7 +176,7 @@ impl Profiler {
     
     /// Record timer duration
     pub fn record_timer(&self, name: &str, duration: Duration) {
-        if !self.is_enabled() {
+        if !self.is_category_enabled(ProfileCategory::TIMERS) {
             return;
         }
         
@@ -127,16 +187,37 @@ impl Profiler {
     
     /// Record a metric
     pub fn record_metric(&self, name: &str, value: f64) {
-        if !self.is_enabled() {
+        if !self.is_category_enabled(ProfileCategory::METRICS) {
             return;
         }
         
         self.metrics.write().unwrap().record(name, value);
     }
     
+    /// Record a value into a histogram metric
+    pub fn record_histogram(&self, name: &str, value: f64) {
+        if !self.is_category_enabled(ProfileCategory::METRICS) {
+            return;
+        }
+        
+        self.metrics.write().unwrap().record_histogram(name, value);
+    }
+    
+    /// Record a cache's hit/miss accounting as gauges
+    pub fn record_cache_metrics(&self, cache: &str, metrics: &CacheMetrics) {
+        if !self.is_category_enabled(ProfileCategory::METRICS) {
+            return;
+        }
+        
+        let mut collector = self.metrics.write().unwrap();
+        for (name, value) in metrics.gauges(cache) {
+            collector.record(&name, value);
+        }
+    }
+    
     /// Increment a counter
     pub fn increment_counter(&self, name: &str, amount: u64) {
-        if !self.is_enabled() {
+        if !self.is_category_enabled(ProfileCategory::METRICS) {
             return;
         }
         
@@ -145,7 +226,7 @@ impl Profiler {
     
     /// Track memory allocation
     pub fn track_allocation(&self, category: &str, size: usize) {
-        if !self.is_enabled() {
+        if !self.is_category_enabled(ProfileCategory::MEMORY) {
             return;
         }
         
@@ -154,7 +235,7 @@ impl Profiler {
     
     /// Track memory deallocation
     pub fn track_deallocation(&self, category: &str, size: usize) {
-        if !self.is_enabled() {
+        if !self.is_category_enabled(ProfileCategory::MEMORY) {
             return;
         }
         
@@ -181,9 +262,9 @@ impl Profiler {
         let mut sorted = frame_times.clone();
         sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
         
-        let p99 = sorted.get((sorted.len() as f64 * 0.99) as usize).copied().unwrap_or(0.0);
-        let p95 = sorted.get((sorted.len() as f64 * 0.95) as usize).copied().unwrap_or(0.0);
-        let p50 = sorted.get(sorted.len() / 2).copied().unwrap_or(0.0);
+        let p99 = percentile(&sorted, 0.99);
+        let p95 = percentile(&sorted, 0.95);
+        let p50 = percentile(&sorted, 0.50);
         
         FrameStats {
             fps: 1000.0 / avg,
@@ -238,6 +319,11 @@ impl Profiler {
         }
     }
     
+    /// Export a report in the Prometheus text exposition format
+    pub fn to_prometheus(&self) -> String {
+        self.generate_report().to_prometheus()
+    }
+    
     /// Reset all profiling data
     pub fn reset(&self) {
         self.frames.write().unwrap().clear();
@@ -472,3 +558,51 @@ macro_rules! profile_function {
         let _guard = $crate::profiling::profiler().start_timer(concat!(module_path!(), "::", function_name!()));
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentile_interpolates_between_ranks() {
+        // 1..=10: rank = 9p
+        let sorted: Vec<f64> = (1..=10).map(f64::from).collect();
+        assert_eq!(percentile(&sorted, 0.50), 5.5);
+        assert!((percentile(&sorted, 0.95) - 9.55).abs() < 1e-9);
+        assert!((percentile(&sorted, 0.99) - 9.91).abs() < 1e-9);
+        assert_eq!(percentile(&sorted, 0.0), 1.0);
+        assert_eq!(percentile(&sorted, 1.0), 10.0);
+
+        // Clamped at both ends and safe on tiny inputs
+        assert_eq!(percentile(&sorted, 1.5), 10.0);
+        assert_eq!(percentile(&[4.0], 0.99), 4.0);
+        assert_eq!(percentile(&[], 0.5), 0.0);
+        assert_eq!(percentile(&[2.0, 4.0], 0.25), 2.5);
+    }
+
+    #[test]
+    fn test_category_gating_keeps_frame_stats() {
+        let profiler = Profiler::new();
+        profiler.set_category_enabled(ProfileCategory::all(), false);
+        profiler.set_category_enabled(ProfileCategory::FRAMES, true);
+        assert_eq!(profiler.categories(), ProfileCategory::FRAMES);
+
+        profiler.begin_frame();
+        profiler.record_timer("mesh", Duration::from_millis(3));
+        profiler.record_metric("chunks", 4.0);
+        profiler.track_allocation("textures", 1024);
+        profiler.end_frame();
+
+        assert!(profiler.get_timer_stats("mesh").is_none());
+        assert!(profiler.get_metric("chunks").is_none());
+        assert_eq!(profiler.get_memory_stats().allocation_count, 0);
+        assert_eq!(profiler.get_frame_stats().frame_count, 1);
+
+        // Turning a category back on resumes collection
+        profiler.set_category_enabled(ProfileCategory::TIMERS | ProfileCategory::METRICS, true);
+        profiler.record_timer("mesh", Duration::from_millis(3));
+        assert!(profiler.get_timer_stats("mesh").is_some());
+        assert!(profiler.is_category_enabled(ProfileCategory::FRAMES | ProfileCategory::TIMERS));
+        assert!(!profiler.is_category_enabled(ProfileCategory::MEMORY));
+    }
+}
@@ -0,0 +1,243 @@
+//! # Prometheus Export
+//!
+//! Writes profiling reports in the Prometheus text exposition format
+//! (version 0.0.4) so a dedicated server can serve them on a scrape
+//! endpoint. Metric names get the `libs_` prefix, use base units
+//! (seconds, bytes) and end in `_total` for counters.
+
+use std::fmt::Write;
+
+use super::metric_names;
+use super::{MetricValue, ProfilingReport};
+
+/// Prefix of every exported metric
+pub const METRIC_PREFIX: &str = "libs_";
+
+/// Turn an internal metric name into a valid Prometheus name
+///
+/// Characters outside `[a-zA-Z0-9_:]` become `_`; a leading digit gets a
+/// `_` in front.
+pub fn sanitize_metric_name(name: &str) -> String {
+    let mut out: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == ':' { c } else { '_' })
+        .collect();
+    if out.starts_with(|c: char| c.is_ascii_digit()) {
+        out.insert(0, '_');
+    }
+    out
+}
+
+/// Escape a label value (backslash, quote and newline)
+pub fn escape_label_value(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// Format a sample value (`+Inf`, `-Inf` and `NaN` spelled as Prometheus expects)
+fn format_value(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 { "+Inf" } else { "-Inf" }.to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+/// Builds exposition text one metric family at a time
+#[derive(Debug, Default)]
+pub struct PrometheusWriter {
+    out: String,
+}
+
+impl PrometheusWriter {
+    /// Create an empty writer
+    pub fn new() -> Self {
+        Self { out: String::new() }
+    }
+
+    /// Start a metric family (`# HELP` and `# TYPE` lines)
+    pub fn family(&mut self, name: &str, kind: &str, help: &str) -> &mut Self {
+        let _ = writeln!(self.out, "# HELP {} {}", name, help.replace('\\', "\\\\").replace('\n', "\\n"));
+        let _ = writeln!(self.out, "# TYPE {} {}", name, kind);
+        self
+    }
+
+    /// Write a sample with labels
+    pub fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: f64) -> &mut Self {
+        self.out.push_str(name);
+        if !labels.is_empty() {
+            let labels: Vec<String> = labels
+                .iter()
+                .map(|(k, v)| format!("{}=\"{}\"", k, escape_label_value(v)))
+                .collect();
+            let _ = write!(self.out, "{{{}}}", labels.join(","));
+        }
+        let _ = writeln!(self.out, " {}", format_value(value));
+        self
+    }
+
+    /// Finish and get the text
+    pub fn finish(self) -> String {
+        self.out
+    }
+}
+
+impl ProfilingReport {
+    /// Export in the Prometheus text exposition format
+    pub fn to_prometheus(&self) -> String {
+        let mut w = PrometheusWriter::new();
+        let frames = &self.frame_stats;
+
+        // Frame time summary over the profiler's frame history
+        let name = "libs_frame_time_seconds";
+        w.family(name, "summary", "Frame time over recent frames");
+        for (quantile, ms) in [("0.5", frames.p50_frame_time_ms), ("0.95", frames.p95_frame_time_ms), ("0.99", frames.p99_frame_time_ms)] {
+            w.sample(name, &[("quantile", quantile)], ms / 1000.0);
+        }
+        w.sample("libs_frame_time_seconds_sum", &[], frames.avg_frame_time_ms * frames.frame_count as f64 / 1000.0);
+        w.sample("libs_frame_time_seconds_count", &[], frames.frame_count as f64);
+
+        w.family("libs_fps", "gauge", "Frames per second from the average frame time");
+        w.sample("libs_fps", &[], if frames.frame_count > 0 { frames.fps } else { 0.0 });
+
+        // Timers, sorted for stable output
+        let mut timers: Vec<_> = self.timer_stats.iter().collect();
+        timers.sort_by(|a, b| a.0.cmp(b.0));
+        w.family("libs_timer_seconds", "gauge", "Average duration of a CPU timer");
+        for (timer, stats) in &timers {
+            w.sample("libs_timer_seconds", &[("timer", timer)], stats.avg_ms / 1000.0);
+        }
+        w.family("libs_timer_calls_total", "counter", "Times a CPU timer was recorded");
+        for (timer, stats) in &timers {
+            w.sample("libs_timer_calls_total", &[("timer", timer)], stats.call_count as f64);
+        }
+
+        // Memory
+        let memory = &self.memory_stats;
+        let mut categories: Vec<_> = memory.categories.iter().collect();
+        categories.sort_by(|a, b| a.0.cmp(b.0));
+        w.family("libs_memory_bytes", "gauge", "Tracked bytes currently allocated per category");
+        for (category, stats) in &categories {
+            w.sample("libs_memory_bytes", &[("category", category)], stats.current_allocated as f64);
+        }
+        w.family("libs_memory_peak_bytes", "gauge", "Peak tracked bytes per category");
+        for (category, stats) in &categories {
+            w.sample("libs_memory_peak_bytes", &[("category", category)], stats.peak_allocated as f64);
+        }
+        w.family("libs_memory_allocated_bytes", "gauge", "Tracked bytes currently allocated in total");
+        w.sample("libs_memory_allocated_bytes", &[], memory.total_allocated as f64);
+
+        // Draw counters are always present so dashboards don't see gaps
+        for (metric, help) in [
+            (metric_names::DRAW_CALLS, "Draw calls issued"),
+            (metric_names::TRIANGLES, "Triangles submitted"),
+        ] {
+            let value = match self.metrics.get(metric) {
+                Some(MetricValue::Counter(v)) => *v as f64,
+                Some(MetricValue::Gauge(v)) => *v,
+                _ => 0.0,
+            };
+            let name = format!("{}{}_total", METRIC_PREFIX, metric);
+            w.family(&name, "counter", help);
+            w.sample(&name, &[], value);
+        }
+
+        // Remaining collector metrics (frame time and fps are exported above)
+        let exported = [
+            metric_names::DRAW_CALLS,
+            metric_names::TRIANGLES,
+            metric_names::FRAME_TIME_MS,
+            metric_names::FPS,
+            "frame_time_histogram",
+        ];
+        let mut metrics: Vec<_> = self
+            .metrics
+            .iter()
+            .filter(|(name, _)| !exported.contains(&name.as_str()))
+            .collect();
+        metrics.sort_by(|a, b| a.0.cmp(b.0));
+        for (metric, value) in metrics {
+            let base = format!("{}{}", METRIC_PREFIX, sanitize_metric_name(metric));
+            match value {
+                MetricValue::Gauge(v) => {
+                    w.family(&base, "gauge", metric).sample(&base, &[], *v);
+                }
+                MetricValue::Counter(v) => {
+                    let name = format!("{}_total", base);
+                    w.family(&name, "counter", metric).sample(&name, &[], *v as f64);
+                }
+                MetricValue::Histogram(h) => {
+                    w.family(&base, "summary", metric);
+                    for (quantile, v) in [("0.5", h.p50), ("0.9", h.p90), ("0.95", h.p95), ("0.99", h.p99)] {
+                        w.sample(&base, &[("quantile", quantile)], v);
+                    }
+                    w.sample(&format!("{}_sum", base), &[], h.sum);
+                    w.sample(&format!("{}_count", base), &[], h.count as f64);
+                }
+            }
+        }
+
+        w.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Profiler;
+    use super::*;
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    #[test]
+    fn test_prometheus_output_parses_with_expected_series() {
+        let profiler = Profiler::new();
+        profiler.begin_frame();
+        profiler.end_frame();
+        profiler.record_timer("world.tick", Duration::from_millis(4));
+        profiler.track_allocation("chunks", 4096);
+        profiler.increment_counter(metric_names::DRAW_CALLS, 12);
+        profiler.increment_counter("packets-sent", 3);
+        profiler.record_metric("chunks_loaded", 81.0);
+
+        let text = profiler.to_prometheus();
+
+        // Every sample line is `name{labels} value`, with a TYPE declared first
+        let mut types: HashMap<String, String> = HashMap::new();
+        let mut samples: HashMap<String, f64> = HashMap::new();
+        for line in text.lines() {
+            if let Some(rest) = line.strip_prefix("# TYPE ") {
+                let (name, kind) = rest.split_once(' ').unwrap();
+                types.insert(name.to_string(), kind.to_string());
+                continue;
+            }
+            if line.starts_with('#') {
+                continue;
+            }
+            let (series, value) = line.rsplit_once(' ').unwrap();
+            let name = series.split('{').next().unwrap();
+            assert!(name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':'), "{}", line);
+            let family = ["_sum", "_count"]
+                .iter()
+                .find_map(|s| name.strip_suffix(s))
+                .filter(|_| !types.contains_key(name))
+                .unwrap_or(name);
+            assert!(types.contains_key(family), "no TYPE for {}", line);
+            samples.insert(series.to_string(), value.parse().unwrap());
+        }
+
+        assert_eq!(types["libs_frame_time_seconds"], "summary");
+        assert!(samples.contains_key("libs_frame_time_seconds{quantile=\"0.99\"}"));
+        assert_eq!(samples["libs_frame_time_seconds_count"], 1.0);
+        assert_eq!(samples["libs_memory_bytes{category=\"chunks\"}"], 4096.0);
+        assert_eq!(samples["libs_timer_seconds{timer=\"world.tick\"}"], 0.004);
+        assert_eq!(types["libs_draw_calls_total"], "counter");
+        assert_eq!(samples["libs_draw_calls_total"], 12.0);
+        assert_eq!(samples["libs_triangles_total"], 0.0);
+        assert_eq!(samples["libs_packets_sent_total"], 3.0);
+        assert_eq!(samples["libs_chunks_loaded"], 81.0);
+
+        assert_eq!(escape_label_value("a\"b\\c"), "a\\\"b\\\\c");
+        assert_eq!(sanitize_metric_name("9lives.x"), "_9lives_x");
+    }
+}
@@ -0,0 +1,85 @@
+//! # Tracing Spans
+//!
+//! Names of the `tracing` spans opened around subsystem ticks, for use with
+//! standard tooling (tracing-chrome, tokio-console, Tracy) alongside the
+//! internal profiler.
+//!
+//! Spans are at `DEBUG` level; a subscriber that filters them out pays only
+//! the cached callsite check, and span fields are not evaluated.
+
+/// `EcsWorld::parallel_tick` (fields: `entities`, `archetypes`)
+pub const ECS_TICK: &str = "ecs.parallel_tick";
+
+/// `WorldManager::tick` (fields: `chunks`, `dirty_chunks`)
+pub const WORLD_TICK: &str = "world.tick";
+
+/// Meshing loop inside the world tick (fields: `batch`)
+pub const WORLD_MESHING: &str = "world.meshing";
+
+/// `QuantumRenderer::begin_frame` (fields: `frame`)
+pub const RENDER_BEGIN_FRAME: &str = "render.begin_frame";
+
+/// `QuantumRenderer::end_frame` (fields: `frame`)
+pub const RENDER_END_FRAME: &str = "render.end_frame";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tracing::span::{Attributes, Id};
+    use tracing::field::{Field, Visit};
+    use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+
+    /// Records `name` or `name{field=value}` for each new span
+    struct SpanRecorder(Arc<Mutex<Vec<String>>>);
+
+    struct FieldVisitor<'a>(&'a mut String);
+
+    impl Visit for FieldVisitor<'_> {
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            self.0.push_str(&format!(" {}={:?}", field.name(), value));
+        }
+    }
+
+    impl<S: tracing::Subscriber> Layer<S> for SpanRecorder {
+        fn on_new_span(&self, attrs: &Attributes<'_>, _id: &Id, _ctx: Context<'_, S>) {
+            let mut entry = attrs.metadata().name().to_string();
+            attrs.record(&mut FieldVisitor(&mut entry));
+            self.0.lock().unwrap().push(entry);
+        }
+    }
+
+    #[test]
+    fn test_subsystem_ticks_emit_spans() {
+        let spans = Arc::new(Mutex::new(Vec::new()));
+        let subscriber = tracing_subscriber::registry().with(SpanRecorder(spans.clone()));
+
+        tracing::subscriber::with_default(subscriber, || {
+            let mut ecs = crate::ecs::EcsWorld::new();
+            ecs.spawn_entity(1, 0, 0.0, 64.0, 0.0);
+            ecs.spawn_entity(2, 0, 1.0, 64.0, 0.0);
+            ecs.parallel_tick(0.05);
+
+            let mut world = crate::world::WorldManager::new();
+            world.submit_chunk(0, 0, &[]);
+            world.tick();
+
+            #[cfg(feature = "vulkan")]
+            {
+                let mut renderer = crate::renderer::quantum::QuantumRenderer::new().unwrap();
+                let _ = renderer.begin_frame();
+                renderer.end_frame();
+            }
+        });
+
+        let spans = spans.lock().unwrap();
+        let names: Vec<&str> = spans.iter().map(|s| s.split(' ').next().unwrap()).collect();
+        let mut expected = vec![ECS_TICK, WORLD_TICK, WORLD_MESHING];
+        if cfg!(feature = "vulkan") {
+            expected.extend([RENDER_BEGIN_FRAME, RENDER_END_FRAME]);
+        }
+        assert_eq!(names, expected);
+        assert!(spans[0].contains("entities=2"));
+        assert!(spans[1].contains("dirty_chunks=1"));
+    }
+}
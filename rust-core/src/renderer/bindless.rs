@@ -358,6 +358,23 @@ impl BindlessTextureManager {
         }
     }
     
+    /// Replace a loaded texture with new pixels (hot reload)
+    ///
+    /// The freed descriptor slot is reused, so shaders keep the same index.
+    pub fn reload_texture(
+        &mut self,
+        resource_location: &str,
+        image_data: &[u8],
+        width: u32,
+        height: u32,
+    ) -> Result<u64, String> {
+        if let Some(device) = &self.device {
+            unsafe { device.device_wait_idle().ok() };
+        }
+        self.unload_texture(resource_location);
+        self.load_texture(resource_location, image_data, width, height)
+    }
+    
     /// Unload texture
     pub fn unload_texture(&mut self, resource_location: &str) {
         if let Some(id) = self.resource_map.remove(resource_location) {
@@ -0,0 +1,436 @@
+//! # Debug Line Renderer
+//!
+//! Immediate-mode wireframe drawing for the F3 debug view: chunk borders,
+//! entity AABBs, frustum outlines and ray-march rays. Shapes are batched
+//! into line-list vertices, drawn after the main pass and cleared every
+//! frame. Each batch can opt out of depth testing to draw on top.
+
+use std::sync::Arc;
+use ash::vk;
+use bytemuck::{Pod, Zeroable};
+use glam::{Mat4, Vec3, Vec4};
+
+use super::vulkan::{VulkanDevice, VulkanError};
+
+/// Line vertex
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable)]
+pub struct DebugVertex {
+    pub position: [f32; 3],
+    pub color: [f32; 4],
+}
+
+/// Host-visible vertex buffer shared by both batches
+struct DebugGpu {
+    device: Arc<VulkanDevice>,
+    layout: vk::PipelineLayout,
+    /// [depth tested, always on top]
+    pipelines: [vk::Pipeline; 2],
+    buffer: vk::Buffer,
+    memory: vk::DeviceMemory,
+    mapped: *mut std::ffi::c_void,
+    /// Buffer capacity in vertices
+    capacity: usize,
+}
+
+/// Debug line renderer
+pub struct DebugRenderer {
+    /// Lines hidden behind world geometry
+    depth_tested: Vec<DebugVertex>,
+    /// Lines drawn on top of everything
+    overlay: Vec<DebugVertex>,
+    /// Batch new shapes go to
+    depth_test: bool,
+    /// Vulkan resources (None when headless)
+    gpu: Option<DebugGpu>,
+}
+
+impl DebugRenderer {
+    /// Create a renderer (geometry only until `create_pipelines`)
+    pub fn new() -> Self {
+        Self {
+            depth_tested: Vec::new(),
+            overlay: Vec::new(),
+            depth_test: true,
+            gpu: None,
+        }
+    }
+
+    /// Select the batch for subsequent shapes
+    pub fn set_depth_test(&mut self, depth_test: bool) {
+        self.depth_test = depth_test;
+    }
+
+    fn batch(&mut self) -> &mut Vec<DebugVertex> {
+        if self.depth_test { &mut self.depth_tested } else { &mut self.overlay }
+    }
+
+    /// Draw a line segment
+    pub fn draw_line(&mut self, a: Vec3, b: Vec3, color: Vec4) {
+        let color = color.to_array();
+        let batch = self.batch();
+        batch.push(DebugVertex { position: a.to_array(), color });
+        batch.push(DebugVertex { position: b.to_array(), color });
+    }
+
+    /// Draw the 12 edges of a box
+    pub fn draw_aabb(&mut self, min: Vec3, max: Vec3, color: Vec4) {
+        let corners = [
+            Vec3::new(min.x, min.y, min.z), Vec3::new(max.x, min.y, min.z),
+            Vec3::new(max.x, min.y, max.z), Vec3::new(min.x, min.y, max.z),
+            Vec3::new(min.x, max.y, min.z), Vec3::new(max.x, max.y, min.z),
+            Vec3::new(max.x, max.y, max.z), Vec3::new(min.x, max.y, max.z),
+        ];
+        self.draw_box_edges(&corners, color);
+    }
+
+    /// Draw the 12 edges of a view frustum
+    pub fn draw_frustum(&mut self, view_proj: Mat4, color: Vec4) {
+        let inverse = view_proj.inverse();
+        // Vulkan clip space: x, y in [-1, 1], z in [0, 1]
+        let corners = [
+            (-1.0, -1.0, 0.0), (1.0, -1.0, 0.0), (1.0, 1.0, 0.0), (-1.0, 1.0, 0.0),
+            (-1.0, -1.0, 1.0), (1.0, -1.0, 1.0), (1.0, 1.0, 1.0), (-1.0, 1.0, 1.0),
+        ].map(|(x, y, z)| inverse.project_point3(Vec3::new(x, y, z)));
+        self.draw_box_edges(&corners, color);
+    }
+
+    /// Draw the vertical outline of a chunk column
+    pub fn draw_chunk_border(&mut self, chunk_x: i32, chunk_z: i32, min_y: f32, max_y: f32, color: Vec4) {
+        let min = Vec3::new(chunk_x as f32 * 16.0, min_y, chunk_z as f32 * 16.0);
+        self.draw_aabb(min, Vec3::new(min.x + 16.0, max_y, min.z + 16.0), color);
+    }
+
+    /// Draw a ray of a given length
+    pub fn draw_ray(&mut self, origin: Vec3, direction: Vec3, length: f32, color: Vec4) {
+        self.draw_line(origin, origin + direction.normalize_or_zero() * length, color);
+    }
+
+    /// Corners ordered: near/bottom face 0..4, far/top face 4..8
+    fn draw_box_edges(&mut self, corners: &[Vec3; 8], color: Vec4) {
+        for i in 0..4 {
+            let next = (i + 1) % 4;
+            self.draw_line(corners[i], corners[next], color);
+            self.draw_line(corners[i + 4], corners[next + 4], color);
+            self.draw_line(corners[i], corners[i + 4], color);
+        }
+    }
+
+    /// Get queued vertices (depth tested, overlay)
+    pub fn vertices(&self) -> (&[DebugVertex], &[DebugVertex]) {
+        (&self.depth_tested, &self.overlay)
+    }
+
+    /// Get total queued vertex count
+    pub fn vertex_count(&self) -> usize {
+        self.depth_tested.len() + self.overlay.len()
+    }
+
+    /// Drop all queued shapes (call once per frame)
+    pub fn clear(&mut self) {
+        self.depth_tested.clear();
+        self.overlay.clear();
+    }
+
+    /// Create line-list pipelines for a render pass from SPIR-V shaders
+    ///
+    /// The vertex shader takes `DebugVertex` (location 0: vec3, 1: vec4)
+    /// and a 64-byte view-projection push constant.
+    pub fn create_pipelines(
+        &mut self,
+        device: Arc<VulkanDevice>,
+        render_pass: vk::RenderPass,
+        vertex_spirv: &[u32],
+        fragment_spirv: &[u32],
+    ) -> Result<(), VulkanError> {
+        self.destroy_gpu();
+        let vk_device = device.handle();
+        let err = |what: &str, e: vk::Result| VulkanError::PipelineCreationFailed(format!("{}: {:?}", what, e));
+
+        unsafe {
+            let push_constants = [vk::PushConstantRange::default()
+                .stage_flags(vk::ShaderStageFlags::VERTEX)
+                .offset(0)
+                .size(64)];
+            let layout = device.create_pipeline_layout(
+                &vk::PipelineLayoutCreateInfo::default().push_constant_ranges(&push_constants),
+            )?;
+
+            let vertex_module = vk_device.create_shader_module(
+                &vk::ShaderModuleCreateInfo::default().code(vertex_spirv), None,
+            ).map_err(|e| err("Failed to create debug vertex shader", e))?;
+            let fragment_module = vk_device.create_shader_module(
+                &vk::ShaderModuleCreateInfo::default().code(fragment_spirv), None,
+            ).map_err(|e| err("Failed to create debug fragment shader", e))?;
+
+            let entry = c"main";
+            let stages = [
+                vk::PipelineShaderStageCreateInfo::default()
+                    .stage(vk::ShaderStageFlags::VERTEX).module(vertex_module).name(entry),
+                vk::PipelineShaderStageCreateInfo::default()
+                    .stage(vk::ShaderStageFlags::FRAGMENT).module(fragment_module).name(entry),
+            ];
+
+            let bindings = [vk::VertexInputBindingDescription::default()
+                .binding(0)
+                .stride(std::mem::size_of::<DebugVertex>() as u32)
+                .input_rate(vk::VertexInputRate::VERTEX)];
+            let attributes = [
+                vk::VertexInputAttributeDescription::default()
+                    .binding(0).location(0).format(vk::Format::R32G32B32_SFLOAT).offset(0),
+                vk::VertexInputAttributeDescription::default()
+                    .binding(0).location(1).format(vk::Format::R32G32B32A32_SFLOAT).offset(12),
+            ];
+            let vertex_input = vk::PipelineVertexInputStateCreateInfo::default()
+                .vertex_binding_descriptions(&bindings)
+                .vertex_attribute_descriptions(&attributes);
+            let input_assembly = vk::PipelineInputAssemblyStateCreateInfo::default()
+                .topology(vk::PrimitiveTopology::LINE_LIST);
+            let viewport_state = vk::PipelineViewportStateCreateInfo::default()
+                .viewport_count(1)
+                .scissor_count(1);
+            let rasterizer = vk::PipelineRasterizationStateCreateInfo::default()
+                .polygon_mode(vk::PolygonMode::FILL)
+                .cull_mode(vk::CullModeFlags::NONE)
+                .line_width(1.0);
+            let multisampling = vk::PipelineMultisampleStateCreateInfo::default()
+                .rasterization_samples(vk::SampleCountFlags::TYPE_1);
+            let blend_attachments = [vk::PipelineColorBlendAttachmentState::default()
+                .color_write_mask(vk::ColorComponentFlags::RGBA)
+                .blend_enable(true)
+                .src_color_blend_factor(vk::BlendFactor::SRC_ALPHA)
+                .dst_color_blend_factor(vk::BlendFactor::ONE_MINUS_SRC_ALPHA)
+                .color_blend_op(vk::BlendOp::ADD)
+                .src_alpha_blend_factor(vk::BlendFactor::ONE)
+                .dst_alpha_blend_factor(vk::BlendFactor::ZERO)
+                .alpha_blend_op(vk::BlendOp::ADD)];
+            let color_blending = vk::PipelineColorBlendStateCreateInfo::default()
+                .attachments(&blend_attachments);
+            let dynamic_states = [vk::DynamicState::VIEWPORT, vk::DynamicState::SCISSOR];
+            let dynamic_state = vk::PipelineDynamicStateCreateInfo::default()
+                .dynamic_states(&dynamic_states);
+
+            let depth_on = vk::PipelineDepthStencilStateCreateInfo::default()
+                .depth_test_enable(true)
+                .depth_write_enable(false)
+                .depth_compare_op(vk::CompareOp::LESS_OR_EQUAL);
+            let depth_off = vk::PipelineDepthStencilStateCreateInfo::default()
+                .depth_test_enable(false)
+                .depth_write_enable(false);
+
+            let infos = [&depth_on, &depth_off].map(|depth| {
+                vk::GraphicsPipelineCreateInfo::default()
+                    .stages(&stages)
+                    .vertex_input_state(&vertex_input)
+                    .input_assembly_state(&input_assembly)
+                    .viewport_state(&viewport_state)
+                    .rasterization_state(&rasterizer)
+                    .multisample_state(&multisampling)
+                    .depth_stencil_state(depth)
+                    .color_blend_state(&color_blending)
+                    .dynamic_state(&dynamic_state)
+                    .layout(layout)
+                    .render_pass(render_pass)
+                    .subpass(0)
+            });
+
+            let created = vk_device.create_graphics_pipelines(vk::PipelineCache::null(), &infos, None);
+            vk_device.destroy_shader_module(vertex_module, None);
+            vk_device.destroy_shader_module(fragment_module, None);
+
+            let pipelines = match created {
+                Ok(pipelines) => [pipelines[0], pipelines[1]],
+                Err((_, e)) => {
+                    vk_device.destroy_pipeline_layout(layout, None);
+                    return Err(err("Failed to create debug line pipelines", e));
+                }
+            };
+
+            self.gpu = Some(DebugGpu {
+                device: device.clone(),
+                layout,
+                pipelines,
+                buffer: vk::Buffer::null(),
+                memory: vk::DeviceMemory::null(),
+                mapped: std::ptr::null_mut(),
+                capacity: 0,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Upload queued lines and record draws into a command buffer inside the render pass
+    pub fn record(&mut self, cmd: vk::CommandBuffer, view_proj: Mat4, extent: vk::Extent2D) -> Result<(), VulkanError> {
+        let total = self.vertex_count();
+        let Some(gpu) = &mut self.gpu else {
+            return Err(VulkanError::NotInitialized);
+        };
+        if total == 0 {
+            return Ok(());
+        }
+
+        gpu.reserve(total)?;
+        let device = gpu.device.handle();
+
+        unsafe {
+            let dst = gpu.mapped as *mut DebugVertex;
+            std::ptr::copy_nonoverlapping(self.depth_tested.as_ptr(), dst, self.depth_tested.len());
+            std::ptr::copy_nonoverlapping(self.overlay.as_ptr(), dst.add(self.depth_tested.len()), self.overlay.len());
+
+            let viewport = vk::Viewport {
+                x: 0.0, y: 0.0,
+                width: extent.width as f32, height: extent.height as f32,
+                min_depth: 0.0, max_depth: 1.0,
+            };
+            device.cmd_set_viewport(cmd, 0, &[viewport]);
+            device.cmd_set_scissor(cmd, 0, &[vk::Rect2D { offset: vk::Offset2D { x: 0, y: 0 }, extent }]);
+            device.cmd_bind_vertex_buffers(cmd, 0, &[gpu.buffer], &[0]);
+            device.cmd_push_constants(
+                cmd, gpu.layout, vk::ShaderStageFlags::VERTEX, 0,
+                bytemuck::bytes_of(&view_proj.to_cols_array()),
+            );
+
+            let batches = [(0, self.depth_tested.len()), (self.depth_tested.len(), self.overlay.len())];
+            for (pipeline, (first, count)) in gpu.pipelines.iter().zip(batches) {
+                if count > 0 {
+                    device.cmd_bind_pipeline(cmd, vk::PipelineBindPoint::GRAPHICS, *pipeline);
+                    device.cmd_draw(cmd, count as u32, 1, first as u32, 0);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn destroy_gpu(&mut self) {
+        if let Some(gpu) = self.gpu.take() {
+            let device = gpu.device.handle();
+            unsafe {
+                device.device_wait_idle().ok();
+                gpu.release_buffer();
+                for pipeline in gpu.pipelines {
+                    device.destroy_pipeline(pipeline, None);
+                }
+                device.destroy_pipeline_layout(gpu.layout, None);
+            }
+        }
+    }
+}
+
+impl DebugGpu {
+    /// Grow the vertex buffer to hold at least `vertices`
+    fn reserve(&mut self, vertices: usize) -> Result<(), VulkanError> {
+        if vertices <= self.capacity {
+            return Ok(());
+        }
+
+        let capacity = vertices.next_power_of_two().max(1024);
+        let size = (capacity * std::mem::size_of::<DebugVertex>()) as vk::DeviceSize;
+        let device = self.device.handle();
+        let err = |what: &str, e: vk::Result| VulkanError::BufferCreationFailed(format!("{}: {:?}", what, e));
+
+        unsafe {
+            // Previous frame may still read the old buffer
+            device.device_wait_idle().ok();
+            self.release_buffer();
+
+            let buffer = device.create_buffer(
+                &vk::BufferCreateInfo::default()
+                    .size(size)
+                    .usage(vk::BufferUsageFlags::VERTEX_BUFFER)
+                    .sharing_mode(vk::SharingMode::EXCLUSIVE),
+                None,
+            ).map_err(|e| err("Failed to create debug vertex buffer", e))?;
+
+            let requirements = device.get_buffer_memory_requirements(buffer);
+            let memory_type = self.device.find_memory_type(
+                requirements.memory_type_bits,
+                vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT,
+            );
+            let Some(memory_type) = memory_type else {
+                device.destroy_buffer(buffer, None);
+                return Err(VulkanError::BufferCreationFailed("No host-visible memory for debug lines".to_string()));
+            };
+
+            let memory = device.allocate_memory(
+                &vk::MemoryAllocateInfo::default()
+                    .allocation_size(requirements.size)
+                    .memory_type_index(memory_type),
+                None,
+            ).map_err(|e| {
+                device.destroy_buffer(buffer, None);
+                err("Failed to allocate debug vertex memory", e)
+            })?;
+
+            let mapped = device.bind_buffer_memory(buffer, memory, 0)
+                .and_then(|_| device.map_memory(memory, 0, size, vk::MemoryMapFlags::empty()))
+                .map_err(|e| {
+                    device.destroy_buffer(buffer, None);
+                    device.free_memory(memory, None);
+                    err("Failed to map debug vertex memory", e)
+                })?;
+
+            self.buffer = buffer;
+            self.memory = memory;
+            self.mapped = mapped;
+            self.capacity = capacity;
+        }
+
+        Ok(())
+    }
+
+    unsafe fn release_buffer(&self) {
+        let device = self.device.handle();
+        if self.buffer != vk::Buffer::null() {
+            device.destroy_buffer(self.buffer, None);
+        }
+        if self.memory != vk::DeviceMemory::null() {
+            device.unmap_memory(self.memory);
+            device.free_memory(self.memory, None);
+        }
+    }
+}
+
+impl Default for DebugRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for DebugRenderer {
+    fn drop(&mut self) {
+        self.destroy_gpu();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shape_vertex_counts() {
+        let mut debug = DebugRenderer::new();
+        let red = Vec4::new(1.0, 0.0, 0.0, 1.0);
+
+        debug.draw_line(Vec3::ZERO, Vec3::X, red);
+        assert_eq!(debug.vertex_count(), 2);
+
+        debug.draw_aabb(Vec3::ZERO, Vec3::ONE, red);
+        assert_eq!(debug.vertex_count(), 2 + 24);
+
+        // Frustum outlines go on top
+        debug.set_depth_test(false);
+        let proj = Mat4::perspective_rh(70f32.to_radians(), 16.0 / 9.0, 0.1, 100.0);
+        debug.draw_frustum(proj, red);
+        debug.draw_chunk_border(2, -1, 0.0, 256.0, red);
+        let (depth_tested, overlay) = debug.vertices();
+        assert_eq!((depth_tested.len(), overlay.len()), (26, 48));
+
+        // Chunk border spans the full column
+        assert_eq!(overlay[24].position, [32.0, 0.0, -16.0]);
+
+        debug.clear();
+        assert_eq!(debug.vertex_count(), 0);
+    }
+}
@@ -0,0 +1,88 @@
+//! # Entity Meshes
+//!
+//! Custom meshes registered by mods and the per-frame instance lists that
+//! draw them. Every entity carrying a `Renderable` lands in the group for
+//! its `mesh_id`; each group is one instanced draw. Animated entities also
+//! get a slice of the frame's bone palette, which the vertex shader indexes
+//! with the instance's `bone_offset`.
+
+use std::collections::HashMap;
+
+use glam::Mat4;
+
+use crate::ecs::components::Renderable;
+use crate::ecs::{EcsWorld, Entity};
+use crate::renderer::vulkan::mesh_shader::MeshVertex;
+
+/// Registered entity mesh
+#[derive(Debug, Clone)]
+pub struct RendererMesh {
+    pub vertices: Vec<MeshVertex>,
+    pub indices: Vec<u32>,
+    // In full implementation, would have vertex/index buffer handles
+}
+
+/// Per-instance data for one entity
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct EntityInstance {
+    /// World position
+    pub position: [f32; 3],
+    /// Uniform scale
+    pub scale: f32,
+    /// Texture handle
+    pub texture_id: u32,
+    /// Owning entity
+    pub entity: Entity,
+    /// First bone matrix in the palette
+    pub bone_offset: u32,
+    /// Number of bone matrices (0 for unanimated entities)
+    pub bone_count: u32,
+}
+
+/// Group renderable entities by mesh id
+///
+/// Entities without a known position or with an unregistered mesh are
+/// skipped. Instances within a group are sorted by entity for stable
+/// upload order. Bone matrices of animated instances are appended to
+/// `bones` (cleared first) in draw order.
+pub fn build_instance_groups(
+    ecs: &EcsWorld,
+    meshes: &HashMap<u32, RendererMesh>,
+    bones: &mut Vec<Mat4>,
+) -> HashMap<u32, Vec<EntityInstance>> {
+    let mut groups: HashMap<u32, Vec<EntityInstance>> = HashMap::new();
+
+    for (entity, renderable) in ecs.query::<Renderable>() {
+        if !meshes.contains_key(&renderable.mesh_id) {
+            continue;
+        }
+        let Some(position) = ecs.position(entity) else {
+            continue;
+        };
+        groups.entry(renderable.mesh_id).or_default().push(EntityInstance {
+            position: position.to_array(),
+            scale: renderable.scale,
+            texture_id: renderable.texture_id,
+            entity,
+            bone_offset: 0,
+            bone_count: 0,
+        });
+    }
+
+    bones.clear();
+    let mut mesh_ids: Vec<u32> = groups.keys().copied().collect();
+    mesh_ids.sort_unstable();
+    for mesh_id in mesh_ids {
+        let instances = groups.get_mut(&mesh_id).expect("mesh id taken from groups");
+        instances.sort_unstable_by_key(|instance| instance.entity);
+        for instance in instances {
+            if let Some(matrices) = ecs.animation().bone_matrices(instance.entity) {
+                instance.bone_offset = bones.len() as u32;
+                instance.bone_count = matrices.len() as u32;
+                bones.extend_from_slice(matrices);
+            }
+        }
+    }
+    groups
+}
@@ -0,0 +1,530 @@
+//! # Render Graph
+//!
+//! Lightweight pass scheduler. Passes declare which buffers/images they
+//! read and write; the graph derives execution order from those
+//! declarations and inserts the pipeline barriers and image layout
+//! transitions between dependent passes.
+//!
+//! A pass reads what the nearest earlier writer produced. A resource with
+//! no contents of its own (a transient buffer, or an image imported in
+//! `UNDEFINED` layout) has nothing to read before its first writer, so a
+//! read of it declared ahead of that writer is scheduled after it instead.
+//! Passes are otherwise kept in declaration order.
+
+use std::collections::HashMap;
+use ash::vk;
+
+/// Graph resource handle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ResourceId(u32);
+
+/// Imported resource
+#[derive(Debug, Clone, Copy)]
+enum GraphResource {
+    Buffer {
+        buffer: vk::Buffer,
+        transient: bool,
+    },
+    Image {
+        image: vk::Image,
+        aspect: vk::ImageAspectFlags,
+        initial_layout: vk::ImageLayout,
+    },
+}
+
+/// How a pass uses a resource
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResourceUse {
+    /// Resource being accessed
+    pub resource: ResourceId,
+    /// Pipeline stage of the access
+    pub stage: vk::PipelineStageFlags,
+    /// Access type
+    pub access: vk::AccessFlags,
+    /// Required layout (images only, UNDEFINED for buffers)
+    pub layout: vk::ImageLayout,
+}
+
+impl ResourceUse {
+    /// Buffer access
+    pub fn buffer(resource: ResourceId, stage: vk::PipelineStageFlags, access: vk::AccessFlags) -> Self {
+        Self { resource, stage, access, layout: vk::ImageLayout::UNDEFINED }
+    }
+
+    /// Image access in a given layout
+    pub fn image(
+        resource: ResourceId,
+        stage: vk::PipelineStageFlags,
+        access: vk::AccessFlags,
+        layout: vk::ImageLayout,
+    ) -> Self {
+        Self { resource, stage, access, layout }
+    }
+}
+
+/// Barrier computed between two passes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlannedBarrier {
+    /// Pass the barrier is recorded before
+    pub before_pass: usize,
+    /// Resource the barrier covers
+    pub resource: ResourceId,
+    /// Source stage
+    pub src_stage: vk::PipelineStageFlags,
+    /// Destination stage
+    pub dst_stage: vk::PipelineStageFlags,
+    /// Source access
+    pub src_access: vk::AccessFlags,
+    /// Destination access
+    pub dst_access: vk::AccessFlags,
+    /// Layout before the barrier (images)
+    pub old_layout: vk::ImageLayout,
+    /// Layout after the barrier (images)
+    pub new_layout: vk::ImageLayout,
+}
+
+/// Pass record callback
+pub type RecordFn<'a> = Box<dyn FnMut(vk::CommandBuffer) + 'a>;
+
+/// Render pass node
+struct PassNode<'a> {
+    /// Pass name
+    name: String,
+    /// Resources read
+    reads: Vec<ResourceUse>,
+    /// Resources written
+    writes: Vec<ResourceUse>,
+    /// Command recording callback
+    record: RecordFn<'a>,
+}
+
+/// Last known state of a resource while planning
+#[derive(Debug, Clone, Copy)]
+struct ResourceState {
+    stage: vk::PipelineStageFlags,
+    access: vk::AccessFlags,
+    layout: vk::ImageLayout,
+    written: bool,
+}
+
+/// Render graph
+pub struct RenderGraph<'a> {
+    /// Imported resources
+    resources: Vec<(String, GraphResource)>,
+    /// Declared passes
+    passes: Vec<PassNode<'a>>,
+}
+
+impl<'a> RenderGraph<'a> {
+    /// Create an empty graph
+    pub fn new() -> Self {
+        Self {
+            resources: Vec::new(),
+            passes: Vec::new(),
+        }
+    }
+
+    /// Import an external buffer, keeping its contents
+    pub fn import_buffer(&mut self, name: &str, buffer: vk::Buffer) -> ResourceId {
+        self.resources.push((name.to_string(), GraphResource::Buffer { buffer, transient: false }));
+        ResourceId(self.resources.len() as u32 - 1)
+    }
+
+    /// Import an external buffer whose contents are produced within the graph
+    pub fn import_transient_buffer(&mut self, name: &str, buffer: vk::Buffer) -> ResourceId {
+        self.resources.push((name.to_string(), GraphResource::Buffer { buffer, transient: true }));
+        ResourceId(self.resources.len() as u32 - 1)
+    }
+
+    /// Import an external image in its current layout
+    pub fn import_image(
+        &mut self,
+        name: &str,
+        image: vk::Image,
+        aspect: vk::ImageAspectFlags,
+        initial_layout: vk::ImageLayout,
+    ) -> ResourceId {
+        self.resources.push((name.to_string(), GraphResource::Image { image, aspect, initial_layout }));
+        ResourceId(self.resources.len() as u32 - 1)
+    }
+
+    /// Add a pass
+    pub fn add_pass<F>(&mut self, name: &str, reads: &[ResourceUse], writes: &[ResourceUse], record_fn: F) -> usize
+    where
+        F: FnMut(vk::CommandBuffer) + 'a,
+    {
+        self.passes.push(PassNode {
+            name: name.to_string(),
+            reads: reads.to_vec(),
+            writes: writes.to_vec(),
+            record: Box::new(record_fn),
+        });
+        self.passes.len() - 1
+    }
+
+    /// Get pass count
+    pub fn pass_count(&self) -> usize {
+        self.passes.len()
+    }
+
+    /// Get pass name
+    pub fn pass_name(&self, pass: usize) -> Option<&str> {
+        self.passes.get(pass).map(|p| p.name.as_str())
+    }
+
+    /// Whether a resource holds contents before any pass writes it
+    fn has_initial_contents(&self, resource: ResourceId) -> bool {
+        match self.resources[resource.0 as usize].1 {
+            GraphResource::Buffer { transient, .. } => !transient,
+            GraphResource::Image { initial_layout, .. } => initial_layout != vk::ImageLayout::UNDEFINED,
+        }
+    }
+
+    /// Compute execution order (topological, stable by declaration order)
+    ///
+    /// Fails naming the passes caught in a dependency cycle.
+    pub fn execution_order(&self) -> Result<Vec<usize>, String> {
+        let count = self.passes.len();
+        let mut deps: Vec<Vec<usize>> = vec![Vec::new(); count];
+        let mut first_writer: HashMap<ResourceId, usize> = HashMap::new();
+        let mut last_writer: HashMap<ResourceId, usize> = HashMap::new();
+        let mut readers_since_write: HashMap<ResourceId, Vec<usize>> = HashMap::new();
+
+        for (i, pass) in self.passes.iter().enumerate() {
+            for w in &pass.writes {
+                first_writer.entry(w.resource).or_insert(i);
+            }
+        }
+
+        for (i, pass) in self.passes.iter().enumerate() {
+            // Read-after-write; an empty resource read ahead of its first writer waits for it
+            let mut early_reads = Vec::new();
+            for r in &pass.reads {
+                if let Some(&w) = last_writer.get(&r.resource) {
+                    deps[i].push(w);
+                } else if !self.has_initial_contents(r.resource) {
+                    match first_writer.get(&r.resource) {
+                        Some(&w) if w != i => {
+                            deps[i].push(w);
+                            early_reads.push(r.resource);
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            // Write-after-write and write-after-read
+            for w in &pass.writes {
+                if let Some(&prev) = last_writer.get(&w.resource) {
+                    deps[i].push(prev);
+                }
+                if let Some(readers) = readers_since_write.get(&w.resource) {
+                    deps[i].extend(readers.iter().copied().filter(|&r| r != i));
+                }
+            }
+            for r in pass.reads.iter().filter(|r| !early_reads.contains(&r.resource)) {
+                readers_since_write.entry(r.resource).or_default().push(i);
+            }
+            for w in &pass.writes {
+                last_writer.insert(w.resource, i);
+                readers_since_write.remove(&w.resource);
+            }
+        }
+
+        // Kahn's algorithm, always picking the lowest ready index
+        let mut remaining: Vec<usize> = deps.iter().map(|d| d.len()).collect();
+        let mut dependents: Vec<Vec<usize>> = vec![Vec::new(); count];
+        for (i, d) in deps.iter().enumerate() {
+            for &p in d {
+                dependents[p].push(i);
+            }
+        }
+
+        let mut ready: std::collections::BTreeSet<usize> =
+            (0..count).filter(|&i| remaining[i] == 0).collect();
+        let mut order = Vec::with_capacity(count);
+
+        while let Some(&next) = ready.iter().next() {
+            ready.remove(&next);
+            order.push(next);
+            for &d in &dependents[next] {
+                remaining[d] -= 1;
+                if remaining[d] == 0 {
+                    ready.insert(d);
+                }
+            }
+        }
+
+        if order.len() < count {
+            let stuck: Vec<&str> = (0..count)
+                .filter(|&i| remaining[i] > 0)
+                .map(|i| self.passes[i].name.as_str())
+                .collect();
+            return Err(format!("render graph has a dependency cycle between passes: {}", stuck.join(", ")));
+        }
+
+        Ok(order)
+    }
+
+    /// Compute barriers needed for the given execution order
+    pub fn plan_barriers(&self, order: &[usize]) -> Vec<PlannedBarrier> {
+        let mut states: HashMap<ResourceId, ResourceState> = HashMap::new();
+        let mut barriers = Vec::new();
+
+        for &pass_idx in order {
+            let pass = &self.passes[pass_idx];
+            let uses = pass.reads.iter().map(|u| (u, false))
+                .chain(pass.writes.iter().map(|u| (u, true)));
+
+            for (usage, is_write) in uses {
+                let is_image = matches!(self.resources[usage.resource.0 as usize].1, GraphResource::Image { .. });
+
+                let prev = states.get(&usage.resource).copied().unwrap_or_else(|| ResourceState {
+                    stage: vk::PipelineStageFlags::TOP_OF_PIPE,
+                    access: vk::AccessFlags::empty(),
+                    layout: match self.resources[usage.resource.0 as usize].1 {
+                        GraphResource::Image { initial_layout, .. } => initial_layout,
+                        GraphResource::Buffer { .. } => vk::ImageLayout::UNDEFINED,
+                    },
+                    written: false,
+                });
+
+                let layout_change = is_image && prev.layout != usage.layout;
+                let hazard = prev.written || (is_write && !prev.access.is_empty());
+
+                if hazard || layout_change {
+                    barriers.push(PlannedBarrier {
+                        before_pass: pass_idx,
+                        resource: usage.resource,
+                        src_stage: prev.stage,
+                        dst_stage: usage.stage,
+                        src_access: prev.access,
+                        dst_access: usage.access,
+                        old_layout: prev.layout,
+                        new_layout: usage.layout,
+                    });
+                    states.insert(usage.resource, ResourceState {
+                        stage: usage.stage,
+                        access: usage.access,
+                        layout: usage.layout,
+                        written: is_write,
+                    });
+                } else if is_write {
+                    // First access is a write: nothing to wait on
+                    states.insert(usage.resource, ResourceState {
+                        stage: usage.stage,
+                        access: usage.access,
+                        layout: usage.layout,
+                        written: true,
+                    });
+                } else {
+                    // Read after read: widen the tracked stages so a later write waits on both
+                    states.insert(usage.resource, ResourceState {
+                        stage: prev.stage | usage.stage,
+                        access: prev.access | usage.access,
+                        layout: prev.layout,
+                        written: false,
+                    });
+                }
+            }
+        }
+
+        barriers
+    }
+
+    /// Record all passes into a command buffer
+    pub fn execute(&mut self, device: &ash::Device, cmd: vk::CommandBuffer) -> Result<(), String> {
+        let order = self.execution_order()?;
+        let barriers = self.plan_barriers(&order);
+
+        for &pass_idx in &order {
+            let pass_barriers: Vec<&PlannedBarrier> =
+                barriers.iter().filter(|b| b.before_pass == pass_idx).collect();
+
+            if !pass_barriers.is_empty() {
+                self.record_barriers(device, cmd, &pass_barriers);
+            }
+
+            (self.passes[pass_idx].record)(cmd);
+        }
+        Ok(())
+    }
+
+    /// Record one pipeline barrier covering all planned barriers for a pass
+    fn record_barriers(&self, device: &ash::Device, cmd: vk::CommandBuffer, barriers: &[&PlannedBarrier]) {
+        let mut src_stage = vk::PipelineStageFlags::empty();
+        let mut dst_stage = vk::PipelineStageFlags::empty();
+        let mut buffer_barriers = Vec::new();
+        let mut image_barriers = Vec::new();
+
+        for b in barriers {
+            src_stage |= b.src_stage;
+            dst_stage |= b.dst_stage;
+
+            match self.resources[b.resource.0 as usize].1 {
+                GraphResource::Buffer { buffer, .. } => {
+                    buffer_barriers.push(
+                        vk::BufferMemoryBarrier::default()
+                            .src_access_mask(b.src_access)
+                            .dst_access_mask(b.dst_access)
+                            .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                            .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                            .buffer(buffer)
+                            .offset(0)
+                            .size(vk::WHOLE_SIZE),
+                    );
+                }
+                GraphResource::Image { image, aspect, .. } => {
+                    image_barriers.push(
+                        vk::ImageMemoryBarrier::default()
+                            .src_access_mask(b.src_access)
+                            .dst_access_mask(b.dst_access)
+                            .old_layout(b.old_layout)
+                            .new_layout(b.new_layout)
+                            .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                            .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                            .image(image)
+                            .subresource_range(vk::ImageSubresourceRange {
+                                aspect_mask: aspect,
+                                base_mip_level: 0,
+                                level_count: vk::REMAINING_MIP_LEVELS,
+                                base_array_layer: 0,
+                                layer_count: vk::REMAINING_ARRAY_LAYERS,
+                            }),
+                    );
+                }
+            }
+        }
+
+        unsafe {
+            device.cmd_pipeline_barrier(
+                cmd,
+                src_stage,
+                dst_stage,
+                vk::DependencyFlags::empty(),
+                &[],
+                &buffer_barriers,
+                &image_barriers,
+            );
+        }
+    }
+}
+
+impl Default for RenderGraph<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_producer_consumer_chain() {
+        let mut graph = RenderGraph::new();
+        let particles = graph.import_buffer("particles", vk::Buffer::null());
+        let color = graph.import_image(
+            "color",
+            vk::Image::null(),
+            vk::ImageAspectFlags::COLOR,
+            vk::ImageLayout::UNDEFINED,
+        );
+
+        let simulate = graph.add_pass(
+            "simulate",
+            &[],
+            &[ResourceUse::buffer(particles, vk::PipelineStageFlags::COMPUTE_SHADER, vk::AccessFlags::SHADER_WRITE)],
+            |_| {},
+        );
+        let draw = graph.add_pass(
+            "draw",
+            &[ResourceUse::buffer(particles, vk::PipelineStageFlags::VERTEX_SHADER, vk::AccessFlags::SHADER_READ)],
+            &[ResourceUse::image(
+                color,
+                vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+                vk::AccessFlags::COLOR_ATTACHMENT_WRITE,
+                vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+            )],
+            |_| {},
+        );
+
+        let order = graph.execution_order().unwrap();
+        assert_eq!(order, vec![simulate, draw]);
+
+        let barriers = graph.plan_barriers(&order);
+        assert_eq!(barriers.len(), 2);
+
+        let buffer_barrier = barriers.iter().find(|b| b.resource == particles).unwrap();
+        assert_eq!(buffer_barrier.before_pass, draw);
+        assert_eq!(buffer_barrier.src_stage, vk::PipelineStageFlags::COMPUTE_SHADER);
+        assert_eq!(buffer_barrier.dst_stage, vk::PipelineStageFlags::VERTEX_SHADER);
+        assert_eq!(buffer_barrier.src_access, vk::AccessFlags::SHADER_WRITE);
+        assert_eq!(buffer_barrier.dst_access, vk::AccessFlags::SHADER_READ);
+
+        let image_barrier = barriers.iter().find(|b| b.resource == color).unwrap();
+        assert_eq!(image_barrier.old_layout, vk::ImageLayout::UNDEFINED);
+        assert_eq!(image_barrier.new_layout, vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL);
+    }
+
+    #[test]
+    fn test_passes_declared_out_of_order() {
+        let mut graph = RenderGraph::new();
+        let particles = graph.import_transient_buffer("particles", vk::Buffer::null());
+        let history = graph.import_buffer("history", vk::Buffer::null());
+        let color = graph.import_image(
+            "color",
+            vk::Image::null(),
+            vk::ImageAspectFlags::COLOR,
+            vk::ImageLayout::UNDEFINED,
+        );
+        let color_read = ResourceUse::image(
+            color,
+            vk::PipelineStageFlags::FRAGMENT_SHADER,
+            vk::AccessFlags::SHADER_READ,
+            vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+        );
+        let color_write = ResourceUse::image(
+            color,
+            vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT,
+            vk::AccessFlags::COLOR_ATTACHMENT_WRITE,
+            vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+        );
+        let particle_write =
+            ResourceUse::buffer(particles, vk::PipelineStageFlags::COMPUTE_SHADER, vk::AccessFlags::SHADER_WRITE);
+        let particle_read =
+            ResourceUse::buffer(particles, vk::PipelineStageFlags::VERTEX_SHADER, vk::AccessFlags::SHADER_READ);
+        let history_read =
+            ResourceUse::buffer(history, vk::PipelineStageFlags::COMPUTE_SHADER, vk::AccessFlags::SHADER_READ);
+        let history_write =
+            ResourceUse::buffer(history, vk::PipelineStageFlags::COMPUTE_SHADER, vk::AccessFlags::SHADER_WRITE);
+
+        // Consumers first, producers last
+        let composite = graph.add_pass("composite", &[color_read], &[], |_| {});
+        let draw = graph.add_pass("draw", &[particle_read], &[color_write], |_| {});
+        let simulate = graph.add_pass("simulate", &[history_read], &[particle_write], |_| {});
+        // Last frame's history is read before this frame's is written
+        let store = graph.add_pass("store", &[], &[history_write], |_| {});
+
+        let order = graph.execution_order().unwrap();
+        assert_eq!(order, vec![simulate, draw, composite, store]);
+
+        let barriers = graph.plan_barriers(&order);
+        let particle_barrier = barriers.iter().find(|b| b.resource == particles).unwrap();
+        assert_eq!(particle_barrier.before_pass, draw);
+        assert_eq!(particle_barrier.src_access, vk::AccessFlags::SHADER_WRITE);
+        let color_barrier = barriers.iter().rev().find(|b| b.resource == color).unwrap();
+        assert_eq!(color_barrier.before_pass, composite);
+        assert_eq!(color_barrier.old_layout, vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL);
+        assert_eq!(color_barrier.new_layout, vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL);
+
+        // A pass feeding its own producer can't be scheduled
+        let mut graph = RenderGraph::new();
+        let a = graph.import_transient_buffer("a", vk::Buffer::null());
+        let b = graph.import_transient_buffer("b", vk::Buffer::null());
+        let use_of = |resource, access| ResourceUse::buffer(resource, vk::PipelineStageFlags::COMPUTE_SHADER, access);
+        graph.add_pass("first", &[use_of(a, vk::AccessFlags::SHADER_READ)], &[use_of(b, vk::AccessFlags::SHADER_WRITE)], |_| {});
+        graph.add_pass("second", &[use_of(b, vk::AccessFlags::SHADER_READ)], &[use_of(a, vk::AccessFlags::SHADER_WRITE)], |_| {});
+        let err = graph.execution_order().unwrap_err();
+        assert!(err.contains("first") && err.contains("second"), "{}", err);
+    }
+}
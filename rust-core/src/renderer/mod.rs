@@ -3,12 +3,18 @@
 //! Vulkan/OpenGL rendering system with mesh shaders, particles, and shader compilation.
 
 pub mod vulkan;
+pub mod opengl;
+pub mod debug;
+pub mod entities;
+pub mod graph;
 pub mod shaders;
 pub mod particles;
 pub mod quantum;
 pub mod bindless;
+pub mod streaming;
 
 use std::collections::HashMap;
+#[cfg(feature = "audio")]
 use crate::engine::EngineConfig;
 
 /// The renderer
@@ -16,6 +22,9 @@ pub struct Renderer {
     /// Render mode
     mode: RenderMode,
     
+    /// Settings the Vulkan backend is created with
+    vulkan_config: vulkan::VulkanConfig,
+    
     /// Current frame number
     frame: u64,
     
@@ -37,6 +46,19 @@ pub struct Renderer {
     
     /// Particle systems
     particle_systems: Vec<particles::ParticleSystem>,
+    
+    /// GL context for GUI rendering (OpenGL/Hybrid modes)
+    gl: Option<opengl::GlContext>,
+    
+    /// Registered entity meshes
+    meshes: HashMap<u32, entities::RendererMesh>,
+    next_mesh_id: u32,
+    
+    /// Entity instances per mesh for the current frame
+    instance_groups: HashMap<u32, Vec<entities::EntityInstance>>,
+    
+    /// Bone matrices of animated instances for the current frame
+    bone_palette: Vec<glam::Mat4>,
 }
 
 /// Render mode
@@ -58,14 +80,21 @@ pub struct RendererTexture {
 }
 
 impl Renderer {
-    /// Create a new renderer
+    /// Create a new renderer (the engine module needs the `audio` feature too)
+    #[cfg(feature = "audio")]
     pub fn new(config: &EngineConfig) -> Result<Self, String> {
         let mode = match config.render_mode {
             crate::engine::config::RenderMode::Vulkan => RenderMode::Vulkan,
             crate::engine::config::RenderMode::Opengl => RenderMode::OpenGL,
             crate::engine::config::RenderMode::Hybrid => RenderMode::Hybrid,
         };
-        
+        let mut renderer = Self::with_mode(mode)?;
+        renderer.vulkan_config.anisotropy_level = config.anisotropic_filtering;
+        Ok(renderer)
+    }
+    
+    /// Create a new renderer in `mode`
+    pub fn with_mode(mode: RenderMode) -> Result<Self, String> {
         log::info!("Renderer created with mode: {:?}", mode);
         
         // Initialize shader manager
@@ -73,6 +102,7 @@ impl Renderer {
         
         Ok(Self {
             mode,
+            vulkan_config: vulkan::VulkanConfig::default(),
             frame: 0,
             in_frame: false,
             camera_x: 0.0,
@@ -83,6 +113,11 @@ impl Renderer {
             textures: HashMap::new(),
             shader_manager,
             particle_systems: Vec::new(),
+            gl: None,
+            meshes: HashMap::new(),
+            next_mesh_id: 1,
+            instance_groups: HashMap::new(),
+            bone_palette: Vec::new(),
         })
     }
     
@@ -137,11 +172,85 @@ impl Renderer {
         }
     }
     
+    /// Register an entity mesh; returns its mesh id (never 0)
+    pub fn register_mesh(&mut self, vertices: &[vulkan::mesh_shader::MeshVertex], indices: &[u32]) -> u32 {
+        let id = self.next_mesh_id;
+        self.next_mesh_id += 1;
+        
+        self.meshes.insert(id, entities::RendererMesh {
+            vertices: vertices.to_vec(),
+            indices: indices.to_vec(),
+        });
+        
+        // In full implementation, would upload vertex/index buffers
+        log::trace!("Renderer: Mesh registered: {} ({} vertices, {} indices)", id, vertices.len(), indices.len());
+        id
+    }
+    
+    /// Get mesh count
+    pub fn mesh_count(&self) -> usize {
+        self.meshes.len()
+    }
+    
+    /// Rebuild per-mesh instance lists from the ECS
+    pub fn update_entity_instances(&mut self, ecs: &crate::ecs::EcsWorld) {
+        self.instance_groups = entities::build_instance_groups(ecs, &self.meshes, &mut self.bone_palette);
+    }
+    
+    /// Get the bone matrices indexed by `EntityInstance::bone_offset` this frame
+    pub fn bone_palette(&self) -> &[glam::Mat4] {
+        &self.bone_palette
+    }
+    
+    /// Get the instances drawn with a mesh this frame
+    pub fn instance_group(&self, mesh_id: u32) -> &[entities::EntityInstance] {
+        self.instance_groups.get(&mesh_id).map_or(&[], Vec::as_slice)
+    }
+    
+    /// Get the number of instanced draws this frame (one per mesh)
+    pub fn instanced_draw_count(&self) -> usize {
+        self.instance_groups.len()
+    }
+    
+    /// Attach the game's GL context for GUI rendering
+    pub fn attach_gl_context(&mut self, gl: opengl::GlContext) {
+        self.gl = Some(gl);
+    }
+    
+    /// Get GL context
+    pub fn gl_context(&mut self) -> Option<&mut opengl::GlContext> {
+        self.gl.as_mut()
+    }
+    
+    /// Draw GUI elements through GL (Vulkan mode draws GUI in the compositor)
+    pub fn draw_gui(&mut self, elements: &[quantum::compositor::GuiElement]) -> Result<(), String> {
+        if self.mode == RenderMode::Vulkan {
+            return Ok(());
+        }
+        match &mut self.gl {
+            Some(gl) => gl.draw_gui(elements),
+            None => Err(format!("{:?} mode requires a GL context", self.mode)),
+        }
+    }
+    
     /// Get render mode
     pub fn mode(&self) -> RenderMode {
         self.mode
     }
     
+    /// Get the settings the Vulkan backend is created with
+    pub fn vulkan_config(&self) -> &vulkan::VulkanConfig {
+        &self.vulkan_config
+    }
+    
+    /// Get GUI compositor settings matching the Vulkan backend's sampling
+    pub fn compositor_config(&self) -> quantum::compositor::CompositorConfig {
+        quantum::compositor::CompositorConfig {
+            anisotropy_level: self.vulkan_config.anisotropy_level,
+            ..Default::default()
+        }
+    }
+    
     /// Get frame count
     pub fn frame_count(&self) -> u64 {
         self.frame
@@ -0,0 +1,367 @@
+//! # OpenGL GUI Backend
+//!
+//! Renders GUI elements as textured quads through the GL context owned by
+//! the game (LWJGL). Used in `Hybrid` mode: the GUI is drawn into an
+//! offscreen color texture which `VulkanGLInterop` shares with Vulkan for
+//! composition over the world.
+
+use std::collections::HashMap;
+use std::num::NonZeroU32;
+use glow::HasContext;
+
+use crate::renderer::quantum::compositor::GuiElement;
+use crate::renderer::vulkan::interop::SharedTexture;
+
+/// Floats per quad vertex (x, y, u, v)
+pub const QUAD_VERTEX_FLOATS: usize = 4;
+
+const QUAD_VERTEX_SHADER: &str = r#"#version 330 core
+layout(location = 0) in vec2 a_pos;
+layout(location = 1) in vec2 a_uv;
+out vec2 v_uv;
+void main() {
+    v_uv = a_uv;
+    gl_Position = vec4(a_pos, 0.0, 1.0);
+}
+"#;
+
+const QUAD_FRAGMENT_SHADER: &str = r#"#version 330 core
+in vec2 v_uv;
+out vec4 o_color;
+uniform sampler2D u_texture;
+uniform float u_opacity;
+uniform int u_textured;
+uniform vec4 u_color;
+uniform float u_sdf_range;
+float median(vec3 v) {
+    return max(min(v.r, v.g), min(max(v.r, v.g), v.b));
+}
+void main() {
+    vec4 color = u_textured != 0 ? texture(u_texture, v_uv) : vec4(1.0);
+    if (u_sdf_range > 0.0) {
+        // MSDF glyph: coverage from the signed distance, one pixel wide edge
+        float distance = u_sdf_range * (median(color.rgb) - 0.5);
+        color = vec4(1.0, 1.0, 1.0, clamp(distance + 0.5, 0.0, 1.0));
+    }
+    color *= u_color;
+    o_color = vec4(color.rgb, color.a * u_opacity);
+}
+"#;
+
+/// Build a triangle-strip quad for a GUI element in normalized device coordinates
+///
+/// Element coordinates are pixels with a top-left origin.
+pub fn gui_quad(element: &GuiElement, width: u32, height: u32) -> [f32; 4 * QUAD_VERTEX_FLOATS] {
+    let to_ndc_x = |x: f32| x / width as f32 * 2.0 - 1.0;
+    let to_ndc_y = |y: f32| 1.0 - y / height as f32 * 2.0;
+
+    let [u0, v0, u1, v1] = element.uv;
+    let left = to_ndc_x(element.x);
+    let right = to_ndc_x(element.x + element.width);
+    let top = to_ndc_y(element.y);
+    let bottom = to_ndc_y(element.y + element.height);
+
+    [
+        left, top, u0, v0,
+        left, bottom, u0, v1,
+        right, top, u1, v0,
+        right, bottom, u1, v1,
+    ]
+}
+
+/// Visible elements in draw order (back to front)
+pub fn draw_order(elements: &[GuiElement]) -> Vec<&GuiElement> {
+    let mut visible: Vec<&GuiElement> = elements.iter()
+        .filter(|e| e.visible && e.opacity > 0.0)
+        .collect();
+    visible.sort_by_key(|e| e.z_order);
+    visible
+}
+
+/// Offscreen GL render target
+struct GlTarget {
+    framebuffer: glow::Framebuffer,
+    texture: glow::Texture,
+    /// Texture owned by this context (not imported from interop)
+    owned: bool,
+    width: u32,
+    height: u32,
+}
+
+/// OpenGL context wrapper for GUI rendering
+pub struct GlContext {
+    gl: glow::Context,
+    program: glow::Program,
+    vertex_array: glow::VertexArray,
+    vertex_buffer: glow::Buffer,
+    u_opacity: Option<glow::UniformLocation>,
+    u_textured: Option<glow::UniformLocation>,
+    u_color: Option<glow::UniformLocation>,
+    u_sdf_range: Option<glow::UniformLocation>,
+    target: Option<GlTarget>,
+    /// GUI texture handle -> GL texture name
+    textures: HashMap<u64, glow::Texture>,
+    /// Quads drawn last frame
+    quads_drawn: usize,
+}
+
+impl GlContext {
+    /// Wrap the GL context current on this thread
+    ///
+    /// # Safety
+    /// A GL 3.3+ context must be current on the calling thread, and every
+    /// later call must happen on a thread where that context is current.
+    pub unsafe fn from_loader<F>(loader: F) -> Result<Self, String>
+    where
+        F: FnMut(&str) -> *const std::ffi::c_void,
+    {
+        let gl = glow::Context::from_loader_function(loader);
+
+        let program = Self::create_program(&gl)?;
+        let vertex_array = gl.create_vertex_array()
+            .map_err(|e| format!("Failed to create vertex array: {}", e))?;
+        let vertex_buffer = gl.create_buffer()
+            .map_err(|e| format!("Failed to create vertex buffer: {}", e))?;
+
+        gl.bind_vertex_array(Some(vertex_array));
+        gl.bind_buffer(glow::ARRAY_BUFFER, Some(vertex_buffer));
+        gl.buffer_data_size(glow::ARRAY_BUFFER, (4 * QUAD_VERTEX_FLOATS * 4) as i32, glow::DYNAMIC_DRAW);
+        let stride = (QUAD_VERTEX_FLOATS * 4) as i32;
+        gl.vertex_attrib_pointer_f32(0, 2, glow::FLOAT, false, stride, 0);
+        gl.enable_vertex_attrib_array(0);
+        gl.vertex_attrib_pointer_f32(1, 2, glow::FLOAT, false, stride, 8);
+        gl.enable_vertex_attrib_array(1);
+        gl.bind_vertex_array(None);
+
+        gl.use_program(Some(program));
+        gl.uniform_1_i32(gl.get_uniform_location(program, "u_texture").as_ref(), 0);
+        let u_opacity = gl.get_uniform_location(program, "u_opacity");
+        let u_textured = gl.get_uniform_location(program, "u_textured");
+        let u_color = gl.get_uniform_location(program, "u_color");
+        let u_sdf_range = gl.get_uniform_location(program, "u_sdf_range");
+        gl.use_program(None);
+
+        log::info!("GL GUI backend ready ({})", gl.get_parameter_string(glow::VERSION));
+
+        Ok(Self {
+            gl,
+            program,
+            vertex_array,
+            vertex_buffer,
+            u_opacity,
+            u_textured,
+            u_color,
+            u_sdf_range,
+            target: None,
+            textures: HashMap::new(),
+            quads_drawn: 0,
+        })
+    }
+
+    unsafe fn create_program(gl: &glow::Context) -> Result<glow::Program, String> {
+        let program = gl.create_program()?;
+
+        let mut shaders = Vec::new();
+        for (stage, source) in [(glow::VERTEX_SHADER, QUAD_VERTEX_SHADER), (glow::FRAGMENT_SHADER, QUAD_FRAGMENT_SHADER)] {
+            let shader = gl.create_shader(stage)?;
+            gl.shader_source(shader, source);
+            gl.compile_shader(shader);
+            if !gl.get_shader_compile_status(shader) {
+                let log = gl.get_shader_info_log(shader);
+                gl.delete_shader(shader);
+                gl.delete_program(program);
+                return Err(format!("GUI shader compile failed: {}", log));
+            }
+            gl.attach_shader(program, shader);
+            shaders.push(shader);
+        }
+
+        gl.link_program(program);
+        for shader in shaders {
+            gl.detach_shader(program, shader);
+            gl.delete_shader(shader);
+        }
+        if !gl.get_program_link_status(program) {
+            let log = gl.get_program_info_log(program);
+            gl.delete_program(program);
+            return Err(format!("GUI program link failed: {}", log));
+        }
+
+        Ok(program)
+    }
+
+    /// Create an owned offscreen RGBA8 target
+    pub fn create_target(&mut self, width: u32, height: u32) -> Result<(), String> {
+        unsafe {
+            let texture = self.gl.create_texture()?;
+            self.gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+            self.gl.tex_image_2d(
+                glow::TEXTURE_2D, 0, glow::RGBA8 as i32,
+                width as i32, height as i32, 0,
+                glow::RGBA, glow::UNSIGNED_BYTE, None,
+            );
+            self.gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_MIN_FILTER, glow::LINEAR as i32);
+            self.gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_MAG_FILTER, glow::LINEAR as i32);
+            self.gl.bind_texture(glow::TEXTURE_2D, None);
+
+            self.attach_target(texture, true, width, height)
+        }
+    }
+
+    /// Render into a texture shared with Vulkan (zero-copy composition)
+    pub fn bind_shared_texture(&mut self, shared: &SharedTexture) -> Result<(), String> {
+        let name = NonZeroU32::new(shared.gl_texture)
+            .ok_or_else(|| format!("Shared texture {} has no GL name", shared.id))?;
+        unsafe { self.attach_target(glow::NativeTexture(name), false, shared.width, shared.height) }
+    }
+
+    unsafe fn attach_target(&mut self, texture: glow::Texture, owned: bool, width: u32, height: u32) -> Result<(), String> {
+        self.destroy_target();
+
+        let framebuffer = self.gl.create_framebuffer()?;
+        self.gl.bind_framebuffer(glow::FRAMEBUFFER, Some(framebuffer));
+        self.gl.framebuffer_texture_2d(glow::FRAMEBUFFER, glow::COLOR_ATTACHMENT0, glow::TEXTURE_2D, Some(texture), 0);
+        let status = self.gl.check_framebuffer_status(glow::FRAMEBUFFER);
+        self.gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+
+        if status != glow::FRAMEBUFFER_COMPLETE {
+            self.gl.delete_framebuffer(framebuffer);
+            if owned {
+                self.gl.delete_texture(texture);
+            }
+            return Err(format!("GUI framebuffer incomplete: 0x{:X}", status));
+        }
+
+        self.target = Some(GlTarget { framebuffer, texture, owned, width, height });
+        Ok(())
+    }
+
+    fn destroy_target(&mut self) {
+        if let Some(target) = self.target.take() {
+            unsafe {
+                self.gl.delete_framebuffer(target.framebuffer);
+                if target.owned {
+                    self.gl.delete_texture(target.texture);
+                }
+            }
+        }
+    }
+
+    /// Map a GUI texture handle to a GL texture name
+    pub fn register_texture(&mut self, handle: u64, gl_name: u32) {
+        match NonZeroU32::new(gl_name) {
+            Some(name) => { self.textures.insert(handle, glow::NativeTexture(name)); }
+            None => { self.textures.remove(&handle); }
+        }
+    }
+
+    /// Draw GUI elements into the target (cleared to transparent first)
+    pub fn draw_gui(&mut self, elements: &[GuiElement]) -> Result<(), String> {
+        let Some(target) = &self.target else {
+            return Err("No GUI render target".to_string());
+        };
+
+        let gl = &self.gl;
+        let mut drawn = 0;
+        unsafe {
+            gl.bind_framebuffer(glow::FRAMEBUFFER, Some(target.framebuffer));
+            gl.viewport(0, 0, target.width as i32, target.height as i32);
+            gl.clear_color(0.0, 0.0, 0.0, 0.0);
+            gl.clear(glow::COLOR_BUFFER_BIT);
+
+            gl.disable(glow::DEPTH_TEST);
+            gl.enable(glow::BLEND);
+            gl.blend_func(glow::SRC_ALPHA, glow::ONE_MINUS_SRC_ALPHA);
+
+            gl.use_program(Some(self.program));
+            gl.bind_vertex_array(Some(self.vertex_array));
+            gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.vertex_buffer));
+            gl.active_texture(glow::TEXTURE0);
+
+            for element in draw_order(elements) {
+                let quad = gui_quad(element, target.width, target.height);
+                gl.buffer_sub_data_u8_slice(glow::ARRAY_BUFFER, 0, bytemuck::cast_slice(&quad));
+
+                let texture = self.textures.get(&element.texture_id).copied();
+                gl.bind_texture(glow::TEXTURE_2D, texture);
+                gl.uniform_1_i32(self.u_textured.as_ref(), texture.is_some() as i32);
+                gl.uniform_1_f32(self.u_opacity.as_ref(), element.opacity.min(1.0));
+                let [r, g, b, a] = element.color;
+                gl.uniform_4_f32(self.u_color.as_ref(), r, g, b, a);
+                gl.uniform_1_f32(self.u_sdf_range.as_ref(), element.sdf_range);
+
+                gl.draw_arrays(glow::TRIANGLE_STRIP, 0, 4);
+                drawn += 1;
+            }
+
+            gl.bind_texture(glow::TEXTURE_2D, None);
+            gl.bind_vertex_array(None);
+            gl.use_program(None);
+            gl.disable(glow::BLEND);
+            gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+        }
+
+        self.quads_drawn = drawn;
+        Ok(())
+    }
+
+    /// Read one RGBA8 pixel from the target (bottom-left origin)
+    pub fn read_pixel(&self, x: u32, y: u32) -> Option<[u8; 4]> {
+        let target = self.target.as_ref()?;
+        let mut pixel = [0u8; 4];
+        unsafe {
+            self.gl.bind_framebuffer(glow::FRAMEBUFFER, Some(target.framebuffer));
+            self.gl.read_pixels(
+                x as i32, y as i32, 1, 1,
+                glow::RGBA, glow::UNSIGNED_BYTE,
+                glow::PixelPackData::Slice(&mut pixel),
+            );
+            self.gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+        }
+        Some(pixel)
+    }
+
+    /// Get GL name of the target color texture (for interop composition)
+    pub fn color_texture(&self) -> Option<u32> {
+        self.target.as_ref().map(|t| t.texture.0.get())
+    }
+
+    /// Get quads drawn by the last `draw_gui`
+    pub fn quads_drawn(&self) -> usize {
+        self.quads_drawn
+    }
+}
+
+impl Drop for GlContext {
+    fn drop(&mut self) {
+        self.destroy_target();
+        unsafe {
+            self.gl.delete_buffer(self.vertex_buffer);
+            self.gl.delete_vertex_array(self.vertex_array);
+            self.gl.delete_program(self.program);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::quantum::compositor::GuiLayer;
+
+    #[test]
+    fn test_gui_quad_layout() {
+        // Top-left quarter of a 200x100 target
+        let element = GuiElement::new(GuiLayer::Hud, 0.0, 0.0, 100.0, 50.0);
+        let quad = gui_quad(&element, 200, 100);
+        assert_eq!(&quad[0..4], &[-1.0, 1.0, 0.0, 0.0]);
+        assert_eq!(&quad[12..16], &[0.0, 0.0, 1.0, 1.0]);
+
+        let mut hidden = GuiElement::new(GuiLayer::Chat, 0.0, 0.0, 1.0, 1.0);
+        hidden.visible = false;
+        let mut front = GuiElement::new(GuiLayer::Debug, 0.0, 0.0, 1.0, 1.0);
+        front.z_order = 5;
+        let elements = [front, hidden, element];
+        let order: Vec<GuiLayer> = draw_order(&elements).iter().map(|e| e.layer).collect();
+        assert_eq!(order, vec![GuiLayer::Hud, GuiLayer::Debug]);
+    }
+}
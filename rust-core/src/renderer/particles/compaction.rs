@@ -0,0 +1,259 @@
+//! # Particle Compaction
+//!
+//! Expired particles leave holes in the particle buffer, and every hole
+//! still costs a simulation thread. After simulating, the alive mask
+//! (lifetime > 0) is prefix-summed so each survivor knows its slot in a
+//! packed copy; a scatter pass moves survivors into the other half of the
+//! double buffer and writes the alive count, both for the indirect draw and
+//! for the host, which sizes next frame's dispatches from it. Slots past
+//! the alive count are free for emission.
+//!
+//! The scan is hierarchical: each 256-wide workgroup scans its block in
+//! shared memory and writes the block total one level up; levels are
+//! scanned until one block covers them, then block offsets are added back
+//! down. All levels live in one `u32` buffer, back to back.
+
+use bytemuck::{Pod, Zeroable};
+
+/// Scan workgroup size (matches `local_size_x` in the shaders)
+pub const SCAN_WORKGROUP_SIZE: u32 = 256;
+
+/// Vertices per particle billboard (instanced draw)
+pub const QUAD_VERTICES: u32 = 6;
+
+/// Block scan; level 0 reads the alive mask from the particles
+pub const SCAN_SHADER: &str = r#"#version 450
+layout(local_size_x = 256) in;
+
+struct Particle { vec4 position_size; vec4 velocity_lifetime; vec4 color; vec4 rotation_tex_flags; };
+
+layout(push_constant) uniform Params { uint count; uint offset; uint sums_offset; uint from_particles; } params;
+layout(std430, binding = 0) readonly buffer Particles { Particle particles[]; };
+layout(std430, binding = 6) buffer Scan { uint scan[]; };
+
+shared uint sums[256];
+
+void main() {
+    uint i = gl_GlobalInvocationID.x;
+    uint lid = gl_LocalInvocationID.x;
+    uint value = 0u;
+    if (i < params.count) {
+        value = params.from_particles != 0u ? uint(particles[i].velocity_lifetime.w > 0.0) : scan[params.offset + i];
+    }
+    sums[lid] = value;
+    barrier();
+    for (uint d = 1u; d < 256u; d <<= 1u) {
+        uint add = lid >= d ? sums[lid - d] : 0u;
+        barrier();
+        sums[lid] += add;
+        barrier();
+    }
+    if (i < params.count) scan[params.offset + i] = sums[lid] - value;
+    if (lid == 255u) scan[params.sums_offset + gl_WorkGroupID.x] = sums[255];
+}
+"#;
+
+/// Adds each block's scanned offset to its elements
+pub const ADD_OFFSETS_SHADER: &str = r#"#version 450
+layout(local_size_x = 256) in;
+
+layout(push_constant) uniform Params { uint count; uint offset; uint sums_offset; uint from_particles; } params;
+layout(std430, binding = 6) buffer Scan { uint scan[]; };
+
+void main() {
+    uint i = gl_GlobalInvocationID.x;
+    if (i < params.count) scan[params.offset + i] += scan[params.sums_offset + gl_WorkGroupID.x];
+}
+"#;
+
+/// Moves survivors to their slots and publishes the alive count
+pub const SCATTER_SHADER: &str = r#"#version 450
+layout(local_size_x = 256) in;
+
+struct Particle { vec4 position_size; vec4 velocity_lifetime; vec4 color; vec4 rotation_tex_flags; };
+struct DrawCommand { uint vertex_count; uint instance_count; uint first_vertex; uint first_instance; };
+
+layout(push_constant) uniform Params { uint count; uint offset; uint sums_offset; uint from_particles; } params;
+layout(std430, binding = 0) readonly buffer Particles { Particle particles[]; };
+layout(std430, binding = 1) writeonly buffer Compacted { Particle compacted[]; };
+layout(std430, binding = 2) writeonly buffer Count { uint alive_count; };
+layout(std430, binding = 4) writeonly buffer Indirect { DrawCommand draw; };
+layout(std430, binding = 6) readonly buffer Scan { uint scan[]; };
+
+void main() {
+    uint i = gl_GlobalInvocationID.x;
+    if (i < params.count && particles[i].velocity_lifetime.w > 0.0) {
+        compacted[scan[params.offset + i]] = particles[i];
+    }
+    if (i == 0u) {
+        uint alive = scan[params.sums_offset];
+        alive_count = alive;
+        draw = DrawCommand(6u, alive, 0u, 0u);
+    }
+}
+"#;
+
+/// Push constants shared by the compaction shaders
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Pod, Zeroable)]
+pub struct CompactionParams {
+    /// Elements in this level
+    pub count: u32,
+    /// Start of this level in the scan buffer
+    pub offset: u32,
+    /// Start of the block totals (for scatter: the alive count)
+    pub sums_offset: u32,
+    /// Read the alive mask from the particles instead of the scan buffer
+    pub from_particles: u32,
+}
+
+/// Shader a compaction step runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompactionStage {
+    Scan,
+    AddBlockOffsets,
+    Scatter,
+}
+
+/// One dispatch of the compaction sequence
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompactionStep {
+    pub stage: CompactionStage,
+    pub params: CompactionParams,
+}
+
+impl CompactionStep {
+    /// Workgroups to dispatch
+    pub fn workgroups(&self) -> u32 {
+        self.params.count.div_ceil(SCAN_WORKGROUP_SIZE)
+    }
+}
+
+/// Element count of each scan level for `count` particles, the last level
+/// holding the single grand total
+pub fn scan_levels(count: u32) -> Vec<u32> {
+    let mut levels = vec![count];
+    let mut n = count;
+    loop {
+        n = n.div_ceil(SCAN_WORKGROUP_SIZE).max(1);
+        levels.push(n);
+        if n == 1 {
+            return levels;
+        }
+    }
+}
+
+/// Length of the scan buffer for up to `max_particles` particles
+pub fn scan_buffer_len(max_particles: u32) -> u32 {
+    scan_levels(max_particles).iter().sum()
+}
+
+/// Dispatches compacting `count` particles, in recording order (a
+/// compute-to-compute barrier goes between consecutive steps)
+pub fn dispatch_sequence(count: u32) -> Vec<CompactionStep> {
+    if count == 0 {
+        return Vec::new();
+    }
+    let levels = scan_levels(count);
+    let offsets: Vec<u32> = levels
+        .iter()
+        .scan(0, |offset, &n| {
+            let start = *offset;
+            *offset += n;
+            Some(start)
+        })
+        .collect();
+    let params = |level: usize| CompactionParams {
+        count: levels[level],
+        offset: offsets[level],
+        sums_offset: offsets[level + 1],
+        from_particles: (level == 0) as u32,
+    };
+
+    // Up until one block covers the level, then offsets back down
+    let scanned = levels.len() - 1;
+    let mut steps: Vec<CompactionStep> = (0..scanned)
+        .map(|level| CompactionStep { stage: CompactionStage::Scan, params: params(level) })
+        .collect();
+    steps.extend((0..scanned - 1).rev().map(|level| CompactionStep {
+        stage: CompactionStage::AddBlockOffsets,
+        params: params(level),
+    }));
+    steps.push(CompactionStep {
+        stage: CompactionStage::Scatter,
+        params: CompactionParams { sums_offset: offsets[scanned], ..params(0) },
+    });
+    steps
+}
+
+/// Host reference of the compaction: source slot of each compacted particle
+///
+/// Runs the same dispatch sequence over a scan buffer, block by block, as
+/// the shaders do.
+pub fn compact_indices(alive: &[bool]) -> Vec<u32> {
+    let block = SCAN_WORKGROUP_SIZE as usize;
+    let mut scan = vec![0u32; scan_buffer_len(alive.len() as u32) as usize];
+    let mut compacted = Vec::new();
+
+    for step in dispatch_sequence(alive.len() as u32) {
+        let CompactionParams { count, offset, sums_offset, from_particles } = step.params;
+        let (count, offset, sums_offset) = (count as usize, offset as usize, sums_offset as usize);
+        match step.stage {
+            CompactionStage::Scan => {
+                for (group, start) in (0..count).step_by(block).enumerate() {
+                    let mut sum = 0;
+                    for i in start..(start + block).min(count) {
+                        let value = if from_particles != 0 { alive[i] as u32 } else { scan[offset + i] };
+                        scan[offset + i] = sum;
+                        sum += value;
+                    }
+                    scan[sums_offset + group] = sum;
+                }
+            }
+            CompactionStage::AddBlockOffsets => {
+                for i in 0..count {
+                    scan[offset + i] += scan[sums_offset + i / block];
+                }
+            }
+            CompactionStage::Scatter => {
+                compacted = vec![0; scan[sums_offset] as usize];
+                for i in (0..count).filter(|&i| alive[i]) {
+                    compacted[scan[offset + i] as usize] = i as u32;
+                }
+            }
+        }
+    }
+    compacted
+}
+
+/// Particle slots next frame's dispatches cover: survivors of the last
+/// compaction plus this frame's emissions, which fill the freed slots
+pub fn next_particle_count(alive: u32, emitted: u32, max_particles: u32) -> u32 {
+    alive.saturating_add(emitted).min(max_particles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compaction_matches_filter() {
+        assert_eq!(compact_indices(&[true, false, false, true, true, false]), vec![0, 3, 4]);
+        assert!(compact_indices(&[]).is_empty());
+        assert_eq!(compact_indices(&[false; 300]), Vec::<u32>::new());
+
+        // Past 256 * 256 particles the scan needs three levels
+        let alive: Vec<bool> = (0..70_000u32).map(|i| (i.wrapping_mul(2_654_435_761) >> 29) % 3 != 0).collect();
+        let expected: Vec<u32> = (0..alive.len() as u32).filter(|&i| alive[i as usize]).collect();
+        assert_eq!(compact_indices(&alive), expected);
+
+        let stages: Vec<CompactionStage> = dispatch_sequence(70_000).iter().map(|s| s.stage).collect();
+        use CompactionStage::*;
+        assert_eq!(stages, vec![Scan, Scan, Scan, AddBlockOffsets, AddBlockOffsets, Scatter]);
+        assert_eq!(scan_levels(70_000), vec![70_000, 274, 2, 1]);
+        assert_eq!(dispatch_sequence(70_000)[0].workgroups(), 274);
+
+        assert_eq!(next_particle_count(900, 200, 1000), 1000);
+        assert_eq!(next_particle_count(10, 5, 1000), 15);
+    }
+}
@@ -46,6 +46,8 @@ pub struct ParticleEmitter {
     pub total_emitted: u64,
     /// Burst mode
     pub burst: Option<BurstConfig>,
+    /// Stop particles on solid blocks
+    pub collide_with_blocks: bool,
 }
 
 impl Default for ParticleEmitter {
@@ -72,6 +74,7 @@ impl Default for ParticleEmitter {
             particles_to_emit: 0,
             total_emitted: 0,
             burst: None,
+            collide_with_blocks: false,
         }
     }
 }
@@ -176,6 +179,12 @@ impl ParticleEmitter {
         self
     }
     
+    /// Enable or disable block collision
+    pub fn with_block_collision(mut self, enabled: bool) -> Self {
+        self.collide_with_blocks = enabled;
+        self
+    }
+    
     /// Update emitter
     pub fn update(&mut self, delta_time: f32) {
         if !self.active {
@@ -3,18 +3,24 @@
 //! High-performance GPU-driven particle system using compute shaders.
 //! Supports millions of particles with physics simulation on GPU.
 
+pub mod compaction;
 pub mod emitter;
 pub mod simulation;
 pub mod renderer;
+pub mod weather;
 
 use std::sync::Arc;
 use ash::vk;
 
 use super::vulkan::{VulkanDevice, VulkanError, Buffer, BufferType};
+use super::vulkan::barrier::{memory_barrier, Transition};
+use super::graph::{RenderGraph, ResourceId, ResourceUse};
+use compaction::CompactionStage;
 
 pub use emitter::*;
 pub use simulation::*;
 pub use renderer::*;
+pub use weather::{Weather, WeatherSystem};
 
 /// Maximum particles per system
 pub const MAX_PARTICLES: usize = 1_000_000;
@@ -70,8 +76,10 @@ pub struct ParticleSystem {
     particle_buffers: [Buffer; 2],
     /// Current buffer index
     current_buffer: usize,
-    /// Alive particle count buffer
+    /// Alive particle count written by compaction, read back by the host
     count_buffer: Buffer,
+    /// Prefix sums of the alive mask, all scan levels
+    scan_buffer: Buffer,
     /// Indirect draw buffer
     indirect_buffer: Buffer,
     /// Emitter data buffer
@@ -82,6 +90,10 @@ pub struct ParticleSystem {
     emission_pipeline: vk::Pipeline,
     /// Sort compute pipeline
     sort_pipeline: Option<vk::Pipeline>,
+    /// Compaction pipelines by stage (scan, add block offsets, scatter)
+    compaction_pipelines: [vk::Pipeline; 3],
+    /// `count_buffer` holds last frame's compacted count
+    compacted: bool,
     /// Render pipeline
     render_pipeline: vk::Pipeline,
     /// Pipeline layout
@@ -113,13 +125,18 @@ impl ParticleSystem {
         let particle_buffer_1 = Buffer::new(device.clone(), buffer_size, BufferType::Storage)?;
         
         // Create count buffer
-        let count_buffer = Buffer::new(device.clone(), 16, BufferType::Storage)?;
+        let count_buffer = Buffer::new(device.clone(), 16, BufferType::Readback)?;
+        count_buffer.write(&[0u32; 4])?;
+        
+        // Create scan buffer
+        let scan_len = compaction::scan_buffer_len(config.max_particles as u32) as u64;
+        let scan_buffer = Buffer::new(device.clone(), scan_len * 4, BufferType::Storage)?;
         
         // Create indirect draw buffer
         let indirect_buffer = Buffer::new(
             device.clone(),
             std::mem::size_of::<vk::DrawIndirectCommand>() as u64,
-            BufferType::Storage,
+            BufferType::Indirect,
         )?;
         
         // Create emitter buffer
@@ -145,10 +162,7 @@ impl ParticleSystem {
             .set_layouts(&layouts)
             .push_constant_ranges(&push_ranges);
         
-        let pipeline_layout = unsafe {
-            device.handle().create_pipeline_layout(&layout_info, None)
-                .map_err(|e| VulkanError::PipelineCreationFailed(format!("{:?}", e)))?
-        };
+        let pipeline_layout = device.create_pipeline_layout(&layout_info)?;
         
         // Create descriptor pool
         let pool_sizes = [
@@ -174,6 +188,7 @@ impl ParticleSystem {
         let emission_pipeline = vk::Pipeline::null();
         let sort_pipeline = if config.sort_particles { Some(vk::Pipeline::null()) } else { None };
         let render_pipeline = vk::Pipeline::null();
+        let compaction_pipelines = [vk::Pipeline::null(); 3];
         
         Ok(Self {
             device,
@@ -181,11 +196,14 @@ impl ParticleSystem {
             particle_buffers: [particle_buffer_0, particle_buffer_1],
             current_buffer: 0,
             count_buffer,
+            scan_buffer,
             indirect_buffer,
             emitter_buffer,
             simulation_pipeline,
             emission_pipeline,
             sort_pipeline,
+            compaction_pipelines,
+            compacted: false,
             render_pipeline,
             pipeline_layout,
             descriptor_layout,
@@ -236,6 +254,12 @@ impl ParticleSystem {
                 .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
                 .descriptor_count(1)
                 .stage_flags(vk::ShaderStageFlags::FRAGMENT),
+            // Binding 6: Alive mask prefix sums
+            vk::DescriptorSetLayoutBinding::default()
+                .binding(6)
+                .descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
+                .descriptor_count(1)
+                .stage_flags(vk::ShaderStageFlags::COMPUTE),
         ];
         
         let layout_info = vk::DescriptorSetLayoutCreateInfo::default()
@@ -261,6 +285,11 @@ impl ParticleSystem {
         }
     }
     
+    /// Get an emitter for modification
+    pub fn emitter_mut(&mut self, id: usize) -> Option<&mut ParticleEmitter> {
+        self.emitters.get_mut(id)
+    }
+    
     /// Update emitter position
     pub fn set_emitter_position(&mut self, id: usize, x: f32, y: f32, z: f32) {
         if let Some(emitter) = self.emitters.get_mut(id) {
@@ -269,6 +298,9 @@ impl ParticleSystem {
     }
     
     /// Update the particle system
+    ///
+    /// Call after the previous frame's fence, so the compacted count it
+    /// wrote is readable.
     pub fn update(&mut self, delta_time: f32) {
         self.accumulated_time += delta_time;
         
@@ -276,13 +308,39 @@ impl ParticleSystem {
         for emitter in &mut self.emitters {
             emitter.update(delta_time);
         }
+        
+        // Survivors are packed at the front; new particles take the slots after them
+        let alive = if self.compacted { self.read_alive_count() } else { self.particle_count };
+        let emitted = self.emitters.iter().map(|e| e.particles_to_emit).sum();
+        self.particle_count = compaction::next_particle_count(alive, emitted, self.config.max_particles as u32);
+        self.compacted = false;
+    }
+    
+    /// Alive count written by the last compaction
+    fn read_alive_count(&self) -> u32 {
+        match self.count_buffer.mapped_ptr() {
+            Some(ptr) => unsafe { std::ptr::read_volatile(ptr as *const u32) },
+            None => self.particle_count,
+        }
     }
     
-    /// Record simulation commands
+    /// Record simulation commands, then the barrier making the output visible to the draw
+    ///
+    /// `record_compaction` adds its own compute barrier; inside a render
+    /// graph use `add_simulation_pass`, which leaves barriers to the graph.
     pub fn record_simulation(&self, cmd: vk::CommandBuffer) {
         if self.simulation_pipeline == vk::Pipeline::null() {
             return;
         }
+        self.record_simulation_dispatch(cmd);
+        unsafe { memory_barrier(self.device.handle(), cmd, Transition::COMPUTE_TO_VERTEX) };
+    }
+    
+    /// Record the simulation dispatch alone
+    fn record_simulation_dispatch(&self, cmd: vk::CommandBuffer) {
+        if self.simulation_pipeline == vk::Pipeline::null() {
+            return;
+        }
         
         unsafe {
             // Bind simulation pipeline
@@ -296,24 +354,71 @@ impl ParticleSystem {
             let workgroup_size = 256;
             let num_workgroups = (self.particle_count as u32 + workgroup_size - 1) / workgroup_size;
             self.device.handle().cmd_dispatch(cmd, num_workgroups, 1, 1);
-            
-            // Memory barrier
-            let barrier = vk::MemoryBarrier::default()
-                .src_access_mask(vk::AccessFlags::SHADER_WRITE)
-                .dst_access_mask(vk::AccessFlags::SHADER_READ);
-            
-            self.device.handle().cmd_pipeline_barrier(
-                cmd,
-                vk::PipelineStageFlags::COMPUTE_SHADER,
-                vk::PipelineStageFlags::COMPUTE_SHADER | vk::PipelineStageFlags::VERTEX_SHADER,
-                vk::DependencyFlags::empty(),
-                &[barrier],
-                &[],
-                &[],
-            );
         }
     }
     
+    /// Record compaction of the simulated particles into the other buffer
+    ///
+    /// Runs after `record_simulation`: scans the alive mask, scatters
+    /// survivors to the front of the other buffer and writes the alive count
+    /// to the count and indirect draw buffers, then swaps buffers so the draw
+    /// reads the compacted ones.
+    pub fn record_compaction(&mut self, cmd: vk::CommandBuffer) {
+        if self.compaction_pipelines.contains(&vk::Pipeline::null()) || self.particle_count == 0 {
+            return;
+        }
+        let device = self.device.handle();
+        
+        unsafe {
+            memory_barrier(device, cmd, Transition::COMPUTE_TO_COMPUTE);
+            for step in compaction::dispatch_sequence(self.particle_count) {
+                let pipeline = match step.stage {
+                    CompactionStage::Scan => self.compaction_pipelines[0],
+                    CompactionStage::AddBlockOffsets => self.compaction_pipelines[1],
+                    CompactionStage::Scatter => self.compaction_pipelines[2],
+                };
+                device.cmd_bind_pipeline(cmd, vk::PipelineBindPoint::COMPUTE, pipeline);
+                device.cmd_push_constants(
+                    cmd,
+                    self.pipeline_layout,
+                    vk::ShaderStageFlags::COMPUTE | vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT,
+                    0,
+                    bytemuck::bytes_of(&step.params),
+                );
+                device.cmd_dispatch(cmd, step.workgroups(), 1, 1);
+                memory_barrier(device, cmd, Transition::COMPUTE_TO_COMPUTE);
+            }
+            memory_barrier(device, cmd, Transition::COMPUTE_TO_INDIRECT);
+        }
+        
+        self.swap_buffers();
+        self.compacted = true;
+    }
+    
+    /// Register the simulation pass with a render graph
+    ///
+    /// The graph inserts the compute -> vertex/indirect barrier before any
+    /// pass that declares `ParticleGraphResources::render_reads`.
+    pub fn add_simulation_pass<'a>(&'a self, graph: &mut RenderGraph<'a>) -> ParticleGraphResources {
+        let particles = graph.import_buffer(
+            "particles",
+            self.particle_buffers[self.current_buffer].handle(),
+        );
+        let indirect = graph.import_buffer("particle_indirect", self.indirect_buffer.handle());
+        
+        graph.add_pass(
+            "particle_simulation",
+            &[],
+            &[
+                ResourceUse::buffer(particles, vk::PipelineStageFlags::COMPUTE_SHADER, vk::AccessFlags::SHADER_WRITE),
+                ResourceUse::buffer(indirect, vk::PipelineStageFlags::COMPUTE_SHADER, vk::AccessFlags::SHADER_WRITE),
+            ],
+            move |cmd| self.record_simulation_dispatch(cmd),
+        );
+        
+        ParticleGraphResources { particles, indirect }
+    }
+    
     /// Record emission commands
     pub fn record_emission(&self, cmd: vk::CommandBuffer) {
         if self.emission_pipeline == vk::Pipeline::null() || self.emitters.is_empty() {
@@ -409,6 +514,11 @@ impl Drop for ParticleSystem {
             if self.render_pipeline != vk::Pipeline::null() {
                 self.device.handle().destroy_pipeline(self.render_pipeline, None);
             }
+            for pipeline in self.compaction_pipelines {
+                if pipeline != vk::Pipeline::null() {
+                    self.device.handle().destroy_pipeline(pipeline, None);
+                }
+            }
             self.device.handle().destroy_pipeline_layout(self.pipeline_layout, None);
             self.device.handle().destroy_descriptor_pool(self.descriptor_pool, None);
             self.device.handle().destroy_descriptor_set_layout(self.descriptor_layout, None);
@@ -416,6 +526,25 @@ impl Drop for ParticleSystem {
     }
 }
 
+/// Particle resources registered with a render graph
+#[derive(Debug, Clone, Copy)]
+pub struct ParticleGraphResources {
+    /// Simulated particle buffer
+    pub particles: ResourceId,
+    /// Indirect draw arguments
+    pub indirect: ResourceId,
+}
+
+impl ParticleGraphResources {
+    /// Reads a particle draw pass must declare
+    pub fn render_reads(&self) -> [ResourceUse; 2] {
+        [
+            ResourceUse::buffer(self.particles, vk::PipelineStageFlags::VERTEX_SHADER, vk::AccessFlags::SHADER_READ),
+            ResourceUse::buffer(self.indirect, vk::PipelineStageFlags::DRAW_INDIRECT, vk::AccessFlags::INDIRECT_COMMAND_READ),
+        ]
+    }
+}
+
 /// Emitter data for GPU (matches shader struct)
 #[repr(C)]
 #[derive(Debug, Clone, Copy, Default)]
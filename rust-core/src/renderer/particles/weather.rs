@@ -0,0 +1,205 @@
+//! # Weather Particles
+//!
+//! Rain and snow built on the box emitter presets. The emitter is a flat
+//! box hovering above the camera, so precipitation always surrounds the
+//! player, and has block collision enabled so drops stop on surfaces.
+//! Rain falls fast and straight down; snow falls slowly and drifts with
+//! the wind.
+
+use super::{EmitterShape, ParticleEmitter, ParticlePreset, ParticleSystem};
+
+/// Horizontal half-size of the spawn box (blocks)
+pub const WEATHER_RADIUS: f32 = 24.0;
+
+/// Height of the spawn box above the camera (blocks)
+pub const WEATHER_HEIGHT: f32 = 20.0;
+
+/// Rain drops per second at full intensity
+const RAIN_RATE: f32 = 4000.0;
+
+/// Snowflakes per second at full intensity
+const SNOW_RATE: f32 = 1200.0;
+
+/// Fraction of the wind velocity snowflakes pick up
+const SNOW_DRIFT: f32 = 0.6;
+
+/// Weather state
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Weather {
+    Clear,
+    /// Rain, `intensity` in 0..=1
+    Rain { intensity: f32 },
+    /// Snow, `intensity` in 0..=1
+    Snow { intensity: f32 },
+}
+
+/// Camera-following precipitation emitter
+#[derive(Debug, Clone)]
+pub struct WeatherSystem {
+    /// Current weather
+    weather: Weather,
+    /// Wind velocity (blocks per second)
+    wind: [f32; 3],
+    /// Camera position the emitter follows
+    camera: [f32; 3],
+    /// Emitter for the current weather (`None` when clear)
+    emitter: Option<ParticleEmitter>,
+    /// Slot of the emitter in the particle system
+    emitter_id: Option<usize>,
+    /// Emitter settings changed since the last `apply`
+    dirty: bool,
+}
+
+impl WeatherSystem {
+    /// Create a clear-weather system
+    pub fn new() -> Self {
+        Self {
+            weather: Weather::Clear,
+            wind: [0.0; 3],
+            camera: [0.0; 3],
+            emitter: None,
+            emitter_id: None,
+            dirty: false,
+        }
+    }
+
+    /// Change the weather
+    pub fn set_weather(&mut self, weather: Weather) {
+        self.weather = weather;
+        self.rebuild();
+    }
+
+    /// Get the current weather
+    pub fn weather(&self) -> Weather {
+        self.weather
+    }
+
+    /// Set the wind velocity (only snow drifts with it)
+    pub fn set_wind(&mut self, wind: [f32; 3]) {
+        self.wind = wind;
+        self.rebuild();
+    }
+
+    /// Move the emitter with the camera
+    pub fn update_camera(&mut self, position: [f32; 3]) {
+        self.camera = position;
+        let center = self.spawn_center();
+        if let Some(emitter) = &mut self.emitter {
+            emitter.position = center;
+        }
+    }
+
+    /// Get the precipitation emitter (`None` when clear)
+    pub fn emitter(&self) -> Option<&ParticleEmitter> {
+        self.emitter.as_ref()
+    }
+
+    /// Push the emitter into a particle system
+    ///
+    /// Settings are copied when the weather or wind changed; otherwise only
+    /// the position is updated. Clear weather stops the emitter but keeps
+    /// its slot so other emitter IDs stay valid.
+    pub fn apply(&mut self, system: &mut ParticleSystem) {
+        let Some(id) = self.emitter_id else {
+            if let Some(emitter) = &self.emitter {
+                self.emitter_id = Some(system.add_emitter(emitter.clone()));
+            }
+            self.dirty = false;
+            return;
+        };
+
+        let Some(slot) = system.emitter_mut(id) else {
+            return;
+        };
+        match &self.emitter {
+            Some(emitter) if self.dirty => *slot = emitter.clone(),
+            Some(emitter) => slot.position = emitter.position,
+            None => slot.stop(),
+        }
+        self.dirty = false;
+    }
+
+    /// Center of the spawn box
+    fn spawn_center(&self) -> [f32; 3] {
+        [self.camera[0], self.camera[1] + WEATHER_HEIGHT, self.camera[2]]
+    }
+
+    /// Recreate the emitter for the current weather and wind
+    fn rebuild(&mut self) {
+        let center = self.spawn_center();
+        let half_extents = [WEATHER_RADIUS, 0.0, WEATHER_RADIUS];
+        let emitter = match self.weather {
+            Weather::Clear => None,
+            Weather::Rain { intensity } => {
+                let mut rain = ParticlePreset::Rain
+                    .create_emitter(center)
+                    .with_rate(RAIN_RATE * intensity.clamp(0.0, 1.0))
+                    .with_velocity([0.0, -14.0, 0.0], [0.0, -18.0, 0.0])
+                    .with_lifetime(1.5, 2.5)
+                    .with_block_collision(true);
+                rain.shape = EmitterShape::Box { half_extents };
+                Some(rain)
+            }
+            Weather::Snow { intensity } => {
+                let [wx, wy, wz] = self.wind.map(|w| w * SNOW_DRIFT);
+                let mut snow = ParticlePreset::Snow
+                    .create_emitter(center)
+                    .with_rate(SNOW_RATE * intensity.clamp(0.0, 1.0))
+                    .with_velocity([wx - 0.3, wy - 1.0, wz - 0.3], [wx + 0.3, wy - 1.5, wz + 0.3])
+                    .with_lifetime(15.0, 20.0)
+                    .with_block_collision(true);
+                snow.shape = EmitterShape::Box { half_extents };
+                Some(snow)
+            }
+        };
+
+        self.emitter = emitter.map(|mut e| {
+            e.direction = [0.0, -1.0, 0.0];
+            e
+        });
+        self.dirty = true;
+    }
+}
+
+impl Default for WeatherSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rain_box_follows_camera_downward() {
+        let mut weather = WeatherSystem::new();
+        weather.update_camera([100.0, 64.0, -30.0]);
+        weather.set_wind([4.0, 0.0, 0.0]);
+        weather.set_weather(Weather::Rain { intensity: 0.5 });
+
+        let rain = weather.emitter().unwrap();
+        assert_eq!(rain.position, [100.0, 64.0 + WEATHER_HEIGHT, -30.0]);
+        assert_eq!(rain.direction, [0.0, -1.0, 0.0]);
+        assert!(rain.collide_with_blocks);
+        assert_eq!(rain.rate, RAIN_RATE * 0.5);
+        // Straight down, ignoring wind
+        for velocity in [rain.velocity_min, rain.velocity_max] {
+            assert_eq!((velocity[0], velocity[2]), (0.0, 0.0));
+            assert!(velocity[1] < -10.0);
+        }
+
+        weather.update_camera([120.0, 70.0, -10.0]);
+        assert_eq!(weather.emitter().unwrap().position, [120.0, 70.0 + WEATHER_HEIGHT, -10.0]);
+
+        // Snow is slower and drifts downwind
+        weather.set_weather(Weather::Snow { intensity: 1.0 });
+        let snow = weather.emitter().unwrap();
+        assert_eq!(snow.position, [120.0, 70.0 + WEATHER_HEIGHT, -10.0]);
+        assert!(snow.velocity_min[1] > -2.0);
+        assert!(snow.velocity_min[0] > 0.0);
+
+        weather.set_weather(Weather::Clear);
+        assert!(weather.emitter().is_none());
+    }
+}
@@ -0,0 +1,260 @@
+//! # Frame Capture
+//!
+//! Reads the rendered frame back to host memory for screenshots and
+//! rendering-regression tests.
+//!
+//! The swapchain path copies the last *presented* swapchain image, so it
+//! needs at least one `present_frame` call before `capture_frame`. Without
+//! Vulkan, the CPU fallback target is captured instead.
+
+use ash::vk;
+
+use super::{QuantumRenderer, RendererError};
+use crate::renderer::vulkan::barrier::image_transition;
+
+/// RGBA8 image
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    /// Width in pixels
+    pub width: u32,
+    /// Height in pixels
+    pub height: u32,
+    /// Bytes per row
+    pub stride: usize,
+    /// Pixel data, top row first
+    pub pixels: Vec<u8>,
+}
+
+impl Image {
+    /// Create a transparent black image with tightly packed rows
+    pub fn new(width: u32, height: u32) -> Self {
+        let stride = width as usize * 4;
+        Self { width, height, stride, pixels: vec![0; stride * height as usize] }
+    }
+
+    /// Get the RGBA value of a pixel
+    pub fn pixel(&self, x: u32, y: u32) -> Option<[u8; 4]> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let i = y as usize * self.stride + x as usize * 4;
+        self.pixels.get(i..i + 4).map(|p| [p[0], p[1], p[2], p[3]])
+    }
+
+    /// Set the RGBA value of a pixel
+    pub fn set_pixel(&mut self, x: u32, y: u32, rgba: [u8; 4]) {
+        if x < self.width && y < self.height {
+            let i = y as usize * self.stride + x as usize * 4;
+            self.pixels[i..i + 4].copy_from_slice(&rgba);
+        }
+    }
+}
+
+/// Check if a format stores blue in the first byte
+pub fn is_bgra(format: vk::Format) -> bool {
+    matches!(format, vk::Format::B8G8R8A8_UNORM | vk::Format::B8G8R8A8_SRGB)
+}
+
+/// Swap the red and blue channels of packed 4-byte pixels
+pub fn swizzle_bgra(pixels: &mut [u8]) {
+    for pixel in pixels.chunks_exact_mut(4) {
+        pixel.swap(0, 2);
+    }
+}
+
+impl QuantumRenderer {
+    /// Use a CPU color target (BGRA8, like most swapchains) when Vulkan is unavailable
+    pub fn enable_cpu_fallback(&mut self, width: u32, height: u32) {
+        self.cpu_frame = Some(Image::new(width, height));
+    }
+
+    /// Get the CPU fallback target for software drawing (BGRA8)
+    pub fn cpu_frame_mut(&mut self) -> Option<&mut Image> {
+        self.cpu_frame.as_mut()
+    }
+
+    /// Record which swapchain image was presented last (the capture source)
+    pub fn present_frame(&mut self, image_index: u32) {
+        self.last_presented = Some(image_index);
+    }
+
+    /// Copy the current frame to host memory as RGBA8
+    ///
+    /// With a swapchain this reads the last presented image; otherwise it
+    /// reads the CPU fallback target.
+    pub fn capture_frame(&self) -> Result<Image, RendererError> {
+        if self.swapchain.is_some() {
+            return self.capture_swapchain();
+        }
+
+        let frame = self.cpu_frame.as_ref().ok_or(RendererError::NotInitialized)?;
+        let mut image = frame.clone();
+        swizzle_bgra(&mut image.pixels);
+        Ok(image)
+    }
+
+    fn capture_swapchain(&self) -> Result<Image, RendererError> {
+        let (Some(instance), Some(device), Some(physical_device), Some(queue), Some(pool), Some(swapchain)) = (
+            &self.instance, &self.device, self.physical_device,
+            self.graphics_queue, self.command_pool, &self.swapchain,
+        ) else {
+            return Err(RendererError::NotInitialized);
+        };
+
+        let index = self.last_presented
+            .ok_or_else(|| RendererError::SwapchainError("No frame presented yet".to_string()))?;
+        let source = *swapchain.images.get(index as usize)
+            .ok_or_else(|| RendererError::SwapchainError(format!("Invalid swapchain image {}", index)))?;
+
+        let extent = swapchain.extent;
+        let size = extent.width as u64 * extent.height as u64 * 4;
+        let vk_err = |what: &str, e: vk::Result| RendererError::VulkanError(format!("{}: {:?}", what, e));
+
+        unsafe {
+            // Host-visible staging buffer
+            let buffer_info = vk::BufferCreateInfo::default()
+                .size(size)
+                .usage(vk::BufferUsageFlags::TRANSFER_DST)
+                .sharing_mode(vk::SharingMode::EXCLUSIVE);
+            let buffer = device.create_buffer(&buffer_info, None)
+                .map_err(|e| vk_err("Failed to create readback buffer", e))?;
+
+            let requirements = device.get_buffer_memory_requirements(buffer);
+            let properties = instance.get_physical_device_memory_properties(physical_device);
+            let wanted = vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT;
+            let Some(memory_type) = (0..properties.memory_type_count).find(|&i| {
+                requirements.memory_type_bits & (1 << i) != 0
+                    && properties.memory_types[i as usize].property_flags.contains(wanted)
+            }) else {
+                device.destroy_buffer(buffer, None);
+                return Err(RendererError::VulkanError("No host-visible memory for readback".to_string()));
+            };
+
+            let alloc_info = vk::MemoryAllocateInfo::default()
+                .allocation_size(requirements.size)
+                .memory_type_index(memory_type);
+            let memory = match device.allocate_memory(&alloc_info, None) {
+                Ok(memory) => memory,
+                Err(e) => {
+                    device.destroy_buffer(buffer, None);
+                    return Err(vk_err("Failed to allocate readback memory", e));
+                }
+            };
+
+            let result = self.record_and_read(device, queue, pool, source, extent, buffer, memory, size);
+
+            device.destroy_buffer(buffer, None);
+            device.free_memory(memory, None);
+
+            let mut pixels = result?;
+            if is_bgra(swapchain.format) {
+                swizzle_bgra(&mut pixels);
+            }
+
+            Ok(Image {
+                width: extent.width,
+                height: extent.height,
+                stride: extent.width as usize * 4,
+                pixels,
+            })
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    unsafe fn record_and_read(
+        &self,
+        device: &ash::Device,
+        queue: vk::Queue,
+        pool: vk::CommandPool,
+        source: vk::Image,
+        extent: vk::Extent2D,
+        buffer: vk::Buffer,
+        memory: vk::DeviceMemory,
+        size: u64,
+    ) -> Result<Vec<u8>, RendererError> {
+        let vk_err = |what: &str, e: vk::Result| RendererError::VulkanError(format!("{}: {:?}", what, e));
+
+        device.bind_buffer_memory(buffer, memory, 0)
+            .map_err(|e| vk_err("Failed to bind readback memory", e))?;
+
+        let cmd_info = vk::CommandBufferAllocateInfo::default()
+            .command_pool(pool)
+            .level(vk::CommandBufferLevel::PRIMARY)
+            .command_buffer_count(1);
+        let cmd = device.allocate_command_buffers(&cmd_info)
+            .map_err(|e| vk_err("Failed to allocate command buffer", e))?[0];
+
+        let region = vk::BufferImageCopy::default()
+            .buffer_offset(0)
+            .buffer_row_length(0) // Tightly packed
+            .buffer_image_height(0)
+            .image_subresource(vk::ImageSubresourceLayers {
+                aspect_mask: vk::ImageAspectFlags::COLOR,
+                mip_level: 0,
+                base_array_layer: 0,
+                layer_count: 1,
+            })
+            .image_extent(vk::Extent3D { width: extent.width, height: extent.height, depth: 1 });
+
+        let begin = vk::CommandBufferBeginInfo::default()
+            .flags(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT);
+        let recorded = device.begin_command_buffer(cmd, &begin).and_then(|_| {
+            image_transition(device, cmd, source, vk::ImageAspectFlags::COLOR,
+                vk::ImageLayout::PRESENT_SRC_KHR, vk::ImageLayout::TRANSFER_SRC_OPTIMAL);
+            device.cmd_copy_image_to_buffer(cmd, source, vk::ImageLayout::TRANSFER_SRC_OPTIMAL, buffer, &[region]);
+            image_transition(device, cmd, source, vk::ImageAspectFlags::COLOR,
+                vk::ImageLayout::TRANSFER_SRC_OPTIMAL, vk::ImageLayout::PRESENT_SRC_KHR);
+            device.end_command_buffer(cmd)
+        });
+
+        let result = recorded
+            .map_err(|e| vk_err("Failed to record readback", e))
+            .and_then(|_| {
+                let fence = device.create_fence(&vk::FenceCreateInfo::default(), None)
+                    .map_err(|e| vk_err("Failed to create fence", e))?;
+                let submit = vk::SubmitInfo::default().command_buffers(std::slice::from_ref(&cmd));
+                let waited = device.queue_submit(queue, &[submit], fence)
+                    .map_err(|e| match e {
+                        vk::Result::ERROR_DEVICE_LOST => {
+                            RendererError::VulkanError(self.watchdog.device_lost("frame capture submit").to_string())
+                        }
+                        _ => vk_err("Readback submit failed", e),
+                    })
+                    .and_then(|_| self.watchdog.wait_for_fences(device, &[fence], "frame capture")
+                        .map_err(|e| RendererError::VulkanError(e.to_string())));
+                device.destroy_fence(fence, None);
+                waited
+            })
+            .and_then(|_| {
+                let mapped = device.map_memory(memory, 0, size, vk::MemoryMapFlags::empty())
+                    .map_err(|e| vk_err("Failed to map readback memory", e))?;
+                let pixels = std::slice::from_raw_parts(mapped as *const u8, size as usize).to_vec();
+                device.unmap_memory(memory);
+                Ok(pixels)
+            });
+
+        device.free_command_buffers(pool, &[cmd]);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cpu_fallback_capture() {
+        let mut renderer = QuantumRenderer::new().unwrap();
+        assert!(matches!(renderer.capture_frame(), Err(RendererError::NotInitialized)));
+
+        renderer.enable_cpu_fallback(64, 36);
+        // Pure red, stored BGRA
+        renderer.cpu_frame_mut().unwrap().set_pixel(3, 5, [0, 0, 255, 255]);
+
+        let image = renderer.capture_frame().unwrap();
+        assert_eq!((image.width, image.height, image.stride), (64, 36, 256));
+        assert_eq!(image.pixels.len(), 64 * 36 * 4);
+        assert_eq!(image.pixel(3, 5), Some([255, 0, 0, 255]));
+        assert_eq!(image.pixel(64, 0), None);
+    }
+}
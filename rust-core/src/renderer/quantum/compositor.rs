@@ -6,6 +6,10 @@ use std::sync::Arc;
 use parking_lot::RwLock;
 use ash::vk;
 
+use crate::renderer::vulkan::{SamplerCache, SamplerDesc};
+use crate::LibsError;
+use super::RendererError;
+
 /// GUI Layer types  
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum GuiLayer {
@@ -20,6 +24,12 @@ pub struct GuiElement {
     pub width: f32, pub height: f32,
     pub opacity: f32,
     pub texture_id: u64,
+    /// Texture rect sampled (u0, v0, u1, v1)
+    pub uv: [f32; 4],
+    /// Tint multiplied with the texture (RGBA)
+    pub color: [f32; 4],
+    /// MSDF distance range in screen pixels (0 = plain texture)
+    pub sdf_range: f32,
     pub z_order: i32,
     pub blur_radius: f32,
     pub visible: bool,
@@ -27,11 +37,18 @@ pub struct GuiElement {
 
 impl GuiElement {
     pub fn new(layer: GuiLayer, x: f32, y: f32, width: f32, height: f32) -> Self {
-        Self { layer, x, y, width, height, opacity: 1.0, texture_id: 0, z_order: 0, blur_radius: 0.0, visible: true }
+        Self {
+            layer, x, y, width, height, opacity: 1.0, texture_id: 0,
+            uv: [0.0, 0.0, 1.0, 1.0], color: [1.0; 4], sdf_range: 0.0,
+            z_order: 0, blur_radius: 0.0, visible: true,
+        }
     }
     
     pub fn with_blur(mut self, radius: f32) -> Self { self.blur_radius = radius; self }
     pub fn with_opacity(mut self, opacity: f32) -> Self { self.opacity = opacity; self }
+    pub fn with_texture(mut self, texture_id: u64, uv: [f32; 4]) -> Self { self.texture_id = texture_id; self.uv = uv; self }
+    pub fn with_color(mut self, color: [f32; 4]) -> Self { self.color = color; self }
+    pub fn with_sdf_range(mut self, range: f32) -> Self { self.sdf_range = range; self }
 }
 
 /// Compositor config
@@ -43,11 +60,16 @@ pub struct CompositorConfig {
     pub scale_factor: f32,
     pub width: u32,
     pub height: u32,
+    /// Anisotropy level for GUI texture sampling (1 = off)
+    pub anisotropy_level: u32,
 }
 
 impl Default for CompositorConfig {
     fn default() -> Self {
-        Self { blur_enabled: true, blur_quality: 2, gui_antialiasing: true, scale_factor: 1.0, width: 1920, height: 1080 }
+        Self {
+            blur_enabled: true, blur_quality: 2, gui_antialiasing: true, scale_factor: 1.0,
+            width: 1920, height: 1080, anisotropy_level: 16,
+        }
     }
 }
 
@@ -68,6 +90,7 @@ pub struct GuiCompositor {
     blur_memory: vk::DeviceMemory,
     blur_view: vk::ImageView,
     sampler: vk::Sampler,
+    samplers: SamplerCache,
     pipeline: vk::Pipeline,
     pipeline_layout: vk::PipelineLayout,
     descriptor_set_layout: vk::DescriptorSetLayout,
@@ -81,10 +104,16 @@ pub struct GuiCompositor {
 
 impl GuiCompositor {
     pub fn new() -> Self {
+        Self::with_config(CompositorConfig::default())
+    }
+    
+    /// Create a compositor whose samplers follow `config.anisotropy_level`
+    pub fn with_config(config: CompositorConfig) -> Self {
         log::debug!("Creating GUI Compositor");
         Self {
             device: None,
-            config: CompositorConfig::default(),
+            samplers: SamplerCache::new(config.anisotropy_level.max(1) as f32),
+            config,
             elements: Vec::with_capacity(256),
             render_pass: vk::RenderPass::null(),
             framebuffer: vk::Framebuffer::null(),
@@ -114,24 +143,15 @@ impl GuiCompositor {
         queue_family_index: u32,
         width: u32,
         height: u32,
-    ) -> Result<(), String> {
+    ) -> Result<(), LibsError> {
         self.device = Some(device.clone());
         self.config.width = width;
         self.config.height = height;
         
         unsafe {
-            // Create sampler
-            let sampler_info = vk::SamplerCreateInfo::default()
-                .mag_filter(vk::Filter::LINEAR)
-                .min_filter(vk::Filter::LINEAR)
-                .address_mode_u(vk::SamplerAddressMode::CLAMP_TO_EDGE)
-                .address_mode_v(vk::SamplerAddressMode::CLAMP_TO_EDGE)
-                .address_mode_w(vk::SamplerAddressMode::CLAMP_TO_EDGE)
-                .mipmap_mode(vk::SamplerMipmapMode::LINEAR)
-                .max_lod(1.0);
-            
-            self.sampler = device.create_sampler(&sampler_info, None)
-                .map_err(|e| format!("Failed to create sampler: {:?}", e))?;
+            // Get sampler
+            let desc = SamplerDesc::linear_clamp().with_anisotropy(self.config.anisotropy_level);
+            self.sampler = self.samplers.get(&device, desc)?;
             
             // Create render pass for GUI compositing
             let color_attachment = vk::AttachmentDescription::default()
@@ -166,7 +186,7 @@ impl GuiCompositor {
                 .dependencies(std::slice::from_ref(&dependency));
             
             self.render_pass = device.create_render_pass(&render_pass_info, None)
-                .map_err(|e| format!("Failed to create render pass: {:?}", e))?;
+                .map_err(|e| RendererError::VulkanError(format!("Failed to create render pass: {:?}", e)))?;
             
             // Create color image and view
             let (color_img, color_mem) = Self::create_image(&device, width, height)?;
@@ -190,7 +210,7 @@ impl GuiCompositor {
                 .flags(vk::CommandPoolCreateFlags::RESET_COMMAND_BUFFER);
             
             self.command_pool = device.create_command_pool(&pool_info, None)
-                .map_err(|e| format!("Failed to create command pool: {:?}", e))?;
+                .map_err(|e| RendererError::VulkanError(format!("Failed to create command pool: {:?}", e)))?;
             
             // Allocate command buffer
             let alloc_info = vk::CommandBufferAllocateInfo::default()
@@ -199,7 +219,7 @@ impl GuiCompositor {
                 .command_buffer_count(1);
             
             let buffers = device.allocate_command_buffers(&alloc_info)
-                .map_err(|e| format!("Failed to allocate command buffer: {:?}", e))?;
+                .map_err(|e| RendererError::VulkanError(format!("Failed to allocate command buffer: {:?}", e)))?;
             self.command_buffer = buffers[0];
             
             // Create descriptor set layout
@@ -213,20 +233,20 @@ impl GuiCompositor {
                 .bindings(std::slice::from_ref(&binding));
             
             self.descriptor_set_layout = device.create_descriptor_set_layout(&layout_info, None)
-                .map_err(|e| format!("Failed to create descriptor set layout: {:?}", e))?;
+                .map_err(|e| RendererError::VulkanError(format!("Failed to create descriptor set layout: {:?}", e)))?;
             
             // Create pipeline layout
             let push_constant = vk::PushConstantRange::default()
                 .stage_flags(vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT)
                 .offset(0)
-                .size(64); // Transform matrix + opacity
+                .size(64); // Rect, UV rect, tint, opacity + MSDF range
             
             let pipeline_layout_info = vk::PipelineLayoutCreateInfo::default()
                 .set_layouts(std::slice::from_ref(&self.descriptor_set_layout))
                 .push_constant_ranges(std::slice::from_ref(&push_constant));
             
             self.pipeline_layout = device.create_pipeline_layout(&pipeline_layout_info, None)
-                .map_err(|e| format!("Failed to create pipeline layout: {:?}", e))?;
+                .map_err(|e| RendererError::VulkanError(format!("Failed to create pipeline layout: {:?}", e)))?;
             
             // Create descriptor pool
             let pool_size = vk::DescriptorPoolSize::default()
@@ -238,7 +258,7 @@ impl GuiCompositor {
                 .max_sets(16);
             
             self.descriptor_pool = device.create_descriptor_pool(&pool_info, None)
-                .map_err(|e| format!("Failed to create descriptor pool: {:?}", e))?;
+                .map_err(|e| RendererError::VulkanError(format!("Failed to create descriptor pool: {:?}", e)))?;
         }
         
         self.initialized = true;
@@ -251,7 +271,7 @@ impl GuiCompositor {
         device: &ash::Device,
         width: u32,
         height: u32,
-    ) -> Result<(vk::Image, vk::DeviceMemory), String> {
+    ) -> Result<(vk::Image, vk::DeviceMemory), LibsError> {
         unsafe {
             let image_info = vk::ImageCreateInfo::default()
                 .image_type(vk::ImageType::TYPE_2D)
@@ -266,7 +286,7 @@ impl GuiCompositor {
                 .samples(vk::SampleCountFlags::TYPE_1);
             
             let image = device.create_image(&image_info, None)
-                .map_err(|e| format!("Failed to create image: {:?}", e))?;
+                .map_err(|e| RendererError::VulkanError(format!("Failed to create image: {:?}", e)))?;
             
             let mem_requirements = device.get_image_memory_requirements(image);
             
@@ -275,16 +295,16 @@ impl GuiCompositor {
                 .memory_type_index(0);
             
             let memory = device.allocate_memory(&alloc_info, None)
-                .map_err(|e| format!("Failed to allocate memory: {:?}", e))?;
+                .map_err(|e| RendererError::VulkanError(format!("Failed to allocate memory: {:?}", e)))?;
             
             device.bind_image_memory(image, memory, 0)
-                .map_err(|e| format!("Failed to bind image memory: {:?}", e))?;
+                .map_err(|e| RendererError::VulkanError(format!("Failed to bind image memory: {:?}", e)))?;
             
             Ok((image, memory))
         }
     }
     
-    fn create_image_view(device: &ash::Device, image: vk::Image) -> Result<vk::ImageView, String> {
+    fn create_image_view(device: &ash::Device, image: vk::Image) -> Result<vk::ImageView, LibsError> {
         let view_info = vk::ImageViewCreateInfo::default()
             .image(image)
             .view_type(vk::ImageViewType::TYPE_2D)
@@ -299,7 +319,7 @@ impl GuiCompositor {
         
         unsafe {
             device.create_image_view(&view_info, None)
-                .map_err(|e| format!("Failed to create image view: {:?}", e))
+                .map_err(|e| RendererError::VulkanError(format!("Failed to create image view: {:?}", e)).into())
         }
     }
     
@@ -309,7 +329,7 @@ impl GuiCompositor {
         image_view: vk::ImageView,
         width: u32,
         height: u32,
-    ) -> Result<vk::Framebuffer, String> {
+    ) -> Result<vk::Framebuffer, LibsError> {
         let framebuffer_info = vk::FramebufferCreateInfo::default()
             .render_pass(self.render_pass)
             .attachments(std::slice::from_ref(&image_view))
@@ -319,7 +339,7 @@ impl GuiCompositor {
         
         unsafe {
             device.create_framebuffer(&framebuffer_info, None)
-                .map_err(|e| format!("Failed to create framebuffer: {:?}", e))
+                .map_err(|e| RendererError::VulkanError(format!("Failed to create framebuffer: {:?}", e)).into())
         }
     }
     
@@ -331,10 +351,10 @@ impl GuiCompositor {
     }
     
     /// Render GUI pass
-    pub fn render(&mut self, queue: vk::Queue) -> Result<(), String> {
+    pub fn render(&mut self, queue: vk::Queue) -> Result<(), LibsError> {
         if !self.initialized { return Ok(()); }
         
-        let device = self.device.as_ref().ok_or("No device")?;
+        let device = self.device.as_ref().ok_or(RendererError::NotInitialized)?;
         
         unsafe {
             // Begin command buffer
@@ -342,7 +362,7 @@ impl GuiCompositor {
                 .flags(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT);
             
             device.begin_command_buffer(self.command_buffer, &begin_info)
-                .map_err(|e| format!("Failed to begin command buffer: {:?}", e))?;
+                .map_err(|e| RendererError::VulkanError(format!("Failed to begin command buffer: {:?}", e)))?;
             
             // Begin render pass
             let clear_value = vk::ClearValue {
@@ -366,10 +386,14 @@ impl GuiCompositor {
                 
                 for element in &self.elements {
                     if element.visible {
-                        // Push constants for transform
+                        // Push constants for transform, texture rect and tint
+                        let [u0, v0, u1, v1] = element.uv;
+                        let [r, g, b, a] = element.color;
                         let push_data = [
                             element.x, element.y, element.width, element.height,
-                            element.opacity, 0.0, 0.0, 0.0,
+                            u0, v0, u1, v1,
+                            r, g, b, a,
+                            element.opacity, element.sdf_range, 0.0, 0.0,
                         ];
                         
                         device.cmd_push_constants(
@@ -389,14 +413,14 @@ impl GuiCompositor {
             device.cmd_end_render_pass(self.command_buffer);
             
             device.end_command_buffer(self.command_buffer)
-                .map_err(|e| format!("Failed to end command buffer: {:?}", e))?;
+                .map_err(|e| RendererError::VulkanError(format!("Failed to end command buffer: {:?}", e)))?;
             
             // Submit
             let submit_info = vk::SubmitInfo::default()
                 .command_buffers(std::slice::from_ref(&self.command_buffer));
             
             device.queue_submit(queue, std::slice::from_ref(&submit_info), vk::Fence::null())
-                .map_err(|e| format!("Failed to submit: {:?}", e))?;
+                .map_err(|e| RendererError::VulkanError(format!("Failed to submit: {:?}", e)))?;
         }
         
         Ok(())
@@ -408,11 +432,14 @@ impl GuiCompositor {
     /// Get element count
     pub fn element_count(&self) -> usize { self.elements.iter().filter(|e| e.visible).count() }
     
+    /// Get the anisotropy limit GUI samplers are clamped to
+    pub fn max_anisotropy(&self) -> f32 { self.samplers.max_anisotropy() }
+    
     /// Resize
-    pub fn resize(&mut self, width: u32, height: u32) -> Result<(), String> {
+    pub fn resize(&mut self, width: u32, height: u32) -> Result<(), LibsError> {
         if width == self.config.width && height == self.config.height { return Ok(()); }
         
-        let device = self.device.as_ref().ok_or("No device")?;
+        let device = self.device.as_ref().ok_or(RendererError::NotInitialized)?;
         
         unsafe {
             device.device_wait_idle().ok();
@@ -467,7 +494,8 @@ impl GuiCompositor {
                 if self.blur_image != vk::Image::null() { device.destroy_image(self.blur_image, None); }
                 if self.color_memory != vk::DeviceMemory::null() { device.free_memory(self.color_memory, None); }
                 if self.blur_memory != vk::DeviceMemory::null() { device.free_memory(self.blur_memory, None); }
-                if self.sampler != vk::Sampler::null() { device.destroy_sampler(self.sampler, None); }
+                self.samplers.destroy(device);
+                self.sampler = vk::Sampler::null();
             }
         }
         
@@ -0,0 +1,153 @@
+//! # Debug Render Modes
+//!
+//! Runtime toggles for inspecting geometry: wireframe, culling off, and
+//! fragment visualizations of normals or LOD level. Rasterizer toggles
+//! pick one of four prebuilt pipeline variants; visualizations are a mode
+//! word pushed to the fragment shader. Wireframe needs the
+//! `fillModeNonSolid` device feature and is dropped when it is missing.
+
+use ash::vk;
+
+use super::nanite::LodLevel;
+
+/// Fragment mode bit: output the surface normal as color
+pub const DEBUG_MODE_NORMALS: u32 = 1 << 0;
+
+/// Fragment mode bit: tint by LOD level
+pub const DEBUG_MODE_LOD_COLORS: u32 = 1 << 1;
+
+/// Debug visualization toggles
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DebugRenderFlags {
+    /// Draw triangle edges only
+    pub wireframe: bool,
+    /// Draw back faces too
+    pub disable_culling: bool,
+    /// Color by surface normal
+    pub show_normals: bool,
+    /// Tint chunks by LOD level
+    pub show_lod_colors: bool,
+}
+
+impl DebugRenderFlags {
+    /// Drop toggles the device can't honor
+    pub fn supported(mut self, wireframe_supported: bool) -> Self {
+        self.wireframe &= wireframe_supported;
+        self
+    }
+
+    /// Get the fragment shader mode word
+    pub fn shader_mode(&self) -> u32 {
+        let mut mode = 0;
+        if self.show_normals {
+            mode |= DEBUG_MODE_NORMALS;
+        }
+        if self.show_lod_colors {
+            mode |= DEBUG_MODE_LOD_COLORS;
+        }
+        mode
+    }
+
+    /// Check if any toggle is on
+    pub fn any(&self) -> bool {
+        *self != Self::default()
+    }
+}
+
+/// Rasterizer state of a world pipeline variant
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PipelineVariant {
+    pub polygon_mode: vk::PolygonMode,
+    pub cull_mode: vk::CullModeFlags,
+}
+
+impl PipelineVariant {
+    /// Variants in `index` order
+    pub const ALL: [PipelineVariant; 4] = [
+        PipelineVariant { polygon_mode: vk::PolygonMode::FILL, cull_mode: vk::CullModeFlags::BACK },
+        PipelineVariant { polygon_mode: vk::PolygonMode::FILL, cull_mode: vk::CullModeFlags::NONE },
+        PipelineVariant { polygon_mode: vk::PolygonMode::LINE, cull_mode: vk::CullModeFlags::BACK },
+        PipelineVariant { polygon_mode: vk::PolygonMode::LINE, cull_mode: vk::CullModeFlags::NONE },
+    ];
+
+    /// Pick the variant for a set of flags
+    ///
+    /// Wireframe falls back to fill when the device lacks `fillModeNonSolid`.
+    pub fn select(flags: DebugRenderFlags, wireframe_supported: bool) -> Self {
+        let flags = flags.supported(wireframe_supported);
+        Self::ALL[(flags.wireframe as usize) << 1 | flags.disable_culling as usize]
+    }
+
+    /// Get the variant's slot in `ALL`
+    pub fn index(&self) -> usize {
+        let wireframe = self.polygon_mode == vk::PolygonMode::LINE;
+        let no_cull = self.cull_mode == vk::CullModeFlags::NONE;
+        (wireframe as usize) << 1 | no_cull as usize
+    }
+
+    /// Check if this variant needs `fillModeNonSolid`
+    pub fn needs_non_solid_fill(&self) -> bool {
+        self.polygon_mode != vk::PolygonMode::FILL
+    }
+
+    /// Rasterization state for building the variant
+    pub fn rasterization_state(&self) -> vk::PipelineRasterizationStateCreateInfo<'static> {
+        vk::PipelineRasterizationStateCreateInfo::default()
+            .depth_clamp_enable(false)
+            .rasterizer_discard_enable(false)
+            .polygon_mode(self.polygon_mode)
+            .line_width(1.0)
+            .cull_mode(self.cull_mode)
+            .front_face(vk::FrontFace::COUNTER_CLOCKWISE)
+            .depth_bias_enable(false)
+    }
+}
+
+impl Default for PipelineVariant {
+    fn default() -> Self {
+        Self::ALL[0]
+    }
+}
+
+/// Tint for `DEBUG_MODE_LOD_COLORS` (green near, red far)
+pub fn lod_debug_color(lod: LodLevel) -> [f32; 3] {
+    match lod {
+        LodLevel::HighPoly => [0.2, 0.9, 0.2],
+        LodLevel::MediumPoly => [0.9, 0.9, 0.2],
+        LodLevel::LowPoly => [0.95, 0.5, 0.1],
+        LodLevel::Imposter => [0.9, 0.15, 0.15],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flags_select_pipeline_variant() {
+        let default = PipelineVariant::select(DebugRenderFlags::default(), true);
+        assert_eq!(default, PipelineVariant { polygon_mode: vk::PolygonMode::FILL, cull_mode: vk::CullModeFlags::BACK });
+
+        let wire = DebugRenderFlags { wireframe: true, disable_culling: true, ..Default::default() };
+        let variant = PipelineVariant::select(wire, true);
+        assert_eq!(variant.polygon_mode, vk::PolygonMode::LINE);
+        assert_eq!(variant.cull_mode, vk::CullModeFlags::NONE);
+        assert!(variant.needs_non_solid_fill());
+
+        // No fillModeNonSolid: wireframe is dropped, culling toggle still applies
+        let fallback = PipelineVariant::select(wire, false);
+        assert_eq!(fallback.polygon_mode, vk::PolygonMode::FILL);
+        assert_eq!(fallback.cull_mode, vk::CullModeFlags::NONE);
+        assert!(!wire.supported(false).wireframe);
+
+        for (i, variant) in PipelineVariant::ALL.iter().enumerate() {
+            assert_eq!(variant.index(), i);
+        }
+
+        // Visualizations go to the shader, not the pipeline choice
+        let lod = DebugRenderFlags { show_lod_colors: true, show_normals: true, ..Default::default() };
+        assert_eq!(PipelineVariant::select(lod, true), default);
+        assert_eq!(lod.shader_mode(), DEBUG_MODE_NORMALS | DEBUG_MODE_LOD_COLORS);
+        assert_eq!(DebugRenderFlags::default().shader_mode(), 0);
+    }
+}
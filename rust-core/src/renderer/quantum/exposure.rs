@@ -0,0 +1,177 @@
+//! # Auto Exposure
+//!
+//! Meters the scene's log-average luminance and eases the tonemap exposure
+//! towards the value that maps it to middle grey. On the GPU the reduction
+//! is one compute dispatch: every 16x16 workgroup sums the log luminance of
+//! its pixels in shared memory and writes one partial (sum, count). The
+//! host reads the few thousand partials back a frame later, folds them with
+//! `average_from_partials`, and calls `AutoExposure::adapt` with the frame
+//! time. `reduce_image` is the host reference of the dispatch.
+
+use glam::Vec3;
+
+/// Reduction workgroup edge (matches `local_size_*` in the shader)
+pub const REDUCE_TILE: u32 = 16;
+
+/// Added before the log so black pixels don't pull the average to zero
+pub const LUMINANCE_EPSILON: f32 = 1e-4;
+
+/// Per-workgroup log-luminance sum; partials are (sum, pixel count)
+pub const LUMINANCE_REDUCE_SHADER: &str = r#"#version 450
+layout(local_size_x = 16, local_size_y = 16) in;
+
+layout(push_constant) uniform Params { uvec2 size; } params;
+layout(binding = 0) uniform sampler2D scene;
+layout(std430, binding = 1) writeonly buffer Partials { vec2 partials[]; };
+
+shared vec2 sums[256];
+
+void main() {
+    uvec2 p = gl_GlobalInvocationID.xy;
+    uint i = gl_LocalInvocationIndex;
+
+    vec2 value = vec2(0.0);
+    if (all(lessThan(p, params.size))) {
+        vec3 color = texelFetch(scene, ivec2(p), 0).rgb;
+        float luminance = dot(color, vec3(0.2126, 0.7152, 0.0722));
+        value = vec2(log(1e-4 + luminance), 1.0);
+    }
+    sums[i] = value;
+    barrier();
+
+    for (uint stride = 128u; stride > 0u; stride >>= 1) {
+        if (i < stride) sums[i] += sums[i + stride];
+        barrier();
+    }
+    if (i == 0u) partials[gl_WorkGroupID.y * gl_NumWorkGroups.x + gl_WorkGroupID.x] = sums[0];
+}
+"#;
+
+/// Workgroups needed to meter an image
+pub fn dispatch_size(width: u32, height: u32) -> [u32; 2] {
+    [width.div_ceil(REDUCE_TILE), height.div_ceil(REDUCE_TILE)]
+}
+
+/// Rec.709 luminance of a linear color
+pub fn luminance(color: Vec3) -> f32 {
+    color.dot(Vec3::new(0.2126, 0.7152, 0.0722))
+}
+
+/// Host version of the reduction dispatch: one (log sum, count) per tile
+pub fn reduce_image(pixels: &[Vec3], width: usize, height: usize) -> Vec<[f32; 2]> {
+    let tile = REDUCE_TILE as usize;
+    let [groups_x, groups_y] = dispatch_size(width as u32, height as u32).map(|n| n as usize);
+    let mut partials = vec![[0.0; 2]; groups_x * groups_y];
+    for y in 0..height {
+        for x in 0..width {
+            let partial = &mut partials[(y / tile) * groups_x + x / tile];
+            partial[0] += (LUMINANCE_EPSILON + luminance(pixels[y * width + x])).ln();
+            partial[1] += 1.0;
+        }
+    }
+    partials
+}
+
+/// Fold reduction partials into the log-average luminance
+pub fn average_from_partials(partials: &[[f32; 2]]) -> f32 {
+    let (sum, count) = partials.iter().fold((0.0, 0.0), |(sum, count), p| (sum + p[0], count + p[1]));
+    if count > 0.0 { (sum / count).exp() } else { 0.0 }
+}
+
+/// Eye adaptation state
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AutoExposure {
+    /// Luminance the average is mapped to
+    pub key: f32,
+    /// Adaptation rate towards brighter scenes (1/s)
+    pub speed_up: f32,
+    /// Adaptation rate towards darker scenes (1/s, eyes adjust to dark slower)
+    pub speed_down: f32,
+    /// Exposure range in stops
+    pub min_ev: f32,
+    pub max_ev: f32,
+    /// Current exposure multiplier
+    exposure: f32,
+}
+
+impl AutoExposure {
+    /// Create with middle grey key and an exposure of 1
+    pub fn new() -> Self {
+        Self {
+            key: 0.18,
+            speed_up: 3.0,
+            speed_down: 1.0,
+            min_ev: -8.0,
+            max_ev: 8.0,
+            exposure: 1.0,
+        }
+    }
+
+    /// Exposure that maps `average_luminance` to the key, within range
+    pub fn target(&self, average_luminance: f32) -> f32 {
+        let target = self.key / average_luminance.max(LUMINANCE_EPSILON);
+        target.clamp(self.min_ev.exp2(), self.max_ev.exp2())
+    }
+
+    /// Move towards the target over `dt` seconds; returns the new exposure
+    pub fn adapt(&mut self, average_luminance: f32, dt: f32) -> f32 {
+        let target = self.target(average_luminance);
+        // Exposure going down means the scene got brighter
+        let speed = if target < self.exposure { self.speed_up } else { self.speed_down };
+        // Ease in log space so a stop takes the same time either way
+        let t = 1.0 - (-dt.max(0.0) * speed).exp();
+        let ev = self.exposure.log2() + (target.log2() - self.exposure.log2()) * t;
+        self.exposure = ev.exp2();
+        self.exposure
+    }
+
+    /// Get the current exposure multiplier
+    pub fn exposure(&self) -> f32 {
+        self.exposure
+    }
+
+    /// Jump to an exposure (e.g. on a camera cut)
+    pub fn reset(&mut self, exposure: f32) {
+        self.exposure = exposure.max(f32::MIN_POSITIVE);
+    }
+}
+
+impl Default for AutoExposure {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reduction_meters_synthetic_image() {
+        // 40x20 image, left half at 0.25 and right half at 4.0 luminance:
+        // the log-average is their geometric mean, 1.0
+        let (width, height) = (40, 20);
+        let pixels: Vec<Vec3> = (0..width * height)
+            .map(|i| Vec3::splat(if i % width < width / 2 { 0.25 } else { 4.0 }))
+            .collect();
+        let partials = reduce_image(&pixels, width, height);
+        assert_eq!(partials.len(), 3 * 2);
+        assert_eq!(partials.iter().map(|p| p[1]).sum::<f32>(), (width * height) as f32);
+        let average = average_from_partials(&partials);
+        assert!((average - 1.0).abs() < 1e-3, "{}", average);
+
+        // Adaptation converges on key / average without overshooting
+        let mut auto = AutoExposure::new();
+        let mut last = auto.exposure();
+        for _ in 0..120 {
+            let exposure = auto.adapt(average, 1.0 / 60.0);
+            assert!(exposure <= last && exposure >= 0.18 - 1e-3);
+            last = exposure;
+        }
+        assert!((last - 0.18).abs() < 0.01, "{}", last);
+
+        // A black frame clamps to the top of the range
+        assert_eq!(auto.target(0.0), 256.0);
+        assert_eq!(average_from_partials(&[]), 0.0);
+    }
+}
@@ -0,0 +1,63 @@
+//! # Chunk Fade-In
+//!
+//! Newly meshed chunks fade from transparent to opaque over
+//! `FADE_IN_SECONDS` to hide pop-in at the render distance edge. The alpha
+//! is written into each visible chunk's uniform.
+
+use std::collections::HashMap;
+use std::time::Instant;
+
+use super::ChunkId;
+
+/// Time for a newly meshed chunk to become fully opaque
+pub const FADE_IN_SECONDS: f32 = 0.5;
+
+/// Alpha of a chunk meshed `age` seconds ago
+pub fn fade_alpha(age: f32) -> f32 {
+    (age / FADE_IN_SECONDS).clamp(0.0, 1.0)
+}
+
+/// Per-chunk uniform (std140 compatible)
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct ChunkUniform {
+    /// World-space origin of the section
+    pub origin: [f32; 3],
+    /// Fade-in alpha
+    pub alpha: f32,
+}
+
+/// Mesh times of chunks still fading in
+#[derive(Debug, Default)]
+pub struct ChunkFade {
+    meshed_at: HashMap<ChunkId, Instant>,
+}
+
+impl ChunkFade {
+    /// Create an empty tracker
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record that a chunk was (re)meshed at `now`
+    pub fn mark_meshed(&mut self, id: ChunkId, now: Instant) {
+        self.meshed_at.insert(id, now);
+    }
+
+    /// Get a chunk's alpha at `now` (chunks not fading are opaque)
+    pub fn alpha(&self, id: ChunkId, now: Instant) -> f32 {
+        self.meshed_at
+            .get(&id)
+            .map_or(1.0, |&t| fade_alpha(now.saturating_duration_since(t).as_secs_f32()))
+    }
+
+    /// Drop chunks that have finished fading
+    pub fn prune(&mut self, now: Instant) {
+        self.meshed_at.retain(|_, t| fade_alpha(now.saturating_duration_since(*t).as_secs_f32()) < 1.0);
+    }
+
+    /// Get the number of chunks still fading
+    pub fn fading_count(&self) -> usize {
+        self.meshed_at.len()
+    }
+}
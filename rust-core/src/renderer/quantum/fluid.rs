@@ -0,0 +1,280 @@
+//! # Fluid Meshing
+//!
+//! Water and lava are left out of the opaque greedy mesh and built into a
+//! separate transparent mesh, drawn after opaque geometry. The top surface
+//! of each fluid block sits at a height derived from its level (sources
+//! just below a full block, flowing fluid lower), smoothed per corner with
+//! neighboring fluid. The slope of the surface gives a flow direction that
+//! is written into every vertex for the shader's UV scroll. Faces are
+//! sorted back to front before drawing so blending is correct.
+
+use glam::Vec3;
+
+use super::greedy_mesh::ChunkVoxelData;
+
+/// Highest flowing level (level 0 is a source; 8 and above are falling)
+pub const MAX_FLOW_LEVEL: u8 = 7;
+
+/// Fluid type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FluidKind {
+    Water,
+    Lava,
+}
+
+impl FluidKind {
+    /// Get the fluid a block is made of (still and flowing IDs)
+    pub fn of(block: u16) -> Option<Self> {
+        match block {
+            8 | 9 => Some(FluidKind::Water),
+            10 | 11 => Some(FluidKind::Lava),
+            _ => None,
+        }
+    }
+}
+
+/// Check if a block is a fluid
+pub fn is_fluid(block: u16) -> bool {
+    FluidKind::of(block).is_some()
+}
+
+/// Surface height of a fluid block at `level` (block units)
+pub fn fluid_height(level: u8) -> f32 {
+    if level > MAX_FLOW_LEVEL {
+        return 1.0; // Falling
+    }
+    (8 - level) as f32 / 9.0
+}
+
+/// Fluid vertex (position, UV, flow direction, block)
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct FluidVertex {
+    /// Section-local position
+    pub position: [f32; 3],
+    /// Texture coordinates
+    pub uv: [f32; 2],
+    /// UV scroll direction (zero for still fluid)
+    pub flow: [f32; 2],
+    /// Block ID
+    pub block: u32,
+}
+
+/// Transparent fluid geometry of one section (four vertices per quad)
+#[derive(Debug, Clone, Default)]
+pub struct FluidMesh {
+    pub vertices: Vec<FluidVertex>,
+    pub indices: Vec<u32>,
+}
+
+impl FluidMesh {
+    /// Get quad count
+    pub fn quad_count(&self) -> usize {
+        self.vertices.len() / 4
+    }
+
+    /// Check if the mesh has no geometry
+    pub fn is_empty(&self) -> bool {
+        self.vertices.is_empty()
+    }
+
+    /// Reorder quads farthest-first from a section-local camera position
+    pub fn sort_back_to_front(&mut self, camera: Vec3) {
+        let mut quads: Vec<(f32, [FluidVertex; 4])> = self
+            .vertices
+            .chunks_exact(4)
+            .map(|q| {
+                let center = q.iter().map(|v| Vec3::from(v.position)).sum::<Vec3>() / 4.0;
+                (center.distance_squared(camera), [q[0], q[1], q[2], q[3]])
+            })
+            .collect();
+        quads.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        self.vertices = quads.into_iter().flat_map(|(_, q)| q).collect();
+        self.indices = quad_indices(self.quad_count());
+    }
+}
+
+/// Indices for `count` quads of four vertices
+fn quad_indices(count: usize) -> Vec<u32> {
+    (0..count as u32)
+        .flat_map(|i| {
+            let base = i * 4;
+            [base, base + 1, base + 2, base, base + 2, base + 3]
+        })
+        .collect()
+}
+
+/// Builds transparent fluid meshes
+#[derive(Debug, Default)]
+pub struct FluidMesher;
+
+impl FluidMesher {
+    /// Create a fluid mesher
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Mesh the fluid in a section
+    ///
+    /// `levels` holds each block's fluid level, indexed like
+    /// `ChunkVoxelData::blocks`; missing entries count as sources.
+    pub fn mesh(&self, chunk: &ChunkVoxelData, levels: &[u8]) -> FluidMesh {
+        let mut mesh = FluidMesh::default();
+
+        for y in 0..16 {
+            for z in 0..16 {
+                for x in 0..16 {
+                    let block = chunk.get_block(x, y, z);
+                    let Some(kind) = FluidKind::of(block) else { continue };
+
+                    let corners = self.corner_heights(chunk, levels, kind, x, y, z);
+                    let flow = flow_direction(corners);
+                    let (fx, fy, fz) = (x as f32, y as f32, z as f32);
+
+                    // Faces hidden by the same fluid or by opaque blocks
+                    let open = |nx: i32, ny: i32, nz: i32| -> bool {
+                        let n = block_at(chunk, nx, ny, nz);
+                        FluidKind::of(n) != Some(kind) && (!chunk.is_solid(n) || chunk.is_transparent(n))
+                    };
+                    let (xi, yi, zi) = (x as i32, y as i32, z as i32);
+                    let [h00, h10, h11, h01] = corners;
+
+                    if open(xi, yi + 1, zi) {
+                        push_quad(&mut mesh, block, flow, [
+                            ([fx, fy + h00, fz], [0.0, 0.0]),
+                            ([fx, fy + h01, fz + 1.0], [0.0, 1.0]),
+                            ([fx + 1.0, fy + h11, fz + 1.0], [1.0, 1.0]),
+                            ([fx + 1.0, fy + h10, fz], [1.0, 0.0]),
+                        ]);
+                    }
+                    if open(xi, yi - 1, zi) {
+                        push_quad(&mut mesh, block, [0.0; 2], [
+                            ([fx, fy, fz + 1.0], [0.0, 0.0]),
+                            ([fx, fy, fz], [0.0, 1.0]),
+                            ([fx + 1.0, fy, fz], [1.0, 1.0]),
+                            ([fx + 1.0, fy, fz + 1.0], [1.0, 0.0]),
+                        ]);
+                    }
+
+                    // Sides run down the wall while the fluid is flowing
+                    let side_flow = if flow == [0.0; 2] { [0.0; 2] } else { [0.0, 1.0] };
+                    let sides = [
+                        ((1, 0), [(1.0, 0.0, h10), (1.0, 1.0, h11)]),
+                        ((-1, 0), [(0.0, 1.0, h01), (0.0, 0.0, h00)]),
+                        ((0, 1), [(1.0, 1.0, h11), (0.0, 1.0, h01)]),
+                        ((0, -1), [(0.0, 0.0, h00), (1.0, 0.0, h10)]),
+                    ];
+                    for ((dx, dz), [(ax, az, ah), (bx, bz, bh)]) in sides {
+                        if open(xi + dx, yi, zi + dz) {
+                            push_quad(&mut mesh, block, side_flow, [
+                                ([fx + ax, fy, fz + az], [0.0, 1.0]),
+                                ([fx + ax, fy + ah, fz + az], [0.0, 1.0 - ah]),
+                                ([fx + bx, fy + bh, fz + bz], [1.0, 1.0 - bh]),
+                                ([fx + bx, fy, fz + bz], [1.0, 1.0]),
+                            ]);
+                        }
+                    }
+                }
+            }
+        }
+
+        mesh.indices = quad_indices(mesh.quad_count());
+        mesh
+    }
+
+    /// Surface height at the four top corners, in order (0,0), (1,0), (1,1), (0,1)
+    ///
+    /// Each corner averages the blocks of the same fluid sharing it; any of
+    /// them with fluid above raises the corner to a full block.
+    fn corner_heights(&self, chunk: &ChunkVoxelData, levels: &[u8], kind: FluidKind, x: usize, y: usize, z: usize) -> [f32; 4] {
+        let height = |bx: i32, bz: i32| -> Option<f32> {
+            let block = block_at(chunk, bx, y as i32, bz);
+            if FluidKind::of(block) != Some(kind) {
+                return None;
+            }
+            if FluidKind::of(block_at(chunk, bx, y as i32 + 1, bz)) == Some(kind) {
+                return Some(1.0);
+            }
+            let level = levels.get(y * 256 + bz as usize * 16 + bx as usize).copied().unwrap_or(0);
+            Some(fluid_height(level))
+        };
+
+        let (x, z) = (x as i32, z as i32);
+        [(0, 0), (1, 0), (1, 1), (0, 1)].map(|(cx, cz)| {
+            let shared = [(x + cx - 1, z + cz - 1), (x + cx, z + cz - 1), (x + cx - 1, z + cz), (x + cx, z + cz)];
+            let heights: Vec<f32> = shared.iter().filter_map(|&(bx, bz)| height(bx, bz)).collect();
+            if heights.contains(&1.0) {
+                1.0
+            } else {
+                heights.iter().sum::<f32>() / heights.len().max(1) as f32
+            }
+        })
+    }
+}
+
+/// Get a block, treating positions outside the section as air
+fn block_at(chunk: &ChunkVoxelData, x: i32, y: i32, z: i32) -> u16 {
+    if x < 0 || y < 0 || z < 0 {
+        return 0;
+    }
+    chunk.get_block(x as usize, y as usize, z as usize)
+}
+
+/// Downhill direction of a surface from its corner heights (normalized, or zero when level)
+fn flow_direction([h00, h10, h11, h01]: [f32; 4]) -> [f32; 2] {
+    let dx = (h00 + h01) - (h10 + h11);
+    let dz = (h00 + h10) - (h01 + h11);
+    let len = (dx * dx + dz * dz).sqrt();
+    if len < 1e-4 {
+        [0.0; 2]
+    } else {
+        [dx / len, dz / len]
+    }
+}
+
+/// Append a quad's vertices
+fn push_quad(mesh: &mut FluidMesh, block: u16, flow: [f32; 2], corners: [([f32; 3], [f32; 2]); 4]) {
+    for (position, uv) in corners {
+        mesh.vertices.push(FluidVertex { position, uv, flow, block: block as u32 });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WATER: u16 = 9;
+
+    #[test]
+    fn test_flowing_surface_sits_below_full_block() {
+        let mut chunk = ChunkVoxelData::default();
+        let mut levels = [0u8; 4096];
+        chunk.set_block(4, 0, 4, WATER);
+        chunk.set_block(5, 0, 4, WATER);
+        levels[4 * 16 + 5] = 4; // Flowing away from the source at x = 4
+
+        let mut mesh = FluidMesher::new().mesh(&chunk, &levels);
+        assert!(!mesh.is_empty());
+        // Fluid is only in the transparent mesh
+        assert!(super::super::greedy_mesh::GpuGreedyMesher::new().mesh_chunk_cpu(&chunk).is_empty());
+        assert_eq!(mesh.indices.len(), mesh.quad_count() * 6);
+
+        let top = mesh.vertices.iter().map(|v| v.position[1]).fold(0.0f32, f32::max);
+        assert!(top < 1.0);
+        assert_eq!(top, fluid_height(0));
+
+        // The flowing block's far edge is lower than its source side
+        let far_edge = mesh.vertices.iter().filter(|v| v.position[0] == 6.0).map(|v| v.position[1]).fold(0.0f32, f32::max);
+        assert_eq!(far_edge, fluid_height(4));
+
+        // Flow points downhill along +X
+        let flowing = mesh.vertices.iter().find(|v| v.position == [6.0, fluid_height(4), 4.0]).unwrap();
+        assert_eq!(flowing.flow, [1.0, 0.0]);
+
+        // Back-to-front: the first quad is farthest from the camera
+        mesh.sort_back_to_front(Vec3::new(0.0, 2.0, 4.5));
+        let first = mesh.vertices[..4].iter().map(|v| v.position[0]).fold(0.0f32, f32::max);
+        assert_eq!(first, 6.0);
+    }
+}
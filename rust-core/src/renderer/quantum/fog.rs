@@ -0,0 +1,188 @@
+//! # Distance and Height Fog
+//!
+//! Fog applied when shading and compositing the world, so distant terrain
+//! (ray-marched SDF chunks and imposters past the mesh range) fades into
+//! the sky color instead of ending in a hard cut. Distance fog is linear
+//! between a start and end distance or exponential in density. Optional
+//! height fog thickens toward a base altitude, filling valleys and caves;
+//! its density along the view ray is integrated analytically, so it costs
+//! one `exp` per sample rather than a march.
+
+use glam::Vec3;
+
+use super::sky::Sky;
+
+/// How fog grows with distance
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FogFalloff {
+    /// None before `start`, full at `end` (blocks)
+    Linear { start: f32, end: f32 },
+    /// `1 - e^(-density * d)`
+    Exponential { density: f32 },
+    /// `1 - e^(-(density * d)²)`, clearer up close than `Exponential`
+    ExponentialSquared { density: f32 },
+}
+
+/// Fog that thickens below a base altitude
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HeightFog {
+    /// Altitude where the density is `density` (world Y)
+    pub base_height: f32,
+    /// Density at `base_height` (per block)
+    pub density: f32,
+    /// Exponential density decay per block of altitude
+    pub falloff: f32,
+}
+
+/// Fog settings
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FogParams {
+    /// Distance falloff
+    pub falloff: FogFalloff,
+    /// Color fogged samples blend toward
+    pub color: Vec3,
+    /// Optional height fog
+    pub height: Option<HeightFog>,
+}
+
+/// Fog settings packed for shaders (std140)
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct FogUniform {
+    /// Color (rgb) and mode (w: 0 linear, 1 exponential, 2 exponential squared)
+    pub color_mode: [f32; 4],
+    /// Start, end, density, unused
+    pub distance: [f32; 4],
+    /// Base height, density, falloff, enabled (0 or 1)
+    pub height: [f32; 4],
+}
+
+impl FogParams {
+    /// Create exponential fog
+    pub fn exponential(density: f32, color: Vec3) -> Self {
+        Self { falloff: FogFalloff::Exponential { density }, color, height: None }
+    }
+
+    /// Create linear fog between `start` and `end`
+    pub fn linear(start: f32, end: f32, color: Vec3) -> Self {
+        Self { falloff: FogFalloff::Linear { start, end }, color, height: None }
+    }
+
+    /// Create exponential fog from the sky's fog color and density
+    pub fn from_sky(sky: &Sky) -> Self {
+        Self::exponential(sky.fog_density(), sky.fog_color())
+    }
+
+    /// Add height fog
+    pub fn with_height_fog(mut self, height: HeightFog) -> Self {
+        self.height = Some(height);
+        self
+    }
+
+    /// Get the distance fog amount at `distance` (0 clear, 1 fully fogged)
+    pub fn distance_factor(&self, distance: f32) -> f32 {
+        let distance = distance.max(0.0);
+        match self.falloff {
+            FogFalloff::Linear { start, end } => {
+                if end <= start {
+                    return if distance >= end { 1.0 } else { 0.0 };
+                }
+                ((distance - start) / (end - start)).clamp(0.0, 1.0)
+            }
+            FogFalloff::Exponential { density } => 1.0 - (-density * distance).exp(),
+            FogFalloff::ExponentialSquared { density } => 1.0 - (-(density * distance).powi(2)).exp(),
+        }
+    }
+
+    /// Get the height fog amount between the camera and a point
+    pub fn height_factor(&self, camera: Vec3, point: Vec3) -> f32 {
+        let Some(fog) = self.height else { return 0.0 };
+        let distance = camera.distance(point);
+        if distance <= 0.0 || fog.density <= 0.0 {
+            return 0.0;
+        }
+
+        // Integral of density * e^(-falloff * (y - base)) along the ray
+        let start = fog.density * (-fog.falloff * (camera.y - fog.base_height)).exp();
+        let rise = fog.falloff * (point.y - camera.y);
+        let spread = if rise.abs() < 1e-4 { 1.0 } else { (1.0 - (-rise).exp()) / rise };
+        let optical_depth = start * distance * spread;
+        1.0 - (-optical_depth).exp()
+    }
+
+    /// Get the combined fog amount between the camera and a point
+    pub fn factor(&self, camera: Vec3, point: Vec3) -> f32 {
+        let clear = (1.0 - self.distance_factor(camera.distance(point))) * (1.0 - self.height_factor(camera, point));
+        (1.0 - clear).clamp(0.0, 1.0)
+    }
+
+    /// Blend a shaded color toward the fog color
+    pub fn apply(&self, color: Vec3, camera: Vec3, point: Vec3) -> Vec3 {
+        color.lerp(self.color, self.factor(camera, point))
+    }
+
+    /// Pack for upload
+    pub fn to_uniform(&self) -> FogUniform {
+        let (mode, distance) = match self.falloff {
+            FogFalloff::Linear { start, end } => (0.0, [start, end, 0.0, 0.0]),
+            FogFalloff::Exponential { density } => (1.0, [0.0, 0.0, density, 0.0]),
+            FogFalloff::ExponentialSquared { density } => (2.0, [0.0, 0.0, density, 0.0]),
+        };
+        let height = self
+            .height
+            .map_or([0.0; 4], |h| [h.base_height, h.density, h.falloff, 1.0]);
+        FogUniform {
+            color_mode: [self.color.x, self.color.y, self.color.z, mode],
+            distance,
+            height,
+        }
+    }
+}
+
+impl Default for FogParams {
+    fn default() -> Self {
+        Self::from_sky(&Sky::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fog_factor_near_mid_far() {
+        let color = Vec3::new(0.7, 0.8, 1.0);
+        let close = |a: f32, b: f32| (a - b).abs() < 1e-5;
+
+        let linear = FogParams::linear(64.0, 192.0, color);
+        assert_eq!(linear.distance_factor(16.0), 0.0);
+        assert_eq!(linear.distance_factor(128.0), 0.5);
+        assert_eq!(linear.distance_factor(400.0), 1.0);
+
+        let exp = FogParams::exponential(0.01, color);
+        assert_eq!(exp.distance_factor(0.0), 0.0);
+        assert!(close(exp.distance_factor(100.0), 1.0 - (-1.0f32).exp())); // 0.632
+        assert!(close(exp.distance_factor(500.0), 1.0 - (-5.0f32).exp())); // 0.993
+
+        let exp2 = FogParams { falloff: FogFalloff::ExponentialSquared { density: 0.01 }, ..exp };
+        assert!(close(exp2.distance_factor(100.0), 1.0 - (-1.0f32).exp()));
+        assert!(exp2.distance_factor(50.0) < exp.distance_factor(50.0));
+
+        // Far terrain blends fully into the fog color
+        let far = exp.apply(Vec3::ZERO, Vec3::ZERO, Vec3::new(5000.0, 0.0, 0.0));
+        assert!(far.distance(color) < 1e-3);
+
+        // Level ray through height fog at its base: 1 - e^(-density * d)
+        let valley = FogParams::linear(1000.0, 2000.0, color)
+            .with_height_fog(HeightFog { base_height: 40.0, density: 0.02, falloff: 0.1 });
+        let at_base = valley.factor(Vec3::new(0.0, 40.0, 0.0), Vec3::new(50.0, 40.0, 0.0));
+        assert!(close(at_base, 1.0 - (-1.0f32).exp()));
+        // Higher up the same ray is clearer
+        let above = valley.factor(Vec3::new(0.0, 80.0, 0.0), Vec3::new(50.0, 80.0, 0.0));
+        assert!(above < at_base * 0.1);
+
+        let uniform = valley.to_uniform();
+        assert_eq!(uniform.color_mode[3], 0.0);
+        assert_eq!(uniform.height, [40.0, 0.02, 0.1, 1.0]);
+    }
+}
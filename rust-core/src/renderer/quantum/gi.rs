@@ -0,0 +1,192 @@
+//! # Probe GI
+//!
+//! One bounce of indirect light, gathered on a grid of irradiance probes.
+//! Each update casts a few rays per probe into the scene (the Nanite SDF in
+//! practice). Where a ray hits, the probe receives the surface's albedo
+//! times the direct light at the hit point. Ray directions rotate every
+//! frame, and results blend into the probe's running average, so a handful
+//! of rays per frame converges over a second or so. Shading reads
+//! irradiance trilinearly from the eight surrounding probes.
+
+use std::collections::HashMap;
+
+use glam::{IVec3, Vec3};
+
+/// Default distance between probes in blocks
+pub const DEFAULT_PROBE_SPACING: f32 = 8.0;
+
+/// Default rays per probe per update
+pub const DEFAULT_GI_RAYS: u32 = 8;
+
+/// Weight of history in the running average once a probe has converged
+const HISTORY_WEIGHT: f32 = 0.9;
+
+/// Golden angle, rotating the ray set between frames
+const GOLDEN_ANGLE: f32 = 2.399_963;
+
+/// Surface a GI ray hit
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GiHit {
+    pub position: Vec3,
+    pub normal: Vec3,
+    /// Surface color 0..1
+    pub albedo: Vec3,
+}
+
+/// Irradiance probe
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Probe {
+    /// Averaged indirect light
+    pub irradiance: Vec3,
+    /// Updates blended in so far
+    pub samples: u32,
+}
+
+/// Grid of irradiance probes
+pub struct ProbeGrid {
+    /// Distance between probes
+    spacing: f32,
+    /// Probes by grid cell
+    probes: HashMap<IVec3, Probe>,
+    /// Update counter (rotates ray directions)
+    frame: u32,
+}
+
+impl ProbeGrid {
+    /// Create an empty grid
+    pub fn new(spacing: f32) -> Self {
+        Self {
+            spacing: spacing.max(1.0),
+            probes: HashMap::new(),
+            frame: 0,
+        }
+    }
+
+    /// Get the world position of a probe cell
+    pub fn probe_position(&self, cell: IVec3) -> Vec3 {
+        cell.as_vec3() * self.spacing
+    }
+
+    /// Update every probe within `radius` cells of `center`
+    ///
+    /// `trace` casts a ray (origin, direction) and `direct` gives the direct
+    /// light arriving at a surface (position, normal). Returns rays cast.
+    pub fn update(
+        &mut self,
+        center: Vec3,
+        radius: i32,
+        ray_count: u32,
+        trace: impl Fn(Vec3, Vec3) -> Option<GiHit>,
+        direct: impl Fn(Vec3, Vec3) -> Vec3,
+    ) -> u32 {
+        let center_cell = (center / self.spacing).round().as_ivec3();
+        let ray_count = ray_count.max(1);
+        let rotation = self.frame as f32 * GOLDEN_ANGLE;
+        self.frame = self.frame.wrapping_add(1);
+
+        let mut rays = 0;
+        for dy in -radius..=radius {
+            for dz in -radius..=radius {
+                for dx in -radius..=radius {
+                    let cell = center_cell + IVec3::new(dx, dy, dz);
+                    let origin = self.probe_position(cell);
+
+                    let mut gathered = Vec3::ZERO;
+                    for i in 0..ray_count {
+                        let direction = sphere_direction(i, ray_count, rotation);
+                        if let Some(hit) = trace(origin, direction) {
+                            gathered += hit.albedo * direct(hit.position, hit.normal);
+                        }
+                    }
+                    rays += ray_count;
+
+                    let probe = self.probes.entry(cell).or_default();
+                    // Plain average until converged, then an exponential one
+                    let weight = (probe.samples as f32 / (probe.samples + 1) as f32).min(HISTORY_WEIGHT);
+                    probe.irradiance = (gathered / ray_count as f32).lerp(probe.irradiance, weight);
+                    probe.samples += 1;
+                }
+            }
+        }
+        rays
+    }
+
+    /// Get the indirect light at a position (trilinear over nearby probes)
+    pub fn irradiance_at(&self, position: Vec3) -> Vec3 {
+        let grid = position / self.spacing;
+        let base = grid.floor();
+        let t = grid - base;
+        let base = base.as_ivec3();
+
+        let mut sum = Vec3::ZERO;
+        let mut total = 0.0;
+        for corner in 0..8 {
+            let offset = IVec3::new(corner & 1, (corner >> 1) & 1, (corner >> 2) & 1);
+            let Some(probe) = self.probes.get(&(base + offset)) else {
+                continue;
+            };
+            let w = Vec3::select(offset.cmpeq(IVec3::ZERO), 1.0 - t, t);
+            let weight = w.x * w.y * w.z;
+            sum += probe.irradiance * weight;
+            total += weight;
+        }
+        // Renormalize so missing probes don't darken their neighbors
+        if total > 0.0 { sum / total } else { Vec3::ZERO }
+    }
+
+    /// Get a probe by cell
+    pub fn probe(&self, cell: IVec3) -> Option<&Probe> {
+        self.probes.get(&cell)
+    }
+
+    /// Get the number of probes
+    pub fn probe_count(&self) -> usize {
+        self.probes.len()
+    }
+
+    /// Drop all probes
+    pub fn clear(&mut self) {
+        self.probes.clear();
+    }
+}
+
+/// Direction `index` of `total` spread over the sphere, rotated about Y
+fn sphere_direction(index: u32, total: u32, rotation: f32) -> Vec3 {
+    let y = 1.0 - 2.0 * (index as f32 + 0.5) / total as f32;
+    let radius = (1.0 - y * y).max(0.0).sqrt();
+    let theta = index as f32 * GOLDEN_ANGLE + rotation;
+    Vec3::new(theta.cos() * radius, y, theta.sin() * radius)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_probe_picks_up_tinted_bounce() {
+        let mut grid = ProbeGrid::new(DEFAULT_PROBE_SPACING);
+        // A red wall at x = 3 lit by bright white light; nothing else
+        let trace = |origin: Vec3, dir: Vec3| {
+            let t = (3.0 - origin.x) / dir.x;
+            (dir.x > 0.0 && t > 0.0).then(|| GiHit {
+                position: origin + dir * t,
+                normal: Vec3::NEG_X,
+                albedo: Vec3::new(0.9, 0.1, 0.1),
+            })
+        };
+        let direct = |_: Vec3, _: Vec3| Vec3::splat(10.0);
+
+        for _ in 0..8 {
+            grid.update(Vec3::ZERO, 0, DEFAULT_GI_RAYS, trace, direct);
+        }
+        let near = grid.probe(IVec3::ZERO).unwrap().irradiance;
+        assert!(near.x > 1.0, "{:?}", near);
+        assert!(near.x > near.y * 5.0 && near.y > 0.0);
+        assert_eq!(grid.irradiance_at(Vec3::new(1.0, 2.0, -1.0)), near);
+
+        // A probe past the wall sees nothing
+        grid.update(Vec3::new(8.0, 0.0, 0.0), 0, DEFAULT_GI_RAYS, trace, direct);
+        assert_eq!(grid.probe(IVec3::X).unwrap().irradiance, Vec3::ZERO);
+        assert!(grid.irradiance_at(Vec3::new(4.0, 0.0, 0.0)).x < near.x);
+    }
+}
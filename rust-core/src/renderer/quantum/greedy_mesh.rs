@@ -2,10 +2,21 @@
 //! 
 //! GPU-accelerated greedy meshing using Vulkan compute shaders.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 use parking_lot::RwLock;
 use ash::vk;
 
+use super::fluid;
+use crate::events::EventQueue;
+use crate::renderer::vulkan::GpuWatchdog;
+use crate::util::jobs::JobSystem;
+use crate::util::rng::Rng;
+
+/// Blocks `ChunkVoxelData::generate_test_pattern` draws from: stone, grass,
+/// dirt, log (rotated UVs), glass (transparent) and water (fluid pass)
+pub const TEST_PATTERN_BLOCKS: [u16; 6] = [1, 2, 3, 17, 20, 8];
+
 /// Block face direction
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[repr(u8)]
@@ -41,6 +52,85 @@ pub struct GreedyFace {
     pub block_id: u16,
     pub texture_layer: u16,
     pub light: u8, pub ao: u8,
+    /// UV rotation in quarter turns (see `UvRotation`)
+    pub rotation: u8,
+}
+
+/// Texture UV rotation applied to every face of a block
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[repr(u8)]
+pub enum UvRotation {
+    #[default]
+    None = 0,
+    Cw90 = 1,
+    Cw180 = 2,
+    Cw270 = 3,
+}
+
+impl UvRotation {
+    /// Map a quarter-turn count (wrapping)
+    pub fn from_quarter_turns(turns: u8) -> Self {
+        match turns % 4 {
+            0 => UvRotation::None,
+            1 => UvRotation::Cw90,
+            2 => UvRotation::Cw180,
+            _ => UvRotation::Cw270,
+        }
+    }
+    
+    /// Rotate a UV inside a `w`×`h` quad
+    pub fn apply(self, u: f32, v: f32, w: f32, h: f32) -> (f32, f32) {
+        match self {
+            UvRotation::None => (u, v),
+            UvRotation::Cw90 => (v, w - u),
+            UvRotation::Cw180 => (w - u, h - v),
+            UvRotation::Cw270 => (h - v, u),
+        }
+    }
+}
+
+/// Per-block, per-face texture layers and UV rotations
+///
+/// Faces without an entry use the block ID as their layer.
+#[derive(Debug, Clone, Default)]
+pub struct BlockFaceTextures {
+    layers: HashMap<(u16, FaceDirection), u16>,
+    rotations: HashMap<u16, UvRotation>,
+}
+
+impl BlockFaceTextures {
+    /// Create an empty table
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Set the texture layer of one face of a block
+    pub fn set_face_texture(&mut self, block_id: u16, face: FaceDirection, layer: u16) {
+        self.layers.insert((block_id, face), layer);
+    }
+    
+    /// Set the UV rotation of a block
+    pub fn set_rotation(&mut self, block_id: u16, rotation: UvRotation) {
+        self.rotations.insert(block_id, rotation);
+    }
+    
+    /// Get the texture layer of one face of a block
+    pub fn layer(&self, block_id: u16, face: FaceDirection) -> u16 {
+        self.layers.get(&(block_id, face)).copied().unwrap_or(block_id)
+    }
+    
+    /// Get the UV rotation of a block
+    pub fn rotation(&self, block_id: u16) -> UvRotation {
+        self.rotations.get(&block_id).copied().unwrap_or_default()
+    }
+}
+
+/// What adjacent faces must share to be merged
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct MergeKey {
+    block_id: u16,
+    texture_layer: u16,
+    rotation: UvRotation,
 }
 
 /// Chunk voxel data
@@ -71,6 +161,72 @@ impl ChunkVoxelData {
     
     pub fn is_solid(&self, block: u16) -> bool { block != 0 }
     pub fn is_transparent(&self, block: u16) -> bool { matches!(block, 0 | 20 | 95 | 8 | 9) }
+    
+    /// Fill a chunk reproducibly for benchmarks and regression tests
+    ///
+    /// Each block is solid with probability `fill_ratio`. Every 4×4×4 cell
+    /// has a main block type that most of its blocks use, so the greedy
+    /// mesher finds runs to merge as it would in real terrain, with
+    /// scattered other types breaking them up.
+    pub fn generate_test_pattern(seed: u64, fill_ratio: f32) -> Self {
+        let mut rng = Rng::new(seed);
+        let cell_types: Vec<u16> = (0..64)
+            .map(|_| *rng.pick(&TEST_PATTERN_BLOCKS).expect("test blocks are not empty"))
+            .collect();
+        
+        let mut chunk = Self::default();
+        for y in 0..16 {
+            for z in 0..16 {
+                for x in 0..16 {
+                    if !rng.chance(fill_ratio) {
+                        continue;
+                    }
+                    let block = if rng.chance(0.75) {
+                        cell_types[(y / 4) * 16 + (z / 4) * 4 + x / 4]
+                    } else {
+                        *rng.pick(&TEST_PATTERN_BLOCKS).expect("test blocks are not empty")
+                    };
+                    chunk.set_block(x, y, z, block);
+                }
+            }
+        }
+        chunk
+    }
+    
+    /// Count non-air blocks
+    pub fn solid_count(&self) -> usize {
+        self.blocks.iter().filter(|&&b| self.is_solid(b)).count()
+    }
+}
+
+/// Result of meshing a generated workload
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MeshBenchReport {
+    /// Chunks meshed
+    pub chunks: usize,
+    /// Quads after greedy merging
+    pub faces: usize,
+    /// Quads a one-per-block-face mesher would emit
+    pub unmerged_faces: usize,
+    /// Wall time spent meshing
+    pub elapsed: std::time::Duration,
+}
+
+impl MeshBenchReport {
+    /// Merged quads produced per second
+    pub fn faces_per_sec(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 { self.faces as f64 / secs } else { 0.0 }
+    }
+    
+    /// Fraction of vertices saved by merging (0 = none, 0.9 = 10× fewer)
+    pub fn vertex_reduction(&self) -> f64 {
+        if self.unmerged_faces == 0 {
+            0.0
+        } else {
+            1.0 - self.faces as f64 / self.unmerged_faces as f64
+        }
+    }
 }
 
 /// GPU Greedy Mesher with real Vulkan compute pipeline
@@ -89,6 +245,8 @@ pub struct GpuGreedyMesher {
     command_pool: vk::CommandPool,
     command_buffer: vk::CommandBuffer,
     fence: vk::Fence,
+    watchdog: Arc<GpuWatchdog>,
+    textures: BlockFaceTextures,
     max_faces: usize,
     initialized: bool,
 }
@@ -111,11 +269,38 @@ impl GpuGreedyMesher {
             command_pool: vk::CommandPool::null(),
             command_buffer: vk::CommandBuffer::null(),
             fence: vk::Fence::null(),
+            watchdog: Arc::new(GpuWatchdog::new(EventQueue::new())),
+            textures: BlockFaceTextures::new(),
             max_faces: 16384,
             initialized: false,
         }
     }
     
+    /// Share a device's watchdog so hangs reach its event queue
+    pub fn set_watchdog(&mut self, watchdog: Arc<GpuWatchdog>) {
+        self.watchdog = watchdog;
+    }
+    
+    /// Get the watchdog bounding the dispatch fence wait
+    pub fn watchdog(&self) -> &Arc<GpuWatchdog> {
+        &self.watchdog
+    }
+    
+    /// Set the texture layer used for one face of a block (e.g. grass top vs side)
+    pub fn set_block_face_texture(&mut self, block_id: u16, face: FaceDirection, layer: u16) {
+        self.textures.set_face_texture(block_id, face, layer);
+    }
+    
+    /// Set the UV rotation of a block's faces
+    pub fn set_block_rotation(&mut self, block_id: u16, rotation: UvRotation) {
+        self.textures.set_rotation(block_id, rotation);
+    }
+    
+    /// Get the per-face texture table
+    pub fn textures(&self) -> &BlockFaceTextures {
+        &self.textures
+    }
+    
     /// Initialize with Vulkan device
     pub fn initialize(
         &mut self,
@@ -268,8 +453,8 @@ impl GpuGreedyMesher {
         
         unsafe {
             // Wait for previous work
-            device.wait_for_fences(std::slice::from_ref(&self.fence), true, u64::MAX)
-                .map_err(|e| format!("Failed to wait for fence: {:?}", e))?;
+            self.watchdog.wait_for_fences(device, std::slice::from_ref(&self.fence), "greedy mesh dispatch")
+                .map_err(|e| e.to_string())?;
             device.reset_fences(std::slice::from_ref(&self.fence))
                 .map_err(|e| format!("Failed to reset fence: {:?}", e))?;
             
@@ -310,7 +495,10 @@ impl GpuGreedyMesher {
                 .command_buffers(std::slice::from_ref(&self.command_buffer));
             
             device.queue_submit(queue, std::slice::from_ref(&submit_info), self.fence)
-                .map_err(|e| format!("Failed to submit: {:?}", e))?;
+                .map_err(|e| match e {
+                    vk::Result::ERROR_DEVICE_LOST => self.watchdog.device_lost("greedy mesh submit").to_string(),
+                    _ => format!("Failed to submit: {:?}", e),
+                })?;
         }
         
         Ok(())
@@ -327,6 +515,38 @@ impl GpuGreedyMesher {
         faces
     }
     
+    /// Mesh many chunks on CPU across the shared job pool
+    pub fn mesh_chunks_cpu(&self, chunks: &[ChunkVoxelData]) -> Vec<Vec<GreedyFace>> {
+        use rayon::prelude::*;
+        
+        JobSystem::global().install(|| {
+            chunks.par_iter().map(|chunk| self.mesh_chunk_cpu(chunk)).collect()
+        })
+    }
+    
+    /// Mesh `chunks` generated test patterns on the job pool and time it
+    ///
+    /// Chunk `i` uses seed `seed + i`, so a run is reproducible from its
+    /// arguments. Generation is not part of the timing.
+    pub fn bench(&self, chunks: usize, seed: u64, fill_ratio: f32) -> MeshBenchReport {
+        let workload: Vec<ChunkVoxelData> = (0..chunks as u64)
+            .map(|i| ChunkVoxelData::generate_test_pattern(seed.wrapping_add(i), fill_ratio))
+            .collect();
+        
+        let start = std::time::Instant::now();
+        let meshes = self.mesh_chunks_cpu(&workload);
+        let elapsed = start.elapsed();
+        
+        let faces = meshes.iter().map(Vec::len).sum();
+        // Each merged quad stands for width × height single-block faces
+        let unmerged_faces = meshes
+            .iter()
+            .flatten()
+            .map(|f| f.width as usize * f.height as usize)
+            .sum();
+        MeshBenchReport { chunks, faces, unmerged_faces, elapsed }
+    }
+    
     fn mesh_direction(&self, chunk: &ChunkVoxelData, direction: FaceDirection, faces: &mut Vec<GreedyFace>) {
         let (u_axis, v_axis, d_axis) = match direction {
             FaceDirection::PosX | FaceDirection::NegX => (2, 1, 0),
@@ -340,7 +560,7 @@ impl GpuGreedyMesher {
         };
         
         for d in 0..16 {
-            let mut mask = [[0u16; 16]; 16];
+            let mut mask = [[None::<MergeKey>; 16]; 16];
             
             for v in 0..16 {
                 for u in 0..16 {
@@ -350,7 +570,8 @@ impl GpuGreedyMesher {
                     pos[d_axis] = d;
                     
                     let block = chunk.get_block(pos[0], pos[1], pos[2]);
-                    if !chunk.is_solid(block) { continue; }
+                    // Fluids go in the transparent pass (see `FluidMesher`)
+                    if !chunk.is_solid(block) || fluid::is_fluid(block) { continue; }
                     
                     let neighbor_d = d as i32 + d_step;
                     let neighbor_block = if neighbor_d < 0 || neighbor_d >= 16 {
@@ -361,7 +582,11 @@ impl GpuGreedyMesher {
                     };
                     
                     if !chunk.is_solid(neighbor_block) || chunk.is_transparent(neighbor_block) {
-                        mask[v][u] = block;
+                        mask[v][u] = Some(MergeKey {
+                            block_id: block,
+                            texture_layer: self.textures.layer(block, direction),
+                            rotation: self.textures.rotation(block),
+                        });
                     }
                 }
             }
@@ -370,16 +595,15 @@ impl GpuGreedyMesher {
             for v in 0..16 {
                 let mut u = 0;
                 while u < 16 {
-                    let block = mask[v][u];
-                    if block == 0 { u += 1; continue; }
+                    let Some(key) = mask[v][u] else { u += 1; continue; };
                     
                     let mut width = 1;
-                    while u + width < 16 && mask[v][u + width] == block { width += 1; }
+                    while u + width < 16 && mask[v][u + width] == Some(key) { width += 1; }
                     
                     let mut height = 1;
                     'height: while v + height < 16 {
                         for wu in 0..width {
-                            if mask[v + height][u + wu] != block { break 'height; }
+                            if mask[v + height][u + wu] != Some(key) { break 'height; }
                         }
                         height += 1;
                     }
@@ -393,13 +617,14 @@ impl GpuGreedyMesher {
                         x: pos[0], y: pos[1], z: pos[2],
                         direction: direction as u8,
                         width: width as u8, height: height as u8,
-                        block_id: block, texture_layer: block,
+                        block_id: key.block_id, texture_layer: key.texture_layer,
                         light: 0xFF, ao: 0,
+                        rotation: key.rotation as u8,
                     });
                     
                     for vh in 0..height {
                         for wu in 0..width {
-                            mask[v + vh][u + wu] = 0;
+                            mask[v + vh][u + wu] = None;
                         }
                     }
                     
@@ -432,14 +657,24 @@ impl GpuGreedyMesher {
         let w = face.width as f32;
         let h = face.height as f32;
         
-        match FaceDirection::all()[face.direction as usize] {
+        let quad = match FaceDirection::all()[face.direction as usize] {
             FaceDirection::PosX => ([x+1.0, y, z, 0.0, 0.0], [x+1.0, y+h, z, 0.0, h], [x+1.0, y+h, z+w, w, h], [x+1.0, y, z+w, w, 0.0]),
             FaceDirection::NegX => ([x, y, z+w, 0.0, 0.0], [x, y+h, z+w, 0.0, h], [x, y+h, z, w, h], [x, y, z, w, 0.0]),
             FaceDirection::PosY => ([x, y+1.0, z, 0.0, 0.0], [x, y+1.0, z+h, 0.0, h], [x+w, y+1.0, z+h, w, h], [x+w, y+1.0, z, w, 0.0]),
             FaceDirection::NegY => ([x, y, z+h, 0.0, 0.0], [x, y, z, 0.0, h], [x+w, y, z, w, h], [x+w, y, z+h, w, 0.0]),
             FaceDirection::PosZ => ([x+w, y, z+1.0, 0.0, 0.0], [x+w, y+h, z+1.0, 0.0, h], [x, y+h, z+1.0, w, h], [x, y, z+1.0, w, 0.0]),
             FaceDirection::NegZ => ([x, y, z, 0.0, 0.0], [x, y+h, z, 0.0, h], [x+w, y+h, z, w, h], [x+w, y, z, w, 0.0]),
+        };
+        
+        let rotation = UvRotation::from_quarter_turns(face.rotation);
+        if rotation == UvRotation::None {
+            return quad;
         }
+        let rotate = |mut vertex: [f32; 5]| {
+            (vertex[3], vertex[4]) = rotation.apply(vertex[3], vertex[4], w, h);
+            vertex
+        };
+        (rotate(quad.0), rotate(quad.1), rotate(quad.2), rotate(quad.3))
     }
     
     /// Generate indices
@@ -454,7 +689,7 @@ impl GpuGreedyMesher {
     
     /// Shutdown
     pub fn shutdown(&mut self) {
-        if let Some(device) = &self.device {
+        if let Some(device) = self.device.take() {
             unsafe {
                 device.device_wait_idle().ok();
                 
@@ -483,3 +718,74 @@ impl Default for GpuGreedyMesher { fn default() -> Self { Self::new() } }
 impl Drop for GpuGreedyMesher {
     fn drop(&mut self) { self.shutdown(); }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GRASS: u16 = 2;
+    const GRASS_TOP: u16 = 100;
+    const GRASS_SIDE: u16 = 101;
+    const DIRT: u16 = 3;
+
+    #[test]
+    fn test_grass_faces_use_per_face_layers() {
+        let mut chunk = ChunkVoxelData::default();
+        for z in 0..4 {
+            for x in 0..4 {
+                chunk.set_block(x, 0, z, GRASS);
+            }
+        }
+
+        let mut mesher = GpuGreedyMesher::new();
+        mesher.set_block_face_texture(GRASS, FaceDirection::PosY, GRASS_TOP);
+        mesher.set_block_face_texture(GRASS, FaceDirection::NegY, DIRT);
+        for side in [FaceDirection::PosX, FaceDirection::NegX, FaceDirection::PosZ, FaceDirection::NegZ] {
+            mesher.set_block_face_texture(GRASS, side, GRASS_SIDE);
+        }
+
+        let faces = mesher.mesh_chunk_cpu(&chunk);
+        // Each side of the 4×4 patch still merges into one quad
+        assert_eq!(faces.len(), 6);
+        for face in &faces {
+            let expected = match FaceDirection::all()[face.direction as usize] {
+                FaceDirection::PosY => GRASS_TOP,
+                FaceDirection::NegY => DIRT,
+                _ => GRASS_SIDE,
+            };
+            assert_eq!(face.texture_layer, expected);
+            assert_eq!(face.block_id, GRASS);
+        }
+
+        // Rotated blocks carry their rotation into the quad UVs
+        const LOG: u16 = 17;
+        chunk.set_block(0, 0, 0, LOG);
+        mesher.set_block_rotation(LOG, UvRotation::Cw90);
+        let faces = mesher.mesh_chunk_cpu(&chunk);
+        let log_top = faces.iter().find(|f| f.block_id == LOG && f.direction == FaceDirection::PosY as u8).unwrap();
+        assert_eq!(log_top.rotation, UvRotation::Cw90 as u8);
+        assert_eq!(log_top.texture_layer, LOG);
+        let vertices = mesher.generate_vertices(std::slice::from_ref(log_top));
+        assert_eq!(&vertices[6..8], &[0.0, 1.0]); // UV (0, 0) turned a quarter
+
+    }
+
+    #[test]
+    fn test_generated_pattern_is_deterministic() {
+        let a = ChunkVoxelData::generate_test_pattern(1234, 0.5);
+        let b = ChunkVoxelData::generate_test_pattern(1234, 0.5);
+        assert_eq!(a.blocks, b.blocks);
+        assert_ne!(a.blocks, ChunkVoxelData::generate_test_pattern(1235, 0.5).blocks);
+
+        let sparse = ChunkVoxelData::generate_test_pattern(1234, 0.2).solid_count();
+        let dense = ChunkVoxelData::generate_test_pattern(1234, 0.8).solid_count();
+        assert!(sparse < a.solid_count() && a.solid_count() < dense);
+        assert_eq!(ChunkVoxelData::generate_test_pattern(9, 0.0).solid_count(), 0);
+        assert_eq!(ChunkVoxelData::generate_test_pattern(9, 1.0).solid_count(), 4096);
+
+        let report = GpuGreedyMesher::new().bench(4, 77, 0.9);
+        assert_eq!(report.chunks, 4);
+        assert!(report.faces > 0 && report.unmerged_faces >= report.faces);
+        assert!((0.0..1.0).contains(&report.vertex_reduction()));
+    }
+}
@@ -0,0 +1,426 @@
+//! # GPU Light Propagation
+//!
+//! Compute-shader version of `world::light` for large relights. The block
+//! grid and two light buffers live in storage buffers; each step is one
+//! dispatch of the relaxation kernel, ping-ponging source and destination,
+//! until no cell changes or the iteration cap is hit. Without a compute
+//! device the CPU flood fill is used instead.
+
+use std::sync::Arc;
+use ash::vk;
+
+use crate::renderer::vulkan::{VulkanDevice, VulkanError};
+use crate::world::light::{self, LightGrid, CONVERGENCE_BOUND};
+
+/// Compute workgroup size (matches `local_size_*` in the shader)
+pub const WORKGROUP_SIZE: [u32; 3] = [4, 4, 4];
+
+/// Relaxation kernel; cells pack `opacity | emission << 8`
+pub const LIGHT_PROPAGATION_SHADER: &str = r#"#version 450
+layout(local_size_x = 4, local_size_y = 4, local_size_z = 4) in;
+
+layout(push_constant) uniform Params { uvec4 size; } params;
+layout(std430, binding = 0) readonly buffer Cells { uint cells[]; };
+layout(std430, binding = 1) readonly buffer Src { uint src[]; };
+layout(std430, binding = 2) writeonly buffer Dst { uint dst[]; };
+layout(std430, binding = 3) buffer Changed { uint changed; };
+
+uint attenuate(uint opacity, uint level) {
+    if (opacity >= 15u) return 0u;
+    return level > opacity + 1u ? level - opacity - 1u : 0u;
+}
+
+void main() {
+    uvec3 p = gl_GlobalInvocationID;
+    uvec3 size = params.size.xyz;
+    if (any(greaterThanEqual(p, size))) return;
+
+    uint i = (p.y * size.z + p.z) * size.x + p.x;
+    uint opacity = cells[i] & 0xFFu;
+    uint level = (cells[i] >> 8) & 0xFFu;
+    uint layer = size.x * size.z;
+
+    if (p.x > 0u)          level = max(level, attenuate(opacity, src[i - 1u]));
+    if (p.x + 1u < size.x) level = max(level, attenuate(opacity, src[i + 1u]));
+    if (p.z > 0u)          level = max(level, attenuate(opacity, src[i - size.x]));
+    if (p.z + 1u < size.z) level = max(level, attenuate(opacity, src[i + size.x]));
+    if (p.y > 0u)          level = max(level, attenuate(opacity, src[i - layer]));
+    if (p.y + 1u < size.y) level = max(level, attenuate(opacity, src[i + layer]));
+
+    dst[i] = level;
+    if (level != src[i]) atomicOr(changed, 1u);
+}
+"#;
+
+/// Workgroups needed to cover a grid
+pub fn dispatch_size(size: [usize; 3]) -> [u32; 3] {
+    [0, 1, 2].map(|axis| (size[axis] as u32).div_ceil(WORKGROUP_SIZE[axis]))
+}
+
+/// Backend that produced the last result
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LightBackend {
+    Cpu,
+    Gpu,
+}
+
+/// Host-visible storage buffer
+struct HostBuffer {
+    buffer: vk::Buffer,
+    memory: vk::DeviceMemory,
+    mapped: *mut u32,
+}
+
+/// Compute pipeline and submission resources
+struct LightGpu {
+    device: Arc<VulkanDevice>,
+    set_layout: vk::DescriptorSetLayout,
+    layout: vk::PipelineLayout,
+    pipeline: vk::Pipeline,
+    descriptor_pool: vk::DescriptorPool,
+    command_pool: vk::CommandPool,
+}
+
+/// Light propagator with GPU acceleration and CPU fallback
+pub struct LightPropagator {
+    gpu: Option<LightGpu>,
+    max_iterations: u32,
+    last_iterations: u32,
+    last_backend: LightBackend,
+}
+
+impl LightPropagator {
+    /// Create a CPU-only propagator
+    pub fn new() -> Self {
+        Self {
+            gpu: None,
+            max_iterations: CONVERGENCE_BOUND,
+            last_iterations: 0,
+            last_backend: LightBackend::Cpu,
+        }
+    }
+
+    /// Set the relaxation step cap for the GPU path
+    pub fn set_max_iterations(&mut self, max_iterations: u32) {
+        self.max_iterations = max_iterations.max(1);
+    }
+
+    /// Get steps run by the last GPU propagation (0 for CPU)
+    pub fn last_iterations(&self) -> u32 {
+        self.last_iterations
+    }
+
+    /// Get the backend that produced the last result
+    pub fn last_backend(&self) -> LightBackend {
+        self.last_backend
+    }
+
+    /// Check if the GPU path is available
+    pub fn has_gpu(&self) -> bool {
+        self.gpu.is_some()
+    }
+
+    /// Propagate light, falling back to the CPU if the GPU path fails
+    pub fn propagate(&mut self, grid: &LightGrid) -> Vec<u8> {
+        if let Some(gpu) = &self.gpu {
+            match gpu.run(grid, self.max_iterations) {
+                Ok((light, iterations)) => {
+                    self.last_backend = LightBackend::Gpu;
+                    self.last_iterations = iterations;
+                    return light;
+                }
+                Err(e) => log::warn!("GPU light propagation failed, using CPU: {}", e),
+            }
+        }
+
+        self.last_backend = LightBackend::Cpu;
+        self.last_iterations = 0;
+        light::propagate(grid)
+    }
+
+    /// Create the compute pipeline from compiled `LIGHT_PROPAGATION_SHADER`
+    pub fn enable_gpu(&mut self, device: Arc<VulkanDevice>, spirv: &[u32]) -> Result<(), VulkanError> {
+        let family = device.queue_families().compute
+            .or(device.queue_families().graphics)
+            .ok_or(VulkanError::NotInitialized)?;
+        let vk_device = device.handle();
+        let err = |what: &str, e: vk::Result| VulkanError::PipelineCreationFailed(format!("{}: {:?}", what, e));
+
+        unsafe {
+            let bindings = [0, 1, 2, 3].map(|binding| vk::DescriptorSetLayoutBinding::default()
+                .binding(binding)
+                .descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
+                .descriptor_count(1)
+                .stage_flags(vk::ShaderStageFlags::COMPUTE));
+            let set_layout = vk_device.create_descriptor_set_layout(
+                &vk::DescriptorSetLayoutCreateInfo::default().bindings(&bindings), None,
+            ).map_err(|e| err("Failed to create light set layout", e))?;
+
+            let set_layouts = [set_layout];
+            let push_constants = [vk::PushConstantRange::default()
+                .stage_flags(vk::ShaderStageFlags::COMPUTE)
+                .offset(0)
+                .size(16)];
+            let layout = device.create_pipeline_layout(
+                &vk::PipelineLayoutCreateInfo::default()
+                    .set_layouts(&set_layouts)
+                    .push_constant_ranges(&push_constants),
+            )?;
+
+            let module = vk_device.create_shader_module(
+                &vk::ShaderModuleCreateInfo::default().code(spirv), None,
+            ).map_err(|e| err("Failed to create light shader", e))?;
+            let stage = vk::PipelineShaderStageCreateInfo::default()
+                .stage(vk::ShaderStageFlags::COMPUTE)
+                .module(module)
+                .name(c"main");
+            let created = vk_device.create_compute_pipelines(
+                vk::PipelineCache::null(),
+                &[vk::ComputePipelineCreateInfo::default().stage(stage).layout(layout)],
+                None,
+            );
+            vk_device.destroy_shader_module(module, None);
+            let pipeline = created.map_err(|(_, e)| err("Failed to create light pipeline", e))?[0];
+
+            // Two sets: A -> B and B -> A
+            let pool_sizes = [vk::DescriptorPoolSize { ty: vk::DescriptorType::STORAGE_BUFFER, descriptor_count: 8 }];
+            let descriptor_pool = vk_device.create_descriptor_pool(
+                &vk::DescriptorPoolCreateInfo::default()
+                    .flags(vk::DescriptorPoolCreateFlags::FREE_DESCRIPTOR_SET)
+                    .max_sets(2)
+                    .pool_sizes(&pool_sizes),
+                None,
+            ).map_err(|e| err("Failed to create light descriptor pool", e))?;
+
+            let command_pool = vk_device.create_command_pool(
+                &vk::CommandPoolCreateInfo::default()
+                    .queue_family_index(family)
+                    .flags(vk::CommandPoolCreateFlags::TRANSIENT),
+                None,
+            ).map_err(|e| err("Failed to create light command pool", e))?;
+
+            self.gpu = Some(LightGpu { device, set_layout, layout, pipeline, descriptor_pool, command_pool });
+        }
+
+        log::info!("Light propagation: GPU compute enabled");
+        Ok(())
+    }
+}
+
+impl LightGpu {
+    /// Run relaxation steps on the GPU until converged or capped
+    fn run(&self, grid: &LightGrid, max_iterations: u32) -> Result<(Vec<u8>, u32), VulkanError> {
+        let cells = grid.len();
+        if cells == 0 {
+            return Ok((Vec::new(), 0));
+        }
+
+        let mut buffers = Vec::with_capacity(4);
+        let result = (|| unsafe {
+            for len in [cells, cells, cells, 1] {
+                buffers.push(self.create_buffer(len)?);
+            }
+
+            let (packed, front, back, changed) = (&buffers[0], &buffers[1], &buffers[2], &buffers[3]);
+            for i in 0..cells {
+                *packed.mapped.add(i) = grid.opacity[i] as u32 | (grid.emission[i] as u32) << 8;
+                *front.mapped.add(i) = grid.emission[i] as u32;
+            }
+
+            let iterations = self.dispatch_until_converged(grid.size, front, back, changed, packed, max_iterations)?;
+
+            // Odd step count leaves the result in `back`
+            let result = if iterations % 2 == 1 { back } else { front };
+            let light = (0..cells).map(|i| *result.mapped.add(i) as u8).collect();
+            Ok((light, iterations))
+        })();
+
+        for buffer in buffers {
+            unsafe { self.destroy_buffer(buffer) };
+        }
+        result
+    }
+
+    unsafe fn dispatch_until_converged(
+        &self,
+        size: [usize; 3],
+        front: &HostBuffer,
+        back: &HostBuffer,
+        changed: &HostBuffer,
+        packed: &HostBuffer,
+        max_iterations: u32,
+    ) -> Result<u32, VulkanError> {
+        let device = self.device.handle();
+        let err = |what: &str, e: vk::Result| VulkanError::CommandBufferError(format!("{}: {:?}", what, e));
+
+        let layouts = [self.set_layout, self.set_layout];
+        let sets = device.allocate_descriptor_sets(
+            &vk::DescriptorSetAllocateInfo::default()
+                .descriptor_pool(self.descriptor_pool)
+                .set_layouts(&layouts),
+        ).map_err(|e| err("Failed to allocate light descriptor sets", e))?;
+
+        for (set, (src, dst)) in sets.iter().zip([(front, back), (back, front)]) {
+            let infos = [packed, src, dst, changed].map(|b| [vk::DescriptorBufferInfo {
+                buffer: b.buffer,
+                offset: 0,
+                range: vk::WHOLE_SIZE,
+            }]);
+            let writes: Vec<_> = infos.iter().enumerate().map(|(binding, info)| {
+                vk::WriteDescriptorSet::default()
+                    .dst_set(*set)
+                    .dst_binding(binding as u32)
+                    .descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
+                    .buffer_info(info)
+            }).collect();
+            device.update_descriptor_sets(&writes, &[]);
+        }
+
+        let cmd = device.allocate_command_buffers(
+            &vk::CommandBufferAllocateInfo::default()
+                .command_pool(self.command_pool)
+                .level(vk::CommandBufferLevel::PRIMARY)
+                .command_buffer_count(1),
+        ).map_err(|e| err("Failed to allocate light command buffer", e))?[0];
+        let fence = device.create_fence(&vk::FenceCreateInfo::default(), None)
+            .map_err(|e| err("Failed to create light fence", e))?;
+
+        let groups = dispatch_size(size);
+        let push = [size[0] as u32, size[1] as u32, size[2] as u32, 0];
+        let queue = self.device.compute_queue();
+
+        let mut iterations = 0;
+        let outcome = loop {
+            if iterations == max_iterations {
+                break Ok(iterations);
+            }
+            *changed.mapped = 0;
+
+            let step = device.reset_command_buffer(cmd, vk::CommandBufferResetFlags::empty())
+                .and_then(|_| device.begin_command_buffer(
+                    cmd, &vk::CommandBufferBeginInfo::default().flags(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT),
+                ))
+                .and_then(|_| {
+                    device.cmd_bind_pipeline(cmd, vk::PipelineBindPoint::COMPUTE, self.pipeline);
+                    device.cmd_bind_descriptor_sets(
+                        cmd, vk::PipelineBindPoint::COMPUTE, self.layout, 0,
+                        &[sets[iterations as usize % 2]], &[],
+                    );
+                    device.cmd_push_constants(cmd, self.layout, vk::ShaderStageFlags::COMPUTE, 0, bytemuck::cast_slice(&push));
+                    device.cmd_dispatch(cmd, groups[0], groups[1], groups[2]);
+                    device.end_command_buffer(cmd)
+                })
+                .and_then(|_| device.reset_fences(&[fence]))
+                .and_then(|_| device.queue_submit(
+                    queue, &[vk::SubmitInfo::default().command_buffers(std::slice::from_ref(&cmd))], fence,
+                ));
+
+            if let Err(e) = step {
+                break Err(err("Light propagation step failed", e));
+            }
+            if let Err(e) = self.device.wait_for_fences(&[fence], "light propagation") {
+                break Err(e);
+            }
+            iterations += 1;
+            if *changed.mapped == 0 {
+                break Ok(iterations);
+            }
+        };
+
+        device.destroy_fence(fence, None);
+        device.free_command_buffers(self.command_pool, &[cmd]);
+        device.free_descriptor_sets(self.descriptor_pool, &sets).ok();
+        outcome
+    }
+
+    unsafe fn create_buffer(&self, len: usize) -> Result<HostBuffer, VulkanError> {
+        let device = self.device.handle();
+        let size = (len * 4) as vk::DeviceSize;
+        let err = |what: &str, e: vk::Result| VulkanError::BufferCreationFailed(format!("{}: {:?}", what, e));
+
+        let buffer = device.create_buffer(
+            &vk::BufferCreateInfo::default()
+                .size(size)
+                .usage(vk::BufferUsageFlags::STORAGE_BUFFER)
+                .sharing_mode(vk::SharingMode::EXCLUSIVE),
+            None,
+        ).map_err(|e| err("Failed to create light buffer", e))?;
+
+        let requirements = device.get_buffer_memory_requirements(buffer);
+        let Some(memory_type) = self.device.find_memory_type(
+            requirements.memory_type_bits,
+            vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT,
+        ) else {
+            device.destroy_buffer(buffer, None);
+            return Err(VulkanError::BufferCreationFailed("No host-visible memory for light buffers".to_string()));
+        };
+
+        let memory = device.allocate_memory(
+            &vk::MemoryAllocateInfo::default()
+                .allocation_size(requirements.size)
+                .memory_type_index(memory_type),
+            None,
+        ).map_err(|e| {
+            device.destroy_buffer(buffer, None);
+            err("Failed to allocate light memory", e)
+        })?;
+
+        let mapped = device.bind_buffer_memory(buffer, memory, 0)
+            .and_then(|_| device.map_memory(memory, 0, size, vk::MemoryMapFlags::empty()))
+            .map_err(|e| {
+                device.destroy_buffer(buffer, None);
+                device.free_memory(memory, None);
+                err("Failed to map light memory", e)
+            })?;
+
+        Ok(HostBuffer { buffer, memory, mapped: mapped as *mut u32 })
+    }
+
+    unsafe fn destroy_buffer(&self, buffer: HostBuffer) {
+        let device = self.device.handle();
+        device.unmap_memory(buffer.memory);
+        device.destroy_buffer(buffer.buffer, None);
+        device.free_memory(buffer.memory, None);
+    }
+}
+
+impl Drop for LightGpu {
+    fn drop(&mut self) {
+        let device = self.device.handle();
+        unsafe {
+            device.device_wait_idle().ok();
+            device.destroy_command_pool(self.command_pool, None);
+            device.destroy_descriptor_pool(self.descriptor_pool, None);
+            device.destroy_pipeline(self.pipeline, None);
+            device.destroy_pipeline_layout(self.layout, None);
+            device.destroy_descriptor_set_layout(self.set_layout, None);
+        }
+    }
+}
+
+impl Default for LightPropagator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dispatch_and_fallback() {
+        assert_eq!(dispatch_size([18, 18, 18]), [5, 5, 5]);
+        assert_eq!(dispatch_size([16, 1, 4]), [4, 1, 1]);
+
+        let mut grid = LightGrid::new([18, 18, 18]);
+        grid.set(8, 8, 8, 0, 15);
+        grid.set(9, 8, 8, 15, 0);
+
+        // No device: CPU flood fill, identical to the kernel's fixed point
+        let mut propagator = LightPropagator::new();
+        let result = propagator.propagate(&grid);
+        assert_eq!(propagator.last_backend(), LightBackend::Cpu);
+        assert_eq!(result, light::propagate_iterative(&grid, CONVERGENCE_BOUND).0);
+    }
+}
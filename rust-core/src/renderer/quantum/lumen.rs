@@ -4,12 +4,22 @@
 //! - Screen-Space Global Illumination (SSGI)
 //! - Simplified Voxel Cone Tracing
 //! - Dynamic light emitter detection for mod compatibility
+//! - One bounce of indirect light from probes traced against the Nanite SDF
 
 use ash::vk;
 use std::sync::Arc;
 use glam::{Vec3, Vec4};
 use std::collections::HashMap;
 
+use super::gi::{GiHit, ProbeGrid, DEFAULT_GI_RAYS, DEFAULT_PROBE_SPACING};
+use super::nanite::NaniteManager;
+
+/// Probes updated around the camera, in cells each way
+const GI_PROBE_RADIUS: i32 = 2;
+
+/// Longest GI ray in blocks
+const GI_MAX_DISTANCE: f32 = 32.0;
+
 /// Light emitter types
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum LightType {
@@ -49,6 +59,14 @@ pub struct LumenLite {
     mod_light_blocks: HashMap<u32, LightProperties>,
     /// SSGI settings
     ssgi_settings: SsgiSettings,
+    /// Ambient sky light added to every sample
+    ambient: Vec3,
+    /// Indirect light probes
+    gi_probes: ProbeGrid,
+    /// Indirect bounces (0 or 1)
+    gi_bounces: u32,
+    /// Rays per probe per update
+    gi_ray_count: u32,
     /// Statistics
     stats: LumenStats,
 }
@@ -87,6 +105,7 @@ pub struct LumenStats {
     pub ssgi_rays: u32,
     pub voxel_volumes: u32,
     pub gi_bounces: u32,
+    pub gi_rays: u32,
 }
 
 impl LumenLite {
@@ -112,6 +131,10 @@ impl LumenLite {
                 intensity: 1.0,
                 quality: SsgiQuality::High,
             },
+            ambient: Vec3::ZERO,
+            gi_probes: ProbeGrid::new(DEFAULT_PROBE_SPACING),
+            gi_bounces: 1,
+            gi_ray_count: DEFAULT_GI_RAYS,
             stats: LumenStats::default(),
         }
     }
@@ -207,10 +230,20 @@ impl LumenLite {
         }
     }
     
-    /// Calculate SSGI for a position
+    /// Set the ambient sky light (see `Sky::ambient_color`)
+    pub fn set_ambient(&mut self, ambient: Vec3) {
+        self.ambient = ambient;
+    }
+    
+    /// Get the ambient sky light
+    pub fn ambient(&self) -> Vec3 {
+        self.ambient
+    }
+    
+    /// Calculate SSGI for a position (plus ambient sky light)
     pub fn calculate_ssgi(&self, position: Vec3, normal: Vec3) -> Vec3 {
         if !self.ssgi_settings.enabled {
-            return Vec3::ZERO;
+            return self.ambient;
         }
         
         let mut accumulated_light = Vec3::ZERO;
@@ -237,7 +270,82 @@ impl LumenLite {
             }
         }
         
-        accumulated_light * self.ssgi_settings.intensity / ray_count as f32
+        accumulated_light * self.ssgi_settings.intensity / ray_count as f32 + self.ambient + self.indirect_light(position)
+    }
+    
+    /// Set indirect bounces (0 disables GI, more than 1 is treated as 1)
+    pub fn set_gi_bounces(&mut self, bounces: u32) {
+        self.gi_bounces = bounces.min(1);
+        if self.gi_bounces == 0 {
+            self.gi_probes.clear();
+        }
+    }
+    
+    /// Get indirect bounces
+    pub fn gi_bounces(&self) -> u32 {
+        self.gi_bounces
+    }
+    
+    /// Set rays per probe per update (more converges faster, costs more)
+    pub fn set_gi_ray_count(&mut self, rays: u32) {
+        self.gi_ray_count = rays.max(1);
+    }
+    
+    /// Get rays per probe per update
+    pub fn gi_ray_count(&self) -> u32 {
+        self.gi_ray_count
+    }
+    
+    /// Direct light from point lights reaching a surface
+    pub fn direct_light(&self, position: Vec3, normal: Vec3) -> Vec3 {
+        Self::point_light(&self.light_sources, position, normal)
+    }
+    
+    fn point_light(lights: &[LightSource], position: Vec3, normal: Vec3) -> Vec3 {
+        lights.iter().fold(Vec3::ZERO, |sum, light| {
+            let to_light = light.position - position;
+            let distance = to_light.length();
+            if distance >= light.radius || distance == 0.0 {
+                return sum;
+            }
+            let attenuation = 1.0 / (1.0 + distance * distance * 0.1);
+            sum + light.color * light.intensity * attenuation * normal.dot(to_light / distance).max(0.0)
+        })
+    }
+    
+    /// Gather one bounce into the probes around the camera (call once per frame)
+    pub fn update_gi(&mut self, nanite: &NaniteManager, camera: Vec3) {
+        if self.gi_bounces == 0 {
+            return;
+        }
+        let trace = |origin: Vec3, direction: Vec3| {
+            let hit = nanite.ray_march(origin, direction);
+            (hit.hit && hit.distance <= GI_MAX_DISTANCE).then(|| GiHit {
+                position: hit.position,
+                normal: hit.normal,
+                albedo: Vec3::new(
+                    ((hit.color >> 16) & 0xFF) as f32,
+                    ((hit.color >> 8) & 0xFF) as f32,
+                    (hit.color & 0xFF) as f32,
+                ) / 255.0 * hit.ao,
+            })
+        };
+        let direct = |position: Vec3, normal: Vec3| Self::point_light(&self.light_sources, position, normal) + self.ambient;
+        let rays = self.gi_probes.update(camera, GI_PROBE_RADIUS, self.gi_ray_count, trace, direct);
+        self.stats.gi_rays += rays;
+    }
+    
+    /// Get the indirect light at a position (zero with GI off)
+    pub fn indirect_light(&self, position: Vec3) -> Vec3 {
+        if self.gi_bounces == 0 {
+            return Vec3::ZERO;
+        }
+        self.gi_probes.irradiance_at(position)
+    }
+    
+    /// Get the indirect light probes
+    pub fn gi_probes(&self) -> &ProbeGrid {
+        &self.gi_probes
     }
     
     /// Fibonacci sphere point distribution
@@ -343,6 +451,7 @@ impl LumenLite {
     pub fn clear(&mut self) {
         self.light_sources.clear();
         self.voxel_volumes.clear();
+        self.gi_probes.clear();
         self.stats = LumenStats::default();
     }
 }
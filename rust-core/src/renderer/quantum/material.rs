@@ -0,0 +1,240 @@
+//! # Materials
+//!
+//! Fixed-function state of transparent surfaces: blend factors and ops,
+//! depth writes and face culling. Materials are registered under an id;
+//! every distinct state gets one pipeline variant, so water and glass
+//! that happen to share a state share a pipeline. Variants are built on
+//! first use by the caller's pipeline builder and dropped when the render
+//! pass changes (e.g. an MSAA switch).
+
+use std::collections::HashMap;
+
+use ash::vk;
+
+use super::RendererError;
+
+/// Material identifier
+pub type MaterialId = u32;
+
+/// Built-in material ids
+pub const OPAQUE: MaterialId = 0;
+pub const WATER: MaterialId = 1;
+pub const GLASS: MaterialId = 2;
+pub const STAINED_GLASS: MaterialId = 3;
+pub const PARTICLE: MaterialId = 4;
+
+/// Color and alpha blend equation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BlendState {
+    pub enabled: bool,
+    pub src_color: vk::BlendFactor,
+    pub dst_color: vk::BlendFactor,
+    pub color_op: vk::BlendOp,
+    pub src_alpha: vk::BlendFactor,
+    pub dst_alpha: vk::BlendFactor,
+    pub alpha_op: vk::BlendOp,
+}
+
+impl BlendState {
+    /// No blending
+    pub const OPAQUE: BlendState = BlendState::new(false, vk::BlendFactor::ONE, vk::BlendFactor::ZERO);
+    /// Classic `src * a + dst * (1 - a)`
+    pub const ALPHA: BlendState = BlendState::new(true, vk::BlendFactor::SRC_ALPHA, vk::BlendFactor::ONE_MINUS_SRC_ALPHA);
+    /// Color already multiplied by alpha
+    pub const PREMULTIPLIED: BlendState = BlendState::new(true, vk::BlendFactor::ONE, vk::BlendFactor::ONE_MINUS_SRC_ALPHA);
+    /// Light adds up (fire, sparks)
+    pub const ADDITIVE: BlendState = BlendState::new(true, vk::BlendFactor::SRC_ALPHA, vk::BlendFactor::ONE);
+    /// Tints what is behind (stained glass)
+    pub const MULTIPLY: BlendState = BlendState::new(true, vk::BlendFactor::DST_COLOR, vk::BlendFactor::ZERO);
+
+    /// Same factors for color and alpha, added
+    pub const fn new(enabled: bool, src: vk::BlendFactor, dst: vk::BlendFactor) -> Self {
+        Self {
+            enabled,
+            src_color: src,
+            dst_color: dst,
+            color_op: vk::BlendOp::ADD,
+            src_alpha: src,
+            dst_alpha: dst,
+            alpha_op: vk::BlendOp::ADD,
+        }
+    }
+
+    /// Attachment state for building a pipeline
+    pub fn attachment(&self) -> vk::PipelineColorBlendAttachmentState {
+        vk::PipelineColorBlendAttachmentState::default()
+            .blend_enable(self.enabled)
+            .src_color_blend_factor(self.src_color)
+            .dst_color_blend_factor(self.dst_color)
+            .color_blend_op(self.color_op)
+            .src_alpha_blend_factor(self.src_alpha)
+            .dst_alpha_blend_factor(self.dst_alpha)
+            .alpha_blend_op(self.alpha_op)
+            .color_write_mask(vk::ColorComponentFlags::RGBA)
+    }
+}
+
+/// Render state of a surface type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Material {
+    pub blend: BlendState,
+    /// Write depth (off for most transparent surfaces so they don't hide each other)
+    pub depth_write: bool,
+    pub cull_mode: vk::CullModeFlags,
+}
+
+impl Material {
+    /// Solid blocks
+    pub const OPAQUE: Material = Material { blend: BlendState::OPAQUE, depth_write: true, cull_mode: vk::CullModeFlags::BACK };
+    /// Water: alpha blended, both faces so it shows from below
+    pub const WATER: Material = Material { blend: BlendState::ALPHA, depth_write: false, cull_mode: vk::CullModeFlags::NONE };
+    /// Clear glass
+    pub const GLASS: Material = Material { blend: BlendState::ALPHA, depth_write: false, cull_mode: vk::CullModeFlags::BACK };
+    /// Stained glass tints the scene behind it
+    pub const STAINED_GLASS: Material = Material { blend: BlendState::MULTIPLY, depth_write: false, cull_mode: vk::CullModeFlags::BACK };
+    /// Billboards glowing additively
+    pub const PARTICLE: Material = Material { blend: BlendState::ADDITIVE, depth_write: false, cull_mode: vk::CullModeFlags::NONE };
+
+    /// Depth state for building a pipeline (always tested, written if `depth_write`)
+    pub fn depth_stencil_state(&self) -> vk::PipelineDepthStencilStateCreateInfo<'static> {
+        vk::PipelineDepthStencilStateCreateInfo::default()
+            .depth_test_enable(true)
+            .depth_write_enable(self.depth_write)
+            .depth_compare_op(vk::CompareOp::LESS_OR_EQUAL)
+    }
+
+    /// Check if the surface needs back-to-front sorting
+    pub fn is_transparent(&self) -> bool {
+        self.blend.enabled
+    }
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Self::OPAQUE
+    }
+}
+
+/// Materials by id and their deduplicated pipeline variants
+pub struct MaterialRegistry {
+    /// Variant index of each material
+    materials: HashMap<MaterialId, usize>,
+    /// Distinct states, indexed by variant
+    variants: Vec<Material>,
+    /// Variant index of each distinct state
+    variant_of: HashMap<Material, usize>,
+    /// Built pipelines by variant (null until first use)
+    pipelines: Vec<vk::Pipeline>,
+}
+
+impl MaterialRegistry {
+    /// Create a registry with the built-in materials
+    pub fn new() -> Self {
+        let mut registry = Self {
+            materials: HashMap::new(),
+            variants: Vec::new(),
+            variant_of: HashMap::new(),
+            pipelines: Vec::new(),
+        };
+        registry.register_material(OPAQUE, Material::OPAQUE);
+        registry.register_material(WATER, Material::WATER);
+        registry.register_material(GLASS, Material::GLASS);
+        registry.register_material(STAINED_GLASS, Material::STAINED_GLASS);
+        registry.register_material(PARTICLE, Material::PARTICLE);
+        registry
+    }
+
+    /// Register or replace a material; returns its pipeline variant
+    pub fn register_material(&mut self, id: MaterialId, material: Material) -> usize {
+        let variant = *self.variant_of.entry(material).or_insert_with(|| {
+            self.variants.push(material);
+            self.pipelines.push(vk::Pipeline::null());
+            self.variants.len() - 1
+        });
+        self.materials.insert(id, variant);
+        variant
+    }
+
+    /// Get a material's state
+    pub fn material(&self, id: MaterialId) -> Option<&Material> {
+        self.materials.get(&id).map(|&variant| &self.variants[variant])
+    }
+
+    /// Get a material's pipeline variant
+    pub fn variant(&self, id: MaterialId) -> Option<usize> {
+        self.materials.get(&id).copied()
+    }
+
+    /// Get the number of distinct pipeline variants
+    pub fn variant_count(&self) -> usize {
+        self.variants.len()
+    }
+
+    /// Get the pipeline for a material, building its variant on first use
+    pub fn pipeline(
+        &mut self,
+        id: MaterialId,
+        build: impl FnOnce(&Material) -> Result<vk::Pipeline, RendererError>,
+    ) -> Result<vk::Pipeline, RendererError> {
+        let variant = self.variant(id).ok_or_else(|| RendererError::VulkanError(format!("Unknown material {}", id)))?;
+        if self.pipelines[variant] == vk::Pipeline::null() {
+            self.pipelines[variant] = build(&self.variants[variant])?;
+        }
+        Ok(self.pipelines[variant])
+    }
+
+    /// Forget built pipelines (e.g. after a render pass change), returning
+    /// them for destruction
+    pub fn take_pipelines(&mut self) -> Vec<vk::Pipeline> {
+        self.pipelines
+            .iter_mut()
+            .map(std::mem::take)
+            .filter(|&pipeline| pipeline != vk::Pipeline::null())
+            .collect()
+    }
+}
+
+impl Default for MaterialRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ash::vk::Handle;
+
+    #[test]
+    fn test_distinct_states_get_distinct_variants() {
+        let mut registry = MaterialRegistry::new();
+        assert_eq!(registry.variant_count(), 5);
+
+        // A custom ice material with the same state as glass shares its variant
+        assert_eq!(registry.register_material(100, Material::GLASS), registry.variant(GLASS).unwrap());
+        let slime = Material { depth_write: true, ..Material::GLASS };
+        let slime_variant = registry.register_material(101, slime);
+        assert_eq!((slime_variant, registry.variant_count()), (5, 6));
+        assert!(registry.material(STAINED_GLASS).unwrap().is_transparent());
+        assert!(!registry.material(OPAQUE).unwrap().is_transparent());
+
+        // Each variant is built once, on first use
+        let builds = std::cell::Cell::new(0);
+        let build = |material: &Material| {
+            builds.set(builds.get() + 1);
+            Ok(vk::Pipeline::from_raw(builds.get() + material.blend.dst_color.as_raw() as u64 * 100))
+        };
+        let glass = registry.pipeline(GLASS, build).unwrap();
+        assert_eq!(registry.pipeline(100, build).unwrap(), glass);
+        let water = registry.pipeline(WATER, build).unwrap();
+        let particle = registry.pipeline(PARTICLE, build).unwrap();
+        assert!(glass != water && water != particle);
+        assert_eq!(builds.get(), 3);
+        assert!(registry.pipeline(999, build).is_err());
+
+        // Dropping pipelines hands back exactly the built ones
+        assert_eq!(registry.take_pipelines().len(), 3);
+        registry.pipeline(GLASS, build).unwrap();
+        assert_eq!(builds.get(), 4);
+    }
+}
@@ -10,13 +10,42 @@
 pub mod compositor;
 pub mod nanite;
 pub mod lumen;
+pub mod gi;
 pub mod pipeline;
 pub mod greedy_mesh;
+pub mod capture;
+pub mod lighting;
+pub mod recovery;
+pub mod fade;
+pub mod fluid;
+pub mod sky;
+pub mod fog;
+pub mod debug_view;
+pub mod tonemap;
+pub mod exposure;
+pub mod msaa;
+pub mod material;
+pub mod text;
 
 use ash::vk;
+use glam::Vec3;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use parking_lot::RwLock;
 
+use crate::events::EventQueue;
+use crate::profiling::spans;
+use crate::renderer::vulkan::GpuWatchdog;
+
+/// Chunk section identifier (chunk x, section y, chunk z)
+pub type ChunkId = (i32, i32, i32);
+
+/// Bounding sphere radius of a 16³ chunk section
+const CHUNK_RADIUS: f32 = 13.856_406;
+
+/// Default render distance in chunks
+pub const DEFAULT_RENDER_DISTANCE: u32 = 12;
+
 /// Quantum Renderer - Hybrid Vulkan/OpenGL rendering system
 pub struct QuantumRenderer {
     /// Vulkan instance
@@ -31,12 +60,62 @@ pub struct QuantumRenderer {
     command_pool: Option<vk::CommandPool>,
     /// Swapchain
     swapchain: Option<SwapchainData>,
+    /// MSAA sample count (clamped to device support once a device exists)
+    msaa: msaa::SampleCount,
+    /// Multisampled targets resolving into the swapchain (None at 1x)
+    msaa_targets: Option<msaa::MsaaTargets>,
     /// Nanite virtual geometry manager
     nanite: Option<nanite::NaniteManager>,
     /// Lumen lighting system
     lumen: Option<lumen::LumenLite>,
+    /// Time of day, sun and ambient light
+    sky: sky::Sky,
+    /// Distance and height fog
+    fog: fog::FogParams,
+    /// Scene to swapchain mapping (SDR or HDR)
+    tonemap: tonemap::TonemapParams,
+    /// Eye adaptation driving the tonemap exposure (None = manual exposure)
+    auto_exposure: Option<exposure::AutoExposure>,
+    /// Transparent materials and their pipeline variants
+    materials: material::MaterialRegistry,
+    /// Pipeline variant bound for each transparent draw this frame
+    transparent_variants: Vec<usize>,
+    /// Debug visualization toggles in effect
+    debug_render: debug_view::DebugRenderFlags,
+    /// Device supports `fillModeNonSolid`
+    wireframe_supported: bool,
     /// Frame statistics
     stats: RenderStats,
+    /// Camera position
+    camera_pos: Vec3,
+    /// Camera view direction (normalized)
+    camera_dir: Vec3,
+    /// Half-angle of the view cone enclosing the frustum (radians)
+    view_half_angle: f32,
+    /// Horizontal render distance in chunks
+    render_distance: u32,
+    /// Chunks fading in after meshing
+    fade: fade::ChunkFade,
+    /// Uniforms of the chunks drawn this frame (parallel to `visible_chunks`)
+    chunk_uniforms: Vec<fade::ChunkUniform>,
+    /// Chunks drawn this frame
+    visible_chunks: Vec<ChunkId>,
+    /// Chunks culled this frame
+    culled_chunks: Vec<ChunkId>,
+    /// Chunks whose fluid was drawn this frame, in draw order
+    fluid_chunks: Vec<ChunkId>,
+    /// Swapchain image presented last
+    last_presented: Option<u32>,
+    /// CPU color target used without Vulkan
+    cpu_frame: Option<capture::Image>,
+    /// Bounds GPU waits and flags hangs for recovery (shared with the mesher)
+    watchdog: Arc<GpuWatchdog>,
+    /// Compute greedy mesher
+    greedy_mesher: greedy_mesh::GpuGreedyMesher,
+    /// Window the renderer was initialized for
+    window_handle: u64,
+    /// Resources to recreate after device loss
+    recovery: recovery::DeviceRecovery,
     /// Initialization state
     initialized: bool,
 }
@@ -66,25 +145,55 @@ pub struct RenderStats {
 }
 
 impl QuantumRenderer {
-    /// Create new uninitialized renderer
-    pub fn new() -> Self {
-        Self {
+    /// Create new uninitialized renderer (Vulkan comes up in `initialize`)
+    pub fn new() -> Result<Self, RendererError> {
+        let events = EventQueue::new();
+        let watchdog = Arc::new(GpuWatchdog::new(events.clone()));
+        let mut greedy_mesher = greedy_mesh::GpuGreedyMesher::new();
+        greedy_mesher.set_watchdog(watchdog.clone());
+        Ok(Self {
             instance: None,
             physical_device: None,
             device: None,
             graphics_queue: None,
             command_pool: None,
             swapchain: None,
+            msaa: msaa::SampleCount::X1,
+            msaa_targets: None,
             nanite: None,
             lumen: None,
+            sky: sky::Sky::new(),
+            fog: fog::FogParams::default(),
+            tonemap: tonemap::TonemapParams::default(),
+            auto_exposure: None,
+            materials: material::MaterialRegistry::new(),
+            transparent_variants: Vec::new(),
+            debug_render: debug_view::DebugRenderFlags::default(),
+            wireframe_supported: false,
             stats: RenderStats::default(),
+            camera_pos: Vec3::ZERO,
+            camera_dir: Vec3::NEG_Z,
+            view_half_angle: std::f32::consts::FRAC_PI_2,
+            render_distance: DEFAULT_RENDER_DISTANCE,
+            fade: fade::ChunkFade::new(),
+            chunk_uniforms: Vec::new(),
+            visible_chunks: Vec::new(),
+            culled_chunks: Vec::new(),
+            fluid_chunks: Vec::new(),
+            last_presented: None,
+            cpu_frame: None,
+            watchdog,
+            greedy_mesher,
+            window_handle: 0,
+            recovery: recovery::DeviceRecovery::new(events),
             initialized: false,
-        }
+        })
     }
     
     /// Initialize Vulkan context
     pub fn initialize(&mut self, window_handle: u64) -> Result<(), RendererError> {
         log::info!("Initializing Quantum Renderer...");
+        self.window_handle = window_handle;
         
         // Create Vulkan instance
         self.create_instance()?;
@@ -98,6 +207,10 @@ impl QuantumRenderer {
         // Create swapchain
         self.create_swapchain(window_handle)?;
         
+        // Re-check the sample count against this device
+        self.msaa = self.msaa.clamp_to(self.supported_sample_counts());
+        self.rebuild_msaa_targets()?;
+        
         // Initialize Nanite geometry system
         self.nanite = Some(nanite::NaniteManager::new(
             self.device.clone().unwrap(),
@@ -108,6 +221,10 @@ impl QuantumRenderer {
             self.device.clone().unwrap(),
         ));
         
+        // Compute meshing on the graphics queue family
+        self.greedy_mesher.initialize(self.device.clone().unwrap(), 0)
+            .map_err(RendererError::VulkanError)?;
+        
         self.initialized = true;
         log::info!("Quantum Renderer initialized successfully");
         
@@ -203,9 +320,13 @@ impl QuantumRenderer {
                 ash::khr::swapchain::NAME.as_ptr(),
             ];
             
+            let supported = instance.get_physical_device_features(physical_device);
+            self.wireframe_supported = supported.fill_mode_non_solid == vk::TRUE;
+            
             let features = vk::PhysicalDeviceFeatures::default()
                 .sampler_anisotropy(true)
-                .multi_draw_indirect(true);
+                .multi_draw_indirect(true)
+                .fill_mode_non_solid(self.wireframe_supported);
             
             let create_info = vk::DeviceCreateInfo::default()
                 .queue_create_infos(std::slice::from_ref(&queue_create_info))
@@ -240,8 +361,93 @@ impl QuantumRenderer {
         Ok(())
     }
     
+    /// Sample counts the device supports (everything before a device is picked)
+    fn supported_sample_counts(&self) -> vk::SampleCountFlags {
+        match (&self.instance, self.physical_device) {
+            (Some(instance), Some(physical_device)) => {
+                let props = unsafe { instance.get_physical_device_properties(physical_device) };
+                msaa::supported_sample_counts(&props.limits)
+            }
+            _ => msaa::SampleCount::ALL.iter().fold(vk::SampleCountFlags::empty(), |flags, count| flags | count.to_vk()),
+        }
+    }
+    
+    /// Set the MSAA sample count; returns the count in effect
+    ///
+    /// Counts the device can't use for both color and depth fall back to the
+    /// highest one it can. Geometry passes render into the multisampled
+    /// targets, which resolve into the swapchain image before the GUI pass.
+    pub fn set_msaa(&mut self, samples: msaa::SampleCount) -> Result<msaa::SampleCount, RendererError> {
+        let samples = samples.clamp_to(self.supported_sample_counts());
+        if samples != self.msaa {
+            self.msaa = samples;
+            self.rebuild_msaa_targets()?;
+            log::info!("MSAA set to {}x", samples.samples());
+        }
+        Ok(samples)
+    }
+    
+    /// Get the MSAA sample count in effect
+    pub fn msaa(&self) -> msaa::SampleCount {
+        self.msaa
+    }
+    
+    /// Get the multisampled targets (None at 1x or without a swapchain)
+    pub fn msaa_targets(&self) -> Option<&msaa::MsaaTargets> {
+        self.msaa_targets.as_ref()
+    }
+    
+    /// Recreate multisampled targets for the current swapchain and sample count
+    fn rebuild_msaa_targets(&mut self) -> Result<(), RendererError> {
+        let Some(device) = self.device.clone() else {
+            return Ok(());
+        };
+        if let Some(mut targets) = self.msaa_targets.take() {
+            unsafe { device.device_wait_idle().ok() };
+            targets.destroy(&device);
+        }
+        // Material pipelines were built against the old render pass
+        for pipeline in self.materials.take_pipelines() {
+            unsafe { device.destroy_pipeline(pipeline, None) };
+        }
+        
+        let (Some(instance), Some(physical_device), Some(swapchain)) = (&self.instance, self.physical_device, &self.swapchain) else {
+            return Ok(());
+        };
+        if self.msaa == msaa::SampleCount::X1 {
+            return Ok(());
+        }
+        let memory_properties = unsafe { instance.get_physical_device_memory_properties(physical_device) };
+        self.msaa_targets = Some(msaa::MsaaTargets::new(
+            &device,
+            &memory_properties,
+            swapchain.extent,
+            swapchain.format,
+            &swapchain.image_views,
+            self.msaa,
+        )?);
+        Ok(())
+    }
+    
     /// Begin frame rendering
     pub fn begin_frame(&mut self) -> Result<FrameContext, RendererError> {
+        let _span = tracing::debug_span!(spans::RENDER_BEGIN_FRAME, frame = self.stats.frames_rendered + 1).entered();
+        self.visible_chunks.clear();
+        self.culled_chunks.clear();
+        self.fluid_chunks.clear();
+        self.transparent_variants.clear();
+        self.chunk_uniforms.clear();
+        self.fade.prune(Instant::now());
+        
+        if self.watchdog.take_recovery() && self.initialized {
+            log::warn!("Tearing down Vulkan after GPU failure ({} hangs so far)", self.watchdog.hang_count());
+            self.recovery.mark_lost();
+            self.shutdown();
+        }
+        if self.recovery.state() == recovery::DeviceState::Lost {
+            self.recreate_device();
+        }
+        
         if !self.initialized {
             return Err(RendererError::NotInitialized);
         }
@@ -253,16 +459,259 @@ impl QuantumRenderer {
         })
     }
     
+    /// Update camera used for culling and LOD selection
+    pub fn update_camera(&mut self, position: Vec3, direction: Vec3, fov_y_degrees: f32, aspect: f32) {
+        self.camera_pos = position;
+        self.camera_dir = direction.normalize_or(Vec3::NEG_Z);
+        
+        // Cone through the frustum corners
+        let tan_half_y = (fov_y_degrees.to_radians() * 0.5).tan();
+        self.view_half_angle = (tan_half_y * (1.0 + aspect * aspect).sqrt()).atan();
+        
+        if let Some(ref mut nanite) = self.nanite {
+            nanite.update_camera(
+                position.x as f64, position.y as f64, position.z as f64,
+                self.camera_dir.x, self.camera_dir.y, self.camera_dir.z,
+            );
+        }
+    }
+    
+    /// Set the time of day, updating the sun and Lumen's ambient light
+    pub fn set_time_of_day(&mut self, ticks_of_day: u32) {
+        self.sky.set_time(ticks_of_day);
+        if let Some(ref mut lumen) = self.lumen {
+            lumen.set_ambient(self.sky.ambient_color());
+        }
+    }
+    
+    /// Get the sky state
+    pub fn sky(&self) -> &sky::Sky {
+        &self.sky
+    }
+    
+    /// Get mutable sky state (e.g. to change the day length)
+    pub fn sky_mut(&mut self) -> &mut sky::Sky {
+        &mut self.sky
+    }
+    
+    /// Set fog (e.g. `FogParams::from_sky` plus biome height fog)
+    pub fn set_fog(&mut self, fog: fog::FogParams) {
+        self.fog = fog;
+    }
+    
+    /// Get the fog settings
+    pub fn fog(&self) -> &fog::FogParams {
+        &self.fog
+    }
+    
+    /// Set tonemapping (use `TonemapParams::for_color_space` with the swapchain's color space)
+    pub fn set_tonemap(&mut self, tonemap: tonemap::TonemapParams) {
+        self.tonemap = tonemap;
+    }
+    
+    /// Get the tonemapping settings
+    pub fn tonemap(&self) -> &tonemap::TonemapParams {
+        &self.tonemap
+    }
+
+    /// Set the SDR tonemapping curve
+    pub fn set_tonemap_operator(&mut self, operator: tonemap::TonemapOperator) {
+        self.tonemap.operator = operator;
+    }
+
+    /// Set a manual exposure in stops (turns auto exposure off)
+    pub fn set_exposure(&mut self, ev: f32) {
+        self.auto_exposure = None;
+        self.tonemap.set_exposure_ev(ev);
+    }
+
+    /// Turn auto exposure on or off (starts from the current exposure)
+    pub fn set_auto_exposure(&mut self, enabled: bool) {
+        self.auto_exposure = enabled.then(|| {
+            let mut auto = exposure::AutoExposure::new();
+            auto.reset(self.tonemap.exposure);
+            auto
+        });
+    }
+
+    /// Get the auto exposure state (None when exposure is manual)
+    pub fn auto_exposure_mut(&mut self) -> Option<&mut exposure::AutoExposure> {
+        self.auto_exposure.as_mut()
+    }
+
+    /// Feed back the luminance reduction partials read from the GPU
+    pub fn adapt_exposure(&mut self, partials: &[[f32; 2]], dt: f32) {
+        if partials.is_empty() {
+            return;
+        }
+        if let Some(auto) = self.auto_exposure.as_mut() {
+            self.tonemap.exposure = auto.adapt(exposure::average_from_partials(partials), dt);
+        }
+    }
+    
+    /// Set debug visualization toggles
+    ///
+    /// Wireframe is ignored (with a warning) if the device lacks
+    /// `fillModeNonSolid`.
+    pub fn set_debug_render(&mut self, flags: debug_view::DebugRenderFlags) {
+        if flags.wireframe && !self.wireframe_supported {
+            log::warn!("Wireframe requested but fillModeNonSolid is unsupported; ignoring");
+        }
+        self.debug_render = flags.supported(self.wireframe_supported);
+    }
+    
+    /// Get the debug toggles in effect
+    pub fn debug_render(&self) -> debug_view::DebugRenderFlags {
+        self.debug_render
+    }
+    
+    /// Get the world pipeline variant for the current debug toggles
+    pub fn pipeline_variant(&self) -> debug_view::PipelineVariant {
+        debug_view::PipelineVariant::select(self.debug_render, self.wireframe_supported)
+    }
+    
+    /// Set the horizontal render distance in chunks
+    pub fn set_render_distance(&mut self, chunks: u32) {
+        self.render_distance = chunks;
+    }
+    
+    /// Get the render distance in chunks
+    pub fn render_distance(&self) -> u32 {
+        self.render_distance
+    }
+    
+    /// Start a chunk's fade-in (call when its mesh is uploaded)
+    pub fn mark_chunk_meshed(&mut self, id: ChunkId) {
+        self.fade.mark_meshed(id, Instant::now());
+    }
+    
+    /// Get a chunk's current fade-in alpha
+    pub fn chunk_alpha(&self, id: ChunkId) -> f32 {
+        self.fade.alpha(id, Instant::now())
+    }
+    
+    /// Get uniforms of the chunks drawn this frame
+    pub fn chunk_uniforms(&self) -> &[fade::ChunkUniform] {
+        &self.chunk_uniforms
+    }
+    
+    /// Check if a chunk column is within the render distance of the camera's chunk
+    fn is_chunk_in_range(&self, id: ChunkId) -> bool {
+        let camera_x = (self.camera_pos.x / 16.0).floor() as i64;
+        let camera_z = (self.camera_pos.z / 16.0).floor() as i64;
+        let dx = id.0 as i64 - camera_x;
+        let dz = id.2 as i64 - camera_z;
+        let radius = self.render_distance as i64;
+        dx * dx + dz * dz <= radius * radius
+    }
+    
+    /// Check if a chunk section's bounding sphere intersects the view cone
+    fn is_chunk_in_view(&self, id: ChunkId) -> bool {
+        let center = Vec3::new(
+            (id.0 * 16 + 8) as f32,
+            (id.1 * 16 + 8) as f32,
+            (id.2 * 16 + 8) as f32,
+        );
+        let to_center = center - self.camera_pos;
+        let along = to_center.dot(self.camera_dir);
+        
+        if along < -CHUNK_RADIUS {
+            return false; // Entirely behind the camera
+        }
+        
+        let from_axis = (to_center.length_squared() - along * along).max(0.0).sqrt();
+        let (sin, cos) = self.view_half_angle.sin_cos();
+        from_axis * cos - along * sin <= CHUNK_RADIUS
+    }
+    
     /// Render chunks using Nanite virtual geometry
     pub fn render_chunks(&mut self, chunks: &[ChunkRenderData]) {
-        if let Some(ref mut nanite) = self.nanite {
-            for chunk in chunks {
+        let now = Instant::now();
+        for chunk in chunks {
+            let id = (chunk.x, chunk.y, chunk.z);
+            
+            if !self.is_chunk_in_range(id) || !self.is_chunk_in_view(id) {
+                self.culled_chunks.push(id);
+                self.stats.chunks_culled += 1;
+                continue;
+            }
+            
+            self.visible_chunks.push(id);
+            self.chunk_uniforms.push(fade::ChunkUniform {
+                origin: [(id.0 * 16) as f32, (id.1 * 16) as f32, (id.2 * 16) as f32],
+                alpha: self.fade.alpha(id, now),
+            });
+            if let Some(ref mut nanite) = self.nanite {
                 nanite.submit_chunk(chunk);
                 self.stats.chunks_rendered += 1;
             }
         }
     }
     
+    /// Register or replace a material; returns its pipeline variant
+    pub fn register_material(&mut self, id: material::MaterialId, material: material::Material) -> usize {
+        self.materials.register_material(id, material)
+    }
+    
+    /// Get the material registry
+    pub fn materials(&self) -> &material::MaterialRegistry {
+        &self.materials
+    }
+    
+    /// Get a material's pipeline, building its variant with `build` on first use
+    pub fn material_pipeline(
+        &mut self,
+        id: material::MaterialId,
+        build: impl FnOnce(&material::Material) -> Result<vk::Pipeline, RendererError>,
+    ) -> Result<vk::Pipeline, RendererError> {
+        self.materials.pipeline(id, build)
+    }
+    
+    /// Get the pipeline variant bound for each transparent draw this frame
+    pub fn transparent_variants(&self) -> &[usize] {
+        &self.transparent_variants
+    }
+    
+    /// Render transparent fluid meshes; call after the opaque `render_chunks`
+    ///
+    /// Sections are drawn farthest first and each mesh is sorted back to
+    /// front, so overlapping fluid blends correctly. Draws bind the water
+    /// material's pipeline variant.
+    pub fn render_fluids(&mut self, fluids: &mut [(ChunkId, fluid::FluidMesh)]) {
+        let water = self.materials.variant(material::WATER).unwrap_or_default();
+        let origin = |id: ChunkId| Vec3::new((id.0 * 16) as f32, (id.1 * 16) as f32, (id.2 * 16) as f32);
+        let camera = self.camera_pos;
+        let distance = |id: ChunkId| (origin(id) + Vec3::splat(8.0)).distance_squared(camera);
+        fluids.sort_by(|a, b| distance(b.0).total_cmp(&distance(a.0)));
+        
+        for (id, mesh) in fluids.iter_mut() {
+            let id = *id;
+            if mesh.is_empty() || !self.is_chunk_in_range(id) || !self.is_chunk_in_view(id) {
+                continue;
+            }
+            mesh.sort_back_to_front(camera - origin(id));
+            self.fluid_chunks.push(id);
+            self.transparent_variants.push(water);
+            self.stats.draw_calls += 1;
+            self.stats.triangles += (mesh.indices.len() / 3) as u64;
+        }
+    }
+    
+    /// Get chunks whose fluid was drawn this frame, in draw order
+    pub fn fluid_chunks(&self) -> &[ChunkId] {
+        &self.fluid_chunks
+    }
+    
+    /// Get chunks that passed culling this frame
+    pub fn visible_chunks(&self) -> &[ChunkId] {
+        &self.visible_chunks
+    }
+    
+    /// Get chunks rejected by culling this frame
+    pub fn culled_chunks(&self) -> &[ChunkId] {
+        &self.culled_chunks
+    }
+    
     /// Render entities
     pub fn render_entities(&mut self, entities: &[EntityRenderData]) {
         for entity in entities {
@@ -276,6 +725,12 @@ impl QuantumRenderer {
     
     /// End frame and present
     pub fn end_frame(&mut self) {
+        let _span = tracing::debug_span!(spans::RENDER_END_FRAME, frame = self.stats.frames_rendered).entered();
+        // Amortized one-bounce GI around the camera
+        if let (Some(lumen), Some(nanite)) = (self.lumen.as_mut(), self.nanite.as_ref()) {
+            lumen.update_gi(nanite, self.camera_pos);
+        }
+        
         // Composite OpenGL UI over Vulkan world
         // Present to swapchain
     }
@@ -290,20 +745,100 @@ impl QuantumRenderer {
         self.initialized
     }
     
-    /// Shutdown renderer
+    /// Get the GPU hang watchdog (checked at the start of every frame)
+    ///
+    /// Pass it to `VulkanRenderer::with_watchdog` so hangs on that device are
+    /// recovered from here too.
+    pub fn watchdog(&self) -> &Arc<GpuWatchdog> {
+        &self.watchdog
+    }
+    
+    /// Get the compute greedy mesher
+    pub fn greedy_mesher(&self) -> &greedy_mesh::GpuGreedyMesher {
+        &self.greedy_mesher
+    }
+    
+    /// Get the compute greedy mesher (to register block face textures)
+    pub fn greedy_mesher_mut(&mut self) -> &mut greedy_mesh::GpuGreedyMesher {
+        &mut self.greedy_mesher
+    }
+    
+    /// Set how long GPU waits may take before they count as a hang
+    pub fn set_gpu_timeout(&self, timeout: Duration) {
+        self.watchdog.set_timeout(timeout);
+    }
+    
+    /// Get device-lost recovery state
+    pub fn recovery(&self) -> &recovery::DeviceRecovery {
+        &self.recovery
+    }
+    
+    /// Get device-lost recovery state for registering and restoring resources
+    pub fn recovery_mut(&mut self) -> &mut recovery::DeviceRecovery {
+        &mut self.recovery
+    }
+    
+    /// Recreate instance, device and swapchain after a loss (retried each frame until it succeeds)
+    fn recreate_device(&mut self) {
+        // Whatever is left of the lost device (or a failed attempt) goes first
+        self.shutdown();
+        match self.initialize(self.window_handle) {
+            Ok(()) => {
+                log::info!("Vulkan recreated, {} resources to re-upload", self.recovery.pending_count());
+                self.recovery.begin_restore();
+            }
+            Err(e) => {
+                log::warn!("Device recreation failed, retrying next frame: {}", e);
+                self.shutdown();
+            }
+        }
+    }
+    
+    /// Shutdown renderer, destroying swapchain, device and instance
+    ///
+    /// Handles are taken as they are destroyed, so calling this again (or
+    /// dropping the renderer afterwards) does nothing.
     pub fn shutdown(&mut self) {
-        if let Some(device) = &self.device {
+        let was_live = self.instance.is_some();
+        self.greedy_mesher.shutdown();
+        // Subsystems holding the device go before it
+        self.nanite = None;
+        self.lumen = None;
+        
+        if let Some(device) = self.device.take() {
             unsafe {
                 device.device_wait_idle().ok();
                 
-                if let Some(pool) = self.command_pool {
+                if let Some(mut targets) = self.msaa_targets.take() {
+                    targets.destroy(&device);
+                }
+                for pipeline in self.materials.take_pipelines() {
+                    device.destroy_pipeline(pipeline, None);
+                }
+                if let Some(pool) = self.command_pool.take() {
                     device.destroy_command_pool(pool, None);
                 }
+                if let Some(swapchain) = self.swapchain.take() {
+                    for view in swapchain.image_views {
+                        device.destroy_image_view(view, None);
+                    }
+                    if let Some(instance) = &self.instance {
+                        ash::khr::swapchain::Device::new(instance, &device).destroy_swapchain(swapchain.swapchain, None);
+                    }
+                }
+                device.destroy_device(None);
             }
         }
+        self.graphics_queue = None;
+        self.physical_device = None;
+        if let Some(instance) = self.instance.take() {
+            unsafe { instance.destroy_instance(None) };
+        }
         
         self.initialized = false;
-        log::info!("Quantum Renderer shutdown complete");
+        if was_live {
+            log::info!("Quantum Renderer shutdown complete");
+        }
     }
 }
 
@@ -357,3 +892,96 @@ impl std::fmt::Display for RendererError {
 }
 
 impl std::error::Error for RendererError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(x: i32, y: i32, z: i32) -> ChunkRenderData {
+        ChunkRenderData { x, y, z, lod_level: 0, vertex_count: 0 }
+    }
+
+    #[test]
+    fn test_chunks_behind_camera_are_culled() {
+        let mut renderer = QuantumRenderer::new().unwrap();
+        renderer.update_camera(Vec3::new(8.0, 72.0, 8.0), Vec3::NEG_Z, 70.0, 16.0 / 9.0);
+
+        renderer.render_chunks(&[
+            chunk(0, 4, -4),  // ahead
+            chunk(3, 4, -6),  // ahead, off to the side
+            chunk(0, 4, 0),   // contains the camera
+            chunk(0, 4, 6),   // behind
+            chunk(-2, 4, 10), // behind
+        ]);
+
+        assert_eq!(renderer.visible_chunks(), &[(0, 4, -4), (3, 4, -6), (0, 4, 0)]);
+        assert_eq!(renderer.culled_chunks(), &[(0, 4, 6), (-2, 4, 10)]);
+
+        // Lists reset every frame, even headless
+        let _ = renderer.begin_frame();
+        assert!(renderer.visible_chunks().is_empty());
+        assert!(renderer.culled_chunks().is_empty());
+    }
+
+    #[test]
+    fn test_render_distance_and_fade_in() {
+        let mut renderer = QuantumRenderer::new().unwrap();
+        renderer.update_camera(Vec3::new(8.0, 72.0, 8.0), Vec3::NEG_Z, 70.0, 16.0 / 9.0);
+        renderer.set_render_distance(4);
+
+        renderer.mark_chunk_meshed((0, 4, -3));
+        renderer.render_chunks(&[chunk(0, 4, -3), chunk(0, 4, -4), chunk(0, 4, -5), chunk(3, 4, -4)]);
+        assert_eq!(renderer.visible_chunks(), &[(0, 4, -3), (0, 4, -4)]);
+        assert_eq!(renderer.culled_chunks(), &[(0, 4, -5), (3, 4, -4)]);
+
+        // The freshly meshed chunk is still fading; the older one is opaque
+        let uniforms = renderer.chunk_uniforms();
+        assert!(uniforms[0].alpha < 1.0);
+        assert_eq!(uniforms[1].alpha, 1.0);
+        assert_eq!(uniforms[1].origin, [0.0, 64.0, -64.0]);
+        for _ in 0..3 {
+            let _ = renderer.begin_frame();
+            assert!(renderer.chunk_alpha((0, 4, -3)) < 1.0);
+        }
+
+        assert_eq!(fade::fade_alpha(0.0), 0.0);
+        assert_eq!(fade::fade_alpha(fade::FADE_IN_SECONDS * 0.5), 0.5);
+        assert_eq!(fade::fade_alpha(fade::FADE_IN_SECONDS + 1.0), 1.0);
+    }
+
+    #[test]
+    fn test_mesher_shares_watchdog_checked_each_frame() {
+        let mut renderer = QuantumRenderer::new().unwrap();
+        assert!(Arc::ptr_eq(renderer.watchdog(), renderer.greedy_mesher().watchdog()));
+
+        // A hang reported by the mesher is consumed by the next frame
+        let err = renderer.greedy_mesher().watchdog().check::<()>("greedy mesh dispatch", Err(vk::Result::TIMEOUT));
+        assert!(err.is_err());
+        assert!(renderer.watchdog().needs_recovery());
+        let _ = renderer.begin_frame();
+        assert!(!renderer.watchdog().needs_recovery());
+        assert_eq!(renderer.watchdog().hang_count(), 1);
+    }
+
+    #[test]
+    fn test_device_loss_marks_recovery_and_shutdown_is_idempotent() {
+        let mut renderer = QuantumRenderer::new().unwrap();
+        renderer.recovery_mut().register(recovery::ResourceId::new(recovery::ResourceKind::Atlas, 1));
+        renderer.initialized = true;
+
+        // Loss at present reaches recovery through the shared watchdog
+        let err = renderer.watchdog().device_lost("present");
+        assert!(matches!(err, crate::renderer::vulkan::VulkanError::DeviceLost));
+        let _ = renderer.begin_frame();
+        assert_eq!(renderer.recovery().loss_count(), 1);
+        assert!(!renderer.watchdog().is_device_lost());
+
+        // Retries don't count as new losses; teardown leaves nothing behind
+        let _ = renderer.begin_frame();
+        assert_eq!(renderer.recovery().loss_count(), 1);
+        renderer.shutdown();
+        renderer.shutdown();
+        assert!(renderer.instance.is_none() && renderer.device.is_none() && renderer.command_pool.is_none());
+        assert!(!renderer.is_initialized());
+    }
+}
@@ -0,0 +1,349 @@
+//! # MSAA Targets
+//!
+//! Multisampled antialiasing as an alternative to TAA. Geometry renders
+//! into multisampled color and depth images; the render pass resolves the
+//! color into the single-sample swapchain image through a resolve
+//! attachment, so no separate `vkCmdResolveImage` is needed. The resolved
+//! image is left in `COLOR_ATTACHMENT_OPTIMAL` for the GUI composite pass,
+//! which transitions it for present.
+//!
+//! Requested sample counts are clamped to the highest count the device
+//! supports for both color and depth framebuffers.
+
+use ash::vk;
+
+use super::RendererError;
+
+/// Depth format of the multisampled depth target
+pub const MSAA_DEPTH_FORMAT: vk::Format = vk::Format::D32_SFLOAT;
+
+/// MSAA sample count
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum SampleCount {
+    /// No multisampling
+    #[default]
+    X1 = 1,
+    X2 = 2,
+    X4 = 4,
+    X8 = 8,
+}
+
+impl SampleCount {
+    /// All counts, lowest first
+    pub const ALL: [SampleCount; 4] = [SampleCount::X1, SampleCount::X2, SampleCount::X4, SampleCount::X8];
+
+    /// Get the number of samples per pixel
+    pub fn samples(self) -> u32 {
+        self as u32
+    }
+
+    /// Convert to the Vulkan flag
+    pub fn to_vk(self) -> vk::SampleCountFlags {
+        match self {
+            SampleCount::X1 => vk::SampleCountFlags::TYPE_1,
+            SampleCount::X2 => vk::SampleCountFlags::TYPE_2,
+            SampleCount::X4 => vk::SampleCountFlags::TYPE_4,
+            SampleCount::X8 => vk::SampleCountFlags::TYPE_8,
+        }
+    }
+
+    /// Highest supported count not above this one (X1 is always supported)
+    pub fn clamp_to(self, supported: vk::SampleCountFlags) -> SampleCount {
+        Self::ALL
+            .into_iter()
+            .filter(|&count| count <= self && supported.contains(count.to_vk()))
+            .max()
+            .unwrap_or(SampleCount::X1)
+    }
+}
+
+/// Sample counts usable for both color and depth framebuffers
+pub fn supported_sample_counts(limits: &vk::PhysicalDeviceLimits) -> vk::SampleCountFlags {
+    limits.framebuffer_color_sample_counts & limits.framebuffer_depth_sample_counts
+}
+
+/// Multisampled color and depth images plus the resolving render pass
+pub struct MsaaTargets {
+    samples: SampleCount,
+    color: (vk::Image, vk::DeviceMemory, vk::ImageView),
+    depth: (vk::Image, vk::DeviceMemory, vk::ImageView),
+    render_pass: vk::RenderPass,
+    /// One per swapchain image (resolve target)
+    framebuffers: Vec<vk::Framebuffer>,
+}
+
+impl MsaaTargets {
+    /// Create targets matching the swapchain, resolving into its images
+    pub fn new(
+        device: &ash::Device,
+        memory_properties: &vk::PhysicalDeviceMemoryProperties,
+        extent: vk::Extent2D,
+        color_format: vk::Format,
+        swapchain_views: &[vk::ImageView],
+        samples: SampleCount,
+    ) -> Result<Self, RendererError> {
+        let mut targets = Self {
+            samples,
+            color: (vk::Image::null(), vk::DeviceMemory::null(), vk::ImageView::null()),
+            depth: (vk::Image::null(), vk::DeviceMemory::null(), vk::ImageView::null()),
+            render_pass: vk::RenderPass::null(),
+            framebuffers: Vec::with_capacity(swapchain_views.len()),
+        };
+        // On failure, whatever was created so far is released
+        if let Err(e) = targets.create(device, memory_properties, extent, color_format, swapchain_views) {
+            targets.destroy(device);
+            return Err(e);
+        }
+        Ok(targets)
+    }
+
+    fn create(
+        &mut self,
+        device: &ash::Device,
+        memory_properties: &vk::PhysicalDeviceMemoryProperties,
+        extent: vk::Extent2D,
+        color_format: vk::Format,
+        swapchain_views: &[vk::ImageView],
+    ) -> Result<(), RendererError> {
+        self.color = Self::create_target(
+            device,
+            memory_properties,
+            extent,
+            color_format,
+            self.samples,
+            vk::ImageUsageFlags::COLOR_ATTACHMENT | vk::ImageUsageFlags::TRANSIENT_ATTACHMENT,
+            vk::ImageAspectFlags::COLOR,
+        )?;
+        self.depth = Self::create_target(
+            device,
+            memory_properties,
+            extent,
+            MSAA_DEPTH_FORMAT,
+            self.samples,
+            vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT | vk::ImageUsageFlags::TRANSIENT_ATTACHMENT,
+            vk::ImageAspectFlags::DEPTH,
+        )?;
+        self.render_pass = Self::create_render_pass(device, color_format, self.samples)?;
+
+        for &resolve_view in swapchain_views {
+            let attachments = [self.color.2, self.depth.2, resolve_view];
+            let framebuffer_info = vk::FramebufferCreateInfo::default()
+                .render_pass(self.render_pass)
+                .attachments(&attachments)
+                .width(extent.width)
+                .height(extent.height)
+                .layers(1);
+            let framebuffer = unsafe {
+                device.create_framebuffer(&framebuffer_info, None)
+                    .map_err(|e| RendererError::VulkanError(format!("Failed to create MSAA framebuffer: {:?}", e)))?
+            };
+            self.framebuffers.push(framebuffer);
+        }
+        Ok(())
+    }
+
+    /// Create one multisampled image with its memory and view
+    fn create_target(
+        device: &ash::Device,
+        memory_properties: &vk::PhysicalDeviceMemoryProperties,
+        extent: vk::Extent2D,
+        format: vk::Format,
+        samples: SampleCount,
+        usage: vk::ImageUsageFlags,
+        aspect: vk::ImageAspectFlags,
+    ) -> Result<(vk::Image, vk::DeviceMemory, vk::ImageView), RendererError> {
+        unsafe {
+            let image_info = vk::ImageCreateInfo::default()
+                .image_type(vk::ImageType::TYPE_2D)
+                .extent(vk::Extent3D { width: extent.width, height: extent.height, depth: 1 })
+                .mip_levels(1)
+                .array_layers(1)
+                .format(format)
+                .tiling(vk::ImageTiling::OPTIMAL)
+                .initial_layout(vk::ImageLayout::UNDEFINED)
+                .usage(usage)
+                .sharing_mode(vk::SharingMode::EXCLUSIVE)
+                .samples(samples.to_vk());
+
+            let image = device.create_image(&image_info, None)
+                .map_err(|e| RendererError::VulkanError(format!("Failed to create MSAA image: {:?}", e)))?;
+
+            let requirements = device.get_image_memory_requirements(image);
+            // Prefer lazily allocated memory so tiled GPUs keep samples on chip
+            let memory_type = find_memory_type(memory_properties, requirements.memory_type_bits, vk::MemoryPropertyFlags::DEVICE_LOCAL | vk::MemoryPropertyFlags::LAZILY_ALLOCATED)
+                .or_else(|| find_memory_type(memory_properties, requirements.memory_type_bits, vk::MemoryPropertyFlags::DEVICE_LOCAL));
+            let Some(memory_type) = memory_type else {
+                device.destroy_image(image, None);
+                return Err(RendererError::VulkanError("No device-local memory for MSAA image".to_string()));
+            };
+
+            let alloc_info = vk::MemoryAllocateInfo::default()
+                .allocation_size(requirements.size)
+                .memory_type_index(memory_type);
+            let memory = match device.allocate_memory(&alloc_info, None) {
+                Ok(memory) => memory,
+                Err(e) => {
+                    device.destroy_image(image, None);
+                    return Err(RendererError::VulkanError(format!("Failed to allocate MSAA memory: {:?}", e)));
+                }
+            };
+
+            let view_info = vk::ImageViewCreateInfo::default()
+                .image(image)
+                .view_type(vk::ImageViewType::TYPE_2D)
+                .format(format)
+                .subresource_range(vk::ImageSubresourceRange {
+                    aspect_mask: aspect,
+                    base_mip_level: 0,
+                    level_count: 1,
+                    base_array_layer: 0,
+                    layer_count: 1,
+                });
+            let view = device.bind_image_memory(image, memory, 0)
+                .and_then(|_| device.create_image_view(&view_info, None));
+            match view {
+                Ok(view) => Ok((image, memory, view)),
+                Err(e) => {
+                    device.destroy_image(image, None);
+                    device.free_memory(memory, None);
+                    Err(RendererError::VulkanError(format!("Failed to set up MSAA image: {:?}", e)))
+                }
+            }
+        }
+    }
+
+    /// Render pass: multisampled color + depth, color resolved into attachment 2
+    fn create_render_pass(device: &ash::Device, color_format: vk::Format, samples: SampleCount) -> Result<vk::RenderPass, RendererError> {
+        let color_attachment = vk::AttachmentDescription::default()
+            .format(color_format)
+            .samples(samples.to_vk())
+            .load_op(vk::AttachmentLoadOp::CLEAR)
+            .store_op(vk::AttachmentStoreOp::DONT_CARE)
+            .stencil_load_op(vk::AttachmentLoadOp::DONT_CARE)
+            .stencil_store_op(vk::AttachmentStoreOp::DONT_CARE)
+            .initial_layout(vk::ImageLayout::UNDEFINED)
+            .final_layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL);
+
+        let depth_attachment = vk::AttachmentDescription::default()
+            .format(MSAA_DEPTH_FORMAT)
+            .samples(samples.to_vk())
+            .load_op(vk::AttachmentLoadOp::CLEAR)
+            .store_op(vk::AttachmentStoreOp::DONT_CARE)
+            .stencil_load_op(vk::AttachmentLoadOp::DONT_CARE)
+            .stencil_store_op(vk::AttachmentStoreOp::DONT_CARE)
+            .initial_layout(vk::ImageLayout::UNDEFINED)
+            .final_layout(vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL);
+
+        // The GUI composites over the resolved image before present
+        let resolve_attachment = vk::AttachmentDescription::default()
+            .format(color_format)
+            .samples(vk::SampleCountFlags::TYPE_1)
+            .load_op(vk::AttachmentLoadOp::DONT_CARE)
+            .store_op(vk::AttachmentStoreOp::STORE)
+            .stencil_load_op(vk::AttachmentLoadOp::DONT_CARE)
+            .stencil_store_op(vk::AttachmentStoreOp::DONT_CARE)
+            .initial_layout(vk::ImageLayout::UNDEFINED)
+            .final_layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL);
+
+        let attachments = [color_attachment, depth_attachment, resolve_attachment];
+
+        let color_ref = vk::AttachmentReference::default()
+            .attachment(0)
+            .layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL);
+        let depth_ref = vk::AttachmentReference::default()
+            .attachment(1)
+            .layout(vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL);
+        let resolve_ref = vk::AttachmentReference::default()
+            .attachment(2)
+            .layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL);
+
+        let subpass = vk::SubpassDescription::default()
+            .pipeline_bind_point(vk::PipelineBindPoint::GRAPHICS)
+            .color_attachments(std::slice::from_ref(&color_ref))
+            .resolve_attachments(std::slice::from_ref(&resolve_ref))
+            .depth_stencil_attachment(&depth_ref);
+
+        let dependency = vk::SubpassDependency::default()
+            .src_subpass(vk::SUBPASS_EXTERNAL)
+            .dst_subpass(0)
+            .src_stage_mask(vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT | vk::PipelineStageFlags::EARLY_FRAGMENT_TESTS)
+            .src_access_mask(vk::AccessFlags::empty())
+            .dst_stage_mask(vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT | vk::PipelineStageFlags::EARLY_FRAGMENT_TESTS)
+            .dst_access_mask(vk::AccessFlags::COLOR_ATTACHMENT_WRITE | vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE);
+
+        let render_pass_info = vk::RenderPassCreateInfo::default()
+            .attachments(&attachments)
+            .subpasses(std::slice::from_ref(&subpass))
+            .dependencies(std::slice::from_ref(&dependency));
+
+        unsafe {
+            device.create_render_pass(&render_pass_info, None)
+                .map_err(|e| RendererError::VulkanError(format!("Failed to create MSAA render pass: {:?}", e)))
+        }
+    }
+
+    /// Destroy all targets (the GPU must be done with them)
+    pub fn destroy(&mut self, device: &ash::Device) {
+        unsafe {
+            for framebuffer in self.framebuffers.drain(..) {
+                device.destroy_framebuffer(framebuffer, None);
+            }
+            if self.render_pass != vk::RenderPass::null() {
+                device.destroy_render_pass(self.render_pass, None);
+                self.render_pass = vk::RenderPass::null();
+            }
+            for (image, memory, view) in [&mut self.color, &mut self.depth] {
+                if *view != vk::ImageView::null() { device.destroy_image_view(*view, None); }
+                if *image != vk::Image::null() { device.destroy_image(*image, None); }
+                if *memory != vk::DeviceMemory::null() { device.free_memory(*memory, None); }
+                *image = vk::Image::null();
+                *memory = vk::DeviceMemory::null();
+                *view = vk::ImageView::null();
+            }
+        }
+    }
+
+    /// Get the sample count
+    pub fn samples(&self) -> SampleCount {
+        self.samples
+    }
+
+    /// Get the render pass geometry passes begin
+    pub fn render_pass(&self) -> vk::RenderPass {
+        self.render_pass
+    }
+
+    /// Get the framebuffer resolving into a swapchain image
+    pub fn framebuffer(&self, image_index: usize) -> vk::Framebuffer {
+        self.framebuffers[image_index]
+    }
+}
+
+/// Index of a memory type allowed by `type_bits` with all `flags`
+fn find_memory_type(properties: &vk::PhysicalDeviceMemoryProperties, type_bits: u32, flags: vk::MemoryPropertyFlags) -> Option<u32> {
+    (0..properties.memory_type_count).find(|&i| {
+        type_bits & (1 << i) != 0 && properties.memory_types[i as usize].property_flags.contains(flags)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unsupported_count_clamps_down() {
+        let limits = vk::PhysicalDeviceLimits {
+            framebuffer_color_sample_counts: vk::SampleCountFlags::TYPE_1 | vk::SampleCountFlags::TYPE_2 | vk::SampleCountFlags::TYPE_4 | vk::SampleCountFlags::TYPE_8,
+            // Depth only goes up to 4x, which caps the usable count
+            framebuffer_depth_sample_counts: vk::SampleCountFlags::TYPE_1 | vk::SampleCountFlags::TYPE_2 | vk::SampleCountFlags::TYPE_4,
+            ..Default::default()
+        };
+        let supported = supported_sample_counts(&limits);
+
+        assert_eq!(SampleCount::X8.clamp_to(supported), SampleCount::X4);
+        assert_eq!(SampleCount::X2.clamp_to(supported), SampleCount::X2);
+        assert_eq!(SampleCount::X1.clamp_to(supported), SampleCount::X1);
+        assert_eq!(SampleCount::X4.clamp_to(vk::SampleCountFlags::TYPE_1 | vk::SampleCountFlags::TYPE_2), SampleCount::X2);
+        assert_eq!(SampleCount::X8.clamp_to(vk::SampleCountFlags::empty()), SampleCount::X1);
+    }
+}
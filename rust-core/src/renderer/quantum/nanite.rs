@@ -10,6 +10,9 @@ use std::sync::Arc;
 use std::collections::HashMap;
 use glam::{Vec3, Vec4, IVec3, Mat4};
 
+use crate::LibsError;
+use super::RendererError;
+
 /// LOD Level definitions
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LodLevel {
@@ -27,6 +30,16 @@ impl LodLevel {
         else { LodLevel::Imposter }
     }
     
+    /// Get the edge length (blocks) of the block groups meshed as one cell
+    pub fn block_group(&self) -> usize {
+        match self {
+            LodLevel::HighPoly => 1,
+            LodLevel::MediumPoly => 2,
+            LodLevel::LowPoly => 4,
+            LodLevel::Imposter => 8,
+        }
+    }
+    
     pub fn reduction_factor(&self) -> f32 {
         match self {
             LodLevel::HighPoly => 1.0,
@@ -118,6 +131,8 @@ pub struct NaniteStats {
 pub struct NaniteManager {
     device: Arc<ash::Device>,
     chunks: HashMap<IVec3, ChunkLod>,
+    /// LOD each submitted chunk was last drawn at
+    chunk_lods: HashMap<IVec3, LodLevel>,
     sdf_chunks: HashMap<IVec3, SdfChunk>,
     camera_pos: Vec3,
     camera_dir: Vec3,
@@ -141,6 +156,7 @@ impl NaniteManager {
         Self {
             device,
             chunks: HashMap::new(),
+            chunk_lods: HashMap::new(),
             sdf_chunks: HashMap::with_capacity(1024),
             camera_pos: Vec3::ZERO,
             camera_dir: Vec3::NEG_Z,
@@ -156,7 +172,7 @@ impl NaniteManager {
     }
     
     /// Initialize GPU resources for ray marching
-    pub fn initialize(&mut self, queue_family_index: u32) -> Result<(), String> {
+    pub fn initialize(&mut self, queue_family_index: u32) -> Result<(), LibsError> {
         unsafe {
             // Create SDF storage buffer (enough for 1024 chunks * 512 floats = 2MB)
             let buffer_size = 1024 * 512 * 4;
@@ -167,7 +183,7 @@ impl NaniteManager {
                 .sharing_mode(vk::SharingMode::EXCLUSIVE);
             
             self.sdf_buffer = self.device.create_buffer(&buffer_info, None)
-                .map_err(|e| format!("Failed to create SDF buffer: {:?}", e))?;
+                .map_err(|e| RendererError::VulkanError(format!("Failed to create SDF buffer: {:?}", e)))?;
             
             let mem_requirements = self.device.get_buffer_memory_requirements(self.sdf_buffer);
             
@@ -176,10 +192,10 @@ impl NaniteManager {
                 .memory_type_index(0);
             
             self.sdf_memory = self.device.allocate_memory(&alloc_info, None)
-                .map_err(|e| format!("Failed to allocate SDF memory: {:?}", e))?;
+                .map_err(|e| RendererError::VulkanError(format!("Failed to allocate SDF memory: {:?}", e)))?;
             
             self.device.bind_buffer_memory(self.sdf_buffer, self.sdf_memory, 0)
-                .map_err(|e| format!("Failed to bind SDF buffer: {:?}", e))?;
+                .map_err(|e| RendererError::VulkanError(format!("Failed to bind SDF buffer: {:?}", e)))?;
         }
         
         self.initialized = true;
@@ -202,6 +218,7 @@ impl NaniteManager {
         
         let distance = self.camera_pos.distance(chunk_center);
         let lod = LodLevel::from_distance(distance);
+        self.chunk_lods.insert(chunk_pos, lod);
         
         match lod {
             LodLevel::HighPoly => self.stats.chunks_high += 1,
@@ -220,6 +237,18 @@ impl NaniteManager {
         self.stats.reduced_vertices += reduced_vertices;
     }
     
+    /// Get the LOD a chunk was last submitted at
+    pub fn chunk_lod(&self, position: IVec3) -> Option<LodLevel> {
+        self.chunk_lods.get(&position).copied()
+    }
+    
+    /// Get the LODs of a chunk's +X, -X, +Z and -Z neighbors (HighPoly
+    /// where a neighbor hasn't been submitted), for skirt meshing
+    pub fn neighbor_lods(&self, position: IVec3) -> [LodLevel; 4] {
+        [IVec3::X, IVec3::NEG_X, IVec3::Z, IVec3::NEG_Z]
+            .map(|offset| self.chunk_lod(position + offset).unwrap_or(LodLevel::HighPoly))
+    }
+    
     /// Generate SDF from chunk data with normals and AO
     pub fn generate_sdf(&mut self, position: IVec3, chunk_data: &[u32; 4096]) -> SdfChunk {
         let resolution = 8;
@@ -486,6 +515,31 @@ impl NaniteManager {
         self.stats.ray_march_steps += (width * height) as u64;
     }
     
+    /// Ray march far terrain and fog it toward the sky
+    ///
+    /// Misses return the fog color, so the terrain fades into the sky
+    /// rather than ending at the march distance.
+    pub fn ray_march_fogged(&self, origin: Vec3, direction: Vec3, fog: &super::fog::FogParams) -> Vec3 {
+        let hit = self.ray_march(origin, direction);
+        if !hit.hit {
+            return fog.color;
+        }
+        let color = Vec3::new(
+            ((hit.color >> 16) & 0xFF) as f32,
+            ((hit.color >> 8) & 0xFF) as f32,
+            (hit.color & 0xFF) as f32,
+        ) / 255.0;
+        fog.apply(color * hit.ao, origin, hit.position)
+    }
+    
+    /// Shadow from the sky's dominant light (0 when it has set)
+    pub fn sky_shadow(&self, origin: Vec3, sky: &super::sky::Sky) -> f32 {
+        if sky.light_color() == Vec3::ZERO {
+            return 0.0;
+        }
+        self.ray_march_shadow(origin, sky.to_light())
+    }
+    
     /// Calculate soft shadows using ray marching
     pub fn ray_march_shadow(&self, origin: Vec3, light_dir: Vec3) -> f32 {
         let settings = &self.ray_march_settings;
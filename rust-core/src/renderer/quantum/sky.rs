@@ -0,0 +1,236 @@
+//! # Sky and Time of Day
+//!
+//! Turns the world's time of day into the lighting inputs of a frame: sun
+//! and moon directions, the color of the dominant directional light, the
+//! ambient sky color, and fog color and density. Ticks follow the vanilla
+//! clock: 0 is sunrise, a quarter day is noon, half a day is sunset. The
+//! sun orbits from east (+X) to west (-X), tilted slightly toward -Z so it
+//! never passes exactly overhead. Colors blend smoothly through dawn and
+//! dusk as the sun crosses the horizon.
+
+use glam::Vec3;
+
+/// Ticks in a vanilla day
+pub const DEFAULT_DAY_LENGTH: u32 = 24_000;
+
+/// Sun orbit tilt toward -Z (radians)
+const ORBIT_TILT: f32 = 0.35;
+
+/// Sun height (sine of elevation) over which day fades into night
+const TWILIGHT_BAND: f32 = 0.2;
+
+/// Sunlight at noon
+const SUN_COLOR: Vec3 = Vec3::new(1.0, 0.96, 0.88);
+/// Sunlight near the horizon
+const SUNSET_COLOR: Vec3 = Vec3::new(1.0, 0.5, 0.25);
+/// Moonlight
+const MOON_COLOR: Vec3 = Vec3::new(0.18, 0.22, 0.35);
+/// Ambient sky at noon
+const DAY_AMBIENT: Vec3 = Vec3::new(0.45, 0.6, 0.85);
+/// Ambient sky at midnight
+const NIGHT_AMBIENT: Vec3 = Vec3::new(0.02, 0.025, 0.06);
+
+/// Fog density at noon (per block, exponential)
+const DAY_FOG_DENSITY: f32 = 0.004;
+/// Fog density at midnight
+const NIGHT_FOG_DENSITY: f32 = 0.01;
+
+/// Time of day and the lighting derived from it
+#[derive(Debug, Clone)]
+pub struct Sky {
+    /// Ticks into the current day
+    time: u32,
+    /// Ticks per full day
+    day_length: u32,
+    /// Direction sunlight travels (toward the ground by day)
+    sun_direction: Vec3,
+    /// Direction moonlight travels
+    moon_direction: Vec3,
+    /// Sunlight color and intensity
+    sun_color: Vec3,
+    /// Moonlight color and intensity
+    moon_color: Vec3,
+    /// Ambient sky light
+    ambient: Vec3,
+    /// Fog color
+    fog_color: Vec3,
+    /// Exponential fog density
+    fog_density: f32,
+}
+
+impl Sky {
+    /// Create a sky at sunrise
+    pub fn new() -> Self {
+        let mut sky = Self {
+            time: 0,
+            day_length: DEFAULT_DAY_LENGTH,
+            sun_direction: Vec3::NEG_Y,
+            moon_direction: Vec3::Y,
+            sun_color: Vec3::ZERO,
+            moon_color: Vec3::ZERO,
+            ambient: Vec3::ZERO,
+            fog_color: Vec3::ZERO,
+            fog_density: 0.0,
+        };
+        sky.update();
+        sky
+    }
+
+    /// Set the time of day (wraps at the day length)
+    pub fn set_time(&mut self, ticks_of_day: u32) {
+        self.time = ticks_of_day % self.day_length;
+        self.update();
+    }
+
+    /// Get ticks into the current day
+    pub fn time(&self) -> u32 {
+        self.time
+    }
+
+    /// Set ticks per day (at least 1)
+    pub fn set_day_length(&mut self, ticks: u32) {
+        self.day_length = ticks.max(1);
+        self.time %= self.day_length;
+        self.update();
+    }
+
+    /// Get ticks per day
+    pub fn day_length(&self) -> u32 {
+        self.day_length
+    }
+
+    /// Get the direction sunlight travels
+    pub fn sun_direction(&self) -> Vec3 {
+        self.sun_direction
+    }
+
+    /// Get the direction moonlight travels
+    pub fn moon_direction(&self) -> Vec3 {
+        self.moon_direction
+    }
+
+    /// Get the sunlight color (black below the horizon)
+    pub fn sun_color(&self) -> Vec3 {
+        self.sun_color
+    }
+
+    /// Get the moonlight color (black by day)
+    pub fn moon_color(&self) -> Vec3 {
+        self.moon_color
+    }
+
+    /// Get the dominant directional light: sun by day, moon by night
+    pub fn light_direction(&self) -> Vec3 {
+        if self.is_day() { self.sun_direction } else { self.moon_direction }
+    }
+
+    /// Get the dominant directional light's color
+    pub fn light_color(&self) -> Vec3 {
+        if self.is_day() { self.sun_color } else { self.moon_color }
+    }
+
+    /// Get the direction toward the dominant light (for shadow rays)
+    pub fn to_light(&self) -> Vec3 {
+        -self.light_direction()
+    }
+
+    /// Get the ambient sky color
+    pub fn ambient_color(&self) -> Vec3 {
+        self.ambient
+    }
+
+    /// Get the fog color
+    pub fn fog_color(&self) -> Vec3 {
+        self.fog_color
+    }
+
+    /// Get the exponential fog density (per block)
+    pub fn fog_density(&self) -> f32 {
+        self.fog_density
+    }
+
+    /// Check if the sun is above the horizon
+    pub fn is_day(&self) -> bool {
+        self.sun_direction.y < 0.0
+    }
+
+    /// Get how much of the day's light is present (0 at night, 1 by day)
+    pub fn daylight(&self) -> f32 {
+        smoothstep(-TWILIGHT_BAND, TWILIGHT_BAND, -self.sun_direction.y)
+    }
+
+    /// Recompute directions and colors from the time
+    fn update(&mut self) {
+        let angle = self.time as f32 / self.day_length as f32 * std::f32::consts::TAU;
+        let to_sun = Vec3::new(angle.cos(), angle.sin() * ORBIT_TILT.cos(), -angle.sin() * ORBIT_TILT.sin());
+        self.sun_direction = -to_sun;
+        self.moon_direction = to_sun;
+
+        let height = to_sun.y;
+        let daylight = self.daylight();
+        // 1 at the horizon, 0 once the sun is a twilight band away from it
+        let twilight = 1.0 - smoothstep(0.0, TWILIGHT_BAND, height.abs());
+
+        let sun_tint = SUNSET_COLOR.lerp(SUN_COLOR, smoothstep(0.0, 2.0 * TWILIGHT_BAND, height));
+        self.sun_color = sun_tint * smoothstep(-0.05, 0.05, height);
+        self.moon_color = MOON_COLOR * smoothstep(-0.05, 0.05, -height);
+
+        self.ambient = NIGHT_AMBIENT.lerp(DAY_AMBIENT, daylight) + SUNSET_COLOR * twilight * daylight.min(1.0 - daylight) * 0.6;
+        self.fog_color = self.ambient.lerp(SUNSET_COLOR * 0.8, twilight * 0.4);
+        self.fog_density = NIGHT_FOG_DENSITY + (DAY_FOG_DENSITY - NIGHT_FOG_DENSITY) * daylight;
+    }
+}
+
+impl Default for Sky {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Hermite step between `edge0` and `edge1`
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sun_points_down_at_noon_and_along_horizon_at_dawn() {
+        let mut sky = Sky::new();
+
+        sky.set_time(DEFAULT_DAY_LENGTH / 4);
+        assert!(sky.sun_direction().y < -0.9);
+        assert!(sky.is_day());
+        assert!((sky.to_light() + sky.sun_direction()).length() < 1e-6);
+        assert!(sky.ambient_color().z > 0.8);
+
+        // Dawn: the sun rises in the east, light crossing the horizon toward -X
+        sky.set_time(0);
+        assert!(sky.sun_direction().y.abs() < 1e-3);
+        assert!(sky.sun_direction().x < -0.99);
+        let dawn_ambient = sky.ambient_color();
+
+        // Light grows smoothly through the morning
+        let mut last = 0.0;
+        for t in (0..=DEFAULT_DAY_LENGTH / 4).step_by(250) {
+            sky.set_time(t);
+            assert!(sky.daylight() >= last);
+            last = sky.daylight();
+        }
+
+        // Midnight: the moon is the light, from above
+        sky.set_time(DEFAULT_DAY_LENGTH * 3 / 4);
+        assert!(!sky.is_day());
+        assert!(sky.light_direction().y < -0.9);
+        assert_eq!(sky.light_color(), sky.moon_color());
+        assert!(sky.ambient_color().length() < dawn_ambient.length());
+        assert!(sky.fog_density() > DAY_FOG_DENSITY);
+
+        // Wraps past the end of the day
+        sky.set_time(DEFAULT_DAY_LENGTH + 6000);
+        assert_eq!(sky.time(), 6000);
+    }
+}
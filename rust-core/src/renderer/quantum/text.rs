@@ -0,0 +1,208 @@
+//! # Text Rendering
+//!
+//! Lays strings out as one textured quad per glyph from a font atlas and
+//! submits them through the `GuiCompositor`, which is how the F3 debug
+//! overlay gets drawn. Atlases are either plain bitmaps (e.g. the vanilla
+//! 16x16 ASCII grid) or multi-channel signed distance fields; MSDF glyphs
+//! carry their distance range so the GUI shader resolves a one-pixel edge
+//! at any scale instead of blurring or aliasing.
+
+use std::collections::HashMap;
+
+use super::compositor::{GuiCompositor, GuiElement, GuiLayer};
+
+/// Placement of one glyph in the atlas, in atlas pixels at scale 1
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GlyphMetrics {
+    /// Pen advance to the next glyph
+    pub advance: f32,
+    /// Offset from the pen to the quad's left edge
+    pub bearing_x: f32,
+    /// Offset from the line top to the quad's top edge
+    pub bearing_y: f32,
+    pub width: f32,
+    pub height: f32,
+    /// Atlas rect (u0, v0, u1, v1)
+    pub uv: [f32; 4],
+}
+
+/// Glyph table and texture of a font
+#[derive(Debug, Clone)]
+pub struct FontAtlas {
+    /// GUI texture handle of the atlas
+    pub texture_id: u64,
+    /// Distance between baselines
+    pub line_height: f32,
+    /// MSDF distance range in atlas pixels (0 = bitmap font)
+    pub px_range: f32,
+    /// Glyph drawn for characters the atlas lacks (skipped if `None`)
+    pub fallback: Option<char>,
+    glyphs: HashMap<char, GlyphMetrics>,
+}
+
+impl FontAtlas {
+    /// Create an empty atlas
+    pub fn new(texture_id: u64, line_height: f32, px_range: f32) -> Self {
+        Self { texture_id, line_height, px_range, fallback: Some('?'), glyphs: HashMap::new() }
+    }
+
+    /// Monospace bitmap atlas laid out as a grid of `columns` cells per row,
+    /// character `c` in cell `c` (the vanilla `ascii.png` layout)
+    pub fn grid(texture_id: u64, columns: u32, rows: u32, cell_width: f32, cell_height: f32) -> Self {
+        let mut atlas = Self::new(texture_id, cell_height, 0.0);
+        let (du, dv) = (1.0 / columns as f32, 1.0 / rows as f32);
+        for code in 0..columns * rows {
+            let Some(c) = char::from_u32(code) else {
+                continue;
+            };
+            let (u, v) = ((code % columns) as f32 * du, (code / columns) as f32 * dv);
+            atlas.insert(c, GlyphMetrics {
+                advance: cell_width,
+                bearing_x: 0.0,
+                bearing_y: 0.0,
+                width: cell_width,
+                height: cell_height,
+                uv: [u, v, u + du, v + dv],
+            });
+        }
+        atlas
+    }
+
+    /// Add or replace a glyph
+    pub fn insert(&mut self, c: char, metrics: GlyphMetrics) {
+        self.glyphs.insert(c, metrics);
+    }
+
+    /// Get a glyph, falling back to the fallback glyph
+    pub fn glyph(&self, c: char) -> Option<&GlyphMetrics> {
+        self.glyphs.get(&c).or_else(|| self.glyphs.get(&self.fallback?))
+    }
+
+    /// Check if this is a distance field atlas
+    pub fn is_msdf(&self) -> bool {
+        self.px_range > 0.0
+    }
+}
+
+/// Draws text through the GUI compositor
+pub struct TextRenderer {
+    atlas: FontAtlas,
+    /// Layer glyph quads go on
+    pub layer: GuiLayer,
+    /// Z order of glyph quads
+    pub z_order: i32,
+}
+
+impl TextRenderer {
+    /// Create a renderer for a font, drawing on the debug layer
+    pub fn new(atlas: FontAtlas) -> Self {
+        Self { atlas, layer: GuiLayer::Debug, z_order: 0 }
+    }
+
+    /// Get the font
+    pub fn atlas(&self) -> &FontAtlas {
+        &self.atlas
+    }
+
+    /// Glyph quads for `text` with its top-left corner at (x, y) pixels
+    ///
+    /// `\n` starts a new line; whitespace advances without a quad.
+    pub fn layout(&self, x: f32, y: f32, text: &str, scale: f32, color: [f32; 4]) -> Vec<GuiElement> {
+        let sdf_range = self.atlas.px_range * scale;
+        let mut quads = Vec::with_capacity(text.len());
+        let (mut pen_x, mut pen_y) = (x, y);
+
+        for c in text.chars() {
+            if c == '\n' {
+                pen_x = x;
+                pen_y += self.atlas.line_height * scale;
+                continue;
+            }
+            let Some(glyph) = self.atlas.glyph(c) else {
+                continue;
+            };
+            if !c.is_whitespace() && glyph.width > 0.0 && glyph.height > 0.0 {
+                let mut quad = GuiElement::new(
+                    self.layer,
+                    pen_x + glyph.bearing_x * scale,
+                    pen_y + glyph.bearing_y * scale,
+                    glyph.width * scale,
+                    glyph.height * scale,
+                )
+                .with_texture(self.atlas.texture_id, glyph.uv)
+                .with_color(color)
+                .with_sdf_range(sdf_range);
+                quad.z_order = self.z_order;
+                quads.push(quad);
+            }
+            pen_x += glyph.advance * scale;
+        }
+        quads
+    }
+
+    /// Size in pixels of the text's bounding box (widest line x line count)
+    pub fn measure(&self, text: &str, scale: f32) -> (f32, f32) {
+        let lines = text.split('\n');
+        let mut count = 0;
+        let width = lines
+            .map(|line| {
+                count += 1;
+                line.chars().filter_map(|c| self.atlas.glyph(c)).map(|g| g.advance).sum::<f32>()
+            })
+            .fold(0.0, f32::max);
+        (width * scale, count as f32 * self.atlas.line_height * scale)
+    }
+
+    /// Submit `text` to the compositor; returns the number of glyph quads
+    pub fn draw_text(
+        &self,
+        compositor: &mut GuiCompositor,
+        x: f32,
+        y: f32,
+        text: &str,
+        scale: f32,
+        color: [f32; 4],
+    ) -> usize {
+        let quads = self.layout(x, y, text, scale, color);
+        let count = quads.len();
+        for quad in quads {
+            compositor.add_element(quad);
+        }
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_multi_line_layout() {
+        // 16x16 grid of 8x8 cells, like ascii.png
+        let renderer = TextRenderer::new(FontAtlas::grid(7, 16, 16, 8.0, 8.0));
+        let white = [1.0; 4];
+        let quads = renderer.layout(4.0, 10.0, "FPS: 60\nChunks: 3", 2.0, white);
+
+        // Spaces take room but no quad
+        assert_eq!(quads.len(), "FPS:60Chunks:3".len());
+        let positions: Vec<(f32, f32)> = quads.iter().map(|q| (q.x, q.y)).collect();
+        assert_eq!(positions[0], (4.0, 10.0));
+        assert_eq!(positions[3], (4.0 + 3.0 * 16.0, 10.0));
+        assert_eq!(positions[4], (4.0 + 5.0 * 16.0, 10.0));
+        assert_eq!(positions[6], (4.0, 26.0));
+        assert!(quads.iter().all(|q| q.width == 16.0 && q.texture_id == 7 && q.sdf_range == 0.0));
+        // 'F' (0x46) sits in row 4, column 6
+        assert_eq!(quads[0].uv, [6.0 / 16.0, 4.0 / 16.0, 7.0 / 16.0, 5.0 / 16.0]);
+        assert_eq!(renderer.measure("FPS: 60\nChunks: 3", 2.0), (9.0 * 16.0, 32.0));
+
+        // MSDF glyphs carry their range scaled to screen pixels; unknown
+        // characters fall back to '?'
+        let mut atlas = FontAtlas::new(1, 40.0, 4.0);
+        let glyph = GlyphMetrics { advance: 20.0, bearing_x: 1.0, bearing_y: 6.0, width: 18.0, height: 30.0, uv: [0.0, 0.0, 0.5, 1.0] };
+        atlas.insert('?', glyph);
+        let quads = TextRenderer::new(atlas).layout(0.0, 0.0, "é?", 0.5, white);
+        assert_eq!(quads.len(), 2);
+        assert_eq!((quads[1].x, quads[1].y, quads[1].height), (10.5, 3.0, 15.0));
+        assert_eq!(quads[1].sdf_range, 2.0);
+    }
+}
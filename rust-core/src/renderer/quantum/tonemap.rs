@@ -0,0 +1,270 @@
+//! # Tonemapping
+//!
+//! Maps the linear HDR scene to what the swapchain expects. On an SDR
+//! swapchain the scene is compressed into 0..1 by the selected operator
+//! (ACES, Reinhard or Uncharted2) and the sRGB format applies the gamma. On HDR swapchains the scene keeps its
+//! range: paper white is placed at a chosen brightness, highlights roll off
+//! towards the display's peak, and the result is either PQ encoded in
+//! Rec.2020 (HDR10) or left linear in scRGB units (1.0 = 80 nits).
+//!
+//! The post shader branches on `TonemapUniform::params[3]` for the output
+//! and `TonemapUniform::operator[0]` for the SDR curve; the functions here
+//! are the host reference of the same math.
+
+use ash::vk;
+use glam::{Mat3, Vec3};
+
+/// Nits of 1.0 in scRGB
+pub const SCRGB_NITS: f32 = 80.0;
+
+/// What the swapchain's color space expects
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DisplayOutput {
+    /// sRGB, 0..1
+    #[default]
+    Sdr,
+    /// Rec.2020 primaries, SMPTE ST 2084 (PQ) encoded
+    Hdr10,
+    /// Rec.709 primaries, linear, 1.0 = 80 nits, may exceed 1
+    ScRgb,
+}
+
+impl DisplayOutput {
+    /// Output for a swapchain color space
+    pub fn from_color_space(color_space: vk::ColorSpaceKHR) -> Self {
+        match color_space {
+            vk::ColorSpaceKHR::HDR10_ST2084_EXT => DisplayOutput::Hdr10,
+            vk::ColorSpaceKHR::EXTENDED_SRGB_LINEAR_EXT => DisplayOutput::ScRgb,
+            _ => DisplayOutput::Sdr,
+        }
+    }
+
+    /// Check if the output keeps highlights above paper white
+    pub fn is_hdr(self) -> bool {
+        self != DisplayOutput::Sdr
+    }
+
+    /// Shader branch index
+    pub fn shader_mode(self) -> u32 {
+        match self {
+            DisplayOutput::Sdr => 0,
+            DisplayOutput::Hdr10 => 1,
+            DisplayOutput::ScRgb => 2,
+        }
+    }
+}
+
+/// SDR tonemapping curve
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TonemapOperator {
+    /// Narkowicz's ACES filmic fit
+    #[default]
+    Aces,
+    /// x / (1 + x)
+    Reinhard,
+    /// Hable's filmic curve from Uncharted 2
+    Uncharted2,
+}
+
+impl TonemapOperator {
+    /// Every operator
+    pub const ALL: [TonemapOperator; 3] = [TonemapOperator::Aces, TonemapOperator::Reinhard, TonemapOperator::Uncharted2];
+
+    /// Map an exposed linear color to 0..1
+    pub fn apply(self, color: Vec3) -> Vec3 {
+        match self {
+            TonemapOperator::Aces => aces_filmic(color),
+            TonemapOperator::Reinhard => reinhard(color),
+            TonemapOperator::Uncharted2 => uncharted2(color),
+        }
+    }
+
+    /// Shader branch index
+    pub fn shader_index(self) -> u32 {
+        match self {
+            TonemapOperator::Aces => 0,
+            TonemapOperator::Reinhard => 1,
+            TonemapOperator::Uncharted2 => 2,
+        }
+    }
+}
+
+/// Tonemapping settings
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TonemapParams {
+    /// Swapchain output
+    pub output: DisplayOutput,
+    /// Curve used on SDR outputs
+    pub operator: TonemapOperator,
+    /// Scene exposure multiplier
+    pub exposure: f32,
+    /// Brightness of scene value 1.0 on HDR outputs (nits)
+    pub paper_white_nits: f32,
+    /// Display peak brightness (nits)
+    pub peak_nits: f32,
+}
+
+/// Tonemapping settings packed for shaders (std140)
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct TonemapUniform {
+    /// Exposure, paper white, peak, output mode (0 SDR, 1 HDR10, 2 scRGB)
+    pub params: [f32; 4],
+    /// Operator (0 ACES, 1 Reinhard, 2 Uncharted2), unused x3
+    pub operator: [f32; 4],
+}
+
+impl Default for TonemapParams {
+    fn default() -> Self {
+        Self {
+            output: DisplayOutput::Sdr,
+            operator: TonemapOperator::Aces,
+            exposure: 1.0,
+            paper_white_nits: 200.0,
+            peak_nits: 1000.0,
+        }
+    }
+}
+
+impl TonemapParams {
+    /// Settings for a swapchain color space
+    pub fn for_color_space(color_space: vk::ColorSpaceKHR) -> Self {
+        Self { output: DisplayOutput::from_color_space(color_space), ..Self::default() }
+    }
+
+    /// Pack for the post shader
+    pub fn uniform(&self) -> TonemapUniform {
+        TonemapUniform {
+            params: [self.exposure, self.paper_white_nits, self.peak_nits, self.output.shader_mode() as f32],
+            operator: [self.operator.shader_index() as f32, 0.0, 0.0, 0.0],
+        }
+    }
+
+    /// Set the exposure in stops (0 EV = multiplier 1)
+    pub fn set_exposure_ev(&mut self, ev: f32) {
+        self.exposure = ev.exp2();
+    }
+
+    /// Get the exposure in stops
+    pub fn exposure_ev(&self) -> f32 {
+        self.exposure.max(f32::MIN_POSITIVE).log2()
+    }
+
+    /// Map a linear Rec.709 scene color to the swapchain's encoding
+    pub fn apply(&self, color: Vec3) -> Vec3 {
+        let color = color.max(Vec3::ZERO) * self.exposure;
+        match self.output {
+            DisplayOutput::Sdr => self.operator.apply(color),
+            DisplayOutput::Hdr10 => {
+                let nits = rolloff(color * self.paper_white_nits, self.peak_nits);
+                pq_encode(rec709_to_rec2020(nits / self.paper_white_nits) * self.paper_white_nits)
+            }
+            DisplayOutput::ScRgb => rolloff(color * self.paper_white_nits, self.peak_nits) / SCRGB_NITS,
+        }
+    }
+}
+
+/// Filmic curve (Narkowicz's ACES fit), 0..1
+pub fn aces_filmic(x: Vec3) -> Vec3 {
+    let (a, b, c, d, e) = (2.51, 0.03, 2.43, 0.59, 0.14);
+    ((x * (a * x + b)) / (x * (c * x + d) + e)).clamp(Vec3::ZERO, Vec3::ONE)
+}
+
+/// Reinhard curve, 0..1
+pub fn reinhard(x: Vec3) -> Vec3 {
+    x / (Vec3::ONE + x)
+}
+
+/// Hable's Uncharted 2 curve with its usual 2x exposure bias and white at 11.2, 0..1
+pub fn uncharted2(x: Vec3) -> Vec3 {
+    fn partial(x: Vec3) -> Vec3 {
+        let (a, b, c, d, e, f) = (0.15, 0.50, 0.10, 0.20, 0.02, 0.30);
+        ((x * (a * x + c * b) + d * e) / (x * (a * x + b) + d * f)) - e / f
+    }
+    let white = partial(Vec3::splat(11.2));
+    (partial(x * 2.0) / white).clamp(Vec3::ZERO, Vec3::ONE)
+}
+
+/// Compress nits above half the peak so nothing exceeds `peak`
+pub fn rolloff(nits: Vec3, peak: f32) -> Vec3 {
+    let knee = peak * 0.5;
+    nits.map(|n| {
+        if n <= knee {
+            n
+        } else {
+            // Asymptotic towards the peak, continuous slope at the knee
+            let over = n - knee;
+            knee + (peak - knee) * (over / (over + (peak - knee)))
+        }
+    })
+}
+
+/// Convert linear Rec.709 primaries to Rec.2020
+pub fn rec709_to_rec2020(color: Vec3) -> Vec3 {
+    let m = Mat3::from_cols(
+        Vec3::new(0.6274, 0.0691, 0.0164),
+        Vec3::new(0.3293, 0.9195, 0.0880),
+        Vec3::new(0.0433, 0.0114, 0.8956),
+    );
+    m * color
+}
+
+/// SMPTE ST 2084 (PQ) encode absolute nits to 0..1
+pub fn pq_encode(nits: Vec3) -> Vec3 {
+    let (m1, m2) = (0.159_301_76, 78.843_75);
+    let (c1, c2, c3) = (0.835_937_5, 18.851_562, 18.6875);
+    nits.map(|n| {
+        let y = (n / 10_000.0).clamp(0.0, 1.0).powf(m1);
+        ((c1 + c2 * y) / (1.0 + c3 * y)).powf(m2)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_branches_on_color_space() {
+        let sdr = TonemapParams::for_color_space(vk::ColorSpaceKHR::SRGB_NONLINEAR);
+        let hdr10 = TonemapParams::for_color_space(vk::ColorSpaceKHR::HDR10_ST2084_EXT);
+        let scrgb = TonemapParams::for_color_space(vk::ColorSpaceKHR::EXTENDED_SRGB_LINEAR_EXT);
+        assert!(!sdr.output.is_hdr() && hdr10.output.is_hdr() && scrgb.output.is_hdr());
+
+        // A bright highlight clips on SDR but stays above paper white on HDR
+        let highlight = Vec3::splat(4.0);
+        assert!(sdr.apply(highlight).x <= 1.0);
+        assert!((scrgb.apply(Vec3::ONE).x - 200.0 / SCRGB_NITS).abs() < 1e-4);
+        assert!(scrgb.apply(highlight).x > scrgb.apply(Vec3::ONE).x);
+        assert!(scrgb.apply(Vec3::splat(1000.0)).x * SCRGB_NITS <= 1000.0);
+
+        // PQ: 100 nits is about 0.508, 10000 nits is 1.0
+        assert!((pq_encode(Vec3::splat(100.0)).x - 0.508).abs() < 1e-3);
+        assert!((pq_encode(Vec3::splat(10_000.0)).x - 1.0).abs() < 1e-5);
+        assert!(hdr10.apply(highlight).x > hdr10.apply(Vec3::ONE).x);
+        assert_eq!(hdr10.uniform().params[3], 1.0);
+    }
+
+    #[test]
+    fn test_operators_on_known_input() {
+        // Middle grey and a bright highlight
+        let grey = Vec3::splat(0.18);
+        let bright = Vec3::splat(4.0);
+        let [aces, reinhard, hable] = TonemapOperator::ALL.map(|op| (op.apply(grey).x, op.apply(bright).x));
+
+        assert!((aces.0 - 0.2669).abs() < 1e-3, "{:?}", aces);
+        assert!((aces.1 - 0.9734).abs() < 1e-3, "{:?}", aces);
+        assert!((reinhard.0 - 0.18 / 1.18).abs() < 1e-5);
+        assert!((reinhard.1 - 0.8).abs() < 1e-5);
+        assert!((hable.0 - 0.1283).abs() < 1e-3, "{:?}", hable);
+        assert!((hable.1 - 0.9180).abs() < 1e-3, "{:?}", hable);
+        // ACES is the most contrasty, Reinhard the flattest in the highlights
+        assert!(aces.1 > hable.1 && hable.1 > reinhard.1);
+
+        // The selected operator and exposure drive the SDR path
+        let mut params = TonemapParams { operator: TonemapOperator::Reinhard, ..TonemapParams::default() };
+        params.set_exposure_ev(2.0);
+        assert!((params.exposure - 4.0).abs() < 1e-6 && (params.exposure_ev() - 2.0).abs() < 1e-6);
+        assert!((params.apply(Vec3::ONE).x - 0.8).abs() < 1e-5);
+        assert_eq!(params.uniform().operator[0], 1.0);
+    }
+}
@@ -5,6 +5,9 @@
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::io::{Read, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::profiling::{profiler, CacheMetrics};
 
 /// Shader cache for storing compiled SPIR-V
 pub struct ShaderCache {
@@ -16,6 +19,12 @@ pub struct ShaderCache {
     max_memory_size: usize,
     /// Current memory cache size
     current_memory_size: usize,
+    /// Lookups served from memory or disk
+    hits: AtomicU64,
+    /// Lookups that found nothing usable
+    misses: AtomicU64,
+    /// Entries evicted from memory
+    evictions: u64,
 }
 
 /// Cache entry
@@ -40,6 +49,9 @@ impl ShaderCache {
             memory_cache: HashMap::new(),
             max_memory_size: 64 * 1024 * 1024, // 64 MB
             current_memory_size: 0,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: 0,
         }
     }
     
@@ -50,6 +62,7 @@ impl ShaderCache {
         // Check memory cache
         if let Some(entry) = self.memory_cache.get(key) {
             if entry.source_hash == source_hash {
+                self.hits.fetch_add(1, Ordering::Relaxed);
                 return Some(entry.spirv.clone());
             }
         }
@@ -58,10 +71,12 @@ impl ShaderCache {
         if let Some(ref cache_dir) = self.cache_dir {
             let cache_path = cache_dir.join(format!("{}.spv", Self::sanitize_key(key)));
             if let Ok(spirv) = Self::read_cache_file(&cache_path, source_hash) {
+                self.hits.fetch_add(1, Ordering::Relaxed);
                 return Some(spirv);
             }
         }
         
+        self.misses.fetch_add(1, Ordering::Relaxed);
         None
     }
     
@@ -132,6 +147,22 @@ impl ShaderCache {
         }
     }
     
+    /// Get hit/miss accounting for the memory and disk cache
+    pub fn metrics(&self) -> CacheMetrics {
+        CacheMetrics {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self.memory_cache.len() as u64,
+            bytes: self.current_memory_size as u64,
+            evictions: self.evictions,
+        }
+    }
+    
+    /// Send `metrics()` to the global profiler as `cache_shader_*` gauges
+    pub fn report_metrics(&self) {
+        profiler().record_cache_metrics("shader", &self.metrics());
+    }
+    
     /// Evict oldest entry from memory cache
     fn evict_oldest(&mut self) {
         let oldest_key = self.memory_cache.iter()
@@ -141,6 +172,7 @@ impl ShaderCache {
         if let Some(key) = oldest_key {
             if let Some(entry) = self.memory_cache.remove(&key) {
                 self.current_memory_size -= entry.spirv.len() * 4;
+                self.evictions += 1;
             }
         }
     }
@@ -244,3 +276,35 @@ pub struct CacheStats {
     /// Number of entries in disk cache
     pub disk_entries: usize,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profiling::{MetricValue, Profiler};
+
+    #[test]
+    fn test_hits_misses_and_evictions_are_counted() {
+        let mut cache = ShaderCache::new(None);
+        cache.max_memory_size = 8; // two 1-word shaders
+
+        assert!(cache.get("a", "src a").is_none());
+        cache.put("a", "src a", &[1]);
+        assert_eq!(cache.get("a", "src a"), Some(vec![1]));
+        assert_eq!(cache.get("a", "src a"), Some(vec![1]));
+        // Changed source misses
+        assert!(cache.get("a", "src a v2").is_none());
+
+        cache.put("b", "src b", &[2]);
+        cache.put("c", "src c", &[3]);
+
+        let metrics = cache.metrics();
+        assert_eq!(metrics, CacheMetrics { hits: 2, misses: 2, entries: 2, bytes: 8, evictions: 1 });
+        assert_eq!(metrics.hit_rate(), 0.5);
+
+        let profiler = Profiler::new();
+        profiler.record_cache_metrics("shader", &metrics);
+        assert!(matches!(profiler.get_metric("cache_shader_hits"), Some(MetricValue::Gauge(v)) if v == 2.0));
+        assert!(matches!(profiler.get_metric("cache_shader_hit_rate"), Some(MetricValue::Gauge(v)) if v == 0.5));
+        assert!(profiler.to_prometheus().contains("libs_cache_shader_evictions 1"));
+    }
+}
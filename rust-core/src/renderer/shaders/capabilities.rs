@@ -0,0 +1,204 @@
+//! # SPIR-V Capabilities
+//!
+//! Extracts the `OpCapability` declarations from SPIR-V and checks them
+//! against what the device enabled, so a shader needing e.g. mesh shading
+//! fails at load time with the missing capability named instead of at
+//! pipeline creation.
+
+use super::ShaderError;
+use crate::renderer::vulkan::features::OptionalFeature;
+use crate::renderer::vulkan::VulkanDevice;
+
+/// SPIR-V magic number
+const SPIRV_MAGIC: u32 = 0x0723_0203;
+
+/// Header words before the first instruction
+const HEADER_WORDS: usize = 5;
+
+/// `OpCapability`
+const OP_CAPABILITY: u32 = 17;
+
+/// `OpMemoryModel` (capabilities all precede it)
+const OP_MEMORY_MODEL: u32 = 14;
+
+/// Capability declared by a SPIR-V module
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SpirvCapability {
+    Shader,
+    Geometry,
+    Tessellation,
+    Float16,
+    Float64,
+    Int64,
+    Int16,
+    Int8,
+    RayQuery,
+    RayTracing,
+    ShaderNonUniform,
+    RuntimeDescriptorArray,
+    MeshShadingNv,
+    MeshShading,
+    PhysicalStorageBufferAddresses,
+    /// Capability without a device check (assumed available)
+    Other(u32),
+}
+
+impl SpirvCapability {
+    /// Map a SPIR-V capability operand
+    pub fn from_word(word: u32) -> Self {
+        match word {
+            1 => Self::Shader,
+            2 => Self::Geometry,
+            3 => Self::Tessellation,
+            9 => Self::Float16,
+            10 => Self::Float64,
+            11 => Self::Int64,
+            22 => Self::Int16,
+            39 => Self::Int8,
+            4472 => Self::RayQuery,
+            4479 => Self::RayTracing,
+            5266 => Self::MeshShadingNv,
+            5283 => Self::MeshShading,
+            5301 => Self::ShaderNonUniform,
+            5302 => Self::RuntimeDescriptorArray,
+            5347 => Self::PhysicalStorageBufferAddresses,
+            other => Self::Other(other),
+        }
+    }
+}
+
+/// Read the capabilities a SPIR-V module declares
+pub fn parse_capabilities(spirv: &[u32]) -> Result<Vec<SpirvCapability>, ShaderError> {
+    if spirv.len() < HEADER_WORDS || spirv[0] != SPIRV_MAGIC {
+        return Err(ShaderError::InvalidSpirv("missing SPIR-V header".to_string()));
+    }
+
+    let mut capabilities = Vec::new();
+    let mut offset = HEADER_WORDS;
+    while offset < spirv.len() {
+        let word_count = (spirv[offset] >> 16) as usize;
+        let opcode = spirv[offset] & 0xFFFF;
+        if word_count == 0 || offset + word_count > spirv.len() {
+            return Err(ShaderError::InvalidSpirv(format!("bad instruction at word {}", offset)));
+        }
+
+        match opcode {
+            OP_CAPABILITY if word_count >= 2 => capabilities.push(SpirvCapability::from_word(spirv[offset + 1])),
+            OP_MEMORY_MODEL => break,
+            _ => {}
+        }
+        offset += word_count;
+    }
+
+    Ok(capabilities)
+}
+
+/// Capabilities a device was created with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeviceCapabilities {
+    pub geometry: bool,
+    pub tessellation: bool,
+    pub float16: bool,
+    pub float64: bool,
+    pub int64: bool,
+    pub int16: bool,
+    pub int8: bool,
+    pub descriptor_indexing: bool,
+    pub buffer_device_address: bool,
+    pub mesh_shading: bool,
+    pub ray_tracing: bool,
+}
+
+impl DeviceCapabilities {
+    /// Get the features `VulkanDevice::new` enables
+    pub fn from_device(device: &VulkanDevice) -> Self {
+        Self {
+            // Not enabled at device creation
+            geometry: false,
+            tessellation: false,
+            float16: false,
+            float64: false,
+            int64: false,
+            int16: false,
+            int8: false,
+            descriptor_indexing: device.feature_report().is_enabled(OptionalFeature::DescriptorIndexing),
+            // Always enabled
+            buffer_device_address: true,
+            mesh_shading: device.supports_mesh_shaders(),
+            ray_tracing: device.supports_ray_tracing(),
+        }
+    }
+
+    /// Check if a capability can be used
+    pub fn supports(&self, capability: SpirvCapability) -> bool {
+        match capability {
+            SpirvCapability::Shader | SpirvCapability::Other(_) => true,
+            SpirvCapability::Geometry => self.geometry,
+            SpirvCapability::Tessellation => self.tessellation,
+            SpirvCapability::Float16 => self.float16,
+            SpirvCapability::Float64 => self.float64,
+            SpirvCapability::Int64 => self.int64,
+            SpirvCapability::Int16 => self.int16,
+            SpirvCapability::Int8 => self.int8,
+            SpirvCapability::ShaderNonUniform | SpirvCapability::RuntimeDescriptorArray => self.descriptor_indexing,
+            SpirvCapability::PhysicalStorageBufferAddresses => self.buffer_device_address,
+            SpirvCapability::MeshShading => self.mesh_shading,
+            // NV mesh shading is never enabled
+            SpirvCapability::MeshShadingNv => false,
+            SpirvCapability::RayQuery | SpirvCapability::RayTracing => self.ray_tracing,
+        }
+    }
+
+    /// Get the capabilities in `required` this device lacks
+    pub fn missing(&self, required: &[SpirvCapability]) -> Vec<SpirvCapability> {
+        required.iter().copied().filter(|&c| !self.supports(c)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const fn op(word_count: u32, opcode: u32) -> u32 {
+        word_count << 16 | opcode
+    }
+
+    #[test]
+    fn test_declared_capabilities_are_reported() {
+        let spirv = [
+            SPIRV_MAGIC, 0x0001_0600, 0, 8, 0,
+            op(2, OP_CAPABILITY), 1,
+            op(2, OP_CAPABILITY), 11,
+            op(2, OP_CAPABILITY), 5283,
+            op(2, OP_CAPABILITY), 6000,
+            op(3, OP_MEMORY_MODEL), 0, 1,
+            op(2, OP_CAPABILITY), 10, // Past the memory model: not a declaration
+        ];
+
+        let capabilities = parse_capabilities(&spirv).unwrap();
+        assert_eq!(capabilities, [
+            SpirvCapability::Shader,
+            SpirvCapability::Int64,
+            SpirvCapability::MeshShading,
+            SpirvCapability::Other(6000),
+        ]);
+
+        let device = DeviceCapabilities {
+            geometry: false,
+            tessellation: false,
+            float16: false,
+            float64: false,
+            int64: false,
+            int16: false,
+            int8: false,
+            descriptor_indexing: true,
+            buffer_device_address: true,
+            mesh_shading: false,
+            ray_tracing: false,
+        };
+        assert_eq!(device.missing(&capabilities), [SpirvCapability::Int64, SpirvCapability::MeshShading]);
+
+        assert!(parse_capabilities(&[0xDEAD_BEEF, 0, 0, 0, 0]).is_err());
+        assert!(parse_capabilities(&[SPIRV_MAGIC, 0, 0, 0, 0, op(4, OP_CAPABILITY), 1]).is_err());
+    }
+}
@@ -6,6 +6,7 @@
 pub mod compiler;
 pub mod cache;
 pub mod reflection;
+pub mod capabilities;
 
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
@@ -15,6 +16,7 @@ use ash::vk;
 pub use compiler::*;
 pub use cache::*;
 pub use reflection::*;
+pub use capabilities::*;
 
 /// Shader stage types
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -87,12 +89,26 @@ pub struct ShaderModule {
     pub entry_point: String,
     /// Reflection data
     pub reflection: ShaderReflection,
+    /// Declared SPIR-V capabilities
+    pub capabilities: Vec<SpirvCapability>,
     /// Source file path (for hot-reloading)
     pub source_path: Option<PathBuf>,
     /// Last modification time
     pub last_modified: Option<std::time::SystemTime>,
 }
 
+impl ShaderModule {
+    /// Get the SPIR-V capabilities the module declares
+    pub fn required_capabilities(&self) -> Vec<SpirvCapability> {
+        self.capabilities.clone()
+    }
+    
+    /// Check if every declared capability is enabled on `device`
+    pub fn is_supported_by(&self, device: &crate::renderer::vulkan::VulkanDevice) -> bool {
+        DeviceCapabilities::from_device(device).missing(&self.capabilities).is_empty()
+    }
+}
+
 /// Shader program (multiple stages)
 pub struct ShaderProgram {
     /// Program name
@@ -174,6 +190,8 @@ pub struct ShaderManager {
     search_paths: Vec<PathBuf>,
     /// Hot-reload enabled
     hot_reload: bool,
+    /// Capabilities of the target device (unchecked when unset)
+    device_capabilities: Option<DeviceCapabilities>,
 }
 
 impl ShaderManager {
@@ -186,6 +204,27 @@ impl ShaderManager {
             programs: RwLock::new(HashMap::new()),
             search_paths: Vec::new(),
             hot_reload: cfg!(debug_assertions),
+            device_capabilities: None,
+        }
+    }
+    
+    /// Reject modules needing capabilities the target device lacks
+    pub fn set_device_capabilities(&mut self, capabilities: DeviceCapabilities) {
+        self.device_capabilities = Some(capabilities);
+    }
+    
+    /// Fail with the first missing capability of a module
+    fn check_capabilities(&self, name: &str, capabilities: &[SpirvCapability]) -> Result<(), ShaderError> {
+        let Some(device) = &self.device_capabilities else {
+            return Ok(());
+        };
+        
+        match device.missing(capabilities).first() {
+            Some(&capability) => {
+                log::error!("Shader '{}' requires {:?}, which the device does not support", name, capability);
+                Err(ShaderError::UnsupportedCapability { shader: name.to_string(), capability })
+            }
+            None => Ok(()),
         }
     }
     
@@ -204,6 +243,8 @@ impl ShaderManager {
         // Check cache first
         let cache_key = format!("{}_{:?}", name, stage);
         if let Some(spirv) = self.cache.read().unwrap().get(&cache_key, source) {
+            let capabilities = parse_capabilities(&spirv)?;
+            self.check_capabilities(name, &capabilities)?;
             let reflection = ShaderReflection::from_spirv(&spirv)?;
             let module = Arc::new(ShaderModule {
                 name: name.to_string(),
@@ -211,6 +252,7 @@ impl ShaderManager {
                 spirv,
                 entry_point: "main".to_string(),
                 reflection,
+                capabilities,
                 source_path: None,
                 last_modified: None,
             });
@@ -225,7 +267,9 @@ impl ShaderManager {
         // Cache compiled shader
         self.cache.write().unwrap().put(&cache_key, source, &spirv);
         
-        // Create reflection data
+        // Check capabilities and create reflection data
+        let capabilities = parse_capabilities(&spirv)?;
+        self.check_capabilities(name, &capabilities)?;
         let reflection = ShaderReflection::from_spirv(&spirv)?;
         
         let module = Arc::new(ShaderModule {
@@ -234,6 +278,7 @@ impl ShaderManager {
             spirv,
             entry_point: "main".to_string(),
             reflection,
+            capabilities,
             source_path: None,
             last_modified: None,
         });
@@ -353,6 +398,7 @@ pub enum ShaderError {
     IoError(String),
     InvalidSpirv(String),
     ReflectionError(String),
+    UnsupportedCapability { shader: String, capability: SpirvCapability },
 }
 
 impl std::fmt::Display for ShaderError {
@@ -363,6 +409,9 @@ impl std::fmt::Display for ShaderError {
             ShaderError::IoError(msg) => write!(f, "IO error: {}", msg),
             ShaderError::InvalidSpirv(msg) => write!(f, "Invalid SPIR-V: {}", msg),
             ShaderError::ReflectionError(msg) => write!(f, "Reflection error: {}", msg),
+            ShaderError::UnsupportedCapability { shader, capability } => {
+                write!(f, "Shader {} requires unsupported capability {:?}", shader, capability)
+            }
         }
     }
 }
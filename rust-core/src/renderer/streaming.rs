@@ -0,0 +1,272 @@
+//! # Texture Streaming
+//!
+//! Keeps texture VRAM within a budget by making only some mip levels
+//! resident. Each frame the renderer calls `request_residency` for the
+//! textures it is about to draw, with a priority from 0 (far away, barely
+//! visible) to 1 (close up). The priority picks the finest mip worth
+//! having; `update` then fits those wishes into the byte budget and returns
+//! the mips to upload and evict. The smallest mip of every texture stays
+//! resident, so a texture that is still streaming in samples its finest
+//! resident mip (`min_lod`) instead of garbage.
+//!
+//! Budget allocation is coarse-first: every texture gets its next mip up
+//! before any texture gets a finer one, ties going to higher priority. A
+//! crowded scene therefore degrades evenly rather than starving the
+//! low-priority textures entirely.
+
+use std::collections::HashMap;
+
+use super::vulkan::GpuMemoryTracker;
+
+/// Default per-frame upload limit (8 MiB)
+pub const DEFAULT_UPLOAD_BYTES_PER_FRAME: u64 = 8 * 1024 * 1024;
+
+/// Mip change the caller performs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MipChange {
+    pub texture_id: u64,
+    pub mip: u32,
+}
+
+/// Work for one frame
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StreamingUpdate {
+    /// Mips to copy in, each finer than the texture's resident ones
+    pub uploads: Vec<MipChange>,
+    /// Mips to release
+    pub evictions: Vec<MipChange>,
+}
+
+/// Streaming state of one texture
+#[derive(Debug, Clone)]
+struct StreamedTexture {
+    width: u32,
+    height: u32,
+    mip_count: u32,
+    bytes_per_texel: u32,
+    /// Finest resident mip (`mip_count - 1` = only the smallest)
+    resident: u32,
+    /// Highest priority requested this frame
+    priority: f32,
+}
+
+impl StreamedTexture {
+    /// Bytes of one mip level
+    fn mip_bytes(&self, mip: u32) -> u64 {
+        let (w, h) = ((self.width >> mip).max(1), (self.height >> mip).max(1));
+        w as u64 * h as u64 * self.bytes_per_texel as u64
+    }
+
+    /// Bytes of mips `top..mip_count`
+    fn bytes_from(&self, top: u32) -> u64 {
+        (top..self.mip_count).map(|mip| self.mip_bytes(mip)).sum()
+    }
+
+    /// Finest mip the priority asks for
+    fn wanted_mip(&self) -> u32 {
+        let coarsest = self.mip_count - 1;
+        ((1.0 - self.priority.clamp(0.0, 1.0)) * coarsest as f32).round() as u32
+    }
+}
+
+/// Texture residency manager
+pub struct TextureStreamer {
+    textures: HashMap<u64, StreamedTexture>,
+    /// Bytes all resident mips may use
+    budget_bytes: u64,
+    /// Upload limit per `update`
+    upload_bytes_per_frame: u64,
+}
+
+impl TextureStreamer {
+    /// Create a streamer with a VRAM budget
+    pub fn new(budget_bytes: u64) -> Self {
+        Self {
+            textures: HashMap::new(),
+            budget_bytes,
+            upload_bytes_per_frame: DEFAULT_UPLOAD_BYTES_PER_FRAME,
+        }
+    }
+
+    /// Track a texture; only its smallest mip starts resident
+    pub fn register(&mut self, texture_id: u64, width: u32, height: u32, bytes_per_texel: u32) {
+        let mip_count = 32 - width.max(height).max(1).leading_zeros();
+        self.textures.insert(texture_id, StreamedTexture {
+            width,
+            height,
+            mip_count,
+            bytes_per_texel,
+            resident: mip_count - 1,
+            priority: 0.0,
+        });
+    }
+
+    /// Stop tracking a texture
+    pub fn unregister(&mut self, texture_id: u64) {
+        self.textures.remove(&texture_id);
+    }
+
+    /// Ask for a texture this frame (0 = far, 1 = full resolution)
+    pub fn request_residency(&mut self, texture_id: u64, priority: f32) {
+        if let Some(texture) = self.textures.get_mut(&texture_id) {
+            texture.priority = texture.priority.max(priority);
+        }
+    }
+
+    /// Set the VRAM budget
+    pub fn set_budget(&mut self, bytes: u64) {
+        self.budget_bytes = bytes;
+    }
+
+    /// Budget what the heap has left under its pressure threshold, counting
+    /// textures already resident as available
+    pub fn set_budget_from_tracker(&mut self, tracker: &GpuMemoryTracker, heap: u32) {
+        let limit = (tracker.budget_bytes(heap) as f64 * tracker.pressure_threshold()) as u64;
+        let others = tracker.used_bytes(heap).saturating_sub(self.resident_bytes());
+        self.budget_bytes = limit.saturating_sub(others);
+    }
+
+    /// Set the upload limit per frame
+    pub fn set_upload_limit(&mut self, bytes_per_frame: u64) {
+        self.upload_bytes_per_frame = bytes_per_frame.max(1);
+    }
+
+    /// Finest mip each texture should have within the budget
+    pub fn plan(&self) -> HashMap<u64, u32> {
+        let mut plan: HashMap<u64, u32> = self.textures.iter().map(|(&id, t)| (id, t.mip_count - 1)).collect();
+        let mut used: u64 = self.textures.values().map(|t| t.mip_bytes(t.mip_count - 1)).sum();
+
+        // Every step up, ordered coarse resolution first, then priority
+        let mut steps: Vec<(u64, u32, &StreamedTexture)> = self
+            .textures
+            .iter()
+            .flat_map(|(&id, t)| (t.wanted_mip()..t.mip_count - 1).map(move |mip| (id, mip, t)))
+            .collect();
+        steps.sort_by(|a, b| {
+            let size = |(_, mip, t): &(u64, u32, &StreamedTexture)| (t.width.max(t.height) >> mip).max(1);
+            size(a)
+                .cmp(&size(b))
+                .then(b.2.priority.total_cmp(&a.2.priority))
+                .then(a.0.cmp(&b.0))
+        });
+
+        for (id, mip, texture) in steps {
+            let top = plan.get_mut(&id).unwrap();
+            // A skipped step blocks the finer ones for that texture
+            if *top != mip + 1 {
+                continue;
+            }
+            let cost = texture.mip_bytes(mip);
+            if used + cost <= self.budget_bytes {
+                used += cost;
+                *top = mip;
+            }
+        }
+        plan
+    }
+
+    /// Fit this frame's requests into the budget and reset them
+    ///
+    /// Evictions apply at once; uploads go one mip per texture per frame,
+    /// highest priority first, up to the upload limit. The returned mips are
+    /// treated as done, so the caller must perform them.
+    pub fn update(&mut self) -> StreamingUpdate {
+        let plan = self.plan();
+        let mut update = StreamingUpdate::default();
+
+        let mut order: Vec<u64> = self.textures.keys().copied().collect();
+        order.sort_by(|a, b| self.textures[b].priority.total_cmp(&self.textures[a].priority).then(a.cmp(b)));
+
+        for id in &order {
+            let texture = self.textures.get_mut(id).unwrap();
+            let target = plan[id];
+            while texture.resident < target {
+                update.evictions.push(MipChange { texture_id: *id, mip: texture.resident });
+                texture.resident += 1;
+            }
+        }
+
+        let mut uploaded = 0;
+        for id in &order {
+            let texture = self.textures.get_mut(id).unwrap();
+            if texture.resident > plan[id] {
+                let mip = texture.resident - 1;
+                let bytes = texture.mip_bytes(mip);
+                if uploaded + bytes > self.upload_bytes_per_frame {
+                    continue;
+                }
+                uploaded += bytes;
+                texture.resident = mip;
+                update.uploads.push(MipChange { texture_id: *id, mip });
+            }
+        }
+
+        for texture in self.textures.values_mut() {
+            texture.priority = 0.0;
+        }
+        update
+    }
+
+    /// Finest resident mip of a texture (clamp the sampler's LOD to it)
+    pub fn min_lod(&self, texture_id: u64) -> Option<u32> {
+        self.textures.get(&texture_id).map(|t| t.resident)
+    }
+
+    /// Get the bytes of all resident mips
+    pub fn resident_bytes(&self) -> u64 {
+        self.textures.values().map(|t| t.bytes_from(t.resident)).sum()
+    }
+
+    /// Get the VRAM budget
+    pub fn budget_bytes(&self) -> u64 {
+        self.budget_bytes
+    }
+
+    /// Get the number of tracked textures
+    pub fn texture_count(&self) -> usize {
+        self.textures.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_goes_coarse_first_then_by_priority() {
+        // Two 256x256 RGBA textures: 9 mips, 256 KiB at mip 0
+        let mut streamer = TextureStreamer::new(u64::MAX);
+        streamer.set_upload_limit(u64::MAX);
+        streamer.register(1, 256, 256, 4);
+        streamer.register(2, 256, 256, 4);
+        assert_eq!(streamer.min_lod(1), Some(8));
+        assert_eq!(streamer.resident_bytes(), 8);
+
+        // Plenty of budget: the near texture goes full res, the far one stays coarse
+        streamer.request_residency(1, 1.0);
+        streamer.request_residency(2, 0.5);
+        assert_eq!(streamer.plan(), HashMap::from([(1, 0), (2, 4)]));
+
+        // 128 KiB fits both through 64x64 (mips 2..8 cost ~21 KiB each), then
+        // one 128x128 mip (64 KiB, the tie goes to the lower id)
+        streamer.request_residency(2, 1.0);
+        streamer.set_budget(128 * 1024);
+        assert_eq!(streamer.plan(), HashMap::from([(1, 1), (2, 2)]));
+
+        // Uploads climb one mip per frame; dropping priority evicts at once
+        let first = streamer.update();
+        assert!(first.evictions.is_empty());
+        assert_eq!(first.uploads, vec![MipChange { texture_id: 1, mip: 7 }, MipChange { texture_id: 2, mip: 7 }]);
+        for _ in 0..8 {
+            streamer.request_residency(1, 1.0);
+            streamer.request_residency(2, 1.0);
+            streamer.update();
+        }
+        assert_eq!((streamer.min_lod(1), streamer.min_lod(2)), (Some(1), Some(2)));
+        assert!(streamer.resident_bytes() <= 128 * 1024);
+
+        let dropped = streamer.update();
+        assert_eq!(dropped.evictions.len(), 7 + 6);
+        assert_eq!((streamer.min_lod(1), streamer.min_lod(2)), (Some(8), Some(8)));
+    }
+}
@@ -0,0 +1,367 @@
+//! # GPU Suballocator
+//!
+//! Allocates device memory in large blocks and carves buffers and images
+//! out of them, so per-chunk buffers don't exhaust the driver's allocation
+//! limit (`maxMemoryAllocationCount`, often 4096).
+//!
+//! Each memory type has two pools (buffers and images are kept apart so
+//! `bufferImageGranularity` never matters). Blocks keep a sorted free list
+//! that coalesces on free; an empty block is released unless it is the
+//! last one in its pool. Requests larger than a block get a dedicated one.
+
+use std::collections::HashMap;
+use std::ffi::c_void;
+use std::sync::Arc;
+use ash::vk;
+use parking_lot::Mutex;
+
+use super::{VulkanDevice, VulkanError};
+
+/// Default block size (64 MiB)
+pub const DEFAULT_BLOCK_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Round `value` up to a multiple of `align` (a power of two)
+fn align_up(value: u64, align: u64) -> u64 {
+    let align = align.max(1);
+    value.div_ceil(align) * align
+}
+
+/// Free ranges of one block, sorted by offset
+#[derive(Debug, Clone)]
+struct FreeList {
+    ranges: Vec<(u64, u64)>,
+    size: u64,
+}
+
+impl FreeList {
+    fn new(size: u64) -> Self {
+        Self { ranges: vec![(0, size)], size }
+    }
+
+    /// First-fit allocation; returns the aligned offset
+    fn allocate(&mut self, size: u64, align: u64) -> Option<u64> {
+        let (index, offset) = self.ranges.iter().enumerate().find_map(|(i, &(start, len))| {
+            let offset = align_up(start, align);
+            (offset + size <= start + len).then_some((i, offset))
+        })?;
+
+        // Split into the alignment gap before and the remainder after
+        let (start, len) = self.ranges.remove(index);
+        let end = start + len;
+        if offset + size < end {
+            self.ranges.insert(index, (offset + size, end - offset - size));
+        }
+        if start < offset {
+            self.ranges.insert(index, (start, offset - start));
+        }
+        Some(offset)
+    }
+
+    /// Return a range, merging with its neighbours
+    fn free(&mut self, offset: u64, size: u64) {
+        let index = self.ranges.partition_point(|&(start, _)| start < offset);
+        self.ranges.insert(index, (offset, size));
+
+        if index + 1 < self.ranges.len() && offset + size == self.ranges[index + 1].0 {
+            self.ranges[index].1 += self.ranges.remove(index + 1).1;
+        }
+        if index > 0 && self.ranges[index - 1].0 + self.ranges[index - 1].1 == offset {
+            self.ranges[index - 1].1 += self.ranges.remove(index).1;
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.ranges.len() == 1 && self.ranges[0] == (0, self.size)
+    }
+
+    fn free_bytes(&self) -> u64 {
+        self.ranges.iter().map(|&(_, len)| len).sum()
+    }
+}
+
+/// Blocks of one memory type
+///
+/// Generic over the block handle so the bookkeeping runs without a device.
+#[derive(Debug)]
+pub struct MemoryPool<M> {
+    blocks: Vec<Option<(M, FreeList)>>,
+    block_size: u64,
+}
+
+impl<M: Copy> MemoryPool<M> {
+    /// Create an empty pool
+    pub fn new(block_size: u64) -> Self {
+        Self { blocks: Vec::new(), block_size }
+    }
+
+    /// Allocate `size` bytes, creating a block with `new_block(bytes)` if none fits
+    ///
+    /// Returns (block index, block handle, offset).
+    pub fn allocate<E>(
+        &mut self,
+        size: u64,
+        align: u64,
+        new_block: impl FnOnce(u64) -> Result<M, E>,
+    ) -> Result<(usize, M, u64), E> {
+        for (index, slot) in self.blocks.iter_mut().enumerate() {
+            if let Some((memory, free)) = slot {
+                if let Some(offset) = free.allocate(size, align) {
+                    return Ok((index, *memory, offset));
+                }
+            }
+        }
+
+        let block_size = self.block_size.max(size);
+        let memory = new_block(block_size)?;
+        let mut free = FreeList::new(block_size);
+        let offset = free.allocate(size, align).unwrap_or(0);
+
+        let index = match self.blocks.iter().position(Option::is_none) {
+            Some(index) => {
+                self.blocks[index] = Some((memory, free));
+                index
+            }
+            None => {
+                self.blocks.push(Some((memory, free)));
+                self.blocks.len() - 1
+            }
+        };
+        Ok((index, memory, offset))
+    }
+
+    /// Free a range; returns the block handle if the block should be released
+    pub fn free(&mut self, block: usize, offset: u64, size: u64) -> Option<M> {
+        let (memory, free) = self.blocks.get_mut(block)?.as_mut()?;
+        free.free(offset, size);
+
+        let memory = *memory;
+        if free.is_empty() && self.block_count() > 1 {
+            self.blocks[block] = None;
+            return Some(memory);
+        }
+        None
+    }
+
+    /// Get live block count
+    pub fn block_count(&self) -> usize {
+        self.blocks.iter().flatten().count()
+    }
+
+    /// Get free bytes across all blocks
+    pub fn free_bytes(&self) -> u64 {
+        self.blocks.iter().flatten().map(|(_, free)| free.free_bytes()).sum()
+    }
+
+    /// Take every block handle (for teardown)
+    pub fn drain(&mut self) -> Vec<M> {
+        self.blocks.drain(..).flatten().map(|(memory, _)| memory).collect()
+    }
+}
+
+/// Device memory block (persistently mapped if host-visible)
+#[derive(Debug, Clone, Copy)]
+struct BlockMemory {
+    memory: vk::DeviceMemory,
+    mapped: Option<*mut c_void>,
+}
+
+/// Pool key: memory type and whether it holds images
+type PoolKey = (u32, bool);
+
+/// Range of a block handed out by `GpuAllocator`
+#[derive(Debug, Clone, Copy)]
+pub struct Suballocation {
+    /// Backing block
+    pub memory: vk::DeviceMemory,
+    /// Offset into the block
+    pub offset: vk::DeviceSize,
+    /// Size in bytes
+    pub size: vk::DeviceSize,
+    /// Host pointer to the range (host-visible memory only)
+    pub mapped_ptr: Option<*mut c_void>,
+    pool: PoolKey,
+    block: usize,
+}
+
+/// Block suballocator
+pub struct GpuAllocator {
+    device: Arc<VulkanDevice>,
+    pools: Mutex<HashMap<PoolKey, MemoryPool<BlockMemory>>>,
+    block_size: u64,
+}
+
+impl GpuAllocator {
+    /// Create an allocator with the default block size
+    pub fn new(device: Arc<VulkanDevice>) -> Self {
+        Self::with_block_size(device, DEFAULT_BLOCK_SIZE)
+    }
+
+    /// Create an allocator with a custom block size
+    pub fn with_block_size(device: Arc<VulkanDevice>, block_size: u64) -> Self {
+        Self { device, pools: Mutex::new(HashMap::new()), block_size }
+    }
+
+    /// Get the device
+    pub fn device(&self) -> &Arc<VulkanDevice> {
+        &self.device
+    }
+
+    /// Get the number of device memory blocks allocated
+    pub fn block_count(&self) -> usize {
+        self.pools.lock().values().map(MemoryPool::block_count).sum()
+    }
+
+    /// Suballocate memory and bind a buffer to it
+    pub fn bind_buffer(&self, buffer: vk::Buffer, flags: vk::MemoryPropertyFlags) -> Result<Suballocation, VulkanError> {
+        let requirements = unsafe { self.device.handle().get_buffer_memory_requirements(buffer) };
+        let allocation = self.allocate(requirements, flags, false)?;
+        unsafe { self.device.handle().bind_buffer_memory(buffer, allocation.memory, allocation.offset) }
+            .map(|_| allocation)
+            .map_err(|e| {
+                self.free(&allocation);
+                VulkanError::BufferCreationFailed(format!("Failed to bind memory: {:?}", e))
+            })
+    }
+
+    /// Suballocate memory and bind an image to it
+    pub fn bind_image(&self, image: vk::Image, flags: vk::MemoryPropertyFlags) -> Result<Suballocation, VulkanError> {
+        let requirements = unsafe { self.device.handle().get_image_memory_requirements(image) };
+        let allocation = self.allocate(requirements, flags, true)?;
+        unsafe { self.device.handle().bind_image_memory(image, allocation.memory, allocation.offset) }
+            .map(|_| allocation)
+            .map_err(|e| {
+                self.free(&allocation);
+                VulkanError::TextureCreationFailed(format!("Failed to bind memory: {:?}", e))
+            })
+    }
+
+    /// Suballocate memory matching `requirements`
+    pub fn allocate(
+        &self,
+        requirements: vk::MemoryRequirements,
+        flags: vk::MemoryPropertyFlags,
+        image: bool,
+    ) -> Result<Suballocation, VulkanError> {
+        let memory_type = self.device.find_memory_type(requirements.memory_type_bits, flags)
+            .ok_or_else(|| VulkanError::BufferCreationFailed("No suitable memory type found".to_string()))?;
+        let key = (memory_type, image);
+        let host_visible = flags.contains(vk::MemoryPropertyFlags::HOST_VISIBLE);
+
+        let mut pools = self.pools.lock();
+        let pool = pools.entry(key).or_insert_with(|| MemoryPool::new(self.block_size));
+        let (block, memory, offset) = pool.allocate(requirements.size, requirements.alignment, |size| {
+            self.allocate_block(memory_type, size, host_visible)
+        })?;
+
+        Ok(Suballocation {
+            memory: memory.memory,
+            offset,
+            size: requirements.size,
+            mapped_ptr: memory.mapped.map(|ptr| unsafe { (ptr as *mut u8).add(offset as usize) as *mut c_void }),
+            pool: key,
+            block,
+        })
+    }
+
+    /// Return a suballocation; the resource bound to it must already be destroyed
+    pub fn free(&self, allocation: &Suballocation) {
+        let released = self.pools.lock()
+            .get_mut(&allocation.pool)
+            .and_then(|pool| pool.free(allocation.block, allocation.offset, allocation.size));
+
+        if let Some(block) = released {
+            unsafe { self.release_block(block) };
+        }
+    }
+
+    fn allocate_block(&self, memory_type: u32, size: u64, host_visible: bool) -> Result<BlockMemory, VulkanError> {
+        let alloc_info = vk::MemoryAllocateInfo::default()
+            .allocation_size(size)
+            .memory_type_index(memory_type);
+        let memory = self.device.allocate_memory(&alloc_info)?;
+
+        let mapped = if host_visible {
+            match unsafe { self.device.handle().map_memory(memory, 0, vk::WHOLE_SIZE, vk::MemoryMapFlags::empty()) } {
+                Ok(ptr) => Some(ptr),
+                Err(e) => {
+                    unsafe { self.device.free_memory(memory) };
+                    return Err(VulkanError::BufferCreationFailed(format!("Failed to map memory: {:?}", e)));
+                }
+            }
+        } else {
+            None
+        };
+
+        log::debug!("GpuAllocator: new {} byte block (memory type {})", size, memory_type);
+        Ok(BlockMemory { memory, mapped })
+    }
+
+    unsafe fn release_block(&self, block: BlockMemory) {
+        if block.mapped.is_some() {
+            self.device.handle().unmap_memory(block.memory);
+        }
+        self.device.free_memory(block.memory);
+    }
+}
+
+impl Drop for GpuAllocator {
+    fn drop(&mut self) {
+        let blocks: Vec<_> = self.pools.lock().values_mut().flat_map(MemoryPool::drain).collect();
+        for block in blocks {
+            unsafe { self.release_block(block) };
+        }
+    }
+}
+
+// Safety: mapped pointers are only handed out per suballocation; the pools are behind a mutex
+unsafe impl Send for GpuAllocator {}
+unsafe impl Sync for GpuAllocator {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_small_buffers_share_blocks() {
+        const BLOCK: u64 = 1 << 20;
+        let mut pool: MemoryPool<u32> = MemoryPool::new(BLOCK);
+        let mut blocks_created = 0;
+
+        // 500 chunk-sized buffers with odd sizes and 256-byte alignment
+        let mut allocations = Vec::new();
+        for i in 0..500u64 {
+            let size = 3000 + i * 7;
+            let (block, _, offset) = pool.allocate(size, 256, |_| -> Result<u32, ()> {
+                blocks_created += 1;
+                Ok(blocks_created)
+            }).unwrap();
+            assert_eq!(offset % 256, 0);
+            allocations.push((block, offset, size));
+        }
+        assert_eq!(blocks_created, 3);
+        assert_eq!(pool.block_count(), 3);
+
+        // No two live ranges in a block overlap
+        let mut sorted = allocations.clone();
+        sorted.sort_unstable();
+        for pair in sorted.windows(2) {
+            let ((b0, o0, s0), (b1, o1, _)) = (pair[0], pair[1]);
+            assert!(b0 != b1 || o0 + s0 <= o1);
+        }
+
+        // Freeing everything coalesces and releases all but one block
+        let released: Vec<u32> = allocations.iter()
+            .filter_map(|&(block, offset, size)| pool.free(block, offset, size))
+            .collect();
+        assert_eq!(released.len(), 2);
+        assert_eq!(pool.block_count(), 1);
+        assert_eq!(pool.free_bytes(), BLOCK);
+
+        // Oversized requests get a dedicated block
+        let (_, _, offset) = pool.allocate(3 * BLOCK, 16, |size| -> Result<u32, ()> {
+            assert_eq!(size, 3 * BLOCK);
+            Ok(99)
+        }).unwrap();
+        assert_eq!(offset, 0);
+    }
+}
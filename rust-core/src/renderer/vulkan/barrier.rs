@@ -0,0 +1,346 @@
+//! # Pipeline Barriers
+//!
+//! Helpers for the barriers passes record by hand, so stage and access
+//! masks come from one table instead of being retyped at every call site.
+//! Common hand-offs are named `Transition`s (compute writes a buffer the
+//! vertex stage reads, a transfer fills a buffer compute reads, ...). Image
+//! layout changes derive their masks from the layouts involved, and an
+//! `ImageLayoutTracker` remembers each image's current layout so callers
+//! only say which layout they need next.
+//!
+//! Passes registered with the render graph get their barriers from the
+//! graph instead; these helpers are for code recording outside it.
+
+use std::collections::HashMap;
+
+use ash::vk;
+
+/// One side of a barrier: the stages and accesses that must finish or wait
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Scope {
+    /// Pipeline stages
+    pub stage: vk::PipelineStageFlags,
+    /// Memory accesses
+    pub access: vk::AccessFlags,
+}
+
+impl Scope {
+    /// Create a scope
+    pub const fn new(stage: vk::PipelineStageFlags, access: vk::AccessFlags) -> Self {
+        Self { stage, access }
+    }
+}
+
+/// Named source/destination pair
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transition {
+    /// Work before the barrier
+    pub src: Scope,
+    /// Work after the barrier
+    pub dst: Scope,
+}
+
+impl Transition {
+    /// Compute shader output read as vertex input or from vertex/task shaders
+    pub const COMPUTE_TO_VERTEX: Transition = Transition {
+        src: Scope::new(vk::PipelineStageFlags::COMPUTE_SHADER, vk::AccessFlags::SHADER_WRITE),
+        dst: Scope::new(
+            vk::PipelineStageFlags::from_raw(
+                vk::PipelineStageFlags::VERTEX_INPUT.as_raw() | vk::PipelineStageFlags::VERTEX_SHADER.as_raw(),
+            ),
+            vk::AccessFlags::from_raw(
+                vk::AccessFlags::VERTEX_ATTRIBUTE_READ.as_raw() | vk::AccessFlags::SHADER_READ.as_raw(),
+            ),
+        ),
+    };
+
+    /// Compute shader output read as indirect draw commands
+    pub const COMPUTE_TO_INDIRECT: Transition = Transition {
+        src: Scope::new(vk::PipelineStageFlags::COMPUTE_SHADER, vk::AccessFlags::SHADER_WRITE),
+        dst: Scope::new(vk::PipelineStageFlags::DRAW_INDIRECT, vk::AccessFlags::INDIRECT_COMMAND_READ),
+    };
+
+    /// Compute shader output consumed by indirect mesh task draws (commands
+    /// and task shaders); only valid with the mesh shader feature enabled
+    pub const COMPUTE_TO_TASK_INDIRECT: Transition = Transition {
+        src: Scope::new(vk::PipelineStageFlags::COMPUTE_SHADER, vk::AccessFlags::SHADER_WRITE),
+        dst: Scope::new(
+            vk::PipelineStageFlags::from_raw(
+                vk::PipelineStageFlags::DRAW_INDIRECT.as_raw() | vk::PipelineStageFlags::TASK_SHADER_EXT.as_raw(),
+            ),
+            vk::AccessFlags::from_raw(
+                vk::AccessFlags::INDIRECT_COMMAND_READ.as_raw() | vk::AccessFlags::SHADER_READ.as_raw(),
+            ),
+        ),
+    };
+
+    /// Copied or filled data read and written by compute
+    pub const TRANSFER_TO_COMPUTE: Transition = Transition {
+        src: Scope::new(vk::PipelineStageFlags::TRANSFER, vk::AccessFlags::TRANSFER_WRITE),
+        dst: Scope::new(
+            vk::PipelineStageFlags::COMPUTE_SHADER,
+            vk::AccessFlags::from_raw(vk::AccessFlags::SHADER_READ.as_raw() | vk::AccessFlags::SHADER_WRITE.as_raw()),
+        ),
+    };
+
+    /// Last use as indirect commands finished before a transfer overwrites them
+    pub const INDIRECT_TO_TRANSFER: Transition = Transition {
+        src: Scope::new(vk::PipelineStageFlags::DRAW_INDIRECT, vk::AccessFlags::INDIRECT_COMMAND_READ),
+        dst: Scope::new(vk::PipelineStageFlags::TRANSFER, vk::AccessFlags::TRANSFER_WRITE),
+    };
+
+    /// Compute output read by a later compute dispatch
+    pub const COMPUTE_TO_COMPUTE: Transition = Transition {
+        src: Scope::new(vk::PipelineStageFlags::COMPUTE_SHADER, vk::AccessFlags::SHADER_WRITE),
+        dst: Scope::new(vk::PipelineStageFlags::COMPUTE_SHADER, vk::AccessFlags::SHADER_READ),
+    };
+}
+
+/// Stages and accesses that use an image in `layout`
+pub fn layout_scope(layout: vk::ImageLayout) -> Scope {
+    use vk::{AccessFlags as A, ImageLayout as L, PipelineStageFlags as S};
+    match layout {
+        L::UNDEFINED | L::PREINITIALIZED => Scope::new(S::TOP_OF_PIPE, A::empty()),
+        L::TRANSFER_DST_OPTIMAL => Scope::new(S::TRANSFER, A::TRANSFER_WRITE),
+        L::TRANSFER_SRC_OPTIMAL => Scope::new(S::TRANSFER, A::TRANSFER_READ),
+        L::SHADER_READ_ONLY_OPTIMAL => Scope::new(S::FRAGMENT_SHADER | S::COMPUTE_SHADER, A::SHADER_READ),
+        L::GENERAL => Scope::new(S::COMPUTE_SHADER, A::SHADER_READ | A::SHADER_WRITE),
+        L::COLOR_ATTACHMENT_OPTIMAL => Scope::new(
+            S::COLOR_ATTACHMENT_OUTPUT,
+            A::COLOR_ATTACHMENT_READ | A::COLOR_ATTACHMENT_WRITE,
+        ),
+        L::DEPTH_STENCIL_ATTACHMENT_OPTIMAL | L::DEPTH_ATTACHMENT_OPTIMAL => Scope::new(
+            S::EARLY_FRAGMENT_TESTS | S::LATE_FRAGMENT_TESTS,
+            A::DEPTH_STENCIL_ATTACHMENT_READ | A::DEPTH_STENCIL_ATTACHMENT_WRITE,
+        ),
+        L::PRESENT_SRC_KHR => Scope::new(S::BOTTOM_OF_PIPE, A::MEMORY_READ),
+        _ => Scope::new(S::ALL_COMMANDS, A::MEMORY_READ | A::MEMORY_WRITE),
+    }
+}
+
+/// A layout change with the scopes its layouts imply
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageTransition {
+    /// Image
+    pub image: vk::Image,
+    /// Layout before
+    pub old_layout: vk::ImageLayout,
+    /// Layout after
+    pub new_layout: vk::ImageLayout,
+    /// Work that must finish first
+    pub src: Scope,
+    /// Work that waits
+    pub dst: Scope,
+}
+
+impl ImageTransition {
+    /// Transition between two layouts
+    pub fn new(image: vk::Image, old_layout: vk::ImageLayout, new_layout: vk::ImageLayout) -> Self {
+        Self {
+            image,
+            old_layout,
+            new_layout,
+            src: layout_scope(old_layout),
+            dst: layout_scope(new_layout),
+        }
+    }
+
+    /// Build the barrier for all mips and layers of `aspect`
+    pub fn barrier(&self, aspect: vk::ImageAspectFlags) -> vk::ImageMemoryBarrier<'static> {
+        vk::ImageMemoryBarrier::default()
+            .src_access_mask(self.src.access)
+            .dst_access_mask(self.dst.access)
+            .old_layout(self.old_layout)
+            .new_layout(self.new_layout)
+            .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+            .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+            .image(self.image)
+            .subresource_range(vk::ImageSubresourceRange {
+                aspect_mask: aspect,
+                base_mip_level: 0,
+                level_count: vk::REMAINING_MIP_LEVELS,
+                base_array_layer: 0,
+                layer_count: vk::REMAINING_ARRAY_LAYERS,
+            })
+    }
+
+    /// Record the barrier
+    ///
+    /// # Safety
+    /// `cmd` must be recording on `device`.
+    pub unsafe fn record(&self, device: &ash::Device, cmd: vk::CommandBuffer, aspect: vk::ImageAspectFlags) {
+        device.cmd_pipeline_barrier(
+            cmd,
+            self.src.stage,
+            self.dst.stage,
+            vk::DependencyFlags::empty(),
+            &[],
+            &[],
+            &[self.barrier(aspect)],
+        );
+    }
+}
+
+/// Record a global memory barrier for a named transition
+///
+/// # Safety
+/// `cmd` must be recording on `device`.
+pub unsafe fn memory_barrier(device: &ash::Device, cmd: vk::CommandBuffer, transition: Transition) {
+    device.cmd_pipeline_barrier(
+        cmd,
+        transition.src.stage,
+        transition.dst.stage,
+        vk::DependencyFlags::empty(),
+        &[vk::MemoryBarrier::default()
+            .src_access_mask(transition.src.access)
+            .dst_access_mask(transition.dst.access)],
+        &[],
+        &[],
+    );
+}
+
+/// Record a barrier on a whole buffer
+///
+/// # Safety
+/// `cmd` must be recording on `device`.
+pub unsafe fn buffer_barrier(
+    device: &ash::Device,
+    cmd: vk::CommandBuffer,
+    buffer: vk::Buffer,
+    src_access: vk::AccessFlags,
+    dst_access: vk::AccessFlags,
+    src_stage: vk::PipelineStageFlags,
+    dst_stage: vk::PipelineStageFlags,
+) {
+    let barrier = vk::BufferMemoryBarrier::default()
+        .src_access_mask(src_access)
+        .dst_access_mask(dst_access)
+        .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+        .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+        .buffer(buffer)
+        .offset(0)
+        .size(vk::WHOLE_SIZE);
+    device.cmd_pipeline_barrier(cmd, src_stage, dst_stage, vk::DependencyFlags::empty(), &[], &[barrier], &[]);
+}
+
+/// Record a barrier on a whole buffer for a named transition
+///
+/// # Safety
+/// `cmd` must be recording on `device`.
+pub unsafe fn buffer_transition(device: &ash::Device, cmd: vk::CommandBuffer, buffer: vk::Buffer, transition: Transition) {
+    let Transition { src, dst } = transition;
+    buffer_barrier(device, cmd, buffer, src.access, dst.access, src.stage, dst.stage);
+}
+
+/// Record a layout change on a whole image
+///
+/// # Safety
+/// `cmd` must be recording on `device`.
+pub unsafe fn image_transition(
+    device: &ash::Device,
+    cmd: vk::CommandBuffer,
+    image: vk::Image,
+    aspect: vk::ImageAspectFlags,
+    old_layout: vk::ImageLayout,
+    new_layout: vk::ImageLayout,
+) {
+    ImageTransition::new(image, old_layout, new_layout).record(device, cmd, aspect);
+}
+
+/// Current layout of each image, for transitions that only name the target
+#[derive(Debug, Default)]
+pub struct ImageLayoutTracker {
+    /// Layout by image
+    layouts: HashMap<vk::Image, vk::ImageLayout>,
+}
+
+impl ImageLayoutTracker {
+    /// Create an empty tracker
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start tracking an image in a known layout
+    pub fn register(&mut self, image: vk::Image, layout: vk::ImageLayout) {
+        self.layouts.insert(image, layout);
+    }
+
+    /// Stop tracking an image (e.g. before destroying it)
+    pub fn forget(&mut self, image: vk::Image) {
+        self.layouts.remove(&image);
+    }
+
+    /// Get an image's current layout (UNDEFINED if untracked)
+    pub fn layout(&self, image: vk::Image) -> vk::ImageLayout {
+        self.layouts.get(&image).copied().unwrap_or(vk::ImageLayout::UNDEFINED)
+    }
+
+    /// Move an image to `new_layout`; `None` if it is already there
+    pub fn transition(&mut self, image: vk::Image, new_layout: vk::ImageLayout) -> Option<ImageTransition> {
+        let old_layout = self.layouts.insert(image, new_layout).unwrap_or(vk::ImageLayout::UNDEFINED);
+        (old_layout != new_layout).then(|| ImageTransition::new(image, old_layout, new_layout))
+    }
+
+    /// Move an image to `new_layout`, recording the barrier if needed
+    ///
+    /// # Safety
+    /// `cmd` must be recording on `device`.
+    pub unsafe fn record_transition(
+        &mut self,
+        device: &ash::Device,
+        cmd: vk::CommandBuffer,
+        image: vk::Image,
+        aspect: vk::ImageAspectFlags,
+        new_layout: vk::ImageLayout,
+    ) {
+        if let Some(transition) = self.transition(image, new_layout) {
+            transition.record(device, cmd, aspect);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ash::vk::Handle;
+
+    #[test]
+    fn test_named_transitions_and_tracked_layouts() {
+        let t = Transition::COMPUTE_TO_VERTEX;
+        assert_eq!(t.src.stage, vk::PipelineStageFlags::COMPUTE_SHADER);
+        assert_eq!(t.src.access, vk::AccessFlags::SHADER_WRITE);
+        assert!(t.dst.stage.contains(vk::PipelineStageFlags::VERTEX_INPUT));
+        assert!(t.dst.access.contains(vk::AccessFlags::VERTEX_ATTRIBUTE_READ));
+
+        // Plain indirect draws never name the task stage
+        let t = Transition::COMPUTE_TO_INDIRECT;
+        assert_eq!((t.dst.stage, t.dst.access), (vk::PipelineStageFlags::DRAW_INDIRECT, vk::AccessFlags::INDIRECT_COMMAND_READ));
+        assert!(Transition::COMPUTE_TO_TASK_INDIRECT.dst.stage.contains(vk::PipelineStageFlags::TASK_SHADER_EXT));
+
+        let t = Transition::TRANSFER_TO_COMPUTE;
+        assert_eq!((t.src.stage, t.src.access), (vk::PipelineStageFlags::TRANSFER, vk::AccessFlags::TRANSFER_WRITE));
+        assert_eq!(t.dst.access, vk::AccessFlags::SHADER_READ | vk::AccessFlags::SHADER_WRITE);
+
+        // Undefined -> shader read waits on nothing
+        let image = vk::Image::from_raw(7);
+        let t = ImageTransition::new(image, vk::ImageLayout::UNDEFINED, vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL);
+        assert_eq!((t.src.stage, t.src.access), (vk::PipelineStageFlags::TOP_OF_PIPE, vk::AccessFlags::empty()));
+        assert_eq!(t.dst.access, vk::AccessFlags::SHADER_READ);
+        assert!(t.dst.stage.contains(vk::PipelineStageFlags::FRAGMENT_SHADER));
+        let barrier = t.barrier(vk::ImageAspectFlags::COLOR);
+        assert_eq!(barrier.new_layout, vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL);
+        assert_eq!(barrier.subresource_range.level_count, vk::REMAINING_MIP_LEVELS);
+
+        // Tracker fills in the old layout and skips no-op transitions
+        let mut tracker = ImageLayoutTracker::new();
+        let upload = tracker.transition(image, vk::ImageLayout::TRANSFER_DST_OPTIMAL).unwrap();
+        assert_eq!(upload.old_layout, vk::ImageLayout::UNDEFINED);
+        let sample = tracker.transition(image, vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL).unwrap();
+        assert_eq!(sample.old_layout, vk::ImageLayout::TRANSFER_DST_OPTIMAL);
+        assert_eq!((sample.src.stage, sample.src.access), (vk::PipelineStageFlags::TRANSFER, vk::AccessFlags::TRANSFER_WRITE));
+        assert!(tracker.transition(image, vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL).is_none());
+        assert_eq!(tracker.layout(image), vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL);
+        tracker.forget(image);
+        assert_eq!(tracker.layout(image), vk::ImageLayout::UNDEFINED);
+    }
+}
@@ -0,0 +1,108 @@
+//! # Block Models
+//!
+//! Geometry the chunk mesher emits per block ID. Blocks without a
+//! registered model are full cubes. Other shapes are lists of cuboid
+//! elements in 1/16 block units, like the `elements` of a Minecraft JSON
+//! model, or one of the special `Cross` (plants) and `Fluid` shapes.
+//!
+//! Only full cubes hide the faces of their neighbors.
+
+use std::collections::HashMap;
+
+/// Axis-aligned box inside a block, in 1/16 block units (0..=16)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cuboid {
+    /// Minimum corner
+    pub from: [f32; 3],
+    /// Maximum corner
+    pub to: [f32; 3],
+}
+
+impl Cuboid {
+    /// Create a cuboid from two corners in 1/16 block units
+    pub fn new(from: [f32; 3], to: [f32; 3]) -> Self {
+        Self { from, to }
+    }
+
+    /// Get the minimum corner in block units
+    pub fn min(&self) -> [f32; 3] {
+        self.from.map(|c| c / 16.0)
+    }
+
+    /// Get the maximum corner in block units
+    pub fn max(&self) -> [f32; 3] {
+        self.to.map(|c| c / 16.0)
+    }
+}
+
+/// Fluid surface height when no fluid is above (in block units)
+pub const FLUID_SURFACE_HEIGHT: f32 = 14.0 / 16.0;
+
+/// Shape of a block
+#[derive(Debug, Clone, PartialEq)]
+pub enum BlockModel {
+    /// Full 1×1×1 cube (the default)
+    Cube,
+    /// Cuboid elements (slabs, stairs, fences, ...)
+    Cuboids(Vec<Cuboid>),
+    /// Two double-sided diagonal quads (flowers, grass, saplings)
+    Cross,
+    /// Lowered box whose faces are hidden by the same fluid
+    Fluid,
+}
+
+impl BlockModel {
+    /// Bottom half slab
+    pub fn slab() -> Self {
+        Self::Cuboids(vec![Cuboid::new([0.0, 0.0, 0.0], [16.0, 8.0, 16.0])])
+    }
+
+    /// Check if this model fills its whole block
+    pub fn is_full_cube(&self) -> bool {
+        match self {
+            Self::Cube => true,
+            Self::Cuboids(elements) => {
+                elements.iter().any(|e| e.from == [0.0; 3] && e.to == [16.0; 3])
+            }
+            Self::Cross | Self::Fluid => false,
+        }
+    }
+}
+
+/// Block ID -> model table
+#[derive(Debug, Clone, Default)]
+pub struct BlockModelRegistry {
+    models: HashMap<u16, BlockModel>,
+}
+
+impl BlockModelRegistry {
+    /// Create an empty registry (every block is a cube)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the model for a block
+    pub fn register_model(&mut self, block_id: u16, model: BlockModel) {
+        self.models.insert(block_id, model);
+    }
+
+    /// Get the model for a block
+    pub fn model(&self, block_id: u16) -> &BlockModel {
+        self.models.get(&block_id).unwrap_or(&BlockModel::Cube)
+    }
+
+    /// Check if a block hides the faces of the blocks next to it
+    pub fn occludes(&self, block_id: u16) -> bool {
+        block_id != 0 && self.model(block_id).is_full_cube()
+    }
+
+    /// Get registered model count
+    pub fn len(&self) -> usize {
+        self.models.len()
+    }
+
+    /// Check if no models are registered
+    pub fn is_empty(&self) -> bool {
+        self.models.is_empty()
+    }
+}
@@ -5,6 +5,7 @@
 use std::sync::Arc;
 use ash::vk;
 
+use super::allocator::{GpuAllocator, Suballocation};
 use super::{VulkanDevice, VulkanError};
 
 /// Buffer type enumeration
@@ -20,6 +21,10 @@ pub enum BufferType {
     Storage,
     /// Staging buffer (CPU -> GPU transfer)
     Staging,
+    /// Storage buffer also read by indirect draws
+    Indirect,
+    /// Storage buffer the host reads back (GPU -> CPU)
+    Readback,
 }
 
 /// GPU buffer wrapper
@@ -36,6 +41,8 @@ pub struct Buffer {
     buffer_type: BufferType,
     /// Mapped pointer (if persistently mapped)
     mapped_ptr: Option<*mut std::ffi::c_void>,
+    /// Shared block the buffer is bound into (`memory` is not owned)
+    suballocation: Option<(Arc<GpuAllocator>, Suballocation)>,
 }
 
 impl Buffer {
@@ -45,7 +52,51 @@ impl Buffer {
         size: vk::DeviceSize,
         buffer_type: BufferType,
     ) -> Result<Self, VulkanError> {
-        let (usage, memory_flags) = match buffer_type {
+        let (usage, memory_flags) = Self::usage_and_flags(buffer_type);
+        Self::create_buffer(device, size, usage, memory_flags, buffer_type)
+    }
+    
+    /// Create a buffer bound into a block of a shared allocator
+    pub fn new_suballocated(
+        allocator: &Arc<GpuAllocator>,
+        size: vk::DeviceSize,
+        buffer_type: BufferType,
+    ) -> Result<Self, VulkanError> {
+        let device = allocator.device().clone();
+        let (usage, memory_flags) = Self::usage_and_flags(buffer_type);
+        
+        let buffer_info = vk::BufferCreateInfo::default()
+            .size(size)
+            .usage(usage)
+            .sharing_mode(vk::SharingMode::EXCLUSIVE);
+        
+        let buffer = unsafe {
+            device.handle().create_buffer(&buffer_info, None)
+                .map_err(|e| VulkanError::BufferCreationFailed(format!("Failed to create buffer: {:?}", e)))?
+        };
+        
+        let allocation = match allocator.bind_buffer(buffer, memory_flags) {
+            Ok(allocation) => allocation,
+            Err(e) => {
+                unsafe { device.handle().destroy_buffer(buffer, None) };
+                return Err(e);
+            }
+        };
+        
+        Ok(Self {
+            device,
+            buffer,
+            memory: allocation.memory,
+            size,
+            buffer_type,
+            mapped_ptr: allocation.mapped_ptr,
+            suballocation: Some((allocator.clone(), allocation)),
+        })
+    }
+    
+    /// Get usage and memory flags for a buffer type
+    fn usage_and_flags(buffer_type: BufferType) -> (vk::BufferUsageFlags, vk::MemoryPropertyFlags) {
+        match buffer_type {
             BufferType::Vertex => (
                 vk::BufferUsageFlags::VERTEX_BUFFER | vk::BufferUsageFlags::TRANSFER_DST,
                 vk::MemoryPropertyFlags::DEVICE_LOCAL,
@@ -66,9 +117,15 @@ impl Buffer {
                 vk::BufferUsageFlags::TRANSFER_SRC,
                 vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT,
             ),
-        };
-        
-        Self::create_buffer(device, size, usage, memory_flags, buffer_type)
+            BufferType::Indirect => (
+                vk::BufferUsageFlags::STORAGE_BUFFER | vk::BufferUsageFlags::INDIRECT_BUFFER | vk::BufferUsageFlags::TRANSFER_DST,
+                vk::MemoryPropertyFlags::DEVICE_LOCAL,
+            ),
+            BufferType::Readback => (
+                vk::BufferUsageFlags::STORAGE_BUFFER | vk::BufferUsageFlags::TRANSFER_DST,
+                vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT,
+            ),
+        }
     }
     
     /// Create buffer with specific usage and memory flags
@@ -102,10 +159,7 @@ impl Buffer {
             .allocation_size(mem_requirements.size)
             .memory_type_index(mem_type);
         
-        let memory = unsafe {
-            device.handle().allocate_memory(&alloc_info, None)
-                .map_err(|e| VulkanError::BufferCreationFailed(format!("Failed to allocate memory: {:?}", e)))?
-        };
+        let memory = device.allocate_memory(&alloc_info)?;
         
         // Bind memory to buffer
         unsafe {
@@ -113,7 +167,7 @@ impl Buffer {
                 .map_err(|e| VulkanError::BufferCreationFailed(format!("Failed to bind memory: {:?}", e)))?;
         }
         
-        // Persistently map uniform and staging buffers
+        // Persistently map uniform, staging and readback buffers
         let mapped_ptr = if memory_flags.contains(vk::MemoryPropertyFlags::HOST_VISIBLE) {
             unsafe {
                 let ptr = device.handle().map_memory(memory, 0, size, vk::MemoryMapFlags::empty())
@@ -131,6 +185,7 @@ impl Buffer {
             size,
             buffer_type,
             mapped_ptr,
+            suballocation: None,
         })
     }
     
@@ -191,16 +246,25 @@ impl Buffer {
     pub fn mapped_ptr(&self) -> Option<*mut std::ffi::c_void> {
         self.mapped_ptr
     }
+    
+    /// Check if the buffer lives in a shared allocator block
+    pub fn is_suballocated(&self) -> bool {
+        self.suballocation.is_some()
+    }
 }
 
 impl Drop for Buffer {
     fn drop(&mut self) {
         unsafe {
+            self.device.handle().destroy_buffer(self.buffer, None);
+            if let Some((allocator, allocation)) = &self.suballocation {
+                allocator.free(allocation);
+                return;
+            }
             if self.mapped_ptr.is_some() {
                 self.device.handle().unmap_memory(self.memory);
             }
-            self.device.handle().destroy_buffer(self.buffer, None);
-            self.device.handle().free_memory(self.memory, None);
+            self.device.free_memory(self.memory);
         }
     }
 }
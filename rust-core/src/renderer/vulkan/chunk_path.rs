@@ -0,0 +1,387 @@
+//! # Chunk Render Paths
+//!
+//! Selects how chunk meshes reach the GPU. Devices with VK_EXT_mesh_shader
+//! use the meshlet pipeline; everything else falls back to classic
+//! vertex/index buffers drawn with vkCmdDrawIndexed.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use ash::vk;
+
+use super::{VulkanDevice, VulkanError, Buffer, BufferType, ChunkVertexFormat, CommandPool};
+use super::mesh_shader::{MeshShaderPipeline, MeshVertex, Meshlet, ChunkMeshData};
+
+/// Chunk render path kind
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChunkPathKind {
+    /// Meshlets drawn by task/mesh shaders
+    MeshShader,
+    /// Vertex + index buffers drawn with vkCmdDrawIndexed
+    Traditional,
+}
+
+impl ChunkPathKind {
+    /// Pick a path from the device capability flag
+    pub fn select(mesh_shaders_supported: bool) -> Self {
+        if mesh_shaders_supported {
+            ChunkPathKind::MeshShader
+        } else {
+            ChunkPathKind::Traditional
+        }
+    }
+
+    /// Convert a chunk mesh into the form this path uploads
+    pub fn prepare(&self, vertices: &[MeshVertex], indices: &[u32]) -> Result<PreparedChunkMesh, VulkanError> {
+        if !indices.len().is_multiple_of(3) {
+            return Err(VulkanError::BufferCreationFailed(format!(
+                "Index count {} is not a multiple of 3", indices.len()
+            )));
+        }
+        if let Some(&bad) = indices.iter().find(|&&i| i as usize >= vertices.len()) {
+            return Err(VulkanError::BufferCreationFailed(format!(
+                "Index {} out of range for {} vertices", bad, vertices.len()
+            )));
+        }
+
+        match self {
+            ChunkPathKind::MeshShader => {
+                let (meshlets, vertices, primitives) = MeshShaderPipeline::build_meshlets(vertices, indices);
+                Ok(PreparedChunkMesh::Meshlets { meshlets, vertices, primitives })
+            }
+            ChunkPathKind::Traditional => Ok(PreparedChunkMesh::Indexed {
+                vertices: vertices.to_vec(),
+                indices: indices.to_vec(),
+            }),
+        }
+    }
+}
+
+/// Chunk mesh converted for a specific render path
+#[derive(Debug, Clone)]
+pub enum PreparedChunkMesh {
+    /// Meshlet data for the mesh shader path
+    Meshlets {
+        meshlets: Vec<Meshlet>,
+        vertices: Vec<MeshVertex>,
+        primitives: Vec<u8>,
+    },
+    /// Plain vertex/index data for the traditional path
+    Indexed {
+        vertices: Vec<MeshVertex>,
+        indices: Vec<u32>,
+    },
+}
+
+impl PreparedChunkMesh {
+    /// Get triangle count
+    pub fn triangle_count(&self) -> usize {
+        match self {
+            PreparedChunkMesh::Meshlets { primitives, .. } => primitives.len() / 3,
+            PreparedChunkMesh::Indexed { indices, .. } => indices.len() / 3,
+        }
+    }
+}
+
+/// Chunk render path interface
+pub trait ChunkRenderPath: Send {
+    /// Get the path kind
+    fn kind(&self) -> ChunkPathKind;
+
+    /// Upload a meshed chunk into the given slot
+    fn upload_chunk(
+        &mut self,
+        chunk_index: usize,
+        chunk_pos: [f32; 3],
+        vertices: &[MeshVertex],
+        indices: &[u32],
+    ) -> Result<(), VulkanError>;
+
+    /// Remove a chunk from the path
+    fn remove_chunk(&mut self, chunk_index: usize);
+
+    /// Record draw commands for all uploaded chunks
+    fn record_draw(&self, cmd: vk::CommandBuffer, descriptor_set: vk::DescriptorSet);
+
+    /// Get uploaded chunk count
+    fn chunk_count(&self) -> usize;
+}
+
+/// Create the best chunk render path for a device
+///
+/// `vertex_pipeline` must take `vertex_format` input; it is only used when
+/// falling back to the traditional path.
+pub fn create_chunk_render_path(
+    device: Arc<VulkanDevice>,
+    render_pass: vk::RenderPass,
+    vertex_pipeline: vk::Pipeline,
+    layout: vk::PipelineLayout,
+    vertex_format: ChunkVertexFormat,
+    max_chunks: usize,
+) -> Result<Box<dyn ChunkRenderPath>, VulkanError> {
+    if ChunkPathKind::select(device.supports_mesh_shaders()) == ChunkPathKind::MeshShader {
+        match MeshShaderPipeline::new(device.clone(), render_pass, max_chunks) {
+            Ok(pipeline) => {
+                log::info!("Chunk render path: mesh shaders");
+                return Ok(Box::new(MeshShaderPath::new(pipeline)));
+            }
+            Err(e) => log::warn!("Mesh shader path unavailable ({}), falling back to indexed draws", e),
+        }
+    }
+
+    log::info!("Chunk render path: traditional indexed draws ({:?} vertices)", vertex_format);
+    Ok(Box::new(TraditionalPath::new(device, vertex_pipeline, layout, vertex_format, max_chunks)?))
+}
+
+/// Mesh shader chunk path
+pub struct MeshShaderPath {
+    /// Underlying meshlet pipeline
+    pipeline: MeshShaderPipeline,
+}
+
+impl MeshShaderPath {
+    /// Wrap an existing mesh shader pipeline
+    pub fn new(pipeline: MeshShaderPipeline) -> Self {
+        Self { pipeline }
+    }
+}
+
+impl ChunkRenderPath for MeshShaderPath {
+    fn kind(&self) -> ChunkPathKind {
+        ChunkPathKind::MeshShader
+    }
+
+    fn upload_chunk(
+        &mut self,
+        chunk_index: usize,
+        chunk_pos: [f32; 3],
+        vertices: &[MeshVertex],
+        indices: &[u32],
+    ) -> Result<(), VulkanError> {
+        let PreparedChunkMesh::Meshlets { meshlets, vertices, primitives } =
+            ChunkPathKind::MeshShader.prepare(vertices, indices)?
+        else {
+            unreachable!();
+        };
+
+        let chunk_data = ChunkMeshData {
+            chunk_pos: [chunk_pos[0], chunk_pos[1], chunk_pos[2], 0.0],
+            meshlet_count: meshlets.len() as u32,
+            ..Default::default()
+        };
+
+        self.pipeline.upload_chunk(chunk_index, &meshlets, &vertices, &primitives, &chunk_data)
+    }
+
+    fn remove_chunk(&mut self, chunk_index: usize) {
+        self.pipeline.remove_chunk(chunk_index);
+    }
+
+    fn record_draw(&self, cmd: vk::CommandBuffer, descriptor_set: vk::DescriptorSet) {
+        self.pipeline.record_draw(cmd, descriptor_set);
+    }
+
+    fn chunk_count(&self) -> usize {
+        self.pipeline.chunk_count()
+    }
+}
+
+/// GPU buffers for one chunk on the traditional path
+struct IndexedChunk {
+    /// Vertex buffer
+    vertex_buffer: Buffer,
+    /// Index buffer
+    index_buffer: Buffer,
+    /// Number of indices
+    index_count: u32,
+    /// Chunk origin, pushed as a constant before drawing
+    chunk_pos: [f32; 4],
+}
+
+/// Traditional vertex/index buffer chunk path
+pub struct TraditionalPath {
+    /// Device reference
+    device: Arc<VulkanDevice>,
+    /// Graphics pipeline taking `vertex_format` input
+    pipeline: vk::Pipeline,
+    /// Pipeline layout
+    layout: vk::PipelineLayout,
+    /// Layout vertices are uploaded in
+    vertex_format: ChunkVertexFormat,
+    /// Command pool for staging copies
+    upload_pool: CommandPool,
+    /// Uploaded chunks by slot
+    chunks: HashMap<usize, IndexedChunk>,
+    /// Maximum chunks
+    max_chunks: usize,
+}
+
+// Safety: CommandPool is only touched from the thread that owns the path
+unsafe impl Send for TraditionalPath {}
+
+impl TraditionalPath {
+    /// Create a new traditional chunk path
+    pub fn new(
+        device: Arc<VulkanDevice>,
+        pipeline: vk::Pipeline,
+        layout: vk::PipelineLayout,
+        vertex_format: ChunkVertexFormat,
+        max_chunks: usize,
+    ) -> Result<Self, VulkanError> {
+        let upload_pool = CommandPool::new(device.clone())?;
+
+        Ok(Self {
+            device,
+            pipeline,
+            layout,
+            vertex_format,
+            upload_pool,
+            chunks: HashMap::new(),
+            max_chunks,
+        })
+    }
+
+    /// Copy data into a device-local buffer through a staging buffer
+    fn upload_device_local(&mut self, bytes: &[u8], buffer_type: BufferType) -> Result<Buffer, VulkanError> {
+        let size = bytes.len() as vk::DeviceSize;
+        let staging = Buffer::new(self.device.clone(), size, BufferType::Staging)?;
+        staging.write_bytes(bytes)?;
+
+        let target = Buffer::new(self.device.clone(), size, buffer_type)?;
+
+        let cmd = self.upload_pool.begin_single_time()?;
+        let region = vk::BufferCopy::default().size(size);
+        unsafe {
+            self.device.handle().cmd_copy_buffer(cmd, staging.handle(), target.handle(), &[region]);
+        }
+        self.upload_pool.end_single_time(cmd)?;
+
+        Ok(target)
+    }
+}
+
+impl ChunkRenderPath for TraditionalPath {
+    fn kind(&self) -> ChunkPathKind {
+        ChunkPathKind::Traditional
+    }
+
+    fn upload_chunk(
+        &mut self,
+        chunk_index: usize,
+        chunk_pos: [f32; 3],
+        vertices: &[MeshVertex],
+        indices: &[u32],
+    ) -> Result<(), VulkanError> {
+        if chunk_index >= self.max_chunks {
+            return Err(VulkanError::BufferCreationFailed("Chunk index out of range".to_string()));
+        }
+
+        let PreparedChunkMesh::Indexed { vertices, indices } =
+            ChunkPathKind::Traditional.prepare(vertices, indices)?
+        else {
+            unreachable!();
+        };
+
+        if indices.is_empty() {
+            self.chunks.remove(&chunk_index);
+            return Ok(());
+        }
+
+        let vertex_bytes = self.vertex_format.encode(&vertices).map_err(VulkanError::BufferCreationFailed)?;
+        let index_bytes = unsafe {
+            std::slice::from_raw_parts(
+                indices.as_ptr() as *const u8,
+                std::mem::size_of_val(indices.as_slice()),
+            )
+        };
+
+        let vertex_buffer = self.upload_device_local(&vertex_bytes, BufferType::Vertex)?;
+        let index_buffer = self.upload_device_local(index_bytes, BufferType::Index)?;
+
+        self.chunks.insert(chunk_index, IndexedChunk {
+            vertex_buffer,
+            index_buffer,
+            index_count: indices.len() as u32,
+            chunk_pos: [chunk_pos[0], chunk_pos[1], chunk_pos[2], 0.0],
+        });
+
+        Ok(())
+    }
+
+    fn remove_chunk(&mut self, chunk_index: usize) {
+        self.chunks.remove(&chunk_index);
+    }
+
+    fn record_draw(&self, cmd: vk::CommandBuffer, descriptor_set: vk::DescriptorSet) {
+        if self.pipeline == vk::Pipeline::null() || self.chunks.is_empty() {
+            return;
+        }
+
+        let device = self.device.handle();
+        unsafe {
+            device.cmd_bind_pipeline(cmd, vk::PipelineBindPoint::GRAPHICS, self.pipeline);
+            device.cmd_bind_descriptor_sets(
+                cmd,
+                vk::PipelineBindPoint::GRAPHICS,
+                self.layout,
+                0,
+                &[descriptor_set],
+                &[],
+            );
+
+            for chunk in self.chunks.values() {
+                let pos_bytes = std::slice::from_raw_parts(
+                    chunk.chunk_pos.as_ptr() as *const u8,
+                    std::mem::size_of_val(&chunk.chunk_pos),
+                );
+                device.cmd_push_constants(
+                    cmd,
+                    self.layout,
+                    vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT,
+                    0,
+                    pos_bytes,
+                );
+                device.cmd_bind_vertex_buffers(cmd, 0, &[chunk.vertex_buffer.handle()], &[0]);
+                device.cmd_bind_index_buffer(cmd, chunk.index_buffer.handle(), 0, vk::IndexType::UINT32);
+                device.cmd_draw_indexed(cmd, chunk.index_count, 1, 0, 0, 0);
+            }
+        }
+    }
+
+    fn chunk_count(&self) -> usize {
+        self.chunks.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::mesh_shader::{ChunkMesher, ChunkNeighbors};
+
+    fn sample_mesh() -> (Vec<MeshVertex>, Vec<u32>) {
+        let mut blocks = [0u16; 4096];
+        for x in 0..4 {
+            blocks[x] = 1;
+        }
+        ChunkMesher::new().mesh_section(&blocks, 0, &ChunkNeighbors::default())
+    }
+
+    #[test]
+    fn test_path_selection() {
+        assert_eq!(ChunkPathKind::select(true), ChunkPathKind::MeshShader);
+        assert_eq!(ChunkPathKind::select(false), ChunkPathKind::Traditional);
+    }
+
+    #[test]
+    fn test_both_paths_accept_same_mesh() {
+        let (vertices, indices) = sample_mesh();
+        assert!(!indices.is_empty());
+
+        for supported in [true, false] {
+            let kind = ChunkPathKind::select(supported);
+            let prepared = kind.prepare(&vertices, &indices).unwrap();
+            assert_eq!(prepared.triangle_count(), indices.len() / 3);
+        }
+
+        assert!(ChunkPathKind::Traditional.prepare(&vertices, &[0, 1]).is_err());
+    }
+}
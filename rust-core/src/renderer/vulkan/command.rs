@@ -59,6 +59,17 @@ impl CommandPool {
         Ok(buffers[0])
     }
     
+    /// Free command buffers allocated from this pool
+    pub fn free_command_buffers(&mut self, buffers: &[vk::CommandBuffer]) {
+        if buffers.is_empty() {
+            return;
+        }
+        self.command_buffers.retain(|cmd| !buffers.contains(cmd));
+        unsafe {
+            self.device.handle().free_command_buffers(self.pool, buffers);
+        }
+    }
+    
     /// Begin single-time command buffer
     pub fn begin_single_time(&mut self) -> Result<vk::CommandBuffer, VulkanError> {
         let cmd = self.allocate_command_buffer()?;
@@ -87,10 +98,16 @@ impl CommandPool {
         
         unsafe {
             self.device.handle().queue_submit(self.device.graphics_queue(), &[submit_info], vk::Fence::null())
-                .map_err(|e| VulkanError::CommandBufferError(format!("Failed to submit command buffer: {:?}", e)))?;
+                .map_err(|e| match e {
+                    vk::Result::ERROR_DEVICE_LOST => self.device.watchdog().device_lost("single-time submit"),
+                    _ => VulkanError::CommandBufferError(format!("Failed to submit command buffer: {:?}", e)),
+                })?;
             
             self.device.handle().queue_wait_idle(self.device.graphics_queue())
-                .map_err(|e| VulkanError::CommandBufferError(format!("Failed to wait for queue: {:?}", e)))?;
+                .map_err(|e| match e {
+                    vk::Result::ERROR_DEVICE_LOST => self.device.watchdog().device_lost("single-time submit"),
+                    _ => VulkanError::CommandBufferError(format!("Failed to wait for queue: {:?}", e)),
+                })?;
             
             self.device.handle().free_command_buffers(self.pool, &command_buffers);
         }
@@ -176,6 +193,38 @@ impl<'a> CommandRecorder<'a> {
         }
     }
     
+    /// Begin render pass whose contents come from secondary command buffers
+    pub fn begin_render_pass_secondary(
+        &self,
+        render_pass: vk::RenderPass,
+        framebuffer: vk::Framebuffer,
+        extent: vk::Extent2D,
+        clear_values: &[vk::ClearValue],
+    ) {
+        let render_pass_info = vk::RenderPassBeginInfo::default()
+            .render_pass(render_pass)
+            .framebuffer(framebuffer)
+            .render_area(vk::Rect2D {
+                offset: vk::Offset2D { x: 0, y: 0 },
+                extent,
+            })
+            .clear_values(clear_values);
+        
+        unsafe {
+            self.device.handle().cmd_begin_render_pass(self.cmd, &render_pass_info, vk::SubpassContents::SECONDARY_COMMAND_BUFFERS);
+        }
+    }
+    
+    /// Execute secondary command buffers
+    pub fn execute_commands(&self, secondaries: &[vk::CommandBuffer]) {
+        if secondaries.is_empty() {
+            return;
+        }
+        unsafe {
+            self.device.handle().cmd_execute_commands(self.cmd, secondaries);
+        }
+    }
+    
     /// End render pass
     pub fn end_render_pass(&self) {
         unsafe {
@@ -4,9 +4,15 @@
 
 use std::ffi::{CStr, CString};
 use std::sync::Arc;
+use std::time::Duration;
 use ash::{vk, Device};
 
 use super::{VulkanConfig, VulkanError, VulkanInstance};
+use super::memory::GpuMemoryTracker;
+use super::watchdog::GpuWatchdog;
+use super::sampler::{SamplerCache, SamplerDesc};
+use super::features::{FeatureReport, OptionalFeature};
+use crate::events::EventQueue;
 
 /// Required device extensions
 const REQUIRED_DEVICE_EXTENSIONS: &[&str] = &[
@@ -18,6 +24,9 @@ const MESH_SHADER_EXTENSIONS: &[&str] = &[
     "VK_EXT_mesh_shader",
 ];
 
+/// Optional device extension for per-heap budgets
+const MEMORY_BUDGET_EXTENSION: &str = "VK_EXT_memory_budget";
+
 /// Optional device extensions for ray tracing
 const RAY_TRACING_EXTENSIONS: &[&str] = &[
     "VK_KHR_acceleration_structure",
@@ -64,15 +73,34 @@ pub struct VulkanDevice {
     features: vk::PhysicalDeviceFeatures,
     /// Memory properties
     memory_properties: vk::PhysicalDeviceMemoryProperties,
-    /// Mesh shader support
-    mesh_shaders_supported: bool,
-    /// Ray tracing support
-    ray_tracing_supported: bool,
+    /// Optional features supported and enabled
+    feature_report: FeatureReport,
+    /// Shared sampler cache
+    samplers: SamplerCache,
+    /// Configured anisotropic filtering level
+    anisotropy_level: u32,
+    /// Device memory accounting
+    memory_tracker: GpuMemoryTracker,
+    /// VK_EXT_memory_budget enabled
+    memory_budget_supported: bool,
+    /// Engine events raised by the device (memory pressure, hangs)
+    events: EventQueue,
+    /// Timeout-bounded waits
+    watchdog: Arc<GpuWatchdog>,
 }
 
 impl VulkanDevice {
     /// Create a new Vulkan device
     pub fn new(instance: Arc<VulkanInstance>, config: &VulkanConfig) -> Result<Self, VulkanError> {
+        Self::with_watchdog(instance, config, Arc::new(GpuWatchdog::new(EventQueue::new())))
+    }
+    
+    /// Create a Vulkan device whose waits report to `watchdog` (events go to its queue)
+    pub fn with_watchdog(
+        instance: Arc<VulkanInstance>,
+        config: &VulkanConfig,
+        watchdog: Arc<GpuWatchdog>,
+    ) -> Result<Self, VulkanError> {
         // Enumerate physical devices
         let physical_devices = instance.enumerate_physical_devices()?;
         
@@ -81,7 +109,7 @@ impl VulkanDevice {
         }
         
         // Select best physical device
-        let (physical_device, queue_families, mesh_supported, rt_supported) = 
+        let (physical_device, queue_families, mesh_available, rt_available) = 
             Self::select_physical_device(&instance, &physical_devices, config)?;
         
         // Get device properties
@@ -89,24 +117,48 @@ impl VulkanDevice {
         let features = instance.get_physical_device_features(physical_device);
         let memory_properties = instance.get_physical_device_memory_properties(physical_device);
         
+        // Decide optional features once; everything else reads the report
+        let mut supported11 = vk::PhysicalDeviceVulkan11Features::default();
+        let mut supported12 = vk::PhysicalDeviceVulkan12Features::default();
+        let mut supported = vk::PhysicalDeviceFeatures2::default()
+            .push_next(&mut supported11)
+            .push_next(&mut supported12);
+        instance.get_physical_device_features2(physical_device, &mut supported);
+        let feature_report = FeatureReport::from_vk(
+            &features, &supported11, &supported12, mesh_available, rt_available, config,
+        );
+        let mesh_supported = feature_report.is_enabled(OptionalFeature::MeshShaders);
+        let rt_supported = feature_report.is_enabled(OptionalFeature::RayTracing);
+        let bindless = feature_report.is_enabled(OptionalFeature::DescriptorIndexing);
+        
         // Build extension list
         let mut extensions: Vec<CString> = REQUIRED_DEVICE_EXTENSIONS
             .iter()
             .map(|e| CString::new(*e).unwrap())
             .collect();
         
-        if mesh_supported && config.mesh_shaders_enabled {
+        if mesh_supported {
             for ext in MESH_SHADER_EXTENSIONS {
                 extensions.push(CString::new(*ext).unwrap());
             }
         }
         
-        if rt_supported && config.ray_tracing_enabled {
+        if rt_supported {
             for ext in RAY_TRACING_EXTENSIONS {
                 extensions.push(CString::new(*ext).unwrap());
             }
         }
         
+        let memory_budget_supported = instance.enumerate_device_extension_properties(physical_device)?
+            .iter()
+            .any(|ext| {
+                let name = unsafe { CStr::from_ptr(ext.extension_name.as_ptr()) };
+                name.to_str() == Ok(MEMORY_BUDGET_EXTENSION)
+            });
+        if memory_budget_supported {
+            extensions.push(CString::new(MEMORY_BUDGET_EXTENSION).unwrap());
+        }
+        
         let extension_ptrs: Vec<*const i8> = extensions.iter().map(|e| e.as_ptr()).collect();
         
         // Create queue create infos
@@ -140,18 +192,24 @@ impl VulkanDevice {
         // Device features
         let device_features = vk::PhysicalDeviceFeatures::default()
             .sampler_anisotropy(true)
-            .fill_mode_non_solid(true)
+            .fill_mode_non_solid(feature_report.is_enabled(OptionalFeature::FillModeNonSolid))
             .wide_lines(true)
-            .multi_draw_indirect(true);
+            .multi_draw_indirect(feature_report.is_enabled(OptionalFeature::MultiDrawIndirect));
+        
+        // Vulkan 1.1 features
+        let mut vulkan11_features = vk::PhysicalDeviceVulkan11Features::default()
+            .storage_buffer16_bit_access(feature_report.is_enabled(OptionalFeature::Storage16Bit));
         
         // Vulkan 1.2 features
         let mut vulkan12_features = vk::PhysicalDeviceVulkan12Features::default()
             .buffer_device_address(true)
-            .descriptor_indexing(true)
-            .runtime_descriptor_array(true)
-            .descriptor_binding_partially_bound(true)
-            .descriptor_binding_variable_descriptor_count(true)
-            .shader_sampled_image_array_non_uniform_indexing(true);
+            .descriptor_indexing(bindless)
+            .runtime_descriptor_array(bindless)
+            .descriptor_binding_partially_bound(bindless)
+            .descriptor_binding_variable_descriptor_count(bindless)
+            .shader_sampled_image_array_non_uniform_indexing(bindless)
+            .timeline_semaphore(feature_report.is_enabled(OptionalFeature::TimelineSemaphores))
+            .draw_indirect_count(feature_report.is_enabled(OptionalFeature::DrawIndirectCount));
         
         // Vulkan 1.3 features
         let mut vulkan13_features = vk::PhysicalDeviceVulkan13Features::default()
@@ -161,18 +219,19 @@ impl VulkanDevice {
         
         // Mesh shader features
         let mut mesh_shader_features = vk::PhysicalDeviceMeshShaderFeaturesEXT::default()
-            .mesh_shader(mesh_supported && config.mesh_shaders_enabled)
-            .task_shader(mesh_supported && config.mesh_shaders_enabled);
+            .mesh_shader(mesh_supported)
+            .task_shader(mesh_supported);
         
         // Chain features
         let mut create_info = vk::DeviceCreateInfo::default()
             .queue_create_infos(&queue_create_infos)
             .enabled_extension_names(&extension_ptrs)
             .enabled_features(&device_features)
+            .push_next(&mut vulkan11_features)
             .push_next(&mut vulkan12_features)
             .push_next(&mut vulkan13_features);
         
-        if mesh_supported && config.mesh_shaders_enabled {
+        if mesh_supported {
             create_info = create_info.push_next(&mut mesh_shader_features);
         }
         
@@ -188,6 +247,12 @@ impl VulkanDevice {
         let compute_queue = unsafe { device.get_device_queue(queue_families.compute.unwrap_or(queue_families.graphics.unwrap()), 0) };
         let transfer_queue = unsafe { device.get_device_queue(queue_families.transfer.unwrap_or(queue_families.graphics.unwrap()), 0) };
         
+        let events = watchdog.events().clone();
+        let memory_tracker = GpuMemoryTracker::new(&memory_properties, events.clone());
+        if memory_budget_supported {
+            memory_tracker.refresh_budgets(instance.handle(), physical_device);
+        }
+        
         Ok(Self {
             instance,
             physical_device,
@@ -200,8 +265,13 @@ impl VulkanDevice {
             properties,
             features,
             memory_properties,
-            mesh_shaders_supported: mesh_supported && config.mesh_shaders_enabled,
-            ray_tracing_supported: rt_supported && config.ray_tracing_enabled,
+            feature_report,
+            samplers: SamplerCache::new(properties.limits.max_sampler_anisotropy),
+            anisotropy_level: config.anisotropy_level,
+            memory_tracker,
+            memory_budget_supported,
+            events,
+            watchdog,
         })
     }
     
@@ -233,14 +303,14 @@ impl VulkanDevice {
             }
             
             // Check optional extensions
-            let has_mesh_shaders = config.mesh_shaders_enabled && MESH_SHADER_EXTENSIONS.iter().all(|required| {
+            let has_mesh_shaders = MESH_SHADER_EXTENSIONS.iter().all(|required| {
                 extensions.iter().any(|ext| {
                     let name = unsafe { CStr::from_ptr(ext.extension_name.as_ptr()) };
                     name.to_str().map(|s| s == *required).unwrap_or(false)
                 })
             });
             
-            let has_ray_tracing = config.ray_tracing_enabled && RAY_TRACING_EXTENSIONS.iter().all(|required| {
+            let has_ray_tracing = RAY_TRACING_EXTENSIONS.iter().all(|required| {
                 extensions.iter().any(|ext| {
                     let name = unsafe { CStr::from_ptr(ext.extension_name.as_ptr()) };
                     name.to_str().map(|s| s == *required).unwrap_or(false)
@@ -267,10 +337,10 @@ impl VulkanDevice {
             if features.tessellation_shader == vk::TRUE {
                 score += 100;
             }
-            if has_mesh_shaders {
+            if has_mesh_shaders && config.mesh_shaders_enabled {
                 score += 500;
             }
-            if has_ray_tracing {
+            if has_ray_tracing && config.ray_tracing_enabled {
                 score += 500;
             }
             
@@ -339,14 +409,24 @@ impl VulkanDevice {
         name.to_string_lossy().into_owned()
     }
     
-    /// Check mesh shader support
+    /// Get which optional features are supported and enabled
+    pub fn feature_report(&self) -> &FeatureReport {
+        &self.feature_report
+    }
+    
+    /// Check if mesh shaders are enabled
     pub fn supports_mesh_shaders(&self) -> bool {
-        self.mesh_shaders_supported
+        self.feature_report.is_enabled(OptionalFeature::MeshShaders)
+    }
+    
+    /// Check if wireframe (`fillModeNonSolid`) is enabled
+    pub fn supports_wireframe(&self) -> bool {
+        self.feature_report.is_enabled(OptionalFeature::FillModeNonSolid)
     }
     
-    /// Check ray tracing support
+    /// Check if ray tracing is enabled
     pub fn supports_ray_tracing(&self) -> bool {
-        self.ray_tracing_supported
+        self.feature_report.is_enabled(OptionalFeature::RayTracing)
     }
     
     /// Get logical device handle
@@ -399,11 +479,105 @@ impl VulkanDevice {
         &self.properties
     }
     
+    /// Get device limits
+    pub fn limits(&self) -> &vk::PhysicalDeviceLimits {
+        &self.properties.limits
+    }
+    
+    /// Create a pipeline layout after checking its push constants fit `maxPushConstantsSize`
+    pub fn create_pipeline_layout(&self, info: &vk::PipelineLayoutCreateInfo) -> Result<vk::PipelineLayout, VulkanError> {
+        let ranges: &[vk::PushConstantRange] = if info.push_constant_range_count == 0 {
+            &[]
+        } else {
+            unsafe { std::slice::from_raw_parts(info.p_push_constant_ranges, info.push_constant_range_count as usize) }
+        };
+        super::pipeline::validate_push_constants(ranges, self.limits().max_push_constants_size)?;
+        
+        unsafe {
+            self.device.create_pipeline_layout(info, None)
+                .map_err(|e| VulkanError::PipelineCreationFailed(format!("Failed to create pipeline layout: {:?}", e)))
+        }
+    }
+    
     /// Get instance reference
     pub fn instance(&self) -> &Arc<VulkanInstance> {
         &self.instance
     }
     
+    /// Get (or create) a cached sampler
+    pub fn sampler(&self, desc: SamplerDesc) -> Result<vk::Sampler, VulkanError> {
+        self.samplers.get(&self.device, desc)
+    }
+    
+    /// Get sampler cache
+    pub fn sampler_cache(&self) -> &SamplerCache {
+        &self.samplers
+    }
+    
+    /// Get configured anisotropy level (before device clamping)
+    pub fn anisotropy_level(&self) -> u32 {
+        self.anisotropy_level
+    }
+    
+    /// Get device memory accounting
+    pub fn memory_tracker(&self) -> &GpuMemoryTracker {
+        &self.memory_tracker
+    }
+    
+    /// Get the queue memory pressure and hang events are raised on
+    pub fn events(&self) -> &EventQueue {
+        &self.events
+    }
+    
+    /// Get the GPU hang watchdog
+    pub fn watchdog(&self) -> &Arc<GpuWatchdog> {
+        &self.watchdog
+    }
+    
+    /// Set how long GPU waits may take before they count as a hang
+    pub fn set_gpu_timeout(&self, timeout: Duration) {
+        self.watchdog.set_timeout(timeout);
+    }
+    
+    /// Wait for fences, bounded by the watchdog timeout
+    pub fn wait_for_fences(&self, fences: &[vk::Fence], operation: &str) -> Result<(), VulkanError> {
+        self.watchdog.wait_for_fences(&self.device, fences, operation)
+    }
+    
+    /// Re-read heap budgets (no-op without VK_EXT_memory_budget)
+    pub fn refresh_memory_budget(&self) {
+        if self.memory_budget_supported {
+            self.memory_tracker.refresh_budgets(self.instance.handle(), self.physical_device);
+        }
+    }
+    
+    /// Allocate device memory and record it in the tracker
+    pub fn allocate_memory(&self, info: &vk::MemoryAllocateInfo) -> Result<vk::DeviceMemory, VulkanError> {
+        match unsafe { self.device.allocate_memory(info, None) } {
+            Ok(memory) => {
+                self.memory_tracker.record_alloc(memory, info.memory_type_index, info.allocation_size);
+                Ok(memory)
+            }
+            Err(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY | vk::Result::ERROR_OUT_OF_HOST_MEMORY) => {
+                self.memory_tracker.record_failure(info.memory_type_index, info.allocation_size);
+                Err(VulkanError::OutOfDeviceMemory {
+                    heap: self.memory_tracker.heap_of_type(info.memory_type_index).unwrap_or(0),
+                    requested: info.allocation_size,
+                })
+            }
+            Err(e) => Err(VulkanError::VkError(format!("vkAllocateMemory failed: {:?}", e))),
+        }
+    }
+    
+    /// Free device memory from `allocate_memory`
+    ///
+    /// # Safety
+    /// `memory` must not be in use by the GPU or bound to live resources.
+    pub unsafe fn free_memory(&self, memory: vk::DeviceMemory) {
+        self.memory_tracker.record_free(memory);
+        self.device.free_memory(memory, None);
+    }
+    
     /// Find memory type index
     pub fn find_memory_type(&self, type_filter: u32, properties: vk::MemoryPropertyFlags) -> Option<u32> {
         for i in 0..self.memory_properties.memory_type_count {
@@ -418,6 +592,7 @@ impl VulkanDevice {
 
 impl Drop for VulkanDevice {
     fn drop(&mut self) {
+        self.samplers.destroy(&self.device);
         unsafe {
             self.device.destroy_device(None);
         }
@@ -0,0 +1,538 @@
+//! # GPU-Driven Entity Draws
+//!
+//! Entity rendering without per-entity CPU work once instances are
+//! uploaded. Every instance (transform, bounding sphere, mesh and material)
+//! sits in a storage buffer. On the host, instances are grouped by material
+//! and then mesh: each (material, mesh) pair gets one indexed indirect
+//! command and a private range of the visible-instance buffer, and each
+//! material gets a contiguous run of commands. A compute pass culls one
+//! instance per invocation, appends survivors to their command's range by
+//! bumping its `instance_count`, and raises the material's draw count to
+//! cover the command. Drawing is then one vkCmdDrawIndexedIndirectCount
+//! per material; the vertex shader finds its instance through
+//! `visible[gl_InstanceIndex]`.
+//!
+//! `draw_commands_cpu` runs the same culling and counting on the host as a
+//! reference.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use ash::vk;
+use glam::{Mat4, Vec4};
+
+use super::barrier::{memory_barrier, Transition};
+use super::features::OptionalFeature;
+use super::gpu_cull::{dispatch_size, frustum_planes, sphere_visible};
+use super::{Buffer, BufferType, VulkanDevice, VulkanError};
+
+/// Culling and compaction kernel; instances, commands, counts and visible
+/// indices at bindings 0-3
+pub const ENTITY_CULL_SHADER: &str = r#"#version 450
+layout(local_size_x = 64) in;
+
+struct Instance { mat4 transform; vec4 sphere; uint mesh_id; uint material_id; uint draw_command; uint material_slot; uint batch_draw; };
+struct DrawCommand { uint index_count; uint instance_count; uint first_index; int vertex_offset; uint first_instance; };
+
+layout(push_constant) uniform Params { mat4 view_proj; uint instance_count; uint pad0; uint pad1; uint pad2; } params;
+layout(std430, binding = 0) readonly buffer Instances { Instance instances[]; };
+layout(std430, binding = 1) buffer Commands { DrawCommand commands[]; };
+layout(std430, binding = 2) buffer Counts { uint draw_counts[]; };
+layout(std430, binding = 3) writeonly buffer Visible { uint visible[]; };
+
+bool visible_sphere(vec4 sphere) {
+    mat4 m = transpose(params.view_proj);
+    vec4 planes[6] = vec4[6](m[3] + m[0], m[3] - m[0], m[3] + m[1], m[3] - m[1], m[2], m[3] - m[2]);
+    for (int i = 0; i < 6; i++) {
+        vec4 p = planes[i];
+        if (dot(p.xyz, sphere.xyz) + p.w < -sphere.w * length(p.xyz)) return false;
+    }
+    return true;
+}
+
+void main() {
+    uint i = gl_GlobalInvocationID.x;
+    if (i >= params.instance_count) return;
+
+    Instance inst = instances[i];
+    if (!visible_sphere(inst.sphere)) return;
+
+    uint c = inst.draw_command;
+    uint slot = atomicAdd(commands[c].instance_count, 1u);
+    visible[commands[c].first_instance + slot] = i;
+    // Draw up to and including this command; commands before it may draw nothing
+    atomicMax(draw_counts[inst.material_slot], inst.batch_draw + 1u);
+}
+"#;
+
+/// Where a mesh lives in the shared vertex and index buffers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MeshRange {
+    pub index_count: u32,
+    pub first_index: u32,
+    pub vertex_offset: i32,
+}
+
+/// Per-instance data (GPU-side, std430)
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct GpuEntityInstance {
+    /// Column-major model matrix
+    pub transform: [[f32; 4]; 4],
+    /// World-space bounding sphere center (xyz) and radius (w)
+    pub sphere: [f32; 4],
+    /// Registered mesh
+    pub mesh_id: u32,
+    /// Material (pipeline and textures)
+    pub material_id: u32,
+    /// Command this instance is drawn by (set by `EntityDrawLayout::build`)
+    pub draw_command: u32,
+    /// Material batch index (set by `EntityDrawLayout::build`)
+    pub material_slot: u32,
+    /// Position of `draw_command` within its batch (set by `EntityDrawLayout::build`)
+    pub batch_draw: u32,
+    _pad: [u32; 3],
+}
+
+impl GpuEntityInstance {
+    /// Create an instance; `radius` bounds the mesh in model space
+    pub fn new(transform: Mat4, mesh_id: u32, material_id: u32, radius: f32) -> Self {
+        let (scale, _, translation) = transform.to_scale_rotation_translation();
+        Self {
+            transform: transform.to_cols_array_2d(),
+            sphere: translation.extend(radius * scale.abs().max_element()).to_array(),
+            mesh_id,
+            material_id,
+            draw_command: 0,
+            material_slot: 0,
+            batch_draw: 0,
+            _pad: [0; 3],
+        }
+    }
+}
+
+/// Indexed indirect command (`VkDrawIndexedIndirectCommand` layout)
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct EntityDrawCommand {
+    pub index_count: u32,
+    /// Visible instances (0 in the template, counted up by the kernel)
+    pub instance_count: u32,
+    pub first_index: u32,
+    pub vertex_offset: i32,
+    /// Start of this command's range in the visible-instance buffer
+    pub first_instance: u32,
+}
+
+/// Byte stride between indirect commands
+pub const ENTITY_COMMAND_STRIDE: u32 = std::mem::size_of::<EntityDrawCommand>() as u32;
+
+/// Push constants of the culling kernel
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct EntityCullParams {
+    /// Column-major view-projection
+    pub view_proj: [[f32; 4]; 4],
+    /// Instances in the instance buffer
+    pub instance_count: u32,
+    _pad: [u32; 3],
+}
+
+impl EntityCullParams {
+    /// Create push constants for a dispatch
+    pub fn new(view_proj: Mat4, instance_count: u32) -> Self {
+        Self { view_proj: view_proj.to_cols_array_2d(), instance_count, _pad: [0; 3] }
+    }
+}
+
+/// Commands drawn with one material
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MaterialBatch {
+    /// Material to bind before drawing
+    pub material_id: u32,
+    /// First command of the batch
+    pub first_command: u32,
+    /// Commands in the batch (the draw count's upper bound)
+    pub command_count: u32,
+}
+
+/// Instances grouped into per-material runs of per-mesh commands
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EntityDrawLayout {
+    /// Instances with `draw_command` and `material_slot` filled in
+    pub instances: Vec<GpuEntityInstance>,
+    /// Command templates (instance counts zero)
+    pub commands: Vec<EntityDrawCommand>,
+    /// Material batches in material id order
+    pub batches: Vec<MaterialBatch>,
+    /// Instances dropped because their mesh isn't registered
+    pub skipped: usize,
+}
+
+impl EntityDrawLayout {
+    /// Group instances by material, then mesh
+    pub fn build(instances: &[GpuEntityInstance], meshes: &HashMap<u32, MeshRange>) -> Self {
+        // BTreeMap keeps batches and commands in a stable order
+        let mut groups: BTreeMap<(u32, u32), Vec<GpuEntityInstance>> = BTreeMap::new();
+        let mut skipped = 0;
+        for instance in instances {
+            if meshes.contains_key(&instance.mesh_id) {
+                groups.entry((instance.material_id, instance.mesh_id)).or_default().push(*instance);
+            } else {
+                skipped += 1;
+            }
+        }
+
+        let mut layout = Self { skipped, ..Self::default() };
+        for ((material_id, mesh_id), members) in groups {
+            if layout.batches.last().map(|b| b.material_id) != Some(material_id) {
+                layout.batches.push(MaterialBatch {
+                    material_id,
+                    first_command: layout.commands.len() as u32,
+                    command_count: 0,
+                });
+            }
+            let slot = layout.batches.len() as u32 - 1;
+            let batch_draw = layout.batches[slot as usize].command_count;
+            layout.batches[slot as usize].command_count += 1;
+
+            let command = layout.commands.len() as u32;
+            let mesh = meshes[&mesh_id];
+            layout.commands.push(EntityDrawCommand {
+                index_count: mesh.index_count,
+                instance_count: 0,
+                first_index: mesh.first_index,
+                vertex_offset: mesh.vertex_offset,
+                first_instance: layout.instances.len() as u32,
+            });
+            layout.instances.extend(members.into_iter().map(|mut instance| {
+                instance.draw_command = command;
+                instance.material_slot = slot;
+                instance.batch_draw = batch_draw;
+                instance
+            }));
+        }
+        layout
+    }
+}
+
+/// Host reference of the kernel: commands with instance counts, and draw counts per batch
+pub fn draw_commands_cpu(layout: &EntityDrawLayout, view_proj: Mat4) -> (Vec<EntityDrawCommand>, Vec<u32>) {
+    let planes = frustum_planes(view_proj);
+    let mut commands = layout.commands.clone();
+    let mut counts = vec![0u32; layout.batches.len()];
+    for instance in &layout.instances {
+        if !sphere_visible(&planes, Vec4::from(instance.sphere)) {
+            continue;
+        }
+        let command = instance.draw_command as usize;
+        commands[command].instance_count += 1;
+        let count = &mut counts[instance.material_slot as usize];
+        *count = (*count).max(instance.batch_draw + 1);
+    }
+    (commands, counts)
+}
+
+/// Compute pass producing per-material indexed indirect draws for visible entities
+pub struct GpuEntityDrawPass {
+    /// Device reference
+    device: Arc<VulkanDevice>,
+    /// Descriptor set layout
+    set_layout: vk::DescriptorSetLayout,
+    /// Pipeline layout
+    layout: vk::PipelineLayout,
+    /// Culling pipeline
+    pipeline: vk::Pipeline,
+    /// Descriptor pool
+    descriptor_pool: vk::DescriptorPool,
+    /// Bindings 0-3
+    descriptor_set: vk::DescriptorSet,
+    /// Instances (binding 0)
+    instance_buffer: Buffer,
+    /// Host copy of the instances
+    instance_staging: Buffer,
+    /// Indirect commands (binding 1)
+    command_buffer: Buffer,
+    /// Command templates copied over the commands every frame
+    command_staging: Buffer,
+    /// Draw count per material batch (binding 2)
+    count_buffer: Buffer,
+    /// Visible instance indices (binding 3)
+    visible_buffer: Buffer,
+    /// Capacity in instances (also bounds commands)
+    max_instances: u32,
+    /// Capacity in material batches
+    max_batches: u32,
+    /// Material batches of the uploaded layout
+    batches: Vec<MaterialBatch>,
+    /// Instances uploaded
+    instance_count: u32,
+    /// Commands uploaded
+    command_count: u32,
+    /// Staging holds a layout not yet copied
+    dirty: bool,
+}
+
+impl GpuEntityDrawPass {
+    /// Create the pass from compiled `ENTITY_CULL_SHADER`
+    pub fn new(
+        device: Arc<VulkanDevice>,
+        spirv: &[u32],
+        max_instances: u32,
+        max_batches: u32,
+    ) -> Result<Self, VulkanError> {
+        if !device.feature_report().is_enabled(OptionalFeature::DrawIndirectCount) {
+            return Err(VulkanError::PipelineCreationFailed("drawIndirectCount not supported".to_string()));
+        }
+
+        let instance_size = max_instances as u64 * std::mem::size_of::<GpuEntityInstance>() as u64;
+        let command_size = max_instances as u64 * ENTITY_COMMAND_STRIDE as u64;
+        let instance_buffer = Buffer::new(device.clone(), instance_size, BufferType::Storage)?;
+        let instance_staging = Buffer::new(device.clone(), instance_size, BufferType::Staging)?;
+        let command_buffer = Buffer::new(device.clone(), command_size, BufferType::Indirect)?;
+        let command_staging = Buffer::new(device.clone(), command_size, BufferType::Staging)?;
+        let count_buffer = Buffer::new(device.clone(), max_batches.max(1) as u64 * 4, BufferType::Indirect)?;
+        let visible_buffer = Buffer::new(device.clone(), max_instances.max(1) as u64 * 4, BufferType::Storage)?;
+
+        let vk_device = device.handle();
+        let err = |what: &str, e: vk::Result| VulkanError::PipelineCreationFailed(format!("{}: {:?}", what, e));
+
+        unsafe {
+            let bindings = [0, 1, 2, 3].map(|binding| vk::DescriptorSetLayoutBinding::default()
+                .binding(binding)
+                .descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
+                .descriptor_count(1)
+                .stage_flags(vk::ShaderStageFlags::COMPUTE | vk::ShaderStageFlags::VERTEX));
+            let set_layout = vk_device.create_descriptor_set_layout(
+                &vk::DescriptorSetLayoutCreateInfo::default().bindings(&bindings), None,
+            ).map_err(|e| err("Failed to create entity cull set layout", e))?;
+
+            let set_layouts = [set_layout];
+            let push_constants = [vk::PushConstantRange::default()
+                .stage_flags(vk::ShaderStageFlags::COMPUTE)
+                .offset(0)
+                .size(std::mem::size_of::<EntityCullParams>() as u32)];
+            let layout = device.create_pipeline_layout(
+                &vk::PipelineLayoutCreateInfo::default()
+                    .set_layouts(&set_layouts)
+                    .push_constant_ranges(&push_constants),
+            )?;
+
+            let module = vk_device.create_shader_module(
+                &vk::ShaderModuleCreateInfo::default().code(spirv), None,
+            ).map_err(|e| err("Failed to create entity cull shader", e))?;
+            let stage = vk::PipelineShaderStageCreateInfo::default()
+                .stage(vk::ShaderStageFlags::COMPUTE)
+                .module(module)
+                .name(c"main");
+            let created = vk_device.create_compute_pipelines(
+                vk::PipelineCache::null(),
+                &[vk::ComputePipelineCreateInfo::default().stage(stage).layout(layout)],
+                None,
+            );
+            vk_device.destroy_shader_module(module, None);
+            let pipeline = created.map_err(|(_, e)| err("Failed to create entity cull pipeline", e))?[0];
+
+            let pool_sizes = [vk::DescriptorPoolSize { ty: vk::DescriptorType::STORAGE_BUFFER, descriptor_count: 4 }];
+            let descriptor_pool = vk_device.create_descriptor_pool(
+                &vk::DescriptorPoolCreateInfo::default().max_sets(1).pool_sizes(&pool_sizes),
+                None,
+            ).map_err(|e| err("Failed to create entity cull descriptor pool", e))?;
+            let descriptor_set = vk_device.allocate_descriptor_sets(
+                &vk::DescriptorSetAllocateInfo::default()
+                    .descriptor_pool(descriptor_pool)
+                    .set_layouts(&set_layouts),
+            ).map_err(|e| err("Failed to allocate entity cull descriptor set", e))?[0];
+
+            let infos = [&instance_buffer, &command_buffer, &count_buffer, &visible_buffer].map(|b| {
+                [vk::DescriptorBufferInfo { buffer: b.handle(), offset: 0, range: vk::WHOLE_SIZE }]
+            });
+            let writes: Vec<_> = infos.iter().enumerate().map(|(binding, info)| {
+                vk::WriteDescriptorSet::default()
+                    .dst_set(descriptor_set)
+                    .dst_binding(binding as u32)
+                    .descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
+                    .buffer_info(info)
+            }).collect();
+            vk_device.update_descriptor_sets(&writes, &[]);
+
+            Ok(Self {
+                device,
+                set_layout,
+                layout,
+                pipeline,
+                descriptor_pool,
+                descriptor_set,
+                instance_buffer,
+                instance_staging,
+                command_buffer,
+                command_staging,
+                count_buffer,
+                visible_buffer,
+                max_instances,
+                max_batches,
+                batches: Vec::new(),
+                instance_count: 0,
+                command_count: 0,
+                dirty: false,
+            })
+        }
+    }
+
+    /// Replace the instances and commands (copied to the GPU by the next `record_cull`)
+    pub fn upload_layout(&mut self, layout: &EntityDrawLayout) -> Result<(), VulkanError> {
+        if layout.instances.len() > self.max_instances as usize || layout.batches.len() > self.max_batches as usize {
+            return Err(VulkanError::BufferCreationFailed(format!(
+                "{} instances in {} materials exceed capacity {} / {}",
+                layout.instances.len(), layout.batches.len(), self.max_instances, self.max_batches
+            )));
+        }
+        self.instance_staging.write(&layout.instances)?;
+        self.command_staging.write(&layout.commands)?;
+        self.instance_count = layout.instances.len() as u32;
+        self.command_count = layout.commands.len() as u32;
+        self.batches = layout.batches.clone();
+        self.dirty = true;
+        Ok(())
+    }
+
+    /// Record the instance copy, command and count reset, and the culling dispatch
+    pub fn record_cull(&mut self, cmd: vk::CommandBuffer, view_proj: Mat4) {
+        let device = self.device.handle();
+
+        unsafe {
+            // Last frame's draws must finish reading before we overwrite
+            memory_barrier(device, cmd, Transition::INDIRECT_TO_TRANSFER);
+            if self.dirty && self.instance_count > 0 {
+                let size = self.instance_count as u64 * std::mem::size_of::<GpuEntityInstance>() as u64;
+                let region = vk::BufferCopy { src_offset: 0, dst_offset: 0, size };
+                device.cmd_copy_buffer(cmd, self.instance_staging.handle(), self.instance_buffer.handle(), &[region]);
+            }
+            self.dirty = false;
+            // Templates have zero instance counts, which resets the commands
+            if self.command_count > 0 {
+                let size = self.command_count as u64 * ENTITY_COMMAND_STRIDE as u64;
+                let region = vk::BufferCopy { src_offset: 0, dst_offset: 0, size };
+                device.cmd_copy_buffer(cmd, self.command_staging.handle(), self.command_buffer.handle(), &[region]);
+            }
+            device.cmd_fill_buffer(cmd, self.count_buffer.handle(), 0, vk::WHOLE_SIZE, 0);
+            memory_barrier(device, cmd, Transition::TRANSFER_TO_COMPUTE);
+
+            if self.instance_count > 0 {
+                let params = EntityCullParams::new(view_proj, self.instance_count);
+                device.cmd_bind_pipeline(cmd, vk::PipelineBindPoint::COMPUTE, self.pipeline);
+                device.cmd_bind_descriptor_sets(
+                    cmd, vk::PipelineBindPoint::COMPUTE, self.layout, 0, &[self.descriptor_set], &[],
+                );
+                device.cmd_push_constants(cmd, self.layout, vk::ShaderStageFlags::COMPUTE, 0, bytemuck::bytes_of(&params));
+                device.cmd_dispatch(cmd, dispatch_size(self.instance_count), 1, 1);
+            }
+
+            memory_barrier(device, cmd, Transition::COMPUTE_TO_INDIRECT);
+            memory_barrier(device, cmd, Transition::COMPUTE_TO_VERTEX);
+        }
+    }
+
+    /// Draw every material batch, calling `bind_material` before each
+    ///
+    /// The entity pipeline, vertex/index buffers and this pass's descriptor
+    /// set (for the vertex shader's instance lookup) must already be bound.
+    pub fn record_draws(&self, cmd: vk::CommandBuffer, mut bind_material: impl FnMut(u32)) {
+        let device = self.device.handle();
+        for (slot, batch) in self.batches.iter().enumerate() {
+            bind_material(batch.material_id);
+            unsafe {
+                device.cmd_draw_indexed_indirect_count(
+                    cmd,
+                    self.command_buffer.handle(),
+                    batch.first_command as u64 * ENTITY_COMMAND_STRIDE as u64,
+                    self.count_buffer.handle(),
+                    slot as u64 * 4,
+                    batch.command_count,
+                    ENTITY_COMMAND_STRIDE,
+                );
+            }
+        }
+    }
+
+    /// Get the descriptor set (bindings 0-3, also visible to vertex shaders)
+    pub fn descriptor_set(&self) -> vk::DescriptorSet {
+        self.descriptor_set
+    }
+
+    /// Get the descriptor set layout
+    pub fn set_layout(&self) -> vk::DescriptorSetLayout {
+        self.set_layout
+    }
+
+    /// Get the material batches of the uploaded layout
+    pub fn batches(&self) -> &[MaterialBatch] {
+        &self.batches
+    }
+
+    /// Get instances uploaded
+    pub fn instance_count(&self) -> u32 {
+        self.instance_count
+    }
+}
+
+impl Drop for GpuEntityDrawPass {
+    fn drop(&mut self) {
+        unsafe {
+            let device = self.device.handle();
+            device.destroy_pipeline(self.pipeline, None);
+            device.destroy_pipeline_layout(self.layout, None);
+            device.destroy_descriptor_pool(self.descriptor_pool, None);
+            device.destroy_descriptor_set_layout(self.set_layout, None);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::Vec3;
+
+    #[test]
+    fn test_instances_grouped_into_material_batches() {
+        let meshes: HashMap<u32, MeshRange> = [
+            (1, MeshRange { index_count: 36, first_index: 0, vertex_offset: 0 }),
+            (2, MeshRange { index_count: 120, first_index: 36, vertex_offset: 24 }),
+        ].into();
+        let at = |x: f32, z: f32| Mat4::from_translation(Vec3::new(x, 0.0, z));
+        let instances = [
+            GpuEntityInstance::new(at(0.0, -10.0), 1, 7, 1.0),
+            GpuEntityInstance::new(at(2.0, -10.0), 2, 7, 1.0),
+            GpuEntityInstance::new(at(4.0, -10.0), 1, 3, 1.0),
+            GpuEntityInstance::new(at(-2.0, -10.0), 1, 7, 1.0),
+            GpuEntityInstance::new(at(0.0, 10.0), 2, 3, 1.0), // behind the camera
+            GpuEntityInstance::new(at(0.0, -12.0), 9, 3, 1.0), // unregistered mesh
+        ];
+
+        let layout = EntityDrawLayout::build(&instances, &meshes);
+        assert_eq!(layout.skipped, 1);
+        assert_eq!(layout.instances.len(), 5);
+        // (3,1) (3,2) | (7,1) (7,2)
+        assert_eq!(layout.commands.len(), 4);
+        assert_eq!(layout.batches, [
+            MaterialBatch { material_id: 3, first_command: 0, command_count: 2 },
+            MaterialBatch { material_id: 7, first_command: 2, command_count: 2 },
+        ]);
+        let firsts: Vec<u32> = layout.commands.iter().map(|c| c.first_instance).collect();
+        assert_eq!(firsts, [0, 1, 2, 4]);
+        assert_eq!(layout.commands[3].vertex_offset, 24);
+        assert!(layout.commands.iter().all(|c| c.instance_count == 0));
+
+        let view = Mat4::look_at_rh(Vec3::ZERO, Vec3::NEG_Z, Vec3::Y);
+        let proj = Mat4::perspective_rh(70f32.to_radians(), 16.0 / 9.0, 0.1, 256.0);
+        let (commands, counts) = draw_commands_cpu(&layout, proj * view);
+        let per_command: Vec<u32> = commands.iter().map(|c| c.instance_count).collect();
+        assert_eq!(per_command, [1, 0, 2, 1]);
+        // Material 3's trailing mesh-2 command drew nothing, so it's not counted
+        assert_eq!(counts, [1, 2]);
+
+        assert_eq!(ENTITY_COMMAND_STRIDE as usize, std::mem::size_of::<vk::DrawIndexedIndirectCommand>());
+        assert_eq!(std::mem::size_of::<GpuEntityInstance>(), 112);
+        assert_eq!(std::mem::size_of::<EntityCullParams>(), 80);
+    }
+}
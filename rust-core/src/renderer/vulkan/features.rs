@@ -0,0 +1,204 @@
+//! # Optional Device Features
+//!
+//! Every optional feature the engine can use, whether the GPU supports it
+//! and whether it was turned on at device creation. The device builds this
+//! once from the physical device's feature structs; everything that picks a
+//! fallback (chunk path, wireframe, shader capabilities) asks the report
+//! instead of re-querying, and the report is logged at startup and copied
+//! into the compatibility report so "why is X off on my GPU" has an answer.
+
+use std::fmt;
+
+use ash::vk;
+
+use super::VulkanConfig;
+
+/// Optional feature the engine knows how to use
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OptionalFeature {
+    /// VK_EXT_mesh_shader task/mesh pipelines
+    MeshShaders,
+    /// VK_KHR_ray_tracing_pipeline and acceleration structures
+    RayTracing,
+    /// Bindless descriptor arrays (descriptorIndexing and friends)
+    DescriptorIndexing,
+    /// Timeline semaphores
+    TimelineSemaphores,
+    /// 16-bit types in storage buffers
+    Storage16Bit,
+    /// More than one draw per indirect call
+    MultiDrawIndirect,
+    /// Draw counts read from a GPU buffer
+    DrawIndirectCount,
+    /// Wireframe rasterization
+    FillModeNonSolid,
+}
+
+impl OptionalFeature {
+    /// All features in report order
+    pub const ALL: [OptionalFeature; 8] = [
+        OptionalFeature::MeshShaders,
+        OptionalFeature::RayTracing,
+        OptionalFeature::DescriptorIndexing,
+        OptionalFeature::TimelineSemaphores,
+        OptionalFeature::Storage16Bit,
+        OptionalFeature::MultiDrawIndirect,
+        OptionalFeature::DrawIndirectCount,
+        OptionalFeature::FillModeNonSolid,
+    ];
+
+    /// Get the display name
+    pub fn name(self) -> &'static str {
+        match self {
+            OptionalFeature::MeshShaders => "mesh shaders",
+            OptionalFeature::RayTracing => "ray tracing",
+            OptionalFeature::DescriptorIndexing => "descriptor indexing",
+            OptionalFeature::TimelineSemaphores => "timeline semaphores",
+            OptionalFeature::Storage16Bit => "16-bit storage",
+            OptionalFeature::MultiDrawIndirect => "multiDrawIndirect",
+            OptionalFeature::DrawIndirectCount => "drawIndirectCount",
+            OptionalFeature::FillModeNonSolid => "fillModeNonSolid",
+        }
+    }
+}
+
+/// One feature's state
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeatureStatus {
+    /// Feature
+    pub feature: OptionalFeature,
+    /// The GPU and driver offer it
+    pub supported: bool,
+    /// It was enabled on the device (supported and not turned off in config)
+    pub enabled: bool,
+}
+
+/// State of every optional feature
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeatureReport {
+    /// Status per feature, in `OptionalFeature::ALL` order
+    entries: Vec<FeatureStatus>,
+}
+
+impl FeatureReport {
+    /// Build from the physical device's features and extension availability
+    pub fn from_vk(
+        core: &vk::PhysicalDeviceFeatures,
+        vulkan11: &vk::PhysicalDeviceVulkan11Features,
+        vulkan12: &vk::PhysicalDeviceVulkan12Features,
+        mesh_shader_extension: bool,
+        ray_tracing_extensions: bool,
+        config: &VulkanConfig,
+    ) -> Self {
+        let on = |flag: vk::Bool32| flag == vk::TRUE;
+        let entries = OptionalFeature::ALL
+            .iter()
+            .map(|&feature| {
+                let (supported, wanted) = match feature {
+                    OptionalFeature::MeshShaders => (mesh_shader_extension, config.mesh_shaders_enabled),
+                    OptionalFeature::RayTracing => (ray_tracing_extensions, config.ray_tracing_enabled),
+                    OptionalFeature::DescriptorIndexing => (
+                        on(vulkan12.descriptor_indexing)
+                            && on(vulkan12.runtime_descriptor_array)
+                            && on(vulkan12.descriptor_binding_partially_bound)
+                            && on(vulkan12.descriptor_binding_variable_descriptor_count)
+                            && on(vulkan12.shader_sampled_image_array_non_uniform_indexing),
+                        true,
+                    ),
+                    OptionalFeature::TimelineSemaphores => (on(vulkan12.timeline_semaphore), true),
+                    OptionalFeature::Storage16Bit => (on(vulkan11.storage_buffer16_bit_access), true),
+                    OptionalFeature::MultiDrawIndirect => (on(core.multi_draw_indirect), true),
+                    OptionalFeature::DrawIndirectCount => (on(vulkan12.draw_indirect_count), true),
+                    OptionalFeature::FillModeNonSolid => (on(core.fill_mode_non_solid), true),
+                };
+                FeatureStatus { feature, supported, enabled: supported && wanted }
+            })
+            .collect();
+        Self { entries }
+    }
+
+    /// Get every feature's status
+    pub fn entries(&self) -> &[FeatureStatus] {
+        &self.entries
+    }
+
+    /// Get one feature's status
+    pub fn status(&self, feature: OptionalFeature) -> FeatureStatus {
+        self.entries
+            .iter()
+            .copied()
+            .find(|s| s.feature == feature)
+            .unwrap_or(FeatureStatus { feature, supported: false, enabled: false })
+    }
+
+    /// Check if a feature is supported by the GPU
+    pub fn is_supported(&self, feature: OptionalFeature) -> bool {
+        self.status(feature).supported
+    }
+
+    /// Check if a feature was enabled on the device
+    pub fn is_enabled(&self, feature: OptionalFeature) -> bool {
+        self.status(feature).enabled
+    }
+
+    /// Log one line per feature
+    pub fn log(&self) {
+        for line in self.to_string().lines() {
+            log::info!("  {}", line);
+        }
+    }
+}
+
+impl fmt::Display for FeatureReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for status in &self.entries {
+            let state = match (status.supported, status.enabled) {
+                (true, true) => "enabled",
+                (true, false) => "supported, disabled in config",
+                (false, _) => "not supported",
+            };
+            writeln!(f, "{}: {}", status.feature.name(), state)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_maps_device_features() {
+        let core = vk::PhysicalDeviceFeatures::default()
+            .multi_draw_indirect(true)
+            .fill_mode_non_solid(false);
+        let vulkan11 = vk::PhysicalDeviceVulkan11Features::default().storage_buffer16_bit_access(true);
+        let vulkan12 = vk::PhysicalDeviceVulkan12Features::default()
+            .descriptor_indexing(true)
+            .runtime_descriptor_array(true)
+            .descriptor_binding_partially_bound(true)
+            .descriptor_binding_variable_descriptor_count(true)
+            .shader_sampled_image_array_non_uniform_indexing(true)
+            .timeline_semaphore(false);
+        let config = VulkanConfig { mesh_shaders_enabled: false, ray_tracing_enabled: false, ..Default::default() };
+
+        let report = FeatureReport::from_vk(&core, &vulkan11, &vulkan12, true, false, &config);
+        assert_eq!(report.entries().len(), OptionalFeature::ALL.len());
+
+        // Supported but turned off in config
+        let mesh = report.status(OptionalFeature::MeshShaders);
+        assert!(mesh.supported && !mesh.enabled);
+        assert!(!report.is_supported(OptionalFeature::RayTracing));
+        assert!(report.is_enabled(OptionalFeature::DescriptorIndexing));
+        assert!(!report.is_supported(OptionalFeature::TimelineSemaphores));
+        assert!(report.is_enabled(OptionalFeature::Storage16Bit));
+        assert!(report.is_enabled(OptionalFeature::MultiDrawIndirect));
+        assert!(!report.is_enabled(OptionalFeature::FillModeNonSolid));
+
+        // Any missing piece of bindless turns the whole feature off
+        let partial = vulkan12.descriptor_binding_partially_bound(false);
+        let report = FeatureReport::from_vk(&core, &vulkan11, &partial, false, false, &config);
+        assert!(!report.is_supported(OptionalFeature::DescriptorIndexing));
+        assert!(report.to_string().contains("mesh shaders: not supported"));
+    }
+}
@@ -0,0 +1,171 @@
+//! # Frame Resources
+//!
+//! Per-frame resources come in two sets with different counts. Semaphores
+//! and fences are indexed by frame in flight (`max_frames_in_flight`);
+//! framebuffers and command buffers are indexed by swapchain image, whose
+//! count the driver picks and may change when the swapchain is recreated.
+//! Mixing the two indices up is what produces "semaphore in use" validation
+//! errors and flicker, so `FrameResourceSet` keeps them apart.
+
+use ash::vk;
+
+use super::{CommandPool, Swapchain, SyncObjects, VulkanDevice, VulkanError};
+
+/// Sync objects for one frame in flight (owned by `SyncObjects`)
+#[derive(Debug, Clone, Copy)]
+pub struct FrameResources {
+    /// Signaled when the acquired image can be rendered to
+    pub image_available: vk::Semaphore,
+    /// Signaled when rendering is done and the image can be presented
+    pub render_finished: vk::Semaphore,
+    /// Signaled when the frame's submission completes
+    pub in_flight: vk::Fence,
+}
+
+/// Resources for one swapchain image
+#[derive(Debug, Clone, Copy)]
+pub struct ImageResources {
+    /// Framebuffer over the image and the shared depth buffer
+    pub framebuffer: vk::Framebuffer,
+    /// Primary command buffer recorded for this image
+    pub command_buffer: vk::CommandBuffer,
+    /// Fence of the frame last rendering to this image (null if none)
+    pub in_flight: vk::Fence,
+}
+
+/// Resources split into a per-frame-in-flight set and a per-image set
+#[derive(Debug, Clone)]
+pub struct FrameResourceSet<F, I> {
+    frames: Vec<F>,
+    images: Vec<I>,
+}
+
+impl<F, I> FrameResourceSet<F, I> {
+    /// Create a set from per-frame and per-image resources
+    pub fn new(frames: Vec<F>, images: Vec<I>) -> Self {
+        Self { frames, images }
+    }
+
+    /// Get a frame in flight's resources
+    pub fn frame(&self, frame_index: usize) -> &F {
+        &self.frames[frame_index]
+    }
+
+    /// Get a swapchain image's resources
+    pub fn image(&self, image_index: usize) -> &I {
+        &self.images[image_index]
+    }
+
+    /// Get a swapchain image's resources mutably
+    pub fn image_mut(&mut self, image_index: usize) -> &mut I {
+        &mut self.images[image_index]
+    }
+
+    /// Replace the per-frame set, returning the old one
+    pub fn replace_frames(&mut self, frames: Vec<F>) -> Vec<F> {
+        std::mem::replace(&mut self.frames, frames)
+    }
+
+    /// Replace the per-image set after a swapchain recreate, returning the
+    /// old one for destruction
+    pub fn replace_images(&mut self, images: Vec<I>) -> Vec<I> {
+        std::mem::replace(&mut self.images, images)
+    }
+
+    /// Get the number of frames in flight
+    pub fn frames_in_flight(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Get the number of swapchain images
+    pub fn image_count(&self) -> usize {
+        self.images.len()
+    }
+}
+
+impl FrameResources {
+    /// Collect handles for every frame in flight
+    pub fn from_sync(sync: &SyncObjects) -> Vec<Self> {
+        (0..sync.frames_in_flight())
+            .map(|frame| Self {
+                image_available: sync.image_available(frame),
+                render_finished: sync.render_finished(frame),
+                in_flight: sync.in_flight_fence(frame),
+            })
+            .collect()
+    }
+}
+
+impl ImageResources {
+    /// Create a framebuffer and command buffer for every swapchain image
+    pub fn create_all(
+        device: &VulkanDevice,
+        pool: &mut CommandPool,
+        render_pass: vk::RenderPass,
+        swapchain: &Swapchain,
+    ) -> Result<Vec<Self>, VulkanError> {
+        let count = swapchain.image_count();
+        let extent = swapchain.extent();
+        let command_buffers = pool.allocate_command_buffers(count as u32)?;
+
+        let mut images = Vec::with_capacity(count);
+        for (index, &command_buffer) in command_buffers.iter().enumerate() {
+            let attachments = [swapchain.image_view(index), swapchain.depth_view()];
+            let framebuffer_info = vk::FramebufferCreateInfo::default()
+                .render_pass(render_pass)
+                .attachments(&attachments)
+                .width(extent.width)
+                .height(extent.height)
+                .layers(1);
+
+            let framebuffer = unsafe { device.handle().create_framebuffer(&framebuffer_info, None) };
+            match framebuffer {
+                Ok(framebuffer) => images.push(Self {
+                    framebuffer,
+                    command_buffer,
+                    in_flight: vk::Fence::null(),
+                }),
+                Err(e) => {
+                    Self::destroy_all(device, pool, images);
+                    pool.free_command_buffers(&command_buffers[index..]);
+                    return Err(VulkanError::VkError(format!("Failed to create framebuffer: {:?}", e)));
+                }
+            }
+        }
+        Ok(images)
+    }
+
+    /// Destroy framebuffers and free command buffers (the GPU must be idle)
+    pub fn destroy_all(device: &VulkanDevice, pool: &mut CommandPool, images: Vec<Self>) {
+        let command_buffers: Vec<vk::CommandBuffer> = images.iter().map(|image| image.command_buffer).collect();
+        pool.free_command_buffers(&command_buffers);
+        for image in images {
+            unsafe { device.handle().destroy_framebuffer(image.framebuffer, None) };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_set_tracks_swapchain_count() {
+        // Two frames in flight against a three-image swapchain
+        let mut set = FrameResourceSet::new(vec!["frame0", "frame1"], vec![0u32, 1, 2]);
+        assert_eq!((set.frames_in_flight(), set.image_count()), (2, 3));
+        *set.image_mut(2) = 20;
+        assert_eq!(*set.image(2), 20);
+
+        // Recreate hands back a four-image swapchain; frames in flight stay put
+        let old = set.replace_images((10..14).collect());
+        assert_eq!(old, vec![0, 1, 20]);
+        assert_eq!((set.frames_in_flight(), set.image_count()), (2, 4));
+        assert_eq!(*set.image(3), 13);
+        assert_eq!(*set.frame(1), "frame1");
+
+        // Rebuilt sync objects replace only the frame set
+        set.replace_frames(vec!["a", "b", "c"]);
+        assert_eq!((set.frames_in_flight(), set.image_count()), (3, 4));
+    }
+}
@@ -0,0 +1,420 @@
+//! # GPU Chunk Culling
+//!
+//! Frustum culling for large chunk counts moved to a compute pass. Each
+//! chunk's bounding sphere sits in a storage buffer; one invocation per
+//! chunk tests it against the planes of the view-projection (passed as a
+//! push constant) and appends a draw command for survivors, bumping an
+//! atomic counter. The mesh pipeline then draws with
+//! vkCmdDrawMeshTasksIndirectCountEXT, so the visible set never returns
+//! to the CPU. `cull_cpu` runs the same test on the host as a reference.
+
+use std::sync::Arc;
+use ash::vk;
+use glam::{Mat4, Vec4};
+
+use super::{VulkanDevice, VulkanError, Buffer, BufferType};
+use super::barrier::{memory_barrier, Transition};
+
+/// Compute workgroup size (matches `local_size_x` in the shader)
+pub const CULL_WORKGROUP_SIZE: u32 = 64;
+
+/// Meshlets handled by one task shader workgroup
+pub const MESHLETS_PER_TASK: u32 = 32;
+
+/// Culling kernel; bounds, commands and count at bindings 0, 1 and 2
+pub const FRUSTUM_CULL_SHADER: &str = r#"#version 450
+layout(local_size_x = 64) in;
+
+struct ChunkBounds { vec4 sphere; uint task_count; uint chunk_index; uint pad0; uint pad1; };
+struct DrawCommand { uint group_count_x; uint group_count_y; uint group_count_z; uint chunk_index; };
+
+layout(push_constant) uniform Params { mat4 view_proj; uint chunk_count; uint max_draws; } params;
+layout(std430, binding = 0) readonly buffer Bounds { ChunkBounds bounds[]; };
+layout(std430, binding = 1) writeonly buffer Commands { DrawCommand commands[]; };
+layout(std430, binding = 2) buffer Count { uint draw_count; };
+
+bool visible(vec4 sphere) {
+    mat4 m = transpose(params.view_proj);
+    // Left, right, bottom, top, near (depth 0..1), far
+    vec4 planes[6] = vec4[6](m[3] + m[0], m[3] - m[0], m[3] + m[1], m[3] - m[1], m[2], m[3] - m[2]);
+    for (int i = 0; i < 6; i++) {
+        vec4 p = planes[i];
+        if (dot(p.xyz, sphere.xyz) + p.w < -sphere.w * length(p.xyz)) return false;
+    }
+    return true;
+}
+
+void main() {
+    uint i = gl_GlobalInvocationID.x;
+    if (i >= params.chunk_count) return;
+
+    ChunkBounds b = bounds[i];
+    if (b.task_count == 0u || !visible(b.sphere)) return;
+
+    uint slot = atomicAdd(draw_count, 1u);
+    if (slot < params.max_draws) {
+        commands[slot] = DrawCommand(b.task_count, 1u, 1u, b.chunk_index);
+    }
+}
+"#;
+
+/// Chunk bounding sphere and draw size (GPU-side, std430)
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct ChunkBounds {
+    /// Sphere center (xyz) and radius (w), world space
+    pub sphere: [f32; 4],
+    /// Task workgroups to launch when visible
+    pub task_count: u32,
+    /// Index into the chunk data buffer
+    pub chunk_index: u32,
+    _pad: [u32; 2],
+}
+
+impl ChunkBounds {
+    /// Create bounds for a chunk with `meshlet_count` meshlets
+    pub fn new(center: [f32; 3], radius: f32, chunk_index: u32, meshlet_count: u32) -> Self {
+        Self {
+            sphere: [center[0], center[1], center[2], radius],
+            task_count: meshlet_count.div_ceil(MESHLETS_PER_TASK),
+            chunk_index,
+            _pad: [0; 2],
+        }
+    }
+}
+
+/// Indirect mesh task command with the chunk it draws
+///
+/// The first 12 bytes are a `VkDrawMeshTasksIndirectCommandEXT`; the
+/// task shader reads `chunk_index` through `gl_DrawID`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct ChunkDrawCommand {
+    pub group_count_x: u32,
+    pub group_count_y: u32,
+    pub group_count_z: u32,
+    /// Index into the chunk data buffer
+    pub chunk_index: u32,
+}
+
+/// Push constants of the culling kernel
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct CullParams {
+    /// Column-major view-projection
+    pub view_proj: [[f32; 4]; 4],
+    /// Chunks in the bounds buffer
+    pub chunk_count: u32,
+    /// Capacity of the command buffer
+    pub max_draws: u32,
+    _pad: [u32; 2],
+}
+
+impl CullParams {
+    /// Create push constants for a dispatch
+    pub fn new(view_proj: Mat4, chunk_count: u32, max_draws: u32) -> Self {
+        Self {
+            view_proj: view_proj.to_cols_array_2d(),
+            chunk_count,
+            max_draws,
+            _pad: [0; 2],
+        }
+    }
+}
+
+/// Byte stride between indirect commands
+pub const DRAW_COMMAND_STRIDE: u32 = std::mem::size_of::<ChunkDrawCommand>() as u32;
+
+/// Workgroups needed to cull `chunk_count` chunks
+pub fn dispatch_size(chunk_count: u32) -> u32 {
+    chunk_count.div_ceil(CULL_WORKGROUP_SIZE)
+}
+
+/// Frustum planes of a view-projection (depth 0..1), as in the shader
+pub fn frustum_planes(view_proj: Mat4) -> [Vec4; 6] {
+    let [r0, r1, r2, r3] = [0, 1, 2, 3].map(|i| view_proj.row(i));
+    [r3 + r0, r3 - r0, r3 + r1, r3 - r1, r2, r3 - r2]
+}
+
+/// Check if a sphere (center xyz, radius w) touches the frustum
+pub fn sphere_visible(planes: &[Vec4; 6], sphere: Vec4) -> bool {
+    planes.iter().all(|p| p.truncate().dot(sphere.truncate()) + p.w >= -sphere.w * p.truncate().length())
+}
+
+/// Host reference of the culling kernel
+pub fn cull_cpu(bounds: &[ChunkBounds], view_proj: Mat4) -> Vec<ChunkDrawCommand> {
+    let planes = frustum_planes(view_proj);
+    bounds
+        .iter()
+        .filter(|b| b.task_count > 0 && sphere_visible(&planes, Vec4::from(b.sphere)))
+        .map(|b| ChunkDrawCommand {
+            group_count_x: b.task_count,
+            group_count_y: 1,
+            group_count_z: 1,
+            chunk_index: b.chunk_index,
+        })
+        .collect()
+}
+
+/// Compute pass producing indirect mesh task draws for visible chunks
+pub struct GpuCullPass {
+    /// Device reference
+    device: Arc<VulkanDevice>,
+    /// VK_EXT_mesh_shader entry points
+    mesh_shader: ash::ext::mesh_shader::Device,
+    /// Descriptor set layout
+    set_layout: vk::DescriptorSetLayout,
+    /// Pipeline layout
+    layout: vk::PipelineLayout,
+    /// Culling pipeline
+    pipeline: vk::Pipeline,
+    /// Descriptor pool
+    descriptor_pool: vk::DescriptorPool,
+    /// Bindings 0-2
+    descriptor_set: vk::DescriptorSet,
+    /// Chunk bounds (binding 0)
+    bounds_buffer: Buffer,
+    /// Host copy of the bounds, copied in by `record_cull`
+    bounds_staging: Buffer,
+    /// Draw commands (binding 1)
+    command_buffer: Buffer,
+    /// Draw count (binding 2)
+    count_buffer: Buffer,
+    /// Capacity in chunks
+    max_chunks: u32,
+    /// Chunks in the bounds buffer
+    chunk_count: u32,
+    /// Staging holds bounds not yet copied
+    bounds_dirty: bool,
+}
+
+impl GpuCullPass {
+    /// Create the pass from compiled `FRUSTUM_CULL_SHADER`
+    pub fn new(device: Arc<VulkanDevice>, spirv: &[u32], max_chunks: u32) -> Result<Self, VulkanError> {
+        if !device.supports_mesh_shaders() {
+            return Err(VulkanError::PipelineCreationFailed("Mesh shaders not supported".to_string()));
+        }
+
+        let bounds_size = (max_chunks as usize * std::mem::size_of::<ChunkBounds>()) as u64;
+        let bounds_buffer = Buffer::new(device.clone(), bounds_size, BufferType::Storage)?;
+        let bounds_staging = Buffer::new(device.clone(), bounds_size, BufferType::Staging)?;
+        let command_buffer = Buffer::new(
+            device.clone(),
+            max_chunks as u64 * DRAW_COMMAND_STRIDE as u64,
+            BufferType::Indirect,
+        )?;
+        let count_buffer = Buffer::new(device.clone(), 4, BufferType::Indirect)?;
+
+        let vk_device = device.handle();
+        let mesh_shader = ash::ext::mesh_shader::Device::new(device.instance().handle(), vk_device);
+        let err = |what: &str, e: vk::Result| VulkanError::PipelineCreationFailed(format!("{}: {:?}", what, e));
+
+        unsafe {
+            let bindings = [0, 1, 2].map(|binding| vk::DescriptorSetLayoutBinding::default()
+                .binding(binding)
+                .descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
+                .descriptor_count(1)
+                .stage_flags(vk::ShaderStageFlags::COMPUTE));
+            let set_layout = vk_device.create_descriptor_set_layout(
+                &vk::DescriptorSetLayoutCreateInfo::default().bindings(&bindings), None,
+            ).map_err(|e| err("Failed to create cull set layout", e))?;
+
+            let set_layouts = [set_layout];
+            let push_constants = [vk::PushConstantRange::default()
+                .stage_flags(vk::ShaderStageFlags::COMPUTE)
+                .offset(0)
+                .size(std::mem::size_of::<CullParams>() as u32)];
+            let layout = device.create_pipeline_layout(
+                &vk::PipelineLayoutCreateInfo::default()
+                    .set_layouts(&set_layouts)
+                    .push_constant_ranges(&push_constants),
+            )?;
+
+            let module = vk_device.create_shader_module(
+                &vk::ShaderModuleCreateInfo::default().code(spirv), None,
+            ).map_err(|e| err("Failed to create cull shader", e))?;
+            let stage = vk::PipelineShaderStageCreateInfo::default()
+                .stage(vk::ShaderStageFlags::COMPUTE)
+                .module(module)
+                .name(c"main");
+            let created = vk_device.create_compute_pipelines(
+                vk::PipelineCache::null(),
+                &[vk::ComputePipelineCreateInfo::default().stage(stage).layout(layout)],
+                None,
+            );
+            vk_device.destroy_shader_module(module, None);
+            let pipeline = created.map_err(|(_, e)| err("Failed to create cull pipeline", e))?[0];
+
+            let pool_sizes = [vk::DescriptorPoolSize { ty: vk::DescriptorType::STORAGE_BUFFER, descriptor_count: 3 }];
+            let descriptor_pool = vk_device.create_descriptor_pool(
+                &vk::DescriptorPoolCreateInfo::default().max_sets(1).pool_sizes(&pool_sizes),
+                None,
+            ).map_err(|e| err("Failed to create cull descriptor pool", e))?;
+            let descriptor_set = vk_device.allocate_descriptor_sets(
+                &vk::DescriptorSetAllocateInfo::default()
+                    .descriptor_pool(descriptor_pool)
+                    .set_layouts(&set_layouts),
+            ).map_err(|e| err("Failed to allocate cull descriptor set", e))?[0];
+
+            let infos = [&bounds_buffer, &command_buffer, &count_buffer].map(|b| [vk::DescriptorBufferInfo {
+                buffer: b.handle(),
+                offset: 0,
+                range: vk::WHOLE_SIZE,
+            }]);
+            let writes: Vec<_> = infos.iter().enumerate().map(|(binding, info)| {
+                vk::WriteDescriptorSet::default()
+                    .dst_set(descriptor_set)
+                    .dst_binding(binding as u32)
+                    .descriptor_type(vk::DescriptorType::STORAGE_BUFFER)
+                    .buffer_info(info)
+            }).collect();
+            vk_device.update_descriptor_sets(&writes, &[]);
+
+            Ok(Self {
+                device,
+                mesh_shader,
+                set_layout,
+                layout,
+                pipeline,
+                descriptor_pool,
+                descriptor_set,
+                bounds_buffer,
+                bounds_staging,
+                command_buffer,
+                count_buffer,
+                max_chunks,
+                chunk_count: 0,
+                bounds_dirty: false,
+            })
+        }
+    }
+
+    /// Replace the chunk bounds (copied to the GPU by the next `record_cull`)
+    pub fn upload_bounds(&mut self, bounds: &[ChunkBounds]) -> Result<(), VulkanError> {
+        if bounds.len() > self.max_chunks as usize {
+            return Err(VulkanError::BufferCreationFailed(format!(
+                "{} chunks exceed cull capacity {}", bounds.len(), self.max_chunks
+            )));
+        }
+        self.bounds_staging.write(bounds)?;
+        self.chunk_count = bounds.len() as u32;
+        self.bounds_dirty = true;
+        Ok(())
+    }
+
+    /// Record the bounds copy, count reset and culling dispatch
+    ///
+    /// Ends with a barrier making the commands and count visible to the
+    /// indirect draw.
+    pub fn record_cull(&mut self, cmd: vk::CommandBuffer, view_proj: Mat4) {
+        let device = self.device.handle();
+
+        unsafe {
+            // Last frame's draw must finish reading before we overwrite
+            memory_barrier(device, cmd, Transition::INDIRECT_TO_TRANSFER);
+            if self.bounds_dirty && self.chunk_count > 0 {
+                let region = vk::BufferCopy {
+                    src_offset: 0,
+                    dst_offset: 0,
+                    size: (self.chunk_count as usize * std::mem::size_of::<ChunkBounds>()) as u64,
+                };
+                device.cmd_copy_buffer(cmd, self.bounds_staging.handle(), self.bounds_buffer.handle(), &[region]);
+            }
+            self.bounds_dirty = false;
+            device.cmd_fill_buffer(cmd, self.count_buffer.handle(), 0, 4, 0);
+            memory_barrier(device, cmd, Transition::TRANSFER_TO_COMPUTE);
+
+            if self.chunk_count > 0 {
+                let params = CullParams::new(view_proj, self.chunk_count, self.max_chunks);
+                device.cmd_bind_pipeline(cmd, vk::PipelineBindPoint::COMPUTE, self.pipeline);
+                device.cmd_bind_descriptor_sets(
+                    cmd, vk::PipelineBindPoint::COMPUTE, self.layout, 0, &[self.descriptor_set], &[],
+                );
+                device.cmd_push_constants(cmd, self.layout, vk::ShaderStageFlags::COMPUTE, 0, bytemuck::bytes_of(&params));
+                device.cmd_dispatch(cmd, dispatch_size(self.chunk_count), 1, 1);
+            }
+
+            // Only built on mesh shader devices, so the task stage is valid
+            memory_barrier(device, cmd, Transition::COMPUTE_TO_TASK_INDIRECT);
+        }
+    }
+
+    /// Draw the surviving chunks (mesh pipeline and descriptors already bound)
+    pub fn record_draw(&self, cmd: vk::CommandBuffer) {
+        if self.chunk_count == 0 {
+            return;
+        }
+        unsafe {
+            self.mesh_shader.cmd_draw_mesh_tasks_indirect_count(
+                cmd,
+                self.command_buffer.handle(),
+                0,
+                self.count_buffer.handle(),
+                0,
+                self.chunk_count,
+                DRAW_COMMAND_STRIDE,
+            );
+        }
+    }
+
+    /// Get the indirect command buffer (for the task shader's `gl_DrawID` lookup)
+    pub fn command_buffer(&self) -> &Buffer {
+        &self.command_buffer
+    }
+
+    /// Get chunks in the bounds buffer
+    pub fn chunk_count(&self) -> u32 {
+        self.chunk_count
+    }
+
+    /// Get capacity in chunks
+    pub fn max_chunks(&self) -> u32 {
+        self.max_chunks
+    }
+}
+
+impl Drop for GpuCullPass {
+    fn drop(&mut self) {
+        unsafe {
+            let device = self.device.handle();
+            device.destroy_pipeline(self.pipeline, None);
+            device.destroy_pipeline_layout(self.layout, None);
+            device.destroy_descriptor_pool(self.descriptor_pool, None);
+            device.destroy_descriptor_set_layout(self.set_layout, None);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::Vec3;
+
+    #[test]
+    fn test_dispatch_size_and_command_packing() {
+        assert_eq!(dispatch_size(0), 0);
+        assert_eq!(dispatch_size(1), 1);
+        assert_eq!(dispatch_size(CULL_WORKGROUP_SIZE), 1);
+        assert_eq!(dispatch_size(CULL_WORKGROUP_SIZE + 1), 2);
+        assert_eq!(dispatch_size(40_000), 625);
+
+        // Leading fields match VkDrawMeshTasksIndirectCommandEXT; std430 sizes match the shader
+        assert_eq!(DRAW_COMMAND_STRIDE, 16);
+        assert!(DRAW_COMMAND_STRIDE as usize >= std::mem::size_of::<vk::DrawMeshTasksIndirectCommandEXT>());
+        let command = ChunkDrawCommand { group_count_x: 3, group_count_y: 1, group_count_z: 1, chunk_index: 7 };
+        assert_eq!(bytemuck::cast::<_, [u32; 4]>(command), [3, 1, 1, 7]);
+        assert_eq!(std::mem::size_of::<ChunkBounds>(), 32);
+        assert_eq!(std::mem::size_of::<CullParams>(), 80);
+
+        let bounds = ChunkBounds::new([8.0, 8.0, -40.0], 14.0, 5, 65);
+        assert_eq!(bounds.task_count, 3);
+
+        // Camera at the origin looking down -Z: the chunk ahead survives, the one behind is culled
+        let view = Mat4::look_at_rh(Vec3::ZERO, Vec3::NEG_Z, Vec3::Y);
+        let proj = Mat4::perspective_rh(70f32.to_radians(), 16.0 / 9.0, 0.1, 512.0);
+        let behind = ChunkBounds::new([8.0, 8.0, 40.0], 14.0, 6, 10);
+        let empty = ChunkBounds::new([8.0, 8.0, -40.0], 14.0, 9, 0);
+        let draws = cull_cpu(&[bounds, behind, empty], proj * view);
+        assert_eq!(draws, [ChunkDrawCommand { group_count_x: 3, group_count_y: 1, group_count_z: 1, chunk_index: 5 }]);
+    }
+}
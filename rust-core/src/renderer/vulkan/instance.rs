@@ -76,6 +76,11 @@ impl VulkanInstance {
             extensions.push(ash::ext::debug_utils::NAME.as_ptr());
         }
         
+        // HDR color spaces need VK_EXT_swapchain_colorspace
+        if config.hdr && Self::has_extension(&entry, ash::ext::swapchain_colorspace::NAME) {
+            extensions.push(ash::ext::swapchain_colorspace::NAME.as_ptr());
+        }
+        
         // Collect layers
         let layers: Vec<CString> = if validation_enabled {
             VALIDATION_LAYERS.iter().map(|l| CString::new(*l).unwrap()).collect()
@@ -160,6 +165,16 @@ impl VulkanInstance {
         true
     }
     
+    /// Check if the loader offers an instance extension
+    fn has_extension(entry: &Entry, name: &CStr) -> bool {
+        let available = match unsafe { entry.enumerate_instance_extension_properties(None) } {
+            Ok(extensions) => extensions,
+            Err(_) => return false,
+        };
+        
+        available.iter().any(|ext| unsafe { CStr::from_ptr(ext.extension_name.as_ptr()) } == name)
+    }
+    
     /// Get the Vulkan entry point
     pub fn entry(&self) -> &Entry {
         &self.entry
@@ -197,6 +212,13 @@ impl VulkanInstance {
         }
     }
     
+    /// Fill physical device features, including chained structs
+    pub fn get_physical_device_features2(&self, device: vk::PhysicalDevice, features: &mut vk::PhysicalDeviceFeatures2) {
+        unsafe {
+            self.instance.get_physical_device_features2(device, features)
+        }
+    }
+    
     /// Get physical device queue family properties
     pub fn get_physical_device_queue_family_properties(&self, device: vk::PhysicalDevice) -> Vec<vk::QueueFamilyProperties> {
         unsafe {
@@ -0,0 +1,235 @@
+//! # GPU Memory Tracking
+//!
+//! Accounts every device memory allocation by heap so low-VRAM GPUs can be
+//! handled gracefully. `VulkanDevice::allocate_memory`/`free_memory` record
+//! into the device's tracker automatically.
+//!
+//! When a heap's usage crosses `pressure_threshold` of its budget, a
+//! `EngineEvent::GpuMemoryPressure` is emitted once; it re-arms after usage
+//! drops back below the threshold. Failed allocations always emit it.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use ash::vk;
+use parking_lot::Mutex;
+
+use crate::events::{EngineEvent, EventQueue};
+
+/// Default fraction of the budget that counts as pressure
+pub const DEFAULT_PRESSURE_THRESHOLD: f64 = 0.9;
+
+/// Per-heap counters
+#[derive(Debug, Default)]
+struct HeapUsage {
+    used: AtomicU64,
+    budget: AtomicU64,
+    under_pressure: AtomicBool,
+}
+
+/// Device memory accounting by heap
+#[derive(Debug)]
+pub struct GpuMemoryTracker {
+    /// Heap index of each memory type
+    type_heaps: Vec<u32>,
+    /// Usage per heap
+    heaps: Vec<HeapUsage>,
+    /// Live allocations: memory -> (heap, size)
+    allocations: Mutex<HashMap<vk::DeviceMemory, (u32, u64)>>,
+    /// Usage fraction that triggers a pressure event (stored as f64 bits)
+    threshold: AtomicU64,
+    /// Where pressure events go
+    events: EventQueue,
+}
+
+impl GpuMemoryTracker {
+    /// Create a tracker; budgets start at the full heap sizes
+    pub fn new(properties: &vk::PhysicalDeviceMemoryProperties, events: EventQueue) -> Self {
+        let type_heaps = properties.memory_types[..properties.memory_type_count as usize]
+            .iter()
+            .map(|t| t.heap_index)
+            .collect();
+        let heaps = properties.memory_heaps[..properties.memory_heap_count as usize]
+            .iter()
+            .map(|h| HeapUsage { budget: AtomicU64::new(h.size), ..HeapUsage::default() })
+            .collect();
+
+        Self {
+            type_heaps,
+            heaps,
+            allocations: Mutex::new(HashMap::new()),
+            threshold: AtomicU64::new(DEFAULT_PRESSURE_THRESHOLD.to_bits()),
+            events,
+        }
+    }
+
+    /// Get heap count
+    pub fn heap_count(&self) -> usize {
+        self.heaps.len()
+    }
+
+    /// Get heap index of a memory type
+    pub fn heap_of_type(&self, memory_type: u32) -> Option<u32> {
+        self.type_heaps.get(memory_type as usize).copied()
+    }
+
+    /// Get bytes allocated on a heap
+    pub fn used_bytes(&self, heap: u32) -> u64 {
+        self.heaps.get(heap as usize).map_or(0, |h| h.used.load(Ordering::Relaxed))
+    }
+
+    /// Get bytes the heap may use
+    pub fn budget_bytes(&self, heap: u32) -> u64 {
+        self.heaps.get(heap as usize).map_or(0, |h| h.budget.load(Ordering::Relaxed))
+    }
+
+    /// Get bytes allocated across all heaps
+    pub fn total_used_bytes(&self) -> u64 {
+        self.heaps.iter().map(|h| h.used.load(Ordering::Relaxed)).sum()
+    }
+
+    /// Get live allocation count
+    pub fn allocation_count(&self) -> usize {
+        self.allocations.lock().len()
+    }
+
+    /// Set a heap's budget
+    pub fn set_budget(&self, heap: u32, bytes: u64) {
+        if let Some(usage) = self.heaps.get(heap as usize) {
+            usage.budget.store(bytes, Ordering::Relaxed);
+            self.check_pressure(heap);
+        }
+    }
+
+    /// Set the usage fraction that triggers pressure events
+    pub fn set_pressure_threshold(&self, fraction: f64) {
+        self.threshold.store(fraction.clamp(0.0, 1.0).to_bits(), Ordering::Relaxed);
+    }
+
+    /// Get the pressure threshold
+    pub fn pressure_threshold(&self) -> f64 {
+        f64::from_bits(self.threshold.load(Ordering::Relaxed))
+    }
+
+    /// Check if a heap is over the pressure threshold
+    pub fn is_under_pressure(&self, heap: u32) -> bool {
+        self.heaps.get(heap as usize).is_some_and(|h| h.under_pressure.load(Ordering::Relaxed))
+    }
+
+    /// Refresh budgets from `VK_EXT_memory_budget`
+    ///
+    /// The budget already excludes other processes' usage, so our own usage is
+    /// added back to get what this process may hold.
+    pub fn refresh_budgets(&self, instance: &ash::Instance, physical_device: vk::PhysicalDevice) {
+        let mut budget = vk::PhysicalDeviceMemoryBudgetPropertiesEXT::default();
+        let mut properties = vk::PhysicalDeviceMemoryProperties2::default().push_next(&mut budget);
+        unsafe { instance.get_physical_device_memory_properties2(physical_device, &mut properties) };
+
+        for heap in 0..self.heaps.len() {
+            self.set_budget(heap as u32, budget.heap_budget[heap]);
+        }
+    }
+
+    /// Record a new allocation
+    pub fn record_alloc(&self, memory: vk::DeviceMemory, memory_type: u32, size: u64) {
+        let Some(heap) = self.heap_of_type(memory_type) else {
+            return;
+        };
+        self.allocations.lock().insert(memory, (heap, size));
+        self.heaps[heap as usize].used.fetch_add(size, Ordering::Relaxed);
+        self.check_pressure(heap);
+    }
+
+    /// Record a free (unknown handles are ignored)
+    pub fn record_free(&self, memory: vk::DeviceMemory) {
+        let Some((heap, size)) = self.allocations.lock().remove(&memory) else {
+            return;
+        };
+        self.heaps[heap as usize].used.fetch_sub(size, Ordering::Relaxed);
+        self.check_pressure(heap);
+    }
+
+    /// Record a failed allocation (always reports pressure)
+    pub fn record_failure(&self, memory_type: u32, size: u64) {
+        let Some(heap) = self.heap_of_type(memory_type) else {
+            return;
+        };
+        log::warn!(
+            "GPU allocation of {} bytes failed on heap {} ({} / {} bytes used)",
+            size, heap, self.used_bytes(heap), self.budget_bytes(heap),
+        );
+        self.heaps[heap as usize].under_pressure.store(true, Ordering::Relaxed);
+        self.emit_pressure(heap);
+    }
+
+    /// Update the pressure flag, emitting on the rising edge
+    fn check_pressure(&self, heap: u32) {
+        let usage = &self.heaps[heap as usize];
+        let used = usage.used.load(Ordering::Relaxed);
+        let budget = usage.budget.load(Ordering::Relaxed);
+        let over = budget > 0 && used as f64 >= budget as f64 * self.pressure_threshold();
+
+        if usage.under_pressure.swap(over, Ordering::Relaxed) != over && over {
+            self.emit_pressure(heap);
+        }
+    }
+
+    fn emit_pressure(&self, heap: u32) {
+        self.events.emit(EngineEvent::GpuMemoryPressure {
+            heap,
+            used_bytes: self.used_bytes(heap),
+            budget_bytes: self.budget_bytes(heap),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ash::vk::Handle;
+
+    fn properties() -> vk::PhysicalDeviceMemoryProperties {
+        let mut properties = vk::PhysicalDeviceMemoryProperties {
+            memory_type_count: 2,
+            memory_heap_count: 2,
+            ..Default::default()
+        };
+        properties.memory_types[0].heap_index = 0; // Device local
+        properties.memory_types[1].heap_index = 1; // Host visible
+        properties.memory_heaps[0].size = 1000;
+        properties.memory_heaps[1].size = 4000;
+        properties
+    }
+
+    #[test]
+    fn test_alloc_free_accounting_and_pressure() {
+        let events = EventQueue::new();
+        let tracker = GpuMemoryTracker::new(&properties(), events.clone());
+        let memory = |raw| vk::DeviceMemory::from_raw(raw);
+
+        tracker.record_alloc(memory(1), 0, 300);
+        tracker.record_alloc(memory(2), 0, 400);
+        tracker.record_alloc(memory(3), 1, 100);
+        assert_eq!((tracker.used_bytes(0), tracker.used_bytes(1)), (700, 100));
+        assert_eq!(tracker.total_used_bytes(), 800);
+        assert!(events.is_empty());
+
+        // Crossing 90% of 1000 fires once
+        tracker.record_alloc(memory(4), 0, 250);
+        tracker.record_alloc(memory(5), 0, 10);
+        assert_eq!(events.drain(), [EngineEvent::GpuMemoryPressure { heap: 0, used_bytes: 950, budget_bytes: 1000 }]);
+        assert!(tracker.is_under_pressure(0));
+
+        tracker.record_free(memory(2));
+        tracker.record_free(memory(2)); // Double free is ignored
+        assert_eq!(tracker.used_bytes(0), 560);
+        assert!(!tracker.is_under_pressure(0));
+        assert_eq!(tracker.allocation_count(), 4);
+
+        // A shrinking budget re-triggers
+        tracker.set_budget(0, 600);
+        assert_eq!(events.drain().len(), 1);
+
+        tracker.record_failure(1, 8000);
+        assert!(matches!(events.drain()[..], [EngineEvent::GpuMemoryPressure { heap: 1, .. }]));
+    }
+}
@@ -7,6 +7,10 @@ use std::sync::Arc;
 use ash::vk;
 
 use super::{VulkanDevice, VulkanError, Buffer, BufferType};
+use super::block_model::{BlockModel, BlockModelRegistry, FLUID_SURFACE_HEIGHT};
+use crate::renderer::quantum::nanite::LodLevel;
+use crate::world::biome::{self, BiomeTintTable};
+use crate::world::ChunkSection;
 
 /// Maximum meshlets per chunk
 pub const MAX_MESHLETS_PER_CHUNK: usize = 4096;
@@ -51,6 +55,64 @@ pub struct ChunkMeshData {
     pub flags: u32,
 }
 
+/// Host-side record of which chunk slots hold meshlets
+#[derive(Debug, Clone)]
+pub struct ChunkSlots {
+    /// Per-slot chunk data, zeroed when the slot is free
+    slots: Vec<ChunkMeshData>,
+    /// One past the highest occupied slot
+    count: usize,
+}
+
+impl ChunkSlots {
+    /// Create an empty table
+    pub fn new(max_chunks: usize) -> Self {
+        Self { slots: vec![ChunkMeshData::default(); max_chunks], count: 0 }
+    }
+
+    /// Store a slot's chunk data
+    pub fn set(&mut self, index: usize, data: ChunkMeshData) {
+        self.slots[index] = data;
+        if data.meshlet_count > 0 {
+            self.count = self.count.max(index + 1);
+        } else {
+            self.shrink();
+        }
+    }
+
+    /// Free a slot so its draw entry launches no task groups
+    pub fn clear(&mut self, index: usize) {
+        if let Some(slot) = self.slots.get_mut(index) {
+            *slot = ChunkMeshData::default();
+            self.shrink();
+        }
+    }
+
+    /// Get a slot's chunk data
+    pub fn get(&self, index: usize) -> &ChunkMeshData {
+        &self.slots[index]
+    }
+
+    /// Get the indirect draw entry for a slot
+    pub fn draw_command(&self, index: usize) -> vk::DrawMeshTasksIndirectCommandEXT {
+        vk::DrawMeshTasksIndirectCommandEXT {
+            group_count_x: self.slots[index].meshlet_count,
+            group_count_y: 1,
+            group_count_z: 1,
+        }
+    }
+
+    /// Get the number of slots drawn (up to the highest occupied one)
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Drop trailing free slots from the draw range
+    fn shrink(&mut self) {
+        self.count = self.slots[..self.count].iter().rposition(|s| s.meshlet_count > 0).map_or(0, |i| i + 1);
+    }
+}
+
 /// Vertex data for mesh shader
 #[repr(C)]
 #[derive(Debug, Clone, Copy, Default)]
@@ -58,7 +120,9 @@ pub struct MeshVertex {
     /// Position (xyz) and packed normal (w)
     pub position_normal: [f32; 4],
     /// UV coordinates and block ID
-    pub uv_block: [f32; 4],
+    pub uv_block: [f32; 3],
+    /// Biome tint color (0xRRGGBB)
+    pub tint: u32,
     /// Ambient occlusion and light level
     pub ao_light: [f32; 4],
 }
@@ -87,8 +151,8 @@ pub struct MeshShaderPipeline {
     indirect_buffer: Option<Buffer>,
     /// Maximum chunks
     max_chunks: usize,
-    /// Current chunk count
-    chunk_count: usize,
+    /// Occupied chunk slots
+    slots: ChunkSlots,
 }
 
 impl MeshShaderPipeline {
@@ -118,10 +182,7 @@ impl MeshShaderPipeline {
             .set_layouts(&layouts)
             .push_constant_ranges(&push_ranges);
         
-        let layout = unsafe {
-            device.handle().create_pipeline_layout(&layout_info, None)
-                .map_err(|e| VulkanError::PipelineCreationFailed(format!("Failed to create pipeline layout: {:?}", e)))?
-        };
+        let layout = device.create_pipeline_layout(&layout_info)?;
         
         // Create descriptor pool
         let pool_sizes = [
@@ -191,7 +252,7 @@ impl MeshShaderPipeline {
             chunk_buffer,
             indirect_buffer,
             max_chunks,
-            chunk_count: 0,
+            slots: ChunkSlots::new(max_chunks),
         })
     }
     
@@ -293,13 +354,36 @@ impl MeshShaderPipeline {
             // Would use staging buffer
         }
         
-        if chunk_index >= self.chunk_count {
-            self.chunk_count = chunk_index + 1;
+        // Upload the slot's draw entry
+        if let Some(ref buffer) = self.indirect_buffer {
+            let offset = chunk_index * std::mem::size_of::<vk::DrawMeshTasksIndirectCommandEXT>();
+            // Would use staging buffer
         }
         
+        self.slots.set(chunk_index, *chunk_data);
+        
         Ok(())
     }
     
+    /// Free a chunk slot, zeroing its meshlet count and draw entry
+    pub fn remove_chunk(&mut self, chunk_index: usize) {
+        if chunk_index >= self.max_chunks {
+            return;
+        }
+        
+        // Zero the chunk data and draw entry so the slot launches no task groups
+        if let Some(ref buffer) = self.chunk_buffer {
+            let offset = chunk_index * std::mem::size_of::<ChunkMeshData>();
+            // Would use staging buffer
+        }
+        if let Some(ref buffer) = self.indirect_buffer {
+            let offset = chunk_index * std::mem::size_of::<vk::DrawMeshTasksIndirectCommandEXT>();
+            // Would use staging buffer
+        }
+        
+        self.slots.clear(chunk_index);
+    }
+    
     /// Build meshlets from raw chunk vertex data
     pub fn build_meshlets(
         vertices: &[MeshVertex],
@@ -414,7 +498,7 @@ impl MeshShaderPipeline {
         cmd: vk::CommandBuffer,
         descriptor_set: vk::DescriptorSet,
     ) {
-        if self.pipeline == vk::Pipeline::null() || self.chunk_count == 0 {
+        if self.pipeline == vk::Pipeline::null() || self.slots.count() == 0 {
             return;
         }
         
@@ -458,7 +542,12 @@ impl MeshShaderPipeline {
     
     /// Get chunk count
     pub fn chunk_count(&self) -> usize {
-        self.chunk_count
+        self.slots.count()
+    }
+    
+    /// Get occupied chunk slots
+    pub fn slots(&self) -> &ChunkSlots {
+        &self.slots
     }
 }
 
@@ -479,25 +568,81 @@ impl Drop for MeshShaderPipeline {
 pub struct ChunkMesher {
     /// Block face visibility cache
     visibility_cache: Vec<u8>,
+    /// Block shapes (unregistered blocks are full cubes)
+    models: BlockModelRegistry,
 }
 
 impl ChunkMesher {
     /// Create a new chunk mesher
     pub fn new() -> Self {
+        Self::with_models(BlockModelRegistry::new())
+    }
+    
+    /// Create a chunk mesher using the given block models
+    pub fn with_models(models: BlockModelRegistry) -> Self {
         Self {
             visibility_cache: Vec::with_capacity(16 * 16 * 384), // Max chunk size
+            models,
         }
     }
     
+    /// Set the model for a block
+    pub fn register_model(&mut self, block_id: u16, model: BlockModel) {
+        self.models.register_model(block_id, model);
+    }
+    
+    /// Get the block model registry
+    pub fn models(&self) -> &BlockModelRegistry {
+        &self.models
+    }
+    
     /// Mesh a chunk section (16x16x16)
     pub fn mesh_section(
         &mut self,
         blocks: &[u16; 4096],
         section_y: i32,
         neighbors: &ChunkNeighbors,
+    ) -> (Vec<MeshVertex>, Vec<u32>) {
+        let biomes = [0u16; biome::BIOMES_PER_SECTION];
+        self.mesh_section_tinted(blocks, &biomes, &BiomeTintTable::new(), section_y, neighbors)
+    }
+    
+    /// Mesh a world section; all-air sections are skipped without
+    /// unpacking their blocks
+    pub fn mesh_chunk_section(
+        &mut self,
+        section: &ChunkSection,
+        tints: &BiomeTintTable,
+        neighbors: &ChunkNeighbors,
+    ) -> (Vec<MeshVertex>, Vec<u32>) {
+        if section.is_empty() {
+            return (Vec::new(), Vec::new());
+        }
+        let mut blocks = [0u16; 4096];
+        for (slot, block) in blocks.iter_mut().zip(section.blocks().iter()) {
+            *slot = block;
+        }
+        let mut biomes = [0u16; biome::BIOMES_PER_SECTION];
+        for (slot, &id) in biomes.iter_mut().zip(section.biomes()) {
+            *slot = id;
+        }
+        self.mesh_section_tinted(&blocks, &biomes, tints, section.section_y(), neighbors)
+    }
+    
+    /// Mesh a chunk section, tinting grass and foliage by biome
+    pub fn mesh_section_tinted(
+        &mut self,
+        blocks: &[u16; 4096],
+        biomes: &[u16; biome::BIOMES_PER_SECTION],
+        tints: &BiomeTintTable,
+        section_y: i32,
+        neighbors: &ChunkNeighbors,
     ) -> (Vec<MeshVertex>, Vec<u32>) {
         let mut vertices = Vec::new();
         let mut indices = Vec::new();
+        if Self::is_all_air(blocks) {
+            return (vertices, indices);
+        }
         
         for y in 0..16 {
             for z in 0..16 {
@@ -509,45 +654,268 @@ impl ChunkMesher {
                         continue; // Air
                     }
                     
-                    // Check each face
-                    let world_y = section_y * 16 + y as i32;
+                    let tint = tints.tint_for_block(block, biomes[biome::biome_index(x, y, z)]);
                     
-                    // +X face
-                    if x == 15 || blocks[idx + 1] == 0 {
-                        self.add_face(&mut vertices, &mut indices, x, y, z, block, Face::PosX);
-                    }
-                    
-                    // -X face
-                    if x == 0 || blocks[idx - 1] == 0 {
-                        self.add_face(&mut vertices, &mut indices, x, y, z, block, Face::NegX);
-                    }
+                    // Neighbor across each face (None at the section edge)
+                    let neighbor = |face: Face| -> Option<u16> {
+                        let inside = match face {
+                            Face::PosX => x < 15,
+                            Face::NegX => x > 0,
+                            Face::PosY => y < 15,
+                            Face::NegY => y > 0,
+                            Face::PosZ => z < 15,
+                            Face::NegZ => z > 0,
+                        };
+                        inside.then(|| blocks[(idx as isize + face.index_offset()) as usize])
+                    };
                     
-                    // +Y face
-                    if y == 15 || blocks[idx + 256] == 0 {
-                        self.add_face(&mut vertices, &mut indices, x, y, z, block, Face::PosY);
+                    let origin = [x as f32, y as f32, z as f32];
+                    match self.models.model(block) {
+                        BlockModel::Cube => {
+                            for face in Face::ALL {
+                                if neighbor(face).is_none_or(|n| !self.models.occludes(n)) {
+                                    self.add_quad(&mut vertices, &mut indices, face.get_geometry(origin[0], origin[1], origin[2]), block, tint);
+                                }
+                            }
+                        }
+                        BlockModel::Cuboids(elements) => {
+                            for element in elements {
+                                let (min, max) = (element.min(), element.max());
+                                for face in Face::ALL {
+                                    // Faces inside the block are always drawn
+                                    let on_boundary = face.is_on_boundary(min, max);
+                                    if !on_boundary || neighbor(face).is_none_or(|n| !self.models.occludes(n)) {
+                                        self.add_quad(&mut vertices, &mut indices, face.box_geometry(origin, min, max), block, tint);
+                                    }
+                                }
+                            }
+                        }
+                        BlockModel::Cross => {
+                            for quad in cross_geometry(origin) {
+                                self.add_quad(&mut vertices, &mut indices, quad, block, tint);
+                            }
+                        }
+                        BlockModel::Fluid => {
+                            let covered = neighbor(Face::PosY) == Some(block);
+                            let height = if covered { 1.0 } else { FLUID_SURFACE_HEIGHT };
+                            for face in Face::ALL {
+                                if neighbor(face).is_none_or(|n| n != block && !self.models.occludes(n)) {
+                                    self.add_quad(&mut vertices, &mut indices, face.box_geometry(origin, [0.0; 3], [1.0, height, 1.0]), block, tint);
+                                }
+                            }
+                        }
                     }
-                    
-                    // -Y face
-                    if y == 0 || blocks[idx - 256] == 0 {
-                        self.add_face(&mut vertices, &mut indices, x, y, z, block, Face::NegY);
-                    }
-                    
-                    // +Z face
-                    if z == 15 || blocks[idx + 16] == 0 {
-                        self.add_face(&mut vertices, &mut indices, x, y, z, block, Face::PosZ);
+                }
+            }
+        }
+        
+        (vertices, indices)
+    }
+    
+    /// Whether a section holds nothing but air
+    fn is_all_air(blocks: &[u16; 4096]) -> bool {
+        blocks.iter().all(|&block| block == 0)
+    }
+    
+    /// Mesh a chunk section at a level of detail
+    ///
+    /// Medium and low LOD collapse 2×2×2 and 4×4×4 block groups into one
+    /// representative block (the most common non-air block; a group is solid
+    /// if any of its blocks is) and mesh the coarse grid. Filling
+    /// conservatively means the coarse surface never falls inside the full
+    /// one, and section walls are always emitted, so LOD borders stay closed.
+    pub fn mesh_section_lod(
+        &mut self,
+        blocks: &[u16; 4096],
+        section_y: i32,
+        neighbors: &ChunkNeighbors,
+        lod: LodLevel,
+    ) -> (Vec<MeshVertex>, Vec<u32>) {
+        let biomes = [0u16; biome::BIOMES_PER_SECTION];
+        self.mesh_section_lod_tinted(blocks, &biomes, &BiomeTintTable::new(), section_y, neighbors, lod)
+    }
+    
+    /// Mesh a chunk section at a level of detail, tinting grass and foliage
+    /// by the biome at each coarse cell's first block
+    pub fn mesh_section_lod_tinted(
+        &mut self,
+        blocks: &[u16; 4096],
+        biomes: &[u16; biome::BIOMES_PER_SECTION],
+        tints: &BiomeTintTable,
+        section_y: i32,
+        neighbors: &ChunkNeighbors,
+        lod: LodLevel,
+    ) -> (Vec<MeshVertex>, Vec<u32>) {
+        if lod == LodLevel::HighPoly {
+            return self.mesh_section_tinted(blocks, biomes, tints, section_y, neighbors);
+        }
+        if Self::is_all_air(blocks) {
+            return (Vec::new(), Vec::new());
+        }
+        let group = lod.block_group();
+        
+        let n = 16 / group;
+        let coarse = Self::downsample(blocks, group);
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+        
+        let cell = |x: usize, y: usize, z: usize| coarse[(y * n + z) * n + x];
+        for y in 0..n {
+            for z in 0..n {
+                for x in 0..n {
+                    let block = cell(x, y, z);
+                    if block == 0 {
+                        continue;
                     }
                     
-                    // -Z face
-                    if z == 0 || blocks[idx - 16] == 0 {
-                        self.add_face(&mut vertices, &mut indices, x, y, z, block, Face::NegZ);
+                    let biome_id = biomes[biome::biome_index(x * group, y * group, z * group)];
+                    let tint = tints.tint_for_block(block, biome_id);
+                    let faces = [
+                        (x == n - 1 || cell(x + 1, y, z) == 0, Face::PosX),
+                        (x == 0 || cell(x - 1, y, z) == 0, Face::NegX),
+                        (y == n - 1 || cell(x, y + 1, z) == 0, Face::PosY),
+                        (y == 0 || cell(x, y - 1, z) == 0, Face::NegY),
+                        (z == n - 1 || cell(x, y, z + 1) == 0, Face::PosZ),
+                        (z == 0 || cell(x, y, z - 1) == 0, Face::NegZ),
+                    ];
+                    for (visible, face) in faces {
+                        if visible {
+                            self.add_face(&mut vertices, &mut indices, x, y, z, block, tint, face);
+                        }
                     }
                 }
             }
         }
         
+        // Scale cells back to block units; UVs tile once per block
+        let scale = group as f32;
+        for vertex in &mut vertices {
+            for c in &mut vertex.position_normal[..3] {
+                *c *= scale;
+            }
+            vertex.uv_block[0] *= scale;
+            vertex.uv_block[1] *= scale;
+        }
+        
+        (vertices, indices)
+    }
+    
+    /// Mesh a chunk section at a level of detail, adding skirts towards
+    /// coarser neighbors
+    ///
+    /// `neighbor_lods` are the +X, -X, +Z and -Z neighbors' LODs (see
+    /// `NaniteManager::neighbor_lods`). Where a neighbor is coarser, its
+    /// border doesn't line up with this section's surface and the seam
+    /// shows cracks; a skirt is a wall on the shared edge running from each
+    /// column's surface down by the neighbor's block group, facing the
+    /// neighbor, which fills the gap from this side.
+    pub fn mesh_section_with_skirts(
+        &mut self,
+        blocks: &[u16; 4096],
+        section_y: i32,
+        neighbors: &ChunkNeighbors,
+        lod: LodLevel,
+        neighbor_lods: [LodLevel; 4],
+    ) -> (Vec<MeshVertex>, Vec<u32>) {
+        let (mut vertices, mut indices) = self.mesh_section_lod(blocks, section_y, neighbors, lod);
+        
+        let edges = [Face::PosX, Face::NegX, Face::PosZ, Face::NegZ];
+        let group = lod.block_group();
+        let tints = BiomeTintTable::new();
+        for (face, neighbor_lod) in edges.into_iter().zip(neighbor_lods) {
+            if neighbor_lod.block_group() <= group {
+                continue;
+            }
+            let depth = neighbor_lod.block_group() as f32;
+            
+            // One skirt segment per cell of this section along the edge
+            for start in (0..16).step_by(group) {
+                let Some((top, block)) = Self::edge_surface(blocks, face, start, group) else {
+                    continue;
+                };
+                let top = top.div_ceil(group) * group;
+                let (top, bottom) = (top as f32, (top as f32 - depth).max(0.0));
+                let (from, to) = (start as f32, (start + group) as f32);
+                let positions = match face {
+                    Face::PosX => [[16.0, bottom, from], [16.0, top, from], [16.0, top, to], [16.0, bottom, to]],
+                    Face::NegX => [[0.0, bottom, to], [0.0, top, to], [0.0, top, from], [0.0, bottom, from]],
+                    Face::PosZ => [[to, bottom, 16.0], [to, top, 16.0], [from, top, 16.0], [from, bottom, 16.0]],
+                    _ => [[from, bottom, 0.0], [from, top, 0.0], [to, top, 0.0], [to, bottom, 0.0]],
+                };
+                let normal = match face {
+                    Face::PosX => [1.0, 0.0, 0.0],
+                    Face::NegX => [-1.0, 0.0, 0.0],
+                    Face::PosZ => [0.0, 0.0, 1.0],
+                    _ => [0.0, 0.0, -1.0],
+                };
+                let height = top - bottom;
+                let width = group as f32;
+                let uvs = [[0.0, height], [0.0, 0.0], [width, 0.0], [width, height]];
+                let tint = tints.tint_for_block(block, 0);
+                self.add_quad(&mut vertices, &mut indices, (positions, normal, uvs), block, tint);
+            }
+        }
+        
         (vertices, indices)
     }
     
+    /// Highest surface (block units, exclusive) and its block among the
+    /// `width` edge columns from `start` along a section edge
+    fn edge_surface(blocks: &[u16; 4096], face: Face, start: usize, width: usize) -> Option<(usize, u16)> {
+        let column = |t: usize| match face {
+            Face::PosX => (15, t),
+            Face::NegX => (0, t),
+            Face::PosZ => (t, 15),
+            _ => (t, 0),
+        };
+        (start..start + width)
+            .filter_map(|t| {
+                let (x, z) = column(t);
+                (0..16).rev().find_map(|y| {
+                    let block = blocks[(y << 8) | (z << 4) | x];
+                    (block != 0).then_some((y + 1, block))
+                })
+            })
+            .max_by_key(|&(top, _)| top)
+    }
+    
+    /// Collapse `group`³ block groups into one block each
+    fn downsample(blocks: &[u16; 4096], group: usize) -> Vec<u16> {
+        let n = 16 / group;
+        let mut coarse = vec![0u16; n * n * n];
+        let mut counts: Vec<(u16, u32)> = Vec::with_capacity(group * group * group);
+        
+        for cy in 0..n {
+            for cz in 0..n {
+                for cx in 0..n {
+                    counts.clear();
+                    for y in cy * group..(cy + 1) * group {
+                        for z in cz * group..(cz + 1) * group {
+                            for x in cx * group..(cx + 1) * group {
+                                let block = blocks[(y << 8) | (z << 4) | x];
+                                if block == 0 {
+                                    continue;
+                                }
+                                match counts.iter_mut().find(|(b, _)| *b == block) {
+                                    Some((_, count)) => *count += 1,
+                                    None => counts.push((block, 1)),
+                                }
+                            }
+                        }
+                    }
+                    // Ties go to the first block seen, keeping the result deterministic
+                    let best = counts.iter().fold(None, |best: Option<(u16, u32)>, &(b, c)| match best {
+                        Some((_, bc)) if bc >= c => best,
+                        _ => Some((b, c)),
+                    });
+                    coarse[(cy * n + cz) * n + cx] = best.map_or(0, |(b, _)| b);
+                }
+            }
+        }
+        
+        coarse
+    }
+    
     /// Add a face to the mesh
     fn add_face(
         &self,
@@ -557,16 +925,28 @@ impl ChunkMesher {
         y: usize,
         z: usize,
         block: u16,
+        tint: u32,
         face: Face,
+    ) {
+        self.add_quad(vertices, indices, face.get_geometry(x as f32, y as f32, z as f32), block, tint);
+    }
+    
+    /// Add a quad to the mesh
+    fn add_quad(
+        &self,
+        vertices: &mut Vec<MeshVertex>,
+        indices: &mut Vec<u32>,
+        (positions, normal, uvs): FaceGeometry,
+        block: u16,
+        tint: u32,
     ) {
         let base_idx = vertices.len() as u32;
         
-        let (positions, normal, uvs) = face.get_geometry(x as f32, y as f32, z as f32);
-        
         for i in 0..4 {
             vertices.push(MeshVertex {
                 position_normal: [positions[i][0], positions[i][1], positions[i][2], Self::pack_normal(normal)],
-                uv_block: [uvs[i][0], uvs[i][1], block as f32, 0.0],
+                uv_block: [uvs[i][0], uvs[i][1], block as f32],
+                tint,
                 ao_light: [1.0, 1.0, 1.0, 1.0], // Would calculate AO
             });
         }
@@ -593,8 +973,11 @@ impl Default for ChunkMesher {
     }
 }
 
+/// Quad corner positions, normal and corner UVs
+type FaceGeometry = ([[f32; 3]; 4], [f32; 3], [[f32; 2]; 4]);
+
 /// Face direction
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Face {
     PosX,
     NegX,
@@ -605,73 +988,109 @@ enum Face {
 }
 
 impl Face {
-    /// Get face geometry
-    fn get_geometry(&self, x: f32, y: f32, z: f32) -> ([[f32; 3]; 4], [f32; 3], [[f32; 2]; 4]) {
+    /// All faces
+    const ALL: [Face; 6] = [Face::PosX, Face::NegX, Face::PosY, Face::NegY, Face::PosZ, Face::NegZ];
+    
+    /// Get the section index offset of the neighbor across this face
+    fn index_offset(self) -> isize {
+        match self {
+            Face::PosX => 1,
+            Face::NegX => -1,
+            Face::PosY => 256,
+            Face::NegY => -256,
+            Face::PosZ => 16,
+            Face::NegZ => -16,
+        }
+    }
+    
+    /// Check if this face of a box (block units) lies on the block boundary
+    fn is_on_boundary(self, min: [f32; 3], max: [f32; 3]) -> bool {
         match self {
+            Face::PosX => max[0] >= 1.0,
+            Face::NegX => min[0] <= 0.0,
+            Face::PosY => max[1] >= 1.0,
+            Face::NegY => min[1] <= 0.0,
+            Face::PosZ => max[2] >= 1.0,
+            Face::NegZ => min[2] <= 0.0,
+        }
+    }
+    
+    /// Get face geometry of a full block
+    fn get_geometry(&self, x: f32, y: f32, z: f32) -> FaceGeometry {
+        self.box_geometry([x, y, z], [0.0; 3], [1.0; 3])
+    }
+    
+    /// Get face geometry of a box spanning `min..max` (block units) inside the block at `origin`
+    ///
+    /// UVs cover the part of the texture the box face covers.
+    fn box_geometry(&self, origin: [f32; 3], min: [f32; 3], max: [f32; 3]) -> FaceGeometry {
+        let [ox, oy, oz] = origin;
+        let (x0, y0, z0) = (ox + min[0], oy + min[1], oz + min[2]);
+        let (x1, y1, z1) = (ox + max[0], oy + max[1], oz + max[2]);
+        
+        let (positions, normal) = match self {
             Face::PosX => (
-                [
-                    [x + 1.0, y, z],
-                    [x + 1.0, y + 1.0, z],
-                    [x + 1.0, y + 1.0, z + 1.0],
-                    [x + 1.0, y, z + 1.0],
-                ],
+                [[x1, y0, z0], [x1, y1, z0], [x1, y1, z1], [x1, y0, z1]],
                 [1.0, 0.0, 0.0],
-                [[0.0, 1.0], [0.0, 0.0], [1.0, 0.0], [1.0, 1.0]],
             ),
             Face::NegX => (
-                [
-                    [x, y, z + 1.0],
-                    [x, y + 1.0, z + 1.0],
-                    [x, y + 1.0, z],
-                    [x, y, z],
-                ],
+                [[x0, y0, z1], [x0, y1, z1], [x0, y1, z0], [x0, y0, z0]],
                 [-1.0, 0.0, 0.0],
-                [[0.0, 1.0], [0.0, 0.0], [1.0, 0.0], [1.0, 1.0]],
             ),
             Face::PosY => (
-                [
-                    [x, y + 1.0, z],
-                    [x, y + 1.0, z + 1.0],
-                    [x + 1.0, y + 1.0, z + 1.0],
-                    [x + 1.0, y + 1.0, z],
-                ],
+                [[x0, y1, z0], [x0, y1, z1], [x1, y1, z1], [x1, y1, z0]],
                 [0.0, 1.0, 0.0],
-                [[0.0, 0.0], [0.0, 1.0], [1.0, 1.0], [1.0, 0.0]],
             ),
             Face::NegY => (
-                [
-                    [x, y, z + 1.0],
-                    [x, y, z],
-                    [x + 1.0, y, z],
-                    [x + 1.0, y, z + 1.0],
-                ],
+                [[x0, y0, z1], [x0, y0, z0], [x1, y0, z0], [x1, y0, z1]],
                 [0.0, -1.0, 0.0],
-                [[0.0, 0.0], [0.0, 1.0], [1.0, 1.0], [1.0, 0.0]],
             ),
             Face::PosZ => (
-                [
-                    [x + 1.0, y, z + 1.0],
-                    [x + 1.0, y + 1.0, z + 1.0],
-                    [x, y + 1.0, z + 1.0],
-                    [x, y, z + 1.0],
-                ],
+                [[x1, y0, z1], [x1, y1, z1], [x0, y1, z1], [x0, y0, z1]],
                 [0.0, 0.0, 1.0],
-                [[0.0, 1.0], [0.0, 0.0], [1.0, 0.0], [1.0, 1.0]],
             ),
             Face::NegZ => (
-                [
-                    [x, y, z],
-                    [x, y + 1.0, z],
-                    [x + 1.0, y + 1.0, z],
-                    [x + 1.0, y, z],
-                ],
+                [[x0, y0, z0], [x0, y1, z0], [x1, y1, z0], [x1, y0, z0]],
                 [0.0, 0.0, -1.0],
-                [[0.0, 1.0], [0.0, 0.0], [1.0, 0.0], [1.0, 1.0]],
             ),
-        }
+        };
+        
+        let uvs = positions.map(|[px, py, pz]| {
+            let (lx, ly, lz) = (px - ox, py - oy, pz - oz);
+            match self {
+                Face::PosX => [lz, 1.0 - ly],
+                Face::NegX => [1.0 - lz, 1.0 - ly],
+                Face::PosY => [lx, lz],
+                Face::NegY => [lx, 1.0 - lz],
+                Face::PosZ => [1.0 - lx, 1.0 - ly],
+                Face::NegZ => [lx, 1.0 - ly],
+            }
+        });
+        
+        (positions, normal, uvs)
     }
 }
 
+/// Get the quads of a cross model: both diagonals, each facing both ways
+fn cross_geometry([x, y, z]: [f32; 3]) -> [FaceGeometry; 4] {
+    let d = std::f32::consts::FRAC_1_SQRT_2;
+    let uvs = [[0.0, 1.0], [0.0, 0.0], [1.0, 0.0], [1.0, 1.0]];
+    let diagonal = |from: [f32; 2], to: [f32; 2], normal: [f32; 3]| -> [FaceGeometry; 2] {
+        let front = [
+            [x + from[0], y, z + from[1]],
+            [x + from[0], y + 1.0, z + from[1]],
+            [x + to[0], y + 1.0, z + to[1]],
+            [x + to[0], y, z + to[1]],
+        ];
+        let back = [front[3], front[2], front[1], front[0]];
+        [(front, normal, uvs), (back, normal.map(|n| -n), uvs)]
+    };
+    
+    let [a, b] = diagonal([0.0, 0.0], [1.0, 1.0], [d, 0.0, -d]);
+    let [c, e] = diagonal([1.0, 0.0], [0.0, 1.0], [d, 0.0, d]);
+    [a, b, c, e]
+}
+
 /// Chunk neighbor data for cross-chunk face culling
 pub struct ChunkNeighbors {
     pub pos_x: Option<Vec<u16>>,
@@ -690,3 +1109,172 @@ impl Default for ChunkNeighbors {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lod_reduces_vertex_count() {
+        // Dense terrain: solid below a bumpy surface, with a few caves
+        let mut blocks = [0u16; 4096];
+        for y in 0..16 {
+            for z in 0..16 {
+                for x in 0..16 {
+                    let surface = 10 + (x * 7 + z * 3) % 5;
+                    let cave = (x + y + z) % 7 == 0 && y < 8;
+                    if y < surface && !cave {
+                        blocks[(y << 8) | (z << 4) | x] = if y + 1 == surface { 2 } else { 1 };
+                    }
+                }
+            }
+        }
+
+        let mut mesher = ChunkMesher::new();
+        let neighbors = ChunkNeighbors::default();
+        let (full, _) = mesher.mesh_section_lod(&blocks, 0, &neighbors, LodLevel::HighPoly);
+        let (medium, _) = mesher.mesh_section_lod(&blocks, 0, &neighbors, LodLevel::MediumPoly);
+        let (low, low_indices) = mesher.mesh_section_lod(&blocks, 0, &neighbors, LodLevel::LowPoly);
+
+        assert!(medium.len() < full.len() / 2);
+        assert!(low.len() < full.len() / 8);
+        assert_eq!(low_indices.len(), low.len() / 4 * 6);
+
+        // Coarse geometry still spans the section
+        let max = low.iter().map(|v| v.position_normal[0]).fold(0.0f32, f32::max);
+        assert_eq!(max, 16.0);
+    }
+
+    #[test]
+    fn test_skirts_on_edges_facing_coarser_neighbors() {
+        // Flat ground 5 blocks deep
+        let mut blocks = [0u16; 4096];
+        for y in 0..5 {
+            for i in 0..256 {
+                blocks[(y << 8) | i] = 1;
+            }
+        }
+
+        let mut mesher = ChunkMesher::new();
+        let neighbors = ChunkNeighbors::default();
+        let (plain, _) = mesher.mesh_section(&blocks, 0, &neighbors);
+        let same = [LodLevel::HighPoly; 4];
+        let (unchanged, _) = mesher.mesh_section_with_skirts(&blocks, 0, &neighbors, LodLevel::HighPoly, same);
+        assert_eq!(unchanged.len(), plain.len());
+
+        // Only +X is coarser: one skirt quad per block along that edge
+        let lods = [LodLevel::LowPoly, LodLevel::HighPoly, LodLevel::HighPoly, LodLevel::HighPoly];
+        let (skirted, indices) = mesher.mesh_section_with_skirts(&blocks, 0, &neighbors, LodLevel::HighPoly, lods);
+        assert_eq!(skirted.len(), plain.len() + 16 * 4);
+        assert_eq!(indices.len(), skirted.len() / 4 * 6);
+
+        let skirt = &skirted[plain.len()..];
+        assert!(skirt.iter().all(|v| v.position_normal[0] == 16.0));
+        assert_eq!(skirt[0].position_normal[3].to_bits(), ChunkMesher::pack_normal([1.0, 0.0, 0.0]).to_bits());
+        // Runs from the surface down by the neighbor's 4-block group
+        let top = skirt.iter().map(|v| v.position_normal[1]).fold(0.0f32, f32::max);
+        let bottom = skirt.iter().map(|v| v.position_normal[1]).fold(16.0f32, f32::min);
+        assert_eq!((top, bottom), (5.0, 1.0));
+    }
+
+    #[test]
+    fn test_slab_model_meshes_half_height_box() {
+        const SLAB: u16 = 44;
+        let mut blocks = [0u16; 4096];
+        blocks[0] = 1;
+        blocks[1] = SLAB;
+
+        let mut mesher = ChunkMesher::new();
+        let neighbors = ChunkNeighbors::default();
+        let (cubes, _) = mesher.mesh_section(&blocks, 0, &neighbors);
+        assert_eq!(cubes.len(), 10 * 4); // Shared face culled
+
+        mesher.register_model(SLAB, BlockModel::slab());
+        let (vertices, indices) = mesher.mesh_section(&blocks, 0, &neighbors);
+        assert_eq!(indices.len(), vertices.len() / 4 * 6);
+
+        // The slab no longer hides the stone's +X face; the stone still hides the slab's -X face
+        assert_eq!(vertices.len(), 11 * 4);
+        let slab: Vec<_> = vertices.iter().filter(|v| v.uv_block[2] == SLAB as f32).collect();
+        assert_eq!(slab.len(), 5 * 4);
+        let top = slab.iter().map(|v| v.position_normal[1]).fold(0.0f32, f32::max);
+        assert_eq!(top, 0.5);
+
+        // Side UVs cover only the lower half of the texture
+        let pos_x = ChunkMesher::pack_normal([1.0, 0.0, 0.0]).to_bits();
+        let side = slab.iter().filter(|v| v.position_normal[3].to_bits() == pos_x);
+        let min_v = side.map(|v| v.uv_block[1]).fold(1.0f32, f32::min);
+        assert_eq!(min_v, 0.5);
+    }
+
+    #[test]
+    fn test_tinted_vertices_carry_biome_color() {
+        let mut mesher = ChunkMesher::new();
+        let neighbors = ChunkNeighbors::default();
+        let mut tints = BiomeTintTable::new();
+        tints.set_tint(7, 0x7CBD6B);
+        let mut biomes = [0u16; biome::BIOMES_PER_SECTION];
+        biomes[biome::biome_index(0, 0, 0)] = 7;
+
+        // Grass in the tinted biome, stone next to it stays white
+        let mut blocks = [0u16; 4096];
+        blocks[0] = 2;
+        blocks[1] = 1;
+        let (vertices, _) = mesher.mesh_section_tinted(&blocks, &biomes, &tints, 0, &neighbors);
+        let grass = vertices.iter().filter(|v| v.uv_block[2] == 2.0);
+        assert!(grass.clone().count() > 0);
+        assert!(grass.clone().all(|v| v.tint == 0x7CBD6B));
+        assert!(vertices.iter().filter(|v| v.uv_block[2] == 1.0).all(|v| v.tint == biome::DEFAULT_TINT));
+
+        // The coarse grass cell takes its biome's color too
+        blocks[1] = 2;
+        let (coarse, _) = mesher.mesh_section_lod_tinted(&blocks, &biomes, &tints, 0, &neighbors, LodLevel::MediumPoly);
+        assert!(!coarse.is_empty());
+        assert!(coarse.iter().all(|v| v.tint == 0x7CBD6B));
+        let packed = super::super::PackedMeshVertex::pack(&coarse[0]).unwrap();
+        assert_eq!(packed.tint, 0x7CBD6B);
+    }
+
+    #[test]
+    fn test_empty_sections_mesh_to_nothing() {
+        let mut mesher = ChunkMesher::new();
+        let (tints, neighbors) = (BiomeTintTable::new(), ChunkNeighbors::default());
+        let mut section = ChunkSection::new(3);
+        assert!(mesher.mesh_chunk_section(&section, &tints, &neighbors).0.is_empty());
+        let air = [0u16; 4096];
+        assert!(mesher.mesh_section(&air, 3, &neighbors).0.is_empty());
+        assert!(mesher.mesh_section_lod(&air, 3, &neighbors, LodLevel::LowPoly).1.is_empty());
+
+        // A lone block meshes the same as through the raw array path
+        section.set_block(2, 4, 6, 1);
+        let mut blocks = [0u16; 4096];
+        blocks[(4 << 8) | (6 << 4) | 2] = 1;
+        let (vertices, indices) = mesher.mesh_chunk_section(&section, &tints, &neighbors);
+        let (expected, _) = mesher.mesh_section(&blocks, 3, &neighbors);
+        assert_eq!((vertices.len(), indices.len()), (expected.len(), 36));
+    }
+
+    #[test]
+    fn test_chunk_slots_free_on_remove() {
+        let data = |meshlet_count| ChunkMeshData { meshlet_count, ..Default::default() };
+        let mut slots = ChunkSlots::new(8);
+        slots.set(2, data(5));
+        slots.set(6, data(3));
+        assert_eq!(slots.count(), 7);
+        assert_eq!(slots.draw_command(6).group_count_x, 3);
+
+        // Removing the last slot shrinks the draw range back to the next occupied one
+        slots.clear(6);
+        assert_eq!(slots.count(), 3);
+        assert_eq!(slots.draw_command(6).group_count_x, 0);
+
+        // A freed slot in the middle draws nothing but keeps the range
+        slots.set(4, data(1));
+        slots.clear(2);
+        assert_eq!(slots.get(2).meshlet_count, 0);
+        assert_eq!(slots.count(), 5);
+
+        slots.set(4, data(0));
+        assert_eq!(slots.count(), 0);
+    }
+}
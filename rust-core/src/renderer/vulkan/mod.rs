@@ -9,22 +9,51 @@ pub mod swapchain;
 pub mod pipeline;
 pub mod buffer;
 pub mod texture;
+pub mod sampler;
 pub mod command;
 pub mod sync;
 pub mod mesh_shader;
+pub mod block_model;
+pub mod chunk_path;
+pub mod gpu_cull;
+pub mod entity_draw;
+pub mod upload;
 pub mod interop;
+pub mod memory;
+pub mod allocator;
+pub mod watchdog;
+pub mod secondary;
+pub mod barrier;
+pub mod features;
+pub mod vertex_format;
+pub mod frame;
 
+use std::collections::HashMap;
 use std::sync::Arc;
 use ash::vk;
 
+use crate::events::EventQueue;
+
 pub use instance::VulkanInstance;
 pub use device::VulkanDevice;
-pub use swapchain::Swapchain;
+pub use swapchain::{PresentMode, Swapchain};
+pub use features::{FeatureReport, OptionalFeature};
 pub use pipeline::Pipeline;
 pub use buffer::{Buffer, BufferType};
 pub use texture::Texture;
+pub use sampler::{SamplerCache, SamplerDesc};
 pub use command::CommandPool;
 pub use sync::SyncObjects;
+pub use block_model::{BlockModel, BlockModelRegistry, Cuboid};
+pub use chunk_path::{ChunkRenderPath, ChunkPathKind};
+pub use gpu_cull::{GpuCullPass, ChunkBounds, ChunkDrawCommand};
+pub use upload::{UploadScheduler, MeshedChunk, ChunkKey};
+pub use memory::GpuMemoryTracker;
+pub use allocator::{GpuAllocator, Suballocation};
+pub use watchdog::GpuWatchdog;
+pub use secondary::{ParallelRecorder, SecondaryRecorder};
+pub use vertex_format::{ChunkVertexFormat, PackedMeshVertex};
+pub use frame::{FrameResourceSet, FrameResources, ImageResources};
 
 /// Vulkan renderer configuration
 #[derive(Debug, Clone)]
@@ -37,12 +66,20 @@ pub struct VulkanConfig {
     pub validation_enabled: bool,
     /// Preferred present mode
     pub preferred_present_mode: vk::PresentModeKHR,
+    /// Use an HDR swapchain format when the display supports one
+    pub hdr: bool,
     /// Max frames in flight
     pub max_frames_in_flight: u32,
     /// Enable mesh shaders if available
     pub mesh_shaders_enabled: bool,
     /// Enable ray tracing if available
     pub ray_tracing_enabled: bool,
+    /// Anisotropic filtering level (1, 2, 4, 8, 16); clamped to device limit
+    pub anisotropy_level: u32,
+    /// Chunk mesh bytes uploaded per frame
+    pub upload_budget_bytes: usize,
+    /// Vertex layout for chunk vertex buffers on the indexed path
+    pub chunk_vertex_format: ChunkVertexFormat,
 }
 
 impl Default for VulkanConfig {
@@ -52,9 +89,13 @@ impl Default for VulkanConfig {
             app_version: vk::make_api_version(0, 1, 0, 0),
             validation_enabled: cfg!(debug_assertions),
             preferred_present_mode: vk::PresentModeKHR::MAILBOX,
+            hdr: false,
             max_frames_in_flight: 2,
             mesh_shaders_enabled: true,
             ray_tracing_enabled: false,
+            anisotropy_level: 16,
+            upload_budget_bytes: 8 * 1024 * 1024,
+            chunk_vertex_format: ChunkVertexFormat::Full,
         }
     }
 }
@@ -73,6 +114,16 @@ pub struct VulkanRenderer {
     command_pool: Option<CommandPool>,
     /// Synchronization objects
     sync: Option<SyncObjects>,
+    /// Sync handles per frame in flight, framebuffers per swapchain image
+    frames: Option<FrameResourceSet<FrameResources, ImageResources>>,
+    /// Chunk render path (mesh shaders or indexed fallback)
+    chunk_path: Option<Box<dyn ChunkRenderPath>>,
+    /// Pending chunk uploads
+    uploads: UploadScheduler,
+    /// Chunk render path slot per chunk
+    chunk_slots: HashMap<ChunkKey, usize>,
+    /// Released chunk slots
+    free_slots: Vec<usize>,
     /// Current frame index
     current_frame: usize,
     /// Configuration
@@ -84,6 +135,14 @@ pub struct VulkanRenderer {
 impl VulkanRenderer {
     /// Create a new Vulkan renderer
     pub fn new(config: VulkanConfig) -> Result<Self, VulkanError> {
+        Self::with_watchdog(config, Arc::new(GpuWatchdog::new(EventQueue::new())))
+    }
+    
+    /// Create a Vulkan renderer whose device reports hangs to `watchdog`
+    ///
+    /// Pass `QuantumRenderer::watchdog()` so the frame loop that checks it
+    /// also recovers from hangs and device loss seen here.
+    pub fn with_watchdog(config: VulkanConfig, watchdog: Arc<GpuWatchdog>) -> Result<Self, VulkanError> {
         log::info!("Creating Vulkan renderer...");
         
         // Create instance
@@ -91,11 +150,10 @@ impl VulkanRenderer {
         log::info!("  Vulkan instance created");
         
         // Create device
-        let device = Arc::new(VulkanDevice::new(instance.clone(), &config)?);
+        let device = Arc::new(VulkanDevice::with_watchdog(instance.clone(), &config, watchdog)?);
         log::info!("  Vulkan device created");
         log::info!("  GPU: {}", device.gpu_name());
-        log::info!("  Mesh shaders: {}", device.supports_mesh_shaders());
-        log::info!("  Ray tracing: {}", device.supports_ray_tracing());
+        device.feature_report().log();
         
         Ok(Self {
             instance,
@@ -104,6 +162,11 @@ impl VulkanRenderer {
             pipeline: None,
             command_pool: None,
             sync: None,
+            frames: None,
+            chunk_path: None,
+            uploads: UploadScheduler::new(),
+            chunk_slots: HashMap::new(),
+            free_slots: Vec::new(),
             current_frame: 0,
             config,
             initialized: false,
@@ -144,6 +207,28 @@ impl VulkanRenderer {
         )?);
         log::info!("  Graphics pipeline created");
         
+        // Framebuffers and command buffers follow the swapchain's image count
+        let images = ImageResources::create_all(
+            &self.device,
+            self.command_pool.as_mut().unwrap(),
+            self.pipeline.as_ref().unwrap().render_pass(),
+            self.swapchain.as_ref().unwrap(),
+        )?;
+        self.frames = Some(FrameResourceSet::new(FrameResources::from_sync(self.sync.as_ref().unwrap()), images));
+        log::info!("  Frame resources created ({} frames in flight, {} swapchain images)",
+            self.config.max_frames_in_flight, self.swapchain.as_ref().unwrap().image_count());
+        
+        // Select chunk render path
+        let pipeline = self.pipeline.as_ref().unwrap();
+        self.chunk_path = Some(chunk_path::create_chunk_render_path(
+            self.device.clone(),
+            pipeline.render_pass(),
+            pipeline.vertex_handle(),
+            pipeline.layout(),
+            self.config.chunk_vertex_format,
+            4096,
+        )?);
+        
         self.initialized = true;
         log::info!("Vulkan renderer initialized");
         
@@ -165,9 +250,21 @@ impl VulkanRenderer {
         // Acquire next image
         let image_index = swapchain.acquire_next_image(sync.image_available(self.current_frame))?;
         
+        // The image may still be in use by an older frame in flight
+        let frames = self.frames.as_mut().unwrap();
+        let frame_fence = frames.frame(self.current_frame).in_flight;
+        let image = frames.image_mut(image_index as usize);
+        if image.in_flight != vk::Fence::null() && image.in_flight != frame_fence {
+            self.device.wait_for_fences(&[image.in_flight], "swapchain image fence")?;
+        }
+        image.in_flight = frame_fence;
+        
         // Reset fence
         sync.reset_fence(self.current_frame)?;
         
+        // Upload this frame's share of pending chunks
+        self.process_uploads()?;
+        
         Ok(FrameContext {
             frame_index: self.current_frame,
             image_index: image_index as usize,
@@ -194,6 +291,62 @@ impl VulkanRenderer {
         Ok(())
     }
     
+    /// Queue a meshed chunk for upload (priority = distance to camera)
+    pub fn queue_chunk_upload(&mut self, chunk: MeshedChunk, priority: f32) {
+        self.uploads.queue(chunk, priority);
+    }
+    
+    /// Remove a chunk from the GPU and the upload queue
+    pub fn remove_chunk(&mut self, key: ChunkKey) {
+        self.uploads.cancel(key);
+        if let Some(slot) = self.chunk_slots.remove(&key) {
+            if let Some(path) = self.chunk_path.as_mut() {
+                path.remove_chunk(slot);
+            }
+            self.free_slots.push(slot);
+        }
+    }
+    
+    /// Upload queued chunks within the per-frame byte budget
+    pub fn process_uploads(&mut self) -> Result<usize, VulkanError> {
+        let path = match self.chunk_path.as_mut() {
+            Some(path) => path,
+            None => return Ok(0),
+        };
+        
+        let completed = self.uploads.drain_budget(self.config.upload_budget_bytes);
+        let count = completed.len();
+        
+        for chunk in completed {
+            let slot = match self.chunk_slots.get(&chunk.key) {
+                Some(&slot) => slot,
+                None => {
+                    let slot = self.free_slots.pop().unwrap_or(self.chunk_slots.len());
+                    self.chunk_slots.insert(chunk.key, slot);
+                    slot
+                }
+            };
+            
+            let origin = [
+                (chunk.key.0 * 16) as f32,
+                (chunk.key.1 * 16) as f32,
+                (chunk.key.2 * 16) as f32,
+            ];
+            path.upload_chunk(slot, origin, &chunk.vertices, &chunk.indices)?;
+        }
+        
+        if self.uploads.pending() > 0 {
+            log::trace!("Deferred {} chunk uploads ({} bytes)", self.uploads.pending(), self.uploads.pending_bytes());
+        }
+        
+        Ok(count)
+    }
+    
+    /// Get number of chunks waiting for upload
+    pub fn pending_uploads(&self) -> usize {
+        self.uploads.pending()
+    }
+    
     /// Resize the swapchain
     pub fn resize(&mut self, width: u32, height: u32) -> Result<(), VulkanError> {
         if !self.initialized {
@@ -207,12 +360,73 @@ impl VulkanRenderer {
         if let Some(ref mut swapchain) = self.swapchain {
             swapchain.recreate(width, height)?;
         }
+        self.rebuild_image_resources()?;
         
         log::info!("Swapchain resized to {}x{}", width, height);
         
         Ok(())
     }
     
+    /// Switch present mode (vsync) without restarting; returns the mode in effect
+    ///
+    /// Waits for the GPU, recreates the swapchain and the per-frame sync
+    /// objects. Modes the surface doesn't support fall back to FIFO.
+    pub fn set_present_mode(&mut self, mode: PresentMode) -> Result<PresentMode, VulkanError> {
+        if !self.initialized {
+            return Err(VulkanError::NotInitialized);
+        }
+        let swapchain = self.swapchain.as_mut().ok_or(VulkanError::NotInitialized)?;
+        
+        self.device.wait_idle()?;
+        let mode = swapchain.set_present_mode(mode)?;
+        
+        // Old semaphores may still be tied to the retired swapchain's images
+        self.sync = None;
+        self.sync = Some(SyncObjects::new(
+            self.device.clone(),
+            self.config.max_frames_in_flight as usize,
+        )?);
+        if let Some(frames) = self.frames.as_mut() {
+            frames.replace_frames(FrameResources::from_sync(self.sync.as_ref().unwrap()));
+        }
+        self.rebuild_image_resources()?;
+        self.current_frame = 0;
+        self.config.preferred_present_mode = mode.to_vk();
+        
+        log::info!("Present mode set to {:?}", mode);
+        Ok(mode)
+    }
+    
+    /// Recreate per-image resources to match the current swapchain (GPU idle)
+    fn rebuild_image_resources(&mut self) -> Result<(), VulkanError> {
+        let (Some(frames), Some(pool), Some(pipeline), Some(swapchain)) =
+            (self.frames.as_mut(), self.command_pool.as_mut(), self.pipeline.as_ref(), self.swapchain.as_ref())
+        else {
+            return Ok(());
+        };
+        let old = frames.replace_images(Vec::new());
+        ImageResources::destroy_all(&self.device, pool, old);
+        frames.replace_images(ImageResources::create_all(&self.device, pool, pipeline.render_pass(), swapchain)?);
+        Ok(())
+    }
+    
+    /// Get a frame in flight's sync objects
+    pub fn frame_resources(&self, frame_index: usize) -> Option<&FrameResources> {
+        let frames = self.frames.as_ref()?;
+        (frame_index < frames.frames_in_flight()).then(|| frames.frame(frame_index))
+    }
+    
+    /// Get a swapchain image's framebuffer and command buffer
+    pub fn image_resources(&self, image_index: usize) -> Option<&ImageResources> {
+        let frames = self.frames.as_ref()?;
+        (image_index < frames.image_count()).then(|| frames.image(image_index))
+    }
+    
+    /// Get the present mode in effect
+    pub fn present_mode(&self) -> Option<PresentMode> {
+        self.swapchain.as_ref().map(Swapchain::present_mode)
+    }
+    
     /// Shutdown the renderer
     pub fn shutdown(&mut self) {
         if !self.initialized {
@@ -225,6 +439,13 @@ impl VulkanRenderer {
         let _ = self.device.wait_idle();
         
         // Cleanup in reverse order
+        self.uploads.clear();
+        self.chunk_slots.clear();
+        self.free_slots.clear();
+        self.chunk_path = None;
+        if let (Some(mut frames), Some(pool)) = (self.frames.take(), self.command_pool.as_mut()) {
+            ImageResources::destroy_all(&self.device, pool, frames.replace_images(Vec::new()));
+        }
         self.pipeline = None;
         self.sync = None;
         self.command_pool = None;
@@ -244,6 +465,17 @@ impl VulkanRenderer {
         self.device.supports_ray_tracing()
     }
     
+    /// Get which optional features are supported and enabled
+    /// (pass to `TheWeaver::set_capabilities` for bug reports)
+    pub fn feature_report(&self) -> &FeatureReport {
+        self.device.feature_report()
+    }
+    
+    /// Get the active chunk render path
+    pub fn chunk_path(&mut self) -> Option<&mut (dyn ChunkRenderPath + 'static)> {
+        self.chunk_path.as_deref_mut()
+    }
+    
     /// Get device reference
     pub fn device(&self) -> &Arc<VulkanDevice> {
         &self.device
@@ -292,6 +524,12 @@ pub enum VulkanError {
     SurfaceLost,
     /// Out of date swapchain
     OutOfDate,
+    /// Device memory exhausted on a heap
+    OutOfDeviceMemory { heap: u32, requested: u64 },
+    /// A GPU wait exceeded the watchdog timeout
+    GpuTimeout(String),
+    /// The device was lost and must be recreated
+    DeviceLost,
     /// Generic Vulkan error
     VkError(String),
 }
@@ -311,6 +549,11 @@ impl std::fmt::Display for VulkanError {
             VulkanError::NotInitialized => write!(f, "Renderer not initialized"),
             VulkanError::SurfaceLost => write!(f, "Surface lost"),
             VulkanError::OutOfDate => write!(f, "Swapchain out of date"),
+            VulkanError::OutOfDeviceMemory { heap, requested } => {
+                write!(f, "Out of device memory: {} bytes requested on heap {}", requested, heap)
+            }
+            VulkanError::GpuTimeout(op) => write!(f, "GPU timed out during {}", op),
+            VulkanError::DeviceLost => write!(f, "Device lost"),
             VulkanError::VkError(msg) => write!(f, "Vulkan error: {}", msg),
         }
     }
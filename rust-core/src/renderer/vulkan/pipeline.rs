@@ -5,7 +5,21 @@
 use std::sync::Arc;
 use ash::vk;
 
-use super::{VulkanConfig, VulkanDevice, VulkanError, Swapchain};
+use super::{ChunkVertexFormat, VulkanConfig, VulkanDevice, VulkanError, Swapchain};
+
+/// Check push constant ranges against the device's `maxPushConstantsSize`
+pub fn validate_push_constants(ranges: &[vk::PushConstantRange], max_size: u32) -> Result<(), VulkanError> {
+    for range in ranges {
+        let end = range.offset as u64 + range.size as u64;
+        if end > max_size as u64 {
+            return Err(VulkanError::PipelineCreationFailed(format!(
+                "Push constant range {}..{} ({:?}) exceeds maxPushConstantsSize of {} bytes",
+                range.offset, end, range.stage_flags, max_size,
+            )));
+        }
+    }
+    Ok(())
+}
 
 /// Graphics pipeline wrapper
 pub struct Pipeline {
@@ -15,6 +29,8 @@ pub struct Pipeline {
     layout: vk::PipelineLayout,
     /// Graphics pipeline
     pipeline: vk::Pipeline,
+    /// Vertex/index pipeline for the traditional chunk path
+    vertex_pipeline: vk::Pipeline,
     /// Render pass
     render_pass: vk::RenderPass,
     /// Descriptor set layout
@@ -49,25 +65,26 @@ impl Pipeline {
             .set_layouts(&layouts)
             .push_constant_ranges(&push_constant_ranges);
         
-        let layout = unsafe {
-            device.handle().create_pipeline_layout(&layout_info, None)
-                .map_err(|e| VulkanError::PipelineCreationFailed(format!("Failed to create pipeline layout: {:?}", e)))?
-        };
+        let layout = device.create_pipeline_layout(&layout_info)?;
         
         // Determine if using mesh shaders
         let is_mesh_shader = device.supports_mesh_shaders() && config.mesh_shaders_enabled;
         
         // Create pipeline (placeholder - would load actual shaders)
+        // The vertex pipeline is always built so chunks can fall back to indexed draws
+        let vertex_pipeline =
+            Self::create_vertex_pipeline(&device, layout, render_pass, swapchain, config.chunk_vertex_format)?;
         let pipeline = if is_mesh_shader {
             Self::create_mesh_shader_pipeline(&device, layout, render_pass, swapchain)?
         } else {
-            Self::create_vertex_pipeline(&device, layout, render_pass, swapchain)?
+            vertex_pipeline
         };
         
         Ok(Self {
             device,
             layout,
             pipeline,
+            vertex_pipeline,
             render_pass,
             descriptor_set_layout,
             is_mesh_shader,
@@ -174,33 +191,14 @@ impl Pipeline {
         layout: vk::PipelineLayout,
         render_pass: vk::RenderPass,
         swapchain: &Swapchain,
+        vertex_format: ChunkVertexFormat,
     ) -> Result<vk::Pipeline, VulkanError> {
         // In production, would load compiled SPIR-V shaders
         // For now, create a minimal pipeline configuration
         
-        // Vertex input state
-        let vertex_binding = vk::VertexInputBindingDescription::default()
-            .binding(0)
-            .stride(32) // Position (12) + Normal (12) + UV (8)
-            .input_rate(vk::VertexInputRate::VERTEX);
-        
-        let vertex_attributes = [
-            vk::VertexInputAttributeDescription::default()
-                .binding(0)
-                .location(0)
-                .format(vk::Format::R32G32B32_SFLOAT)
-                .offset(0),
-            vk::VertexInputAttributeDescription::default()
-                .binding(0)
-                .location(1)
-                .format(vk::Format::R32G32B32_SFLOAT)
-                .offset(12),
-            vk::VertexInputAttributeDescription::default()
-                .binding(0)
-                .location(2)
-                .format(vk::Format::R32G32_SFLOAT)
-                .offset(24),
-        ];
+        // Vertex input state, matching the chunk vertex format
+        let vertex_binding = vertex_format.binding_description();
+        let vertex_attributes = vertex_format.attribute_descriptions();
         
         let bindings = [vertex_binding];
         
@@ -304,6 +302,11 @@ impl Pipeline {
         self.pipeline
     }
     
+    /// Get the vertex/index pipeline (taking the configured chunk vertex format)
+    pub fn vertex_handle(&self) -> vk::Pipeline {
+        self.vertex_pipeline
+    }
+    
     /// Get pipeline layout
     pub fn layout(&self) -> vk::PipelineLayout {
         self.layout
@@ -328,12 +331,41 @@ impl Pipeline {
 impl Drop for Pipeline {
     fn drop(&mut self) {
         unsafe {
-            if self.pipeline != vk::Pipeline::null() {
+            if self.pipeline != vk::Pipeline::null() && self.pipeline != self.vertex_pipeline {
                 self.device.handle().destroy_pipeline(self.pipeline, None);
             }
+            if self.vertex_pipeline != vk::Pipeline::null() {
+                self.device.handle().destroy_pipeline(self.vertex_pipeline, None);
+            }
             self.device.handle().destroy_pipeline_layout(self.layout, None);
             self.device.handle().destroy_render_pass(self.render_pass, None);
             self.device.handle().destroy_descriptor_set_layout(self.descriptor_set_layout, None);
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_constants_checked_against_limit() {
+        let range = |offset, size| vk::PushConstantRange::default()
+            .stage_flags(vk::ShaderStageFlags::MESH_EXT)
+            .offset(offset)
+            .size(size);
+
+        assert!(validate_push_constants(&[range(0, 128)], 128).is_ok());
+        assert!(validate_push_constants(&[], 0).is_ok());
+
+        // A 128-byte block on a GPU that only offers 64
+        let err = validate_push_constants(&[range(0, 64), range(64, 64)], 64).unwrap_err();
+        match err {
+            VulkanError::PipelineCreationFailed(msg) => {
+                assert!(msg.contains("64..128"), "{}", msg);
+                assert!(msg.contains("maxPushConstantsSize of 64 bytes"), "{}", msg);
+            }
+            other => panic!("unexpected error: {}", other),
+        }
+    }
+}
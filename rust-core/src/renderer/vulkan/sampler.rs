@@ -0,0 +1,190 @@
+//! # Sampler Cache
+//!
+//! Deduplicated sampler creation keyed by sampler description.
+//! Anisotropy requests are clamped to the device's `maxSamplerAnisotropy`.
+
+use std::collections::HashMap;
+use parking_lot::Mutex;
+use ash::vk;
+
+use super::VulkanError;
+
+/// Sampler description (hashable key for the cache)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SamplerDesc {
+    /// Magnification filter
+    pub mag_filter: vk::Filter,
+    /// Minification filter
+    pub min_filter: vk::Filter,
+    /// Mipmap mode
+    pub mipmap_mode: vk::SamplerMipmapMode,
+    /// Address mode (U)
+    pub address_u: vk::SamplerAddressMode,
+    /// Address mode (V)
+    pub address_v: vk::SamplerAddressMode,
+    /// Address mode (W)
+    pub address_w: vk::SamplerAddressMode,
+    /// Anisotropy level (1 = disabled, 2/4/8/16)
+    pub anisotropy: u32,
+}
+
+impl Default for SamplerDesc {
+    fn default() -> Self {
+        Self::linear_repeat()
+    }
+}
+
+impl SamplerDesc {
+    /// Trilinear, repeating sampler (block textures)
+    pub fn linear_repeat() -> Self {
+        Self {
+            mag_filter: vk::Filter::LINEAR,
+            min_filter: vk::Filter::LINEAR,
+            mipmap_mode: vk::SamplerMipmapMode::LINEAR,
+            address_u: vk::SamplerAddressMode::REPEAT,
+            address_v: vk::SamplerAddressMode::REPEAT,
+            address_w: vk::SamplerAddressMode::REPEAT,
+            anisotropy: 1,
+        }
+    }
+
+    /// Linear, edge-clamped sampler (GUI and post-process)
+    pub fn linear_clamp() -> Self {
+        Self {
+            address_u: vk::SamplerAddressMode::CLAMP_TO_EDGE,
+            address_v: vk::SamplerAddressMode::CLAMP_TO_EDGE,
+            address_w: vk::SamplerAddressMode::CLAMP_TO_EDGE,
+            ..Self::linear_repeat()
+        }
+    }
+
+    /// Nearest-neighbour, repeating sampler (pixel-art textures)
+    pub fn nearest_repeat() -> Self {
+        Self {
+            mag_filter: vk::Filter::NEAREST,
+            min_filter: vk::Filter::NEAREST,
+            mipmap_mode: vk::SamplerMipmapMode::NEAREST,
+            ..Self::linear_repeat()
+        }
+    }
+
+    /// Set anisotropy level
+    pub fn with_anisotropy(mut self, level: u32) -> Self {
+        self.anisotropy = level;
+        self
+    }
+
+    /// Clamp the anisotropy level to the device limit
+    pub fn clamped(mut self, device_max_anisotropy: f32) -> Self {
+        let device_max = (device_max_anisotropy.floor() as u32).max(1);
+        self.anisotropy = self.anisotropy.clamp(1, device_max);
+        self
+    }
+
+    /// Check if anisotropic filtering is enabled
+    pub fn anisotropy_enabled(&self) -> bool {
+        self.anisotropy > 1
+    }
+
+    /// Build the Vulkan create info
+    fn to_create_info(self) -> vk::SamplerCreateInfo<'static> {
+        vk::SamplerCreateInfo::default()
+            .mag_filter(self.mag_filter)
+            .min_filter(self.min_filter)
+            .mipmap_mode(self.mipmap_mode)
+            .address_mode_u(self.address_u)
+            .address_mode_v(self.address_v)
+            .address_mode_w(self.address_w)
+            .anisotropy_enable(self.anisotropy_enabled())
+            .max_anisotropy(self.anisotropy as f32)
+            .border_color(vk::BorderColor::INT_OPAQUE_BLACK)
+            .unnormalized_coordinates(false)
+            .compare_enable(false)
+            .compare_op(vk::CompareOp::ALWAYS)
+            .mip_lod_bias(0.0)
+            .min_lod(0.0)
+            .max_lod(vk::LOD_CLAMP_NONE)
+    }
+}
+
+/// Sampler cache
+pub struct SamplerCache {
+    /// Device limit for anisotropy
+    max_anisotropy: f32,
+    /// Created samplers by description
+    samplers: Mutex<HashMap<SamplerDesc, vk::Sampler>>,
+}
+
+impl SamplerCache {
+    /// Create a new sampler cache
+    pub fn new(max_anisotropy: f32) -> Self {
+        Self {
+            max_anisotropy,
+            samplers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Get (or create) a sampler matching the description
+    pub fn get(&self, device: &ash::Device, desc: SamplerDesc) -> Result<vk::Sampler, VulkanError> {
+        let desc = desc.clamped(self.max_anisotropy);
+        let mut samplers = self.samplers.lock();
+
+        if let Some(&sampler) = samplers.get(&desc) {
+            return Ok(sampler);
+        }
+
+        let sampler = unsafe {
+            device.create_sampler(&desc.to_create_info(), None)
+                .map_err(|e| VulkanError::TextureCreationFailed(format!("Failed to create sampler: {:?}", e)))?
+        };
+
+        log::debug!("Created sampler {:?}", desc);
+        samplers.insert(desc, sampler);
+        Ok(sampler)
+    }
+
+    /// Get device anisotropy limit
+    pub fn max_anisotropy(&self) -> f32 {
+        self.max_anisotropy
+    }
+
+    /// Get number of cached samplers
+    pub fn len(&self) -> usize {
+        self.samplers.lock().len()
+    }
+
+    /// Check if cache is empty
+    pub fn is_empty(&self) -> bool {
+        self.samplers.lock().is_empty()
+    }
+
+    /// Destroy all cached samplers
+    pub fn destroy(&self, device: &ash::Device) {
+        for (_, sampler) in self.samplers.lock().drain() {
+            unsafe {
+                device.destroy_sampler(sampler, None);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_anisotropy_clamped_to_device_max() {
+        let desc = SamplerDesc::linear_repeat().with_anisotropy(16);
+        assert_eq!(desc.clamped(8.0).anisotropy, 8);
+        assert_eq!(desc.clamped(16.0).anisotropy, 16);
+        assert_eq!(desc.with_anisotropy(4).clamped(16.0).anisotropy, 4);
+
+        // Devices without anisotropy report 1.0
+        let clamped = desc.clamped(1.0);
+        assert_eq!(clamped.anisotropy, 1);
+        assert!(!clamped.anisotropy_enabled());
+
+        // Zero is treated as disabled
+        assert_eq!(desc.with_anisotropy(0).clamped(16.0).anisotropy, 1);
+    }
+}
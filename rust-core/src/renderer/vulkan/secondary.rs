@@ -0,0 +1,214 @@
+//! # Parallel Secondary Command Buffers
+//!
+//! Spreads chunk draw recording across the job system. Each worker owns a
+//! `SecondaryRecorder` (its own command pool, since pools are externally
+//! synchronized) and records its share of the draws into a secondary
+//! command buffer that continues the primary's render pass. The primary
+//! then runs them all with `vkCmdExecuteCommands`.
+//!
+//! The primary must begin the render pass with
+//! `SubpassContents::SECONDARY_COMMAND_BUFFERS`
+//! (see `CommandRecorder::begin_render_pass_secondary`).
+
+use std::ops::Range;
+use std::sync::Arc;
+use ash::vk;
+
+use super::command::CommandRecorder;
+use super::{VulkanDevice, VulkanError};
+use crate::util::jobs::JobSystem;
+
+/// Split `count` items into `parts` contiguous ranges whose sizes differ by at most one
+pub fn partition(count: usize, parts: usize) -> Vec<Range<usize>> {
+    if parts == 0 {
+        return Vec::new();
+    }
+
+    let base = count / parts;
+    let extra = count % parts;
+    let mut start = 0;
+    (0..parts)
+        .map(|i| {
+            let len = base + usize::from(i < extra);
+            let range = start..start + len;
+            start += len;
+            range
+        })
+        .collect()
+}
+
+/// Inheritance info for secondaries that continue `render_pass`/`subpass`
+///
+/// `framebuffer` may be null when it isn't known at record time.
+pub fn inheritance_info(
+    render_pass: vk::RenderPass,
+    subpass: u32,
+    framebuffer: vk::Framebuffer,
+) -> vk::CommandBufferInheritanceInfo<'static> {
+    vk::CommandBufferInheritanceInfo::default()
+        .render_pass(render_pass)
+        .subpass(subpass)
+        .framebuffer(framebuffer)
+}
+
+/// Usage flags for a secondary recorded once per frame inside a render pass
+pub fn secondary_usage_flags() -> vk::CommandBufferUsageFlags {
+    vk::CommandBufferUsageFlags::RENDER_PASS_CONTINUE | vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT
+}
+
+/// One worker's command pool and secondary command buffer
+pub struct SecondaryRecorder {
+    /// Device reference
+    device: Arc<VulkanDevice>,
+    /// Pool owned by this recorder
+    pool: vk::CommandPool,
+    /// Secondary command buffer
+    buffer: vk::CommandBuffer,
+}
+
+impl SecondaryRecorder {
+    /// Create a recorder on the graphics queue family
+    pub fn new(device: Arc<VulkanDevice>) -> Result<Self, VulkanError> {
+        let family = device.queue_families().graphics.ok_or(VulkanError::NotInitialized)?;
+        let pool_info = vk::CommandPoolCreateInfo::default()
+            .queue_family_index(family)
+            .flags(vk::CommandPoolCreateFlags::TRANSIENT);
+
+        let pool = unsafe {
+            device.handle().create_command_pool(&pool_info, None)
+                .map_err(|e| VulkanError::CommandBufferError(format!("Failed to create secondary pool: {:?}", e)))?
+        };
+
+        let alloc_info = vk::CommandBufferAllocateInfo::default()
+            .command_pool(pool)
+            .level(vk::CommandBufferLevel::SECONDARY)
+            .command_buffer_count(1);
+
+        let buffer = match unsafe { device.handle().allocate_command_buffers(&alloc_info) } {
+            Ok(buffers) => buffers[0],
+            Err(e) => {
+                unsafe { device.handle().destroy_command_pool(pool, None) };
+                return Err(VulkanError::CommandBufferError(format!("Failed to allocate secondary buffer: {:?}", e)));
+            }
+        };
+
+        Ok(Self { device, pool, buffer })
+    }
+
+    /// Reset the pool and begin recording inside the inherited render pass
+    pub fn begin(&self, inheritance: &vk::CommandBufferInheritanceInfo) -> Result<CommandRecorder<'_>, VulkanError> {
+        unsafe {
+            self.device.handle().reset_command_pool(self.pool, vk::CommandPoolResetFlags::empty())
+                .map_err(|e| VulkanError::CommandBufferError(format!("Failed to reset secondary pool: {:?}", e)))?;
+
+            let begin_info = vk::CommandBufferBeginInfo::default()
+                .flags(secondary_usage_flags())
+                .inheritance_info(inheritance);
+            self.device.handle().begin_command_buffer(self.buffer, &begin_info)
+                .map_err(|e| VulkanError::CommandBufferError(format!("Failed to begin secondary buffer: {:?}", e)))?;
+        }
+
+        Ok(CommandRecorder::new(&self.device, self.buffer))
+    }
+
+    /// Get the secondary command buffer
+    pub fn buffer(&self) -> vk::CommandBuffer {
+        self.buffer
+    }
+}
+
+impl Drop for SecondaryRecorder {
+    fn drop(&mut self) {
+        unsafe {
+            self.device.handle().free_command_buffers(self.pool, &[self.buffer]);
+            self.device.handle().destroy_command_pool(self.pool, None);
+        }
+    }
+}
+
+/// A set of recorders, one per worker
+pub struct ParallelRecorder {
+    recorders: Vec<SecondaryRecorder>,
+}
+
+impl ParallelRecorder {
+    /// Create one recorder per job system thread
+    pub fn new(device: Arc<VulkanDevice>) -> Result<Self, VulkanError> {
+        Self::with_recorders(device, JobSystem::global().thread_count())
+    }
+
+    /// Create a fixed number of recorders
+    pub fn with_recorders(device: Arc<VulkanDevice>, count: usize) -> Result<Self, VulkanError> {
+        let recorders = (0..count.max(1))
+            .map(|_| SecondaryRecorder::new(device.clone()))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { recorders })
+    }
+
+    /// Get recorder count
+    pub fn recorder_count(&self) -> usize {
+        self.recorders.len()
+    }
+
+    /// Record `items` in parallel inside `render_pass`/`subpass`; `record` is
+    /// called for each item with its worker's recorder
+    ///
+    /// Returns the secondaries that received work, in item order.
+    pub fn record<T: Sync>(
+        &self,
+        items: &[T],
+        render_pass: vk::RenderPass,
+        subpass: u32,
+        framebuffer: vk::Framebuffer,
+        record: impl Fn(&CommandRecorder, &T) + Send + Sync,
+    ) -> Result<Vec<vk::CommandBuffer>, VulkanError> {
+        use rayon::prelude::*;
+
+        let ranges = partition(items.len(), self.recorders.len());
+
+        JobSystem::global().install(|| {
+            self.recorders
+                .par_iter()
+                .zip(ranges)
+                .filter(|(_, range)| !range.is_empty())
+                .map(|(recorder, range)| {
+                    let inheritance = inheritance_info(render_pass, subpass, framebuffer);
+                    let cmd = recorder.begin(&inheritance)?;
+                    for item in &items[range] {
+                        record(&cmd, item);
+                    }
+                    cmd.end()?;
+                    Ok(recorder.buffer())
+                })
+                .collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunks_split_evenly_across_recorders() {
+        for (chunks, recorders) in [(1000, 8), (10, 3), (2, 4), (0, 5), (7, 1)] {
+            let ranges = partition(chunks, recorders);
+            assert_eq!(ranges.len(), recorders);
+
+            // Contiguous, covering every chunk exactly once
+            assert_eq!(ranges.first().unwrap().start, 0);
+            assert_eq!(ranges.last().unwrap().end, chunks);
+            assert!(ranges.windows(2).all(|w| w[0].end == w[1].start));
+
+            let sizes: Vec<usize> = ranges.iter().map(|r| r.len()).collect();
+            let (min, max) = (sizes.iter().min().unwrap(), sizes.iter().max().unwrap());
+            assert!(max - min <= 1, "{} chunks / {} recorders: {:?}", chunks, recorders, sizes);
+        }
+        assert!(partition(10, 0).is_empty());
+
+        let info = inheritance_info(vk::RenderPass::null(), 1, vk::Framebuffer::null());
+        assert_eq!(info.subpass, 1);
+        assert_eq!(info.s_type, vk::StructureType::COMMAND_BUFFER_INHERITANCE_INFO);
+        assert!(secondary_usage_flags().contains(vk::CommandBufferUsageFlags::RENDER_PASS_CONTINUE));
+    }
+}
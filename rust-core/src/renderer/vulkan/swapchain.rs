@@ -7,6 +7,78 @@ use ash::{vk, khr};
 
 use super::{VulkanConfig, VulkanDevice, VulkanError, VulkanInstance};
 
+/// Presentation mode players can pick at runtime
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PresentMode {
+    /// No vsync; may tear
+    Immediate,
+    /// Triple buffering: no tearing, newest frame wins
+    Mailbox,
+    /// Vsync (always supported)
+    Fifo,
+}
+
+impl PresentMode {
+    /// Get the Vulkan present mode
+    pub fn to_vk(self) -> vk::PresentModeKHR {
+        match self {
+            PresentMode::Immediate => vk::PresentModeKHR::IMMEDIATE,
+            PresentMode::Mailbox => vk::PresentModeKHR::MAILBOX,
+            PresentMode::Fifo => vk::PresentModeKHR::FIFO,
+        }
+    }
+    
+    /// Convert from a Vulkan present mode (FIFO variants map to `Fifo`)
+    pub fn from_vk(mode: vk::PresentModeKHR) -> Self {
+        match mode {
+            vk::PresentModeKHR::IMMEDIATE => PresentMode::Immediate,
+            vk::PresentModeKHR::MAILBOX => PresentMode::Mailbox,
+            _ => PresentMode::Fifo,
+        }
+    }
+    
+    /// Mode for a vsync setting
+    pub fn from_vsync(vsync: bool) -> Self {
+        if vsync { PresentMode::Fifo } else { PresentMode::Immediate }
+    }
+}
+
+/// HDR formats in order of preference
+const HDR_FORMATS: [(vk::Format, vk::ColorSpaceKHR); 3] = [
+    (vk::Format::A2B10G10R10_UNORM_PACK32, vk::ColorSpaceKHR::HDR10_ST2084_EXT),
+    (vk::Format::A2R10G10B10_UNORM_PACK32, vk::ColorSpaceKHR::HDR10_ST2084_EXT),
+    (vk::Format::R16G16B16A16_SFLOAT, vk::ColorSpaceKHR::EXTENDED_SRGB_LINEAR_EXT),
+];
+
+/// Pick a surface format: HDR when requested and offered, else 8-bit sRGB
+///
+/// Falls back to the first format if the surface offers no sRGB one.
+pub fn choose_surface_format(formats: &[vk::SurfaceFormatKHR], hdr: bool) -> vk::SurfaceFormatKHR {
+    let offered = |format: vk::Format, color_space: vk::ColorSpaceKHR| {
+        formats.iter().find(|f| f.format == format && f.color_space == color_space).copied()
+    };
+    
+    if hdr {
+        if let Some(format) = HDR_FORMATS.iter().find_map(|&(f, cs)| offered(f, cs)) {
+            return format;
+        }
+        log::info!("HDR requested but the surface offers no HDR format; using SDR");
+    }
+    
+    // Prefer SRGB with B8G8R8A8
+    offered(vk::Format::B8G8R8A8_SRGB, vk::ColorSpaceKHR::SRGB_NONLINEAR)
+        .unwrap_or(formats[0])
+}
+
+/// Pick `requested` if the surface supports it, otherwise FIFO
+pub fn select_present_mode(supported: &[vk::PresentModeKHR], requested: PresentMode) -> PresentMode {
+    if supported.contains(&requested.to_vk()) {
+        requested
+    } else {
+        PresentMode::Fifo
+    }
+}
+
 /// Swapchain wrapper
 pub struct Swapchain {
     /// Instance reference
@@ -31,6 +103,8 @@ pub struct Swapchain {
     extent: vk::Extent2D,
     /// Present mode
     present_mode: vk::PresentModeKHR,
+    /// Present modes the surface supports
+    supported_present_modes: Vec<vk::PresentModeKHR>,
     /// Depth image
     depth_image: vk::Image,
     /// Depth image memory
@@ -70,7 +144,8 @@ impl Swapchain {
                 .map_err(|e| VulkanError::SwapchainCreationFailed(format!("Failed to get surface formats: {:?}", e)))?
         };
         
-        let format = Self::choose_surface_format(&formats);
+        let format = choose_surface_format(&formats, config.hdr);
+        log::info!("Swapchain format {:?} in {:?}", format.format, format.color_space);
         
         // Choose present mode
         let present_modes = unsafe {
@@ -134,6 +209,7 @@ impl Swapchain {
             format,
             extent,
             present_mode,
+            supported_present_modes: present_modes,
             depth_image,
             depth_memory,
             depth_view,
@@ -169,20 +245,6 @@ impl Swapchain {
         }
     }
     
-    /// Choose best surface format
-    fn choose_surface_format(formats: &[vk::SurfaceFormatKHR]) -> vk::SurfaceFormatKHR {
-        // Prefer SRGB with B8G8R8A8
-        for format in formats {
-            if format.format == vk::Format::B8G8R8A8_SRGB 
-                && format.color_space == vk::ColorSpaceKHR::SRGB_NONLINEAR {
-                return *format;
-            }
-        }
-        
-        // Fallback to first available
-        formats[0]
-    }
-    
     /// Choose best present mode
     fn choose_present_mode(modes: &[vk::PresentModeKHR], preferred: vk::PresentModeKHR) -> vk::PresentModeKHR {
         // Try preferred first
@@ -306,10 +368,7 @@ impl Swapchain {
             .allocation_size(mem_requirements.size)
             .memory_type_index(mem_type);
         
-        let memory = unsafe {
-            device.handle().allocate_memory(&alloc_info, None)
-                .map_err(|e| VulkanError::SwapchainCreationFailed(format!("Failed to allocate depth memory: {:?}", e)))?
-        };
+        let memory = device.allocate_memory(&alloc_info)?;
         
         // Bind memory
         unsafe {
@@ -340,17 +399,19 @@ impl Swapchain {
     
     /// Acquire next swapchain image
     pub fn acquire_next_image(&self, semaphore: vk::Semaphore) -> Result<u32, VulkanError> {
-        unsafe {
-            let (index, _suboptimal) = self.swapchain_loader
-                .acquire_next_image(self.swapchain, u64::MAX, semaphore, vk::Fence::null())
-                .map_err(|e| match e {
-                    vk::Result::ERROR_OUT_OF_DATE_KHR => VulkanError::OutOfDate,
-                    vk::Result::ERROR_SURFACE_LOST_KHR => VulkanError::SurfaceLost,
-                    _ => VulkanError::VkError(format!("Failed to acquire image: {:?}", e)),
-                })?;
-            
-            Ok(index)
-        }
+        let watchdog = self.device.watchdog();
+        let result = unsafe {
+            self.swapchain_loader
+                .acquire_next_image(self.swapchain, watchdog.timeout_ns(), semaphore, vk::Fence::null())
+        };
+        
+        let (index, _suboptimal) = match result {
+            Err(vk::Result::ERROR_OUT_OF_DATE_KHR) => return Err(VulkanError::OutOfDate),
+            Err(vk::Result::ERROR_SURFACE_LOST_KHR) => return Err(VulkanError::SurfaceLost),
+            result => watchdog.check("swapchain acquire", result)?,
+        };
+        
+        Ok(index)
     }
     
     /// Present the current image
@@ -369,6 +430,7 @@ impl Swapchain {
                 .map_err(|e| match e {
                     vk::Result::ERROR_OUT_OF_DATE_KHR => VulkanError::OutOfDate,
                     vk::Result::ERROR_SURFACE_LOST_KHR => VulkanError::SurfaceLost,
+                    vk::Result::ERROR_DEVICE_LOST => self.device.watchdog().device_lost("present"),
                     _ => VulkanError::VkError(format!("Failed to present: {:?}", e)),
                 })?;
         }
@@ -444,13 +506,40 @@ impl Swapchain {
         Ok(())
     }
     
+    /// Switch present mode, recreating the swapchain; returns the mode in effect
+    ///
+    /// Unsupported modes fall back to FIFO. The caller must make sure no
+    /// frame is in flight and recreate per-frame sync objects afterwards,
+    /// since semaphores may still reference the old swapchain's images.
+    pub fn set_present_mode(&mut self, requested: PresentMode) -> Result<PresentMode, VulkanError> {
+        let mode = select_present_mode(&self.supported_present_modes, requested);
+        if mode != requested {
+            log::warn!("Present mode {:?} unsupported; using {:?}", requested, mode);
+        }
+        if mode.to_vk() != self.present_mode {
+            self.present_mode = mode.to_vk();
+            self.recreate(self.extent.width, self.extent.height)?;
+        }
+        Ok(mode)
+    }
+    
+    /// Get the present mode in effect
+    pub fn present_mode(&self) -> PresentMode {
+        PresentMode::from_vk(self.present_mode)
+    }
+    
+    /// Get the present modes the surface supports
+    pub fn supported_present_modes(&self) -> &[vk::PresentModeKHR] {
+        &self.supported_present_modes
+    }
+    
     /// Cleanup swapchain resources
     fn cleanup_swapchain(&mut self) {
         unsafe {
             // Destroy depth resources
             self.device.handle().destroy_image_view(self.depth_view, None);
             self.device.handle().destroy_image(self.depth_image, None);
-            self.device.handle().free_memory(self.depth_memory, None);
+            self.device.free_memory(self.depth_memory);
             
             // Destroy image views
             for view in &self.image_views {
@@ -465,6 +554,16 @@ impl Swapchain {
         self.format.format
     }
     
+    /// Get swapchain color space
+    pub fn color_space(&self) -> vk::ColorSpaceKHR {
+        self.format.color_space
+    }
+    
+    /// Check if the swapchain presents HDR (tonemapping must not clip to SDR)
+    pub fn is_hdr(&self) -> bool {
+        HDR_FORMATS.iter().any(|&(_, color_space)| color_space == self.format.color_space)
+    }
+    
     /// Get swapchain extent
     pub fn extent(&self) -> vk::Extent2D {
         self.extent
@@ -503,3 +602,37 @@ impl Drop for Swapchain {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hdr_surface_format_preference() {
+        let format = |format, color_space| vk::SurfaceFormatKHR { format, color_space };
+        let srgb = format(vk::Format::B8G8R8A8_SRGB, vk::ColorSpaceKHR::SRGB_NONLINEAR);
+        let hdr10 = format(vk::Format::A2B10G10R10_UNORM_PACK32, vk::ColorSpaceKHR::HDR10_ST2084_EXT);
+        let scrgb = format(vk::Format::R16G16B16A16_SFLOAT, vk::ColorSpaceKHR::EXTENDED_SRGB_LINEAR_EXT);
+        let unorm = format(vk::Format::B8G8R8A8_UNORM, vk::ColorSpaceKHR::SRGB_NONLINEAR);
+
+        assert_eq!(choose_surface_format(&[unorm, srgb, scrgb, hdr10], true), hdr10);
+        assert_eq!(choose_surface_format(&[unorm, srgb, scrgb], true), scrgb);
+        assert_eq!(choose_surface_format(&[unorm, srgb, hdr10], false), srgb);
+        // 10-bit format in sRGB space isn't HDR
+        let wide_sdr = format(vk::Format::A2B10G10R10_UNORM_PACK32, vk::ColorSpaceKHR::SRGB_NONLINEAR);
+        assert_eq!(choose_surface_format(&[wide_sdr, srgb], true), srgb);
+        assert_eq!(choose_surface_format(&[unorm], true), unorm);
+    }
+
+    #[test]
+    fn test_unsupported_present_mode_falls_back_to_fifo() {
+        let supported = [vk::PresentModeKHR::FIFO, vk::PresentModeKHR::MAILBOX];
+        assert_eq!(select_present_mode(&supported, PresentMode::Mailbox), PresentMode::Mailbox);
+        assert_eq!(select_present_mode(&supported, PresentMode::Immediate), PresentMode::Fifo);
+        assert_eq!(select_present_mode(&[vk::PresentModeKHR::FIFO], PresentMode::Mailbox), PresentMode::Fifo);
+        assert_eq!(select_present_mode(&[], PresentMode::Fifo), PresentMode::Fifo);
+
+        assert_eq!(PresentMode::from_vsync(false).to_vk(), vk::PresentModeKHR::IMMEDIATE);
+        assert_eq!(PresentMode::from_vk(vk::PresentModeKHR::FIFO_RELAXED), PresentMode::Fifo);
+    }
+}
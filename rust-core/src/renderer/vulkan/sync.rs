@@ -66,10 +66,7 @@ impl SyncObjects {
     pub fn wait_for_fence(&self, frame_index: usize) -> Result<(), VulkanError> {
         let fence = self.in_flight_fences[frame_index];
         
-        unsafe {
-            self.device.handle().wait_for_fences(&[fence], true, u64::MAX)
-                .map_err(|e| VulkanError::SyncError(format!("Failed to wait for fence: {:?}", e)))
-        }
+        self.device.wait_for_fences(&[fence], "frame fence")
     }
     
     /// Reset fence at frame index
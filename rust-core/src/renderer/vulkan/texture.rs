@@ -5,7 +5,7 @@
 use std::sync::Arc;
 use ash::vk;
 
-use super::{VulkanDevice, VulkanError};
+use super::{VulkanDevice, VulkanError, SamplerDesc};
 
 /// Texture format
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -72,7 +72,7 @@ pub struct Texture {
     memory: vk::DeviceMemory,
     /// Image view
     view: vk::ImageView,
-    /// Sampler
+    /// Sampler (owned by the device sampler cache)
     sampler: vk::Sampler,
     /// Texture width
     width: u32,
@@ -129,10 +129,7 @@ impl Texture {
             .allocation_size(mem_requirements.size)
             .memory_type_index(mem_type);
         
-        let memory = unsafe {
-            device.handle().allocate_memory(&alloc_info, None)
-                .map_err(|e| VulkanError::TextureCreationFailed(format!("Failed to allocate memory: {:?}", e)))?
-        };
+        let memory = device.allocate_memory(&alloc_info)?;
         
         // Bind memory
         unsafe {
@@ -158,28 +155,10 @@ impl Texture {
                 .map_err(|e| VulkanError::TextureCreationFailed(format!("Failed to create image view: {:?}", e)))?
         };
         
-        // Create sampler
-        let sampler_info = vk::SamplerCreateInfo::default()
-            .mag_filter(vk::Filter::LINEAR)
-            .min_filter(vk::Filter::LINEAR)
-            .mipmap_mode(vk::SamplerMipmapMode::LINEAR)
-            .address_mode_u(vk::SamplerAddressMode::REPEAT)
-            .address_mode_v(vk::SamplerAddressMode::REPEAT)
-            .address_mode_w(vk::SamplerAddressMode::REPEAT)
-            .anisotropy_enable(true)
-            .max_anisotropy(device.properties().limits.max_sampler_anisotropy)
-            .border_color(vk::BorderColor::INT_OPAQUE_BLACK)
-            .unnormalized_coordinates(false)
-            .compare_enable(false)
-            .compare_op(vk::CompareOp::ALWAYS)
-            .mip_lod_bias(0.0)
-            .min_lod(0.0)
-            .max_lod(mip_levels as f32);
-        
-        let sampler = unsafe {
-            device.handle().create_sampler(&sampler_info, None)
-                .map_err(|e| VulkanError::TextureCreationFailed(format!("Failed to create sampler: {:?}", e)))?
-        };
+        // Get shared sampler
+        let sampler = device.sampler(
+            SamplerDesc::linear_repeat().with_anisotropy(device.anisotropy_level())
+        )?;
         
         Ok(Self {
             device,
@@ -258,10 +237,9 @@ impl Texture {
 impl Drop for Texture {
     fn drop(&mut self) {
         unsafe {
-            self.device.handle().destroy_sampler(self.sampler, None);
             self.device.handle().destroy_image_view(self.view, None);
             self.device.handle().destroy_image(self.image, None);
-            self.device.handle().free_memory(self.memory, None);
+            self.device.free_memory(self.memory);
         }
     }
 }
@@ -0,0 +1,226 @@
+//! # Chunk Upload Scheduler
+//!
+//! Queues meshed chunks and hands them out under a per-frame byte budget,
+//! closest chunks first. Re-submitting a chunk that is still pending
+//! replaces the older mesh instead of uploading twice.
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+use super::mesh_shader::MeshVertex;
+
+/// Chunk section key (chunk x, section y, chunk z)
+pub type ChunkKey = (i32, i32, i32);
+
+/// Meshed chunk waiting for upload
+#[derive(Debug, Clone)]
+pub struct MeshedChunk {
+    /// Chunk section key
+    pub key: ChunkKey,
+    /// Vertex data
+    pub vertices: Vec<MeshVertex>,
+    /// Index data
+    pub indices: Vec<u32>,
+}
+
+impl MeshedChunk {
+    /// Get upload size in bytes
+    pub fn byte_size(&self) -> usize {
+        std::mem::size_of_val(self.vertices.as_slice()) + std::mem::size_of_val(self.indices.as_slice())
+    }
+}
+
+/// Chunk handed out by `drain_budget`
+pub type Completed = MeshedChunk;
+
+/// Heap entry (lowest priority value = closest = first)
+#[derive(Debug, Clone, Copy)]
+struct HeapEntry {
+    priority: f32,
+    generation: u64,
+    key: ChunkKey,
+}
+
+impl PartialEq for HeapEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for HeapEntry {}
+
+impl PartialOrd for HeapEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for HeapEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed so BinaryHeap pops the smallest distance; older submissions win ties
+        other.priority.total_cmp(&self.priority)
+            .then_with(|| other.generation.cmp(&self.generation))
+    }
+}
+
+/// Pending upload slot
+struct PendingUpload {
+    chunk: MeshedChunk,
+    generation: u64,
+}
+
+/// Upload scheduler
+pub struct UploadScheduler {
+    /// Priority queue (may contain stale entries)
+    heap: BinaryHeap<HeapEntry>,
+    /// Latest submission per chunk
+    pending: HashMap<ChunkKey, PendingUpload>,
+    /// Submission counter
+    next_generation: u64,
+    /// Bytes currently queued
+    pending_bytes: usize,
+    /// Submissions replaced before upload
+    coalesced: u64,
+}
+
+impl UploadScheduler {
+    /// Create a new upload scheduler
+    pub fn new() -> Self {
+        Self {
+            heap: BinaryHeap::new(),
+            pending: HashMap::new(),
+            next_generation: 0,
+            pending_bytes: 0,
+            coalesced: 0,
+        }
+    }
+
+    /// Queue a meshed chunk (priority = distance to camera, lower first)
+    pub fn queue(&mut self, chunk: MeshedChunk, priority: f32) {
+        let generation = self.next_generation;
+        self.next_generation += 1;
+
+        let key = chunk.key;
+        self.pending_bytes += chunk.byte_size();
+
+        if let Some(old) = self.pending.insert(key, PendingUpload { chunk, generation }) {
+            self.pending_bytes -= old.chunk.byte_size();
+            self.coalesced += 1;
+        }
+
+        self.heap.push(HeapEntry { priority, generation, key });
+    }
+
+    /// Take chunks closest-first until the byte budget is spent
+    ///
+    /// At least one chunk is returned when any are pending, so a single
+    /// oversized chunk cannot stall the queue forever.
+    pub fn drain_budget(&mut self, bytes: usize) -> Vec<Completed> {
+        let mut completed = Vec::new();
+        let mut spent = 0usize;
+
+        while let Some(entry) = self.heap.peek().copied() {
+            let size = match self.pending.get(&entry.key) {
+                Some(p) if p.generation == entry.generation => p.chunk.byte_size(),
+                _ => {
+                    // Superseded by a newer submission
+                    self.heap.pop();
+                    continue;
+                }
+            };
+
+            if !completed.is_empty() && spent + size > bytes {
+                break;
+            }
+
+            self.heap.pop();
+            let upload = self.pending.remove(&entry.key).unwrap();
+            self.pending_bytes -= size;
+            spent += size;
+            completed.push(upload.chunk);
+
+            if spent >= bytes {
+                break;
+            }
+        }
+
+        completed
+    }
+
+    /// Drop a pending upload (e.g. chunk unloaded)
+    pub fn cancel(&mut self, key: ChunkKey) -> bool {
+        if let Some(old) = self.pending.remove(&key) {
+            self.pending_bytes -= old.chunk.byte_size();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Get number of pending chunks
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Get number of pending bytes
+    pub fn pending_bytes(&self) -> usize {
+        self.pending_bytes
+    }
+
+    /// Get number of coalesced submissions
+    pub fn coalesced_count(&self) -> u64 {
+        self.coalesced
+    }
+
+    /// Clear all pending uploads
+    pub fn clear(&mut self) {
+        self.heap.clear();
+        self.pending.clear();
+        self.pending_bytes = 0;
+    }
+}
+
+impl Default for UploadScheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(x: i32, triangles: usize) -> MeshedChunk {
+        MeshedChunk {
+            key: (x, 0, 0),
+            vertices: vec![MeshVertex::default(); 3],
+            indices: vec![0; triangles * 3],
+        }
+    }
+
+    #[test]
+    fn test_drain_closest_first() {
+        let mut scheduler = UploadScheduler::new();
+        scheduler.queue(chunk(3, 1), 48.0);
+        scheduler.queue(chunk(1, 1), 16.0);
+        scheduler.queue(chunk(4, 1), 64.0);
+        scheduler.queue(chunk(2, 1), 32.0);
+
+        // Re-submit chunk 4 closer; the stale entry must be coalesced
+        scheduler.queue(chunk(4, 1), 8.0);
+        assert_eq!(scheduler.pending(), 4);
+        assert_eq!(scheduler.coalesced_count(), 1);
+
+        let size = chunk(0, 1).byte_size();
+        let first = scheduler.drain_budget(size * 2);
+        let keys: Vec<i32> = first.iter().map(|c| c.key.0).collect();
+        assert_eq!(keys, vec![4, 1]);
+        assert_eq!(scheduler.pending(), 2);
+
+        let rest = scheduler.drain_budget(usize::MAX);
+        let keys: Vec<i32> = rest.iter().map(|c| c.key.0).collect();
+        assert_eq!(keys, vec![2, 3]);
+        assert_eq!(scheduler.pending(), 0);
+        assert_eq!(scheduler.pending_bytes(), 0);
+    }
+}
@@ -0,0 +1,249 @@
+//! # Chunk Vertex Formats
+//!
+//! `MeshVertex` spends 48 bytes (eleven floats and a tint word) on data
+//! that, for terrain, is mostly small integers: positions sit on a fine
+//! grid inside a 16³ section, normals come from a short list, and AO and
+//! light have a few levels each. `PackedMeshVertex` stores the same vertex in four words
+//! (16 bytes), cutting chunk vertex buffers and the bandwidth to read them
+//! to a third. The format is picked with `VulkanConfig::chunk_vertex_format`;
+//! the mesher always produces `MeshVertex` and the chunk path packs on
+//! upload.
+//!
+//! Packed layout:
+//! - `position`: x, y, z at 10 bits each in 1/32-block steps (0..32 blocks)
+//! - `normal_light`: normal in the mesher's 8-bit-per-axis encoding (bits
+//!   0-23), AO (24-27) and light (28-31) as 4-bit levels
+//! - `uv_block`: u, v at 8 bits each in 1/64 steps (0..4), block ID (16-31)
+//! - `tint`: biome tint, 0xRRGGBB
+//!
+//! Tint keeps a word of its own because biome colors use all 24 bits.
+
+use ash::vk;
+
+use super::mesh_shader::MeshVertex;
+
+/// Position steps per block
+const POSITION_SCALE: f32 = 32.0;
+
+/// UV steps per texture
+const UV_SCALE: f32 = 64.0;
+
+/// Highest AO / light level
+const LEVEL_MAX: f32 = 15.0;
+
+/// Vertex-shader inputs and decoding for the packed format
+pub const PACKED_VERTEX_INPUT_GLSL: &str = r#"
+layout(location = 0) in uint in_position;
+layout(location = 1) in uint in_normal_light;
+layout(location = 2) in uint in_uv_block;
+layout(location = 3) in uint in_tint;
+
+vec3 packed_position() {
+    return vec3(in_position & 0x3FFu, (in_position >> 10) & 0x3FFu, (in_position >> 20) & 0x3FFu) / 32.0;
+}
+
+vec3 packed_normal() {
+    return vec3((in_normal_light >> 16) & 0xFFu, (in_normal_light >> 8) & 0xFFu, in_normal_light & 0xFFu) / 127.5 - 1.0;
+}
+
+float packed_ao() { return float((in_normal_light >> 24) & 0xFu) / 15.0; }
+float packed_light() { return float(in_normal_light >> 28) / 15.0; }
+vec2 packed_uv() { return vec2(in_uv_block & 0xFFu, (in_uv_block >> 8) & 0xFFu) / 64.0; }
+uint packed_block() { return in_uv_block >> 16; }
+vec3 packed_tint() { return vec3((in_tint >> 16) & 0xFFu, (in_tint >> 8) & 0xFFu, in_tint & 0xFFu) / 255.0; }
+"#;
+
+/// Vertex layout used for chunk vertex buffers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChunkVertexFormat {
+    /// `MeshVertex` as produced by the mesher (48 bytes)
+    #[default]
+    Full,
+    /// `PackedMeshVertex` (16 bytes)
+    Packed,
+}
+
+impl ChunkVertexFormat {
+    /// Get the size of one vertex in bytes
+    pub fn stride(self) -> u32 {
+        match self {
+            ChunkVertexFormat::Full => std::mem::size_of::<MeshVertex>() as u32,
+            ChunkVertexFormat::Packed => std::mem::size_of::<PackedMeshVertex>() as u32,
+        }
+    }
+
+    /// Get the vertex buffer binding (binding 0, per vertex)
+    pub fn binding_description(self) -> vk::VertexInputBindingDescription {
+        vk::VertexInputBindingDescription::default()
+            .binding(0)
+            .stride(self.stride())
+            .input_rate(vk::VertexInputRate::VERTEX)
+    }
+
+    /// Get the vertex attributes (locations 0..3 in field order, tint at
+    /// location 3 as a `uint` in both formats)
+    pub fn attribute_descriptions(self) -> Vec<vk::VertexInputAttributeDescription> {
+        use std::mem::offset_of;
+        let attributes = match self {
+            ChunkVertexFormat::Full => [
+                (vk::Format::R32G32B32A32_SFLOAT, offset_of!(MeshVertex, position_normal)),
+                (vk::Format::R32G32B32_SFLOAT, offset_of!(MeshVertex, uv_block)),
+                (vk::Format::R32G32B32A32_SFLOAT, offset_of!(MeshVertex, ao_light)),
+                (vk::Format::R32_UINT, offset_of!(MeshVertex, tint)),
+            ],
+            ChunkVertexFormat::Packed => [
+                (vk::Format::R32_UINT, offset_of!(PackedMeshVertex, position)),
+                (vk::Format::R32_UINT, offset_of!(PackedMeshVertex, normal_light)),
+                (vk::Format::R32_UINT, offset_of!(PackedMeshVertex, uv_block)),
+                (vk::Format::R32_UINT, offset_of!(PackedMeshVertex, tint)),
+            ],
+        };
+        attributes
+            .into_iter()
+            .zip(0..)
+            .map(|((format, offset), location)| {
+                vk::VertexInputAttributeDescription::default()
+                    .binding(0)
+                    .location(location)
+                    .format(format)
+                    .offset(offset as u32)
+            })
+            .collect()
+    }
+
+    /// Encode mesher output into vertex buffer bytes
+    pub fn encode(self, vertices: &[MeshVertex]) -> Result<Vec<u8>, String> {
+        match self {
+            ChunkVertexFormat::Full => Ok(as_bytes(vertices).to_vec()),
+            ChunkVertexFormat::Packed => {
+                let packed = vertices.iter().map(PackedMeshVertex::pack).collect::<Result<Vec<_>, _>>()?;
+                Ok(as_bytes(&packed).to_vec())
+            }
+        }
+    }
+}
+
+/// View a slice of plain `repr(C)` vertices as bytes
+fn as_bytes<T: Copy>(values: &[T]) -> &[u8] {
+    // Safety: only called with padding-free repr(C) vertex structs
+    unsafe { std::slice::from_raw_parts(values.as_ptr() as *const u8, std::mem::size_of_val(values)) }
+}
+
+/// Chunk vertex packed into four words
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PackedMeshVertex {
+    /// Position, 10 bits per axis
+    pub position: u32,
+    /// Packed normal, AO and light
+    pub normal_light: u32,
+    /// UV and block ID
+    pub uv_block: u32,
+    /// Tint color (0xRRGGBB)
+    pub tint: u32,
+}
+
+impl PackedMeshVertex {
+    /// Pack a mesher vertex
+    ///
+    /// Fails if the position is outside 0..32 blocks or UVs outside 0..4;
+    /// positions and UVs are rounded to the nearest step.
+    pub fn pack(vertex: &MeshVertex) -> Result<Self, String> {
+        let [x, y, z, normal] = vertex.position_normal;
+        let [u, v, block] = vertex.uv_block;
+        let [ao, light, ..] = vertex.ao_light;
+
+        let mut position = 0;
+        for (axis, value) in [x, y, z].into_iter().enumerate() {
+            position |= quantize(value, POSITION_SCALE, 0x3FF)
+                .ok_or_else(|| format!("vertex position {:?} outside the packed range", [x, y, z]))?
+                << (axis * 10);
+        }
+        let uv_error = || format!("vertex UV {:?} outside the packed range", [u, v]);
+        let packed_u = quantize(u, UV_SCALE, 0xFF).ok_or_else(uv_error)?;
+        let packed_v = quantize(v, UV_SCALE, 0xFF).ok_or_else(uv_error)?;
+
+        let level = |value: f32| (value.clamp(0.0, 1.0) * LEVEL_MAX).round() as u32;
+        Ok(Self {
+            position,
+            normal_light: (normal.to_bits() & 0xFF_FFFF) | (level(ao) << 24) | (level(light) << 28),
+            uv_block: packed_u | (packed_v << 8) | ((block as u32 & 0xFFFF) << 16),
+            tint: vertex.tint & 0xFF_FFFF,
+        })
+    }
+
+    /// Recover a mesher vertex (AO and light fill all four `ao_light` lanes
+    /// as ao, light, ao, light)
+    pub fn unpack(&self) -> MeshVertex {
+        let axis = |shift: u32| ((self.position >> shift) & 0x3FF) as f32 / POSITION_SCALE;
+        let ao = ((self.normal_light >> 24) & 0xF) as f32 / LEVEL_MAX;
+        let light = (self.normal_light >> 28) as f32 / LEVEL_MAX;
+        MeshVertex {
+            position_normal: [axis(0), axis(10), axis(20), f32::from_bits(self.normal_light & 0xFF_FFFF)],
+            uv_block: [
+                (self.uv_block & 0xFF) as f32 / UV_SCALE,
+                ((self.uv_block >> 8) & 0xFF) as f32 / UV_SCALE,
+                (self.uv_block >> 16) as f32,
+            ],
+            tint: self.tint,
+            ao_light: [ao, light, ao, light],
+        }
+    }
+
+    /// Get the packed normal as a unit vector
+    pub fn normal(&self) -> [f32; 3] {
+        let channel = |shift: u32| ((self.normal_light >> shift) & 0xFF) as f32 / 127.5 - 1.0;
+        [channel(16), channel(8), channel(0)]
+    }
+}
+
+/// Round `value * scale` to an integer in `0..=max`
+fn quantize(value: f32, scale: f32, max: u32) -> Option<u32> {
+    let steps = (value * scale).round();
+    (steps >= 0.0 && steps <= max as f32).then_some(steps as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_packed_vertex_round_trip() {
+        // Mesher's normal encoding for +Y
+        let up = f32::from_bits((127 << 16) | (255 << 8) | 127);
+        let vertex = MeshVertex {
+            position_normal: [3.0, 16.0, 0.875, up],
+            uv_block: [0.25, 1.0, 1234.0],
+            tint: 0x7CBD6B,
+            ao_light: [1.0, 1.0, 1.0, 1.0],
+        };
+
+        let packed = PackedMeshVertex::pack(&vertex).unwrap();
+        let restored = packed.unpack();
+        assert_eq!(restored.position_normal[..3], vertex.position_normal[..3]);
+        assert_eq!(restored.position_normal[3].to_bits(), up.to_bits());
+        assert_eq!(restored.uv_block, vertex.uv_block);
+        assert_eq!(restored.tint, 0x7CBD6B);
+        assert_eq!(restored.ao_light, vertex.ao_light);
+        assert!((packed.normal()[1] - 1.0).abs() < 1e-6);
+
+        // Outside a section's range
+        let mut far = vertex;
+        far.position_normal[0] = -1.0;
+        assert!(PackedMeshVertex::pack(&far).is_err());
+
+        // A third of the full vertex, and the layout matches the stride
+        assert_eq!(ChunkVertexFormat::Packed.stride(), 16);
+        assert_eq!(ChunkVertexFormat::Full.stride() / ChunkVertexFormat::Packed.stride(), 3);
+        let bytes = ChunkVertexFormat::Packed.encode(&[vertex, vertex]).unwrap();
+        assert_eq!(bytes.len(), 32);
+        let attributes = ChunkVertexFormat::Packed.attribute_descriptions();
+        assert_eq!(attributes.len(), 4);
+        assert_eq!(attributes[3].offset, 12);
+
+        // Both formats feed the tint to location 3 as a uint
+        let full = ChunkVertexFormat::Full.attribute_descriptions();
+        assert_eq!((full[3].location, full[3].format), (3, vk::Format::R32_UINT));
+        assert_eq!(full[3].offset, 28);
+    }
+}
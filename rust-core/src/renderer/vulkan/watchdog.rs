@@ -0,0 +1,186 @@
+//! # GPU Watchdog
+//!
+//! Bounds every fence wait and image acquire with a finite timeout so a hung
+//! dispatch (bad shader, driver bug) becomes a recoverable error instead of
+//! freezing the engine.
+//!
+//! On `VK_TIMEOUT` the watchdog logs the operation, emits
+//! `EngineEvent::GpuHang` and flags the device for recovery; the renderer
+//! checks the flag at the start of the next frame. `VK_ERROR_DEVICE_LOST`
+//! (from waits, submits, acquire or present) emits `EngineEvent::DeviceLost`
+//! and flags recovery the same way. `QuantumRenderer` owns the watchdog and
+//! shares it with its greedy mesher and any `VulkanRenderer` built with
+//! `with_watchdog`, so every wait reports to the one flag it checks.
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Duration;
+use ash::prelude::VkResult;
+use ash::vk;
+
+use crate::events::{EngineEvent, EventQueue};
+use super::VulkanError;
+
+/// Default time a GPU wait may take before it counts as a hang
+pub const DEFAULT_GPU_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Timeout-bounded GPU waits with hang reporting
+#[derive(Debug)]
+pub struct GpuWatchdog {
+    /// Wait timeout in nanoseconds
+    timeout_ns: AtomicU64,
+    /// Hangs seen so far
+    hangs: AtomicU64,
+    /// Set when a hang needs device recovery
+    needs_recovery: AtomicBool,
+    /// Set when the device reported loss (cleared with the recovery flag)
+    device_lost: AtomicBool,
+    /// Where hang events go
+    events: EventQueue,
+}
+
+impl GpuWatchdog {
+    /// Create a watchdog with the default timeout
+    pub fn new(events: EventQueue) -> Self {
+        Self {
+            timeout_ns: AtomicU64::new(DEFAULT_GPU_TIMEOUT.as_nanos() as u64),
+            hangs: AtomicU64::new(0),
+            needs_recovery: AtomicBool::new(false),
+            device_lost: AtomicBool::new(false),
+            events,
+        }
+    }
+
+    /// Set the wait timeout
+    pub fn set_timeout(&self, timeout: Duration) {
+        let nanos = u64::try_from(timeout.as_nanos()).unwrap_or(u64::MAX - 1);
+        self.timeout_ns.store(nanos.max(1), Ordering::Relaxed);
+    }
+
+    /// Get the wait timeout
+    pub fn timeout(&self) -> Duration {
+        Duration::from_nanos(self.timeout_ns())
+    }
+
+    /// Get the wait timeout in nanoseconds (for Vulkan calls)
+    pub fn timeout_ns(&self) -> u64 {
+        self.timeout_ns.load(Ordering::Relaxed)
+    }
+
+    /// Get the number of hangs seen
+    pub fn hang_count(&self) -> u64 {
+        self.hangs.load(Ordering::Relaxed)
+    }
+
+    /// Get the queue hang events are raised on
+    pub fn events(&self) -> &EventQueue {
+        &self.events
+    }
+
+    /// Check if a hang is waiting to be recovered from
+    pub fn needs_recovery(&self) -> bool {
+        self.needs_recovery.load(Ordering::Relaxed)
+    }
+
+    /// Check if the device reported loss since the last recovery
+    pub fn is_device_lost(&self) -> bool {
+        self.device_lost.load(Ordering::Relaxed)
+    }
+
+    /// Clear the recovery flag, returning whether it was set
+    pub fn take_recovery(&self) -> bool {
+        self.device_lost.store(false, Ordering::Relaxed);
+        self.needs_recovery.swap(false, Ordering::Relaxed)
+    }
+
+    /// Report device loss seen by `operation`
+    pub fn device_lost(&self, operation: &str) -> VulkanError {
+        log::error!("GPU device lost during '{}'", operation);
+        self.device_lost.store(true, Ordering::Relaxed);
+        self.needs_recovery.store(true, Ordering::Relaxed);
+        self.events.emit(EngineEvent::DeviceLost { operation: operation.to_string() });
+        VulkanError::DeviceLost
+    }
+
+    /// Map the result of a bounded wait, reporting timeouts and device loss
+    pub fn check<T>(&self, operation: &str, result: VkResult<T>) -> Result<T, VulkanError> {
+        match result {
+            Ok(value) => Ok(value),
+            Err(vk::Result::TIMEOUT) => {
+                self.report_hang(operation);
+                Err(VulkanError::GpuTimeout(operation.to_string()))
+            }
+            Err(vk::Result::ERROR_DEVICE_LOST) => Err(self.device_lost(operation)),
+            Err(e) => Err(VulkanError::SyncError(format!("{}: {:?}", operation, e))),
+        }
+    }
+
+    /// Wait for all `fences` with the configured timeout
+    pub fn wait_for_fences(&self, device: &ash::Device, fences: &[vk::Fence], operation: &str) -> Result<(), VulkanError> {
+        let result = unsafe { device.wait_for_fences(fences, true, self.timeout_ns()) };
+        self.check(operation, result)
+    }
+
+    fn report_hang(&self, operation: &str) {
+        let timeout_ms = self.timeout().as_millis() as u64;
+        log::error!("GPU hang: '{}' did not complete within {} ms", operation, timeout_ms);
+
+        self.hangs.fetch_add(1, Ordering::Relaxed);
+        self.needs_recovery.store(true, Ordering::Relaxed);
+        self.events.emit(EngineEvent::GpuHang {
+            operation: operation.to_string(),
+            timeout_ms,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timeout_maps_to_hang_event() {
+        let events = EventQueue::new();
+        let watchdog = GpuWatchdog::new(events.clone());
+        assert_eq!(watchdog.timeout(), DEFAULT_GPU_TIMEOUT);
+        watchdog.set_timeout(Duration::from_millis(500));
+        assert_eq!(watchdog.timeout_ns(), 500_000_000);
+
+        assert_eq!(watchdog.check("ok", Ok(7)).unwrap(), 7);
+        assert!(events.is_empty());
+
+        let err = watchdog.check::<()>("mesh fence", Err(vk::Result::TIMEOUT)).unwrap_err();
+        assert!(matches!(err, VulkanError::GpuTimeout(ref op) if op == "mesh fence"));
+        assert_eq!(events.drain(), [EngineEvent::GpuHang {
+            operation: "mesh fence".to_string(),
+            timeout_ms: 500,
+        }]);
+        assert!(watchdog.take_recovery());
+        assert!(!watchdog.needs_recovery());
+
+        let err = watchdog.check::<()>("acquire", Err(vk::Result::ERROR_DEVICE_LOST)).unwrap_err();
+        assert!(matches!(err, VulkanError::DeviceLost));
+        assert_eq!(events.drain(), [EngineEvent::DeviceLost { operation: "acquire".to_string() }]);
+        assert!(watchdog.is_device_lost());
+        assert!(watchdog.take_recovery());
+        assert!(!watchdog.is_device_lost());
+
+        // Other failures are plain errors, not hangs
+        assert!(watchdog.check::<()>("reset", Err(vk::Result::ERROR_OUT_OF_HOST_MEMORY)).is_err());
+        assert!(events.is_empty());
+        assert_eq!(watchdog.hang_count(), 1); // Device loss is not counted as a hang
+    }
+
+    #[test]
+    fn test_hot_paths_use_bounded_waits() {
+        let sources = [
+            include_str!("sync.rs"),
+            include_str!("swapchain.rs"),
+            include_str!("../quantum/greedy_mesh.rs"),
+            include_str!("../quantum/lighting.rs"),
+            include_str!("../quantum/capture.rs"),
+        ];
+        for source in sources {
+            assert!(!source.contains("u64::MAX"));
+        }
+    }
+}
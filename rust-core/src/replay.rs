@@ -0,0 +1,365 @@
+//! # Replay Recording
+//!
+//! Captures the engine API calls a host makes (chunk submits, block and
+//! entity commands, ticks) so a field crash can be reproduced offline.
+//! While a `ReplayRecorder` is attached, the engine (`LibsEngine` for C
+//! hosts, `AetherEngine` behind the JNI bridge) appends every call with
+//! its time since recording started. The file is a magic + version
+//! header followed by one bincode record per call, written through a
+//! buffered writer and capped in size so a long session can't fill the
+//! disk. `ReplayPlayer` reads it back and re-issues the calls, in order,
+//! against a fresh engine of the same kind; the seed recorded first and
+//! deterministic ECS ticks make the result match the original session.
+
+use std::io::{BufWriter, Read, Write};
+use std::path::Path;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+use crate::lockstep::Command;
+use crate::LibsEngine;
+
+/// File magic
+const REPLAY_MAGIC: [u8; 4] = *b"LRPL";
+
+/// Current replay format version
+pub const REPLAY_VERSION: u32 = 1;
+
+/// Default recording size cap (64 MiB)
+pub const DEFAULT_MAX_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Replay errors
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(std::io::Error),
+    BadMagic,
+    UnsupportedVersion(u32),
+    Corrupt(String),
+}
+
+impl std::fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "I/O error: {}", e),
+            Self::BadMagic => write!(f, "Not a replay"),
+            Self::UnsupportedVersion(v) => write!(f, "Unsupported replay version {} (expected {})", v, REPLAY_VERSION),
+            Self::Corrupt(e) => write!(f, "Corrupt replay: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl From<std::io::Error> for ReplayError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// One recorded engine call
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ReplayCall {
+    /// `enable_lockstep(seed)`
+    Seed(u64),
+    /// `tick(delta_time)`
+    Tick(f32),
+    /// Chunk, block or entity call
+    Command(Command),
+}
+
+/// Recorded call with its timestamp
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayEvent {
+    /// Microseconds since recording started
+    pub time_us: u64,
+    pub call: ReplayCall,
+}
+
+/// Appends engine calls to a replay stream
+pub struct ReplayRecorder {
+    /// Buffered output
+    writer: BufWriter<Box<dyn Write + Send + Sync>>,
+    /// Recording start
+    started: Instant,
+    /// Bytes written so far
+    written: u64,
+    /// Size cap
+    max_bytes: u64,
+    /// Calls recorded
+    events: u64,
+    /// Set once the cap was hit or a write failed; nothing more is recorded
+    stopped: bool,
+}
+
+impl ReplayRecorder {
+    /// Record into any writer, capped at `max_bytes`
+    pub fn new(writer: Box<dyn Write + Send + Sync>, max_bytes: u64) -> Result<Self, ReplayError> {
+        let mut writer = BufWriter::new(writer);
+        writer.write_all(&REPLAY_MAGIC)?;
+        writer.write_all(&REPLAY_VERSION.to_le_bytes())?;
+        Ok(Self {
+            writer,
+            started: Instant::now(),
+            written: 8,
+            max_bytes,
+            events: 0,
+            stopped: false,
+        })
+    }
+
+    /// Record into a new file with the default size cap
+    pub fn create(path: impl AsRef<Path>) -> Result<Self, ReplayError> {
+        let file = std::fs::File::create(path)?;
+        Self::new(Box::new(file), DEFAULT_MAX_BYTES)
+    }
+
+    /// Append a call (dropped once the size cap is reached)
+    pub fn record(&mut self, call: ReplayCall) {
+        if self.stopped {
+            return;
+        }
+        let event = ReplayEvent { time_us: self.started.elapsed().as_micros() as u64, call };
+        let size = bincode::serialized_size(&event).unwrap_or(u64::MAX);
+        if self.written.saturating_add(size) > self.max_bytes {
+            log::warn!("Replay reached its {} byte cap after {} calls; recording stopped", self.max_bytes, self.events);
+            self.stopped = true;
+            return;
+        }
+        if let Err(e) = bincode::serialize_into(&mut self.writer, &event) {
+            log::warn!("Replay write failed, recording stopped: {}", e);
+            self.stopped = true;
+            return;
+        }
+        self.written += size;
+        self.events += 1;
+    }
+
+    /// Flush buffered calls to the writer
+    pub fn flush(&mut self) -> Result<(), ReplayError> {
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    /// Get the number of calls recorded
+    pub fn event_count(&self) -> u64 {
+        self.events
+    }
+
+    /// Check if recording stopped early (size cap or write failure)
+    pub fn is_truncated(&self) -> bool {
+        self.stopped
+    }
+}
+
+impl Drop for ReplayRecorder {
+    fn drop(&mut self) {
+        let _ = self.writer.flush();
+    }
+}
+
+/// Engine a replay can be played against
+pub trait ReplayTarget {
+    /// Seed the simulation (`ReplayCall::Seed`)
+    fn replay_seed(&mut self, seed: u64);
+    /// Advance the simulation (`ReplayCall::Tick`)
+    fn replay_tick(&mut self, delta_time: f32);
+    /// Apply a chunk, block or entity call (`ReplayCall::Command`)
+    fn replay_command(&mut self, command: &Command);
+}
+
+impl ReplayTarget for LibsEngine {
+    fn replay_seed(&mut self, seed: u64) {
+        self.enable_lockstep(seed);
+    }
+
+    fn replay_tick(&mut self, delta_time: f32) {
+        self.tick(delta_time);
+    }
+
+    fn replay_command(&mut self, command: &Command) {
+        self.execute(command);
+    }
+}
+
+/// Recorded session loaded for playback
+pub struct ReplayPlayer {
+    events: Vec<ReplayEvent>,
+}
+
+impl ReplayPlayer {
+    /// Parse a replay stream
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ReplayError> {
+        if bytes.len() < 8 || bytes[..4] != REPLAY_MAGIC {
+            return Err(ReplayError::BadMagic);
+        }
+        let version = u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+        if version != REPLAY_VERSION {
+            return Err(ReplayError::UnsupportedVersion(version));
+        }
+
+        let mut events = Vec::new();
+        let mut rest = &bytes[8..];
+        while !rest.is_empty() {
+            let event: ReplayEvent = bincode::deserialize_from(&mut rest)
+                .map_err(|e| ReplayError::Corrupt(format!("call {}: {}", events.len(), e)))?;
+            events.push(event);
+        }
+        Ok(Self { events })
+    }
+
+    /// Load a replay file
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, ReplayError> {
+        let mut bytes = Vec::new();
+        std::fs::File::open(path)?.read_to_end(&mut bytes)?;
+        Self::from_bytes(&bytes)
+    }
+
+    /// Get the recorded calls
+    pub fn events(&self) -> &[ReplayEvent] {
+        &self.events
+    }
+
+    /// Re-issue every call against a fresh engine of the kind that recorded
+    /// them (e.g. `LibsEngine::new_headless()`)
+    pub fn play(&self, engine: &mut impl ReplayTarget) {
+        for event in &self.events {
+            match &event.call {
+                ReplayCall::Seed(seed) => engine.replay_seed(*seed),
+                ReplayCall::Tick(delta_time) => engine.replay_tick(*delta_time),
+                ReplayCall::Command(command) => engine.replay_command(command),
+            }
+        }
+    }
+}
+
+impl LibsEngine {
+    /// Start recording API calls (replaces any recording in progress)
+    pub fn start_recording(&mut self, recorder: ReplayRecorder) {
+        self.recorder = Some(recorder);
+    }
+
+    /// Stop recording, returning the recorder after flushing it
+    pub fn stop_recording(&mut self) -> Option<ReplayRecorder> {
+        let mut recorder = self.recorder.take()?;
+        if let Err(e) = recorder.flush() {
+            log::warn!("Replay flush failed: {}", e);
+        }
+        Some(recorder)
+    }
+
+    /// Check if API calls are being recorded
+    pub fn is_recording(&self) -> bool {
+        self.recorder.is_some()
+    }
+
+    /// Log a call if recording
+    pub(crate) fn record_call(&mut self, call: impl FnOnce() -> ReplayCall) {
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record(call());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    /// Writer the test can read back after the recorder is done
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_recorded_session_replays_to_same_state() {
+        let buffer = SharedBuffer::default();
+        let mut engine = LibsEngine::new_headless();
+        engine.start_recording(ReplayRecorder::new(Box::new(buffer.clone()), DEFAULT_MAX_BYTES).unwrap());
+
+        engine.enable_lockstep(99);
+        engine.submit_chunk(0, 0, &[]);
+        engine.execute(&Command::SpawnEntity { id: 3, entity_type: 0, x: 1.0, y: 64.0, z: 1.0 });
+        for tick in 0..20 {
+            let mut commands = vec![Command::MoveEntity { id: 3, x: tick as f64, y: 64.0, z: 1.0, yaw: 0.0, pitch: 0.0 }];
+            if tick % 4 == 0 {
+                commands.push(Command::SetBlock { x: tick, y: 65, z: 3, block_id: 7 });
+            }
+            engine.step_with_inputs(&commands);
+        }
+        let recorder = engine.stop_recording().unwrap();
+        assert!(!recorder.is_truncated());
+        let recorded = recorder.event_count();
+        drop(recorder);
+
+        let player = ReplayPlayer::from_bytes(&buffer.0.lock().unwrap()).unwrap();
+        assert_eq!(player.events().len() as u64, recorded);
+        assert!(player.events().windows(2).all(|w| w[0].time_us <= w[1].time_us));
+
+        let mut replayed = LibsEngine::new_headless();
+        player.play(&mut replayed);
+        let (world, ecs) = (replayed.world.as_ref().unwrap(), replayed.ecs.as_ref().unwrap());
+        assert_eq!(world.state_hash(), engine.world.as_ref().unwrap().state_hash());
+        assert_eq!(ecs.state_hash(), engine.ecs.as_ref().unwrap().state_hash());
+        assert_eq!(world.get_block(8, 65, 3), 7);
+
+        // Format checks and the size cap
+        assert!(matches!(ReplayPlayer::from_bytes(b"nope"), Err(ReplayError::BadMagic)));
+        let bytes = buffer.0.lock().unwrap().clone();
+        assert!(matches!(ReplayPlayer::from_bytes(&bytes[..bytes.len() - 1]), Err(ReplayError::Corrupt(_))));
+        let mut capped = ReplayRecorder::new(Box::new(SharedBuffer::default()), 32).unwrap();
+        (0..10).for_each(|_| capped.record(ReplayCall::Tick(0.05)));
+        assert!(capped.is_truncated() && capped.event_count() < 10);
+    }
+
+    #[test]
+    #[cfg(all(feature = "vulkan", feature = "audio"))]
+    fn test_bridge_session_replays_to_same_state() {
+        use crate::engine::AetherEngine;
+
+        // Drive the engine through the calls the JNI bridge makes
+        let buffer = SharedBuffer::default();
+        let mut engine = AetherEngine::new(&[]).unwrap();
+        engine.start_recording(ReplayRecorder::new(Box::new(buffer.clone()), DEFAULT_MAX_BYTES).unwrap());
+        engine.submit_chunk(0, 0, &[]);
+        engine.submit_chunk(1, 0, &[]);
+        engine.register_entity(3, 0, 1.0, 64.0, 1.0, None);
+        engine.register_entity(4, 0, 20.0, 64.0, 1.0, None);
+        for tick in 0..20 {
+            engine.update_entity(3, tick as f64, 64.0, 1.0, 0.0, 0.0);
+            if tick % 4 == 0 {
+                engine.set_block(tick, 65, 3, 7);
+            }
+            if tick == 10 {
+                engine.remove_entity(4);
+                engine.update_chunk(1, 0, &[]);
+            }
+            engine.tick(0.05);
+        }
+        engine.unload_chunk(1, 0);
+        let recorder = engine.stop_recording().unwrap();
+        // Seed, 2 submits, 2 spawns, 20 moves, 5 blocks, despawn, update, 20 ticks, unload
+        assert_eq!(recorder.event_count(), 1 + 2 + 2 + 20 + 5 + 1 + 1 + 20 + 1);
+        drop(recorder);
+
+        let player = ReplayPlayer::from_bytes(&buffer.0.lock().unwrap()).unwrap();
+        assert!(matches!(player.events()[0].call, ReplayCall::Seed(_)));
+        let mut replayed = AetherEngine::new(&[]).unwrap();
+        player.play(&mut replayed);
+        let (world, ecs) = (replayed.world().unwrap(), replayed.ecs().unwrap());
+        assert_eq!(world.state_hash(), engine.world().unwrap().state_hash());
+        assert_eq!(ecs.state_hash(), engine.ecs().unwrap().state_hash());
+        assert_eq!((world.chunk_count(), ecs.entity_count()), (1, 1));
+        assert_eq!(world.get_block(8, 65, 3), 7);
+    }
+}
@@ -0,0 +1,323 @@
+//! # Test Support
+//!
+//! Headless engine harness for integration tests (feature `test-support`).
+//! `TestEngine` boots a `LibsEngine` without renderer or audio, feeds it
+//! synthetic chunks and entities, ticks it and checks the invariants every
+//! run must keep: the ECS holds exactly the entities spawned and not yet
+//! despawned, every submitted chunk is loaded, each tick is one profiled
+//! frame, and shutdown hands all off-heap memory back. The engine's void
+//! manager allocates through a tracking allocator, so a leak shows up as
+//! live bytes that `VoidManager`'s own accounting can't hide.
+
+use std::alloc::Layout;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicI64, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use crate::lockstep::Command;
+use crate::memory::backing::{BackingAllocator, SystemAllocator};
+use crate::memory::void_manager::VoidManager;
+use crate::profiling::{FrameStats, Profiler};
+use crate::world::{ChunkPayload, ChunkSection, WorldHeight, WorldManager};
+use crate::LibsEngine;
+
+/// System allocator counting what is still allocated through it
+#[derive(Debug, Default)]
+pub struct TrackingAllocator {
+    live_bytes: AtomicI64,
+    live_allocations: AtomicUsize,
+}
+
+impl TrackingAllocator {
+    /// Bytes allocated and not yet freed
+    pub fn live_bytes(&self) -> i64 {
+        self.live_bytes.load(Ordering::SeqCst)
+    }
+
+    /// Allocations not yet freed
+    pub fn live_allocations(&self) -> usize {
+        self.live_allocations.load(Ordering::SeqCst)
+    }
+}
+
+impl BackingAllocator for TrackingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = SystemAllocator.alloc(layout);
+        if !ptr.is_null() {
+            self.live_bytes.fetch_add(layout.size() as i64, Ordering::SeqCst);
+            self.live_allocations.fetch_add(1, Ordering::SeqCst);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.live_bytes.fetch_sub(layout.size() as i64, Ordering::SeqCst);
+        self.live_allocations.fetch_sub(1, Ordering::SeqCst);
+        SystemAllocator.dealloc(ptr, layout)
+    }
+
+    fn name(&self) -> &'static str {
+        "tracking"
+    }
+}
+
+/// Harness settings
+#[derive(Debug, Clone, Copy)]
+pub struct TestConfig {
+    /// Seconds per tick
+    pub delta_time: f32,
+    /// Run the ECS in deterministic mode
+    pub deterministic: bool,
+    /// Vertical extent of the world
+    pub world_height: WorldHeight,
+}
+
+impl Default for TestConfig {
+    fn default() -> Self {
+        Self { delta_time: 0.05, deterministic: true, world_height: WorldHeight::default() }
+    }
+}
+
+/// A headless engine under test
+pub struct TestEngine {
+    engine: LibsEngine,
+    config: TestConfig,
+    /// Allocator under the engine's void manager
+    allocator: Arc<TrackingAllocator>,
+    /// One frame per tick
+    profiler: Profiler,
+    /// Entities spawned and not despawned
+    alive: HashSet<i32>,
+    next_entity_id: i32,
+    spawns: u64,
+    despawns: u64,
+    /// Chunks submitted (resubmits replace)
+    chunks: HashSet<(i32, i32)>,
+    ticks: u64,
+}
+
+impl TestEngine {
+    /// Boot a headless engine with the default settings
+    pub fn new() -> Self {
+        Self::with_config(TestConfig::default())
+    }
+
+    /// Boot a headless engine
+    pub fn with_config(config: TestConfig) -> Self {
+        let mut engine = LibsEngine::new_headless();
+        let allocator = Arc::new(TrackingAllocator::default());
+        engine.memory = VoidManager::with_allocator(allocator.clone());
+        if let Some(ecs) = &mut engine.ecs {
+            ecs.set_deterministic(config.deterministic);
+        }
+        if engine.world.is_some() {
+            engine.world = Some(WorldManager::with_height(config.world_height));
+        }
+
+        Self {
+            engine,
+            config,
+            allocator,
+            profiler: Profiler::new(),
+            alive: HashSet::new(),
+            next_entity_id: 1,
+            spawns: 0,
+            despawns: 0,
+            chunks: HashSet::new(),
+            ticks: 0,
+        }
+    }
+
+    /// Get the engine
+    pub fn engine(&self) -> &LibsEngine {
+        &self.engine
+    }
+
+    /// Get the engine mutably (changes made here bypass the bookkeeping)
+    pub fn engine_mut(&mut self) -> &mut LibsEngine {
+        &mut self.engine
+    }
+
+    /// Get the allocator under the engine's void manager
+    pub fn allocator(&self) -> &TrackingAllocator {
+        &self.allocator
+    }
+
+    /// Submit a chunk payload; returns the chunk handle
+    pub fn submit_chunk(&mut self, x: i32, z: i32, payload: &ChunkPayload) -> Option<i64> {
+        let handle = self.engine.submit_chunk(x, z, &payload.encode());
+        if handle.is_some() {
+            self.chunks.insert((x, z));
+        }
+        handle
+    }
+
+    /// Submit a chunk of air with a solid `block_id` floor at `floor_y`
+    pub fn submit_flat_chunk(&mut self, x: i32, z: i32, floor_y: i32, block_id: u16) -> Option<i64> {
+        let mut section = ChunkSection::new(floor_y.div_euclid(16));
+        let local_y = floor_y.rem_euclid(16) as usize;
+        for bx in 0..16 {
+            for bz in 0..16 {
+                section.set_block(bx, local_y, bz, block_id);
+            }
+        }
+        let mut payload = ChunkPayload::default();
+        payload.sections.push(section);
+        self.submit_chunk(x, z, &payload)
+    }
+
+    /// Spawn an entity; returns its id
+    pub fn spawn_entity(&mut self, x: f64, y: f64, z: f64) -> i32 {
+        let id = self.next_entity_id;
+        self.next_entity_id += 1;
+        self.engine.execute(&Command::SpawnEntity { id, entity_type: 0, x, y, z });
+        self.alive.insert(id);
+        self.spawns += 1;
+        id
+    }
+
+    /// Despawn an entity; false if it isn't alive
+    pub fn despawn_entity(&mut self, id: i32) -> bool {
+        if !self.alive.remove(&id) {
+            return false;
+        }
+        self.engine.execute(&Command::DespawnEntity { id });
+        self.despawns += 1;
+        true
+    }
+
+    /// Ids of the entities alive, in spawn order
+    pub fn alive_entities(&self) -> Vec<i32> {
+        let mut ids: Vec<i32> = self.alive.iter().copied().collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    /// Run `n` ticks, each one profiled frame
+    pub fn tick(&mut self, n: u32) {
+        for _ in 0..n {
+            self.profiler.begin_frame();
+            self.engine.tick(self.config.delta_time);
+            self.profiler.end_frame();
+            self.ticks += 1;
+        }
+    }
+
+    /// Get the number of ticks run
+    pub fn tick_count(&self) -> u64 {
+        self.ticks
+    }
+
+    /// Get the number of entities in the ECS
+    pub fn entity_count(&self) -> u32 {
+        self.engine.ecs.as_ref().map_or(0, |ecs| ecs.entity_count())
+    }
+
+    /// Get the number of loaded chunks
+    pub fn chunk_count(&self) -> usize {
+        self.engine.world.as_ref().map_or(0, |world| world.chunk_count())
+    }
+
+    /// Get timing of the profiled ticks
+    pub fn frame_stats(&self) -> FrameStats {
+        self.profiler.get_frame_stats()
+    }
+
+    /// Every invariant that doesn't hold, described
+    pub fn violations(&self) -> Vec<String> {
+        let mut violations = Vec::new();
+
+        let expected_entities = self.spawns - self.despawns;
+        if self.entity_count() as u64 != expected_entities {
+            violations.push(format!(
+                "{} entities in the ECS, expected {} ({} spawned, {} despawned)",
+                self.entity_count(), expected_entities, self.spawns, self.despawns
+            ));
+        }
+        if self.chunk_count() != self.chunks.len() {
+            violations.push(format!("{} chunks loaded, {} submitted", self.chunk_count(), self.chunks.len()));
+        }
+        if let Some(world) = &self.engine.world {
+            if world.dirty_chunk_count() > world.chunk_count() {
+                violations.push(format!("{} dirty chunks but {} loaded", world.dirty_chunk_count(), world.chunk_count()));
+            }
+        }
+        if let Some(ecs) = &self.engine.ecs {
+            if ecs.current_tick() != self.ticks {
+                violations.push(format!("ECS at tick {}, harness ran {}", ecs.current_tick(), self.ticks));
+            }
+        }
+
+        let frames = self.frame_stats().frame_count;
+        if frames != self.ticks.min(300) {
+            violations.push(format!("{} frames profiled for {} ticks", frames, self.ticks));
+        }
+
+        let accounted = self.engine.memory.get_stats().current_usage as i64;
+        if accounted != self.allocator.live_bytes() {
+            violations.push(format!(
+                "void manager accounts {} bytes, {} are allocated",
+                accounted, self.allocator.live_bytes()
+            ));
+        }
+        violations
+    }
+
+    /// Panic listing every broken invariant
+    pub fn assert_invariants(&self) {
+        let violations = self.violations();
+        assert!(violations.is_empty(), "engine invariants broken:\n  {}", violations.join("\n  "));
+    }
+
+    /// Shut the engine down and check that it freed all off-heap memory
+    pub fn shutdown(mut self) -> Result<(), String> {
+        self.engine.shutdown();
+        match (self.allocator.live_bytes(), self.allocator.live_allocations()) {
+            (0, 0) => Ok(()),
+            (bytes, count) => Err(format!("{} bytes in {} allocations leaked after shutdown", bytes, count)),
+        }
+    }
+}
+
+impl Default for TestEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::void_manager::AssetType;
+
+    #[test]
+    fn test_invariants_hold_and_leaks_are_caught() {
+        let mut harness = TestEngine::with_config(TestConfig {
+            world_height: WorldHeight::OVERWORLD,
+            ..TestConfig::default()
+        });
+        harness.submit_flat_chunk(0, 0, -60, 1).unwrap();
+        let a = harness.spawn_entity(0.5, -59.0, 0.5);
+        harness.spawn_entity(4.5, -59.0, 4.5);
+        harness.tick(5);
+        assert!(harness.despawn_entity(a));
+        assert!(!harness.despawn_entity(a));
+        harness.tick(5);
+
+        assert_eq!((harness.entity_count(), harness.chunk_count()), (1, 1));
+        assert_eq!(harness.engine().world.as_ref().unwrap().get_block(7, -60, 7), 1);
+        assert_eq!(harness.frame_stats().frame_count, 10);
+        harness.assert_invariants();
+
+        // Memory the engine still holds at shutdown is released by it
+        harness.engine().memory.allocate(4096, AssetType::Texture).unwrap();
+        assert_eq!(harness.allocator().live_bytes(), 4096);
+        harness.assert_invariants();
+        assert_eq!(harness.shutdown(), Ok(()));
+
+        // Bookkeeping bypassed through the engine shows up as a violation
+        let mut harness = TestEngine::new();
+        harness.engine_mut().execute(&Command::SpawnEntity { id: 99, entity_type: 0, x: 0.0, y: 64.0, z: 0.0 });
+        assert_eq!(harness.violations().len(), 1);
+    }
+}
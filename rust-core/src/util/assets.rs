@@ -0,0 +1,410 @@
+//! # Async Asset Loading
+//!
+//! `AssetManager` hands out a typed `AssetHandle<T>` immediately and runs
+//! the load on the shared job system, so file I/O and decoding never stall
+//! the frame. Subsystems keep the handle and `poll` it until it resolves
+//! to `Loaded` or `Failed`. Loading the same path as the same type again
+//! returns the existing handle.
+//!
+//! With hot reload on (debug builds by default), files loaded through
+//! `load_file` are watched by polling their modification time, like
+//! `ShaderManager::check_hot_reload`. A changed file is decoded again in
+//! the background and swapped in, then `EngineEvent::AssetReloaded` tells
+//! holders (e.g. the texture manager) to re-upload it.
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::SystemTime;
+use parking_lot::RwLock;
+
+use super::jobs::JobSystem;
+use crate::events::{EngineEvent, EventQueue};
+use crate::profiling::{profiler, CacheMetrics};
+
+/// Load state of an asset
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AssetState {
+    /// Queued or running on the job system
+    Loading,
+    /// Ready for `AssetManager::get`
+    Loaded,
+    /// The loader returned an error
+    Failed(String),
+    /// The handle doesn't refer to a live asset
+    Unknown,
+}
+
+/// Typed reference to an asset owned by an `AssetManager`
+pub struct AssetHandle<T> {
+    id: u64,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> AssetHandle<T> {
+    fn new(id: u64) -> Self {
+        Self { id, _marker: PhantomData }
+    }
+
+    /// Get the raw handle ID
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+}
+
+impl<T> Clone for AssetHandle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for AssetHandle<T> {}
+
+impl<T> PartialEq for AssetHandle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl<T> Eq for AssetHandle<T> {}
+
+impl<T> Hash for AssetHandle<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+impl<T> fmt::Debug for AssetHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "AssetHandle({})", self.id)
+    }
+}
+
+/// Stored asset value or outcome
+enum Slot {
+    Loading,
+    Loaded(Arc<dyn Any + Send + Sync>),
+    Failed(String),
+}
+
+/// Type-erased loader that can run again on reload
+type ReloadFn = Arc<dyn Fn(&str) -> Result<Arc<dyn Any + Send + Sync>, String> + Send + Sync>;
+
+/// A file-backed asset checked for changes
+struct WatchedFile {
+    path: String,
+    last_modified: Option<SystemTime>,
+    reload: ReloadFn,
+}
+
+/// Issues asset handles and loads assets in the background
+pub struct AssetManager {
+    /// Asset slots by handle ID (shared with load jobs)
+    slots: Arc<RwLock<HashMap<u64, Slot>>>,
+    /// Handle ID by path and asset type, for dedup
+    by_path: HashMap<(String, TypeId), u64>,
+    /// Next handle ID
+    next_id: AtomicU64,
+    /// File-backed assets by handle ID
+    watched: HashMap<u64, WatchedFile>,
+    /// Hot-reload enabled
+    hot_reload: bool,
+    /// Where reload events go
+    events: EventQueue,
+    /// Loads answered with an existing handle
+    hits: u64,
+    /// Loads that started a new load
+    misses: u64,
+}
+
+impl AssetManager {
+    /// Create an empty manager
+    pub fn new() -> Self {
+        Self {
+            slots: Arc::new(RwLock::new(HashMap::new())),
+            by_path: HashMap::new(),
+            next_id: AtomicU64::new(1),
+            watched: HashMap::new(),
+            hot_reload: cfg!(debug_assertions),
+            events: EventQueue::new(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+    
+    /// Send reload events to a shared queue
+    pub fn set_event_queue(&mut self, events: EventQueue) {
+        self.events = events;
+    }
+    
+    /// Get the queue reload events go to
+    pub fn events(&self) -> &EventQueue {
+        &self.events
+    }
+    
+    /// Enable or disable watching files for changes
+    pub fn set_hot_reload(&mut self, enabled: bool) {
+        self.hot_reload = enabled;
+    }
+    
+    /// Check if hot reload is enabled
+    pub fn is_hot_reload(&self) -> bool {
+        self.hot_reload
+    }
+
+    /// Load an asset with `loader` on the job system
+    ///
+    /// Returns at once with a handle in the `Loading` state. If `path` was
+    /// already requested as a `T`, the existing handle is returned and
+    /// `loader` is not run.
+    pub fn load<T, F>(&mut self, path: &str, loader: F) -> AssetHandle<T>
+    where
+        T: Send + Sync + 'static,
+        F: FnOnce(&str) -> Result<T, String> + Send + 'static,
+    {
+        let key = (path.to_string(), TypeId::of::<T>());
+        if let Some(&id) = self.by_path.get(&key) {
+            self.hits += 1;
+            return AssetHandle::new(id);
+        }
+        self.misses += 1;
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.by_path.insert(key, id);
+        self.slots.write().insert(id, Slot::Loading);
+
+        let slots = self.slots.clone();
+        let path = path.to_string();
+        JobSystem::global().spawn(move || {
+            let slot = match loader(&path) {
+                Ok(asset) => Slot::Loaded(Arc::new(asset)),
+                Err(e) => {
+                    log::warn!("Failed to load asset {}: {}", path, e);
+                    Slot::Failed(e)
+                }
+            };
+            // Unloaded while loading: drop the result
+            if let Some(entry) = slots.write().get_mut(&id) {
+                *entry = slot;
+            }
+        });
+
+        AssetHandle::new(id)
+    }
+
+    /// Read a file and decode it on the job system
+    ///
+    /// The file is watched for changes while hot reload is enabled.
+    pub fn load_file<T, F>(&mut self, path: &str, decode: F) -> AssetHandle<T>
+    where
+        T: Send + Sync + 'static,
+        F: Fn(&[u8]) -> Result<T, String> + Send + Sync + 'static,
+    {
+        let decode = Arc::new(decode);
+        let reload: ReloadFn = {
+            let decode = decode.clone();
+            Arc::new(move |path| {
+                let bytes = std::fs::read(Path::new(path)).map_err(|e| e.to_string())?;
+                decode(&bytes).map(|asset| Arc::new(asset) as Arc<dyn Any + Send + Sync>)
+            })
+        };
+        
+        let known = self.by_path.contains_key(&(path.to_string(), TypeId::of::<T>()));
+        let handle = self.load(path, move |path| {
+            let bytes = std::fs::read(Path::new(path)).map_err(|e| e.to_string())?;
+            decode(&bytes)
+        });
+        
+        if !known {
+            self.watched.insert(handle.id, WatchedFile {
+                path: path.to_string(),
+                last_modified: modified_time(path),
+                reload,
+            });
+        }
+        handle
+    }
+    
+    /// Check watched files and reload the changed ones in the background
+    ///
+    /// Returns the paths flagged for reload. A file that fails to decode
+    /// keeps its previous asset.
+    pub fn check_hot_reload(&mut self) -> Vec<String> {
+        if !self.hot_reload {
+            return Vec::new();
+        }
+        
+        let mut changed = Vec::new();
+        for (&id, file) in &mut self.watched {
+            let Some(current) = modified_time(&file.path) else { continue };
+            if file.last_modified.is_some_and(|last| current <= last) {
+                continue;
+            }
+            file.last_modified = Some(current);
+            changed.push(file.path.clone());
+            
+            let slots = self.slots.clone();
+            let events = self.events.clone();
+            let reload = file.reload.clone();
+            let path = file.path.clone();
+            JobSystem::global().spawn(move || match reload(&path) {
+                Ok(asset) => {
+                    if let Some(entry) = slots.write().get_mut(&id) {
+                        *entry = Slot::Loaded(asset);
+                    }
+                    log::info!("Reloaded asset: {}", path);
+                    events.emit(EngineEvent::AssetReloaded { path });
+                }
+                Err(e) => log::warn!("Failed to reload asset {}: {}", path, e),
+            });
+        }
+        
+        changed
+    }
+    
+    /// Get an asset's load state
+    pub fn poll<T>(&self, handle: AssetHandle<T>) -> AssetState {
+        match self.slots.read().get(&handle.id) {
+            Some(Slot::Loading) => AssetState::Loading,
+            Some(Slot::Loaded(_)) => AssetState::Loaded,
+            Some(Slot::Failed(e)) => AssetState::Failed(e.clone()),
+            None => AssetState::Unknown,
+        }
+    }
+
+    /// Get a loaded asset
+    pub fn get<T: Send + Sync + 'static>(&self, handle: AssetHandle<T>) -> Option<Arc<T>> {
+        match self.slots.read().get(&handle.id) {
+            Some(Slot::Loaded(asset)) => asset.clone().downcast::<T>().ok(),
+            _ => None,
+        }
+    }
+
+    /// Drop an asset; its handle becomes `Unknown` and the path can be loaded again
+    pub fn unload<T>(&mut self, handle: AssetHandle<T>) {
+        self.slots.write().remove(&handle.id);
+        self.by_path.retain(|_, id| *id != handle.id);
+        self.watched.remove(&handle.id);
+    }
+
+    /// Get the number of assets still loading
+    pub fn loading_count(&self) -> usize {
+        self.slots.read().values().filter(|s| matches!(s, Slot::Loading)).count()
+    }
+
+    /// Get the number of live assets
+    pub fn asset_count(&self) -> usize {
+        self.slots.read().len()
+    }
+
+    /// Get load dedup accounting (asset sizes are opaque, so `bytes` is 0)
+    pub fn metrics(&self) -> CacheMetrics {
+        CacheMetrics {
+            hits: self.hits,
+            misses: self.misses,
+            entries: self.asset_count() as u64,
+            bytes: 0,
+            evictions: 0,
+        }
+    }
+
+    /// Send `metrics()` to the global profiler as `cache_asset_*` gauges
+    pub fn report_metrics(&self) {
+        profiler().record_cache_metrics("asset", &self.metrics());
+    }
+}
+
+/// Get a file's modification time
+fn modified_time(path: &str) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+impl Default for AssetManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::time::{Duration, Instant};
+
+    fn wait_until_resolved<T>(assets: &AssetManager, handle: AssetHandle<T>) -> AssetState {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        loop {
+            let state = assets.poll(handle);
+            if state != AssetState::Loading || Instant::now() > deadline {
+                return state;
+            }
+            std::thread::yield_now();
+        }
+    }
+
+    #[test]
+    fn test_handle_moves_from_loading_to_loaded() {
+        let mut assets = AssetManager::new();
+        let (release, gate) = mpsc::channel::<()>();
+
+        let texture: AssetHandle<Vec<u8>> = assets.load("textures/stone.png", move |path| {
+            gate.recv().map_err(|e| e.to_string())?;
+            Ok(path.as_bytes().to_vec())
+        });
+        assert_eq!(assets.poll(texture), AssetState::Loading);
+        assert!(assets.get(texture).is_none());
+
+        // Same path and type: same handle, loader not run again
+        let again: AssetHandle<Vec<u8>> = assets.load("textures/stone.png", |_| panic!("loaded twice"));
+        assert_eq!(again, texture);
+        assert_eq!(assets.asset_count(), 1);
+        assert_eq!((assets.metrics().hits, assets.metrics().misses), (1, 1));
+
+        release.send(()).unwrap();
+        assert_eq!(wait_until_resolved(&assets, texture), AssetState::Loaded);
+        assert_eq!(assets.get(texture).unwrap().as_slice(), b"textures/stone.png");
+        assert_eq!(assets.loading_count(), 0);
+
+        let missing: AssetHandle<u32> = assets.load("missing.nbt", |_| Err("not found".to_string()));
+        assert_eq!(wait_until_resolved(&assets, missing), AssetState::Failed("not found".to_string()));
+
+        assets.unload(texture);
+        assert_eq!(assets.poll(texture), AssetState::Unknown);
+    }
+
+    #[test]
+    fn test_touched_file_is_flagged_and_reloaded() {
+        let path = std::env::temp_dir().join(format!("libs_asset_reload_{}.txt", std::process::id()));
+        std::fs::write(&path, "v1").unwrap();
+        let path_str = path.to_str().unwrap();
+
+        let mut assets = AssetManager::new();
+        let text: AssetHandle<String> = assets.load_file(path_str, |bytes| Ok(String::from_utf8_lossy(bytes).into_owned()));
+        assert_eq!(wait_until_resolved(&assets, text), AssetState::Loaded);
+
+        // Off: changes are ignored
+        assets.set_hot_reload(false);
+        std::fs::write(&path, "v2").unwrap();
+        let file = std::fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(10)).unwrap();
+        assert!(assets.check_hot_reload().is_empty());
+
+        assets.set_hot_reload(true);
+        assert_eq!(assets.check_hot_reload(), [path_str.to_string()]);
+        assert!(assets.check_hot_reload().is_empty()); // Flagged once per change
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while assets.events().is_empty() && Instant::now() < deadline {
+            std::thread::yield_now();
+        }
+        assert_eq!(assets.events().drain(), [EngineEvent::AssetReloaded { path: path_str.to_string() }]);
+        assert_eq!(assets.get(text).unwrap().as_str(), "v2");
+
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,320 @@
+//! # Bounding Volume Hierarchy
+//!
+//! Binary tree of boxes for ray and frustum queries over objects of very
+//! different sizes (a dropped item next to a boss), where a uniform grid
+//! either wastes cells or puts everything in one. Built top-down by
+//! splitting at the median centroid along the widest axis, with a few items
+//! per leaf.
+//!
+//! For moving objects, `refit` after updating bounds keeps the tree shape
+//! and only recomputes node boxes, which is much cheaper than `build`. The
+//! tree gets looser as objects drift apart, so rebuild now and then (e.g.
+//! when many objects spawned or teleported).
+
+use glam::{Mat4, Vec3, Vec4};
+
+/// Items per leaf before splitting
+const LEAF_SIZE: usize = 4;
+
+/// Axis-aligned box
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bounds {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Bounds {
+    /// Box containing nothing (identity for `union`)
+    pub const EMPTY: Bounds = Bounds { min: Vec3::INFINITY, max: Vec3::NEG_INFINITY };
+
+    /// Create a box from corners
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        Self { min, max }
+    }
+
+    /// Box around a center with half extents
+    pub fn from_center(center: Vec3, half_extents: Vec3) -> Self {
+        Self { min: center - half_extents, max: center + half_extents }
+    }
+
+    /// Smallest box containing both
+    pub fn union(&self, other: &Bounds) -> Bounds {
+        Bounds { min: self.min.min(other.min), max: self.max.max(other.max) }
+    }
+
+    /// Get the center
+    pub fn center(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    /// Check if two boxes overlap
+    pub fn intersects(&self, other: &Bounds) -> bool {
+        self.min.cmple(other.max).all() && other.min.cmple(self.max).all()
+    }
+
+    /// Distance along a ray to where it enters the box, if it does within `max_dist`
+    ///
+    /// `inv_dir` is the reciprocal of the ray direction. A ray starting
+    /// inside the box enters at 0.
+    pub fn ray_entry(&self, origin: Vec3, inv_dir: Vec3, max_dist: f32) -> Option<f32> {
+        let t0 = (self.min - origin) * inv_dir;
+        let t1 = (self.max - origin) * inv_dir;
+        let near = t0.min(t1).max_element().max(0.0);
+        let far = t0.max(t1).min_element().min(max_dist);
+        (near <= far).then_some(near)
+    }
+}
+
+/// View frustum as six inward-facing planes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frustum {
+    /// Planes as (normal xyz, distance w); inside where `n·p + w >= 0`
+    pub planes: [Vec4; 6],
+}
+
+impl Frustum {
+    /// Extract the planes of a view-projection (depth 0..1)
+    pub fn from_view_proj(view_proj: Mat4) -> Self {
+        let [r0, r1, r2, r3] = [0, 1, 2, 3].map(|i| view_proj.row(i));
+        Self { planes: [r3 + r0, r3 - r0, r3 + r1, r3 - r1, r2, r3 - r2] }
+    }
+
+    /// Check if a box touches the frustum (conservative near corners)
+    pub fn intersects(&self, bounds: &Bounds) -> bool {
+        self.planes.iter().all(|plane| {
+            let normal = plane.truncate();
+            // Corner furthest along the plane normal
+            let corner = Vec3::select(normal.cmpge(Vec3::ZERO), bounds.max, bounds.min);
+            normal.dot(corner) + plane.w >= 0.0
+        })
+    }
+}
+
+/// Tree node
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    /// Box around everything below
+    bounds: Bounds,
+    /// Leaf: first slot in `order`; inner: index of the right child
+    /// (the left child always follows its parent)
+    start: u32,
+    /// Leaf: number of items; inner: 0
+    count: u32,
+}
+
+/// Bounding volume hierarchy over `(id, bounds)` items
+#[derive(Debug, Clone)]
+pub struct Bvh<Id> {
+    /// Nodes in depth-first order, root first
+    nodes: Vec<Node>,
+    /// Items in insertion order
+    items: Vec<(Id, Bounds)>,
+    /// Item indices grouped by leaf
+    order: Vec<u32>,
+}
+
+impl<Id: Copy> Bvh<Id> {
+    /// Build a tree over items
+    pub fn build(items: impl IntoIterator<Item = (Id, Bounds)>) -> Self {
+        let items: Vec<(Id, Bounds)> = items.into_iter().collect();
+        let mut bvh = Self {
+            nodes: Vec::with_capacity(items.len().div_ceil(LEAF_SIZE) * 2),
+            order: (0..items.len() as u32).collect(),
+            items,
+        };
+        if !bvh.items.is_empty() {
+            bvh.build_node(0, bvh.items.len());
+        }
+        bvh
+    }
+
+    /// Build the node over `order[start..end]`; returns its index
+    fn build_node(&mut self, start: usize, end: usize) -> usize {
+        let index = self.nodes.len();
+        let bounds = self.order[start..end]
+            .iter()
+            .fold(Bounds::EMPTY, |b, &i| b.union(&self.items[i as usize].1));
+        self.nodes.push(Node { bounds, start: start as u32, count: (end - start) as u32 });
+        if end - start <= LEAF_SIZE {
+            return index;
+        }
+
+        // Median split along the axis the centroids spread most on
+        let centroids = self.order[start..end]
+            .iter()
+            .fold(Bounds::EMPTY, |b, &i| {
+                let c = self.items[i as usize].1.center();
+                b.union(&Bounds::new(c, c))
+            });
+        let extent = centroids.max - centroids.min;
+        let axis = if extent.x >= extent.y && extent.x >= extent.z { 0 } else if extent.y >= extent.z { 1 } else { 2 };
+        let mid = (start + end) / 2;
+        let items = &self.items;
+        self.order[start..end].select_nth_unstable_by(mid - start, |&a, &b| {
+            let ca = items[a as usize].1.center()[axis];
+            let cb = items[b as usize].1.center()[axis];
+            ca.total_cmp(&cb)
+        });
+
+        self.build_node(start, mid);
+        let right = self.build_node(mid, end);
+        self.nodes[index].start = right as u32;
+        self.nodes[index].count = 0;
+        index
+    }
+
+    /// Get the number of items
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Check if the tree is empty
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Get the items in insertion order
+    pub fn items(&self) -> &[(Id, Bounds)] {
+        &self.items
+    }
+
+    /// Change the bounds of the item at `index` (takes effect on `refit`)
+    pub fn set_bounds(&mut self, index: usize, bounds: Bounds) {
+        if let Some(item) = self.items.get_mut(index) {
+            item.1 = bounds;
+        }
+    }
+
+    /// Recompute node boxes from the current item bounds
+    pub fn refit(&mut self) {
+        // Children come after their parent, so walk backwards
+        for index in (0..self.nodes.len()).rev() {
+            let node = self.nodes[index];
+            self.nodes[index].bounds = if node.count > 0 {
+                let slots = &self.order[node.start as usize..(node.start + node.count) as usize];
+                slots.iter().fold(Bounds::EMPTY, |b, &i| b.union(&self.items[i as usize].1))
+            } else {
+                self.nodes[index + 1].bounds.union(&self.nodes[node.start as usize].bounds)
+            };
+        }
+    }
+
+    /// Items a ray passes through, nearest first
+    pub fn query_ray(&self, origin: Vec3, dir: Vec3) -> Vec<Id> {
+        self.query_ray_hits(origin, dir, f32::INFINITY)
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect()
+    }
+
+    /// Items a ray passes through within `max_dist`, with entry distances, nearest first
+    pub fn query_ray_hits(&self, origin: Vec3, dir: Vec3, max_dist: f32) -> Vec<(Id, f32)> {
+        let mut hits = Vec::new();
+        let Some(dir) = dir.try_normalize() else {
+            return hits;
+        };
+        let inv_dir = dir.recip();
+        self.traverse(
+            |bounds| bounds.ray_entry(origin, inv_dir, max_dist).is_some(),
+            |id, bounds| {
+                if let Some(t) = bounds.ray_entry(origin, inv_dir, max_dist) {
+                    hits.push((id, t));
+                }
+            },
+        );
+        hits.sort_by(|a, b| a.1.total_cmp(&b.1));
+        hits
+    }
+
+    /// Items touching a frustum
+    pub fn query_frustum(&self, frustum: &Frustum) -> Vec<Id> {
+        let mut found = Vec::new();
+        self.traverse(|bounds| frustum.intersects(bounds), |id, bounds| {
+            if frustum.intersects(bounds) {
+                found.push(id);
+            }
+        });
+        found
+    }
+
+    /// Items overlapping a box
+    pub fn query_bounds(&self, query: &Bounds) -> Vec<Id> {
+        let mut found = Vec::new();
+        self.traverse(|bounds| bounds.intersects(query), |id, bounds| {
+            if bounds.intersects(query) {
+                found.push(id);
+            }
+        });
+        found
+    }
+
+    /// Visit items in leaves whose path passes `enter`
+    fn traverse(&self, enter: impl Fn(&Bounds) -> bool, mut visit: impl FnMut(Id, &Bounds)) {
+        if self.nodes.is_empty() {
+            return;
+        }
+        let mut stack = vec![0usize];
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if !enter(&node.bounds) {
+                continue;
+            }
+            if node.count > 0 {
+                for &i in &self.order[node.start as usize..(node.start + node.count) as usize] {
+                    let (id, bounds) = &self.items[i as usize];
+                    visit(*id, bounds);
+                }
+            } else {
+                stack.push(node.start as usize);
+                stack.push(index + 1);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ray_hits_near_to_far_and_frustum() {
+        // A row of small items along +X, one big box, and clutter off the ray
+        let mut items = vec![
+            (1u32, Bounds::from_center(Vec3::new(20.0, 0.0, 0.0), Vec3::splat(0.25))),
+            (2, Bounds::from_center(Vec3::new(6.0, 0.0, 0.0), Vec3::new(2.0, 3.0, 2.0))),
+            (3, Bounds::from_center(Vec3::new(10.0, 5.0, 0.0), Vec3::splat(0.5))),
+            (4, Bounds::from_center(Vec3::new(-8.0, 0.0, 0.0), Vec3::splat(1.0))),
+        ];
+        for i in 0..40 {
+            let center = Vec3::new(i as f32 * 3.0 - 60.0, 12.0, (i % 7) as f32 * 4.0);
+            items.push((100 + i, Bounds::from_center(center, Vec3::splat(0.4))));
+        }
+        let mut bvh = Bvh::build(items);
+
+        assert_eq!(bvh.query_ray(Vec3::ZERO, Vec3::X), vec![2, 1]);
+        let hits = bvh.query_ray_hits(Vec3::ZERO, Vec3::X, 10.0);
+        assert_eq!(hits.len(), 1);
+        assert!((hits[0].1 - 4.0).abs() < 1e-5);
+        assert_eq!(bvh.query_ray(Vec3::ZERO, Vec3::NEG_X), vec![4]);
+
+        // Moving the small box onto the ray in front of the big one
+        bvh.set_bounds(2, Bounds::from_center(Vec3::new(2.0, 0.0, 0.0), Vec3::splat(0.5)));
+        bvh.refit();
+        assert_eq!(bvh.query_ray(Vec3::ZERO, Vec3::X), vec![3, 2, 1]);
+
+        // Camera at the origin looking down +X sees the row but not what's behind it
+        let view = Mat4::look_at_rh(Vec3::ZERO, Vec3::X, Vec3::Y);
+        let proj = Mat4::perspective_rh(60f32.to_radians(), 1.0, 0.1, 100.0);
+        let frustum = Frustum::from_view_proj(proj * view);
+        let mut visible = bvh.query_frustum(&frustum);
+        visible.sort_unstable();
+        assert!(visible.starts_with(&[1, 2, 3]));
+        assert!(!visible.contains(&4));
+        let brute: Vec<u32> = bvh.items()
+            .iter()
+            .filter(|(_, b)| frustum.intersects(b))
+            .map(|(id, _)| *id)
+            .collect();
+        assert_eq!(visible.len(), brute.len());
+    }
+}
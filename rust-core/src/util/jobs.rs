@@ -0,0 +1,223 @@
+//! # Job System
+//!
+//! One rayon pool shared by every subsystem (ECS, meshing, light) so they
+//! don't oversubscribe the CPU with a pool each. The pool is built on first
+//! use; call `JobSystem::configure` before that to pick the thread count and
+//! `JobSystem::configure_pinning` to pin workers to cores.
+//!
+//! Pinning needs the `affinity` feature. Workers are pinned past the
+//! reserved cores, so the render thread and the OS keep cores 0 and 1.
+
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::OnceLock;
+
+/// The shared pool
+static JOBS: OnceLock<JobSystem> = OnceLock::new();
+
+/// Requested thread count (0 = default)
+static REQUESTED_THREADS: AtomicUsize = AtomicUsize::new(0);
+
+/// Pin shared pool workers to cores
+static PIN_THREADS: AtomicBool = AtomicBool::new(false);
+
+/// Number of shared pools built (stays at 1)
+static POOLS_BUILT: AtomicUsize = AtomicUsize::new(0);
+
+/// Cores left free for the render thread and the OS
+pub const RESERVED_CORES: usize = 2;
+
+/// Default worker count: every core except the reserved ones, at least one
+pub fn default_thread_count() -> usize {
+    num_cpus::get().saturating_sub(RESERVED_CORES).max(1)
+}
+
+/// Shared worker pool
+pub struct JobSystem {
+    pool: rayon::ThreadPool,
+}
+
+impl JobSystem {
+    /// Set the worker count; fails once the pool has been built
+    pub fn configure(threads: usize) -> Result<(), String> {
+        if JOBS.get().is_some() {
+            return Err("job system already running".to_string());
+        }
+        REQUESTED_THREADS.store(threads, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// Pin workers to cores; fails once the pool has been built
+    pub fn configure_pinning(pin: bool) -> Result<(), String> {
+        if JOBS.get().is_some() {
+            return Err("job system already running".to_string());
+        }
+        PIN_THREADS.store(pin, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// Get the shared job system, building it on first use
+    pub fn global() -> &'static JobSystem {
+        Self::try_global().expect("Failed to create job pool")
+    }
+
+    /// Get the shared job system, building it on first use; fails if the pool can't be built
+    pub fn try_global() -> Result<&'static JobSystem, String> {
+        if let Some(jobs) = JOBS.get() {
+            return Ok(jobs);
+        }
+        let threads = REQUESTED_THREADS.load(Ordering::SeqCst);
+        let mut built = Some(Self::build("libs-worker", threads, PIN_THREADS.load(Ordering::SeqCst))?);
+
+        // A racing caller may have installed its pool first; this one is then dropped
+        Ok(JOBS.get_or_init(|| {
+            let jobs = built.take().unwrap();
+            POOLS_BUILT.fetch_add(1, Ordering::SeqCst);
+            log::info!("Job system started with {} threads", jobs.thread_count());
+            jobs
+        }))
+    }
+
+    /// Build a dedicated pool (0 threads = default count)
+    ///
+    /// For subsystems that must not share workers, such as a server-side
+    /// ECS sized separately from the client's meshing. Not counted by
+    /// `pools_built`.
+    pub fn dedicated(name: &str, threads: usize, pin: bool) -> Result<Self, String> {
+        Self::build(name, threads, pin)
+    }
+
+    /// Build a pool named `{name}-{i}`
+    fn build(name: &str, threads: usize, pin: bool) -> Result<Self, String> {
+        let threads = if threads == 0 { default_thread_count() } else { threads };
+        let prefix = name.to_string();
+        let mut builder = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .thread_name(move |i| format!("{}-{}", prefix, i));
+        if pin {
+            builder = builder.start_handler(pin_worker);
+        }
+        let pool = builder
+            .build()
+            .map_err(|e| format!("Failed to create job pool: {}", e))?;
+        Ok(JobSystem { pool })
+    }
+
+    /// Get the number of pools ever built
+    pub fn pools_built() -> usize {
+        POOLS_BUILT.load(Ordering::SeqCst)
+    }
+
+    /// Get worker thread count
+    pub fn thread_count(&self) -> usize {
+        self.pool.current_num_threads()
+    }
+
+    /// Run a job in the background
+    pub fn spawn(&self, job: impl FnOnce() + Send + 'static) {
+        self.pool.spawn(job);
+    }
+
+    /// Run jobs that borrow from the caller; returns once all have finished
+    pub fn scope<'scope, R: Send>(&self, f: impl FnOnce(&rayon::Scope<'scope>) -> R + Send) -> R {
+        self.pool.scope(f)
+    }
+
+    /// Run `f` on the pool so rayon parallel iterators inside it use these workers
+    pub fn install<R: Send>(&self, f: impl FnOnce() -> R + Send) -> R {
+        self.pool.install(f)
+    }
+
+    /// Call `f` for every index in `range`, `chunk` indices per job
+    pub fn parallel_for(&self, range: Range<usize>, chunk: usize, f: impl Fn(usize) + Send + Sync) {
+        use rayon::prelude::*;
+
+        self.pool.install(|| {
+            range.into_par_iter()
+                .with_min_len(chunk.max(1))
+                .for_each(f);
+        });
+    }
+}
+
+/// Pin worker `index` to a core past the reserved ones
+#[cfg(feature = "affinity")]
+fn pin_worker(index: usize) {
+    let Some(cores) = core_affinity::get_core_ids().filter(|c| !c.is_empty()) else {
+        log::warn!("Core list unavailable; worker {} not pinned", index);
+        return;
+    };
+    let core = cores[(index + RESERVED_CORES) % cores.len()];
+    if !core_affinity::set_for_current(core) {
+        log::warn!("Failed to pin worker {} to core {}", index, core.id);
+    }
+}
+
+/// Pinning is unavailable without the `affinity` feature
+#[cfg(not(feature = "affinity"))]
+fn pin_worker(index: usize) {
+    if index == 0 {
+        log::warn!("Worker pinning requested but the `affinity` feature is disabled");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicU64;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_jobs_share_one_pool() {
+        let jobs = JobSystem::global();
+        assert!(std::ptr::eq(jobs, JobSystem::global()));
+        assert!(jobs.thread_count() >= 1);
+        assert!(JobSystem::configure(3).is_err());
+
+        // Fire-and-forget jobs, then a scope that waits for its own
+        let done = Arc::new(AtomicU64::new(0));
+        for _ in 0..500 {
+            let done = done.clone();
+            jobs.spawn(move || {
+                done.fetch_add(1, Ordering::SeqCst);
+            });
+        }
+        let scoped = AtomicU64::new(0);
+        jobs.scope(|s| {
+            for i in 0..500 {
+                let scoped = &scoped;
+                s.spawn(move |_| {
+                    scoped.fetch_add(i, Ordering::SeqCst);
+                });
+            }
+        });
+        assert_eq!(scoped.load(Ordering::SeqCst), (0..500).sum::<u64>());
+
+        let sum = AtomicU64::new(0);
+        jobs.parallel_for(0..10_000, 64, |i| {
+            assert!(rayon::current_thread_index().is_some());
+            sum.fetch_add(i as u64, Ordering::SeqCst);
+        });
+        assert_eq!(sum.load(Ordering::SeqCst), (0..10_000).sum::<u64>());
+
+        while done.load(Ordering::SeqCst) < 500 {
+            std::thread::yield_now();
+        }
+
+        // ECS worlds borrow the shared pool instead of building their own
+        let _a = crate::ecs::EcsWorld::new();
+        let _b = crate::ecs::EcsWorld::new();
+        assert_eq!(JobSystem::pools_built(), 1);
+    }
+
+    #[test]
+    fn test_ecs_world_with_own_pool() {
+        let ecs = crate::ecs::EcsWorld::with_threads(3).unwrap();
+        assert_eq!(ecs.thread_count(), 3);
+        assert_eq!(ecs.jobs().install(rayon::current_num_threads), 3);
+        assert!(!std::ptr::eq(ecs.jobs(), JobSystem::global()));
+
+        let pinned = crate::ecs::EcsWorld::with_pinned_threads(1).unwrap();
+        assert_eq!(pinned.thread_count(), 1);
+    }
+}
@@ -0,0 +1,175 @@
+//! # Logging
+//!
+//! Installs the global `tracing` subscriber (which also receives `log`
+//! records) behind an `EnvFilter`. The starting filter comes from
+//! `RUST_LOG`, falling back to `info`. Per-module levels can then be changed
+//! at runtime through a reloadable layer, e.g. to turn the renderer up to
+//! `debug` or silence per-chunk `trace!` output without restarting.
+
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+use parking_lot::Mutex;
+use tracing_subscriber::filter::{EnvFilter, LevelFilter};
+use tracing_subscriber::{reload, Registry};
+
+/// Filter used when `RUST_LOG` is unset or invalid
+pub const DEFAULT_FILTER: &str = "info";
+
+/// Crate prefix for short module names
+const CRATE_NAME: &str = env!("CARGO_CRATE_NAME");
+
+/// Levels of the global subscriber
+static LEVELS: OnceLock<Mutex<LogLevels>> = OnceLock::new();
+
+/// Handle for changing a subscriber's filter at runtime
+pub struct LogLevels {
+    /// Reload handle of the filter layer
+    handle: reload::Handle<EnvFilter, Registry>,
+    /// Directives from `RUST_LOG` (or the default)
+    base: String,
+    /// Per-module levels set at runtime
+    overrides: BTreeMap<String, LevelFilter>,
+}
+
+impl LogLevels {
+    /// Create a reloadable filter layer starting from `base` directives
+    pub fn layer(base: &str) -> (reload::Layer<EnvFilter, Registry>, Self) {
+        let base = if EnvFilter::try_new(base).is_ok() { base } else { DEFAULT_FILTER };
+        let (layer, handle) = reload::Layer::new(build_filter(base, &BTreeMap::new()));
+        (layer, Self { handle, base: base.to_string(), overrides: BTreeMap::new() })
+    }
+
+    /// Set the level for a module and everything under it
+    ///
+    /// Names without `::` (like `renderer`) are taken relative to this
+    /// crate; full paths such as `naga::front` are used as written.
+    pub fn set(&mut self, module: &str, level: LevelFilter) -> Result<(), String> {
+        self.overrides.insert(target_for(module), level);
+        self.apply()
+    }
+
+    /// Drop a module's level, falling back to the base filter
+    pub fn reset(&mut self, module: &str) -> Result<(), String> {
+        self.overrides.remove(&target_for(module));
+        self.apply()
+    }
+
+    /// Get the current filter directives
+    pub fn directives(&self) -> String {
+        build_filter(&self.base, &self.overrides).to_string()
+    }
+
+    /// Swap the live filter for one with the current overrides
+    fn apply(&self) -> Result<(), String> {
+        self.handle
+            .reload(build_filter(&self.base, &self.overrides))
+            .map_err(|e| format!("Failed to update log filter: {}", e))
+    }
+}
+
+/// Combine base directives with per-module levels
+fn build_filter(base: &str, overrides: &BTreeMap<String, LevelFilter>) -> EnvFilter {
+    overrides.iter().fold(EnvFilter::new(base), |filter, (target, level)| {
+        match format!("{}={}", target, level).parse() {
+            Ok(directive) => filter.add_directive(directive),
+            Err(_) => filter,
+        }
+    })
+}
+
+/// Expand a short module name to a target under this crate
+fn target_for(module: &str) -> String {
+    if module.contains("::") || module == CRATE_NAME || module.is_empty() {
+        module.to_string()
+    } else {
+        format!("{}::{}", CRATE_NAME, module)
+    }
+}
+
+/// Install the global subscriber (later calls do nothing)
+pub fn init() {
+    use tracing_subscriber::fmt;
+    use tracing_subscriber::prelude::*;
+
+    if LEVELS.get().is_some() {
+        return;
+    }
+    let base = std::env::var(EnvFilter::DEFAULT_ENV).unwrap_or_else(|_| DEFAULT_FILTER.to_string());
+    let (filter, levels) = LogLevels::layer(&base);
+    let installed = tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt::layer())
+        .try_init();
+    if installed.is_ok() {
+        let _ = LEVELS.set(Mutex::new(levels));
+    }
+}
+
+/// Set the level for a module of the global subscriber
+pub fn set_log_level(module: &str, level: LevelFilter) -> Result<(), String> {
+    LEVELS
+        .get()
+        .ok_or_else(|| "logging not initialized".to_string())?
+        .lock()
+        .set(module, level)
+}
+
+/// Drop a module's level from the global subscriber
+pub fn reset_log_level(module: &str) -> Result<(), String> {
+    LEVELS
+        .get()
+        .ok_or_else(|| "logging not initialized".to_string())?
+        .lock()
+        .reset(module)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use tracing_subscriber::layer::{Context, Layer};
+    use tracing_subscriber::prelude::*;
+
+    /// Records the target of every event that gets through
+    struct Capture(Arc<Mutex<Vec<String>>>);
+
+    impl<S: tracing::Subscriber> Layer<S> for Capture {
+        fn on_event(&self, event: &tracing::Event<'_>, _ctx: Context<'_, S>) {
+            self.0.lock().push(format!("{} {}", event.metadata().target(), event.metadata().level()));
+        }
+    }
+
+    #[test]
+    fn test_module_level_filters_at_runtime() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let (filter, mut levels) = LogLevels::layer("warn");
+        let subscriber = tracing_subscriber::registry()
+            .with(filter)
+            .with(Capture(seen.clone()));
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::debug!(target: "libs_core::renderer::vulkan", "frame");
+            tracing::warn!(target: "libs_core::renderer", "slow frame");
+
+            levels.set("renderer", LevelFilter::DEBUG).unwrap();
+            levels.set("world", LevelFilter::ERROR).unwrap();
+            tracing::callsite::rebuild_interest_cache();
+            tracing::trace!(target: "libs_core::renderer::vulkan", "draw");
+            tracing::debug!(target: "libs_core::renderer::vulkan", "frame");
+            tracing::warn!(target: "libs_core::world", "chunk");
+            tracing::debug!(target: "libs_core::ecs", "tick");
+
+            levels.reset("renderer").unwrap();
+            tracing::callsite::rebuild_interest_cache();
+            tracing::info!(target: "libs_core::renderer", "resize");
+        });
+
+        assert_eq!(*seen.lock(), vec![
+            "libs_core::renderer WARN".to_string(),
+            "libs_core::renderer::vulkan DEBUG".to_string(),
+        ]);
+        assert_eq!(target_for("renderer"), "libs_core::renderer");
+        assert_eq!(target_for("wgpu_core::device"), "wgpu_core::device");
+    }
+}
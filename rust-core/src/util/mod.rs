@@ -4,5 +4,15 @@
 
 pub mod math;
 pub mod hash;
+pub mod jobs;
+pub mod assets;
+pub mod noise;
+pub mod logging;
+pub mod bvh;
+pub mod rng;
+pub mod occlusion;
 
 pub use math::*;
+pub use bvh::{Bounds, Bvh, Frustum};
+pub use rng::Rng;
+pub use occlusion::SoftwareOccluder;
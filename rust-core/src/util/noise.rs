@@ -0,0 +1,87 @@
+//! # Noise
+//!
+//! Seeded 2D Perlin noise and fractal sums of it for terrain. The
+//! permutation table is shuffled with the crate's own SplitMix64 `Rng`,
+//! so the same seed gives the same noise on every platform and release
+//! (no dependence on an external RNG's algorithm).
+
+use super::rng::Rng;
+
+/// Gradient directions for 2D noise
+const GRADIENTS: [(f32, f32); 8] = [
+    (1.0, 0.0), (-1.0, 0.0), (0.0, 1.0), (0.0, -1.0),
+    (0.707_106_77, 0.707_106_77), (-0.707_106_77, 0.707_106_77),
+    (0.707_106_77, -0.707_106_77), (-0.707_106_77, -0.707_106_77),
+];
+
+/// Seeded 2D Perlin noise
+#[derive(Clone)]
+pub struct Perlin {
+    /// Shuffled 0..256, repeated to avoid wrapping
+    perm: [u8; 512],
+}
+
+impl Perlin {
+    /// Create noise for a seed
+    pub fn new(seed: u64) -> Self {
+        let mut table: [u8; 256] = std::array::from_fn(|i| i as u8);
+        let mut rng = Rng::new(seed);
+        for i in (1..256).rev() {
+            let j = rng.below(i as u64 + 1) as usize;
+            table.swap(i, j);
+        }
+
+        let mut perm = [0u8; 512];
+        for (i, p) in perm.iter_mut().enumerate() {
+            *p = table[i & 255];
+        }
+        Self { perm }
+    }
+
+    /// Sample noise at a point (roughly -1..1, 0 on integer lattice points)
+    pub fn noise2(&self, x: f64, y: f64) -> f32 {
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = ((x - x0) as f32, (y - y0) as f32);
+        let (xi, yi) = ((x0 as i64 & 255) as usize, (y0 as i64 & 255) as usize);
+
+        let corner = |cx: usize, cy: usize, dx: f32, dy: f32| -> f32 {
+            let hash = self.perm[self.perm[xi + cx] as usize + yi + cy];
+            let (gx, gy) = GRADIENTS[(hash & 7) as usize];
+            gx * dx + gy * dy
+        };
+
+        let (u, v) = (fade(fx), fade(fy));
+        let bottom = lerp(corner(0, 0, fx, fy), corner(1, 0, fx - 1.0, fy), u);
+        let top = lerp(corner(0, 1, fx, fy - 1.0), corner(1, 1, fx - 1.0, fy - 1.0), u);
+        lerp(bottom, top, v) * std::f32::consts::SQRT_2
+    }
+
+    /// Fractal sum of `octaves` layers, normalized to roughly -1..1
+    ///
+    /// Each octave doubles the frequency and multiplies the amplitude by
+    /// `gain`.
+    pub fn fbm2(&self, x: f64, y: f64, octaves: u32, gain: f32) -> f32 {
+        let mut sum = 0.0;
+        let mut amplitude = 1.0;
+        let mut total = 0.0;
+        let mut frequency = 1.0;
+        for octave in 0..octaves {
+            // Offset octaves so their lattice points don't line up
+            let offset = octave as f64 * 17.31;
+            sum += self.noise2(x * frequency + offset, y * frequency - offset) * amplitude;
+            total += amplitude;
+            amplitude *= gain;
+            frequency *= 2.0;
+        }
+        if total > 0.0 { sum / total } else { 0.0 }
+    }
+}
+
+/// Perlin fade curve 6t⁵ - 15t⁴ + 10t³
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
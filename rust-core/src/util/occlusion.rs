@@ -0,0 +1,234 @@
+//! # Software Occlusion Culling
+//!
+//! Masked-rasterizer style culling on the CPU, so it needs no GPU queries
+//! and adds no frame of latency. Each frame, `begin` with the camera's
+//! view-projection, `add_occluder` the nearest opaque chunk boxes front to
+//! back (solid terrain makes large, cheap occluders), then `test` every
+//! chunk box before submitting it.
+//!
+//! Occluders are rasterized into a coarse depth buffer (depth 0..1, nearest
+//! wins) at pixel centers. A test projects the box, takes its nearest depth
+//! and reports it visible if any pixel under its screen rectangle is
+//! farther. 8x8 tiles keep their farthest depth, so tiles that are entirely
+//! in front of the box are rejected without touching their pixels.
+//!
+//! Anything crossing the near plane is treated conservatively: such
+//! occluders are skipped and such boxes count as visible.
+
+use glam::{Mat4, Vec3};
+
+use super::bvh::Bounds;
+
+/// Default depth buffer width
+pub const DEFAULT_WIDTH: usize = 256;
+
+/// Default depth buffer height
+pub const DEFAULT_HEIGHT: usize = 128;
+
+/// Tile edge length in pixels for the hierarchical test
+const TILE: usize = 8;
+
+/// Smallest clip-space w treated as in front of the camera
+const NEAR_W: f32 = 1e-4;
+
+/// Corner indices of the 12 triangles of a box (bit 0 = x, 1 = y, 2 = z)
+const BOX_TRIANGLES: [[usize; 3]; 12] = [
+    [0, 1, 3], [0, 3, 2], [4, 6, 7], [4, 7, 5], // -z, +z
+    [0, 4, 5], [0, 5, 1], [2, 3, 7], [2, 7, 6], // -y, +y
+    [0, 2, 6], [0, 6, 4], [1, 5, 7], [1, 7, 3], // -x, +x
+];
+
+/// CPU occlusion culler
+pub struct SoftwareOccluder {
+    /// Depth buffer size
+    width: usize,
+    height: usize,
+    /// Nearest occluder depth per pixel (1.0 = empty)
+    depth: Vec<f32>,
+    /// Farthest depth per tile
+    tile_max: Vec<f32>,
+    /// Tiles per row
+    tiles_x: usize,
+    /// Camera for this frame
+    view_proj: Mat4,
+}
+
+impl SoftwareOccluder {
+    /// Create a culler with a depth buffer of `width` x `height`
+    pub fn new(width: usize, height: usize) -> Self {
+        let (width, height) = (width.max(1), height.max(1));
+        let tiles_x = width.div_ceil(TILE);
+        Self {
+            width,
+            height,
+            depth: vec![1.0; width * height],
+            tile_max: vec![1.0; tiles_x * height.div_ceil(TILE)],
+            tiles_x,
+            view_proj: Mat4::IDENTITY,
+        }
+    }
+
+    /// Start a frame: clear the depth buffer and set the camera
+    pub fn begin(&mut self, view_proj: Mat4) {
+        self.view_proj = view_proj;
+        self.depth.fill(1.0);
+        self.tile_max.fill(1.0);
+    }
+
+    /// Rasterize an opaque box into the depth buffer
+    pub fn add_occluder(&mut self, bounds: &Bounds) {
+        let Some(corners) = self.project(bounds) else {
+            return;
+        };
+        for [a, b, c] in BOX_TRIANGLES {
+            self.rasterize(corners[a], corners[b], corners[c]);
+        }
+    }
+
+    /// Check if a box may be visible (false = hidden behind occluders)
+    pub fn test(&self, bounds: &Bounds) -> bool {
+        let Some(corners) = self.project(bounds) else {
+            return true;
+        };
+        let (min, max) = corners.iter().fold((Vec3::INFINITY, Vec3::NEG_INFINITY), |(lo, hi), &c| (lo.min(c), hi.max(c)));
+        if max.x < 0.0 || max.y < 0.0 || min.x >= self.width as f32 || min.y >= self.height as f32 {
+            return false;
+        }
+
+        let x0 = (min.x.max(0.0) as usize).min(self.width - 1);
+        let x1 = (max.x.max(0.0) as usize).min(self.width - 1);
+        let y0 = (min.y.max(0.0) as usize).min(self.height - 1);
+        let y1 = (max.y.max(0.0) as usize).min(self.height - 1);
+        let nearest = min.z;
+
+        for ty in y0 / TILE..=y1 / TILE {
+            for tx in x0 / TILE..=x1 / TILE {
+                if nearest > self.tile_max[ty * self.tiles_x + tx] {
+                    continue;
+                }
+                let xs = (tx * TILE).max(x0)..=((tx + 1) * TILE - 1).min(x1);
+                for y in (ty * TILE).max(y0)..=((ty + 1) * TILE - 1).min(y1) {
+                    let row = &self.depth[y * self.width..(y + 1) * self.width];
+                    if row[xs.clone()].iter().any(|&depth| nearest <= depth) {
+                        return true;
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    /// Project box corners to (pixel x, pixel y, depth); None if any is behind the near plane
+    fn project(&self, bounds: &Bounds) -> Option<[Vec3; 8]> {
+        let mut corners = [Vec3::ZERO; 8];
+        for (i, corner) in corners.iter_mut().enumerate() {
+            let p = Vec3::select(
+                glam::BVec3::new(i & 1 != 0, i & 2 != 0, i & 4 != 0),
+                bounds.max,
+                bounds.min,
+            );
+            let clip = self.view_proj * p.extend(1.0);
+            if clip.w <= NEAR_W {
+                return None;
+            }
+            let ndc = clip.truncate() / clip.w;
+            *corner = Vec3::new(
+                (ndc.x * 0.5 + 0.5) * self.width as f32,
+                (ndc.y * 0.5 + 0.5) * self.height as f32,
+                ndc.z,
+            );
+        }
+        Some(corners)
+    }
+
+    /// Rasterize one screen-space triangle at pixel centers, keeping the nearest depth
+    fn rasterize(&mut self, a: Vec3, b: Vec3, c: Vec3) {
+        let area = (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x);
+        if area.abs() < 1e-6 {
+            return;
+        }
+
+        let min_x = a.x.min(b.x).min(c.x).max(0.0);
+        let max_x = a.x.max(b.x).max(c.x).min(self.width as f32 - 0.5);
+        let min_y = a.y.min(b.y).min(c.y).max(0.0);
+        let max_y = a.y.max(b.y).max(c.y).min(self.height as f32 - 0.5);
+        if min_x > max_x || min_y > max_y {
+            return;
+        }
+        let (x0, x1) = ((min_x - 0.5).ceil().max(0.0) as usize, (max_x - 0.5).floor() as usize);
+        let (y0, y1) = ((min_y - 0.5).ceil().max(0.0) as usize, (max_y - 0.5).floor() as usize);
+
+        let edge = |p: Vec3, q: Vec3, x: f32, y: f32| ((q.x - p.x) * (y - p.y) - (q.y - p.y) * (x - p.x)) / area;
+        for y in y0..=y1 {
+            let py = y as f32 + 0.5;
+            for x in x0..=x1 {
+                let px = x as f32 + 0.5;
+                let (wa, wb, wc) = (edge(b, c, px, py), edge(c, a, px, py), edge(a, b, px, py));
+                if wa < 0.0 || wb < 0.0 || wc < 0.0 {
+                    continue;
+                }
+                // Post-divide depth is linear in screen space
+                let z = wa * a.z + wb * b.z + wc * c.z;
+                let pixel = &mut self.depth[y * self.width + x];
+                if z < *pixel {
+                    *pixel = z.max(0.0);
+                }
+            }
+        }
+
+        for ty in y0 / TILE..=y1 / TILE {
+            for tx in x0 / TILE..=x1 / TILE {
+                self.tile_max[ty * self.tiles_x + tx] = self.farthest_in_tile(tx, ty);
+            }
+        }
+    }
+
+    /// Farthest depth in a tile
+    fn farthest_in_tile(&self, tx: usize, ty: usize) -> f32 {
+        let xs = tx * TILE..((tx + 1) * TILE).min(self.width);
+        (ty * TILE..((ty + 1) * TILE).min(self.height))
+            .flat_map(|y| self.depth[y * self.width + xs.start..y * self.width + xs.end].iter().copied())
+            .fold(0.0, f32::max)
+    }
+
+    /// Get the depth buffer size
+    pub fn resolution(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+}
+
+impl Default for SoftwareOccluder {
+    fn default() -> Self {
+        Self::new(DEFAULT_WIDTH, DEFAULT_HEIGHT)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_box_behind_wall_is_culled() {
+        let view = Mat4::look_at_rh(Vec3::ZERO, Vec3::NEG_Z, Vec3::Y);
+        let proj = Mat4::perspective_rh(std::f32::consts::FRAC_PI_2, 2.0, 0.1, 1000.0);
+        let mut occluder = SoftwareOccluder::default();
+        occluder.begin(proj * view);
+
+        let wall = Bounds::new(Vec3::new(-5.0, -5.0, -12.0), Vec3::new(5.0, 5.0, -10.0));
+        let hidden = Bounds::new(Vec3::new(-2.0, -2.0, -32.0), Vec3::new(2.0, 2.0, -30.0));
+        assert!(occluder.test(&hidden));
+
+        occluder.add_occluder(&wall);
+        assert!(!occluder.test(&hidden));
+        assert!(occluder.test(&wall));
+
+        // In front of the wall, beside it, and behind the camera stay visible
+        assert!(occluder.test(&Bounds::new(Vec3::new(-1.0, -1.0, -6.0), Vec3::new(1.0, 1.0, -4.0))));
+        assert!(occluder.test(&Bounds::new(Vec3::new(30.0, -2.0, -32.0), Vec3::new(34.0, 2.0, -30.0))));
+        assert!(occluder.test(&Bounds::new(Vec3::new(-1.0, -1.0, 4.0), Vec3::new(1.0, 1.0, 6.0))));
+
+        // A new frame forgets last frame's occluders
+        occluder.begin(proj * view);
+        assert!(occluder.test(&hidden));
+    }
+}
@@ -487,6 +487,18 @@ impl NbtAssetLoader {
         })
     }
     
+    /// Parse binary NBT into an arena-backed tree
+    ///
+    /// Everything the tree points at lives in `arena`; extract what's needed,
+    /// then `arena.reset()` before parsing the next chunk.
+    pub fn parse_into_arena<'a>(
+        &self,
+        bytes: &[u8],
+        arena: &'a bumpalo::Bump,
+    ) -> Result<(&'a str, super::nbt::ArenaTag<'a>), String> {
+        super::nbt::parse_into_arena(bytes, arena)
+    }
+    
     /// Check for file changes and queue reloads
     pub fn check_for_changes(&mut self) -> Vec<String> {
         let mut changed = Vec::new();
@@ -0,0 +1,54 @@
+//! # Biome Tinting
+//!
+//! Biome grid helpers and the biome → tint color table used by the mesher
+//! to color grass and foliage.
+
+use std::collections::HashMap;
+
+/// Biome cells per section (4x4x4 grid)
+pub const BIOMES_PER_SECTION: usize = 64;
+
+/// Tint applied when a biome has no entry (white = untinted)
+pub const DEFAULT_TINT: u32 = 0xFF_FF_FF;
+
+/// Biome grid index for local block coordinates (y, z, x order like Anvil)
+pub fn biome_index(x: usize, y: usize, z: usize) -> usize {
+    ((y >> 2) << 4) | ((z >> 2) << 2) | (x >> 2)
+}
+
+/// Check if a block takes the biome tint (grass, leaves, tall grass, vines, lily pad)
+pub fn is_tintable(block_id: u16) -> bool {
+    matches!(block_id, 2 | 18 | 31 | 106 | 111 | 161)
+}
+
+/// Biome ID -> tint color (0xRRGGBB) table
+#[derive(Debug, Clone, Default)]
+pub struct BiomeTintTable {
+    tints: HashMap<u16, u32>,
+}
+
+impl BiomeTintTable {
+    /// Create an empty table
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the tint color for a biome
+    pub fn set_tint(&mut self, biome_id: u16, color: u32) {
+        self.tints.insert(biome_id, color & 0xFF_FF_FF);
+    }
+
+    /// Resolve the tint color for a biome
+    pub fn tint(&self, biome_id: u16) -> u32 {
+        self.tints.get(&biome_id).copied().unwrap_or(DEFAULT_TINT)
+    }
+
+    /// Resolve the tint for a block in a biome (untinted blocks stay white)
+    pub fn tint_for_block(&self, block_id: u16, biome_id: u16) -> u32 {
+        if is_tintable(block_id) {
+            self.tint(biome_id)
+        } else {
+            DEFAULT_TINT
+        }
+    }
+}
@@ -0,0 +1,144 @@
+//! # Dirty Chunk Queue
+//!
+//! Chunks waiting for a re-mesh, handed out closest to the camera first so
+//! the terrain around the player updates before the horizon does.
+//! Re-marking a queued chunk refreshes its entry instead of queueing it
+//! twice; moving the camera re-keys every queued chunk.
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+/// Chunk column key (chunk x, chunk z)
+pub type ChunkPos = (i32, i32);
+
+/// Heap entry (lowest distance = first)
+#[derive(Debug, Clone, Copy)]
+struct HeapEntry {
+    distance_sq: i64,
+    generation: u64,
+    pos: ChunkPos,
+}
+
+impl PartialEq for HeapEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for HeapEntry {}
+
+impl PartialOrd for HeapEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for HeapEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed so BinaryHeap pops the closest chunk; older marks win ties
+        other.distance_sq.cmp(&self.distance_sq)
+            .then_with(|| other.generation.cmp(&self.generation))
+    }
+}
+
+/// Dirty chunks by distance to the camera
+pub struct DirtyChunkQueue {
+    /// Priority queue (may contain stale entries)
+    heap: BinaryHeap<HeapEntry>,
+    /// Latest mark generation per queued chunk
+    queued: HashMap<ChunkPos, u64>,
+    /// Mark counter
+    next_generation: u64,
+    /// Chunk the camera is in
+    camera: ChunkPos,
+}
+
+impl DirtyChunkQueue {
+    /// Create an empty queue with the camera at chunk (0, 0)
+    pub fn new() -> Self {
+        Self {
+            heap: BinaryHeap::new(),
+            queued: HashMap::new(),
+            next_generation: 0,
+            camera: (0, 0),
+        }
+    }
+
+    /// Squared distance from the camera chunk
+    fn distance_sq(&self, (x, z): ChunkPos) -> i64 {
+        let dx = x as i64 - self.camera.0 as i64;
+        let dz = z as i64 - self.camera.1 as i64;
+        dx * dx + dz * dz
+    }
+
+    /// Queue a chunk, or refresh its entry if it is already queued
+    pub fn push(&mut self, pos: ChunkPos) {
+        let generation = self.next_generation;
+        self.next_generation += 1;
+        self.queued.insert(pos, generation);
+        self.heap.push(HeapEntry { distance_sq: self.distance_sq(pos), generation, pos });
+    }
+
+    /// Take the closest dirty chunk
+    pub fn pop(&mut self) -> Option<ChunkPos> {
+        while let Some(entry) = self.heap.pop() {
+            if self.queued.get(&entry.pos) == Some(&entry.generation) {
+                self.queued.remove(&entry.pos);
+                return Some(entry.pos);
+            }
+            // Superseded by a newer mark or removed
+        }
+        None
+    }
+
+    /// Drop a chunk from the queue (e.g. unloaded)
+    pub fn remove(&mut self, pos: ChunkPos) -> bool {
+        self.queued.remove(&pos).is_some()
+    }
+
+    /// Move the camera, re-keying every queued chunk
+    pub fn set_camera(&mut self, x: i32, z: i32) {
+        if self.camera == (x, z) {
+            return;
+        }
+        self.camera = (x, z);
+        let entries: Vec<HeapEntry> = self
+            .queued
+            .iter()
+            .map(|(&pos, &generation)| HeapEntry { distance_sq: self.distance_sq(pos), generation, pos })
+            .collect();
+        self.heap = BinaryHeap::from(entries);
+    }
+
+    /// Get the chunk the camera is in
+    pub fn camera(&self) -> ChunkPos {
+        self.camera
+    }
+
+    /// Check if a chunk is queued
+    pub fn contains(&self, pos: ChunkPos) -> bool {
+        self.queued.contains_key(&pos)
+    }
+
+    /// Get the number of queued chunks
+    pub fn len(&self) -> usize {
+        self.queued.len()
+    }
+
+    /// Check if no chunk is queued
+    pub fn is_empty(&self) -> bool {
+        self.queued.is_empty()
+    }
+
+    /// Clear the queue (the camera stays)
+    pub fn clear(&mut self) {
+        self.heap.clear();
+        self.queued.clear();
+    }
+}
+
+impl Default for DirtyChunkQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,216 @@
+//! # Edit History
+//!
+//! Undo/redo for world-editing tools. In edit mode every `set_block` that
+//! changes a block is logged as a `BlockEdit`. Edits made between
+//! `begin_edit` and `commit_edit` form one named transaction, so a fill
+//! undoes as a single step; edits outside a transaction are one step each.
+//! Undo and redo replay the inverse or forward edits through `set_block`,
+//! which re-marks the affected chunks dirty.
+
+use super::WorldManager;
+
+/// Default number of undoable transactions kept
+pub const DEFAULT_MAX_UNDO: usize = 256;
+
+/// One logged block change
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockEdit {
+    /// World position
+    pub pos: (i32, i32, i32),
+    /// Block before the edit
+    pub old_id: u16,
+    /// Block after the edit
+    pub new_id: u16,
+}
+
+/// Edits undone and redone together
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EditTransaction {
+    /// Name shown in the editor's history
+    pub name: String,
+    /// Edits in the order they were made
+    pub edits: Vec<BlockEdit>,
+}
+
+/// Edit log state
+pub struct EditLog {
+    /// Whether `set_block` is logged
+    enabled: bool,
+    /// Transaction being recorded
+    open: Option<EditTransaction>,
+    /// Committed transactions, oldest first
+    undo: Vec<EditTransaction>,
+    /// Undone transactions, most recently undone last
+    redo: Vec<EditTransaction>,
+    /// Undo depth limit
+    max_undo: usize,
+    /// Set while undo/redo replays so the replay isn't logged
+    replaying: bool,
+}
+
+impl EditLog {
+    /// Create a disabled edit log
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            open: None,
+            undo: Vec::new(),
+            redo: Vec::new(),
+            max_undo: DEFAULT_MAX_UNDO,
+            replaying: false,
+        }
+    }
+
+    /// Log an edit into the open transaction, or as a step of its own
+    pub(super) fn record(&mut self, edit: BlockEdit) {
+        if !self.enabled || self.replaying || edit.old_id == edit.new_id {
+            return;
+        }
+        match self.open.as_mut() {
+            Some(transaction) => transaction.edits.push(edit),
+            None => self.push(EditTransaction { name: "set_block".to_string(), edits: vec![edit] }),
+        }
+    }
+
+    /// Whether edits are being logged right now
+    pub(super) fn is_recording(&self) -> bool {
+        self.enabled && !self.replaying
+    }
+
+    /// Add a committed transaction; new edits invalidate the redo history
+    fn push(&mut self, transaction: EditTransaction) {
+        self.redo.clear();
+        self.undo.push(transaction);
+        if self.undo.len() > self.max_undo {
+            let excess = self.undo.len() - self.max_undo;
+            self.undo.drain(..excess);
+        }
+    }
+}
+
+impl Default for EditLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WorldManager {
+    /// Turn edit logging on or off (turning it off drops the history)
+    pub fn set_edit_mode(&mut self, enabled: bool) {
+        if !enabled {
+            self.edits = EditLog { max_undo: self.edits.max_undo, ..EditLog::new() };
+        }
+        self.edits.enabled = enabled;
+    }
+
+    /// Check if edits are logged
+    pub fn is_edit_mode(&self) -> bool {
+        self.edits.enabled
+    }
+
+    /// Set how many transactions can be undone
+    pub fn set_max_undo(&mut self, steps: usize) {
+        self.edits.max_undo = steps.max(1);
+        let excess = self.edits.undo.len().saturating_sub(self.edits.max_undo);
+        self.edits.undo.drain(..excess);
+    }
+
+    /// Start grouping edits into one undo step (commits any open one first)
+    pub fn begin_edit(&mut self, name: &str) {
+        self.commit_edit();
+        if self.edits.enabled {
+            self.edits.open = Some(EditTransaction { name: name.to_string(), edits: Vec::new() });
+        }
+    }
+
+    /// Finish the open transaction; false if none was open or it changed nothing
+    pub fn commit_edit(&mut self) -> bool {
+        match self.edits.open.take() {
+            Some(transaction) if !transaction.edits.is_empty() => {
+                log::debug!("Edit '{}' committed ({} blocks)", transaction.name, transaction.edits.len());
+                self.edits.push(transaction);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Revert the last transaction; returns its name
+    pub fn undo(&mut self) -> Option<String> {
+        self.commit_edit();
+        let transaction = self.edits.undo.pop()?;
+        self.replay(transaction.edits.iter().rev().map(|edit| (edit.pos, edit.old_id)));
+        let name = transaction.name.clone();
+        self.edits.redo.push(transaction);
+        Some(name)
+    }
+
+    /// Re-apply the last undone transaction; returns its name
+    pub fn redo(&mut self) -> Option<String> {
+        self.commit_edit();
+        let transaction = self.edits.redo.pop()?;
+        self.replay(transaction.edits.iter().map(|edit| (edit.pos, edit.new_id)));
+        let name = transaction.name.clone();
+        self.edits.undo.push(transaction);
+        Some(name)
+    }
+
+    /// Set blocks without logging them
+    fn replay(&mut self, blocks: impl Iterator<Item = ((i32, i32, i32), u16)>) {
+        self.edits.replaying = true;
+        for ((x, y, z), block_id) in blocks {
+            self.set_block(x, y, z, block_id as u32);
+        }
+        self.edits.replaying = false;
+    }
+
+    /// Get the number of undoable transactions
+    pub fn undo_count(&self) -> usize {
+        self.edits.undo.len()
+    }
+
+    /// Get the number of redoable transactions
+    pub fn redo_count(&self) -> usize {
+        self.edits.redo.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transaction_undoes_as_one_step() {
+        let mut world = WorldManager::new();
+        world.set_random_tick_rate(0);
+        world.submit_chunk(0, 0, &[]);
+        world.submit_chunk(1, 0, &[]);
+        world.set_block(2, 64, 2, 1);
+        world.set_edit_mode(true);
+        world.tick();
+        assert_eq!(world.dirty_chunk_count(), 0);
+
+        world.begin_edit("fill");
+        for x in 0..20 {
+            world.set_block(x, 64, 2, 5);
+        }
+        assert!(world.commit_edit());
+        world.set_block(3, 70, 3, 9);
+        assert_eq!(world.undo_count(), 2);
+        world.tick();
+
+        assert_eq!(world.undo().as_deref(), Some("set_block"));
+        assert_eq!(world.get_block(3, 70, 3), 0);
+        assert_eq!(world.undo().as_deref(), Some("fill"));
+        assert_eq!(world.get_block(2, 64, 2), 1);
+        assert!((0..20).filter(|&x| x != 2).all(|x| world.get_block(x, 64, 2) == 0));
+        assert_eq!(world.dirty_chunk_count(), 2);
+        assert!(world.undo().is_none());
+
+        // Redo restores the fill; a new edit then drops the remaining redo
+        assert_eq!(world.redo().as_deref(), Some("fill"));
+        assert!((0..20).all(|x| world.get_block(x, 64, 2) == 5));
+        world.set_block(0, 0, 0, 7);
+        assert_eq!((world.undo_count(), world.redo_count()), (2, 0));
+    }
+}
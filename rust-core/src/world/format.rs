@@ -0,0 +1,140 @@
+//! # Chunk Wire Format
+//!
+//! Decoding and encoding of the chunk payload submitted from Java.
+//!
+//! Layout (little-endian):
+//! - `u16` section count, then per section: `i32` y, 4096 × `u16` block IDs,
+//!   4096 × `u8` light (sky << 4 | block), 64 × `u16` biome IDs (4x4x4 grid)
+//! - `u16` block entity count, then per entity: `i32` x, y, z (world coords),
+//!   `u16` id length + UTF-8 id, `u32` NBT length + raw NBT bytes
+
+use std::collections::HashMap;
+
+use super::biome::BIOMES_PER_SECTION;
+use super::{BlockEntity, ChunkSection, PalettedStorage};
+
+/// Blocks per section
+const SECTION_VOLUME: usize = 4096;
+
+/// Decoded chunk payload
+#[derive(Default)]
+pub struct ChunkPayload {
+    /// Block sections
+    pub sections: Vec<ChunkSection>,
+    /// Block entities by world coordinates
+    pub block_entities: HashMap<(i32, i32, i32), BlockEntity>,
+}
+
+/// Bounds-checked little-endian reader
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let end = self.pos.checked_add(len)
+            .filter(|&end| end <= self.data.len())
+            .ok_or_else(|| format!("Chunk payload truncated at byte {}", self.pos))?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        let b = self.take(2)?;
+        Ok(u16::from_le_bytes([b[0], b[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        let b = self.take(4)?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn i32(&mut self) -> Result<i32, String> {
+        Ok(self.u32()? as i32)
+    }
+}
+
+impl ChunkPayload {
+    /// Decode a chunk payload (an empty payload decodes to an empty chunk)
+    pub fn parse(data: &[u8]) -> Result<Self, String> {
+        let mut payload = Self::default();
+        if data.is_empty() {
+            return Ok(payload);
+        }
+
+        let mut reader = Reader::new(data);
+
+        let section_count = reader.u16()?;
+        for _ in 0..section_count {
+            let y = reader.i32()?;
+            let blocks: Vec<u16> = reader.take(SECTION_VOLUME * 2)?
+                .chunks_exact(2)
+                .map(|b| u16::from_le_bytes([b[0], b[1]]))
+                .collect();
+            let light = PalettedStorage::from_slice(reader.take(SECTION_VOLUME)?);
+            let biomes: Vec<u16> = reader.take(BIOMES_PER_SECTION * 2)?
+                .chunks_exact(2)
+                .map(|b| u16::from_le_bytes([b[0], b[1]]))
+                .collect();
+            let non_air = ChunkSection::count_non_air(&blocks);
+            let blocks = PalettedStorage::from_slice(&blocks);
+
+            payload.sections.push(ChunkSection { y, blocks, light, biomes, non_air });
+        }
+
+        let entity_count = reader.u16()?;
+        for _ in 0..entity_count {
+            let pos = (reader.i32()?, reader.i32()?, reader.i32()?);
+            let id_len = reader.u16()? as usize;
+            let id = std::str::from_utf8(reader.take(id_len)?)
+                .map_err(|e| format!("Invalid block entity id: {}", e))?
+                .to_string();
+            let nbt_len = reader.u32()? as usize;
+            let nbt = reader.take(nbt_len)?.to_vec();
+
+            payload.block_entities.insert(pos, BlockEntity { id, nbt });
+        }
+
+        if reader.pos != data.len() {
+            return Err(format!("{} trailing bytes in chunk payload", data.len() - reader.pos));
+        }
+
+        Ok(payload)
+    }
+
+    /// Encode into the wire format
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+
+        out.extend_from_slice(&(self.sections.len() as u16).to_le_bytes());
+        for section in &self.sections {
+            out.extend_from_slice(&section.y.to_le_bytes());
+            for block in section.blocks.iter() {
+                out.extend_from_slice(&block.to_le_bytes());
+            }
+            out.extend(section.light.iter());
+            for biome in &section.biomes {
+                out.extend_from_slice(&biome.to_le_bytes());
+            }
+        }
+
+        out.extend_from_slice(&(self.block_entities.len() as u16).to_le_bytes());
+        for (&(x, y, z), entity) in &self.block_entities {
+            out.extend_from_slice(&x.to_le_bytes());
+            out.extend_from_slice(&y.to_le_bytes());
+            out.extend_from_slice(&z.to_le_bytes());
+            out.extend_from_slice(&(entity.id.len() as u16).to_le_bytes());
+            out.extend_from_slice(entity.id.as_bytes());
+            out.extend_from_slice(&(entity.nbt.len() as u32).to_le_bytes());
+            out.extend_from_slice(&entity.nbt);
+        }
+
+        out
+    }
+}
@@ -3,12 +3,37 @@
 //! Chunk storage and world data management.
 
 pub mod assets;
+pub mod biome;
+pub mod dirty;
+pub mod edits;
+pub mod format;
+pub mod light;
+pub mod nbt;
+pub mod palette;
+pub mod raycast;
+pub mod save;
+pub mod ticks;
+pub mod visibility;
+pub mod worldgen;
 
 pub use assets::NbtAssetLoader;
+pub use biome::BiomeTintTable;
+pub use dirty::DirtyChunkQueue;
+pub use edits::{BlockEdit, EditTransaction};
+pub use format::ChunkPayload;
+pub use nbt::ArenaTag;
+pub use palette::PalettedStorage;
+pub use raycast::BlockHit;
+pub use save::SaveError;
+pub use ticks::BlockTickFn;
+pub use visibility::VisibilityGraph;
+pub use worldgen::{NoiseTerrainGenerator, TerrainGenerator};
 
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicI64, Ordering};
 
+use crate::profiling::spans;
+
 /// Next chunk handle
 static NEXT_CHUNK_HANDLE: AtomicI64 = AtomicI64::new(1);
 
@@ -20,8 +45,26 @@ pub struct WorldManager {
     /// Chunk handles mapping
     chunk_handles: HashMap<i64, (i32, i32)>,
     
-    /// Dirty chunks that need re-meshing
-    dirty_chunks: Vec<(i32, i32)>,
+    /// Dirty chunks that need re-meshing, closest to the camera first
+    dirty_chunks: DirtyChunkQueue,
+    
+    /// Biome tint colors
+    biome_tints: BiomeTintTable,
+    
+    /// Scheduled and random block ticks
+    ticks: ticks::BlockTicks,
+    
+    /// Generator for chunks the server doesn't send
+    generator: Box<dyn TerrainGenerator>,
+    
+    /// World seed passed to the generator
+    seed: u64,
+    
+    /// Undo/redo log for edit mode
+    edits: edits::EditLog,
+    
+    /// Vertical extent of every chunk
+    height: WorldHeight,
 }
 
 /// Chunk data container
@@ -46,6 +89,74 @@ pub struct ChunkData {
     
     /// Raw data for mesh generation
     raw_data: Vec<u8>,
+    
+    /// Block entities by world coordinates
+    block_entities: HashMap<(i32, i32, i32), BlockEntity>,
+}
+
+/// Block entity (chest, sign, furnace...)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockEntity {
+    /// Block entity ID (e.g. "minecraft:chest")
+    pub id: String,
+    
+    /// Raw NBT payload
+    pub nbt: Vec<u8>,
+}
+
+/// Blocks per section
+const SECTION_VOLUME: usize = 4096;
+
+/// Vertical extent of the world, in whole sections
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WorldHeight {
+    /// Lowest block Y
+    pub min_y: i32,
+    /// One above the highest block Y
+    pub max_y: i32,
+}
+
+impl WorldHeight {
+    /// Pre-1.18 worlds (Y 0..256)
+    pub const LEGACY: WorldHeight = WorldHeight { min_y: 0, max_y: 256 };
+    /// 1.18+ overworld (Y -64..320)
+    pub const OVERWORLD: WorldHeight = WorldHeight { min_y: -64, max_y: 320 };
+    
+    /// Create a height range; both bounds must be multiples of 16
+    pub fn new(min_y: i32, max_y: i32) -> Result<Self, String> {
+        if min_y % 16 != 0 || max_y % 16 != 0 || min_y >= max_y {
+            return Err(format!("Invalid world height {}..{} (need section-aligned min < max)", min_y, max_y));
+        }
+        Ok(Self { min_y, max_y })
+    }
+    
+    /// Section Y of the lowest section
+    pub fn min_section(&self) -> i32 {
+        self.min_y >> 4
+    }
+    
+    /// Number of sections in a chunk column
+    pub fn section_count(&self) -> usize {
+        ((self.max_y - self.min_y) >> 4) as usize
+    }
+    
+    /// Check if a block Y is inside the world
+    pub fn contains(&self, y: i32) -> bool {
+        (self.min_y..self.max_y).contains(&y)
+    }
+    
+    /// Index into a chunk's sections of section Y `section_y`
+    pub fn section_index(&self, section_y: i32) -> Option<usize> {
+        let index = section_y - self.min_section();
+        (0..self.section_count() as i32).contains(&index).then_some(index as usize)
+    }
+}
+
+impl Default for WorldHeight {
+    /// Legacy height; tall worlds opt in with `WorldManager::with_height`
+    fn default() -> Self {
+        Self::LEGACY
+    }
 }
 
 /// A 16x16x16 chunk section
@@ -53,14 +164,17 @@ pub struct ChunkSection {
     /// Section Y index
     y: i32,
     
-    /// Block IDs (4096 entries)
-    blocks: Vec<u16>,
+    /// Block IDs (4096 entries, paletted)
+    blocks: PalettedStorage<u16>,
     
-    /// Light levels
-    light: Vec<u8>,
+    /// Light levels (4096 entries, paletted)
+    light: PalettedStorage<u8>,
     
-    /// Is empty (all air)
-    empty: bool,
+    /// Biome IDs (4x4x4 grid, 64 entries)
+    biomes: Vec<u16>,
+    
+    /// Non-air blocks (0 = all air)
+    non_air: u16,
 }
 
 impl ChunkSection {
@@ -68,26 +182,131 @@ impl ChunkSection {
     pub fn new(y: i32) -> Self {
         Self {
             y,
-            blocks: vec![0; 4096],
-            light: vec![0; 4096],
-            empty: true,
+            blocks: PalettedStorage::new(SECTION_VOLUME),
+            light: PalettedStorage::new(SECTION_VOLUME),
+            biomes: vec![0; biome::BIOMES_PER_SECTION],
+            non_air: 0,
         }
     }
     
+    /// Count the non-air blocks of a full block array
+    fn count_non_air(blocks: &[u16]) -> u16 {
+        blocks.iter().filter(|&&b| b != 0).count() as u16
+    }
+    
     /// Get block at local coordinates
     pub fn get_block(&self, x: usize, y: usize, z: usize) -> u16 {
         let index = (y << 8) | (z << 4) | x;
-        self.blocks.get(index).copied().unwrap_or(0)
+        self.blocks.get(index)
     }
     
     /// Set block at local coordinates
     pub fn set_block(&mut self, x: usize, y: usize, z: usize, block_id: u16) {
         let index = (y << 8) | (z << 4) | x;
-        if index < self.blocks.len() {
-            self.blocks[index] = block_id;
-            self.empty = block_id == 0 && self.blocks.iter().all(|&b| b == 0);
+        if index < SECTION_VOLUME {
+            let previous = self.blocks.get(index);
+            self.blocks.set(index, block_id);
+            match (previous != 0, block_id != 0) {
+                (false, true) => self.non_air += 1,
+                (true, false) => {
+                    self.non_air -= 1;
+                    if self.non_air == 0 {
+                        // Collapse back to a single air palette entry
+                        self.blocks = PalettedStorage::new(SECTION_VOLUME);
+                    }
+                }
+                _ => {}
+            }
         }
     }
+    
+    /// Check if the section is all air
+    pub fn is_empty(&self) -> bool {
+        self.non_air == 0
+    }
+    
+    /// Get the number of non-air blocks
+    pub fn non_air_blocks(&self) -> u16 {
+        self.non_air
+    }
+    
+    /// Get the section Y index
+    pub fn section_y(&self) -> i32 {
+        self.y
+    }
+    
+    /// Get the block storage
+    pub fn blocks(&self) -> &PalettedStorage<u16> {
+        &self.blocks
+    }
+    
+    /// Bytes used by this section, including heap storage
+    pub fn memory_bytes(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + self.blocks.heap_bytes()
+            + self.light.heap_bytes()
+            + self.biomes.capacity() * std::mem::size_of::<u16>()
+    }
+    
+    /// Get biome at local block coordinates
+    pub fn get_biome(&self, x: usize, y: usize, z: usize) -> u16 {
+        self.biomes.get(biome::biome_index(x, y, z)).copied().unwrap_or(0)
+    }
+    
+    /// Get the biome grid
+    pub fn biomes(&self) -> &[u16] {
+        &self.biomes
+    }
+}
+
+impl ChunkData {
+    /// Get block entity count
+    pub fn block_entity_count(&self) -> usize {
+        self.block_entities.len()
+    }
+    
+    /// Section holding block Y (`y >> 4` floors, so negative Y works)
+    fn section(&self, height: WorldHeight, y: i32) -> Option<&ChunkSection> {
+        self.sections.get(height.section_index(y >> 4)?)
+    }
+    
+    /// Section holding block Y, creating it and any missing ones below
+    fn section_mut(&mut self, height: WorldHeight, y: i32) -> Option<&mut ChunkSection> {
+        let index = height.section_index(y >> 4)?;
+        while self.sections.len() <= index {
+            self.sections.push(ChunkSection::new(height.min_section() + self.sections.len() as i32));
+        }
+        self.sections.get_mut(index)
+    }
+    
+    /// Store a section at its Y; false if outside the world height
+    fn place_section(&mut self, height: WorldHeight, section: ChunkSection) -> bool {
+        let Some(slot) = self.section_mut(height, section.y << 4) else {
+            return false;
+        };
+        *slot = section;
+        true
+    }
+    
+    /// Replace contents with a decoded payload; a malformed payload leaves the chunk unchanged
+    fn apply_payload(&mut self, data: &[u8], height: WorldHeight) -> Result<(), String> {
+        let payload = ChunkPayload::parse(data)
+            .map_err(|e| format!("Chunk ({}, {}) payload rejected: {}", self.x, self.z, e))?;
+        
+        self.sections.clear();
+        for section in payload.sections {
+            let y = section.y;
+            if !self.place_section(height, section) {
+                log::debug!("Chunk ({}, {}): dropping section {} outside the world height", self.x, self.z, y);
+            }
+        }
+        
+        let (cx, cz) = (self.x, self.z);
+        self.block_entities = payload.block_entities;
+        self.block_entities.retain(|&(x, _, z), _| (x >> 4, z >> 4) == (cx, cz));
+        self.raw_data = data.to_vec();
+        Ok(())
+    }
 }
 
 impl WorldManager {
@@ -98,16 +317,40 @@ impl WorldManager {
         Self {
             chunks: HashMap::new(),
             chunk_handles: HashMap::new(),
-            dirty_chunks: Vec::new(),
+            dirty_chunks: DirtyChunkQueue::new(),
+            biome_tints: BiomeTintTable::new(),
+            ticks: ticks::BlockTicks::new(),
+            generator: Box::new(NoiseTerrainGenerator::new()),
+            seed: 0,
+            edits: edits::EditLog::new(),
+            height: WorldHeight::default(),
         }
     }
     
-    /// Process a tick (chunk loading/meshing)
+    /// Create a world manager with a custom height range
+    pub fn with_height(height: WorldHeight) -> Self {
+        Self { height, ..Self::new() }
+    }
+    
+    /// Get the world height range
+    pub fn height(&self) -> WorldHeight {
+        self.height
+    }
+    
+    /// Process a tick (block updates, chunk loading/meshing)
     pub fn tick(&mut self) {
+        let _span = tracing::debug_span!(
+            spans::WORLD_TICK,
+            chunks = self.chunks.len(),
+            dirty_chunks = self.dirty_chunks.len(),
+        ).entered();
+        self.process_block_ticks();
+        
         // Process dirty chunks for meshing
         if !self.dirty_chunks.is_empty() {
-            // Process up to 4 chunks per tick
-            let to_process: Vec<_> = self.dirty_chunks.drain(..self.dirty_chunks.len().min(4)).collect();
+            // Process up to 4 chunks per tick, nearest first
+            let to_process: Vec<_> = std::iter::from_fn(|| self.dirty_chunks.pop()).take(4).collect();
+            let _meshing = tracing::debug_span!(spans::WORLD_MESHING, batch = to_process.len()).entered();
             
             for (x, z) in to_process {
                 if let Some(chunk) = self.chunks.get_mut(&(x, z)) {
@@ -125,15 +368,19 @@ impl WorldManager {
         let handle = NEXT_CHUNK_HANDLE.fetch_add(1, Ordering::SeqCst);
         
         // Parse chunk data
-        let chunk = ChunkData {
+        let mut chunk = ChunkData {
             handle,
             x,
             z,
-            sections: Vec::new(), // Would parse from data
+            sections: Vec::new(),
             meshed: false,
             dirty: true,
-            raw_data: data.to_vec(),
+            raw_data: Vec::new(),
+            block_entities: HashMap::new(),
         };
+        if let Err(e) = chunk.apply_payload(data, self.height) {
+            log::warn!("{}", e);
+        }
         
         self.chunks.insert((x, z), chunk);
         self.chunk_handles.insert(handle, (x, z));
@@ -144,19 +391,42 @@ impl WorldManager {
         handle
     }
     
-    /// Update chunk data
-    pub fn update_chunk(&mut self, x: i32, z: i32, data: &[u8]) {
+    /// Generate a chunk if it isn't loaded, returning its handle
+    pub fn generate_chunk(&mut self, x: i32, z: i32) -> i64 {
+        if let Some(chunk) = self.chunks.get(&(x, z)) {
+            return chunk.handle;
+        }
+        let payload = self.generator.generate_chunk(x, z, self.seed);
+        self.submit_chunk(x, z, &payload.encode())
+    }
+    
+    /// Replace the terrain generator
+    pub fn set_generator(&mut self, generator: Box<dyn TerrainGenerator>) {
+        self.generator = generator;
+    }
+    
+    /// Set the world seed
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+    
+    /// Get the world seed
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+    
+    /// Update chunk data; a malformed payload is rejected and the chunk kept as it was
+    pub fn update_chunk(&mut self, x: i32, z: i32, data: &[u8]) -> Result<(), String> {
         if let Some(chunk) = self.chunks.get_mut(&(x, z)) {
-            chunk.raw_data = data.to_vec();
+            chunk.apply_payload(data, self.height)?;
             chunk.dirty = true;
             chunk.meshed = false;
             
-            if !self.dirty_chunks.contains(&(x, z)) {
-                self.dirty_chunks.push((x, z));
-            }
+            self.dirty_chunks.push((x, z));
             
             log::trace!("Chunk updated: ({}, {})", x, z);
         }
+        Ok(())
     }
     
     /// Mark chunk as dirty
@@ -165,19 +435,22 @@ impl WorldManager {
             chunk.dirty = true;
             chunk.meshed = false;
             
-            if !self.dirty_chunks.contains(&(x, z)) {
-                self.dirty_chunks.push((x, z));
-            }
+            self.dirty_chunks.push((x, z));
             
             log::trace!("Chunk marked dirty: ({}, {})", x, z);
         }
     }
     
+    /// Set the chunk the camera is in (dirty chunks mesh nearest first)
+    pub fn set_camera_chunk(&mut self, x: i32, z: i32) {
+        self.dirty_chunks.set_camera(x, z);
+    }
+    
     /// Unload a chunk
     pub fn unload_chunk(&mut self, x: i32, z: i32) {
         if let Some(chunk) = self.chunks.remove(&(x, z)) {
             self.chunk_handles.remove(&chunk.handle);
-            self.dirty_chunks.retain(|&c| c != (x, z));
+            self.dirty_chunks.remove((x, z));
             log::trace!("Chunk unloaded: ({}, {})", x, z);
         }
     }
@@ -187,30 +460,35 @@ impl WorldManager {
         let chunk_x = x >> 4;
         let chunk_z = z >> 4;
         
+        if !self.height.contains(y) {
+            log::trace!("Ignoring block at Y {} outside {}..{}", y, self.height.min_y, self.height.max_y);
+            return;
+        }
+        
+        if self.edits.is_recording() && self.chunks.contains_key(&(chunk_x, chunk_z)) {
+            let old_id = self.get_block(x, y, z);
+            self.edits.record(BlockEdit { pos: (x, y, z), old_id, new_id: block_id as u16 });
+        }
+        
         if let Some(chunk) = self.chunks.get_mut(&(chunk_x, chunk_z)) {
-            // Calculate section and local coordinates
-            let section_y = y >> 4;
+            // Local coordinates (`& 15` wraps negative Y into its section)
             let local_x = (x & 15) as usize;
             let local_y = (y & 15) as usize;
             let local_z = (z & 15) as usize;
             
-            // Ensure section exists
-            while chunk.sections.len() <= section_y as usize {
-                chunk.sections.push(ChunkSection::new(chunk.sections.len() as i32));
-            }
-            
-            // Set block
-            if let Some(section) = chunk.sections.get_mut(section_y as usize) {
+            // Set block, creating the section if needed
+            if let Some(section) = chunk.section_mut(self.height, y) {
                 section.set_block(local_x, local_y, local_z, block_id as u16);
             }
             
+            // Replacing a block drops its block entity
+            chunk.block_entities.remove(&(x, y, z));
+            
             // Mark for re-mesh
             chunk.dirty = true;
             chunk.meshed = false;
             
-            if !self.dirty_chunks.contains(&(chunk_x, chunk_z)) {
-                self.dirty_chunks.push((chunk_x, chunk_z));
-            }
+            self.dirty_chunks.push((chunk_x, chunk_z));
             
             log::trace!("Block set at ({}, {}, {}) = {}", x, y, z, block_id);
         }
@@ -222,12 +500,11 @@ impl WorldManager {
         let chunk_z = z >> 4;
         
         if let Some(chunk) = self.chunks.get(&(chunk_x, chunk_z)) {
-            let section_y = y >> 4;
             let local_x = (x & 15) as usize;
             let local_y = (y & 15) as usize;
             let local_z = (z & 15) as usize;
             
-            if let Some(section) = chunk.sections.get(section_y as usize) {
+            if let Some(section) = chunk.section(self.height, y) {
                 return section.get_block(local_x, local_y, local_z);
             }
         }
@@ -235,6 +512,87 @@ impl WorldManager {
         0 // Air
     }
     
+    /// Get biome ID at world coordinates (0 if not loaded)
+    pub fn get_biome(&self, x: i32, y: i32, z: i32) -> u16 {
+        self.chunks.get(&(x >> 4, z >> 4))
+            .and_then(|chunk| chunk.section(self.height, y))
+            .map(|section| section.get_biome((x & 15) as usize, (y & 15) as usize, (z & 15) as usize))
+            .unwrap_or(0)
+    }
+    
+    /// Set the tint color (0xRRGGBB) for a biome
+    pub fn set_biome_tint(&mut self, biome_id: u16, color: u32) {
+        self.biome_tints.set_tint(biome_id, color);
+    }
+    
+    /// Get the biome tint table (consulted by the mesher)
+    pub fn biome_tints(&self) -> &BiomeTintTable {
+        &self.biome_tints
+    }
+    
+    /// Get a block entity by world coordinates
+    pub fn get_block_entity(&self, x: i32, y: i32, z: i32) -> Option<&BlockEntity> {
+        self.chunks.get(&(x >> 4, z >> 4))?.block_entities.get(&(x, y, z))
+    }
+    
+    /// Set (or with `None`, remove) a block entity; returns false if the chunk is not loaded
+    pub fn set_block_entity(&mut self, x: i32, y: i32, z: i32, entity: Option<BlockEntity>) -> bool {
+        let key = (x >> 4, z >> 4);
+        let Some(chunk) = self.chunks.get_mut(&key) else {
+            return false;
+        };
+        
+        match entity {
+            Some(entity) => {
+                chunk.block_entities.insert((x, y, z), entity);
+            }
+            None => {
+                chunk.block_entities.remove(&(x, y, z));
+            }
+        }
+        
+        chunk.dirty = true;
+        chunk.meshed = false;
+        self.dirty_chunks.push(key);
+        
+        true
+    }
+    
+    /// Hash of all loaded blocks, light, biomes, block entities and tick state
+    pub fn state_hash(&self) -> u64 {
+        let mut keys: Vec<&(i32, i32)> = self.chunks.keys().collect();
+        keys.sort_unstable();
+        
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&self.current_tick().to_le_bytes());
+        bytes.extend_from_slice(&(self.scheduled_update_count() as u64).to_le_bytes());
+        
+        for key in keys {
+            let chunk = &self.chunks[key];
+            bytes.extend_from_slice(&chunk.x.to_le_bytes());
+            bytes.extend_from_slice(&chunk.z.to_le_bytes());
+            
+            for section in &chunk.sections {
+                bytes.extend_from_slice(&section.y.to_le_bytes());
+                bytes.extend(section.blocks.iter().flat_map(|b| b.to_le_bytes()));
+                bytes.extend(section.light.iter());
+                bytes.extend(section.biomes.iter().flat_map(|b| b.to_le_bytes()));
+            }
+            
+            let mut entities: Vec<_> = chunk.block_entities.iter().collect();
+            entities.sort_unstable_by_key(|(pos, _)| **pos);
+            for ((x, y, z), entity) in entities {
+                for c in [x, y, z] {
+                    bytes.extend_from_slice(&c.to_le_bytes());
+                }
+                bytes.extend_from_slice(entity.id.as_bytes());
+                bytes.extend_from_slice(&entity.nbt);
+            }
+        }
+        
+        crate::util::hash::fnv1a(&bytes)
+    }
+    
     /// Get chunk count
     pub fn chunk_count(&self) -> usize {
         self.chunks.len()
@@ -261,3 +619,257 @@ impl Default for WorldManager {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_entity_from_chunk_payload() {
+        let mut payload = ChunkPayload::default();
+        let mut section = ChunkSection::new(4);
+        section.set_block(3, 2, 5, 54);
+        payload.sections.push(section);
+        payload.block_entities.insert((35, 66, -27), BlockEntity {
+            id: "minecraft:chest".to_string(),
+            nbt: vec![10, 0, 0, 0],
+        });
+
+        let mut world = WorldManager::new();
+        world.submit_chunk(2, -2, &payload.encode());
+
+        assert_eq!(world.get_block(35, 66, -27), 54);
+        let entity = world.get_block_entity(35, 66, -27).unwrap();
+        assert_eq!(entity.id, "minecraft:chest");
+        assert_eq!(entity.nbt, vec![10, 0, 0, 0]);
+
+        // Block entities unload with their chunk
+        world.unload_chunk(2, -2);
+        assert!(world.get_block_entity(35, 66, -27).is_none());
+        assert!(!world.set_block_entity(35, 66, -27, None));
+    }
+
+    #[test]
+    fn test_biome_lookup_and_tint() {
+        let mut payload = ChunkPayload::default();
+        let mut section = ChunkSection::new(4);
+        // Cell (x 4..8, y 8..12, z 12..16) is a forest
+        section.biomes[biome::biome_index(5, 9, 13)] = 4;
+        payload.sections.push(section);
+
+        let mut world = WorldManager::new();
+        world.submit_chunk(1, 0, &payload.encode());
+        world.set_biome_tint(4, 0x59AE30);
+
+        let biome = world.get_biome(16 + 6, 64 + 10, 14);
+        assert_eq!(biome, 4);
+        assert_eq!(world.get_biome(16, 64, 0), 0);
+        assert_eq!(world.biome_tints().tint_for_block(2, biome), 0x59AE30);
+        assert_eq!(world.biome_tints().tint_for_block(1, biome), biome::DEFAULT_TINT);
+    }
+
+    #[test]
+    fn test_nearest_dirty_chunks_mesh_first() {
+        let mut world = WorldManager::new();
+        for x in (0..8).rev() {
+            world.submit_chunk(x * 2, 0, &[]);
+        }
+        world.set_camera_chunk(14, 0);
+        // Re-marking a queued chunk doesn't queue it twice
+        world.mark_chunk_dirty(0, 0);
+        world.set_block(14 * 16, 64, 0, 1);
+        assert_eq!(world.dirty_chunk_count(), 8);
+
+        let meshed = |world: &WorldManager| (0..8).filter(|x| world.get_chunk(x * 2, 0).unwrap().meshed).map(|x| x * 2).collect::<Vec<_>>();
+        world.tick();
+        assert_eq!(meshed(&world), vec![8, 10, 12, 14]);
+
+        // The camera moved to the other end before the rest were meshed
+        world.set_camera_chunk(0, 0);
+        world.mark_chunk_dirty(14, 0);
+        world.tick();
+        assert_eq!(meshed(&world), vec![0, 2, 4, 6, 8, 10, 12]);
+        world.tick();
+        assert_eq!(world.dirty_chunk_count(), 0);
+        assert_eq!(meshed(&world).len(), 8);
+    }
+
+    #[test]
+    fn test_tall_world_negative_and_high_y() {
+        assert_eq!(WorldManager::new().height(), WorldHeight::LEGACY);
+        let mut world = WorldManager::with_height(WorldHeight::OVERWORLD);
+        world.submit_chunk(-1, 0, &[]);
+        world.set_block(-5, -40, 3, 7);
+        world.set_block(-5, 300, 3, 9);
+        world.set_block(-5, 320, 3, 9);
+        world.set_block(-5, -65, 3, 9);
+        assert_eq!(world.get_block(-5, -40, 3), 7);
+        assert_eq!(world.get_block(-5, -39, 3), 0);
+        assert_eq!(world.get_block(-5, 300, 3), 9);
+        assert_eq!((world.get_block(-5, 320, 3), world.get_block(-5, -65, 3)), (0, 0));
+        // Sections run from Y -64 up to the highest one touched
+        assert_eq!(world.get_chunk(-1, 0).unwrap().sections.len(), (300 + 64) / 16 + 1);
+
+        // Payload sections land at their own Y; those outside the range are dropped
+        let mut payload = ChunkPayload::default();
+        for y in [-5, -3, 4, 19, 20] {
+            let mut section = ChunkSection::new(y);
+            section.set_block(0, 0, 0, 1);
+            payload.sections.push(section);
+        }
+        world.submit_chunk(0, 0, &payload.encode());
+        assert_eq!(world.get_block(0, -48, 0), 1);
+        assert_eq!(world.get_block(0, 64, 0), 1);
+        assert_eq!(world.get_block(0, 304, 0), 1);
+        assert_eq!(world.get_block(0, -80, 0), 0);
+
+        // Round trip through a save restores the height; a legacy height can't hold it
+        let mut bytes = Vec::new();
+        world.save(&mut bytes).unwrap();
+        let loaded = WorldManager::load(bytes.as_slice()).unwrap();
+        assert_eq!(loaded.height(), WorldHeight::OVERWORLD);
+        assert_eq!(loaded.get_block(-5, -40, 3), 7);
+        assert_eq!(loaded.get_block(0, 304, 0), 1);
+        assert!(matches!(
+            WorldManager::load_with_height(bytes.as_slice(), WorldHeight::LEGACY),
+            Err(SaveError::Corrupt(_))
+        ));
+        assert!(WorldHeight::new(-60, 320).is_err());
+    }
+
+    #[test]
+    fn test_bad_update_keeps_chunk() {
+        let mut world = WorldManager::new();
+        world.submit_chunk(0, 0, &[]);
+        world.set_block(3, 64, 3, 5);
+        world.tick();
+        assert_eq!(world.dirty_chunk_count(), 0);
+
+        // Claims one section but is cut off before its Y
+        assert!(world.update_chunk(0, 0, &[1, 0, 0]).is_err());
+        assert_eq!(world.get_block(3, 64, 3), 5);
+        assert_eq!(world.dirty_chunk_count(), 0);
+
+        assert!(world.update_chunk(0, 0, &[]).is_ok());
+        assert_eq!(world.get_block(3, 64, 3), 0);
+    }
+
+    #[test]
+    fn test_save_load_round_trip() {
+        let mut world = WorldManager::new();
+        world.submit_chunk(0, 0, &[]);
+        world.submit_chunk(-1, 3, &[]);
+        for x in 0..16 {
+            world.set_block(x, 64, 5, 1);
+        }
+        world.set_block(7, 10, 7, 54);
+        world.set_block(-3, 200, 50, 89);
+        world.set_block_entity(7, 10, 7, Some(BlockEntity {
+            id: "minecraft:chest".to_string(),
+            nbt: vec![10, 0, 0, 0],
+        }));
+
+        let mut bytes = Vec::new();
+        world.save(&mut bytes).unwrap();
+        let loaded = WorldManager::load(bytes.as_slice()).unwrap();
+
+        assert_eq!(loaded.chunk_count(), 2);
+        assert_eq!(loaded.dirty_chunk_count(), 2);
+        for (x, y, z) in [(0, 64, 5), (15, 64, 5), (7, 10, 7), (-3, 200, 50), (3, 65, 5), (-3, 199, 50)] {
+            assert_eq!(loaded.get_block(x, y, z), world.get_block(x, y, z));
+        }
+        assert_eq!(loaded.get_block_entity(7, 10, 7).unwrap().id, "minecraft:chest");
+
+        bytes[0] = b'X';
+        assert!(matches!(WorldManager::load(bytes.as_slice()), Err(SaveError::BadMagic)));
+    }
+
+    #[test]
+    fn test_scheduled_update_fires_after_delay() {
+        use std::sync::{Arc, Mutex};
+
+        let mut world = WorldManager::new();
+        world.set_random_tick_rate(0);
+        world.submit_chunk(0, 0, &[]);
+        world.set_block(1, 2, 3, 8);
+
+        let fired = Arc::new(Mutex::new(Vec::new()));
+        let log = fired.clone();
+        world.register_block_ticker(8, move |world, x, y, z, block| {
+            log.lock().unwrap().push((world.current_tick(), (x, y, z), block));
+        });
+
+        world.schedule_block_update(1, 2, 3, 3);
+        world.tick();
+        world.tick();
+        assert!(fired.lock().unwrap().is_empty());
+        assert_eq!(world.scheduled_update_count(), 1);
+
+        world.tick();
+        assert_eq!(*fired.lock().unwrap(), vec![(3, (1, 2, 3), 8)]);
+        assert_eq!(world.scheduled_update_count(), 0);
+
+        world.tick();
+        assert_eq!(fired.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_random_ticks_follow_seed_not_map_order() {
+        use std::sync::{Arc, Mutex};
+
+        let run = |order: &[(i32, i32)]| {
+            let mut world = WorldManager::new();
+            world.seed_random_ticks(7);
+            for &(x, z) in order {
+                world.submit_chunk(x, z, &[]);
+                for bx in 0..16 {
+                    for bz in 0..16 {
+                        world.set_block(x * 16 + bx, 0, z * 16 + bz, 2);
+                    }
+                }
+            }
+            let fired = Arc::new(Mutex::new(Vec::new()));
+            let log = fired.clone();
+            world.register_block_ticker(2, move |_, x, y, z, _| log.lock().unwrap().push((x, y, z)));
+            for _ in 0..4 {
+                world.tick();
+            }
+            let fired = fired.lock().unwrap().clone();
+            fired
+        };
+
+        let chunks = [(0, 0), (1, 0), (-1, 2), (3, -4), (0, 1)];
+        let mut reversed = chunks;
+        reversed.reverse();
+        let first = run(&chunks);
+        assert!(!first.is_empty());
+        assert_eq!(first, run(&reversed));
+    }
+
+    #[test]
+    fn test_section_counts_non_air_blocks() {
+        let mut section = ChunkSection::new(0);
+        assert!(section.is_empty());
+
+        section.set_block(0, 0, 0, 1);
+        section.set_block(15, 15, 15, 2);
+        // Solid over solid and air over air leave the count alone
+        section.set_block(0, 0, 0, 3);
+        section.set_block(5, 5, 5, 0);
+        assert_eq!(section.non_air_blocks(), 2);
+        assert!(!section.is_empty());
+
+        section.set_block(0, 0, 0, 0);
+        assert!(!section.is_empty());
+        section.set_block(15, 15, 15, 0);
+        assert!(section.is_empty());
+        assert_eq!(section.blocks().palette(), &[0]);
+
+        // Sections read from a payload start with the right count
+        section.set_block(1, 2, 3, 4);
+        let mut payload = ChunkPayload::default();
+        payload.sections.push(section);
+        let parsed = ChunkPayload::parse(&payload.encode()).unwrap();
+        assert_eq!(parsed.sections[0].non_air_blocks(), 1);
+    }
+}
@@ -0,0 +1,286 @@
+//! # Arena NBT Parsing
+//!
+//! Binary NBT (big-endian, already decompressed) parsed into a tree whose
+//! nodes, strings and arrays all live in a caller-supplied bump arena.
+//! A chunk's NBT is a few thousand small nodes; building them as separate
+//! `Vec`/`String`/`HashMap` allocations dominates world loading. With an
+//! arena the loader parses, pulls out what it needs, then `reset`s the
+//! arena and reuses the same memory for the next chunk.
+//!
+//! Compounds keep their entries in file order and are searched linearly,
+//! which is faster than hashing for the handful of keys a compound has.
+
+use std::collections::HashMap;
+
+use bumpalo::Bump;
+
+use super::assets::NbtTag;
+
+/// Nesting limit (same as the game's)
+pub const MAX_DEPTH: usize = 512;
+
+/// Tag IDs
+mod id {
+    pub const END: u8 = 0;
+    pub const BYTE: u8 = 1;
+    pub const SHORT: u8 = 2;
+    pub const INT: u8 = 3;
+    pub const LONG: u8 = 4;
+    pub const FLOAT: u8 = 5;
+    pub const DOUBLE: u8 = 6;
+    pub const BYTE_ARRAY: u8 = 7;
+    pub const STRING: u8 = 8;
+    pub const LIST: u8 = 9;
+    pub const COMPOUND: u8 = 10;
+    pub const INT_ARRAY: u8 = 11;
+    pub const LONG_ARRAY: u8 = 12;
+}
+
+/// NBT tag borrowed from an arena
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArenaTag<'a> {
+    End,
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(&'a [i8]),
+    String(&'a str),
+    List(&'a [ArenaTag<'a>]),
+    Compound(&'a [(&'a str, ArenaTag<'a>)]),
+    IntArray(&'a [i32]),
+    LongArray(&'a [i64]),
+}
+
+impl<'a> ArenaTag<'a> {
+    /// Look up a compound entry
+    pub fn get(&self, name: &str) -> Option<&ArenaTag<'a>> {
+        self.as_compound()?.iter().find(|(key, _)| *key == name).map(|(_, tag)| tag)
+    }
+
+    pub fn as_byte(&self) -> Option<i8> {
+        if let ArenaTag::Byte(v) = self { Some(*v) } else { None }
+    }
+    pub fn as_short(&self) -> Option<i16> {
+        if let ArenaTag::Short(v) = self { Some(*v) } else { None }
+    }
+    pub fn as_int(&self) -> Option<i32> {
+        if let ArenaTag::Int(v) = self { Some(*v) } else { None }
+    }
+    pub fn as_long(&self) -> Option<i64> {
+        if let ArenaTag::Long(v) = self { Some(*v) } else { None }
+    }
+    pub fn as_double(&self) -> Option<f64> {
+        if let ArenaTag::Double(v) = self { Some(*v) } else { None }
+    }
+    pub fn as_string(&self) -> Option<&'a str> {
+        if let ArenaTag::String(v) = self { Some(v) } else { None }
+    }
+    pub fn as_list(&self) -> Option<&'a [ArenaTag<'a>]> {
+        if let ArenaTag::List(v) = self { Some(v) } else { None }
+    }
+    pub fn as_compound(&self) -> Option<&'a [(&'a str, ArenaTag<'a>)]> {
+        if let ArenaTag::Compound(v) = self { Some(v) } else { None }
+    }
+    pub fn as_long_array(&self) -> Option<&'a [i64]> {
+        if let ArenaTag::LongArray(v) = self { Some(v) } else { None }
+    }
+
+    /// Copy into a heap-allocated tag that outlives the arena
+    pub fn to_owned_tag(&self) -> NbtTag {
+        match *self {
+            ArenaTag::End => NbtTag::End,
+            ArenaTag::Byte(v) => NbtTag::Byte(v),
+            ArenaTag::Short(v) => NbtTag::Short(v),
+            ArenaTag::Int(v) => NbtTag::Int(v),
+            ArenaTag::Long(v) => NbtTag::Long(v),
+            ArenaTag::Float(v) => NbtTag::Float(v),
+            ArenaTag::Double(v) => NbtTag::Double(v),
+            ArenaTag::ByteArray(v) => NbtTag::ByteArray(v.to_vec()),
+            ArenaTag::String(v) => NbtTag::String(v.to_string()),
+            ArenaTag::List(v) => NbtTag::List(v.iter().map(ArenaTag::to_owned_tag).collect()),
+            ArenaTag::Compound(v) => NbtTag::Compound(
+                v.iter().map(|(k, t)| (k.to_string(), t.to_owned_tag())).collect::<HashMap<_, _>>(),
+            ),
+            ArenaTag::IntArray(v) => NbtTag::IntArray(v.to_vec()),
+            ArenaTag::LongArray(v) => NbtTag::LongArray(v.to_vec()),
+        }
+    }
+}
+
+/// Parse a named root tag into `arena`, returning its name and value
+pub fn parse_into_arena<'a>(bytes: &[u8], arena: &'a Bump) -> Result<(&'a str, ArenaTag<'a>), String> {
+    let mut reader = Reader { bytes, pos: 0, arena };
+    let tag_id = reader.u8()?;
+    if tag_id == id::END {
+        return Ok(("", ArenaTag::End));
+    }
+    let name = reader.string()?;
+    let tag = reader.payload(tag_id, 0)?;
+    Ok((name, tag))
+}
+
+/// Big-endian cursor allocating into the arena
+struct Reader<'b, 'a> {
+    bytes: &'b [u8],
+    pos: usize,
+    arena: &'a Bump,
+}
+
+impl<'a> Reader<'_, 'a> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        let end = self.pos + N;
+        let slice = self.bytes.get(self.pos..end).ok_or("unexpected end of NBT data")?;
+        self.pos = end;
+        Ok(slice.try_into().expect("slice length is N"))
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take::<1>()?[0])
+    }
+
+    /// Array or list length (negative means empty)
+    fn len(&mut self) -> Result<usize, String> {
+        let len = i32::from_be_bytes(self.take()?).max(0) as usize;
+        // Every element is at least a byte; reject lengths the data can't hold
+        if len > self.bytes.len() - self.pos {
+            return Err(format!("NBT length {} exceeds remaining data", len));
+        }
+        Ok(len)
+    }
+
+    fn string(&mut self) -> Result<&'a str, String> {
+        let len = u16::from_be_bytes(self.take()?) as usize;
+        let raw = self.bytes.get(self.pos..self.pos + len).ok_or("unexpected end of NBT data")?;
+        self.pos += len;
+        // Modified UTF-8 only differs for NUL and supplementary characters
+        Ok(match std::str::from_utf8(raw) {
+            Ok(s) => self.arena.alloc_str(s),
+            Err(_) => self.arena.alloc_str(&String::from_utf8_lossy(raw)),
+        })
+    }
+
+    fn payload(&mut self, tag_id: u8, depth: usize) -> Result<ArenaTag<'a>, String> {
+        if depth > MAX_DEPTH {
+            return Err("NBT nested too deeply".to_string());
+        }
+        Ok(match tag_id {
+            id::END => ArenaTag::End,
+            id::BYTE => ArenaTag::Byte(self.u8()? as i8),
+            id::SHORT => ArenaTag::Short(i16::from_be_bytes(self.take()?)),
+            id::INT => ArenaTag::Int(i32::from_be_bytes(self.take()?)),
+            id::LONG => ArenaTag::Long(i64::from_be_bytes(self.take()?)),
+            id::FLOAT => ArenaTag::Float(f32::from_be_bytes(self.take()?)),
+            id::DOUBLE => ArenaTag::Double(f64::from_be_bytes(self.take()?)),
+            id::BYTE_ARRAY => {
+                let len = self.len()?;
+                let raw = &self.bytes[self.pos..self.pos + len];
+                self.pos += len;
+                ArenaTag::ByteArray(self.arena.alloc_slice_fill_iter(raw.iter().map(|&b| b as i8)))
+            }
+            id::STRING => ArenaTag::String(self.string()?),
+            id::LIST => {
+                let element = self.u8()?;
+                let len = self.len()?;
+                let mut items = bumpalo::collections::Vec::with_capacity_in(len, self.arena);
+                for _ in 0..len {
+                    items.push(self.payload(element, depth + 1)?);
+                }
+                ArenaTag::List(items.into_bump_slice())
+            }
+            id::COMPOUND => {
+                let mut entries = bumpalo::collections::Vec::new_in(self.arena);
+                loop {
+                    let child = self.u8()?;
+                    if child == id::END {
+                        break;
+                    }
+                    let name = self.string()?;
+                    entries.push((name, self.payload(child, depth + 1)?));
+                }
+                ArenaTag::Compound(entries.into_bump_slice())
+            }
+            id::INT_ARRAY => {
+                let len = self.len()?;
+                let mut values = bumpalo::collections::Vec::with_capacity_in(len, self.arena);
+                for _ in 0..len {
+                    values.push(i32::from_be_bytes(self.take()?));
+                }
+                ArenaTag::IntArray(values.into_bump_slice())
+            }
+            id::LONG_ARRAY => {
+                let len = self.len()?;
+                let mut values = bumpalo::collections::Vec::with_capacity_in(len, self.arena);
+                for _ in 0..len {
+                    values.push(i64::from_be_bytes(self.take()?));
+                }
+                ArenaTag::LongArray(values.into_bump_slice())
+            }
+            other => return Err(format!("unknown NBT tag id {}", other)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Append a named tag header
+    fn named(out: &mut Vec<u8>, tag: u8, name: &str) {
+        out.push(tag);
+        out.extend_from_slice(&(name.len() as u16).to_be_bytes());
+        out.extend_from_slice(name.as_bytes());
+    }
+
+    #[test]
+    fn test_parse_chunk_nbt_and_reset_arena() {
+        // { DataVersion: 3700, xPos: -2, Status: "full",
+        //   sections: [ { Y: 4b, data: [1L, -1L] } ] }
+        let mut blob = Vec::new();
+        named(&mut blob, 10, "");
+        named(&mut blob, 3, "DataVersion");
+        blob.extend_from_slice(&3700i32.to_be_bytes());
+        named(&mut blob, 3, "xPos");
+        blob.extend_from_slice(&(-2i32).to_be_bytes());
+        named(&mut blob, 8, "Status");
+        blob.extend_from_slice(&4u16.to_be_bytes());
+        blob.extend_from_slice(b"full");
+        named(&mut blob, 9, "sections");
+        blob.push(10);
+        blob.extend_from_slice(&1i32.to_be_bytes());
+        named(&mut blob, 1, "Y");
+        blob.push(4);
+        named(&mut blob, 12, "data");
+        blob.extend_from_slice(&2i32.to_be_bytes());
+        blob.extend_from_slice(&1i64.to_be_bytes());
+        blob.extend_from_slice(&(-1i64).to_be_bytes());
+        blob.push(0); // end of section
+        blob.push(0); // end of root
+
+        let mut arena = Bump::new();
+        {
+            let (name, root) = parse_into_arena(&blob, &arena).unwrap();
+            assert_eq!(name, "");
+            assert_eq!(root.get("DataVersion").and_then(ArenaTag::as_int), Some(3700));
+            assert_eq!(root.get("xPos").and_then(ArenaTag::as_int), Some(-2));
+            assert_eq!(root.get("Status").and_then(ArenaTag::as_string), Some("full"));
+            let section = &root.get("sections").and_then(ArenaTag::as_list).unwrap()[0];
+            assert_eq!(section.get("Y").and_then(ArenaTag::as_byte), Some(4));
+            assert_eq!(section.get("data").and_then(ArenaTag::as_long_array), Some(&[1i64, -1][..]));
+
+            let owned = root.to_owned_tag();
+            assert_eq!(owned.as_compound().unwrap()["Status"].as_string(), Some("full"));
+        }
+        assert!(arena.allocated_bytes() > 0);
+
+        // The chunk's whole tree goes at once; the arena keeps one chunk for reuse
+        arena.reset();
+        let (_, root) = parse_into_arena(&blob, &arena).unwrap();
+        assert_eq!(root.get("DataVersion").and_then(ArenaTag::as_int), Some(3700));
+
+        assert!(parse_into_arena(&blob[..blob.len() - 3], &arena).is_err());
+        assert!(parse_into_arena(&[10, 0, 0, 99], &arena).is_err());
+    }
+}
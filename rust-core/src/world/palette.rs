@@ -0,0 +1,226 @@
+//! # Paletted Storage
+//!
+//! Compact per-section storage in the style of Anvil: a palette of the
+//! distinct values present and a bit-packed index per cell, using
+//! `ceil(log2(palette_len))` bits. Indices never straddle a `u64` word,
+//! so a word holds `64 / bits` of them. A section holding a single value
+//! (all air, all unlit) keeps one palette entry and no index words at all.
+//!
+//! Setting a value missing from a full palette repacks the whole array,
+//! which also drops palette entries no cell uses any more.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Fixed-length array of values stored as palette indices
+#[derive(Debug, Clone)]
+pub struct PalettedStorage<T> {
+    /// Number of cells
+    len: usize,
+    /// Distinct values (may hold stale entries until the next repack)
+    palette: Vec<T>,
+    /// Bits per index (0 when the palette has one entry)
+    bits: u32,
+    /// Packed indices
+    words: Vec<u64>,
+}
+
+/// Bits needed to index a palette of `len` entries
+fn bits_for(len: usize) -> u32 {
+    if len <= 1 { 0 } else { usize::BITS - (len - 1).leading_zeros() }
+}
+
+impl<T: Copy + Eq + Hash + Default> PalettedStorage<T> {
+    /// Create storage with every cell set to the default value
+    pub fn new(len: usize) -> Self {
+        Self::filled(len, T::default())
+    }
+
+    /// Create storage with every cell set to `value`
+    pub fn filled(len: usize, value: T) -> Self {
+        Self { len, palette: vec![value], bits: 0, words: Vec::new() }
+    }
+
+    /// Pack a slice of values
+    pub fn from_slice(values: &[T]) -> Self {
+        let mut palette = Vec::new();
+        let mut lookup: HashMap<T, u64> = HashMap::new();
+        let indices: Vec<u64> = values
+            .iter()
+            .map(|&value| {
+                *lookup.entry(value).or_insert_with(|| {
+                    palette.push(value);
+                    (palette.len() - 1) as u64
+                })
+            })
+            .collect();
+        if palette.is_empty() {
+            palette.push(T::default());
+        }
+
+        let bits = bits_for(palette.len());
+        let mut storage = Self { len: values.len(), palette, bits, words: Vec::new() };
+        if bits > 0 {
+            let per_word = storage.per_word();
+            storage.words = vec![0; values.len().div_ceil(per_word)];
+            for (i, index) in indices.into_iter().enumerate() {
+                storage.words[i / per_word] |= index << ((i % per_word) as u32 * bits);
+            }
+        }
+        storage
+    }
+
+    /// Get the number of cells
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if there are no cells
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get the value at `index` (default when out of range)
+    pub fn get(&self, index: usize) -> T {
+        if index >= self.len {
+            return T::default();
+        }
+        self.palette[self.palette_index(index)]
+    }
+
+    /// Set the value at `index` (ignored when out of range)
+    pub fn set(&mut self, index: usize, value: T) {
+        if index >= self.len {
+            return;
+        }
+        let palette_index = match self.palette.iter().position(|&v| v == value) {
+            Some(i) => i,
+            None if self.palette.len() < 1 << self.bits => {
+                self.palette.push(value);
+                self.palette.len() - 1
+            }
+            None => {
+                // Palette is full: repack with the new value in place
+                let mut values = self.to_vec();
+                values[index] = value;
+                *self = Self::from_slice(&values);
+                return;
+            }
+        };
+
+        if self.bits > 0 {
+            let per_word = self.per_word();
+            let shift = (index % per_word) as u32 * self.bits;
+            let mask = ((1u64 << self.bits) - 1) << shift;
+            let word = &mut self.words[index / per_word];
+            *word = (*word & !mask) | ((palette_index as u64) << shift);
+        }
+    }
+
+    /// Drop unused palette entries and shrink the index width
+    pub fn compact(&mut self) {
+        *self = Self::from_slice(&self.to_vec());
+    }
+
+    /// Get the single value every cell holds, if the palette has one entry
+    pub fn uniform(&self) -> Option<T> {
+        (self.palette.len() == 1).then(|| self.palette[0])
+    }
+
+    /// Get the palette
+    pub fn palette(&self) -> &[T] {
+        &self.palette
+    }
+
+    /// Get bits per index
+    pub fn bits_per_entry(&self) -> u32 {
+        self.bits
+    }
+
+    /// Iterate all values in order
+    pub fn iter(&self) -> impl Iterator<Item = T> + '_ {
+        (0..self.len).map(|i| self.palette[self.palette_index(i)])
+    }
+
+    /// Unpack into a flat vector
+    pub fn to_vec(&self) -> Vec<T> {
+        self.iter().collect()
+    }
+
+    /// Heap bytes used by the palette and indices
+    pub fn heap_bytes(&self) -> usize {
+        self.palette.capacity() * std::mem::size_of::<T>() + self.words.capacity() * std::mem::size_of::<u64>()
+    }
+
+    /// Indices per word
+    fn per_word(&self) -> usize {
+        (64 / self.bits) as usize
+    }
+
+    /// Read the palette index of a cell
+    fn palette_index(&self, index: usize) -> usize {
+        if self.bits == 0 {
+            return 0;
+        }
+        let per_word = self.per_word();
+        let shift = (index % per_word) as u32 * self.bits;
+        ((self.words[index / per_word] >> shift) & ((1u64 << self.bits) - 1)) as usize
+    }
+}
+
+impl<T: Copy + Eq + Hash + Default> PartialEq for PalettedStorage<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::ChunkSection;
+    use super::*;
+
+    #[test]
+    fn test_two_block_section_packs_far_below_flat_size() {
+        let mut section = ChunkSection::new(0);
+        let air_bytes = section.memory_bytes();
+
+        // Checkerboard of stone and dirt
+        for y in 0..16 {
+            for z in 0..16 {
+                for x in 0..16 {
+                    section.set_block(x, y, z, if (x + y + z) % 2 == 0 { 1 } else { 3 });
+                }
+            }
+        }
+        assert_eq!(section.get_block(4, 7, 9), 1);
+        assert_eq!(section.get_block(4, 8, 9), 3);
+        // Air, stone and dirt were live at once while filling: 2-bit indices
+        // (1KB) plus a small palette, versus 8KB of u16s
+        assert_eq!(section.blocks().bits_per_entry(), 2);
+        assert!(section.memory_bytes() < 2048, "{} bytes", section.memory_bytes());
+        let mut packed = section.blocks().clone();
+        packed.compact();
+        assert_eq!(packed.bits_per_entry(), 1);
+        assert_eq!(packed, *section.blocks());
+
+        // Many distinct IDs widen the indices without losing values
+        let values: Vec<u16> = (0..4096).map(|i| (i % 300) as u16).collect();
+        let mut wide = PalettedStorage::from_slice(&values);
+        assert_eq!(wide.bits_per_entry(), 9);
+        assert_eq!(wide.to_vec(), values);
+        wide.set(17, 5000);
+        assert_eq!(wide.get(17), 5000);
+        assert_eq!(wide.get(18), 18);
+
+        // Clearing every block collapses back to a single air entry
+        for y in 0..16 {
+            for z in 0..16 {
+                for x in 0..16 {
+                    section.set_block(x, y, z, 0);
+                }
+            }
+        }
+        assert!(section.is_empty());
+        assert_eq!(section.memory_bytes(), air_bytes);
+    }
+}
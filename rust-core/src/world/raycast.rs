@@ -0,0 +1,141 @@
+//! # Block Raycast
+//!
+//! Finds the block a ray hits first, for block picking (breaking, placing)
+//! and line-of-sight checks. The ray walks the voxel grid cell by cell with
+//! a DDA (Amanatides & Woo), so the cost grows with distance rather than
+//! with the number of loaded blocks, and no cell along the ray is skipped.
+
+use glam::{IVec3, Vec3};
+
+use super::visibility::Face;
+use super::WorldManager;
+
+/// First block along a ray
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlockHit {
+    /// Hit block coordinates
+    pub block: IVec3,
+    /// Face the ray entered through
+    pub face: Face,
+    /// Distance from the origin to the hit point
+    pub distance: f32,
+    /// Hit point on the face
+    pub point: Vec3,
+    /// Block ID
+    pub block_id: u16,
+}
+
+impl BlockHit {
+    /// Coordinates a block placed against the hit face goes to
+    pub fn place_position(&self) -> IVec3 {
+        let (dx, dy, dz) = self.face.offset();
+        self.block + IVec3::new(dx, dy, dz)
+    }
+}
+
+/// Check if a ray stops at a block (air and fluids are passed through)
+pub fn is_pickable_block(block: u16) -> bool {
+    !matches!(block, 0 | 8..=11)
+}
+
+impl WorldManager {
+    /// Find the first pickable block within `max_dist` along a ray
+    ///
+    /// A ray starting inside a block hits it at distance 0, on the face
+    /// pointing back along the ray's main axis.
+    pub fn raycast(&self, origin: Vec3, dir: Vec3, max_dist: f32) -> Option<BlockHit> {
+        let dir = dir.try_normalize()?;
+        let mut cell = origin.floor().as_ivec3();
+
+        // Faces entered when stepping along each axis in the ray direction
+        let entry = [
+            if dir.x > 0.0 { Face::West } else { Face::East },
+            if dir.y > 0.0 { Face::Down } else { Face::Up },
+            if dir.z > 0.0 { Face::North } else { Face::South },
+        ];
+
+        let block_id = self.get_block(cell.x, cell.y, cell.z);
+        if is_pickable_block(block_id) {
+            let axis = largest_axis(dir.abs());
+            return Some(BlockHit { block: cell, face: entry[axis], distance: 0.0, point: origin, block_id });
+        }
+
+        let step = IVec3::from_array(dir.to_array().map(|d| if d > 0.0 { 1 } else if d < 0.0 { -1 } else { 0 }));
+        let t_delta = dir.recip().abs();
+        let mut t_max = Vec3::ZERO;
+        for axis in 0..3 {
+            t_max[axis] = if dir[axis] > 0.0 {
+                (cell[axis] as f32 + 1.0 - origin[axis]) / dir[axis]
+            } else if dir[axis] < 0.0 {
+                (origin[axis] - cell[axis] as f32) / -dir[axis]
+            } else {
+                f32::INFINITY
+            };
+        }
+
+        loop {
+            let axis = largest_axis(-t_max);
+            let distance = t_max[axis];
+            if distance > max_dist {
+                return None;
+            }
+            cell[axis] += step[axis];
+            t_max[axis] += t_delta[axis];
+
+            let block_id = self.get_block(cell.x, cell.y, cell.z);
+            if is_pickable_block(block_id) {
+                return Some(BlockHit {
+                    block: cell,
+                    face: entry[axis],
+                    distance,
+                    point: origin + dir * distance,
+                    block_id,
+                });
+            }
+        }
+    }
+}
+
+/// Index of the largest component (first on ties)
+fn largest_axis(v: Vec3) -> usize {
+    if v.x >= v.y && v.x >= v.z {
+        0
+    } else if v.y >= v.z {
+        1
+    } else {
+        2
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_raycast_hits_block_face() {
+        let mut world = WorldManager::new();
+        world.submit_chunk(0, 0, &[]);
+        world.set_block(5, 64, 3, 1);
+
+        // Looking east along the block's row hits its west face
+        let origin = Vec3::new(0.5, 64.5, 3.5);
+        let hit = world.raycast(origin, Vec3::X, 10.0).unwrap();
+        assert_eq!(hit.block, IVec3::new(5, 64, 3));
+        assert_eq!(hit.face, Face::West);
+        assert!((hit.distance - 4.5).abs() < 1e-5);
+        assert_eq!(hit.place_position(), IVec3::new(4, 64, 3));
+        assert_eq!(hit.block_id, 1);
+
+        // Looking down onto it from above hits the top face
+        let hit = world.raycast(Vec3::new(5.2, 70.0, 3.7), Vec3::new(0.0, -1.0, 0.0), 10.0).unwrap();
+        assert_eq!(hit.block, IVec3::new(5, 64, 3));
+        assert_eq!(hit.face, Face::Up);
+        assert!((hit.distance - 5.0).abs() < 1e-5);
+
+        // Diagonal ray still finds it; out of reach or looking away misses
+        let hit = world.raycast(Vec3::new(2.5, 67.0, 3.5), Vec3::new(1.0, -1.0, 0.0), 10.0).unwrap();
+        assert_eq!(hit.block, IVec3::new(5, 64, 3));
+        assert!(world.raycast(origin, Vec3::X, 4.0).is_none());
+        assert!(world.raycast(origin, Vec3::NEG_X, 10.0).is_none());
+    }
+}
@@ -0,0 +1,246 @@
+//! # World Save Format
+//!
+//! Compact snapshot of loaded chunks for fast save/restore.
+//! A magic + version + world height header is followed by a bincode body where each
+//! section stores a block palette with run-length encoded indices, and
+//! run-length encoded light and biome arrays.
+
+use std::collections::HashMap;
+use std::io::{Read, Write};
+
+use serde::{Deserialize, Serialize};
+
+use super::biome::BIOMES_PER_SECTION;
+use super::{BlockEntity, ChunkData, ChunkSection, PalettedStorage, WorldHeight, WorldManager};
+
+/// File magic
+const SAVE_MAGIC: [u8; 4] = *b"LWSV";
+
+/// Current save format version
+pub const SAVE_VERSION: u32 = 2;
+
+/// Blocks per section
+const SECTION_VOLUME: usize = 4096;
+
+/// Save/load errors
+#[derive(Debug)]
+pub enum SaveError {
+    Io(std::io::Error),
+    BadMagic,
+    UnsupportedVersion(u32),
+    Corrupt(String),
+}
+
+impl std::fmt::Display for SaveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "I/O error: {}", e),
+            Self::BadMagic => write!(f, "Not a world save"),
+            Self::UnsupportedVersion(v) => write!(f, "Unsupported save version {} (expected {})", v, SAVE_VERSION),
+            Self::Corrupt(e) => write!(f, "Corrupt save: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SaveError {}
+
+impl From<std::io::Error> for SaveError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// Saved world body
+#[derive(Serialize, Deserialize)]
+struct SavedWorld {
+    chunks: Vec<SavedChunk>,
+}
+
+/// Saved chunk column
+#[derive(Serialize, Deserialize)]
+struct SavedChunk {
+    x: i32,
+    z: i32,
+    sections: Vec<SavedSection>,
+    block_entities: Vec<SavedBlockEntity>,
+}
+
+/// Saved block entity
+#[derive(Serialize, Deserialize)]
+struct SavedBlockEntity {
+    pos: (i32, i32, i32),
+    id: String,
+    nbt: Vec<u8>,
+}
+
+/// Saved section (palette + runs)
+#[derive(Serialize, Deserialize)]
+struct SavedSection {
+    y: i32,
+    palette: Vec<u16>,
+    block_runs: Vec<(u16, u16)>,
+    light_runs: Vec<(u16, u8)>,
+    biome_runs: Vec<(u16, u16)>,
+}
+
+/// Run-length encode as (count, value) pairs
+fn rle_encode<T: Copy + PartialEq>(values: &[T]) -> Vec<(u16, T)> {
+    let mut runs: Vec<(u16, T)> = Vec::new();
+    for &value in values {
+        match runs.last_mut() {
+            Some((count, last)) if *last == value && *count < u16::MAX => *count += 1,
+            _ => runs.push((1, value)),
+        }
+    }
+    runs
+}
+
+/// Expand (count, value) runs, checking the decoded length
+fn rle_decode<T: Copy>(runs: &[(u16, T)], expected: usize) -> Result<Vec<T>, SaveError> {
+    let mut values = Vec::with_capacity(expected);
+    for &(count, value) in runs {
+        values.extend(std::iter::repeat_n(value, count as usize));
+    }
+    if values.len() != expected {
+        return Err(SaveError::Corrupt(format!("run length {} != {}", values.len(), expected)));
+    }
+    Ok(values)
+}
+
+impl SavedSection {
+    fn from_section(section: &ChunkSection) -> Self {
+        let mut palette: Vec<u16> = Vec::new();
+        let mut lookup: HashMap<u16, u16> = HashMap::new();
+        let indices: Vec<u16> = section.blocks.iter()
+            .map(|block| {
+                *lookup.entry(block).or_insert_with(|| {
+                    palette.push(block);
+                    (palette.len() - 1) as u16
+                })
+            })
+            .collect();
+
+        Self {
+            y: section.y,
+            palette,
+            block_runs: rle_encode(&indices),
+            light_runs: rle_encode(&section.light.to_vec()),
+            biome_runs: rle_encode(&section.biomes),
+        }
+    }
+
+    fn into_section(self) -> Result<ChunkSection, SaveError> {
+        let blocks = rle_decode(&self.block_runs, SECTION_VOLUME)?
+            .into_iter()
+            .map(|index| self.palette.get(index as usize).copied()
+                .ok_or_else(|| SaveError::Corrupt(format!("palette index {} out of range", index))))
+            .collect::<Result<Vec<u16>, SaveError>>()?;
+        let non_air = ChunkSection::count_non_air(&blocks);
+
+        Ok(ChunkSection {
+            y: self.y,
+            blocks: PalettedStorage::from_slice(&blocks),
+            light: PalettedStorage::from_slice(&rle_decode(&self.light_runs, SECTION_VOLUME)?),
+            biomes: rle_decode(&self.biome_runs, BIOMES_PER_SECTION)?,
+            non_air,
+        })
+    }
+}
+
+impl WorldManager {
+    /// Write all loaded chunks to a compact snapshot
+    pub fn save<W: Write>(&self, mut writer: W) -> Result<(), SaveError> {
+        let chunks = self.chunks.values()
+            .map(|chunk| SavedChunk {
+                x: chunk.x,
+                z: chunk.z,
+                sections: chunk.sections.iter().map(SavedSection::from_section).collect(),
+                block_entities: chunk.block_entities.iter()
+                    .map(|(&pos, entity)| SavedBlockEntity {
+                        pos,
+                        id: entity.id.clone(),
+                        nbt: entity.nbt.clone(),
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        writer.write_all(&SAVE_MAGIC)?;
+        writer.write_all(&SAVE_VERSION.to_le_bytes())?;
+        writer.write_all(&self.height.min_y.to_le_bytes())?;
+        writer.write_all(&self.height.max_y.to_le_bytes())?;
+        bincode::serialize_into(&mut writer, &SavedWorld { chunks })
+            .map_err(|e| SaveError::Corrupt(e.to_string()))?;
+        writer.flush()?;
+
+        log::debug!("World saved ({} chunks)", self.chunks.len());
+        Ok(())
+    }
+
+    /// Restore a world from a snapshot at its saved height (all chunks start dirty for re-meshing)
+    pub fn load<R: Read>(reader: R) -> Result<WorldManager, SaveError> {
+        Self::load_impl(reader, None)
+    }
+
+    /// Restore a world into a custom height range; fails if a saved section falls outside it
+    pub fn load_with_height<R: Read>(reader: R, height: WorldHeight) -> Result<WorldManager, SaveError> {
+        Self::load_impl(reader, Some(height))
+    }
+
+    fn load_impl<R: Read>(mut reader: R, height: Option<WorldHeight>) -> Result<WorldManager, SaveError> {
+        let mut header = [0u8; 16];
+        reader.read_exact(&mut header)?;
+        if header[..4] != SAVE_MAGIC {
+            return Err(SaveError::BadMagic);
+        }
+        let le32 = |at: usize| [header[at], header[at + 1], header[at + 2], header[at + 3]];
+        let version = u32::from_le_bytes(le32(4));
+        if version != SAVE_VERSION {
+            return Err(SaveError::UnsupportedVersion(version));
+        }
+        let saved_height = WorldHeight::new(i32::from_le_bytes(le32(8)), i32::from_le_bytes(le32(12)))
+            .map_err(SaveError::Corrupt)?;
+        let height = height.unwrap_or(saved_height);
+
+        let saved: SavedWorld = bincode::deserialize_from(reader)
+            .map_err(|e| SaveError::Corrupt(e.to_string()))?;
+
+        let mut world = WorldManager::with_height(height);
+        for saved_chunk in saved.chunks {
+            let handle = super::NEXT_CHUNK_HANDLE.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            let sections = saved_chunk.sections.into_iter()
+                .map(SavedSection::into_section)
+                .collect::<Result<Vec<_>, _>>()?;
+            let block_entities = saved_chunk.block_entities.into_iter()
+                .map(|e| (e.pos, BlockEntity { id: e.id, nbt: e.nbt }))
+                .collect();
+
+            let key = (saved_chunk.x, saved_chunk.z);
+            let mut chunk = ChunkData {
+                handle,
+                x: saved_chunk.x,
+                z: saved_chunk.z,
+                sections: Vec::new(),
+                meshed: false,
+                dirty: true,
+                raw_data: Vec::new(),
+                block_entities,
+            };
+            for section in sections {
+                let y = section.y;
+                if !chunk.place_section(height, section) {
+                    return Err(SaveError::Corrupt(format!(
+                        "chunk ({}, {}) section {} outside world height {}..{}",
+                        chunk.x, chunk.z, y, height.min_y, height.max_y
+                    )));
+                }
+            }
+            world.chunks.insert(key, chunk);
+            world.chunk_handles.insert(handle, key);
+            world.dirty_chunks.push(key);
+        }
+
+        log::debug!("World loaded ({} chunks)", world.chunks.len());
+        Ok(world)
+    }
+}
@@ -0,0 +1,154 @@
+//! # Block Ticks
+//!
+//! Scheduled block updates (fluids, redstone) and random ticks (crops,
+//! leaf decay). Both dispatch to tickers registered per block ID.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::sync::Arc;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use super::WorldManager;
+
+/// Block tick callback (world, x, y, z, block ID)
+pub type BlockTickFn = Arc<dyn Fn(&mut WorldManager, i32, i32, i32, u16) + Send + Sync>;
+
+/// Default random ticks per section per tick (vanilla `randomTickSpeed`)
+pub const DEFAULT_RANDOM_TICK_RATE: u32 = 3;
+
+/// Scheduled update (fire tick, insertion order, position)
+type ScheduledUpdate = Reverse<(u64, u64, (i32, i32, i32))>;
+
+/// Block tick state
+pub struct BlockTicks {
+    /// Ticks elapsed
+    current_tick: u64,
+    /// Pending scheduled updates (earliest first)
+    scheduled: BinaryHeap<ScheduledUpdate>,
+    /// Insertion counter for stable ordering
+    next_sequence: u64,
+    /// Tickers by block ID
+    tickers: HashMap<u16, BlockTickFn>,
+    /// Random ticks per section per tick
+    random_tick_rate: u32,
+    /// Random tick position source
+    rng: StdRng,
+}
+
+impl BlockTicks {
+    /// Create a new tick state
+    pub fn new() -> Self {
+        Self {
+            current_tick: 0,
+            scheduled: BinaryHeap::new(),
+            next_sequence: 0,
+            tickers: HashMap::new(),
+            random_tick_rate: DEFAULT_RANDOM_TICK_RATE,
+            rng: StdRng::from_entropy(),
+        }
+    }
+}
+
+impl Default for BlockTicks {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WorldManager {
+    /// Register the tick handler for a block ID
+    pub fn register_block_ticker<F>(&mut self, block_id: u16, ticker: F)
+    where
+        F: Fn(&mut WorldManager, i32, i32, i32, u16) + Send + Sync + 'static,
+    {
+        self.ticks.tickers.insert(block_id, Arc::new(ticker));
+    }
+
+    /// Schedule a block update `delay_ticks` ticks from now
+    pub fn schedule_block_update(&mut self, x: i32, y: i32, z: i32, delay_ticks: u32) {
+        let ticks = &mut self.ticks;
+        let fire_at = ticks.current_tick + delay_ticks.max(1) as u64;
+        ticks.scheduled.push(Reverse((fire_at, ticks.next_sequence, (x, y, z))));
+        ticks.next_sequence += 1;
+    }
+
+    /// Set random ticks per section per tick (0 disables random ticking)
+    pub fn set_random_tick_rate(&mut self, rate: u32) {
+        self.ticks.random_tick_rate = rate;
+    }
+
+    /// Get random ticks per section per tick
+    pub fn random_tick_rate(&self) -> u32 {
+        self.ticks.random_tick_rate
+    }
+
+    /// Seed the random tick generator (deterministic replays)
+    pub fn seed_random_ticks(&mut self, seed: u64) {
+        self.ticks.rng = StdRng::seed_from_u64(seed);
+    }
+
+    /// Get ticks elapsed
+    pub fn current_tick(&self) -> u64 {
+        self.ticks.current_tick
+    }
+
+    /// Get pending scheduled update count
+    pub fn scheduled_update_count(&self) -> usize {
+        self.ticks.scheduled.len()
+    }
+
+    /// Advance the tick counter and fire due scheduled updates, then random ticks
+    pub(super) fn process_block_ticks(&mut self) {
+        self.ticks.current_tick += 1;
+        let now = self.ticks.current_tick;
+
+        let mut due = Vec::new();
+        while let Some(Reverse((fire_at, _, pos))) = self.ticks.scheduled.peek().copied() {
+            if fire_at > now {
+                break;
+            }
+            self.ticks.scheduled.pop();
+            due.push(pos);
+        }
+
+        let mut fired = due;
+        if self.ticks.random_tick_rate > 0 && !self.ticks.tickers.is_empty() {
+            fired.extend(self.pick_random_ticks());
+        }
+
+        for (x, y, z) in fired {
+            let block = self.get_block(x, y, z);
+            if let Some(ticker) = self.ticks.tickers.get(&block).cloned() {
+                ticker(self, x, y, z, block);
+            }
+        }
+    }
+
+    /// Pick random block positions in every non-empty loaded section
+    ///
+    /// Chunks are visited in key order so the same seed picks the same
+    /// blocks whatever the map's iteration order.
+    fn pick_random_ticks(&mut self) -> Vec<(i32, i32, i32)> {
+        let rate = self.ticks.random_tick_rate;
+        let rng = &mut self.ticks.rng;
+        let mut picks = Vec::new();
+
+        let mut keys: Vec<&(i32, i32)> = self.chunks.keys().collect();
+        keys.sort_unstable();
+        for chunk in keys.into_iter().map(|key| &self.chunks[key]) {
+            for section in chunk.sections.iter().filter(|s| !s.is_empty()) {
+                for _ in 0..rate {
+                    let index = rng.gen_range(0..4096usize);
+                    let x = chunk.x * 16 + (index & 15) as i32;
+                    let z = chunk.z * 16 + ((index >> 4) & 15) as i32;
+                    let y = section.y * 16 + (index >> 8) as i32;
+                    picks.push((x, y, z));
+                }
+            }
+        }
+
+        picks
+    }
+}
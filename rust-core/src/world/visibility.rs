@@ -0,0 +1,316 @@
+//! # Section Visibility Graph
+//!
+//! Region-connectivity culling for caves and interiors ("advanced culling").
+//! Each 16³ section records which of its six faces are connected through
+//! non-opaque blocks. A breadth-first walk from the camera's section then
+//! only crosses into a neighbor when the face it entered through connects
+//! to the face it leaves through, so sections sealed off by solid walls are
+//! never reached and can be culled without GPU occlusion queries.
+//!
+//! Sections of a loaded column that are not stored (air above the terrain)
+//! count as fully open up to `max_section_y`.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use super::WorldManager;
+
+/// Section position (chunk x, section y, chunk z)
+pub type SectionPos = (i32, i32, i32);
+
+/// Default highest section index walked (256-block world)
+pub const DEFAULT_MAX_SECTION_Y: i32 = 15;
+
+/// Section face
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Face {
+    Down,
+    Up,
+    North,
+    South,
+    West,
+    East,
+}
+
+impl Face {
+    /// All faces, in bit order
+    pub const ALL: [Face; 6] = [Face::Down, Face::Up, Face::North, Face::South, Face::West, Face::East];
+
+    /// Get the section offset across this face
+    pub fn offset(self) -> (i32, i32, i32) {
+        match self {
+            Face::Down => (0, -1, 0),
+            Face::Up => (0, 1, 0),
+            Face::North => (0, 0, -1),
+            Face::South => (0, 0, 1),
+            Face::West => (-1, 0, 0),
+            Face::East => (1, 0, 0),
+        }
+    }
+
+    /// Get the face on the other side of the boundary
+    pub fn opposite(self) -> Face {
+        match self {
+            Face::Down => Face::Up,
+            Face::Up => Face::Down,
+            Face::North => Face::South,
+            Face::South => Face::North,
+            Face::West => Face::East,
+            Face::East => Face::West,
+        }
+    }
+
+    fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+/// Check if a block stops visibility (air, glass, stained glass and water don't)
+pub fn is_opaque_block(block: u16) -> bool {
+    !matches!(block, 0 | 8 | 9 | 20 | 95)
+}
+
+/// Which faces of a section see each other (6×6 bit matrix)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FaceConnectivity(u64);
+
+impl FaceConnectivity {
+    /// No faces connected (solid section)
+    pub const NONE: Self = Self(0);
+    /// Every face connected (empty section)
+    pub const ALL: Self = Self((1 << 36) - 1);
+
+    /// Compute connectivity of a 16³ section (index `y << 8 | z << 4 | x`)
+    pub fn compute(blocks: &[u16], opaque: impl Fn(u16) -> bool) -> Self {
+        if blocks.len() < 4096 {
+            return Self::ALL;
+        }
+
+        let mut visited = vec![false; 4096];
+        let mut queue = VecDeque::new();
+        let mut result = Self::NONE;
+
+        for start in 0..4096 {
+            if visited[start] || opaque(blocks[start]) {
+                continue;
+            }
+
+            // Flood the open region containing `start`, noting faces it touches
+            let mut faces = 0u8;
+            visited[start] = true;
+            queue.push_back(start);
+            while let Some(i) = queue.pop_front() {
+                let (x, z, y) = (i & 15, (i >> 4) & 15, i >> 8);
+                faces |= boundary_faces(x, y, z);
+
+                let mut visit = |n: usize| {
+                    if !visited[n] && !opaque(blocks[n]) {
+                        visited[n] = true;
+                        queue.push_back(n);
+                    }
+                };
+                if x > 0 { visit(i - 1); }
+                if x < 15 { visit(i + 1); }
+                if z > 0 { visit(i - 16); }
+                if z < 15 { visit(i + 16); }
+                if y > 0 { visit(i - 256); }
+                if y < 15 { visit(i + 256); }
+            }
+
+            for a in Face::ALL {
+                for b in Face::ALL {
+                    if faces & a.bit() != 0 && faces & b.bit() != 0 {
+                        result.connect(a, b);
+                    }
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Mark two faces as seeing each other
+    pub fn connect(&mut self, a: Face, b: Face) {
+        self.0 |= 1 << (a as u32 * 6 + b as u32);
+        self.0 |= 1 << (b as u32 * 6 + a as u32);
+    }
+
+    /// Check if two faces see each other
+    pub fn is_connected(&self, a: Face, b: Face) -> bool {
+        self.0 & (1 << (a as u32 * 6 + b as u32)) != 0
+    }
+}
+
+/// Faces of the section a cell lies on
+fn boundary_faces(x: usize, y: usize, z: usize) -> u8 {
+    let mut faces = 0;
+    if y == 0 { faces |= Face::Down.bit(); }
+    if y == 15 { faces |= Face::Up.bit(); }
+    if z == 0 { faces |= Face::North.bit(); }
+    if z == 15 { faces |= Face::South.bit(); }
+    if x == 0 { faces |= Face::West.bit(); }
+    if x == 15 { faces |= Face::East.bit(); }
+    faces
+}
+
+/// Connectivity of the loaded sections
+#[derive(Debug, Clone)]
+pub struct VisibilityGraph {
+    /// Stored sections
+    sections: HashMap<SectionPos, FaceConnectivity>,
+    /// Loaded chunk columns
+    columns: HashSet<(i32, i32)>,
+    /// Highest section index walked in a loaded column
+    max_section_y: i32,
+}
+
+impl VisibilityGraph {
+    /// Create an empty graph
+    pub fn new() -> Self {
+        Self {
+            sections: HashMap::new(),
+            columns: HashSet::new(),
+            max_section_y: DEFAULT_MAX_SECTION_Y,
+        }
+    }
+
+    /// Build a graph over every loaded section
+    pub fn from_world(world: &WorldManager) -> Self {
+        let mut graph = Self::new();
+        for (&(cx, cz), chunk) in &world.chunks {
+            graph.add_column(cx, cz);
+            for section in &chunk.sections {
+                let connectivity = if section.is_empty() {
+                    FaceConnectivity::ALL
+                } else {
+                    FaceConnectivity::compute(&section.blocks.to_vec(), is_opaque_block)
+                };
+                graph.set_section((cx, section.y, cz), connectivity);
+            }
+        }
+        graph
+    }
+
+    /// Set the highest section index walked in a loaded column
+    pub fn set_max_section_y(&mut self, max_section_y: i32) {
+        self.max_section_y = max_section_y;
+    }
+
+    /// Mark a chunk column as loaded
+    pub fn add_column(&mut self, cx: i32, cz: i32) {
+        self.columns.insert((cx, cz));
+    }
+
+    /// Remove a chunk column and its sections
+    pub fn remove_column(&mut self, cx: i32, cz: i32) {
+        self.columns.remove(&(cx, cz));
+        self.sections.retain(|&(x, _, z), _| (x, z) != (cx, cz));
+    }
+
+    /// Compute and store a section's connectivity from its blocks
+    pub fn insert_section(&mut self, pos: SectionPos, blocks: &[u16]) {
+        self.set_section(pos, FaceConnectivity::compute(blocks, is_opaque_block));
+    }
+
+    /// Store a section's connectivity
+    pub fn set_section(&mut self, pos: SectionPos, connectivity: FaceConnectivity) {
+        self.columns.insert((pos.0, pos.2));
+        self.sections.insert(pos, connectivity);
+    }
+
+    /// Get a section's connectivity (`None` outside the loaded world)
+    pub fn connectivity(&self, pos: SectionPos) -> Option<FaceConnectivity> {
+        if let Some(&connectivity) = self.sections.get(&pos) {
+            return Some(connectivity);
+        }
+        let in_column = self.columns.contains(&(pos.0, pos.2)) && (0..=self.max_section_y).contains(&pos.1);
+        in_column.then_some(FaceConnectivity::ALL)
+    }
+
+    /// Get sections reachable from the camera's section through open boundaries
+    pub fn visible_sections(&self, camera_section: SectionPos) -> HashSet<SectionPos> {
+        let mut visible = HashSet::new();
+        if self.connectivity(camera_section).is_none() {
+            return visible;
+        }
+        visible.insert(camera_section);
+
+        // A section may be entered through several faces, each opening different exits
+        let mut entered: HashSet<(SectionPos, Face)> = HashSet::new();
+        let mut queue: VecDeque<(SectionPos, Option<Face>)> = VecDeque::new();
+        queue.push_back((camera_section, None));
+
+        while let Some((pos, from)) = queue.pop_front() {
+            let Some(connectivity) = self.connectivity(pos) else {
+                continue;
+            };
+
+            for out in Face::ALL {
+                if from.is_some_and(|from| !connectivity.is_connected(from, out)) {
+                    continue;
+                }
+                let (dx, dy, dz) = out.offset();
+                let next = (pos.0 + dx, pos.1 + dy, pos.2 + dz);
+                if self.connectivity(next).is_none() {
+                    continue;
+                }
+                if entered.insert((next, out.opposite())) {
+                    visible.insert(next);
+                    queue.push_back((next, Some(out.opposite())));
+                }
+            }
+        }
+
+        visible
+    }
+
+    /// Get stored section count
+    pub fn section_count(&self) -> usize {
+        self.sections.len()
+    }
+}
+
+impl Default for VisibilityGraph {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STONE: u16 = 1;
+
+    #[test]
+    fn test_wall_culls_far_room() {
+        let air = vec![0u16; 4096];
+        let mut wall = vec![STONE; 4096];
+
+        // Near room | solid wall | far room, one section tall
+        let mut graph = VisibilityGraph::new();
+        graph.set_max_section_y(0);
+        graph.insert_section((0, 0, 0), &air);
+        graph.insert_section((1, 0, 0), &wall);
+        graph.insert_section((2, 0, 0), &air);
+
+        let visible = graph.visible_sections((0, 0, 0));
+        assert!(visible.contains(&(1, 0, 0))); // The wall itself is seen
+        assert!(!visible.contains(&(2, 0, 0)));
+        assert_eq!(visible.len(), 2);
+
+        // A dead-end pocket opening on the near side still doesn't connect
+        wall[8 << 8 | 8 << 4] = 0;
+        graph.insert_section((1, 0, 0), &wall);
+        assert!(!graph.visible_sections((0, 0, 0)).contains(&(2, 0, 0)));
+
+        // A tunnel through the wall does
+        for x in 0..16 {
+            wall[8 << 8 | 8 << 4 | x] = 0;
+        }
+        graph.insert_section((1, 0, 0), &wall);
+        let connectivity = graph.connectivity((1, 0, 0)).unwrap();
+        assert!(connectivity.is_connected(Face::West, Face::East));
+        assert!(!connectivity.is_connected(Face::West, Face::Up));
+        assert!(graph.visible_sections((0, 0, 0)).contains(&(2, 0, 0)));
+    }
+}
@@ -0,0 +1,157 @@
+//! # World Generation
+//!
+//! Hook for filling chunks the server hasn't sent (single-player worlds,
+//! pre-generating around spawn). A `TerrainGenerator` turns chunk
+//! coordinates and a world seed into a `ChunkPayload`; the result must
+//! depend on nothing else, so the same seed always rebuilds the same
+//! terrain. The default generator is a layered noise heightmap: bedrock,
+//! stone, a few blocks of dirt under grass, sand at the shore and water up
+//! to sea level.
+
+use crate::util::noise::Perlin;
+
+use super::{ChunkPayload, ChunkSection, PalettedStorage};
+
+/// Air
+pub const AIR: u16 = 0;
+/// Stone
+pub const STONE: u16 = 1;
+/// Grass block
+pub const GRASS: u16 = 2;
+/// Dirt
+pub const DIRT: u16 = 3;
+/// Bedrock
+pub const BEDROCK: u16 = 7;
+/// Still water
+pub const WATER: u16 = 9;
+/// Sand
+pub const SAND: u16 = 12;
+
+/// Produces chunk contents from coordinates and a seed
+pub trait TerrainGenerator: Send + Sync {
+    /// Generate the chunk at chunk coordinates (`x`, `z`)
+    fn generate_chunk(&self, x: i32, z: i32, seed: u64) -> ChunkPayload;
+}
+
+/// Noise heightmap terrain with soil layers and water
+#[derive(Debug, Clone)]
+pub struct NoiseTerrainGenerator {
+    /// Average surface height
+    pub base_height: i32,
+    /// Max deviation from `base_height` (blocks)
+    pub amplitude: f32,
+    /// Horizontal feature size (blocks per noise unit)
+    pub scale: f64,
+    /// Noise octaves
+    pub octaves: u32,
+    /// Amplitude falloff per octave
+    pub gain: f32,
+    /// Water fills air up to this height
+    pub sea_level: i32,
+    /// Dirt blocks between grass and stone
+    pub soil_depth: i32,
+}
+
+impl NoiseTerrainGenerator {
+    /// Create a generator with vanilla-like proportions
+    pub fn new() -> Self {
+        Self {
+            base_height: 64,
+            amplitude: 24.0,
+            scale: 96.0,
+            octaves: 4,
+            gain: 0.5,
+            sea_level: 62,
+            soil_depth: 3,
+        }
+    }
+
+    /// Get the surface height of a world column
+    pub fn height_at(&self, noise: &Perlin, x: i32, z: i32) -> i32 {
+        let n = noise.fbm2(x as f64 / self.scale, z as f64 / self.scale, self.octaves, self.gain);
+        (self.base_height + (n * self.amplitude).round() as i32).max(1)
+    }
+
+    /// Get the block at height `y` of a column whose surface is `surface`
+    fn block_at(&self, y: i32, surface: i32) -> u16 {
+        let shore = surface <= self.sea_level + 1;
+        if y == 0 {
+            BEDROCK
+        } else if y > surface {
+            if y <= self.sea_level { WATER } else { AIR }
+        } else if y == surface {
+            if shore { SAND } else { GRASS }
+        } else if y > surface - 1 - self.soil_depth {
+            if shore { SAND } else { DIRT }
+        } else {
+            STONE
+        }
+    }
+}
+
+impl Default for NoiseTerrainGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TerrainGenerator for NoiseTerrainGenerator {
+    fn generate_chunk(&self, x: i32, z: i32, seed: u64) -> ChunkPayload {
+        let noise = Perlin::new(seed);
+
+        let mut heights = [0i32; 256];
+        for (i, height) in heights.iter_mut().enumerate() {
+            let (lx, lz) = ((i & 15) as i32, (i >> 4) as i32);
+            *height = self.height_at(&noise, (x << 4) + lx, (z << 4) + lz);
+        }
+        let top = heights.iter().copied().max().unwrap_or(0).max(self.sea_level);
+
+        // Pack whole sections at once; set_block rescans for emptiness
+        let mut sections = Vec::new();
+        let mut blocks = vec![AIR; 4096];
+        for section_y in 0..=(top >> 4) {
+            for ly in 0..16 {
+                let y = (section_y << 4) + ly;
+                for (i, &surface) in heights.iter().enumerate() {
+                    blocks[(ly as usize) << 8 | i] = self.block_at(y, surface);
+                }
+            }
+            let mut section = ChunkSection::new(section_y);
+            section.non_air = ChunkSection::count_non_air(&blocks);
+            section.blocks = PalettedStorage::from_slice(&blocks);
+            sections.push(section);
+        }
+
+        ChunkPayload { sections, ..Default::default() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::WorldManager;
+    use super::*;
+
+    #[test]
+    fn test_generation_is_deterministic_per_seed() {
+        let generator = NoiseTerrainGenerator::new();
+        let a = generator.generate_chunk(3, -2, 42).encode();
+        let b = generator.generate_chunk(3, -2, 42).encode();
+        assert_eq!(a, b);
+        assert_ne!(a, generator.generate_chunk(3, -2, 43).encode());
+        assert_ne!(a, generator.generate_chunk(4, -2, 42).encode());
+
+        let mut world = WorldManager::new();
+        world.set_seed(42);
+        let handle = world.generate_chunk(3, -2);
+        assert!(world.is_chunk_loaded(3, -2));
+        // Already loaded chunks are left alone
+        assert_eq!(world.generate_chunk(3, -2), handle);
+
+        // Every column has bedrock at the bottom and soil, sand or water at the top
+        let (wx, wz) = (3 * 16 + 5, -2 * 16 + 9);
+        assert_eq!(world.get_block(wx, 0, wz), BEDROCK);
+        let surface = (1..256).rev().find(|&y| world.get_block(wx, y, wz) != AIR).unwrap();
+        assert!([GRASS, SAND, WATER].contains(&world.get_block(wx, surface, wz)));
+        assert_eq!(world.get_block(wx, 5, wz), STONE);
+    }
+}
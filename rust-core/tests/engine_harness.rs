@@ -0,0 +1,41 @@
+//! # Engine Harness Tests
+//!
+//! End-to-end runs of a headless engine through `test_support::TestEngine`.
+//! Run with `cargo test --features test-support --test engine_harness`.
+
+use libs_core::test_support::TestEngine;
+
+#[test]
+fn test_fifty_tick_session_keeps_invariants() {
+    let mut harness = TestEngine::new();
+
+    for x in -2..2 {
+        for z in -2..2 {
+            harness.submit_flat_chunk(x, z, 63, 1).unwrap();
+        }
+    }
+
+    let mut ids = Vec::new();
+    for tick in 0..50 {
+        if tick % 5 == 0 {
+            ids.push(harness.spawn_entity(tick as f64, 64.0, 0.0));
+        }
+        if tick % 10 == 9 {
+            assert!(harness.despawn_entity(ids.remove(0)));
+        }
+        harness.tick(1);
+        harness.assert_invariants();
+    }
+
+    assert_eq!(harness.tick_count(), 50);
+    assert_eq!(harness.chunk_count(), 16);
+    assert_eq!(harness.entity_count(), 10 - 5);
+    assert_eq!(harness.alive_entities(), ids);
+
+    let frames = harness.frame_stats();
+    assert_eq!(frames.frame_count, 50);
+    assert!(frames.min_frame_time_ms <= frames.p50_frame_time_ms);
+    assert!(frames.p50_frame_time_ms <= frames.max_frame_time_ms);
+
+    harness.shutdown().unwrap();
+}